@@ -9,6 +9,10 @@ pub struct ClaudeConfig {
     pub working_dir: String,
     pub prompt: String,
     pub session_id: Option<String>,
+    /// Resume `session_id` as a new session (`--fork-session`).
+    pub fork_session: bool,
+    /// Resume `session_id` only up to this assistant message id.
+    pub resume_session_at: Option<String>,
     pub model: Option<String>,
     pub permission_mode: Option<String>,
     pub shell_prefix: Option<String>,
@@ -50,6 +54,13 @@ impl ClaudeConfig {
         if let Some(ref id) = self.session_id {
             args.push("--resume".to_string());
             args.push(id.clone());
+            if self.fork_session {
+                args.push("--fork-session".to_string());
+            }
+            if let Some(ref message_id) = self.resume_session_at {
+                args.push("--resume-session-at".to_string());
+                args.push(message_id.clone());
+            }
         }
 
         // Build the initial prompt JSON
@@ -84,6 +95,8 @@ mod tests {
             working_dir: "/tmp".to_string(),
             prompt: "Hello".to_string(),
             session_id: Some("sess-123".to_string()),
+            fork_session: false,
+            resume_session_at: None,
             model: Some("opus".to_string()),
             permission_mode: Some("plan".to_string()),
             shell_prefix: None,
@@ -111,6 +124,8 @@ mod tests {
             working_dir: "/tmp".to_string(),
             prompt: "Hello".to_string(),
             session_id: None,
+            fork_session: false,
+            resume_session_at: None,
             model: None,
             permission_mode: None,
             shell_prefix: None,
@@ -129,6 +144,8 @@ mod tests {
             working_dir: "/tmp".to_string(),
             prompt: "Hello".to_string(),
             session_id: None,
+            fork_session: false,
+            resume_session_at: None,
             model: None,
             permission_mode: None,
             shell_prefix: None,
@@ -146,6 +163,8 @@ mod tests {
             working_dir: "/tmp".to_string(),
             prompt: "Hello".to_string(),
             session_id: None,
+            fork_session: false,
+            resume_session_at: None,
             model: None,
             permission_mode: None,
             shell_prefix: None,
@@ -155,4 +174,27 @@ mod tests {
         let spawn = config.build();
         assert!(!spawn.args.contains(&"--effort".to_string()));
     }
+
+    #[test]
+    fn claude_config_forks_the_resumed_session() {
+        let config = ClaudeConfig {
+            binary_path: "/usr/bin/claude".to_string(),
+            working_dir: "/tmp".to_string(),
+            prompt: "Hello".to_string(),
+            session_id: Some("sess-123".to_string()),
+            fork_session: true,
+            resume_session_at: Some("msg_1".to_string()),
+            ..Default::default()
+        };
+
+        let args = config.build().args;
+        let resume = args.iter().position(|a| a == "--resume").unwrap();
+        assert_eq!(args[resume + 1], "sess-123");
+        assert!(args.contains(&"--fork-session".to_string()));
+        let at = args
+            .iter()
+            .position(|a| a == "--resume-session-at")
+            .unwrap();
+        assert_eq!(args[at + 1], "msg_1");
+    }
 }
@@ -0,0 +1,760 @@
+//! Exporting chats: Markdown, self-contained HTML, and portable bundles.
+//!
+//! # Documents
+//!
+//! [`render_markdown`] and [`render_html`] turn a chat into a readable
+//! document using the same folding as the chat views
+//! ([`chat_view`](crate::chat_view)): the `text` view is just the exchange,
+//! the `full` view adds thinking, tool calls and tool results. Those extras
+//! are collapsed (`<details>`) so the conversation stays readable.
+//!
+//! # Bundles
+//!
+//! A `.overseer-chat` bundle is one JSON document carrying everything needed
+//! to recreate the chat on another machine: its metadata, the raw event
+//! stream, and the bytes of every attached file.
+//!
+//! ```text
+//! { "format": "overseer-chat", "version": 1, "exportedAt": ...,
+//!   "metadata": { ... }, "events": [ ... ],
+//!   "attachments": [ { "id", "filename", "path", "data" (base64) } ] }
+//! ```
+//!
+//! Importing writes the attachments under the target's
+//! `attachments/{uuid}/{filename}`, points the events at the new paths, and
+//! adds the chat to the target workspace's chat index. The agent session id
+//! is dropped: agent sessions live on the machine that ran them, so the next
+//! message starts a fresh one.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::agents::event::AgentEvent;
+use crate::chat_view::{fold_events, View, ViewMessage};
+use crate::persistence::chat_jsonl::{
+    load_chat_events, load_chat_events_with_seq, load_chat_metadata, save_chat_events,
+    save_chat_metadata, ChatJsonlError,
+};
+use crate::persistence::encryption::{read_attachment, write_attachment};
+use crate::persistence::index::{update_chat_index, upsert_chat_entry, IndexError};
+use crate::persistence::types::{ChatIndexEntry, ChatMetadata};
+use crate::persistence::SeqEvent;
+
+/// Value of a bundle's `format` field.
+pub const BUNDLE_FORMAT: &str = "overseer-chat";
+
+/// File extension for bundles.
+pub const BUNDLE_EXTENSION: &str = "overseer-chat";
+
+/// Newest bundle version this build writes and reads.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Errors from exporting or importing a chat.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Chat error: {0}")]
+    Chat(#[from] ChatJsonlError),
+
+    #[error("Chat index error: {0}")]
+    Index(#[from] IndexError),
+
+    #[error("Not an Overseer chat bundle (format '{0}')")]
+    NotABundle(String),
+
+    #[error("Unsupported bundle version {0} (this build reads up to {BUNDLE_VERSION})")]
+    UnsupportedVersion(u32),
+
+    #[error("Invalid attachment in bundle: {0}")]
+    InvalidAttachment(String),
+
+    #[error("Invalid chat id in bundle: {0}")]
+    InvalidChatId(String),
+}
+
+/// What to export a chat as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    Bundle,
+}
+
+impl ExportFormat {
+    /// Parse a `format` parameter.
+    pub fn parse(value: &str) -> Result<ExportFormat, String> {
+        match value {
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            "bundle" => Ok(ExportFormat::Bundle),
+            other => Err(format!(
+                "Unknown export format '{other}' (expected 'markdown', 'html' or 'bundle')"
+            )),
+        }
+    }
+
+    /// File extension for an exported file.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::Bundle => BUNDLE_EXTENSION,
+        }
+    }
+
+    /// MIME type of an exported file.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Html => "text/html; charset=utf-8",
+            ExportFormat::Bundle => "application/json",
+        }
+    }
+}
+
+/// A portable chat: metadata, events and attachment bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatBundle {
+    /// Always [`BUNDLE_FORMAT`].
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub metadata: ChatMetadata,
+    pub events: Vec<AgentEvent>,
+    #[serde(default)]
+    pub attachments: Vec<BundledAttachment>,
+}
+
+/// An attached file carried in a bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledAttachment {
+    pub id: String,
+    pub filename: String,
+    /// Where the file was stored on the exporting machine. Events refer to
+    /// it by this path; importing rewrites it.
+    pub path: String,
+    /// File contents, base64.
+    pub data: String,
+}
+
+// ============================================================================
+// MARKDOWN
+// ============================================================================
+
+/// Render a chat as Markdown.
+pub fn render_markdown(metadata: &ChatMetadata, events: &[SeqEvent], view: View) -> String {
+    let mut out = format!("# {}\n\n", metadata.label.trim());
+    for (name, value) in header_fields(metadata) {
+        out.push_str(&format!("- **{name}:** {value}\n"));
+    }
+    out.push_str("\n---\n");
+
+    for message in fold_events(events, view).messages {
+        out.push('\n');
+        match message.kind.as_deref() {
+            None => {
+                let role = if message.role == "user" {
+                    "User"
+                } else {
+                    "Assistant"
+                };
+                out.push_str(&format!("## {role}\n\n{}\n", message.text.trim_end()));
+            }
+            Some(_) => {
+                let (summary, body) = collapsed_parts(&message);
+                out.push_str(&format!(
+                    "<details>\n<summary>{}</summary>\n\n{}\n\n</details>\n",
+                    escape_html(&summary),
+                    fenced(&body)
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// Wrap `text` in a code fence longer than any backtick run inside it.
+fn fenced(text: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}\n{}\n{fence}", text.trim_end())
+}
+
+// ============================================================================
+// HTML
+// ============================================================================
+
+const HTML_STYLE: &str = "\
+body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;\
+max-width:860px;margin:2rem auto;padding:0 1rem;color:#1f2328;background:#fff}\
+header{border-bottom:1px solid #d0d7de;margin-bottom:1.5rem}\
+header dl{display:grid;grid-template-columns:max-content 1fr;gap:.25rem 1rem;font-size:.9rem}\
+header dt{color:#656d76}header dd{margin:0}\
+.message{margin:1rem 0;padding:.75rem 1rem;border-radius:8px}\
+.message.user{background:#ddf4ff}.message.assistant{background:#f6f8fa}\
+.role{font-weight:600;font-size:.8rem;text-transform:uppercase;color:#656d76;margin-bottom:.25rem}\
+.text{white-space:pre-wrap;word-wrap:break-word}\
+details{margin:.5rem 0;border:1px solid #d0d7de;border-radius:6px;padding:.25rem .75rem}\
+details.error{border-color:#cf222e}\
+summary{cursor:pointer;color:#656d76;font-size:.9rem}\
+pre{white-space:pre-wrap;word-wrap:break-word;font-size:.85rem;margin:.5rem 0}";
+
+/// Render a chat as a self-contained HTML page (inline styles, no scripts).
+pub fn render_html(metadata: &ChatMetadata, events: &[SeqEvent], view: View) -> String {
+    let title = escape_html(metadata.label.trim());
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{HTML_STYLE}</style>\n</head>\n<body>\n\
+         <header>\n<h1>{title}</h1>\n<dl>\n"
+    );
+    for (name, value) in header_fields(metadata) {
+        out.push_str(&format!(
+            "<dt>{name}</dt><dd>{}</dd>\n",
+            escape_html(&value)
+        ));
+    }
+    out.push_str("</dl>\n</header>\n<main>\n");
+
+    for message in fold_events(events, view).messages {
+        match message.kind.as_deref() {
+            None => {
+                let (class, role) = if message.role == "user" {
+                    ("user", "User")
+                } else {
+                    ("assistant", "Assistant")
+                };
+                out.push_str(&format!(
+                    "<section class=\"message {class}\">\n<div class=\"role\">{role}</div>\n\
+                     <div class=\"text\">{}</div>\n</section>\n",
+                    escape_html(message.text.trim_end())
+                ));
+            }
+            Some(_) => {
+                let (summary, body) = collapsed_parts(&message);
+                let class = if message.is_error == Some(true) {
+                    " class=\"error\""
+                } else {
+                    ""
+                };
+                out.push_str(&format!(
+                    "<details{class}>\n<summary>{}</summary>\n<pre>{}</pre>\n</details>\n",
+                    escape_html(&summary),
+                    escape_html(body.trim_end())
+                ));
+            }
+        }
+    }
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// SHARED RENDERING
+// ============================================================================
+
+/// The labelled facts shown above the conversation.
+fn header_fields(metadata: &ChatMetadata) -> Vec<(&'static str, String)> {
+    let mut fields = Vec::new();
+    if let Some(agent) = &metadata.agent_type {
+        fields.push(("Agent", agent.clone()));
+    }
+    if let Some(model) = &metadata.model_version {
+        fields.push(("Model", model.clone()));
+    }
+    fields.push(("Created", metadata.created_at.to_rfc3339()));
+    fields.push(("Updated", metadata.updated_at.to_rfc3339()));
+    fields
+}
+
+/// Summary line and body of a message shown collapsed (thinking, tool calls,
+/// tool results).
+fn collapsed_parts(message: &ViewMessage) -> (String, String) {
+    match message.kind.as_deref() {
+        Some("thinking") => ("Thinking".to_string(), message.text.clone()),
+        Some("tool") => {
+            let name = message.tool_name.as_deref().unwrap_or("Tool");
+            // The content leads with the `[ToolName]` label already shown in
+            // the summary.
+            let body = match message.text.split_once(']') {
+                Some((_, rest)) if message.text.starts_with('[') => rest.trim_start(),
+                _ => message.text.as_str(),
+            };
+            (format!("Tool call: {name}"), body.to_string())
+        }
+        Some("bashOutput") => ("Command output".to_string(), message.text.clone()),
+        _ if message.is_error == Some(true) => {
+            ("Tool result (error)".to_string(), message.text.clone())
+        }
+        _ => ("Tool result".to_string(), message.text.clone()),
+    }
+}
+
+// ============================================================================
+// BUNDLES
+// ============================================================================
+
+/// Collect a chat in `chat_dir` into a bundle, reading its attachments from
+/// `config_dir/attachments`.
+///
+/// Attachments outside that directory (after resolving `..` and symlinks),
+/// or no longer on disk, are left out (with a warning); their references
+/// stay in the events.
+pub fn export_bundle(
+    config_dir: &Path,
+    chat_dir: &Path,
+    chat_id: &str,
+) -> Result<ChatBundle, ExportError> {
+    let metadata = load_chat_metadata(chat_dir, chat_id)?;
+    let events = load_chat_events(chat_dir, chat_id)?;
+
+    let attachments_root = config_dir.join("attachments").canonicalize().ok();
+    let mut attachments = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for (id, filename, path) in events.iter().flat_map(attachment_refs) {
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        let file = match PathBuf::from(&path).canonicalize() {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("Not bundling attachment {path}: {e}");
+                continue;
+            }
+        };
+        if !attachments_root
+            .as_ref()
+            .is_some_and(|root| file.starts_with(root))
+        {
+            log::warn!("Not bundling attachment outside the attachments directory: {path}");
+            continue;
+        }
+        match read_attachment(&file) {
+            Ok(bytes) => attachments.push(BundledAttachment {
+                id,
+                filename,
+                path,
+                data: base64::engine::general_purpose::STANDARD.encode(bytes),
+            }),
+            Err(e) => log::warn!("Not bundling attachment {path}: {e}"),
+        }
+    }
+
+    Ok(ChatBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: Utc::now(),
+        metadata,
+        events,
+        attachments,
+    })
+}
+
+/// Parse a bundle file's contents, checking its format and version.
+pub fn parse_bundle(json: &str) -> Result<ChatBundle, ExportError> {
+    let bundle: ChatBundle = serde_json::from_str(json)?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(ExportError::NotABundle(bundle.format));
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(ExportError::UnsupportedVersion(bundle.version));
+    }
+    Ok(bundle)
+}
+
+/// Recreate a bundled chat in `chat_dir`, belonging to `workspace_id`.
+///
+/// Keeps the chat's id unless a chat with that id already exists there. A
+/// bundle whose chat id isn't a plain file name (`../x`) is refused before
+/// anything is written. Returns the imported chat's metadata.
+pub fn import_bundle(
+    config_dir: &Path,
+    chat_dir: &Path,
+    workspace_id: &str,
+    bundle: ChatBundle,
+) -> Result<ChatMetadata, ExportError> {
+    if !is_file_name(&bundle.metadata.id) {
+        return Err(ExportError::InvalidChatId(bundle.metadata.id));
+    }
+    fs::create_dir_all(chat_dir)?;
+
+    // Attachments first, so the events can point at where they landed.
+    let mut moved: HashMap<String, (String, String)> = HashMap::new();
+    for attachment in &bundle.attachments {
+        let filename = Path::new(&attachment.filename)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|name| *name != "..")
+            .ok_or_else(|| ExportError::InvalidAttachment(attachment.filename.clone()))?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&attachment.data)
+            .map_err(|e| ExportError::InvalidAttachment(format!("{}: {e}", attachment.filename)))?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let dir = config_dir.join("attachments").join(&id);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(filename);
+        write_attachment(&path, &bytes)?;
+        moved.insert(
+            attachment.path.clone(),
+            (id, path.to_string_lossy().to_string()),
+        );
+    }
+
+    let mut metadata = bundle.metadata;
+    if chat_dir.join(format!("{}.jsonl", metadata.id)).exists()
+        || load_chat_metadata(chat_dir, &metadata.id).is_ok()
+    {
+        metadata.id = uuid::Uuid::new_v4().to_string();
+    }
+    metadata.workspace_id = workspace_id.to_string();
+    metadata.agent_session_id = None;
+
+    let mut events = bundle.events;
+    for event in &mut events {
+        rewrite_attachment_paths(event, &moved);
+    }
+    save_chat_events(chat_dir, &metadata.id, &events)?;
+    save_chat_metadata(chat_dir, &metadata)?;
+
+    let entry = ChatIndexEntry {
+        id: metadata.id.clone(),
+        label: metadata.label.clone(),
+        agent_type: metadata.agent_type.clone(),
+        created_at: metadata.created_at,
+        updated_at: metadata.updated_at,
+        is_archived: None,
+        archived_at: None,
+    };
+    update_chat_index(chat_dir, |index| upsert_chat_entry(index, entry.clone()))?;
+
+    Ok(metadata)
+}
+
+/// Whether `name` is a single normal path component.
+fn is_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none()
+}
+
+/// `(id, filename, path)` of each attachment a user message references.
+pub(crate) fn attachment_refs(event: &AgentEvent) -> Vec<(String, String, String)> {
+    let AgentEvent::UserMessage {
+        meta: Some(meta), ..
+    } = event
+    else {
+        return Vec::new();
+    };
+    let Some(list) = meta.get("attachments").and_then(|a| a.as_array()) else {
+        return Vec::new();
+    };
+    list.iter()
+        .filter_map(|attachment| {
+            let path = attachment.get("path")?.as_str()?.to_string();
+            let filename = attachment
+                .get("filename")
+                .and_then(|f| f.as_str())
+                .map(str::to_string)
+                .or_else(|| {
+                    Path::new(&path)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })?;
+            let id = attachment
+                .get("id")
+                .and_then(|i| i.as_str())
+                .unwrap_or_default()
+                .to_string();
+            Some((id, filename, path))
+        })
+        .collect()
+}
+
+/// Point a user message (its attachment metadata, and the attachment paths
+/// listed in the prompt of the hidden echo) at the imported attachments.
+/// `moved` maps old path to `(new id, new path)`.
+pub(crate) fn rewrite_attachment_paths(
+    event: &mut AgentEvent,
+    moved: &HashMap<String, (String, String)>,
+) {
+    let AgentEvent::UserMessage { content, meta, .. } = event else {
+        return;
+    };
+    for (old_path, (_, new_path)) in moved {
+        if content.contains(old_path.as_str()) {
+            *content = content.replace(old_path.as_str(), new_path);
+        }
+    }
+    let Some(list) = meta
+        .as_mut()
+        .and_then(|m| m.get_mut("attachments"))
+        .and_then(|a| a.as_array_mut())
+    else {
+        return;
+    };
+    for attachment in list {
+        let Some(old_path) = attachment.get("path").and_then(|p| p.as_str()) else {
+            continue;
+        };
+        if let Some((id, new_path)) = moved.get(old_path) {
+            attachment["path"] = serde_json::Value::String(new_path.clone());
+            attachment["id"] = serde_json::Value::String(id.clone());
+        }
+    }
+}
+
+/// Render a chat in `chat_dir` in `format`. Bundles are returned as JSON.
+pub fn export_chat(
+    config_dir: &Path,
+    chat_dir: &Path,
+    chat_id: &str,
+    format: ExportFormat,
+    view: View,
+) -> Result<String, ExportError> {
+    if format == ExportFormat::Bundle {
+        let bundle = export_bundle(config_dir, chat_dir, chat_id)?;
+        return Ok(serde_json::to_string(&bundle)?);
+    }
+    let metadata = load_chat_metadata(chat_dir, chat_id)?;
+    let events = load_chat_events_with_seq(chat_dir, chat_id)?;
+    Ok(match format {
+        ExportFormat::Markdown => render_markdown(&metadata, &events, view),
+        _ => render_html(&metadata, &events, view),
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::chat_jsonl::append_chat_event;
+    use crate::persistence::index::load_chat_index;
+    use crate::test_support::{sample_chat_metadata, sample_message, sample_user_message};
+    use tempfile::tempdir;
+
+    fn seq_events(events: Vec<AgentEvent>) -> Vec<SeqEvent> {
+        events
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| SeqEvent {
+                seq: i as u64 + 1,
+                event,
+            })
+            .collect()
+    }
+
+    fn sample_turn() -> Vec<SeqEvent> {
+        seq_events(vec![
+            sample_user_message("Fix <the> build"),
+            sample_message("Let me look.", Some("Thinking")),
+            sample_message("[Bash]\n{\n  \"command\": \"cargo build\"\n}", None),
+            AgentEvent::ToolResult {
+                tool_use_id: "t1".to_string(),
+                content: "error: ```oops```".to_string(),
+                is_error: true,
+            },
+            sample_message("Fixed & done.", None),
+            AgentEvent::TurnComplete,
+        ])
+    }
+
+    #[test]
+    fn markdown_text_view_is_just_the_exchange() {
+        let markdown = render_markdown(&sample_chat_metadata("chat-1"), &sample_turn(), View::Text);
+        assert!(markdown.contains("## User\n\nFix <the> build\n"));
+        assert!(markdown.contains("## Assistant\n\nFixed & done.\n"));
+        assert!(!markdown.contains("<details>"));
+    }
+
+    #[test]
+    fn markdown_full_view_collapses_thinking_and_tools() {
+        let markdown = render_markdown(&sample_chat_metadata("chat-1"), &sample_turn(), View::Full);
+        assert!(markdown.contains("<summary>Thinking</summary>"));
+        assert!(markdown.contains(
+            "<summary>Tool call: Bash</summary>\n\n```\n{\n  \"command\": \"cargo build\"\n}\n```"
+        ));
+        // A fence longer than the backticks inside the result.
+        assert!(markdown.contains("````\nerror: ```oops```\n````"));
+        assert!(markdown.contains("<summary>Tool result (error)</summary>"));
+    }
+
+    #[test]
+    fn html_is_escaped_and_collapsible() {
+        let html = render_html(&sample_chat_metadata("chat-1"), &sample_turn(), View::Full);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<div class=\"text\">Fix &lt;the&gt; build</div>"));
+        assert!(html.contains("Fixed &amp; done."));
+        assert!(html.contains("<details class=\"error\">"));
+        assert!(html.contains("<summary>Tool call: Bash</summary>"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn bundle_round_trips_with_attachments() {
+        let source = tempdir().unwrap();
+        let source_chats = source.path().join("chats/p/ws");
+        fs::create_dir_all(&source_chats).unwrap();
+        let attachment_dir = source.path().join("attachments/a1");
+        fs::create_dir_all(&attachment_dir).unwrap();
+        let attachment_path = attachment_dir.join("notes.md");
+        fs::write(&attachment_path, "# notes").unwrap();
+        let old_path = attachment_path.to_string_lossy().to_string();
+
+        let mut metadata = sample_chat_metadata("chat-1");
+        metadata.agent_session_id = Some("agent-session".to_string());
+        save_chat_metadata(&source_chats, &metadata).unwrap();
+        let shown = AgentEvent::UserMessage {
+            id: "m1".to_string(),
+            content: "read this".to_string(),
+            timestamp: Utc::now(),
+            meta: Some(serde_json::json!({
+                "attachments": [{ "id": "a1", "filename": "notes.md", "path": old_path }]
+            })),
+        };
+        let echo = AgentEvent::UserMessage {
+            id: "m2".to_string(),
+            content: format!("[Attached files:\n- {old_path}]\n\nread this"),
+            timestamp: Utc::now(),
+            meta: Some(serde_json::json!({ "type": "system" })),
+        };
+        for event in [&shown, &echo, &sample_message("Read it.", None)] {
+            append_chat_event(&source_chats, "chat-1", event).unwrap();
+        }
+
+        let json = export_chat(
+            source.path(),
+            &source_chats,
+            "chat-1",
+            ExportFormat::Bundle,
+            View::Full,
+        )
+        .unwrap();
+        let bundle = parse_bundle(&json).unwrap();
+        assert_eq!(bundle.attachments.len(), 1);
+
+        let target = tempdir().unwrap();
+        let target_chats = target.path().join("chats/q/other");
+        let imported = import_bundle(target.path(), &target_chats, "ws-9", bundle).unwrap();
+        assert_eq!(imported.id, "chat-1");
+        assert_eq!(imported.workspace_id, "ws-9");
+        assert_eq!(imported.agent_session_id, None);
+
+        let events = load_chat_events(&target_chats, "chat-1").unwrap();
+        assert_eq!(events.len(), 3);
+        let AgentEvent::UserMessage { meta, .. } = &events[0] else {
+            panic!("expected user message");
+        };
+        let new_path = meta.as_ref().unwrap()["attachments"][0]["path"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(new_path.starts_with(&*target.path().join("attachments").to_string_lossy()));
+        assert_eq!(fs::read_to_string(&new_path).unwrap(), "# notes");
+        let AgentEvent::UserMessage { content, .. } = &events[1] else {
+            panic!("expected user message");
+        };
+        assert!(content.contains(&new_path));
+
+        let index = load_chat_index(&target_chats).unwrap();
+        assert_eq!(index.chats.len(), 1);
+        assert_eq!(index.chats[0].id, "chat-1");
+
+        // Importing again doesn't overwrite the first copy.
+        let again = parse_bundle(&json).unwrap();
+        let second = import_bundle(target.path(), &target_chats, "ws-9", again).unwrap();
+        assert_ne!(second.id, "chat-1");
+        assert_eq!(load_chat_index(&target_chats).unwrap().chats.len(), 2);
+    }
+
+    #[test]
+    fn bundles_cant_reach_outside_their_directories() {
+        let config = tempdir().unwrap();
+        let chats = config.path().join("chats/p/ws");
+        fs::create_dir_all(&chats).unwrap();
+        fs::create_dir_all(config.path().join("attachments")).unwrap();
+        let secret = config.path().join("secret.txt");
+        fs::write(&secret, "hunter2").unwrap();
+        let sneaky = config
+            .path()
+            .join("attachments/../secret.txt")
+            .to_string_lossy()
+            .to_string();
+        save_chat_metadata(&chats, &sample_chat_metadata("chat-1")).unwrap();
+        let message = AgentEvent::UserMessage {
+            id: "m1".to_string(),
+            content: "read this".to_string(),
+            timestamp: Utc::now(),
+            meta: Some(serde_json::json!({
+                "attachments": [{ "id": "a1", "filename": "secret.txt", "path": sneaky }]
+            })),
+        };
+        append_chat_event(&chats, "chat-1", &message).unwrap();
+
+        let mut bundle = export_bundle(config.path(), &chats, "chat-1").unwrap();
+        assert!(bundle.attachments.is_empty());
+
+        let target = tempdir().unwrap();
+        let target_chats = target.path().join("chats/q/ws");
+        bundle.metadata.id = "../../escaped".to_string();
+        assert!(matches!(
+            import_bundle(target.path(), &target_chats, "ws-9", bundle),
+            Err(ExportError::InvalidChatId(_))
+        ));
+        assert!(!target.path().join("chats/escaped.jsonl").exists());
+        assert!(!target_chats.exists());
+    }
+
+    #[test]
+    fn parse_bundle_rejects_other_formats_and_newer_versions() {
+        let bundle = ChatBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION + 1,
+            exported_at: Utc::now(),
+            metadata: sample_chat_metadata("chat-1"),
+            events: Vec::new(),
+            attachments: Vec::new(),
+        };
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert!(matches!(
+            parse_bundle(&json),
+            Err(ExportError::UnsupportedVersion(_))
+        ));
+
+        let json = json.replace(BUNDLE_FORMAT, "something-else");
+        assert!(matches!(
+            parse_bundle(&json),
+            Err(ExportError::NotABundle(_))
+        ));
+    }
+}
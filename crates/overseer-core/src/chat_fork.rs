@@ -0,0 +1,674 @@
+//! Forking a chat: trying a different direction from an earlier point
+//! without losing the original.
+//!
+//! [`fork_chat`] copies a chat's events through a chosen seq into a new chat
+//! (its own index entry and copies of the attached files) and records how
+//! the agent should pick up from there in a `{chat_id}.fork.json` sidecar:
+//!
+//! - **Claude** resumes the source session as a new one
+//!   (`--resume <session> --fork-session`). Forks before the last turn are
+//!   cut with `--resume-session-at <message id>`, read from the session's
+//!   Claude Code transcript.
+//! - **Codex** forks the source thread (`thread/fork`) and rolls back the
+//!   turns after the fork point (`thread/rollback`).
+//! - Everything else replays the conversation: a summary of it is prefaced
+//!   to the first message. This covers other agents, sources that never got
+//!   an agent session, and Claude transcripts that can't be found.
+//!
+//! A fork keeps whole turns. Forking at any event of a turn keeps the rest
+//! of that turn, so the copy and the resumed agent session end at the same
+//! place.
+//!
+//! The plan is pending until the forked chat's first message takes it
+//! ([`take_pending_fork`]); the sidecar then stays as a record of where the
+//! chat came from.
+//!
+//! Rewinding a chat ([`plan_rewind`]) forks it onto itself: the same plan,
+//! for the turns that are kept, goes in a `{chat_id}.rewind.json` sidecar
+//! that is removed once taken.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::agents::event::AgentEvent;
+use crate::chat_export::{attachment_refs, rewrite_attachment_paths};
+use crate::chat_view::{fold_events, is_system_meta, View};
+use crate::persistence::chat_jsonl::{
+    load_chat_events_with_seq, load_chat_metadata, save_chat_events, save_chat_metadata,
+    ChatJsonlError,
+};
+use crate::persistence::index::{update_chat_index, upsert_chat_entry, IndexError};
+use crate::persistence::types::{ChatIndexEntry, ChatMetadata};
+use crate::persistence::SeqEvent;
+use crate::transcript_import::{claude_prompt_text, TranscriptRoots};
+
+/// Most characters of conversation a replay prompt carries. Older messages
+/// are dropped first.
+const REPLAY_CHARS: usize = 60_000;
+
+/// Longest single message in a replay prompt.
+const REPLAY_MESSAGE_CHARS: usize = 8_000;
+
+/// Errors from forking a chat.
+#[derive(Debug, Error)]
+pub enum ForkError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Chat error: {0}")]
+    Chat(#[from] ChatJsonlError),
+
+    #[error("Chat index error: {0}")]
+    Index(#[from] IndexError),
+
+    #[error("Seq {seq} is not in the chat (it has {len} events)")]
+    SeqOutOfRange { seq: u64, len: u64 },
+}
+
+/// How a forked chat's agent picks up from the fork point.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum ForkResume {
+    /// `--resume <session_id> --fork-session`, plus
+    /// `--resume-session-at <resume_at>` when the fork isn't at the end.
+    #[serde(rename_all = "camelCase")]
+    Claude {
+        session_id: String,
+        resume_at: Option<String>,
+    },
+    /// `thread/fork` of `thread_id`, then `thread/rollback` of `drop_turns`
+    /// turns (none if zero).
+    #[serde(rename_all = "camelCase")]
+    Codex {
+        thread_id: String,
+        drop_turns: usize,
+    },
+    /// A fresh session, with the replay prompt prefaced to the first message.
+    Replay,
+}
+
+/// Where a forked chat came from, and how its agent resumes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatFork {
+    pub source_chat_id: String,
+    /// Last seq copied from the source (the end of the chosen turn).
+    pub through_seq: u64,
+    pub forked_at: DateTime<Utc>,
+    pub resume: ForkResume,
+    /// The copied conversation as a prompt, for when the native fork can't be
+    /// used.
+    pub replay_prompt: String,
+    /// True until the forked chat's first message takes the plan.
+    pub pending: bool,
+}
+
+/// A newly forked chat.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkedChat {
+    pub metadata: ChatMetadata,
+    pub fork: ChatFork,
+}
+
+/// Fork the chat `chat_id` in `chat_dir` at `at_seq`, copying its
+/// attachments under `config_dir/attachments`.
+///
+/// `claude_root` is Claude Code's config directory, where the source
+/// session's transcript is looked up for a Claude fork before the last turn.
+pub fn fork_chat(
+    config_dir: &Path,
+    chat_dir: &Path,
+    chat_id: &str,
+    at_seq: u64,
+    claude_root: Option<&Path>,
+) -> Result<ForkedChat, ForkError> {
+    let source = load_chat_metadata(chat_dir, chat_id)?;
+    let events = load_chat_events_with_seq(chat_dir, chat_id)?;
+    let len = events.last().map_or(0, |e| e.seq);
+    if at_seq == 0 || at_seq > len {
+        return Err(ForkError::SeqOutOfRange { seq: at_seq, len });
+    }
+
+    let through_seq = turn_end(&events, at_seq);
+    let kept: Vec<SeqEvent> = events
+        .iter()
+        .filter(|e| e.seq <= through_seq)
+        .cloned()
+        .collect();
+    let kept_prompts = prompt_count(&kept);
+    let dropped_prompts = prompt_count(&events) - kept_prompts;
+
+    let moved = copy_attachments(config_dir, &kept)?;
+    let mut copied: Vec<AgentEvent> = kept.iter().map(|e| e.event.clone()).collect();
+    for event in &mut copied {
+        rewrite_attachment_paths(event, &moved);
+    }
+
+    let now = Utc::now();
+    let metadata = ChatMetadata {
+        id: uuid::Uuid::new_v4().to_string(),
+        label: format!("{} (fork)", source.label),
+        agent_session_id: None,
+        created_at: now,
+        updated_at: now,
+        ..source.clone()
+    };
+    let fork = ChatFork {
+        source_chat_id: chat_id.to_string(),
+        through_seq,
+        forked_at: now,
+        resume: resume_plan(&source, kept_prompts, dropped_prompts, claude_root),
+        replay_prompt: replay_prompt(&kept),
+        pending: true,
+    };
+
+    save_chat_events(chat_dir, &metadata.id, &copied)?;
+    save_chat_metadata(chat_dir, &metadata)?;
+    save_chat_fork(chat_dir, &metadata.id, &fork)?;
+
+    let entry = ChatIndexEntry {
+        id: metadata.id.clone(),
+        label: metadata.label.clone(),
+        agent_type: metadata.agent_type.clone(),
+        created_at: metadata.created_at,
+        updated_at: metadata.updated_at,
+        is_archived: None,
+        archived_at: None,
+    };
+    update_chat_index(chat_dir, |index| upsert_chat_entry(index, entry.clone()))?;
+
+    Ok(ForkedChat { metadata, fork })
+}
+
+/// Claude Code's config directory: a project's raw `CLAUDE_CONFIG_DIR`
+/// override (`~` allowed) if it has one, else `$CLAUDE_CONFIG_DIR` or
+/// `~/.claude`.
+pub fn claude_root(claude_config_dir: Option<&str>) -> Option<PathBuf> {
+    std::env::var("HOME")
+        .ok()
+        .and_then(|home| crate::paths::expand_config_dir(claude_config_dir, &home))
+        .map(PathBuf::from)
+        .or_else(|| TranscriptRoots::from_env().claude)
+}
+
+/// The fork record of `chat_id`, if it was forked from another chat.
+pub fn load_chat_fork(chat_dir: &Path, chat_id: &str) -> Result<Option<ChatFork>, ForkError> {
+    read_plan(&fork_path(chat_dir, chat_id))
+}
+
+/// Take `chat_id`'s pending rewind or fork plan, marking it used. Returns
+/// `None` once it was taken, or if the chat has neither.
+pub fn take_pending_fork(chat_dir: &Path, chat_id: &str) -> Result<Option<ChatFork>, ForkError> {
+    let rewind = rewind_path(chat_dir, chat_id);
+    if let Some(plan) = read_plan(&rewind)? {
+        fs::remove_file(&rewind)?;
+        return Ok(Some(plan));
+    }
+    let Some(mut fork) = load_chat_fork(chat_dir, chat_id)?.filter(|f| f.pending) else {
+        return Ok(None);
+    };
+    fork.pending = false;
+    write_plan(&fork_path(chat_dir, chat_id), &fork)?;
+    Ok(Some(fork))
+}
+
+/// Plan how `chat_id`'s agent picks up once the chat is cut back to
+/// `through_seq`, and leave it pending for the next message. Call before
+/// the events are cut.
+///
+/// The agent session is resumed as a fork cut at the kept turns, the same
+/// way [`fork_chat`] does it. With no turns kept there is nothing to resume:
+/// any pending plan is dropped and `None` returned.
+pub fn plan_rewind(
+    chat_dir: &Path,
+    chat_id: &str,
+    through_seq: u64,
+    claude_root: Option<&Path>,
+) -> Result<Option<ChatFork>, ForkError> {
+    let mut source = load_chat_metadata(chat_dir, chat_id)?;
+    let events = load_chat_events_with_seq(chat_dir, chat_id)?;
+    let kept: Vec<SeqEvent> = events
+        .iter()
+        .filter(|e| e.seq <= through_seq)
+        .cloned()
+        .collect();
+    let kept_prompts = prompt_count(&kept);
+    let mut dropped_prompts = prompt_count(&events) - kept_prompts;
+
+    // Rewound again before the last rewind was taken: the agent session
+    // still holds the turns that rewind dropped.
+    let path = rewind_path(chat_dir, chat_id);
+    if source.agent_session_id.is_none() {
+        match read_plan(&path)?.map(|plan| plan.resume) {
+            Some(ForkResume::Claude {
+                session_id,
+                resume_at,
+            }) => {
+                source.agent_session_id = Some(session_id);
+                dropped_prompts += usize::from(resume_at.is_some());
+            }
+            Some(ForkResume::Codex {
+                thread_id,
+                drop_turns,
+            }) => {
+                source.agent_session_id = Some(thread_id);
+                dropped_prompts += drop_turns;
+            }
+            Some(ForkResume::Replay) | None => {}
+        }
+    }
+
+    if kept_prompts == 0 {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        return Ok(None);
+    }
+
+    let plan = ChatFork {
+        source_chat_id: chat_id.to_string(),
+        through_seq,
+        forked_at: Utc::now(),
+        resume: resume_plan(&source, kept_prompts, dropped_prompts, claude_root),
+        replay_prompt: replay_prompt(&kept),
+        pending: true,
+    };
+    write_plan(&path, &plan)?;
+    Ok(Some(plan))
+}
+
+/// Write `{chat_id}.fork.json` (atomic tmp+rename).
+fn save_chat_fork(chat_dir: &Path, chat_id: &str, fork: &ChatFork) -> Result<(), ForkError> {
+    write_plan(&fork_path(chat_dir, chat_id), fork)
+}
+
+fn read_plan(path: &Path) -> Result<Option<ChatFork>, ForkError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+}
+
+fn write_plan(path: &Path, plan: &ChatFork) -> Result<(), ForkError> {
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_string_pretty(plan)?)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+fn fork_path(chat_dir: &Path, chat_id: &str) -> PathBuf {
+    chat_dir.join(format!("{chat_id}.fork.json"))
+}
+
+fn rewind_path(chat_dir: &Path, chat_id: &str) -> PathBuf {
+    chat_dir.join(format!("{chat_id}.rewind.json"))
+}
+
+/// The conversation in `events` as a preface for a fresh agent session.
+pub fn replay_prompt(events: &[SeqEvent]) -> String {
+    let fold = fold_events(events, View::Text);
+    let mut budget = REPLAY_CHARS;
+    let mut parts = Vec::new();
+    for message in fold.messages.iter().rev() {
+        let speaker = if message.role == "user" {
+            "User"
+        } else {
+            "Assistant"
+        };
+        let text = clip(message.text.trim(), REPLAY_MESSAGE_CHARS);
+        let part = format!("{speaker}: {text}");
+        let size = part.chars().count();
+        if size > budget {
+            parts.push("(earlier messages omitted)".to_string());
+            break;
+        }
+        budget -= size;
+        parts.push(part);
+    }
+    parts.reverse();
+    format!(
+        "This conversation continues an earlier one. Here is what was said so far:\n\n\
+         <conversation>\n{}\n</conversation>\n\n\
+         Pick up from there. The request follows.",
+        parts.join("\n\n")
+    )
+}
+
+/// `text`, cut to `max` characters.
+pub(crate) fn clip(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max).collect();
+    format!("{}… (truncated)", cut.trim_end())
+}
+
+/// True for a message the user typed (not the hidden echo of it).
+pub(crate) fn is_prompt(event: &AgentEvent) -> bool {
+    matches!(event, AgentEvent::UserMessage { meta, .. } if !is_system_meta(meta))
+}
+
+fn prompt_count(events: &[SeqEvent]) -> usize {
+    events.iter().filter(|e| is_prompt(&e.event)).count()
+}
+
+/// The last seq of the turn holding `seq`: the one before the next prompt,
+/// or the end of the chat.
+fn turn_end(events: &[SeqEvent], seq: u64) -> u64 {
+    events
+        .iter()
+        .find(|e| e.seq > seq && is_prompt(&e.event))
+        .map_or_else(|| events.last().map_or(seq, |e| e.seq), |next| next.seq - 1)
+}
+
+fn resume_plan(
+    source: &ChatMetadata,
+    kept_prompts: usize,
+    dropped_prompts: usize,
+    claude_root: Option<&Path>,
+) -> ForkResume {
+    let Some(session_id) = source.agent_session_id.clone() else {
+        return ForkResume::Replay;
+    };
+    match source.agent_type.as_deref().unwrap_or("claude") {
+        "claude" if dropped_prompts == 0 => ForkResume::Claude {
+            session_id,
+            resume_at: None,
+        },
+        "claude" => claude_root
+            .and_then(|root| claude_resume_at(root, &session_id, kept_prompts))
+            .map_or(ForkResume::Replay, |id| ForkResume::Claude {
+                session_id,
+                resume_at: Some(id),
+            }),
+        "codex" => ForkResume::Codex {
+            thread_id: session_id,
+            drop_turns: dropped_prompts,
+        },
+        _ => ForkResume::Replay,
+    }
+}
+
+/// The message id of the last assistant message before the prompt after the
+/// first `prompts`, in Claude Code's transcript of `session_id`.
+///
+/// `None` if the transcript isn't there or doesn't go past that many prompts.
+fn claude_resume_at(claude_root: &Path, session_id: &str, prompts: usize) -> Option<String> {
+    let file_name = format!("{session_id}.jsonl");
+    let transcript = fs::read_dir(claude_root.join("projects"))
+        .ok()?
+        .flatten()
+        .map(|project| project.path().join(&file_name))
+        .find(|path| path.is_file())?;
+    let contents = fs::read_to_string(transcript).ok()?;
+
+    let mut seen = 0;
+    let mut last_assistant = None;
+    for raw in contents.lines() {
+        let Ok(line) = serde_json::from_str::<Value>(raw) else {
+            continue;
+        };
+        let flag = |name: &str| line.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+        if flag("isSidechain") || flag("isMeta") || flag("isCompactSummary") {
+            continue;
+        }
+        match line.get("type").and_then(|t| t.as_str()) {
+            Some("user") => {
+                let Some(text) = claude_prompt_text(&line) else {
+                    continue;
+                };
+                // Written by Claude Code when a turn is stopped, not typed.
+                if text.starts_with("[Request interrupted by user") {
+                    continue;
+                }
+                seen += 1;
+                if seen > prompts {
+                    return last_assistant;
+                }
+            }
+            Some("assistant") => {
+                if let Some(id) = line.pointer("/message/id").and_then(|i| i.as_str()) {
+                    last_assistant = Some(id.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Copy the attachments `events` reference into new directories under
+/// `config_dir/attachments`. Returns old path → `(new id, new path)`.
+///
+/// Attachments outside that directory, or no longer on disk, keep their
+/// references (with a warning).
+fn copy_attachments(
+    config_dir: &Path,
+    events: &[SeqEvent],
+) -> Result<HashMap<String, (String, String)>, ForkError> {
+    let attachments_root = config_dir.join("attachments");
+    let mut moved = HashMap::new();
+    for (_, filename, path) in events.iter().flat_map(|e| attachment_refs(&e.event)) {
+        if moved.contains_key(&path) {
+            continue;
+        }
+        let file = Path::new(&path);
+        let Some(name) = Path::new(&filename).file_name() else {
+            continue;
+        };
+        if !file.starts_with(&attachments_root) || !file.is_file() {
+            log::warn!("Not copying attachment into fork: {path}");
+            continue;
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        let dir = attachments_root.join(&id);
+        fs::create_dir_all(&dir)?;
+        let target = dir.join(name);
+        fs::copy(file, &target)?;
+        moved.insert(path, (id, target.to_string_lossy().to_string()));
+    }
+    Ok(moved)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::chat_jsonl::append_chat_event;
+    use crate::persistence::index::load_chat_index;
+    use crate::test_support::{sample_chat_metadata, sample_message, sample_user_message};
+    use tempfile::tempdir;
+
+    /// Two turns; the first prompt attaches a file. Returns the config dir's
+    /// attachment path.
+    fn write_source(config_dir: &Path, chat_dir: &Path, metadata: &ChatMetadata) -> String {
+        let attachment_dir = config_dir.join("attachments").join("a1");
+        fs::create_dir_all(&attachment_dir).unwrap();
+        let attachment = attachment_dir.join("notes.txt");
+        fs::write(&attachment, "notes").unwrap();
+        let path = attachment.to_string_lossy().to_string();
+
+        fs::create_dir_all(chat_dir).unwrap();
+        save_chat_metadata(chat_dir, metadata).unwrap();
+        let mut first = sample_user_message("Read the notes");
+        if let AgentEvent::UserMessage { meta, .. } = &mut first {
+            *meta = Some(serde_json::json!({
+                "attachments": [{ "id": "a1", "filename": "notes.txt", "path": path }]
+            }));
+        }
+        for event in [
+            first,
+            sample_message("They say hello", None),
+            AgentEvent::TurnComplete,
+            sample_user_message("Now reply"),
+            sample_message("Hello back", None),
+            AgentEvent::TurnComplete,
+        ] {
+            append_chat_event(chat_dir, &metadata.id, &event).unwrap();
+        }
+        path
+    }
+
+    fn claude_source() -> ChatMetadata {
+        let mut metadata = sample_chat_metadata("source");
+        metadata.agent_type = Some("claude".to_string());
+        metadata.agent_session_id = Some("sess-1".to_string());
+        metadata
+    }
+
+    #[test]
+    fn fork_copies_through_the_end_of_the_turn() {
+        let config = tempdir().unwrap();
+        let chat_dir = config.path().join("chats");
+        let old_path = write_source(config.path(), &chat_dir, &claude_source());
+
+        let forked = fork_chat(config.path(), &chat_dir, "source", 2, None).unwrap();
+        assert_eq!(forked.fork.through_seq, 3);
+        assert_ne!(forked.metadata.id, "source");
+        assert_eq!(forked.metadata.agent_session_id, None);
+
+        let events = load_chat_events_with_seq(&chat_dir, &forked.metadata.id).unwrap();
+        assert_eq!(events.len(), 3);
+        let refs = attachment_refs(&events[0].event);
+        let (_, _, new_path) = &refs[0];
+        assert_ne!(new_path, &old_path);
+        assert_eq!(fs::read_to_string(new_path).unwrap(), "notes");
+
+        let index = load_chat_index(&chat_dir).unwrap();
+        assert!(index.chats.iter().any(|c| c.id == forked.metadata.id));
+        assert!(forked.fork.replay_prompt.contains("User: Read the notes"));
+        assert!(!forked.fork.replay_prompt.contains("Now reply"));
+    }
+
+    #[test]
+    fn resume_plan_follows_the_agent() {
+        let config = tempdir().unwrap();
+        let chat_dir = config.path().join("chats");
+        write_source(config.path(), &chat_dir, &claude_source());
+
+        // At the end: resume the whole session.
+        let forked = fork_chat(config.path(), &chat_dir, "source", 6, None).unwrap();
+        assert_eq!(
+            forked.fork.resume,
+            ForkResume::Claude {
+                session_id: "sess-1".to_string(),
+                resume_at: None
+            }
+        );
+
+        // Earlier, with no transcript to cut it at: replay.
+        let forked = fork_chat(config.path(), &chat_dir, "source", 1, None).unwrap();
+        assert_eq!(forked.fork.resume, ForkResume::Replay);
+
+        // Earlier, cut at the first turn's last assistant message.
+        let claude_root = tempdir().unwrap();
+        let project = claude_root.path().join("projects").join("-work");
+        fs::create_dir_all(&project).unwrap();
+        let lines = [
+            r#"{"type":"user","message":{"role":"user","content":"Read the notes"}}"#,
+            r#"{"type":"assistant","message":{"id":"msg_1","content":[]}}"#,
+            r#"{"type":"user","isSidechain":true,"message":{"role":"user","content":"sub"}}"#,
+            r#"{"type":"user","message":{"role":"user","content":"Now reply"}}"#,
+            r#"{"type":"assistant","message":{"id":"msg_2","content":[]}}"#,
+        ];
+        fs::write(project.join("sess-1.jsonl"), lines.join("\n")).unwrap();
+        let forked = fork_chat(
+            config.path(),
+            &chat_dir,
+            "source",
+            1,
+            Some(claude_root.path()),
+        )
+        .unwrap();
+        assert_eq!(
+            forked.fork.resume,
+            ForkResume::Claude {
+                session_id: "sess-1".to_string(),
+                resume_at: Some("msg_1".to_string())
+            }
+        );
+
+        let mut codex = claude_source();
+        codex.agent_type = Some("codex".to_string());
+        save_chat_metadata(&chat_dir, &codex).unwrap();
+        let forked = fork_chat(config.path(), &chat_dir, "source", 2, None).unwrap();
+        assert_eq!(
+            forked.fork.resume,
+            ForkResume::Codex {
+                thread_id: "sess-1".to_string(),
+                drop_turns: 1
+            }
+        );
+    }
+
+    #[test]
+    fn pending_fork_is_taken_once() {
+        let config = tempdir().unwrap();
+        let chat_dir = config.path().join("chats");
+        write_source(config.path(), &chat_dir, &claude_source());
+        let forked = fork_chat(config.path(), &chat_dir, "source", 6, None).unwrap();
+        let id = &forked.metadata.id;
+
+        assert!(take_pending_fork(&chat_dir, id).unwrap().is_some());
+        assert!(take_pending_fork(&chat_dir, id).unwrap().is_none());
+        let record = load_chat_fork(&chat_dir, id).unwrap().unwrap();
+        assert_eq!(record.source_chat_id, "source");
+        assert!(!record.pending);
+        assert!(load_chat_fork(&chat_dir, "source").unwrap().is_none());
+    }
+
+    #[test]
+    fn rewind_plan_is_taken_before_the_fork_record() {
+        let config = tempdir().unwrap();
+        let chat_dir = config.path().join("chats");
+        let mut codex = claude_source();
+        codex.agent_type = Some("codex".to_string());
+        write_source(config.path(), &chat_dir, &codex);
+
+        let plan = plan_rewind(&chat_dir, "source", 3, None).unwrap().unwrap();
+        assert_eq!(
+            plan.resume,
+            ForkResume::Codex {
+                thread_id: "sess-1".to_string(),
+                drop_turns: 1
+            }
+        );
+        assert!(!plan.replay_prompt.contains("Now reply"));
+
+        // Rewinding to the start leaves nothing to resume.
+        assert!(plan_rewind(&chat_dir, "source", 0, None).unwrap().is_none());
+        assert!(take_pending_fork(&chat_dir, "source").unwrap().is_none());
+
+        plan_rewind(&chat_dir, "source", 3, None).unwrap();
+        let taken = take_pending_fork(&chat_dir, "source").unwrap().unwrap();
+        assert_eq!(taken.through_seq, 3);
+        assert!(take_pending_fork(&chat_dir, "source").unwrap().is_none());
+        assert!(load_chat_fork(&chat_dir, "source").unwrap().is_none());
+    }
+
+    #[test]
+    fn fork_rejects_seqs_outside_the_chat() {
+        let config = tempdir().unwrap();
+        let chat_dir = config.path().join("chats");
+        write_source(config.path(), &chat_dir, &claude_source());
+        for seq in [0, 7] {
+            assert!(matches!(
+                fork_chat(config.path(), &chat_dir, "source", seq, None),
+                Err(ForkError::SeqOutOfRange { .. })
+            ));
+        }
+    }
+}
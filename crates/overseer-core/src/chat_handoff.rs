@@ -0,0 +1,369 @@
+//! Handing a chat over to a different agent.
+//!
+//! A chat is bound to one agent. When that agent stops being able to help
+//! (rate limits, a stuck session), the task can continue in another agent in
+//! the same chat. The new agent gets no session to resume, so
+//! [`handoff_prompt`] builds a context transfer prompt from the chat's events
+//! and the workspace diff:
+//!
+//! - **Goal**: the chat's first request, and the latest one if it differs.
+//! - **Progress**: each turn's request with the agent's final reply. When the
+//!   budget runs out, older turns are dropped first.
+//! - **Plan**: the latest plan the agent put up for approval.
+//! - **Files touched**: committed and uncommitted changes in the workspace.
+//! - **Open todos**: unfinished items from the agent's last todo list.
+//!
+//! Handing off (`ChatSessionManager::hand_off`) switches the chat to the new
+//! agent with a fresh session and records the prompt as a
+//! `{ "type": "handoff" }` user message. That message shows the switch in the
+//! chat's timeline, and is what the new agent is started with. It is skipped
+//! as a request when a later handoff is built.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::agents::event::AgentEvent;
+use crate::chat_fork::{clip, is_prompt};
+use crate::chat_view::{fold_event, View};
+use crate::git::{ChangedFile, ChangedFilesResult};
+use crate::persistence::types::ChatMetadata;
+use crate::persistence::SeqEvent;
+
+/// Most characters the progress section carries.
+const PROGRESS_CHARS: usize = 24_000;
+
+/// Longest single request or reply in the progress section.
+const TURN_MESSAGE_CHARS: usize = 2_000;
+
+/// Longest goal or plan.
+const SECTION_CHARS: usize = 8_000;
+
+/// Most files listed under "Files touched".
+const MAX_FILES: usize = 200;
+
+/// Meta `type` of the user message that records a handoff.
+pub const HANDOFF_META_TYPE: &str = "handoff";
+
+/// A context transfer prompt for the agent taking over a chat.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffPrompt {
+    /// Timeline label, e.g. "Handed off from Claude to Codex".
+    pub label: String,
+    /// The prompt to send to the new agent.
+    pub prompt: String,
+}
+
+/// A chat that was handed over to another agent.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatHandoff {
+    /// The chat's metadata, now on the new agent with a fresh session.
+    pub metadata: ChatMetadata,
+    /// The prompt to start the new agent with.
+    pub handoff: HandoffPrompt,
+    /// The recorded handoff message, carrying the prompt.
+    pub event: AgentEvent,
+    /// Seq of the handoff message.
+    pub seq: u64,
+}
+
+/// One request and the agent's final reply to it.
+struct Turn {
+    /// `None` for a previous handoff's prompt.
+    request: Option<String>,
+    reply: Option<String>,
+}
+
+/// Build the prompt that hands a chat from `from_agent` to `to_agent`.
+///
+/// `from_agent` is the chat's `agent_type` (`None` is Claude). `touched` is
+/// the workspace's changed files (see [`touched_files`]).
+pub fn handoff_prompt(
+    from_agent: Option<&str>,
+    to_agent: &str,
+    events: &[SeqEvent],
+    touched: &[ChangedFile],
+) -> HandoffPrompt {
+    let from = agent_display_name(from_agent);
+    let to = agent_display_name(Some(to_agent));
+
+    let mut turns: Vec<Turn> = Vec::new();
+    let mut plan = None;
+    let mut todos = None;
+    for SeqEvent { seq, event } in events {
+        if let AgentEvent::PlanApproval { content, .. } = event {
+            plan = Some(content.clone());
+            continue;
+        }
+        if is_prompt(event) {
+            turns.push(Turn {
+                request: (!is_handoff(event)).then(|| prompt_content(event)),
+                reply: None,
+            });
+            continue;
+        }
+        let Some(message) = fold_event(*seq, event, View::Full) else {
+            continue;
+        };
+        match (message.kind.as_deref(), message.tool_name.as_deref()) {
+            (None, _) if message.role == "assistant" => {
+                if let Some(turn) = turns.last_mut() {
+                    turn.reply = Some(message.text);
+                }
+            }
+            (Some("tool"), Some("TodoWrite")) => todos = Some(open_todos(&message.text)),
+            _ => {}
+        }
+    }
+
+    let mut sections = vec![format!(
+        "You are taking over a task that {from} was working on in this workspace. \
+         {from} can't continue, so the work is yours from here. Here is where it stands."
+    )];
+
+    let requests: Vec<&str> = turns.iter().filter_map(|t| t.request.as_deref()).collect();
+    if let Some(first) = requests.first() {
+        let mut goal = format!("## Goal\n\n{}", clip(first.trim(), SECTION_CHARS));
+        if let Some(latest) = requests.last().filter(|latest| *latest != first) {
+            goal.push_str(&format!(
+                "\n\nLatest request:\n\n{}",
+                clip(latest.trim(), SECTION_CHARS)
+            ));
+        }
+        sections.push(goal);
+    }
+
+    let progress = progress(&turns);
+    if !progress.is_empty() {
+        sections.push(format!("## Progress so far\n\n{progress}"));
+    }
+
+    if let Some(plan) = plan.filter(|plan| !plan.trim().is_empty()) {
+        sections.push(format!("## Plan\n\n{}", clip(plan.trim(), SECTION_CHARS)));
+    }
+
+    if !touched.is_empty() {
+        let mut lines: Vec<String> = touched.iter().take(MAX_FILES).map(file_line).collect();
+        if touched.len() > MAX_FILES {
+            lines.push(format!("- … and {} more", touched.len() - MAX_FILES));
+        }
+        sections.push(format!("## Files touched\n\n{}", lines.join("\n")));
+    }
+
+    if let Some(todos) = todos.filter(|todos| !todos.is_empty()) {
+        let lines: Vec<String> = todos.iter().map(|todo| format!("- [ ] {todo}")).collect();
+        sections.push(format!("## Open todos\n\n{}", lines.join("\n")));
+    }
+
+    sections.push(format!(
+        "Check the current state of the files before changing anything, then \
+         continue where {from} left off. Don't redo finished work."
+    ));
+
+    HandoffPrompt {
+        label: format!("Handed off from {from} to {to}"),
+        prompt: sections.join("\n\n"),
+    }
+}
+
+/// Meta of the user message that records a handoff.
+pub fn handoff_meta(handoff: &HandoffPrompt, from_agent: &str, to_agent: &str) -> Value {
+    serde_json::json!({
+        "type": HANDOFF_META_TYPE,
+        "label": handoff.label,
+        "fromAgent": from_agent,
+        "toAgent": to_agent,
+    })
+}
+
+/// The workspace's changed files: committed changes against the default
+/// branch, then uncommitted ones not already listed.
+pub fn touched_files(changes: ChangedFilesResult) -> Vec<ChangedFile> {
+    let mut files = changes.files;
+    for file in changes.uncommitted {
+        if !files.iter().any(|f| f.path == file.path) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+/// Display name for an agent type, as the desktop UI shows it
+/// (`getAgentDisplayName`). Unknown and missing types are Claude.
+pub fn agent_display_name(agent_type: Option<&str>) -> &'static str {
+    match agent_type {
+        Some("codex") => "Codex",
+        Some("copilot") => "Copilot",
+        Some("gemini") => "Gemini",
+        Some("hermes") => "Hermes",
+        Some("opencode") => "OpenCode",
+        Some("pi") => "Pi",
+        _ => "Claude",
+    }
+}
+
+fn is_handoff(event: &AgentEvent) -> bool {
+    matches!(
+        event,
+        AgentEvent::UserMessage { meta: Some(meta), .. }
+            if meta.get("type").and_then(Value::as_str) == Some(HANDOFF_META_TYPE)
+    )
+}
+
+fn prompt_content(event: &AgentEvent) -> String {
+    match event {
+        AgentEvent::UserMessage { content, .. } => content.clone(),
+        _ => String::new(),
+    }
+}
+
+/// The turns as "Request / Reply" pairs, newest kept when over budget.
+fn progress(turns: &[Turn]) -> String {
+    let mut budget = PROGRESS_CHARS;
+    let mut parts = Vec::new();
+    for turn in turns.iter().rev() {
+        let request = match &turn.request {
+            Some(request) => clip(request.trim(), TURN_MESSAGE_CHARS),
+            None => "(handoff from the previous agent)".to_string(),
+        };
+        let mut part = format!("Request: {request}");
+        if let Some(reply) = &turn.reply {
+            part.push_str(&format!(
+                "\nReply: {}",
+                clip(reply.trim(), TURN_MESSAGE_CHARS)
+            ));
+        }
+        let size = part.chars().count();
+        if size > budget {
+            parts.push("(earlier turns omitted)".to_string());
+            break;
+        }
+        budget -= size;
+        parts.push(part);
+    }
+    parts.reverse();
+    parts.join("\n\n")
+}
+
+/// Unfinished items of a persisted `[TodoWrite]` call.
+fn open_todos(content: &str) -> Vec<String> {
+    let input = content.split_once('\n').map_or("", |(_, json)| json);
+    let Ok(input) = serde_json::from_str::<Value>(input) else {
+        return Vec::new();
+    };
+    input
+        .get("todos")
+        .and_then(Value::as_array)
+        .map(|todos| {
+            todos
+                .iter()
+                .filter(|todo| todo.get("status").and_then(Value::as_str) != Some("completed"))
+                .filter_map(|todo| todo.get("content").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn file_line(file: &ChangedFile) -> String {
+    let mut line = format!("- {} {}", file.status, file.path);
+    if let Some(old_path) = &file.old_path {
+        line.push_str(&format!(" (from {old_path})"));
+    }
+    if let (Some(additions), Some(deletions)) = (file.additions, file.deletions) {
+        line.push_str(&format!(" (+{additions} −{deletions})"));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{sample_message, sample_user_message};
+
+    fn seq_events(events: Vec<AgentEvent>) -> Vec<SeqEvent> {
+        events
+            .into_iter()
+            .enumerate()
+            .map(|(i, event)| SeqEvent {
+                seq: i as u64 + 1,
+                event,
+            })
+            .collect()
+    }
+
+    fn changed(status: &str, path: &str) -> ChangedFile {
+        ChangedFile {
+            status: status.to_string(),
+            path: path.to_string(),
+            old_path: None,
+            additions: Some(3),
+            deletions: Some(1),
+            is_binary: false,
+        }
+    }
+
+    #[test]
+    fn prompt_carries_goal_progress_files_and_open_todos() {
+        let todos = serde_json::json!({ "todos": [
+            { "content": "Add parser", "status": "completed", "activeForm": "Adding parser" },
+            { "content": "Write tests", "status": "in_progress", "activeForm": "Writing tests" },
+        ]});
+        let events = seq_events(vec![
+            sample_user_message("Build a CSV importer"),
+            sample_message(&format!("[TodoWrite]\n{todos:#}"), None),
+            sample_message("Parser is in place.", None),
+            AgentEvent::TurnComplete,
+            sample_user_message("Handle quoted fields too"),
+            sample_message("Working on quoting.", None),
+        ]);
+
+        let handoff = handoff_prompt(
+            Some("claude"),
+            "codex",
+            &events,
+            &[changed("M", "src/csv.rs")],
+        );
+
+        assert_eq!(handoff.label, "Handed off from Claude to Codex");
+        let prompt = &handoff.prompt;
+        assert!(prompt.contains("## Goal\n\nBuild a CSV importer"));
+        assert!(prompt.contains("Latest request:\n\nHandle quoted fields too"));
+        assert!(prompt.contains("Request: Build a CSV importer\nReply: Parser is in place."));
+        assert!(prompt.contains("- M src/csv.rs (+3 −1)"));
+        assert!(prompt.contains("- [ ] Write tests"));
+        assert!(!prompt.contains("Add parser"));
+    }
+
+    #[test]
+    fn earlier_handoff_prompts_are_not_requests() {
+        let mut handoff = sample_user_message("You are taking over a task…");
+        if let AgentEvent::UserMessage { meta, .. } = &mut handoff {
+            *meta = Some(serde_json::json!({ "type": HANDOFF_META_TYPE, "label": "x" }));
+        }
+        let events = seq_events(vec![
+            sample_user_message("Fix the login bug"),
+            handoff,
+            sample_message("Picked it up.", None),
+        ]);
+
+        let prompt = handoff_prompt(Some("codex"), "gemini", &events, &[]).prompt;
+        assert!(prompt.contains("## Goal\n\nFix the login bug"));
+        assert!(!prompt.contains("Latest request"));
+        assert!(prompt.contains("Request: (handoff from the previous agent)\nReply: Picked it up."));
+        assert!(!prompt.contains("## Files touched"));
+    }
+
+    #[test]
+    fn touched_files_lists_uncommitted_changes_once() {
+        let files = touched_files(ChangedFilesResult {
+            files: vec![changed("M", "a.rs")],
+            uncommitted: vec![changed("M", "a.rs"), changed("?", "b.rs")],
+            is_default_branch: false,
+            submodules: Vec::new(),
+        });
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["a.rs", "b.rs"]);
+    }
+}
@@ -0,0 +1,1680 @@
+//! Full-text search across every chat.
+//!
+//! # Overview
+//!
+//! Chats are stored as `chats/<project>/<workspace>/{chat_id}.jsonl`. The
+//! [`ChatSearchIndex`] keeps an in-memory inverted index over the searchable
+//! parts of those events:
+//!
+//! - user messages
+//! - assistant text replies and plans
+//! - tool calls
+//! - tool results (streamed bash output is joined into one document)
+//!
+//! Events are classified by the same folding as the chat views
+//! ([`chat_view`](crate::chat_view)), so streaming `Text` deltas, thinking and
+//! hidden system echoes are not indexed.
+//!
+//! # Keeping It Current
+//!
+//! The index is fed by [`ChatSessionManager`](crate::managers::ChatSessionManager):
+//! every event appended to a registered session is indexed as it arrives, so
+//! a search sees events that are still buffered and not yet on disk.
+//!
+//! Indexed documents are also appended to `search/chats.jsonl` in the config
+//! directory, so restarting doesn't mean re-reading every chat. Every search
+//! first looks for chat files whose size changed since they were last read
+//! and reads only their events past what was indexed. A chat that shrank
+//! (rewound) or disappeared is dropped and re-read; the log is rewritten
+//! whenever documents are removed. Like the chats, the log is encrypted if
+//! they are.
+//!
+//! Chat files are read without holding the index lock, so indexing live
+//! events never waits for a scan. While a tracked chat's file is being read,
+//! its live events are held back and indexed after the file's.
+//!
+//! # Queries
+//!
+//! Every word of the query must match the start of a word in the document
+//! (`migrat` finds "migration"), case-insensitively. Results can be filtered
+//! by project, workspace, agent type, date and archived state, and carry a
+//! snippet around the first match with the matched words marked.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::agents::event::AgentEvent;
+use crate::chat_view::{fold_event, View};
+use crate::persistence::chat_jsonl::{
+    count_events, load_chat_events_since_seq, load_chat_metadata, SeqEvent,
+};
+use crate::persistence::encryption::{decode_line, encode_line};
+use crate::persistence::index::load_chat_index;
+use crate::persistence::types::ChatIndex;
+use crate::redaction::redacted_event;
+
+/// Directory (under the config dir) holding the search index.
+const SEARCH_DIR: &str = "search";
+
+/// Append-only log of indexed documents.
+const LOG_FILE: &str = "chats.jsonl";
+
+/// Name of the per-project directory archived chats are moved into.
+const ARCHIVED_CHATS_DIR: &str = "archived";
+
+/// Longest document text kept, in characters. Tool results can be whole
+/// files; the start is what people search for.
+const MAX_DOC_CHARS: usize = 8_000;
+
+/// Characters of context shown before the first match in a snippet.
+const SNIPPET_LEAD: usize = 60;
+
+/// Total snippet length, in characters.
+const SNIPPET_CHARS: usize = 200;
+
+/// Results returned when the query doesn't set a limit.
+const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Error, Debug)]
+pub enum SearchError {
+    #[error("Config directory not set")]
+    NoConfigDir,
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// What part of the conversation a hit is from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HitKind {
+    User,
+    Assistant,
+    ToolCall,
+    ToolResult,
+}
+
+/// A search and its filters. Unset filters match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchQuery {
+    /// Words to find; all of them must match
+    #[serde(alias = "q")]
+    pub query: String,
+    /// Project name (the first chat-path segment)
+    pub project: Option<String>,
+    /// Workspace name (the second chat-path segment)
+    pub workspace: Option<String>,
+    /// Agent type, e.g. "claude" or "codex"
+    pub agent_type: Option<String>,
+    /// Only documents from this time on
+    pub since: Option<DateTime<Utc>>,
+    /// Only documents up to this time
+    pub until: Option<DateTime<Utc>>,
+    /// `Some(true)` for archived chats only, `Some(false)` for active ones
+    pub archived: Option<bool>,
+    /// Maximum number of hits (default 50)
+    pub limit: Option<usize>,
+}
+
+/// A run of snippet text, marked if it matched the query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlight: bool,
+}
+
+/// One matching document.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub chat_id: String,
+    /// The chat's label, if it's in its workspace's chat index
+    pub chat_label: Option<String>,
+    pub project: String,
+    pub workspace: String,
+    pub agent_type: Option<String>,
+    pub archived: bool,
+    /// Seq of the (first) event the document was built from
+    pub seq: u64,
+    pub kind: HitKind,
+    pub timestamp: DateTime<Utc>,
+    pub snippet: Vec<SnippetPart>,
+    /// Number of matched words; higher is better
+    pub score: usize,
+}
+
+/// Hits for a query, best first.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+    /// Matches before `limit` was applied
+    pub total: usize,
+}
+
+/// Search index over all chats. See the [module docs](self).
+#[derive(Default)]
+pub struct ChatSearchIndex {
+    state: Mutex<IndexState>,
+    /// Held for a whole scan, so concurrent searches don't read the same
+    /// chats twice
+    scanning: Mutex<()>,
+}
+
+impl ChatSearchIndex {
+    /// Create an index with no config directory; nothing is indexed until
+    /// [`set_config_dir`](Self::set_config_dir) is called.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Point the index at a config directory, discarding what was loaded.
+    pub fn set_config_dir(&self, config_dir: PathBuf) {
+        *self.state.lock().unwrap() = IndexState {
+            config_dir: Some(config_dir),
+            ..IndexState::default()
+        };
+    }
+
+    /// Start indexing a chat as its events are appended.
+    ///
+    /// Events already on disk that the index hasn't seen are indexed first.
+    /// Until [`untrack_chat`](Self::untrack_chat), the chat's on-disk file is
+    /// never compared against the index (it may lag behind buffered events).
+    pub fn track_chat(
+        &self,
+        chat_id: &str,
+        project: &str,
+        workspace: &str,
+        agent_type: Option<String>,
+    ) -> Result<(), SearchError> {
+        self.ensure_loaded()?;
+        let location = ChatLocation {
+            project: project.to_string(),
+            workspace: workspace.to_string(),
+            agent_type,
+            archived_dir: false,
+        };
+        self.catch_up(chat_id, location, true)
+    }
+
+    /// Stop live indexing of a chat (its events are all on disk again).
+    pub fn untrack_chat(&self, chat_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(chat) = state.chat_mut(chat_id) {
+            chat.live = false;
+        }
+        state.flush_log();
+    }
+
+    /// Index one appended event. Ignored for chats that aren't tracked.
+    pub fn index_event(&self, chat_id: &str, seq: u64, event: &AgentEvent) {
+        let dir = {
+            let state = self.state.lock().unwrap();
+            let Some(chat) = state.chat(chat_id).filter(|chat| chat.live) else {
+                return;
+            };
+            state.location_dir(&chat.location)
+        };
+        // Index the event as it will be stored, without secrets.
+        let event = match &dir {
+            Ok(dir) => redacted_event(dir, event),
+            Err(_) => Cow::Borrowed(event),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let Some(chat) = state.chat_mut(chat_id).filter(|chat| chat.live) else {
+            return;
+        };
+        if let Some(pending) = chat.pending.as_mut() {
+            pending.push((seq, event.into_owned()));
+            return;
+        }
+        if let Err(e) = state.add_event(chat_id, seq, &event, Utc::now()) {
+            log::warn!("Failed to index event {seq} of chat {chat_id}: {e}");
+        }
+    }
+
+    /// Forget a chat's documents after `keep_through_seq` once its file was
+    /// cut there (it was rewound).
+    pub fn truncate_chat(&self, chat_id: &str, keep_through_seq: u64) -> Result<(), SearchError> {
+        let (location, live) = {
+            let mut state = self.state.lock().unwrap();
+            let Some(chat) = state.chat(chat_id) else {
+                return Ok(());
+            };
+            let found = (chat.location.clone(), chat.live);
+            state.truncate(chat_id, keep_through_seq);
+            state.rewrite_log()?;
+            found
+        };
+        // Streamed documents cut in the middle are re-read up to the cut.
+        self.catch_up(chat_id, location, live)
+    }
+
+    /// Run a query over every chat.
+    pub fn search(&self, query: &SearchQuery) -> Result<SearchResults, SearchError> {
+        self.ensure_loaded()?;
+        self.scan()?;
+        let mut state = self.state.lock().unwrap();
+        state.flush_log();
+        Ok(state.query(query))
+    }
+
+    /// Replay the log into memory, once. The log is read without the lock.
+    fn ensure_loaded(&self) -> Result<(), SearchError> {
+        let path = {
+            let state = self.state.lock().unwrap();
+            if state.loaded {
+                return Ok(());
+            }
+            state.log_path()?
+        };
+        let records = read_log(&path)?;
+        let mut state = self.state.lock().unwrap();
+        if !state.loaded {
+            for record in records {
+                state.replay(record);
+            }
+            state.loaded = true;
+        }
+        Ok(())
+    }
+
+    /// Bring a chat up to date with its file.
+    ///
+    /// With `live`, the chat is tracked from now on and its live events wait
+    /// until the file is read. Otherwise nothing is done for a tracked chat.
+    fn catch_up(
+        &self,
+        chat_id: &str,
+        location: ChatLocation,
+        live: bool,
+    ) -> Result<(), SearchError> {
+        loop {
+            let Some(plan) =
+                self.state
+                    .lock()
+                    .unwrap()
+                    .plan_catch_up(chat_id, location.clone(), live)?
+            else {
+                return Ok(());
+            };
+            let tail = read_tail(chat_id, &plan);
+            // A file that shrank is trimmed to and read again.
+            if !self
+                .state
+                .lock()
+                .unwrap()
+                .finish_catch_up(chat_id, plan, tail)?
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Read chats whose files changed since they were last read, and drop
+    /// chats whose files are gone.
+    fn scan(&self) -> Result<(), SearchError> {
+        let _scanning = self.scanning.lock().unwrap();
+        let chats_root = self.state.lock().unwrap().config_dir()?.join("chats");
+        let found = find_chats(&chats_root);
+
+        let mut seen = HashSet::new();
+        for (chat_id, mut location, dir) in found {
+            seen.insert(chat_id.clone());
+            let file_len = chat_file_len(&dir, &chat_id);
+            let unchanged = self
+                .state
+                .lock()
+                .unwrap()
+                .chat(&chat_id)
+                .is_some_and(|chat| {
+                    chat.live || (chat.location.same_place(&location) && chat.file_len == file_len)
+                });
+            if unchanged {
+                continue;
+            }
+            location.agent_type = load_chat_metadata(&dir, &chat_id)
+                .ok()
+                .and_then(|meta| meta.agent_type);
+            self.catch_up(&chat_id, location, false)?;
+        }
+
+        // Chats whose files are gone were deleted.
+        let mut state = self.state.lock().unwrap();
+        let gone: Vec<String> = state
+            .chats
+            .iter()
+            .flatten()
+            .filter(|chat| !chat.live && !seen.contains(&chat.chat_id))
+            .map(|chat| chat.chat_id.clone())
+            .collect();
+        if !gone.is_empty() {
+            for chat_id in &gone {
+                state.remove_chat(chat_id);
+            }
+            state.rewrite_log()?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// INDEX STATE
+// ============================================================================
+
+/// Where a chat lives and what it was run with.
+#[derive(Debug, Clone, PartialEq)]
+struct ChatLocation {
+    project: String,
+    workspace: String,
+    agent_type: Option<String>,
+    /// True if the chat sits under the project's archived chats directory
+    archived_dir: bool,
+}
+
+impl ChatLocation {
+    /// Whether both are the same chat directory.
+    fn same_place(&self, other: &ChatLocation) -> bool {
+        self.project == other.project
+            && self.workspace == other.workspace
+            && self.archived_dir == other.archived_dir
+    }
+}
+
+struct IndexedChat {
+    chat_id: String,
+    location: ChatLocation,
+    /// Highest seq looked at
+    indexed_seq: u64,
+    docs: Vec<Doc>,
+    /// Registered with the chat session manager and indexed as it grows
+    live: bool,
+    /// Live events held back while the chat's file is read
+    pending: Option<Vec<(u64, AgentEvent)>>,
+    /// Size of the chat's file when it was last read
+    file_len: Option<u64>,
+}
+
+/// What a catch-up reads, decided under the lock.
+struct CatchUp {
+    dir: PathBuf,
+    location: ChatLocation,
+    /// Events up to here are indexed already
+    from_seq: u64,
+    /// Date of the turn `from_seq` is in, if the index knows it
+    turn_time: Option<DateTime<Utc>>,
+    /// The chat moved since it was indexed
+    moved: bool,
+    /// This catch-up holds back the chat's live events
+    live: bool,
+}
+
+/// What a catch-up read from the chat's file, without the lock.
+struct ChatTail {
+    on_disk: u64,
+    file_len: Option<u64>,
+    events: Vec<SeqEvent>,
+    turn_time: DateTime<Utc>,
+}
+
+struct Doc {
+    seq: u64,
+    end_seq: u64,
+    kind: HitKind,
+    /// Built from streamed chunks; the next chunk is appended
+    streamed: bool,
+    timestamp: DateTime<Utc>,
+    text: String,
+}
+
+/// One line of the index log: a document, or (without `text`) a chat's
+/// location and how far it was indexed.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogRecord {
+    chat_id: String,
+    project: String,
+    workspace: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent_type: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    archived: bool,
+    seq: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end_seq: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kind: Option<HitKind>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    streamed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timestamp: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+/// A document reference: (chat slot, doc index).
+type DocRef = (usize, usize);
+
+#[derive(Default)]
+struct IndexState {
+    config_dir: Option<PathBuf>,
+    loaded: bool,
+    /// Chat slots; removed chats leave `None`
+    chats: Vec<Option<IndexedChat>>,
+    slots: HashMap<String, usize>,
+    postings: BTreeMap<String, HashSet<DocRef>>,
+    log: Option<BufWriter<fs::File>>,
+}
+
+impl IndexState {
+    fn config_dir(&self) -> Result<&Path, SearchError> {
+        self.config_dir.as_deref().ok_or(SearchError::NoConfigDir)
+    }
+
+    fn log_path(&self) -> Result<PathBuf, SearchError> {
+        Ok(self.config_dir()?.join(SEARCH_DIR).join(LOG_FILE))
+    }
+
+    fn location_dir(&self, location: &ChatLocation) -> Result<PathBuf, SearchError> {
+        let project_dir = self.config_dir()?.join("chats").join(&location.project);
+        Ok(if location.archived_dir {
+            project_dir
+                .join(ARCHIVED_CHATS_DIR)
+                .join(&location.workspace)
+        } else {
+            project_dir.join(&location.workspace)
+        })
+    }
+
+    fn chat(&self, chat_id: &str) -> Option<&IndexedChat> {
+        let slot = *self.slots.get(chat_id)?;
+        self.chats[slot].as_ref()
+    }
+
+    fn chat_mut(&mut self, chat_id: &str) -> Option<&mut IndexedChat> {
+        let slot = *self.slots.get(chat_id)?;
+        self.chats[slot].as_mut()
+    }
+
+    fn replay(&mut self, record: LogRecord) {
+        let location = ChatLocation {
+            project: record.project,
+            workspace: record.workspace,
+            agent_type: record.agent_type,
+            archived_dir: record.archived,
+        };
+        let slot = self.slot_for(&record.chat_id, location);
+        let chat = self.chats[slot].as_mut().expect("slot was just filled");
+        let end_seq = record.end_seq.unwrap_or(record.seq);
+        chat.indexed_seq = chat.indexed_seq.max(end_seq);
+        if let (Some(kind), Some(text)) = (record.kind, record.text) {
+            let timestamp = record.timestamp.unwrap_or_else(Utc::now);
+            self.insert_doc(
+                slot,
+                record.seq,
+                end_seq,
+                kind,
+                record.streamed,
+                timestamp,
+                text,
+            );
+        }
+    }
+
+    /// The slot of a chat, created or moved to `location` as needed.
+    fn slot_for(&mut self, chat_id: &str, location: ChatLocation) -> usize {
+        if let Some(&slot) = self.slots.get(chat_id) {
+            if let Some(chat) = self.chats[slot].as_mut() {
+                chat.location = location;
+                return slot;
+            }
+        }
+        let slot = self.chats.len();
+        self.chats.push(Some(IndexedChat {
+            chat_id: chat_id.to_string(),
+            location,
+            indexed_seq: 0,
+            docs: Vec::new(),
+            live: false,
+            pending: None,
+            file_len: None,
+        }));
+        self.slots.insert(chat_id.to_string(), slot);
+        slot
+    }
+
+    /// Add a document, or extend the chat's last one if both are chunks of
+    /// the same stream.
+    #[allow(clippy::too_many_arguments)]
+    fn insert_doc(
+        &mut self,
+        slot: usize,
+        seq: u64,
+        end_seq: u64,
+        kind: HitKind,
+        streamed: bool,
+        timestamp: DateTime<Utc>,
+        text: String,
+    ) {
+        let chat = self.chats[slot].as_mut().expect("chat slot is filled");
+        let (doc_index, added) = match chat.docs.last_mut() {
+            Some(last)
+                if streamed && last.streamed && last.kind == kind && last.end_seq + 1 == seq =>
+            {
+                let room = MAX_DOC_CHARS.saturating_sub(last.text.chars().count());
+                let added: String = text.chars().take(room).collect();
+                last.text.push_str(&added);
+                last.end_seq = end_seq;
+                (chat.docs.len() - 1, added)
+            }
+            _ => {
+                let text: String = text.chars().take(MAX_DOC_CHARS).collect();
+                chat.docs.push(Doc {
+                    seq,
+                    end_seq,
+                    kind,
+                    streamed,
+                    timestamp,
+                    text: text.clone(),
+                });
+                (chat.docs.len() - 1, text)
+            }
+        };
+        // A word split across chunks is indexed whole once both are joined.
+        let doc_text = &chat.docs[doc_index].text;
+        let start = doc_text.len() - added.len();
+        let from = doc_text[..start]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !is_word_char(*c))
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        for (_, word) in words(&doc_text[from..]) {
+            self.postings
+                .entry(word.to_lowercase())
+                .or_default()
+                .insert((slot, doc_index));
+        }
+    }
+
+    /// Index an event and log it.
+    fn add_event(
+        &mut self,
+        chat_id: &str,
+        seq: u64,
+        event: &AgentEvent,
+        now: DateTime<Utc>,
+    ) -> Result<(), SearchError> {
+        let Some(&slot) = self.slots.get(chat_id) else {
+            return Ok(());
+        };
+        let Some(chat) = self.chats[slot].as_mut() else {
+            return Ok(());
+        };
+        if seq <= chat.indexed_seq {
+            return Ok(());
+        }
+        chat.indexed_seq = seq;
+        let Some((kind, text, streamed)) = searchable(event) else {
+            return Ok(());
+        };
+        let timestamp = match event {
+            AgentEvent::UserMessage { timestamp, .. } => *timestamp,
+            _ => now,
+        };
+        let record = LogRecord {
+            chat_id: chat.chat_id.clone(),
+            project: chat.location.project.clone(),
+            workspace: chat.location.workspace.clone(),
+            agent_type: chat.location.agent_type.clone(),
+            archived: chat.location.archived_dir,
+            seq,
+            end_seq: None,
+            kind: Some(kind),
+            streamed,
+            timestamp: Some(timestamp),
+            text: Some(text.clone()),
+        };
+        self.insert_doc(slot, seq, seq, kind, streamed, timestamp, text);
+        self.append_log(&record)
+    }
+
+    /// Where a catch-up of `chat_id` starts reading, or `None` if the chat
+    /// is tracked and this isn't the catch-up that tracks it.
+    fn plan_catch_up(
+        &mut self,
+        chat_id: &str,
+        location: ChatLocation,
+        live: bool,
+    ) -> Result<Option<CatchUp>, SearchError> {
+        let moved = self
+            .chat(chat_id)
+            .is_some_and(|chat| chat.location != location);
+        if !live && self.chat(chat_id).is_some_and(|chat| chat.live) {
+            return Ok(None);
+        }
+        let dir = self.location_dir(&location)?;
+        let slot = self.slot_for(chat_id, location.clone());
+        let chat = self.chats[slot].as_mut().expect("slot was just filled");
+        if live {
+            chat.live = true;
+            chat.pending.get_or_insert_with(Vec::new);
+        }
+        Ok(Some(CatchUp {
+            dir,
+            location,
+            from_seq: chat.indexed_seq,
+            turn_time: chat.docs.last().map(|doc| doc.timestamp),
+            moved,
+            live,
+        }))
+    }
+
+    /// Index what a catch-up read. Returns true if the file shrank and has
+    /// to be read again from where it was trimmed to.
+    fn finish_catch_up(
+        &mut self,
+        chat_id: &str,
+        plan: CatchUp,
+        tail: Option<ChatTail>,
+    ) -> Result<bool, SearchError> {
+        let Some(tail) = tail else {
+            if plan.live {
+                self.release_pending(chat_id)?;
+            }
+            return Ok(false);
+        };
+        let indexed = self.chat(chat_id).map(|chat| chat.indexed_seq).unwrap_or(0);
+        if indexed > tail.on_disk {
+            // The file shrank: it was rewound, or lost buffered events.
+            self.truncate(chat_id, tail.on_disk);
+            self.rewrite_log()?;
+            return Ok(true);
+        }
+
+        // Text events carry no time; date them by the turn they belong to.
+        let mut turn_time = tail.turn_time;
+        for seq_event in &tail.events {
+            if let AgentEvent::UserMessage { timestamp, .. } = &seq_event.event {
+                turn_time = *timestamp;
+            }
+            self.add_event(chat_id, seq_event.seq, &seq_event.event, turn_time)?;
+        }
+        if plan.live {
+            self.release_pending(chat_id)?;
+        }
+        if let Some(chat) = self.chat_mut(chat_id) {
+            chat.file_len = tail.file_len;
+        }
+        if tail.on_disk == plan.from_seq && !plan.moved {
+            return Ok(false);
+        }
+
+        // Record the location and how far this chat was read, so it isn't
+        // re-read next time even if its last events weren't searchable.
+        let location = plan.location;
+        self.append_log(&LogRecord {
+            chat_id: chat_id.to_string(),
+            project: location.project,
+            workspace: location.workspace,
+            agent_type: location.agent_type,
+            archived: location.archived_dir,
+            seq: tail.on_disk,
+            end_seq: None,
+            kind: None,
+            streamed: false,
+            timestamp: None,
+            text: None,
+        })?;
+        Ok(false)
+    }
+
+    /// Index the live events held back during a catch-up.
+    fn release_pending(&mut self, chat_id: &str) -> Result<(), SearchError> {
+        let Some(pending) = self.chat_mut(chat_id).and_then(|chat| chat.pending.take()) else {
+            return Ok(());
+        };
+        for (seq, event) in pending {
+            self.add_event(chat_id, seq, &event, Utc::now())?;
+        }
+        Ok(())
+    }
+
+    /// Drop documents past `keep_through_seq`, in memory only.
+    fn truncate(&mut self, chat_id: &str, keep_through_seq: u64) {
+        let Some(&slot) = self.slots.get(chat_id) else {
+            return;
+        };
+        let Some(chat) = self.chats[slot].as_mut() else {
+            return;
+        };
+        let keep = chat
+            .docs
+            .iter()
+            .take_while(|doc| doc.end_seq <= keep_through_seq)
+            .count();
+        let removed: Vec<Doc> = chat.docs.drain(keep..).collect();
+        chat.indexed_seq = chat.indexed_seq.min(keep_through_seq);
+        // A streamed document cut in the middle is re-read whole on catch-up.
+        if let Some(first) = removed.first() {
+            chat.indexed_seq = chat.indexed_seq.min(first.seq.saturating_sub(1));
+        }
+        for (offset, doc) in removed.iter().enumerate() {
+            self.unpost(slot, keep + offset, &doc.text);
+        }
+    }
+
+    fn remove_chat(&mut self, chat_id: &str) {
+        let Some(slot) = self.slots.remove(chat_id) else {
+            return;
+        };
+        if let Some(chat) = self.chats[slot].take() {
+            for (index, doc) in chat.docs.iter().enumerate() {
+                self.unpost(slot, index, &doc.text);
+            }
+        }
+    }
+
+    fn unpost(&mut self, slot: usize, doc_index: usize, text: &str) {
+        for (_, word) in words(text) {
+            let word = word.to_lowercase();
+            if let Some(refs) = self.postings.get_mut(&word) {
+                refs.remove(&(slot, doc_index));
+                if refs.is_empty() {
+                    self.postings.remove(&word);
+                }
+            }
+        }
+    }
+
+    fn append_log(&mut self, record: &LogRecord) -> Result<(), SearchError> {
+        let path = self.log_path()?;
+        if self.log.is_none() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            self.log = Some(BufWriter::new(file));
+        }
+        let log = self.log.as_mut().expect("log was just opened");
+        let line = encode_line(&path, &serde_json::to_string(record)?)?;
+        writeln!(log, "{line}")?;
+        Ok(())
+    }
+
+    fn flush_log(&mut self) {
+        if let Some(log) = self.log.as_mut() {
+            if let Err(e) = log.flush() {
+                log::warn!("Failed to write search index: {e}");
+            }
+        }
+    }
+
+    /// Replace the log with the current in-memory index.
+    fn rewrite_log(&mut self) -> Result<(), SearchError> {
+        self.log = None;
+        let path = self.log_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp_path = path.with_extension("jsonl.tmp");
+        {
+            let mut writer = BufWriter::new(fs::File::create(&temp_path)?);
+            for chat in self.chats.iter().flatten() {
+                let record = |seq: u64| LogRecord {
+                    chat_id: chat.chat_id.clone(),
+                    project: chat.location.project.clone(),
+                    workspace: chat.location.workspace.clone(),
+                    agent_type: chat.location.agent_type.clone(),
+                    archived: chat.location.archived_dir,
+                    seq,
+                    end_seq: None,
+                    kind: None,
+                    streamed: false,
+                    timestamp: None,
+                    text: None,
+                };
+                for doc in &chat.docs {
+                    let record = LogRecord {
+                        end_seq: (doc.end_seq != doc.seq).then_some(doc.end_seq),
+                        kind: Some(doc.kind),
+                        streamed: doc.streamed,
+                        timestamp: Some(doc.timestamp),
+                        text: Some(doc.text.clone()),
+                        ..record(doc.seq)
+                    };
+                    let line = encode_line(&path, &serde_json::to_string(&record)?)?;
+                    writeln!(writer, "{line}")?;
+                }
+                let line = encode_line(&path, &serde_json::to_string(&record(chat.indexed_seq))?)?;
+                writeln!(writer, "{line}")?;
+            }
+            writer.flush()?;
+        }
+        fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+
+    fn query(&self, query: &SearchQuery) -> SearchResults {
+        let terms: Vec<String> = words(&query.query)
+            .map(|(_, word)| word.to_lowercase())
+            .collect();
+        if terms.is_empty() {
+            return SearchResults {
+                hits: Vec::new(),
+                total: 0,
+            };
+        }
+
+        // Documents with a word starting with every term.
+        let mut candidates: Option<HashSet<DocRef>> = None;
+        for term in &terms {
+            let mut matching = HashSet::new();
+            for (_, refs) in self
+                .postings
+                .range::<String, _>(term.clone()..)
+                .take_while(|(word, _)| word.starts_with(term.as_str()))
+            {
+                matching.extend(refs.iter().copied());
+            }
+            candidates = Some(match candidates {
+                Some(previous) => previous.intersection(&matching).copied().collect(),
+                None => matching,
+            });
+        }
+
+        let mut chat_indexes = HashMap::new();
+        let mut hits = Vec::new();
+        for (slot, doc_index) in candidates.unwrap_or_default() {
+            let Some(chat) = self.chats[slot].as_ref() else {
+                continue;
+            };
+            // Postings of removed documents can outlive them.
+            let Some(doc) = chat.docs.get(doc_index) else {
+                continue;
+            };
+            let location = &chat.location;
+            if query
+                .project
+                .as_ref()
+                .is_some_and(|p| *p != location.project)
+                || query
+                    .workspace
+                    .as_ref()
+                    .is_some_and(|w| *w != location.workspace)
+                || query
+                    .agent_type
+                    .as_ref()
+                    .is_some_and(|a| Some(a) != location.agent_type.as_ref())
+                || query.since.is_some_and(|since| doc.timestamp < since)
+                || query.until.is_some_and(|until| doc.timestamp > until)
+            {
+                continue;
+            }
+
+            let entry = self.index_entry(&mut chat_indexes, location, &chat.chat_id);
+            let archived = location.archived_dir || entry.archived;
+            if query.archived.is_some_and(|wanted| wanted != archived) {
+                continue;
+            }
+
+            if !covers_all(&doc.text, &terms) {
+                continue;
+            }
+            let matches = match_ranges(&doc.text, &terms);
+            hits.push(SearchHit {
+                chat_id: chat.chat_id.clone(),
+                chat_label: entry.label,
+                project: location.project.clone(),
+                workspace: location.workspace.clone(),
+                agent_type: location.agent_type.clone(),
+                archived,
+                seq: doc.seq,
+                kind: doc.kind,
+                timestamp: doc.timestamp,
+                snippet: snippet(&doc.text, &matches),
+                score: matches.len(),
+            });
+        }
+
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(b.timestamp.cmp(&a.timestamp))
+                .then(a.chat_id.cmp(&b.chat_id))
+                .then(a.seq.cmp(&b.seq))
+        });
+        let total = hits.len();
+        hits.truncate(query.limit.unwrap_or(DEFAULT_LIMIT));
+        SearchResults { hits, total }
+    }
+
+    /// A chat's label and archived flag from its workspace's `chats.json`.
+    /// A chat whose workspace directory is gone counts as archived.
+    fn index_entry(
+        &self,
+        cache: &mut HashMap<PathBuf, Option<ChatIndex>>,
+        location: &ChatLocation,
+        chat_id: &str,
+    ) -> IndexEntryInfo {
+        let Ok(dir) = self.location_dir(location) else {
+            return IndexEntryInfo::default();
+        };
+        let index = cache.entry(dir.clone()).or_insert_with(|| {
+            dir.exists()
+                .then(|| load_chat_index(&dir).unwrap_or_default())
+        });
+        match index {
+            Some(index) => index
+                .chats
+                .iter()
+                .find(|entry| entry.id == chat_id)
+                .map(|entry| IndexEntryInfo {
+                    label: Some(entry.label.clone()),
+                    archived: entry.is_archived == Some(true),
+                })
+                .unwrap_or_default(),
+            None => IndexEntryInfo {
+                label: None,
+                archived: true,
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+struct IndexEntryInfo {
+    label: Option<String>,
+    archived: bool,
+}
+
+// ============================================================================
+// TEXT HELPERS
+// ============================================================================
+
+/// The searchable text of an event: (kind, text, streamed).
+fn searchable(event: &AgentEvent) -> Option<(HitKind, String, bool)> {
+    let (kind, text, streamed) = match event {
+        AgentEvent::PlanApproval { content, .. } => (HitKind::Assistant, content.clone(), false),
+        _ => {
+            let message = fold_event(0, event, View::Full)?;
+            match (message.role.as_str(), message.kind.as_deref()) {
+                ("user", _) => (HitKind::User, message.text, false),
+                ("assistant", None) => (HitKind::Assistant, message.text, false),
+                ("assistant", Some("tool")) => (HitKind::ToolCall, message.text, false),
+                (_, Some("toolResult")) => (HitKind::ToolResult, message.text, false),
+                (_, Some("bashOutput")) => (HitKind::ToolResult, message.text, true),
+                _ => return None,
+            }
+        }
+    };
+    (!text.trim().is_empty()).then_some((kind, text, streamed))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Words of `text` with their byte offsets.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        let (start, _) = rest.by_ref().find(|(_, c)| is_word_char(*c))?;
+        let mut end = text.len();
+        while let Some(&(i, c)) = rest.peek() {
+            if !is_word_char(c) {
+                end = i;
+                break;
+            }
+            rest.next();
+        }
+        Some((start, &text[start..end]))
+    })
+}
+
+/// Byte ranges of the words in `text` that start with one of `terms`.
+fn match_ranges(text: &str, terms: &[String]) -> Vec<(usize, usize)> {
+    words(text)
+        .filter(|(_, word)| {
+            let word = word.to_lowercase();
+            terms.iter().any(|term| word.starts_with(term.as_str()))
+        })
+        .map(|(start, word)| (start, start + word.len()))
+        .collect()
+}
+
+/// True if every term starts some word of `text`.
+fn covers_all(text: &str, terms: &[String]) -> bool {
+    let words: HashSet<String> = words(text).map(|(_, word)| word.to_lowercase()).collect();
+    terms
+        .iter()
+        .all(|term| words.iter().any(|word| word.starts_with(term.as_str())))
+}
+
+/// A window of `text` around the first match, whitespace collapsed, split
+/// into plain and highlighted parts.
+fn snippet(text: &str, matches: &[(usize, usize)]) -> Vec<SnippetPart> {
+    let first = matches.first().map(|m| m.0).unwrap_or(0);
+    let lead_start = text[..first]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_LEAD - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let end = text[lead_start..]
+        .char_indices()
+        .nth(SNIPPET_CHARS)
+        .map(|(i, _)| lead_start + i)
+        .unwrap_or(text.len());
+
+    let mut parts: Vec<SnippetPart> = Vec::new();
+    let mut push = |segment: &str, highlight: bool| {
+        let mut collapsed = String::new();
+        for c in segment.chars() {
+            if c.is_whitespace() {
+                if !collapsed.ends_with(' ') {
+                    collapsed.push(' ');
+                }
+            } else {
+                collapsed.push(c);
+            }
+        }
+        if collapsed.is_empty() {
+            return;
+        }
+        match parts.last_mut() {
+            Some(last) if last.highlight == highlight => last.text.push_str(&collapsed),
+            _ => parts.push(SnippetPart {
+                text: collapsed,
+                highlight,
+            }),
+        }
+    };
+
+    if lead_start > 0 {
+        push("…", false);
+    }
+    let mut cursor = lead_start;
+    for &(start, stop) in matches {
+        if start < cursor || start >= end {
+            continue;
+        }
+        let stop = stop.min(end);
+        push(&text[cursor..start], false);
+        push(&text[start..stop], true);
+        cursor = stop;
+    }
+    push(&text[cursor..end], false);
+    if end < text.len() {
+        push("…", false);
+    }
+
+    if let Some(first) = parts.first_mut() {
+        first.text = first.text.trim_start().to_string();
+    }
+    if let Some(last) = parts.last_mut() {
+        last.text = last.text.trim_end().to_string();
+    }
+    parts.retain(|part| !part.text.is_empty());
+    parts
+}
+
+/// Read the index log's records. A missing log has none.
+fn read_log(path: &Path) -> Result<Vec<LogRecord>, SearchError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut records = Vec::new();
+    let reader = BufReader::new(fs::File::open(path)?);
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // A torn last line from a crash is skipped; catch-up re-reads
+        // whatever it described.
+        let record = decode_line(path, line.as_bytes())
+            .and_then(|line| Ok(serde_json::from_slice::<LogRecord>(&line)?));
+        match record {
+            Ok(record) => records.push(record),
+            Err(e) => log::warn!("Skipping unreadable search index line: {e}"),
+        }
+    }
+    Ok(records)
+}
+
+/// Read a chat's events past `plan.from_seq`, or `None` (logged) if its
+/// file can't be read.
+fn read_tail(chat_id: &str, plan: &CatchUp) -> Option<ChatTail> {
+    let file_len = chat_file_len(&plan.dir, chat_id);
+    let read = count_events(&plan.dir, chat_id).and_then(|on_disk| {
+        let events = if on_disk > plan.from_seq {
+            load_chat_events_since_seq(&plan.dir, chat_id, plan.from_seq)?
+        } else {
+            Vec::new()
+        };
+        Ok((on_disk, events))
+    });
+    let (on_disk, events) = match read {
+        Ok(read) => read,
+        Err(e) => {
+            log::warn!("Failed to read chat {chat_id} for search: {e}");
+            return None;
+        }
+    };
+    let turn_time = plan.turn_time.unwrap_or_else(|| {
+        load_chat_metadata(&plan.dir, chat_id)
+            .map(|meta| meta.created_at)
+            .unwrap_or_else(|_| Utc::now())
+    });
+    Some(ChatTail {
+        on_disk,
+        file_len,
+        events,
+        turn_time,
+    })
+}
+
+/// Size of a chat's `.jsonl` file, if it exists.
+fn chat_file_len(dir: &Path, chat_id: &str) -> Option<u64> {
+    fs::metadata(dir.join(format!("{chat_id}.jsonl")))
+        .ok()
+        .map(|meta| meta.len())
+}
+
+/// Every chat under `chats_root`, with its location and directory.
+fn find_chats(chats_root: &Path) -> Vec<(String, ChatLocation, PathBuf)> {
+    let mut found = Vec::new();
+    for project in subdirs(chats_root) {
+        let project_dir = chats_root.join(&project);
+        for workspace in subdirs(&project_dir) {
+            if workspace == ARCHIVED_CHATS_DIR {
+                let archived_dir = project_dir.join(ARCHIVED_CHATS_DIR);
+                for archived in subdirs(&archived_dir) {
+                    found.extend(chat_files(
+                        &archived_dir.join(&archived),
+                        &project,
+                        &archived,
+                        true,
+                    ));
+                }
+            } else {
+                found.extend(chat_files(
+                    &project_dir.join(&workspace),
+                    &project,
+                    &workspace,
+                    false,
+                ));
+            }
+        }
+    }
+    found
+}
+
+/// Subdirectory names of `dir`; empty if it can't be read.
+fn subdirs(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .collect()
+}
+
+/// Chats (by their `.jsonl` files) in a workspace chat directory.
+fn chat_files(
+    dir: &Path,
+    project: &str,
+    workspace: &str,
+    archived_dir: bool,
+) -> Vec<(String, ChatLocation, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let chat_id = name.strip_suffix(".jsonl")?.to_string();
+            Some((
+                chat_id,
+                ChatLocation {
+                    project: project.to_string(),
+                    workspace: workspace.to_string(),
+                    agent_type: None,
+                    archived_dir,
+                },
+                dir.to_path_buf(),
+            ))
+        })
+        .collect()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::chat_jsonl::truncate_chat_events;
+    use crate::persistence::chat_jsonl::{append_chat_event, save_chat_metadata};
+    use crate::persistence::index::save_chat_index;
+    use crate::persistence::types::ChatIndexEntry;
+    use crate::test_support::{
+        sample_chat_metadata, sample_message, sample_text_event, sample_user_message, TestChatDir,
+    };
+
+    fn write_chat(
+        test_dir: &TestChatDir,
+        project: &str,
+        workspace: &str,
+        chat_id: &str,
+        events: &[AgentEvent],
+    ) -> PathBuf {
+        let dir = test_dir.create_chat_dir(project, workspace);
+        save_chat_metadata(&dir, &sample_chat_metadata(chat_id)).unwrap();
+        for event in events {
+            append_chat_event(&dir, chat_id, event).unwrap();
+        }
+        dir
+    }
+
+    fn index_for(test_dir: &TestChatDir) -> ChatSearchIndex {
+        let index = ChatSearchIndex::new();
+        index.set_config_dir(test_dir.path());
+        index
+    }
+
+    fn search(index: &ChatSearchIndex, text: &str) -> SearchResults {
+        index
+            .search(&SearchQuery {
+                query: text.to_string(),
+                ..SearchQuery::default()
+            })
+            .unwrap()
+    }
+
+    fn highlighted(hit: &SearchHit) -> Vec<&str> {
+        hit.snippet
+            .iter()
+            .filter(|part| part.highlight)
+            .map(|part| part.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn finds_chats_on_disk_by_word_prefix() {
+        let test_dir = TestChatDir::new();
+        write_chat(
+            &test_dir,
+            "proj",
+            "ws",
+            "chat-1",
+            &[
+                sample_user_message("Why does the migration fail?"),
+                sample_text_event("The migration bug is in "),
+                sample_message("The migration bug is in the schema loader.", None),
+                sample_message("[Bash]\ncargo test", None),
+            ],
+        );
+        let index = index_for(&test_dir);
+
+        let results = search(&index, "MIGRAT");
+        assert_eq!(results.total, 2);
+        let assistant = results
+            .hits
+            .iter()
+            .find(|hit| hit.kind == HitKind::Assistant)
+            .unwrap();
+        // The streaming delta isn't indexed; the consolidated message is.
+        assert_eq!(assistant.seq, 3);
+        assert_eq!(highlighted(assistant), vec!["migration"]);
+
+        let results = search(&index, "bug loader");
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].project, "proj");
+        assert_eq!(results.hits[0].workspace, "ws");
+        assert_eq!(results.hits[0].agent_type.as_deref(), Some("claude"));
+
+        let results = search(&index, "bash cargo");
+        assert_eq!(results.hits[0].kind, HitKind::ToolCall);
+    }
+
+    #[test]
+    fn live_events_are_searchable_before_flush() {
+        let test_dir = TestChatDir::new();
+        write_chat(&test_dir, "proj", "ws", "chat-1", &[]);
+        let index = index_for(&test_dir);
+        index
+            .track_chat("chat-1", "proj", "ws", Some("codex".to_string()))
+            .unwrap();
+
+        index.index_event("chat-1", 1, &sample_user_message("refactor the parser"));
+        index.index_event("chat-1", 2, &sample_text_event("Done with the parser"));
+        index.index_event(
+            "chat-1",
+            3,
+            &sample_message("Done with the parser rewrite.", None),
+        );
+        let bash_output = |text: &str| AgentEvent::BashOutput {
+            text: text.to_string(),
+        };
+        index.index_event("chat-1", 4, &bash_output("cargo bu"));
+        index.index_event("chat-1", 5, &bash_output("ild finished"));
+
+        let results = search(&index, "parser");
+        assert_eq!(results.total, 2);
+        assert!(results
+            .hits
+            .iter()
+            .all(|hit| hit.agent_type.as_deref() == Some("codex")));
+        // The chat file is still empty; a live chat isn't trimmed to match it.
+        assert_eq!(search(&index, "rewrite").total, 1);
+        // Streamed bash output is one document.
+        let results = search(&index, "build");
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].seq, 4);
+    }
+
+    #[test]
+    fn filters_by_project_workspace_agent_and_date() {
+        let test_dir = TestChatDir::new();
+        write_chat(
+            &test_dir,
+            "a",
+            "one",
+            "chat-a",
+            &[sample_user_message("deploy now")],
+        );
+        write_chat(
+            &test_dir,
+            "b",
+            "two",
+            "chat-b",
+            &[sample_user_message("deploy later")],
+        );
+        let index = index_for(&test_dir);
+
+        let query = |query: SearchQuery| {
+            index
+                .search(&SearchQuery {
+                    query: "deploy".to_string(),
+                    ..query
+                })
+                .unwrap()
+                .total
+        };
+        assert_eq!(query(SearchQuery::default()), 2);
+        assert_eq!(
+            query(SearchQuery {
+                project: Some("a".to_string()),
+                ..SearchQuery::default()
+            }),
+            1
+        );
+        assert_eq!(
+            query(SearchQuery {
+                workspace: Some("two".to_string()),
+                ..SearchQuery::default()
+            }),
+            1
+        );
+        assert_eq!(
+            query(SearchQuery {
+                agent_type: Some("gemini".to_string()),
+                ..SearchQuery::default()
+            }),
+            0
+        );
+        assert_eq!(
+            query(SearchQuery {
+                since: Some(Utc::now() + chrono::Duration::hours(1)),
+                ..SearchQuery::default()
+            }),
+            0
+        );
+        assert_eq!(
+            query(SearchQuery {
+                limit: Some(1),
+                ..SearchQuery::default()
+            }),
+            2
+        );
+    }
+
+    #[test]
+    fn archived_filter_uses_chat_index_and_archived_dir() {
+        let test_dir = TestChatDir::new();
+        let dir = write_chat(
+            &test_dir,
+            "p",
+            "ws",
+            "old",
+            &[sample_user_message("flaky test")],
+        );
+        write_chat(
+            &test_dir,
+            "p",
+            "ws",
+            "new",
+            &[sample_user_message("flaky build")],
+        );
+        write_chat(
+            &test_dir,
+            "p",
+            "archived/gone-20240101",
+            "moved",
+            &[sample_user_message("flaky deploy")],
+        );
+        let now = Utc::now();
+        save_chat_index(
+            &dir,
+            &ChatIndex {
+                chats: vec![ChatIndexEntry {
+                    id: "old".to_string(),
+                    label: "Old chat".to_string(),
+                    agent_type: None,
+                    created_at: now,
+                    updated_at: now,
+                    is_archived: Some(true),
+                    archived_at: Some(now),
+                }],
+            },
+            None,
+        )
+        .unwrap();
+        let index = index_for(&test_dir);
+
+        let archived = index
+            .search(&SearchQuery {
+                query: "flaky".to_string(),
+                archived: Some(true),
+                ..SearchQuery::default()
+            })
+            .unwrap();
+        let mut ids: Vec<_> = archived.hits.iter().map(|h| h.chat_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["moved", "old"]);
+        let old = archived.hits.iter().find(|h| h.chat_id == "old").unwrap();
+        assert_eq!(old.chat_label.as_deref(), Some("Old chat"));
+        let moved = archived.hits.iter().find(|h| h.chat_id == "moved").unwrap();
+        assert_eq!(moved.workspace, "gone-20240101");
+
+        let active = index
+            .search(&SearchQuery {
+                query: "flaky".to_string(),
+                archived: Some(false),
+                ..SearchQuery::default()
+            })
+            .unwrap();
+        assert_eq!(active.total, 1);
+        assert_eq!(active.hits[0].chat_id, "new");
+    }
+
+    #[test]
+    fn reload_reads_only_new_events() {
+        let test_dir = TestChatDir::new();
+        let dir = write_chat(
+            &test_dir,
+            "p",
+            "ws",
+            "chat",
+            &[sample_user_message("alpha")],
+        );
+        assert_eq!(search(&index_for(&test_dir), "alpha").total, 1);
+
+        append_chat_event(&dir, "chat", &sample_user_message("beta")).unwrap();
+        let index = index_for(&test_dir);
+        assert_eq!(search(&index, "alpha").total, 1);
+        assert_eq!(search(&index, "beta").total, 1);
+
+        // Each document was logged once.
+        let log = fs::read_to_string(test_dir.path().join(SEARCH_DIR).join(LOG_FILE)).unwrap();
+        assert_eq!(log.matches("\"alpha\"").count(), 1);
+        assert_eq!(log.matches("\"beta\"").count(), 1);
+    }
+
+    #[test]
+    fn later_searches_read_chats_that_changed() {
+        let test_dir = TestChatDir::new();
+        let dir = write_chat(
+            &test_dir,
+            "p",
+            "ws",
+            "chat",
+            &[sample_user_message("alpha")],
+        );
+        let index = index_for(&test_dir);
+        assert_eq!(search(&index, "alpha").total, 1);
+
+        append_chat_event(&dir, "chat", &sample_user_message("beta")).unwrap();
+        write_chat(&test_dir, "p", "ws", "new", &[sample_user_message("gamma")]);
+        assert_eq!(search(&index, "beta").total, 1);
+        assert_eq!(search(&index, "gamma").total, 1);
+        assert_eq!(search(&index, "alpha").total, 1);
+    }
+
+    #[test]
+    fn live_events_wait_for_the_file_being_read() {
+        let test_dir = TestChatDir::new();
+        write_chat(
+            &test_dir,
+            "p",
+            "ws",
+            "chat",
+            &[sample_user_message("first words")],
+        );
+        let index = index_for(&test_dir);
+        index.ensure_loaded().unwrap();
+        let location = ChatLocation {
+            project: "p".to_string(),
+            workspace: "ws".to_string(),
+            agent_type: None,
+            archived_dir: false,
+        };
+        let plan = index
+            .state
+            .lock()
+            .unwrap()
+            .plan_catch_up("chat", location, true)
+            .unwrap()
+            .unwrap();
+
+        // Arrives while the file is read without the lock.
+        index.index_event("chat", 2, &sample_user_message("second words"));
+        assert_eq!(search(&index, "words").total, 0);
+
+        let tail = read_tail("chat", &plan);
+        let again = index
+            .state
+            .lock()
+            .unwrap()
+            .finish_catch_up("chat", plan, tail)
+            .unwrap();
+        assert!(!again);
+        let results = search(&index, "words");
+        let mut seqs: Vec<u64> = results.hits.iter().map(|hit| hit.seq).collect();
+        seqs.sort();
+        assert_eq!(seqs, vec![1, 2]);
+    }
+
+    #[test]
+    fn rewound_and_deleted_chats_are_dropped() {
+        let test_dir = TestChatDir::new();
+        let dir = write_chat(
+            &test_dir,
+            "p",
+            "ws",
+            "chat",
+            &[
+                sample_user_message("keep me"),
+                sample_user_message("drop me"),
+            ],
+        );
+        write_chat(
+            &test_dir,
+            "p",
+            "ws",
+            "other",
+            &[sample_user_message("drop this too")],
+        );
+        let index = index_for(&test_dir);
+        assert_eq!(search(&index, "drop").total, 2);
+
+        truncate_chat_events(&dir, "chat", 1).unwrap();
+        index.truncate_chat("chat", 1).unwrap();
+        assert_eq!(search(&index, "drop").total, 1);
+        assert_eq!(search(&index, "keep").total, 1);
+
+        // Deleted and rewound on disk while the app was closed.
+        fs::remove_file(dir.join("other.jsonl")).unwrap();
+        let index = index_for(&test_dir);
+        assert_eq!(search(&index, "drop").total, 0);
+        assert_eq!(search(&index, "keep").total, 1);
+    }
+
+    #[test]
+    fn snippet_is_windowed_and_collapsed() {
+        let text = format!(
+            "{}\n\nthe needle   is here {}",
+            "x ".repeat(100),
+            "y ".repeat(200)
+        );
+        let matches = match_ranges(&text, &["needle".to_string()]);
+        let parts = snippet(&text, &matches);
+
+        assert_eq!(parts.first().unwrap().text.chars().next(), Some('…'));
+        assert_eq!(parts.last().unwrap().text.chars().last(), Some('…'));
+        assert!(parts.iter().any(|p| p.highlight && p.text == "needle"));
+        assert!(parts.iter().all(|p| !p.text.contains("  ")));
+    }
+
+    #[test]
+    fn empty_query_matches_nothing() {
+        let test_dir = TestChatDir::new();
+        write_chat(
+            &test_dir,
+            "p",
+            "ws",
+            "chat",
+            &[sample_user_message("hello")],
+        );
+        assert_eq!(search(&index_for(&test_dir), "  ").total, 0);
+    }
+}
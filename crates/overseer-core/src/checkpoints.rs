@@ -0,0 +1,353 @@
+//! Per-turn checkpoints of a chat's workspace, and rewinding to them.
+//!
+//! # Overview
+//!
+//! Each time a user message starts a turn, [`checkpoint_turn`] snapshots the
+//! workspace under a hidden ref keyed by the chat and the message's id (see
+//! [`git::checkpoint`](crate::git::checkpoint)). This module ties those
+//! snapshots back to the chat:
+//!
+//! - [`list_turn_checkpoints`] - every `UserMessage` of a chat with its
+//!   checkpoint, if one was taken
+//! - [`rewind_to_turn`] - restore a turn's checkpoint and, optionally, cut the
+//!   conversation back to just before that message
+//!
+//! Diffing a checkpoint against the current state is a plain git operation:
+//! [`diff_checkpoint`](crate::git::diff_checkpoint).
+//!
+//! # Truncating the Conversation
+//!
+//! The chat's events are cut at the turn's `UserMessage` (the message itself
+//! goes too, so it can be edited and resent). The agent keeps its own copy
+//! of the conversation, so the chat is forked onto itself at that turn
+//! ([`plan_rewind`](crate::chat_fork::plan_rewind)): the chat's
+//! `agent_session_id` is cleared and the next message resumes the agent
+//! session cut at the kept turns, with the context it had then. Checkpoints
+//! of the dropped turns are deleted.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::agents::event::AgentEvent;
+use crate::git::{
+    create_checkpoint, delete_checkpoints, find_checkpoint, is_git_repo, list_checkpoints,
+    restore_checkpoint, Checkpoint, GitError,
+};
+use crate::managers::ChatSessionManager;
+use crate::persistence::SeqEvent;
+
+/// A chat turn (one `UserMessage`) and its checkpoint.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnCheckpoint {
+    /// Id of the `UserMessage` event
+    pub turn_id: String,
+    /// Seq of the `UserMessage` event in the chat's JSONL
+    pub seq: u64,
+    /// The message text
+    pub content: String,
+    /// When the message was sent
+    pub timestamp: DateTime<Utc>,
+    /// The workspace snapshot taken before the turn, if any
+    pub checkpoint: Option<Checkpoint>,
+}
+
+/// What [`rewind_to_turn`] did.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RewindResult {
+    /// The checkpoint that was restored
+    pub checkpoint: Checkpoint,
+    /// Snapshot of the workspace just before the restore
+    pub undo_commit: String,
+    /// Chat events removed (0 unless the chat was truncated)
+    pub removed_events: u64,
+}
+
+/// Snapshot `workspace_path` for the turn that `user_message` starts.
+///
+/// Best effort: a failed checkpoint is logged and must never stop the
+/// message from being sent. Non-git workspaces are skipped.
+pub async fn checkpoint_turn(
+    workspace_path: &Path,
+    chat_id: &str,
+    user_message: &AgentEvent,
+) -> Option<Checkpoint> {
+    let AgentEvent::UserMessage { id, .. } = user_message else {
+        return None;
+    };
+    if !is_git_repo(workspace_path) {
+        return None;
+    }
+
+    match create_checkpoint(workspace_path, chat_id, id).await {
+        Ok(checkpoint) => Some(checkpoint),
+        Err(e) => {
+            log::warn!("Failed to checkpoint turn {id} of chat {chat_id}: {e}");
+            None
+        }
+    }
+}
+
+/// Every `UserMessage` in `events`, paired with its checkpoint.
+pub fn match_turns(events: &[SeqEvent], checkpoints: Vec<Checkpoint>) -> Vec<TurnCheckpoint> {
+    let mut checkpoints = checkpoints;
+    events
+        .iter()
+        .filter_map(|e| match &e.event {
+            AgentEvent::UserMessage {
+                id,
+                content,
+                timestamp,
+                ..
+            } => {
+                let checkpoint = checkpoints
+                    .iter()
+                    .position(|c| &c.turn_id == id)
+                    .map(|i| checkpoints.swap_remove(i));
+                Some(TurnCheckpoint {
+                    turn_id: id.clone(),
+                    seq: e.seq,
+                    content: content.clone(),
+                    timestamp: *timestamp,
+                    checkpoint,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The chat's turns with their checkpoints, oldest first.
+pub async fn list_turn_checkpoints(
+    chat_sessions: &ChatSessionManager,
+    project_name: &str,
+    workspace_name: &str,
+    chat_id: &str,
+    workspace_path: &Path,
+) -> Result<Vec<TurnCheckpoint>, GitError> {
+    // The latest user message may still be buffered.
+    chat_sessions.flush(chat_id).map_err(GitError::Other)?;
+    let events = chat_sessions
+        .load_events_with_seq(project_name, workspace_name, chat_id)
+        .map_err(GitError::Other)?;
+    let checkpoints = list_checkpoints(workspace_path, chat_id).await?;
+    Ok(match_turns(&events, checkpoints))
+}
+
+/// Restore the checkpoint taken before `turn_id`.
+///
+/// With `truncate_chat`, the conversation is also cut back to just before
+/// that turn (see the module docs). `claude_root` is Claude Code's config
+/// directory, where a Claude session's transcript is read to resume it.
+#[allow(clippy::too_many_arguments)]
+pub async fn rewind_to_turn(
+    chat_sessions: &ChatSessionManager,
+    project_name: &str,
+    workspace_name: &str,
+    chat_id: &str,
+    workspace_path: &Path,
+    turn_id: &str,
+    truncate_chat: bool,
+    claude_root: Option<&Path>,
+) -> Result<RewindResult, GitError> {
+    let checkpoint = find_checkpoint(workspace_path, chat_id, turn_id)
+        .await?
+        .ok_or_else(|| GitError::Other(format!("No checkpoint for turn {turn_id}")))?;
+
+    // Look the turn up before touching the workspace, so a bad turn id
+    // fails cleanly.
+    let turns = if truncate_chat {
+        let turns = list_turn_checkpoints(
+            chat_sessions,
+            project_name,
+            workspace_name,
+            chat_id,
+            workspace_path,
+        )
+        .await?;
+        if !turns.iter().any(|t| t.turn_id == turn_id) {
+            return Err(GitError::Other(format!(
+                "Turn {turn_id} not found in chat {chat_id}"
+            )));
+        }
+        turns
+    } else {
+        Vec::new()
+    };
+
+    let undo_commit = restore_checkpoint(workspace_path, &checkpoint).await?;
+
+    let mut removed_events = 0;
+    if let Some(turn) = turns.iter().find(|t| t.turn_id == turn_id) {
+        chat_sessions
+            .plan_rewind(
+                project_name,
+                workspace_name,
+                chat_id,
+                turn.seq - 1,
+                claude_root,
+            )
+            .map_err(GitError::Other)?;
+        removed_events = chat_sessions
+            .truncate_events(project_name, workspace_name, chat_id, turn.seq - 1)
+            .map_err(GitError::Other)?;
+
+        let dropped: Vec<Checkpoint> = turns
+            .iter()
+            .filter(|t| t.seq >= turn.seq)
+            .filter_map(|t| t.checkpoint.clone())
+            .collect();
+        delete_checkpoints(workspace_path, &dropped).await?;
+
+        let mut metadata = chat_sessions
+            .load_metadata(project_name, workspace_name, chat_id)
+            .map_err(GitError::Other)?;
+        metadata.agent_session_id = None;
+        metadata.updated_at = Utc::now();
+        chat_sessions
+            .save_metadata(project_name, workspace_name, metadata)
+            .map_err(GitError::Other)?;
+    }
+
+    Ok(RewindResult {
+        checkpoint,
+        undo_commit,
+        removed_events,
+    })
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{sample_chat_metadata, sample_text_event, TestChatDir};
+    use std::process::Command;
+
+    fn user_message(id: &str, content: &str) -> AgentEvent {
+        AgentEvent::UserMessage {
+            id: id.to_string(),
+            content: content.to_string(),
+            timestamp: Utc::now(),
+            meta: None,
+        }
+    }
+
+    fn checkpoint(turn_id: &str) -> Checkpoint {
+        Checkpoint {
+            chat_id: "chat-1".to_string(),
+            turn_id: turn_id.to_string(),
+            ref_name: format!("refs/overseer/checkpoints/chat-1/{turn_id}"),
+            commit: "abc".to_string(),
+            head: None,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn match_turns_pairs_user_messages_with_checkpoints() {
+        let events = vec![
+            SeqEvent {
+                seq: 1,
+                event: user_message("t1", "first"),
+            },
+            SeqEvent {
+                seq: 2,
+                event: sample_text_event("reply"),
+            },
+            SeqEvent {
+                seq: 3,
+                event: user_message("t2", "second"),
+            },
+        ];
+
+        let turns = match_turns(&events, vec![checkpoint("t2"), checkpoint("gone")]);
+        assert_eq!(turns.len(), 2);
+        assert_eq!((turns[0].turn_id.as_str(), turns[0].seq), ("t1", 1));
+        assert!(turns[0].checkpoint.is_none());
+        assert_eq!(turns[1].content, "second");
+        assert_eq!(turns[1].checkpoint.as_ref().unwrap().turn_id, "t2");
+    }
+
+    #[tokio::test]
+    async fn rewind_restores_files_and_truncates_chat() {
+        let repo = tempfile::tempdir().unwrap();
+        let path = repo.path();
+        let git = |args: &[&str]| {
+            let out = Command::new("git")
+                .args(args)
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .current_dir(path)
+                .output()
+                .unwrap();
+            assert!(out.status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-b", "main"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::write(path.join("a.txt"), "original\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        let test_dir = TestChatDir::new();
+        let manager = ChatSessionManager::new();
+        manager.set_config_dir(test_dir.path());
+        let mut metadata = sample_chat_metadata("chat-1");
+        metadata.agent_session_id = Some("agent-session".to_string());
+        manager
+            .register_session(
+                "chat-1".to_string(),
+                "p".to_string(),
+                "w".to_string(),
+                metadata,
+            )
+            .unwrap();
+
+        for (turn, text) in [("t1", "one\n"), ("t2", "two\n")] {
+            let message = user_message(turn, "do it");
+            manager.append_event("chat-1", message.clone()).unwrap();
+            assert!(checkpoint_turn(path, "chat-1", &message).await.is_some());
+            std::fs::write(path.join("a.txt"), text).unwrap();
+            manager
+                .append_event("chat-1", sample_text_event("done"))
+                .unwrap();
+        }
+
+        let turns = list_turn_checkpoints(&manager, "p", "w", "chat-1", path)
+            .await
+            .unwrap();
+        assert_eq!(turns.len(), 2);
+        assert!(turns.iter().all(|t| t.checkpoint.is_some()));
+
+        let result = rewind_to_turn(&manager, "p", "w", "chat-1", path, "t2", true, None)
+            .await
+            .unwrap();
+        assert_eq!(result.removed_events, 2);
+        assert_eq!(
+            std::fs::read_to_string(path.join("a.txt")).unwrap(),
+            "one\n"
+        );
+
+        let turns = list_turn_checkpoints(&manager, "p", "w", "chat-1", path)
+            .await
+            .unwrap();
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].turn_id, "t1");
+        assert!(list_checkpoints(path, "chat-1")
+            .await
+            .unwrap()
+            .iter()
+            .all(|c| c.turn_id == "t1"));
+        let metadata = manager.load_metadata("p", "w", "chat-1").unwrap();
+        assert_eq!(metadata.agent_session_id, None);
+        // The next message resumes the agent session at the kept turn.
+        let plan = manager.take_pending_fork("chat-1").unwrap().unwrap();
+        assert_eq!(plan.through_seq, 2);
+        assert!(plan.replay_prompt.contains("do it"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Handing sync conflicts to the workspace's agent.
+//!
+//! # Overview
+//!
+//! When [`sync_with_default_branch`](crate::git::sync_with_default_branch)
+//! stops on conflicts, the worktree is left mid-merge (or mid-rebase). This
+//! module turns the conflicted files into a prompt asking the agent to
+//! resolve them in place. The agent is told not to finish the merge itself:
+//! once it's done, [`continue_sync`](crate::git::continue_sync) checks that
+//! no conflict markers are left before committing or continuing the rebase.
+//!
+//! ```ignore
+//! if let Some(resolution) = prepare_conflict_resolution(&workspace).await? {
+//!     send_to_agent(&resolution.prompt);
+//! }
+//! // ...after the agent's turn:
+//! let result = continue_sync(&workspace).await?;
+//! ```
+
+use std::fmt::Write;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::git::{ConflictFile, GitError, SyncStrategy};
+use crate::vcs::Vcs;
+
+/// A prompt built from a stopped sync's conflicts.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictResolution {
+    pub strategy: SyncStrategy,
+    /// The commit being merged in, or rebased onto
+    pub upstream: String,
+    /// Paths of the conflicted files
+    pub files: Vec<String>,
+    /// The prompt to send to the agent
+    pub prompt: String,
+}
+
+/// Build the resolution prompt for the sync stopped in `workspace_path`.
+///
+/// # Returns
+///
+/// `Ok(None)` if no sync is in progress or nothing is conflicted.
+pub async fn prepare_conflict_resolution(
+    workspace_path: &Path,
+) -> Result<Option<ConflictResolution>, GitError> {
+    let Some(state) = Vcs::detect(workspace_path)
+        .get_sync_state(workspace_path)
+        .await?
+    else {
+        return Ok(None);
+    };
+    if state.conflicts.is_empty() {
+        return Ok(None);
+    }
+
+    let prompt = build_conflict_prompt(state.strategy, &state.upstream, &state.conflicts);
+    Ok(Some(ConflictResolution {
+        strategy: state.strategy,
+        upstream: state.upstream,
+        files: state.conflicts.iter().map(|c| c.path.clone()).collect(),
+        prompt,
+    }))
+}
+
+/// The prompt asking the agent to resolve `conflicts`.
+pub fn build_conflict_prompt(
+    strategy: SyncStrategy,
+    upstream: &str,
+    conflicts: &[ConflictFile],
+) -> String {
+    let mut prompt = String::new();
+    let action = match strategy {
+        SyncStrategy::Merge => "Merging the default branch into this workspace",
+        SyncStrategy::Rebase => "Rebasing this workspace onto the default branch",
+    };
+    let _ = writeln!(
+        prompt,
+        "{action} (`{upstream}`) stopped on conflicts in {} file(s):\n",
+        conflicts.len()
+    );
+
+    for conflict in conflicts {
+        let _ = write!(prompt, "- `{}`", conflict.path);
+        match (&conflict.branch, &conflict.upstream) {
+            (None, Some(_)) => prompt.push_str(" (deleted in this workspace, changed upstream)"),
+            (Some(_), None) => prompt.push_str(" (changed in this workspace, deleted upstream)"),
+            _ if conflict.marker_lines.is_empty() => {}
+            _ => {
+                let lines: Vec<String> = conflict
+                    .marker_lines
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect();
+                let _ = write!(prompt, " (markers on lines {})", lines.join(", "));
+            }
+        }
+        prompt.push('\n');
+    }
+
+    // Mid-rebase, git's "ours" is the upstream; spell out which side is which.
+    let (ours, theirs) = match strategy {
+        SyncStrategy::Merge => ("this workspace's changes", "the default branch's changes"),
+        SyncStrategy::Rebase => ("the default branch's changes", "this workspace's changes"),
+    };
+    let _ = write!(
+        prompt,
+        "\nIn each file, the side after `<<<<<<<` is {ours} and the side before \
+         `>>>>>>>` is {theirs}.\n\n\
+         Resolve every conflict so both sides' intent is kept, remove all conflict \
+         markers, and check the code still builds. Stage the resolved files with \
+         `git add`, but do not commit and do not run `git merge --continue` or \
+         `git rebase --continue`; the sync is finished for you once no markers are left."
+    );
+    prompt
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conflict(path: &str, branch: Option<&str>, upstream: Option<&str>) -> ConflictFile {
+        ConflictFile {
+            path: path.to_string(),
+            base: None,
+            branch: branch.map(str::to_string),
+            upstream: upstream.map(str::to_string),
+            marker_lines: if branch.is_some() && upstream.is_some() {
+                vec![3, 5, 7]
+            } else {
+                vec![]
+            },
+        }
+    }
+
+    #[test]
+    fn prompt_lists_files_and_explains_sides() {
+        let prompt = build_conflict_prompt(
+            SyncStrategy::Rebase,
+            "origin/main",
+            &[
+                conflict("src/lib.rs", Some("a"), Some("b")),
+                conflict("old.rs", None, Some("b")),
+            ],
+        );
+
+        assert!(
+            prompt.starts_with("Rebasing this workspace onto the default branch (`origin/main`)")
+        );
+        assert!(prompt.contains("- `src/lib.rs` (markers on lines 3, 5, 7)"));
+        assert!(prompt.contains("- `old.rs` (deleted in this workspace, changed upstream)"));
+        assert!(prompt.contains("after `<<<<<<<` is the default branch's changes"));
+        assert!(prompt.contains("do not commit"));
+    }
+}
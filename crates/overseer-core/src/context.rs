@@ -36,6 +36,7 @@ use crate::managers::{
     GeminiAgentManager, HermesAgentManager, OpenCodeAgentManager, PiAgentManager,
     ProjectApprovalManager, PtyManager,
 };
+use crate::persistence::{self, ConfigDocument, ConfigWatcher};
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
@@ -168,6 +169,13 @@ impl OverseerContextBuilder {
             .pty_manager
             .unwrap_or_else(|| Arc::new(PtyManager::new()));
 
+        // Agent events are typed `agent:<kind>:<chat id>`.
+        let sessions = Arc::clone(&chat_sessions);
+        event_bus.set_chat_dir_resolver(Arc::new(move |event_type| {
+            let chat_id = event_type.strip_prefix("agent:")?.split_once(':')?.1;
+            sessions.chat_dir(chat_id)
+        }));
+
         OverseerContext {
             event_bus,
             config_dir: Arc::new(RwLock::new(self.config_dir)),
@@ -240,6 +248,25 @@ impl OverseerContext {
         *self.config_dir.write().unwrap() = Some(dir);
     }
 
+    /// Watch the config directory for changes another process makes (see
+    /// [`persistence::watch`]). Cached approvals are dropped when their file
+    /// changes. Returns `None` if the config directory isn't set.
+    pub fn watch_config_dir(&self) -> Option<ConfigWatcher> {
+        let config_dir = self.config_dir()?;
+        let approval_manager = Arc::clone(&self.approval_manager);
+        Some(persistence::watch_config_dir(
+            config_dir,
+            Arc::clone(&self.event_bus),
+            move |change| {
+                if let (ConfigDocument::Approvals, Some(project)) =
+                    (change.document, &change.project)
+                {
+                    approval_manager.forget(project);
+                }
+            },
+        ))
+    }
+
     /// Get the chats directory for a project/workspace.
     pub fn get_chat_dir(&self, project_name: &str, workspace_name: &str) -> Option<PathBuf> {
         self.config_dir
@@ -6,6 +6,8 @@
 //!
 //! - [`rename_branch`] - Rename the current branch
 //! - [`delete_branch`] - Delete a branch from the repository
+//! - [`force_delete_branch`] - Delete a branch git doesn't consider merged
+//! - [`list_merged_branches`] - Branches whose commits all landed in another
 //!
 //! # Safety
 //!
@@ -85,13 +87,13 @@ pub async fn rename_branch(
 /// - Fails if branch has unmerged commits
 /// - Fails if trying to delete the current branch
 ///
-/// To force-delete, use git directly with `-D`.
+/// To force-delete, use [`force_delete_branch`].
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - Branch doesn't exist
-/// - Branch has unmerged commits (use `-D` manually if intended)
+/// - Branch has unmerged commits (use [`force_delete_branch`] if intended)
 /// - Currently checked out on that branch
 ///
 /// # Example
@@ -105,6 +107,71 @@ pub async fn delete_branch(repo_path: &Path, branch_name: &str) -> Result<(), Gi
     Ok(())
 }
 
+/// Delete a branch even if it has unmerged commits (`git branch -D`).
+///
+/// For branches whose work landed some other way, e.g. a squash merge,
+/// after which git still sees the branch's own commits as unmerged.
+///
+/// # Errors
+///
+/// Returns an error if the branch doesn't exist or is checked out.
+pub async fn force_delete_branch(repo_path: &Path, branch_name: &str) -> Result<(), GitError> {
+    run_git_success(&["branch", "-D", branch_name], repo_path).await?;
+    Ok(())
+}
+
+/// Local branches with commits of their own that are all contained in `target`.
+///
+/// A branch that never got a commit (e.g. a fresh workspace) also points at
+/// an ancestor of `target`, but nothing of it was merged. Such branches are
+/// left out: their reflog holds only branch creation and renames. Squash
+/// merged branches aren't found either, since their commits never land as-is.
+///
+/// # Arguments
+///
+/// * `repo_path` - Path to the repository (or any worktree)
+/// * `target` - The branch merged into, usually the default branch
+pub async fn list_merged_branches(repo_path: &Path, target: &str) -> Result<Vec<String>, GitError> {
+    let stdout = run_git_success(
+        &[
+            "for-each-ref",
+            "--merged",
+            target,
+            "--format=%(refname:short)",
+            "refs/heads",
+        ],
+        repo_path,
+    )
+    .await?;
+
+    let mut merged = Vec::new();
+    for branch in stdout.lines().map(str::trim).filter(|b| !b.is_empty()) {
+        let target_name = target.strip_prefix("origin/").unwrap_or(target);
+        if branch == target_name || is_default_branch_name(branch, None) {
+            continue;
+        }
+        let reflog = run_git_success(
+            &[
+                "reflog",
+                "show",
+                "--format=%gs",
+                &format!("refs/heads/{branch}"),
+                "--",
+            ],
+            repo_path,
+        )
+        .await
+        .unwrap_or_default();
+        let has_commits = reflog
+            .lines()
+            .any(|entry| !entry.to_ascii_lowercase().starts_with("branch:"));
+        if has_commits {
+            merged.push(branch.to_string());
+        }
+    }
+    Ok(merged)
+}
+
 /// List recently-updated remote branches from `origin`, sorted newest first.
 ///
 /// First runs `git fetch origin --prune` to ensure we have the latest remote refs
@@ -321,6 +388,37 @@ mod tests {
         // Try to delete - should fail with -d (safe delete)
         let result = delete_branch(path, "unmerged-feature").await;
         assert!(result.is_err());
+
+        // -D deletes it anyway
+        force_delete_branch(path, "unmerged-feature").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_merged_branches_skips_branches_without_commits() {
+        let dir = init_temp_repo("main");
+        let path = dir.path();
+        let git = |args: &[&str]| {
+            let out = Command::new("git")
+                .args(args)
+                .current_dir(path)
+                .output()
+                .unwrap();
+            assert!(
+                out.status.success(),
+                "{}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+        };
+        git(&["branch", "fresh"]);
+        git(&["checkout", "-b", "done"]);
+        git(&["commit", "--allow-empty", "-m", "work"]);
+        git(&["checkout", "main"]);
+        git(&["merge", "--ff-only", "done"]);
+        git(&["branch", "-m", "fresh", "renamed"]);
+
+        let merged = list_merged_branches(path, "main").await.unwrap();
+
+        assert_eq!(merged, vec!["done".to_string()]);
     }
 
     #[tokio::test]
@@ -0,0 +1,541 @@
+//! Per-turn workspace checkpoints.
+//!
+//! # Overview
+//!
+//! Before each agent turn, the workspace is snapshotted so a turn that
+//! trashes the worktree can be undone without manual git surgery. A
+//! checkpoint is an ordinary commit stored under a hidden ref:
+//!
+//! ```text
+//! refs/overseer/checkpoints/<chat_id>/<turn_id>
+//! ```
+//!
+//! where `<turn_id>` is the id of the chat's `UserMessage` that started the
+//! turn. Hidden refs don't show up in `git branch` or `git log --all`'s usual
+//! views of branches, are never pushed, and keep the snapshot reachable so
+//! `git gc` won't collect it.
+//!
+//! # Snapshots
+//!
+//! The snapshot covers tracked *and* untracked files (anything not ignored).
+//! It is built with a temporary index (`GIT_INDEX_FILE`), seeded from the
+//! real one for speed, so the user's staging area is never touched:
+//!
+//! ```text
+//! cp .git/index tmp-index
+//! GIT_INDEX_FILE=tmp-index git add -A
+//! GIT_INDEX_FILE=tmp-index git write-tree      -> tree
+//! git commit-tree <tree> -p HEAD               -> checkpoint commit
+//! git update-ref refs/overseer/checkpoints/...  <commit>
+//! ```
+//!
+//! The checkpoint commit's parent is the HEAD at the time, which is how
+//! [`restore_checkpoint`] knows where to move the branch back to.
+//!
+//! # Restoring
+//!
+//! [`restore_checkpoint`] first snapshots the current state under
+//! `refs/overseer/checkpoint-undo/<chat_id>`, so a restore can be undone the
+//! same way. It then deletes files created since the checkpoint, resets the
+//! branch and index to the checkpoint's HEAD, and writes the checkpoint's
+//! files back into the worktree. Ignored files are never touched.
+
+use super::diff::DiffOptions;
+use super::patch::{parse_unified_diff, FileDiff};
+use super::{git_path, run_git, run_git_success, run_git_with_env, GitError};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Namespace for checkpoint refs.
+pub const CHECKPOINT_REF_PREFIX: &str = "refs/overseer/checkpoints";
+
+/// Namespace for the pre-restore snapshot of each chat.
+pub const CHECKPOINT_UNDO_REF_PREFIX: &str = "refs/overseer/checkpoint-undo";
+
+/// Identity for checkpoint commits, so they work without `user.name` set
+/// and are recognisable in the reflog.
+const CHECKPOINT_IDENTITY: [(&str, &str); 4] = [
+    ("GIT_AUTHOR_NAME", "Overseer"),
+    ("GIT_AUTHOR_EMAIL", "overseer@localhost"),
+    ("GIT_COMMITTER_NAME", "Overseer"),
+    ("GIT_COMMITTER_EMAIL", "overseer@localhost"),
+];
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// A snapshot of a workspace taken at the start of a chat turn.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    /// Chat the turn belongs to
+    pub chat_id: String,
+    /// Id of the `UserMessage` event that started the turn
+    pub turn_id: String,
+    /// Full ref name (`refs/overseer/checkpoints/<chat>/<turn>`)
+    pub ref_name: String,
+    /// The snapshot commit
+    pub commit: String,
+    /// HEAD when the snapshot was taken (`None` on an unborn branch)
+    pub head: Option<String>,
+    /// When the snapshot was taken, RFC 3339
+    pub created_at: String,
+}
+
+// ============================================================================
+// OPERATIONS
+// ============================================================================
+
+/// Snapshot `workspace_path` as the checkpoint for `turn_id` of `chat_id`.
+///
+/// Overwrites an existing checkpoint for the same turn.
+pub async fn create_checkpoint(
+    workspace_path: &Path,
+    chat_id: &str,
+    turn_id: &str,
+) -> Result<Checkpoint, GitError> {
+    let ref_name = checkpoint_ref(chat_id, turn_id)?;
+    let head = current_head(workspace_path).await?;
+    let tree = snapshot_tree(workspace_path).await?;
+    let message = format!("Overseer checkpoint\n\nChat: {chat_id}\nTurn: {turn_id}\n");
+    let commit = commit_tree(workspace_path, &tree, head.as_deref(), &message).await?;
+
+    run_git_success(&["update-ref", &ref_name, &commit], workspace_path).await?;
+
+    find_checkpoint(workspace_path, chat_id, turn_id)
+        .await?
+        .ok_or_else(|| GitError::Other(format!("Checkpoint {ref_name} vanished after creation")))
+}
+
+/// All checkpoints of `chat_id`, by turn id.
+///
+/// Snapshot times have one-second resolution, so they can't order quick
+/// turns. The chat's events do:
+/// [`list_turn_checkpoints`](crate::checkpoints::list_turn_checkpoints)
+/// lists them by turn.
+pub async fn list_checkpoints(
+    workspace_path: &Path,
+    chat_id: &str,
+) -> Result<Vec<Checkpoint>, GitError> {
+    validate_ref_component(chat_id)?;
+    read_checkpoints(
+        workspace_path,
+        &format!("{CHECKPOINT_REF_PREFIX}/{chat_id}/"),
+    )
+    .await
+}
+
+/// The checkpoint for one turn, if it was taken.
+pub async fn find_checkpoint(
+    workspace_path: &Path,
+    chat_id: &str,
+    turn_id: &str,
+) -> Result<Option<Checkpoint>, GitError> {
+    let ref_name = checkpoint_ref(chat_id, turn_id)?;
+    Ok(read_checkpoints(workspace_path, &ref_name)
+        .await?
+        .into_iter()
+        .next())
+}
+
+/// Diff a checkpoint against the workspace as it is now (including
+/// uncommitted and untracked files).
+pub async fn diff_checkpoint(
+    workspace_path: &Path,
+    checkpoint: &Checkpoint,
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, GitError> {
+    let current_tree = snapshot_tree(workspace_path).await?;
+    let checkpoint_tree = format!("{}^{{tree}}", checkpoint.commit);
+
+    let option_args = options.git_args();
+    let mut args = vec!["diff", "-M"];
+    args.extend(option_args.iter().map(String::as_str));
+    args.extend([checkpoint_tree.as_str(), current_tree.as_str()]);
+
+    let output = run_git(&args, workspace_path).await?;
+    if !output.success {
+        return Err(GitError::GitFailed {
+            stderr: output.stderr_str(),
+            stdout: output.stdout_str(),
+        });
+    }
+    Ok(parse_unified_diff(&output.stdout_str()))
+}
+
+/// Put the workspace back the way it was when `checkpoint` was taken.
+///
+/// Returns the commit of the pre-restore snapshot, also stored under
+/// `refs/overseer/checkpoint-undo/<chat_id>`.
+///
+/// If the agent committed since the checkpoint, the branch is reset to the
+/// checkpoint's HEAD; the newer commits stay reachable from the undo
+/// snapshot and the reflog.
+pub async fn restore_checkpoint(
+    workspace_path: &Path,
+    checkpoint: &Checkpoint,
+) -> Result<String, GitError> {
+    let undo_ref = format!(
+        "{CHECKPOINT_UNDO_REF_PREFIX}/{}",
+        validate_ref_component(&checkpoint.chat_id)?
+    );
+
+    // Keep the current state so the restore itself can be undone.
+    let head = current_head(workspace_path).await?;
+    let current_tree = snapshot_tree(workspace_path).await?;
+    let message = format!(
+        "Overseer checkpoint undo\n\nChat: {}\nRestored turn: {}\n",
+        checkpoint.chat_id, checkpoint.turn_id
+    );
+    let undo = commit_tree(workspace_path, &current_tree, head.as_deref(), &message).await?;
+    run_git_success(&["update-ref", &undo_ref, &undo], workspace_path).await?;
+
+    // Files created since the checkpoint have nothing to be restored to.
+    let checkpoint_tree = format!("{}^{{tree}}", checkpoint.commit);
+    let created = run_git_success(
+        &[
+            "diff",
+            "--name-only",
+            "-z",
+            "--no-renames",
+            "--diff-filter=A",
+            &checkpoint_tree,
+            &current_tree,
+        ],
+        workspace_path,
+    )
+    .await?;
+    for path in created.split('\0').filter(|p| !p.is_empty()) {
+        match std::fs::remove_file(workspace_path.join(path)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(GitError::CommandFailed(e)),
+        }
+    }
+
+    if let Some(checkpoint_head) = &checkpoint.head {
+        run_git_success(&["reset", "-q", "--mixed", checkpoint_head], workspace_path).await?;
+    }
+
+    let index = TempIndex::new(workspace_path).await?;
+    let env = [("GIT_INDEX_FILE", index.path_str()?)];
+    run_git_checked(&["read-tree", &checkpoint.commit], workspace_path, &env).await?;
+    run_git_checked(&["checkout-index", "-a", "-f"], workspace_path, &env).await?;
+
+    Ok(undo)
+}
+
+/// Delete the given checkpoints' refs.
+pub async fn delete_checkpoints(
+    workspace_path: &Path,
+    checkpoints: &[Checkpoint],
+) -> Result<(), GitError> {
+    for checkpoint in checkpoints {
+        run_git_success(&["update-ref", "-d", &checkpoint.ref_name], workspace_path).await?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// `refs/overseer/checkpoints/<chat_id>/<turn_id>`, validating both parts.
+fn checkpoint_ref(chat_id: &str, turn_id: &str) -> Result<String, GitError> {
+    Ok(format!(
+        "{CHECKPOINT_REF_PREFIX}/{}/{}",
+        validate_ref_component(chat_id)?,
+        validate_ref_component(turn_id)?
+    ))
+}
+
+/// Chat and message ids are UUIDs; reject anything that could escape the
+/// ref namespace or isn't a valid ref name.
+fn validate_ref_component(component: &str) -> Result<&str, GitError> {
+    let valid = !component.is_empty()
+        && !component.starts_with('.')
+        && !component.ends_with(".lock")
+        && !component.contains("..")
+        && component
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(component)
+    } else {
+        Err(GitError::Other(format!(
+            "Invalid checkpoint id: {component:?}"
+        )))
+    }
+}
+
+/// Read checkpoints matching a `for-each-ref` pattern, oldest first.
+async fn read_checkpoints(
+    workspace_path: &Path,
+    pattern: &str,
+) -> Result<Vec<Checkpoint>, GitError> {
+    let stdout = run_git_success(
+        &[
+            "for-each-ref",
+            "--sort=refname",
+            "--format=%(refname)%00%(objectname)%00%(parent)%00%(creatordate:iso-strict)",
+            pattern,
+        ],
+        workspace_path,
+    )
+    .await?;
+    Ok(parse_checkpoint_refs(&stdout))
+}
+
+/// Parse `for-each-ref` output from [`read_checkpoints`].
+fn parse_checkpoint_refs(stdout: &str) -> Vec<Checkpoint> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let ref_name = fields.next()?;
+            let commit = fields.next()?;
+            let parent = fields.next()?;
+            let created_at = fields.next()?;
+
+            let rest = ref_name
+                .strip_prefix(CHECKPOINT_REF_PREFIX)?
+                .strip_prefix('/')?;
+            let (chat_id, turn_id) = rest.split_once('/')?;
+            Some(Checkpoint {
+                chat_id: chat_id.to_string(),
+                turn_id: turn_id.to_string(),
+                ref_name: ref_name.to_string(),
+                commit: commit.to_string(),
+                head: parent.split_whitespace().next().map(str::to_string),
+                created_at: created_at.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// HEAD's commit, or `None` on an unborn branch.
+async fn current_head(workspace_path: &Path) -> Result<Option<String>, GitError> {
+    let output = run_git(&["rev-parse", "--verify", "-q", "HEAD"], workspace_path).await?;
+    Ok(output
+        .success
+        .then(|| output.stdout_str().trim().to_string()))
+}
+
+/// Write the worktree (tracked + untracked, minus ignored) as a tree object.
+async fn snapshot_tree(workspace_path: &Path) -> Result<String, GitError> {
+    let index = TempIndex::new(workspace_path).await?;
+
+    // Starting from the real index lets `git add` skip unchanged files by
+    // their cached stat data instead of rehashing the whole tree.
+    let real_index = git_path(workspace_path, "index").await?;
+    if real_index.exists() {
+        std::fs::copy(&real_index, &index.path)?;
+    }
+
+    let env = [("GIT_INDEX_FILE", index.path_str()?)];
+    run_git_checked(&["add", "-A"], workspace_path, &env).await?;
+    let tree = run_git_checked(&["write-tree"], workspace_path, &env).await?;
+    Ok(tree)
+}
+
+/// `git commit-tree` with the checkpoint identity.
+async fn commit_tree(
+    workspace_path: &Path,
+    tree: &str,
+    parent: Option<&str>,
+    message: &str,
+) -> Result<String, GitError> {
+    let mut args = vec!["commit-tree", tree, "-m", message];
+    if let Some(parent) = parent {
+        args.extend(["-p", parent]);
+    }
+    run_git_checked(&args, workspace_path, &CHECKPOINT_IDENTITY).await
+}
+
+/// [`run_git_with_env`], returning trimmed stdout or a `GitFailed` error.
+async fn run_git_checked(
+    args: &[&str],
+    cwd: &Path,
+    env: &[(&str, &str)],
+) -> Result<String, GitError> {
+    let output = run_git_with_env(args, cwd, env).await?;
+    if output.success {
+        Ok(output.stdout_str().trim().to_string())
+    } else {
+        Err(GitError::GitFailed {
+            stderr: output.stderr_str().trim().to_string(),
+            stdout: output.stdout_str().trim().to_string(),
+        })
+    }
+}
+
+/// A scratch index file in the worktree's git dir, removed on drop.
+struct TempIndex {
+    path: PathBuf,
+}
+
+impl TempIndex {
+    async fn new(workspace_path: &Path) -> Result<Self, GitError> {
+        let name = format!("overseer-checkpoint-{}.index", uuid::Uuid::new_v4());
+        Ok(Self {
+            path: git_path(workspace_path, &name).await?,
+        })
+    }
+
+    fn path_str(&self) -> Result<&str, GitError> {
+        self.path
+            .to_str()
+            .ok_or_else(|| GitError::PathError(format!("Non-UTF-8 path: {:?}", self.path)))
+    }
+}
+
+impl Drop for TempIndex {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(path: &Path, args: &[&str]) -> String {
+        let out = Command::new("git")
+            .args(args)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .current_dir(path)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+
+    fn init_repo(path: &Path) {
+        git(path, &["init", "-b", "main"]);
+        git(path, &["config", "user.email", "test@test.com"]);
+        git(path, &["config", "user.name", "Test"]);
+        git(path, &["config", "commit.gpgsign", "false"]);
+        std::fs::write(path.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(path.join("a.txt"), "one\n").unwrap();
+        git(path, &["add", "."]);
+        git(path, &["commit", "-m", "init"]);
+    }
+
+    #[test]
+    fn rejects_ids_that_escape_the_namespace() {
+        assert!(checkpoint_ref("chat-1", "turn-1").is_ok());
+        assert!(checkpoint_ref("../heads", "main").is_err());
+        assert!(checkpoint_ref("chat", "a/b").is_err());
+        assert!(checkpoint_ref("chat", "").is_err());
+        assert!(checkpoint_ref("chat", "x.lock").is_err());
+    }
+
+    #[test]
+    fn parses_for_each_ref_output() {
+        let stdout = "refs/overseer/checkpoints/c1/t1\0abc\0def\x002026-01-01T10:00:00+00:00\n\
+                      refs/overseer/checkpoints/c1/t2\0ghi\0\x002026-01-01T10:05:00+00:00\n";
+        let checkpoints = parse_checkpoint_refs(stdout);
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].turn_id, "t1");
+        assert_eq!(checkpoints[0].head.as_deref(), Some("def"));
+        assert_eq!(checkpoints[1].head, None);
+    }
+
+    #[tokio::test]
+    async fn snapshot_includes_untracked_and_leaves_index_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        init_repo(path);
+
+        std::fs::write(path.join("a.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(path.join("new.txt"), "new\n").unwrap();
+        std::fs::create_dir(path.join("target")).unwrap();
+        std::fs::write(path.join("target/out"), "ignored\n").unwrap();
+
+        let checkpoint = create_checkpoint(path, "chat-1", "turn-1").await.unwrap();
+        assert_eq!(
+            checkpoint.ref_name,
+            "refs/overseer/checkpoints/chat-1/turn-1"
+        );
+        assert_eq!(
+            checkpoint.head.as_deref(),
+            Some(git(path, &["rev-parse", "HEAD"]).as_str())
+        );
+
+        let files = git(path, &["ls-tree", "--name-only", &checkpoint.commit]);
+        assert_eq!(files, ".gitignore\na.txt\nnew.txt");
+        // Nothing was staged in the real index.
+        assert_eq!(git(path, &["diff", "--cached", "--name-only"]), "");
+
+        let listed = list_checkpoints(path, "chat-1").await.unwrap();
+        assert_eq!(listed, vec![checkpoint]);
+    }
+
+    #[tokio::test]
+    async fn restore_undoes_edits_new_files_and_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        init_repo(path);
+        std::fs::write(path.join("wip.txt"), "work in progress\n").unwrap();
+        let checkpoint = create_checkpoint(path, "chat-1", "turn-1").await.unwrap();
+
+        // The "agent" edits, deletes, creates and commits.
+        std::fs::write(path.join("a.txt"), "trashed\n").unwrap();
+        std::fs::remove_file(path.join("wip.txt")).unwrap();
+        std::fs::write(path.join("junk.txt"), "junk\n").unwrap();
+        git(path, &["add", "-A"]);
+        git(path, &["commit", "-m", "agent commit"]);
+        std::fs::write(path.join("more.txt"), "more\n").unwrap();
+
+        let diff = diff_checkpoint(path, &checkpoint, &DiffOptions::default())
+            .await
+            .unwrap();
+        let mut changed: Vec<_> = diff
+            .iter()
+            .map(|f| (f.status.as_str(), f.path().to_string()))
+            .collect();
+        changed.sort();
+        assert_eq!(
+            changed,
+            vec![
+                ("A", "junk.txt".to_string()),
+                ("A", "more.txt".to_string()),
+                ("D", "wip.txt".to_string()),
+                ("M", "a.txt".to_string()),
+            ]
+        );
+
+        let undo = restore_checkpoint(path, &checkpoint).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(path.join("a.txt")).unwrap(),
+            "one\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(path.join("wip.txt")).unwrap(),
+            "work in progress\n"
+        );
+        assert!(!path.join("junk.txt").exists());
+        assert!(!path.join("more.txt").exists());
+        assert_eq!(Some(git(path, &["rev-parse", "HEAD"])), checkpoint.head);
+        // wip.txt is untracked again, as it was.
+        assert_eq!(git(path, &["status", "--porcelain"]), "?? wip.txt");
+        assert_eq!(
+            git(path, &["rev-parse", "refs/overseer/checkpoint-undo/chat-1"]),
+            undo
+        );
+
+        delete_checkpoints(path, &[checkpoint]).await.unwrap();
+        assert!(list_checkpoints(path, "chat-1").await.unwrap().is_empty());
+    }
+}
@@ -18,6 +18,10 @@
 //! - [`list_commit_files`] - Get files changed in a specific commit
 //! - [`get_commit_diff`] - Get the diff for a file in a specific commit
 //!
+//! Each diff function has a `get_structured_*` counterpart that takes
+//! [`DiffOptions`] (whitespace handling, context size) and returns the diff
+//! parsed into files, hunks and lines (see [`patch`](super::patch)).
+//!
 //! # File Status Codes
 //!
 //! Files are tagged with a status code from git:
@@ -28,12 +32,22 @@
 //! | `M`  | Modified|
 //! | `D`  | Deleted |
 //! | `R`  | Renamed |
+//! | `C`  | Copied  |
 //! | `?`  | Untracked (not in git) |
+//!
+//! # Line Statistics
+//!
+//! Every [`ChangedFile`] carries added/removed line counts from
+//! `git diff --numstat`, whether git treats it as binary, and the source path
+//! of a rename or copy. Branch and uncommitted changes are read with a single
+//! `git diff --raw --numstat -z` call each (see [`parse_diff_raw_numstat`]).
+//! Untracked files aren't known to git, so their lines are counted directly.
 
+use super::patch::{parse_unified_diff, FileDiff};
 use super::{
     get_current_branch, is_default_branch_name, resolve_default_branch, run_git, GitError,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 // ============================================================================
@@ -42,27 +56,67 @@ use std::path::Path;
 
 /// A file that has been changed.
 ///
-/// Represents a single file change with its status and path.
-#[derive(Debug, Clone, Serialize)]
+/// Represents a single file change with its status, path and line counts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ChangedFile {
-    /// Status code: A (added), M (modified), D (deleted), R (renamed), ? (untracked)
+    /// Status code: A (added), M (modified), D (deleted), R (renamed), C (copied), ? (untracked)
     pub status: String,
 
     /// Path to the file, relative to the workspace root
     pub path: String,
+
+    /// Path the file was renamed or copied from (R and C only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+
+    /// Lines added, or `None` for binary files and when unknown
+    pub additions: Option<u64>,
+
+    /// Lines removed, or `None` for binary files and when unknown
+    pub deletions: Option<u64>,
+
+    /// True if git treats the file as binary
+    pub is_binary: bool,
+}
+
+impl ChangedFile {
+    /// A changed file with no line statistics.
+    pub fn new(status: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            status: status.into(),
+            path: path.into(),
+            ..Default::default()
+        }
+    }
 }
 
 /// A commit on the branch.
 ///
-/// Represents a single commit with its short SHA and message.
-#[derive(Debug, Clone, Serialize)]
+/// Represents a single commit with its SHA, author, date and message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Commit {
     /// Short commit SHA (7 characters)
     pub short_id: String,
 
+    /// Full commit SHA
+    pub id: String,
+
     /// First line of the commit message
     pub message: String,
+
+    /// The whole commit message, subject included
+    pub full_message: String,
+
+    /// Author name
+    pub author_name: String,
+
+    /// Author email
+    pub author_email: String,
+
+    /// Author date, RFC 3339 (e.g. `2026-01-01T10:00:00+01:00`)
+    pub date: String,
 }
 
 /// Result of listing changed files.
@@ -122,7 +176,53 @@ impl SubmoduleResult {
     pub fn total_file_count(&self) -> usize {
         self.files.len()
             + self.uncommitted.len()
-            + self.submodules.iter().map(|s| s.total_file_count()).sum::<usize>()
+            + self
+                .submodules
+                .iter()
+                .map(|s| s.total_file_count())
+                .sum::<usize>()
+    }
+}
+
+/// How a diff treats whitespace-only changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WhitespaceMode {
+    /// Show every change (git's default)
+    #[default]
+    Show,
+    /// Ignore changes at end of line (`--ignore-space-at-eol`)
+    IgnoreAtEol,
+    /// Ignore changes in the amount of whitespace (`-b`)
+    IgnoreChange,
+    /// Ignore all whitespace (`-w`)
+    IgnoreAll,
+}
+
+/// Options for the structured diff functions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiffOptions {
+    /// How to treat whitespace-only changes
+    pub whitespace: WhitespaceMode,
+    /// Lines of context around each change (git's default of 3 when `None`)
+    pub context_lines: Option<u32>,
+}
+
+impl DiffOptions {
+    /// Extra `git diff` arguments for these options.
+    pub(crate) fn git_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        match self.whitespace {
+            WhitespaceMode::Show => {}
+            WhitespaceMode::IgnoreAtEol => args.push("--ignore-space-at-eol".to_string()),
+            WhitespaceMode::IgnoreChange => args.push("-b".to_string()),
+            WhitespaceMode::IgnoreAll => args.push("-w".to_string()),
+        }
+        if let Some(lines) = self.context_lines {
+            args.push(format!("-U{lines}"));
+        }
+        args
     }
 }
 
@@ -137,8 +237,12 @@ impl SubmoduleResult {
 /// M\tpath/to/modified.rs
 /// A\tpath/to/added.rs
 /// D\tpath/to/deleted.rs
+/// R100\told/path.rs\tnew/path.rs
 /// ```
 ///
+/// Renames and copies carry both paths; the second becomes `path` and the
+/// first `old_path`. No line statistics are filled in.
+///
 /// # Arguments
 ///
 /// * `stdout` - The stdout from a `git diff --name-status` command
@@ -153,17 +257,112 @@ pub fn parse_diff_name_status(stdout: &str) -> Vec<ChangedFile> {
         // Split on tab: "M\tpath/to/file"
         let parts: Vec<&str> = line.splitn(2, '\t').collect();
         if parts.len() == 2 {
-            files.push(ChangedFile {
-                // Take first character of status (handles R100, C50, etc.)
-                status: parts[0].chars().next().unwrap_or('?').to_string(),
-                path: parts[1].to_string(),
-            });
+            // Take first character of status (handles R100, C50, etc.)
+            let status = parts[0].chars().next().unwrap_or('?').to_string();
+            let file = match parts[1].split_once('\t') {
+                Some((old, new)) if status == "R" || status == "C" => ChangedFile {
+                    old_path: Some(old.to_string()),
+                    ..ChangedFile::new(status, new)
+                },
+                _ => ChangedFile::new(status, parts[1]),
+            };
+            files.push(file);
+        }
+    }
+
+    files
+}
+
+/// Parse the output of `git diff --raw --numstat -z` (or `git diff-tree`
+/// with the same flags).
+///
+/// With `-z`, fields are NUL-separated and paths are never quoted. All raw
+/// records come first, then one numstat record per raw record, in the same
+/// order:
+///
+/// ```text
+/// :100644 100644 <sha> <sha> M\0src/lib.rs\0
+/// :100644 100644 <sha> <sha> R087\0old.rs\0new.rs\0
+/// 3\t1\tsrc/lib.rs\0
+/// 2\t2\t\0old.rs\0new.rs\0
+/// ```
+///
+/// Binary files show `-` for both counts in the numstat record.
+pub fn parse_diff_raw_numstat(stdout: &str) -> Vec<ChangedFile> {
+    let mut files: Vec<ChangedFile> = Vec::new();
+    let mut stats_seen = 0;
+    let mut tokens = stdout.split('\0');
+
+    while let Some(token) = tokens.next() {
+        if let Some(raw) = token.strip_prefix(':') {
+            // ":<modes> <shas> <status>" — status is the last field
+            let status_field = raw.rsplit(' ').next().unwrap_or_default();
+            let status = status_field.chars().next().unwrap_or('?').to_string();
+            let Some(path) = tokens.next() else { break };
+
+            let file = if status == "R" || status == "C" {
+                let Some(new_path) = tokens.next() else { break };
+                ChangedFile {
+                    old_path: Some(path.to_string()),
+                    ..ChangedFile::new(status, new_path)
+                }
+            } else {
+                ChangedFile::new(status, path)
+            };
+            files.push(file);
+        } else if let Some((added, rest)) = token.split_once('\t') {
+            let (deleted, path) = rest.split_once('\t').unwrap_or((rest, ""));
+            if path.is_empty() {
+                // Rename/copy: the two paths follow as separate tokens
+                tokens.next();
+                tokens.next();
+            }
+
+            if let Some(file) = files.get_mut(stats_seen) {
+                file.additions = added.parse().ok();
+                file.deletions = deleted.parse().ok();
+                file.is_binary = added == "-" && deleted == "-";
+            }
+            stats_seen += 1;
         }
     }
 
     files
 }
 
+/// Largest untracked file whose lines are counted; bigger files get no stats.
+const MAX_UNTRACKED_STAT_BYTES: u64 = 1024 * 1024;
+
+/// Line statistics for an untracked file, read straight from disk.
+///
+/// Uses git's heuristic for binary detection: a NUL byte in the first 8000
+/// bytes.
+pub(super) fn untracked_file(workspace_path: &Path, path: &str) -> ChangedFile {
+    let mut file = ChangedFile::new("?", path);
+    let full_path = workspace_path.join(path);
+
+    let too_big = std::fs::metadata(&full_path)
+        .map(|m| m.len() > MAX_UNTRACKED_STAT_BYTES)
+        .unwrap_or(true);
+    if too_big {
+        return file;
+    }
+    let Ok(bytes) = std::fs::read(&full_path) else {
+        return file;
+    };
+
+    if bytes.iter().take(8000).any(|b| *b == 0) {
+        file.is_binary = true;
+        return file;
+    }
+
+    let newlines = bytes.iter().filter(|b| **b == b'\n').count() as u64;
+    let unterminated = u64::from(!bytes.is_empty() && !bytes.ends_with(b"\n"));
+    file.additions = Some(newlines + unterminated);
+    file.deletions = Some(0);
+    file
+}
+
 /// Parse the output of `git config --file .gitmodules --get-regexp path`.
 ///
 /// The format is:
@@ -228,9 +427,7 @@ async fn list_submodules(workspace_path: &Path) -> Vec<(String, String)> {
     .await;
 
     match output {
-        Ok(result) if result.success => {
-            parse_submodules(&String::from_utf8_lossy(&result.stdout))
-        }
+        Ok(result) if result.success => parse_submodules(&String::from_utf8_lossy(&result.stdout)),
         _ => Vec::new(),
     }
 }
@@ -357,10 +554,13 @@ async fn list_changed_files_internal(
         submodule_list.iter().map(|(_, p)| p.as_str()).collect();
 
     // === Uncommitted changes (staged + unstaged against HEAD) ===
-    let uncommitted_output = run_git(&["diff", "--name-status", "HEAD"], workspace_path).await?;
-    let all_uncommitted = parse_diff_name_status(&String::from_utf8_lossy(
-        &uncommitted_output.stdout,
-    ));
+    let uncommitted_output = run_git(
+        &["diff", "--raw", "--numstat", "-z", "-M", "HEAD"],
+        workspace_path,
+    )
+    .await?;
+    let all_uncommitted =
+        parse_diff_raw_numstat(&String::from_utf8_lossy(&uncommitted_output.stdout));
 
     // Filter out submodule entries
     uncommitted.extend(
@@ -380,10 +580,7 @@ async fn list_changed_files_internal(
     for line in untracked_stdout.lines() {
         let trimmed = line.trim();
         if !trimmed.is_empty() && !submodule_paths.contains(trimmed) {
-            uncommitted.push(ChangedFile {
-                status: "?".to_string(),
-                path: trimmed.to_string(),
-            });
+            uncommitted.push(untracked_file(workspace_path, trimmed));
         }
     }
 
@@ -408,12 +605,12 @@ async fn list_changed_files_internal(
 
             // Diff from merge-base to HEAD (committed changes only)
             let output = run_git(
-                &["diff", "--name-status", &base_ref, "HEAD"],
+                &["diff", "--raw", "--numstat", "-z", "-M", &base_ref, "HEAD"],
                 workspace_path,
             )
             .await?;
 
-            let all_files = parse_diff_name_status(&String::from_utf8_lossy(&output.stdout));
+            let all_files = parse_diff_raw_numstat(&String::from_utf8_lossy(&output.stdout));
 
             // Filter out submodule entries
             files.extend(
@@ -478,10 +675,47 @@ pub async fn get_file_diff(
     file_status: &str,
     main_branch: Option<&str>,
 ) -> Result<String, GitError> {
+    file_diff_text(
+        workspace_path,
+        file_path,
+        file_status,
+        main_branch,
+        &DiffOptions::default(),
+    )
+    .await
+}
+
+/// [`get_file_diff`], parsed.
+///
+/// Honors `options` for whitespace handling and context size.
+pub async fn get_structured_file_diff(
+    workspace_path: &Path,
+    file_path: &str,
+    file_status: &str,
+    main_branch: Option<&str>,
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, GitError> {
+    let text = file_diff_text(workspace_path, file_path, file_status, main_branch, options).await?;
+    Ok(parse_unified_diff(&text))
+}
+
+async fn file_diff_text(
+    workspace_path: &Path,
+    file_path: &str,
+    file_status: &str,
+    main_branch: Option<&str>,
+    options: &DiffOptions,
+) -> Result<String, GitError> {
+    let option_args = options.git_args();
+
     // Untracked and newly added files: diff against /dev/null
     if file_status == "?" || file_status == "A" {
         let output = run_git(
-            &["diff", "--no-index", "/dev/null", file_path],
+            &diff_args(
+                &["diff", "--no-index"],
+                &option_args,
+                &["/dev/null", file_path],
+            ),
             workspace_path,
         )
         .await?;
@@ -512,7 +746,11 @@ pub async fn get_file_diff(
         }
     };
 
-    let output = run_git(&["diff", &base_ref, "--", file_path], workspace_path).await?;
+    let output = run_git(
+        &diff_args(&["diff"], &option_args, &[&base_ref, "--", file_path]),
+        workspace_path,
+    )
+    .await?;
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
@@ -540,10 +778,44 @@ pub async fn get_uncommitted_diff(
     file_path: &str,
     file_status: &str,
 ) -> Result<String, GitError> {
+    uncommitted_diff_text(
+        workspace_path,
+        file_path,
+        file_status,
+        &DiffOptions::default(),
+    )
+    .await
+}
+
+/// [`get_uncommitted_diff`], parsed.
+///
+/// Honors `options` for whitespace handling and context size.
+pub async fn get_structured_uncommitted_diff(
+    workspace_path: &Path,
+    file_path: &str,
+    file_status: &str,
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, GitError> {
+    let text = uncommitted_diff_text(workspace_path, file_path, file_status, options).await?;
+    Ok(parse_unified_diff(&text))
+}
+
+async fn uncommitted_diff_text(
+    workspace_path: &Path,
+    file_path: &str,
+    file_status: &str,
+    options: &DiffOptions,
+) -> Result<String, GitError> {
+    let option_args = options.git_args();
+
     // Untracked files: diff against /dev/null
     if file_status == "?" {
         let output = run_git(
-            &["diff", "--no-index", "/dev/null", file_path],
+            &diff_args(
+                &["diff", "--no-index"],
+                &option_args,
+                &["/dev/null", file_path],
+            ),
             workspace_path,
         )
         .await?;
@@ -553,11 +825,24 @@ pub async fn get_uncommitted_diff(
     }
 
     // Diff against HEAD for uncommitted changes
-    let output = run_git(&["diff", "HEAD", "--", file_path], workspace_path).await?;
+    let output = run_git(
+        &diff_args(&["diff"], &option_args, &["HEAD", "--", file_path]),
+        workspace_path,
+    )
+    .await?;
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// `head`, then the option flags, then `tail`, as one argument list.
+fn diff_args<'a>(head: &[&'a str], options: &'a [String], tail: &[&'a str]) -> Vec<&'a str> {
+    head.iter()
+        .copied()
+        .chain(options.iter().map(String::as_str))
+        .chain(tail.iter().copied())
+        .collect()
+}
+
 // ============================================================================
 // SUBMODULE DIFFS
 // ============================================================================
@@ -625,7 +910,7 @@ pub async fn get_submodule_uncommitted_diff(
 ///
 /// # Returns
 ///
-/// A vector of `Commit` with short SHA and message.
+/// A vector of `Commit` with SHA, author, date and message.
 pub async fn list_commits_on_branch(
     workspace_path: &Path,
     main_branch: Option<&str>,
@@ -651,10 +936,9 @@ pub async fn list_commits_on_branch(
         .to_string();
 
     // Get commits from merge-base to HEAD, oldest first
-    // Format: short-sha|commit message (first line)
     let range = format!("{}..HEAD", base_ref);
     let output = run_git(
-        &["log", "--pretty=format:%h|%s", "--reverse", &range],
+        &["log", COMMIT_LOG_FORMAT, "--reverse", &range],
         workspace_path,
     )
     .await?;
@@ -669,21 +953,33 @@ pub async fn list_commits_on_branch(
     Ok(commits)
 }
 
-/// Parse the output of `git log --pretty=format:"%h|%s"`.
+/// `git log` format for [`parse_commit_log`]: NUL-separated fields, one
+/// record per commit terminated by an ASCII record separator (0x1e), since
+/// the full message may contain anything else.
+const COMMIT_LOG_FORMAT: &str = "--pretty=format:%h%x00%H%x00%an%x00%ae%x00%aI%x00%s%x00%B%x1e";
+
+/// Parse the output of `git log` with [`COMMIT_LOG_FORMAT`].
 ///
-/// The format is:
+/// Each record is:
 /// ```text
-/// abc1234|First commit message
-/// def5678|Second commit message
+/// short-sha\0sha\0author name\0author email\0date\0subject\0full message\x1e
 /// ```
 fn parse_commit_log(stdout: &str) -> Vec<Commit> {
     let mut commits = Vec::new();
 
-    for line in stdout.lines() {
-        if let Some((short_id, message)) = line.split_once('|') {
+    for record in stdout.split('\x1e') {
+        // git puts a newline between records
+        let record = record.trim_start_matches('\n');
+        let fields: Vec<&str> = record.splitn(7, '\0').collect();
+        if let [short_id, id, author_name, author_email, date, message, full_message] = fields[..] {
             commits.push(Commit {
                 short_id: short_id.to_string(),
+                id: id.to_string(),
                 message: message.to_string(),
+                full_message: full_message.trim_end().to_string(),
+                author_name: author_name.to_string(),
+                author_email: author_email.to_string(),
+                date: date.to_string(),
             });
         }
     }
@@ -700,14 +996,23 @@ fn parse_commit_log(stdout: &str) -> Vec<Commit> {
 ///
 /// # Returns
 ///
-/// A vector of `ChangedFile` with status and path.
+/// A vector of `ChangedFile` with status, path and line counts.
 pub async fn list_commit_files(
     workspace_path: &Path,
     commit_sha: &str,
 ) -> Result<Vec<ChangedFile>, GitError> {
     // Use diff-tree to get files changed in this commit
     let output = run_git(
-        &["diff-tree", "--no-commit-id", "--name-status", "-r", commit_sha],
+        &[
+            "diff-tree",
+            "--no-commit-id",
+            "--raw",
+            "--numstat",
+            "-z",
+            "-M",
+            "-r",
+            commit_sha,
+        ],
         workspace_path,
     )
     .await?;
@@ -720,7 +1025,7 @@ pub async fn list_commit_files(
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let files = parse_diff_name_status(&stdout);
+    let files = parse_diff_raw_numstat(&stdout);
 
     Ok(files)
 }
@@ -745,11 +1050,49 @@ pub async fn get_commit_diff(
     file_path: &str,
     file_status: &str,
 ) -> Result<String, GitError> {
+    commit_diff_text(
+        workspace_path,
+        commit_sha,
+        file_path,
+        file_status,
+        &DiffOptions::default(),
+    )
+    .await
+}
+
+/// [`get_commit_diff`], parsed.
+///
+/// Honors `options` for whitespace handling and context size.
+pub async fn get_structured_commit_diff(
+    workspace_path: &Path,
+    commit_sha: &str,
+    file_path: &str,
+    file_status: &str,
+    options: &DiffOptions,
+) -> Result<Vec<FileDiff>, GitError> {
+    let text =
+        commit_diff_text(workspace_path, commit_sha, file_path, file_status, options).await?;
+    Ok(parse_unified_diff(&text))
+}
+
+async fn commit_diff_text(
+    workspace_path: &Path,
+    commit_sha: &str,
+    file_path: &str,
+    file_status: &str,
+    options: &DiffOptions,
+) -> Result<String, GitError> {
+    let option_args = options.git_args();
+
     // For added files, show full content as diff
     if file_status == "A" {
         // Show the file as added (diff from empty)
         let output = run_git(
-            &["show", "--format=", commit_sha, "--", file_path],
+            &diff_args(
+                &["show", "--format="],
+                &option_args,
+                &[commit_sha, "--", file_path],
+            ),
             workspace_path,
         )
         .await?;
@@ -762,7 +1105,11 @@ pub async fn get_commit_diff(
         // Show the diff for the deleted file
         let parent = format!("{}^", commit_sha);
         let output = run_git(
-            &["diff", &parent, commit_sha, "--", file_path],
+            &diff_args(
+                &["diff"],
+                &option_args,
+                &[&parent, commit_sha, "--", file_path],
+            ),
             workspace_path,
         )
         .await?;
@@ -772,7 +1119,11 @@ pub async fn get_commit_diff(
 
     // For modified/other files, show the diff introduced by this commit
     let output = run_git(
-        &["show", "--format=", commit_sha, "--", file_path],
+        &diff_args(
+            &["show", "--format="],
+            &option_args,
+            &[commit_sha, "--", file_path],
+        ),
         workspace_path,
     )
     .await?;
@@ -811,16 +1162,15 @@ mod tests {
 
     #[test]
     fn parse_diff_name_status_rename() {
-        // Rename shows as R100 (100% match) or similar
+        // Rename shows as R100 (100% match) or similar, with both paths
         let output = "R100\told/path.rs\tnew/path.rs";
         let files = parse_diff_name_status(output);
 
         // Our parser takes the first character, so R100 becomes R
-        // The path will be "old/path.rs\tnew/path.rs" due to splitn(2)
-        // This is a simplification - for accurate rename handling,
-        // we'd need to use -M flag and parse differently
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].status, "R");
+        assert_eq!(files[0].path, "new/path.rs");
+        assert_eq!(files[0].old_path.as_deref(), Some("old/path.rs"));
     }
 
     #[test]
@@ -840,20 +1190,75 @@ mod tests {
         assert_eq!(files[0].path, "path/with spaces/file.rs");
     }
 
+    #[test]
+    fn parse_diff_raw_numstat_matches_stats_to_files() {
+        let output = concat!(
+            ":100644 100644 aaa bbb M\0src/lib.rs\0",
+            ":000000 100644 000 ccc A\0docs/new file.md\0",
+            ":100644 100644 ddd eee R087\0old.rs\0new.rs\0",
+            ":100644 100644 fff 111 M\0logo.png\0",
+            "3\t1\tsrc/lib.rs\0",
+            "10\t0\tdocs/new file.md\0",
+            "2\t2\t\0old.rs\0new.rs\0",
+            "-\t-\tlogo.png\0",
+        );
+        let files = parse_diff_raw_numstat(output);
+
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[0].path, "src/lib.rs");
+        assert_eq!((files[0].additions, files[0].deletions), (Some(3), Some(1)));
+
+        assert_eq!(files[1].status, "A");
+        assert_eq!(files[1].path, "docs/new file.md");
+        assert_eq!(files[1].additions, Some(10));
+
+        assert_eq!(files[2].status, "R");
+        assert_eq!(files[2].path, "new.rs");
+        assert_eq!(files[2].old_path.as_deref(), Some("old.rs"));
+        assert_eq!((files[2].additions, files[2].deletions), (Some(2), Some(2)));
+
+        assert!(files[3].is_binary);
+        assert_eq!(files[3].additions, None);
+        assert!(!files[0].is_binary);
+    }
+
+    #[test]
+    fn parse_diff_raw_numstat_empty() {
+        assert!(parse_diff_raw_numstat("").is_empty());
+    }
+
+    #[test]
+    fn untracked_file_counts_lines_and_detects_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "one\ntwo\nthree").unwrap();
+        std::fs::write(dir.path().join("blob.bin"), [0u8, 1, 2, 3]).unwrap();
+
+        let text = untracked_file(dir.path(), "notes.txt");
+        assert_eq!(text.status, "?");
+        assert_eq!((text.additions, text.deletions), (Some(3), Some(0)));
+
+        let binary = untracked_file(dir.path(), "blob.bin");
+        assert!(binary.is_binary);
+        assert_eq!(binary.additions, None);
+
+        let missing = untracked_file(dir.path(), "gone.txt");
+        assert_eq!(missing.additions, None);
+        assert!(!missing.is_binary);
+    }
+
     // ------------------------------------------------------------------------
     // Type Tests
     // ------------------------------------------------------------------------
 
     #[test]
     fn changed_file_serializes() {
-        let file = ChangedFile {
-            status: "M".to_string(),
-            path: "src/lib.rs".to_string(),
-        };
+        let file = ChangedFile::new("M", "src/lib.rs");
 
         let json = serde_json::to_string(&file).unwrap();
         assert!(json.contains("\"status\":\"M\""));
         assert!(json.contains("src/lib.rs"));
+        assert!(json.contains("\"isBinary\":false"));
+        assert!(!json.contains("oldPath"));
     }
 
     #[test]
@@ -878,22 +1283,10 @@ mod tests {
     #[test]
     fn uncommitted_sorting_tracked_before_untracked() {
         let mut files = vec![
-            ChangedFile {
-                status: "?".to_string(),
-                path: "aaa.txt".to_string(),
-            },
-            ChangedFile {
-                status: "M".to_string(),
-                path: "zzz.txt".to_string(),
-            },
-            ChangedFile {
-                status: "?".to_string(),
-                path: "bbb.txt".to_string(),
-            },
-            ChangedFile {
-                status: "A".to_string(),
-                path: "ccc.txt".to_string(),
-            },
+            ChangedFile::new("?", "aaa.txt"),
+            ChangedFile::new("M", "zzz.txt"),
+            ChangedFile::new("?", "bbb.txt"),
+            ChangedFile::new("A", "ccc.txt"),
         ];
 
         // Sort like list_changed_files does
@@ -915,25 +1308,44 @@ mod tests {
     // Commit Parsing Tests
     // ------------------------------------------------------------------------
 
+    fn log_record(short_id: &str, subject: &str, body: &str) -> String {
+        format!(
+            "{short_id}\0{short_id}000\0Ada Lovelace\0ada@example.com\02026-01-01T10:00:00+00:00\0{subject}\0{body}\x1e"
+        )
+    }
+
     #[test]
     fn parse_commit_log_basic() {
-        let output = "abc1234|First commit\ndef5678|Second commit";
-        let commits = parse_commit_log(output);
+        let output = format!(
+            "{}\n{}",
+            log_record("abc1234", "First commit", "First commit\n"),
+            log_record(
+                "def5678",
+                "Second commit",
+                "Second commit\n\nWith a body.\n"
+            )
+        );
+        let commits = parse_commit_log(&output);
 
         assert_eq!(commits.len(), 2);
 
         assert_eq!(commits[0].short_id, "abc1234");
+        assert_eq!(commits[0].id, "abc1234000");
         assert_eq!(commits[0].message, "First commit");
+        assert_eq!(commits[0].author_name, "Ada Lovelace");
+        assert_eq!(commits[0].author_email, "ada@example.com");
+        assert_eq!(commits[0].date, "2026-01-01T10:00:00+00:00");
 
         assert_eq!(commits[1].short_id, "def5678");
         assert_eq!(commits[1].message, "Second commit");
+        assert_eq!(commits[1].full_message, "Second commit\n\nWith a body.");
     }
 
     #[test]
     fn parse_commit_log_with_pipe_in_message() {
         // Commit message containing a pipe should still work
-        let output = "abc1234|Fix bug | add test";
-        let commits = parse_commit_log(output);
+        let output = log_record("abc1234", "Fix bug | add test", "Fix bug | add test");
+        let commits = parse_commit_log(&output);
 
         assert_eq!(commits.len(), 1);
         assert_eq!(commits[0].short_id, "abc1234");
@@ -953,6 +1365,8 @@ mod tests {
         let commit = Commit {
             short_id: "abc1234".to_string(),
             message: "Test commit".to_string(),
+            author_name: "Ada".to_string(),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&commit).unwrap();
@@ -960,6 +1374,8 @@ mod tests {
         assert!(json.contains("shortId"));
         assert!(json.contains("\"abc1234\""));
         assert!(json.contains("Test commit"));
+        assert!(json.contains("authorName"));
+        assert!(json.contains("fullMessage"));
     }
 
     // ------------------------------------------------------------------------
@@ -1047,10 +1463,7 @@ mod tests {
             name: "test".to_string(),
             path: "lib/test".to_string(),
             is_initialized: true,
-            files: vec![ChangedFile {
-                status: "M".to_string(),
-                path: "src/lib.rs".to_string(),
-            }],
+            files: vec![ChangedFile::new("M", "src/lib.rs")],
             uncommitted: vec![],
             submodules: vec![],
         };
@@ -1065,10 +1478,7 @@ mod tests {
             path: "lib/test".to_string(),
             is_initialized: true,
             files: vec![],
-            uncommitted: vec![ChangedFile {
-                status: "A".to_string(),
-                path: "new.rs".to_string(),
-            }],
+            uncommitted: vec![ChangedFile::new("A", "new.rs")],
             submodules: vec![],
         };
 
@@ -1081,10 +1491,7 @@ mod tests {
             name: "nested".to_string(),
             path: "lib/nested".to_string(),
             is_initialized: true,
-            files: vec![ChangedFile {
-                status: "M".to_string(),
-                path: "src/lib.rs".to_string(),
-            }],
+            files: vec![ChangedFile::new("M", "src/lib.rs")],
             uncommitted: vec![],
             submodules: vec![],
         };
@@ -1107,14 +1514,8 @@ mod tests {
             name: "nested".to_string(),
             path: "lib/nested".to_string(),
             is_initialized: true,
-            files: vec![ChangedFile {
-                status: "M".to_string(),
-                path: "src/lib.rs".to_string(),
-            }],
-            uncommitted: vec![ChangedFile {
-                status: "A".to_string(),
-                path: "new.rs".to_string(),
-            }],
+            files: vec![ChangedFile::new("M", "src/lib.rs")],
+            uncommitted: vec![ChangedFile::new("A", "new.rs")],
             submodules: vec![],
         };
 
@@ -1122,16 +1523,7 @@ mod tests {
             name: "parent".to_string(),
             path: "lib/parent".to_string(),
             is_initialized: true,
-            files: vec![
-                ChangedFile {
-                    status: "M".to_string(),
-                    path: "a.rs".to_string(),
-                },
-                ChangedFile {
-                    status: "M".to_string(),
-                    path: "b.rs".to_string(),
-                },
-            ],
+            files: vec![ChangedFile::new("M", "a.rs"), ChangedFile::new("M", "b.rs")],
             uncommitted: vec![],
             submodules: vec![nested],
         };
@@ -1140,6 +1532,108 @@ mod tests {
         assert_eq!(result.total_file_count(), 4);
     }
 
-    // Note: Full integration tests for list_changed_files, get_file_diff, etc.
-    // require a real git repository and are better suited for integration tests.
+    #[tokio::test]
+    async fn list_commits_and_files_with_stats() {
+        use std::process::Command;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        let git = |args: &[&str]| {
+            let out = Command::new("git")
+                .args(args)
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .current_dir(path)
+                .output()
+                .unwrap();
+            assert!(
+                out.status.success(),
+                "git {args:?} failed: {}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+        };
+
+        git(&["init", "-b", "main"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "commit.gpgsign", "false"]);
+        std::fs::write(path.join("a.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        git(&["checkout", "-b", "feature"]);
+        git(&["mv", "a.txt", "b.txt"]);
+        std::fs::write(path.join("b.txt"), "one\ntwo\nthree\nfour\nfive\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "Rename a to b\n\nAnd add a line."]);
+
+        let commits = list_commits_on_branch(path, Some("main")).await.unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].message, "Rename a to b");
+        assert_eq!(commits[0].full_message, "Rename a to b\n\nAnd add a line.");
+        assert_eq!(commits[0].author_email, "test@test.com");
+
+        let files = list_commit_files(path, &commits[0].id).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].status, "R");
+        assert_eq!(files[0].path, "b.txt");
+        assert_eq!(files[0].old_path.as_deref(), Some("a.txt"));
+        assert_eq!((files[0].additions, files[0].deletions), (Some(1), Some(0)));
+
+        let changes = list_changed_files(path, Some("main")).await.unwrap();
+        assert_eq!(changes.files, files);
+    }
+
+    #[tokio::test]
+    async fn structured_diff_honors_options() {
+        use std::process::Command;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        let git = |args: &[&str]| {
+            let out = Command::new("git")
+                .args(args)
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .current_dir(path)
+                .output()
+                .unwrap();
+            assert!(out.status.success(), "git {args:?} failed");
+        };
+
+        git(&["init", "-b", "main"]);
+        git(&["config", "user.email", "test@test.com"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "commit.gpgsign", "false"]);
+        let lines: Vec<String> = (1..=10).map(|n| format!("line {n}")).collect();
+        std::fs::write(path.join("a.txt"), lines.join("\n") + "\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        let mut edited = lines.clone();
+        edited[1] = "line  2".to_string();
+        edited[7] = "line eight".to_string();
+        std::fs::write(path.join("a.txt"), edited.join("\n") + "\n").unwrap();
+
+        let files = get_structured_uncommitted_diff(path, "a.txt", "M", &DiffOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].hunks.len(), 1);
+
+        let options = DiffOptions {
+            whitespace: WhitespaceMode::IgnoreChange,
+            context_lines: Some(0),
+        };
+        let files = get_structured_uncommitted_diff(path, "a.txt", "M", &options)
+            .await
+            .unwrap();
+        let hunk = &files[0].hunks[0];
+        assert_eq!(files[0].hunks.len(), 1);
+        assert_eq!((hunk.old_start, hunk.new_start), (8, 8));
+        assert_eq!(hunk.lines.len(), 2);
+        assert_eq!(hunk.lines[1].content, "line eight");
+        assert!(hunk.lines[1]
+            .segments
+            .iter()
+            .any(|s| s.changed && s.text == "eight"));
+    }
 }
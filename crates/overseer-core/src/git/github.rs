@@ -0,0 +1,941 @@
+//! GitHub pull request and issue operations via the `gh` CLI.
+//!
+//! # Overview
+//!
+//! This module queries GitHub for pull request information about a workspace:
+//!
+//! - [`get_pr_status`] - The PR opened from a branch (number, state, URL, draft)
+//! - [`list_pr_checks`] - CI check runs reported on a PR
+//! - [`list_review_comments`] - Inline review comments left on a PR's diff
+//! - [`list_review_threads`] - Review comment threads with their resolved state
+//! - [`list_review_prs`] - Open PRs where the current user's review is requested
+//! - [`get_pr_head`] - Where a PR's head branch lives (for checking it out)
+//! - [`get_issue`] - An issue's title and body
+//!
+//! # Why `gh` Instead of the REST API
+//!
+//! Same reasoning as using the `git` CLI: `gh` already holds the user's
+//! GitHub credentials (`gh auth login`, `GH_TOKEN`, enterprise hosts), and it
+//! resolves `{owner}/{repo}` from the repository's remotes. We never touch
+//! a token ourselves.
+//!
+//! # Runners
+//!
+//! All operations go through a [`GhRunner`]. Production code uses
+//! [`ShellGhRunner`], which runs `gh` inside the user's login shell so PATH
+//! and credentials match their terminal. Tests substitute a fake runner that
+//! returns canned output.
+//!
+//! The runner is synchronous (like [`ProcessSpawner`](crate::spawn::ProcessSpawner));
+//! async callers should wrap these calls in `spawn_blocking`.
+//!
+//! # Errors
+//!
+//! Unlike the old Tauri-side helpers, failures are not swallowed: a missing
+//! `gh` binary, an auth failure or unparseable output all come back as
+//! `GitError`. The only "expected" failure that maps to an empty result is
+//! "no pull request for this branch", which [`get_pr_status`] reports as `None`.
+
+use super::{GitError, GitOutput};
+use crate::shell::build_login_shell_command;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// ============================================================================
+// RUNNER
+// ============================================================================
+
+/// Runs `gh` subcommands.
+///
+/// Implementations must be `Send + Sync` so a runner can be shared with
+/// blocking tasks.
+pub trait GhRunner: Send + Sync {
+    /// Run `gh <args...>` in `cwd` and return its output.
+    ///
+    /// A non-zero exit status is **not** an error here; the caller inspects
+    /// `GitOutput::success` since some `gh` commands (e.g. `pr checks`) exit
+    /// non-zero while still printing valid JSON.
+    fn run(&self, args: &[String], cwd: &Path) -> Result<GitOutput, GitError>;
+}
+
+/// Default runner: executes `gh` in the user's login shell.
+#[derive(Debug, Clone, Default)]
+pub struct ShellGhRunner {
+    /// Optional shell prefix override (e.g. "/bin/zsh -l -c"), same as the
+    /// `agentShell` setting used for agents.
+    agent_shell: Option<String>,
+}
+
+impl ShellGhRunner {
+    /// Create a runner using the given shell prefix (or the default login shell).
+    pub fn new(agent_shell: Option<String>) -> Self {
+        Self { agent_shell }
+    }
+}
+
+impl GhRunner for ShellGhRunner {
+    fn run(&self, args: &[String], cwd: &Path) -> Result<GitOutput, GitError> {
+        let cwd = cwd.to_string_lossy();
+        let mut cmd =
+            build_login_shell_command("gh", args, Some(&cwd), self.agent_shell.as_deref())
+                .map_err(GitError::Other)?;
+
+        let output = cmd
+            .output()
+            .map_err(|e| GitError::Other(format!("Failed to run gh: {e}")))?;
+
+        Ok(GitOutput {
+            success: output.status.success(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+}
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// The pull request opened from a branch.
+///
+/// Field names stay snake_case on the wire: the frontend's `PrStatus` type
+/// predates the move into core.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrStatus {
+    /// PR number
+    pub number: u64,
+    /// "OPEN", "CLOSED" or "MERGED"
+    pub state: String,
+    /// Web URL of the PR
+    pub url: String,
+    /// Whether the PR is a draft
+    pub is_draft: bool,
+}
+
+/// A CI check run reported on a pull request.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrCheck {
+    /// Check name (e.g. "test (ubuntu-latest)")
+    pub name: String,
+    /// Raw state from GitHub (e.g. "SUCCESS", "FAILURE", "IN_PROGRESS")
+    pub state: String,
+    /// Normalized outcome: "pass", "fail", "pending", "skipping" or "cancel"
+    pub bucket: String,
+    /// Workflow the check belongs to, if any
+    pub workflow: Option<String>,
+    /// Link to the check's details page
+    pub link: Option<String>,
+    /// When the check started (ISO 8601)
+    pub started_at: Option<String>,
+    /// When the check completed (ISO 8601)
+    pub completed_at: Option<String>,
+}
+
+/// An inline review comment on a pull request's diff.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewComment {
+    /// GitHub comment id
+    pub id: u64,
+    /// File the comment is anchored to, relative to the repo root
+    pub path: String,
+    /// Line in the current diff, or `None` if the comment is outdated
+    pub line: Option<u64>,
+    /// Line the comment was originally left on
+    pub original_line: Option<u64>,
+    /// The diff hunk shown above the comment
+    pub diff_hunk: String,
+    /// Comment body (Markdown)
+    pub body: String,
+    /// Login of the comment author
+    pub author: String,
+    /// Creation time (ISO 8601)
+    pub created_at: String,
+    /// Web URL of the comment
+    pub url: String,
+    /// Id of the comment this one replies to, if it's part of a thread
+    pub in_reply_to_id: Option<u64>,
+}
+
+/// A review thread: a conversation anchored to one spot in a PR's diff.
+///
+/// Unlike [`ReviewComment`], threads carry GitHub's "resolved" state, which
+/// is only available through the GraphQL API.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewThread {
+    /// GraphQL node id of the thread (stable across fetches)
+    pub id: String,
+    /// File the thread is anchored to, relative to the repo root
+    pub path: String,
+    /// Line in the current diff, or `None` if the thread is outdated
+    pub line: Option<u64>,
+    /// Line the thread was originally started on
+    pub original_line: Option<u64>,
+    /// Whether someone marked the thread as resolved
+    pub is_resolved: bool,
+    /// Whether the code the thread was left on has since changed
+    pub is_outdated: bool,
+    /// The diff hunk shown above the first comment
+    pub diff_hunk: String,
+    /// Comments in the thread, oldest first
+    pub comments: Vec<ReviewThreadComment>,
+}
+
+/// One comment within a [`ReviewThread`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewThreadComment {
+    /// Login of the comment author
+    pub author: String,
+    /// Comment body (Markdown)
+    pub body: String,
+    /// Creation time (ISO 8601)
+    pub created_at: String,
+    /// Web URL of the comment
+    pub url: String,
+}
+
+/// An open pull request awaiting the current user's review.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewPr {
+    /// PR number
+    pub number: u64,
+    /// PR title
+    pub title: String,
+    /// Branch the PR was opened from
+    pub head_ref_name: String,
+    /// Login of the PR author
+    pub author_login: String,
+}
+
+/// The head of a pull request: what to fetch to check it out.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrHead {
+    /// PR number
+    pub number: u64,
+    /// PR title
+    pub title: String,
+    /// Web URL of the PR
+    pub url: String,
+    /// Branch the PR was opened from
+    pub head_ref_name: String,
+    /// Owner of the repository the head branch lives in
+    pub head_owner: String,
+    /// True if the head branch lives in a fork
+    pub is_cross_repository: bool,
+    /// "OPEN", "CLOSED" or "MERGED"
+    pub state: String,
+}
+
+/// A GitHub issue.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    /// Issue number
+    pub number: u64,
+    /// Issue title
+    pub title: String,
+    /// Issue body (markdown, may be empty)
+    pub body: String,
+    /// Web URL of the issue
+    pub url: String,
+}
+
+// ============================================================================
+// OPERATIONS
+// ============================================================================
+
+/// Get the pull request opened from `branch`.
+///
+/// Runs `gh pr view <branch> --json number,state,url,isDraft`.
+///
+/// # Returns
+///
+/// `Ok(None)` if the branch has no PR; an error for anything else `gh`
+/// complains about (not installed, not authenticated, not a GitHub remote).
+pub fn get_pr_status(
+    runner: &dyn GhRunner,
+    cwd: &Path,
+    branch: &str,
+) -> Result<Option<PrStatus>, GitError> {
+    let args = to_args(&["pr", "view", branch, "--json", "number,state,url,isDraft"]);
+    let output = runner.run(&args, cwd)?;
+
+    if !output.success {
+        let stderr = output.stderr_str();
+        if stderr.contains("no pull requests found") {
+            return Ok(None);
+        }
+        return Err(gh_failed("pr view", &output));
+    }
+
+    parse_pr_status(&output.stdout_str()).map(Some)
+}
+
+/// List the CI check runs on pull request `pr_number`.
+///
+/// Runs `gh pr checks <n> --json ...`. `gh` exits non-zero when any check
+/// failed or is still pending, so the JSON on stdout is trusted whenever it
+/// parses. A PR with no checks configured yields an empty list.
+pub fn list_pr_checks(
+    runner: &dyn GhRunner,
+    cwd: &Path,
+    pr_number: u64,
+) -> Result<Vec<PrCheck>, GitError> {
+    let number = pr_number.to_string();
+    let args = to_args(&[
+        "pr",
+        "checks",
+        &number,
+        "--json",
+        "name,state,bucket,workflow,link,startedAt,completedAt",
+    ]);
+    let output = runner.run(&args, cwd)?;
+
+    let stdout = output.stdout_str();
+    if !stdout.trim().is_empty() {
+        if let Ok(checks) = parse_pr_checks(&stdout) {
+            return Ok(checks);
+        }
+    }
+
+    if output.stderr_str().contains("no checks reported") {
+        return Ok(Vec::new());
+    }
+
+    if output.success {
+        return parse_pr_checks(&stdout);
+    }
+    Err(gh_failed("pr checks", &output))
+}
+
+/// List the inline review comments on pull request `pr_number`.
+///
+/// Uses the REST endpoint `repos/{owner}/{repo}/pulls/<n>/comments`; `gh`
+/// fills in owner and repo from the current repository. Paginated responses
+/// are concatenated.
+pub fn list_review_comments(
+    runner: &dyn GhRunner,
+    cwd: &Path,
+    pr_number: u64,
+) -> Result<Vec<ReviewComment>, GitError> {
+    let endpoint = format!("repos/{{owner}}/{{repo}}/pulls/{pr_number}/comments");
+    let args = to_args(&["api", &endpoint, "--paginate"]);
+    let output = runner.run(&args, cwd)?;
+
+    if !output.success {
+        return Err(gh_failed("api", &output));
+    }
+
+    parse_review_comments(&output.stdout_str())
+}
+
+/// GraphQL query for a PR's review threads. Owner and repo are filled in by
+/// `gh` from the current repository's remotes.
+const REVIEW_THREADS_QUERY: &str = r#"
+query($owner: String!, $repo: String!, $number: Int!) {
+  repository(owner: $owner, name: $repo) {
+    pullRequest(number: $number) {
+      reviewThreads(first: 100) {
+        nodes {
+          id
+          path
+          line
+          originalLine
+          isResolved
+          isOutdated
+          comments(first: 50) {
+            nodes { body createdAt url diffHunk author { login } }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+/// List the review threads on pull request `pr_number`, resolved or not.
+///
+/// Uses the GraphQL API since the REST comments endpoint has no notion of
+/// resolved threads. Fetches up to 100 threads of 50 comments each.
+pub fn list_review_threads(
+    runner: &dyn GhRunner,
+    cwd: &Path,
+    pr_number: u64,
+) -> Result<Vec<ReviewThread>, GitError> {
+    let number = format!("number={pr_number}");
+    let query = format!("query={REVIEW_THREADS_QUERY}");
+    let args = to_args(&[
+        "api",
+        "graphql",
+        "-F",
+        "owner={owner}",
+        "-F",
+        "repo={repo}",
+        "-F",
+        &number,
+        "-f",
+        &query,
+    ]);
+    let output = runner.run(&args, cwd)?;
+
+    if !output.success {
+        return Err(gh_failed("api graphql", &output));
+    }
+
+    parse_review_threads(&output.stdout_str())
+}
+
+/// List open pull requests where the current user's review is requested.
+///
+/// Runs `gh pr list --review-requested @me`, newest first, capped at `limit`.
+pub fn list_review_prs(
+    runner: &dyn GhRunner,
+    cwd: &Path,
+    limit: usize,
+) -> Result<Vec<ReviewPr>, GitError> {
+    let limit = limit.to_string();
+    let args = to_args(&[
+        "pr",
+        "list",
+        "--review-requested",
+        "@me",
+        "--json",
+        "number,title,headRefName,author",
+        "--limit",
+        &limit,
+    ]);
+    let output = runner.run(&args, cwd)?;
+
+    if !output.success {
+        return Err(gh_failed("pr list", &output));
+    }
+
+    parse_review_prs(&output.stdout_str())
+}
+
+/// Look up where pull request `pr_number`'s head branch lives.
+///
+/// Runs `gh pr view <n> --json ...`. Works for PRs from forks too; see
+/// [`PrHead::is_cross_repository`].
+pub fn get_pr_head(runner: &dyn GhRunner, cwd: &Path, pr_number: u64) -> Result<PrHead, GitError> {
+    let number = pr_number.to_string();
+    let args = to_args(&[
+        "pr",
+        "view",
+        &number,
+        "--json",
+        "number,title,url,headRefName,headRepositoryOwner,isCrossRepository,state",
+    ]);
+    let output = runner.run(&args, cwd)?;
+
+    if !output.success {
+        return Err(gh_failed("pr view", &output));
+    }
+
+    parse_pr_head(&output.stdout_str())
+}
+
+/// Fetch issue `number`'s title and body.
+///
+/// Runs `gh issue view <n> --json number,title,body,url`.
+pub fn get_issue(runner: &dyn GhRunner, cwd: &Path, number: u64) -> Result<Issue, GitError> {
+    let number = number.to_string();
+    let args = to_args(&["issue", "view", &number, "--json", "number,title,body,url"]);
+    let output = runner.run(&args, cwd)?;
+
+    if !output.success {
+        return Err(gh_failed("issue view", &output));
+    }
+
+    parse_issue(&output.stdout_str())
+}
+
+// ============================================================================
+// PARSING HELPERS
+// ============================================================================
+
+/// Parse `gh pr view --json number,state,url,isDraft` output.
+pub fn parse_pr_status(json: &str) -> Result<PrStatus, GitError> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Raw {
+        number: u64,
+        #[serde(default)]
+        state: Option<String>,
+        #[serde(default)]
+        url: Option<String>,
+        #[serde(default)]
+        is_draft: bool,
+    }
+
+    let raw: Raw = parse_json(json)?;
+    Ok(PrStatus {
+        number: raw.number,
+        state: raw.state.unwrap_or_else(|| "OPEN".to_string()),
+        url: raw.url.unwrap_or_default(),
+        is_draft: raw.is_draft,
+    })
+}
+
+/// Parse `gh pr checks --json ...` output.
+pub fn parse_pr_checks(json: &str) -> Result<Vec<PrCheck>, GitError> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Raw {
+        name: String,
+        #[serde(default)]
+        state: String,
+        #[serde(default)]
+        bucket: String,
+        #[serde(default)]
+        workflow: Option<String>,
+        #[serde(default)]
+        link: Option<String>,
+        #[serde(default)]
+        started_at: Option<String>,
+        #[serde(default)]
+        completed_at: Option<String>,
+    }
+
+    let raw: Vec<Raw> = parse_json(json)?;
+    Ok(raw
+        .into_iter()
+        .map(|c| PrCheck {
+            name: c.name,
+            state: c.state,
+            bucket: c.bucket,
+            workflow: c.workflow.filter(|w| !w.is_empty()),
+            link: c.link.filter(|l| !l.is_empty()),
+            started_at: c.started_at.filter(|t| !t.is_empty()),
+            completed_at: c.completed_at.filter(|t| !t.is_empty()),
+        })
+        .collect())
+}
+
+/// Parse the REST review comments response.
+///
+/// `gh api --paginate` prints one JSON array per page back to back
+/// (`[...][...]`), so this reads a stream of arrays and flattens them.
+pub fn parse_review_comments(json: &str) -> Result<Vec<ReviewComment>, GitError> {
+    #[derive(Deserialize)]
+    struct User {
+        login: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Raw {
+        id: u64,
+        path: String,
+        #[serde(default)]
+        line: Option<u64>,
+        #[serde(default)]
+        original_line: Option<u64>,
+        #[serde(default)]
+        diff_hunk: String,
+        #[serde(default)]
+        body: String,
+        #[serde(default)]
+        user: Option<User>,
+        #[serde(default)]
+        created_at: String,
+        #[serde(default)]
+        html_url: String,
+        #[serde(default)]
+        in_reply_to_id: Option<u64>,
+    }
+
+    let mut comments = Vec::new();
+    for page in serde_json::Deserializer::from_str(json).into_iter::<Vec<Raw>>() {
+        let page = page.map_err(|e| GitError::Other(format!("Failed to parse gh output: {e}")))?;
+        comments.extend(page.into_iter().map(|c| ReviewComment {
+            id: c.id,
+            path: c.path,
+            line: c.line,
+            original_line: c.original_line,
+            diff_hunk: c.diff_hunk,
+            body: c.body,
+            author: c.user.map(|u| u.login).unwrap_or_default(),
+            created_at: c.created_at,
+            url: c.html_url,
+            in_reply_to_id: c.in_reply_to_id,
+        }));
+    }
+    Ok(comments)
+}
+
+/// Parse the GraphQL response of [`list_review_threads`].
+pub fn parse_review_threads(json: &str) -> Result<Vec<ReviewThread>, GitError> {
+    let value: serde_json::Value = parse_json(json)?;
+
+    if let Some(message) = value["errors"][0]["message"].as_str() {
+        return Err(GitError::Other(format!("gh api graphql failed: {message}")));
+    }
+
+    let nodes = value["data"]["repository"]["pullRequest"]["reviewThreads"]["nodes"]
+        .as_array()
+        .ok_or_else(|| {
+            GitError::Other("Failed to parse gh output: missing reviewThreads".to_string())
+        })?;
+
+    Ok(nodes
+        .iter()
+        .filter_map(|node| {
+            let comments: Vec<&serde_json::Value> = node["comments"]["nodes"]
+                .as_array()
+                .map(|c| c.iter().collect())
+                .unwrap_or_default();
+
+            Some(ReviewThread {
+                id: node["id"].as_str()?.to_string(),
+                path: node["path"].as_str().unwrap_or("").to_string(),
+                line: node["line"].as_u64(),
+                original_line: node["originalLine"].as_u64(),
+                is_resolved: node["isResolved"].as_bool().unwrap_or(false),
+                is_outdated: node["isOutdated"].as_bool().unwrap_or(false),
+                diff_hunk: comments
+                    .first()
+                    .and_then(|c| c["diffHunk"].as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                comments: comments
+                    .iter()
+                    .map(|c| ReviewThreadComment {
+                        author: c["author"]["login"].as_str().unwrap_or("").to_string(),
+                        body: c["body"].as_str().unwrap_or("").to_string(),
+                        created_at: c["createdAt"].as_str().unwrap_or("").to_string(),
+                        url: c["url"].as_str().unwrap_or("").to_string(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect())
+}
+
+/// Parse `gh pr list --json number,title,headRefName,author` output.
+pub fn parse_review_prs(json: &str) -> Result<Vec<ReviewPr>, GitError> {
+    #[derive(Deserialize)]
+    struct Author {
+        #[serde(default)]
+        login: String,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Raw {
+        number: u64,
+        title: String,
+        head_ref_name: String,
+        #[serde(default)]
+        author: Option<Author>,
+    }
+
+    let raw: Vec<Raw> = parse_json(json)?;
+    Ok(raw
+        .into_iter()
+        .map(|pr| ReviewPr {
+            number: pr.number,
+            title: pr.title,
+            head_ref_name: pr.head_ref_name,
+            author_login: pr.author.map(|a| a.login).unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Parse `gh pr view --json number,title,url,headRefName,headRepositoryOwner,isCrossRepository` output.
+pub fn parse_pr_head(json: &str) -> Result<PrHead, GitError> {
+    #[derive(Deserialize)]
+    struct Owner {
+        #[serde(default)]
+        login: String,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Raw {
+        number: u64,
+        #[serde(default)]
+        title: String,
+        #[serde(default)]
+        url: String,
+        head_ref_name: String,
+        #[serde(default)]
+        head_repository_owner: Option<Owner>,
+        #[serde(default)]
+        is_cross_repository: bool,
+        #[serde(default)]
+        state: String,
+    }
+
+    let raw: Raw = parse_json(json)?;
+    Ok(PrHead {
+        number: raw.number,
+        title: raw.title,
+        url: raw.url,
+        head_ref_name: raw.head_ref_name,
+        head_owner: raw
+            .head_repository_owner
+            .map(|o| o.login)
+            .unwrap_or_default(),
+        is_cross_repository: raw.is_cross_repository,
+        state: raw.state,
+    })
+}
+
+/// Parse `gh issue view --json number,title,body,url` output.
+pub fn parse_issue(json: &str) -> Result<Issue, GitError> {
+    #[derive(Deserialize)]
+    struct Raw {
+        number: u64,
+        #[serde(default)]
+        title: String,
+        #[serde(default)]
+        body: Option<String>,
+        #[serde(default)]
+        url: String,
+    }
+
+    let raw: Raw = parse_json(json)?;
+    Ok(Issue {
+        number: raw.number,
+        title: raw.title,
+        body: raw.body.unwrap_or_default(),
+        url: raw.url,
+    })
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, GitError> {
+    serde_json::from_str(json.trim())
+        .map_err(|e| GitError::Other(format!("Failed to parse gh output: {e}")))
+}
+
+fn to_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+/// Build the error for a failed `gh` invocation, preferring stderr.
+fn gh_failed(subcommand: &str, output: &GitOutput) -> GitError {
+    let stderr = output.stderr_str();
+    let message = if stderr.trim().is_empty() {
+        output.stdout_str()
+    } else {
+        stderr
+    };
+    GitError::Other(format!("gh {subcommand} failed: {}", message.trim()))
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FakeGhRunner;
+
+    fn cwd() -> &'static Path {
+        Path::new("/tmp/repo")
+    }
+
+    #[test]
+    fn pr_status_parses_and_serializes_snake_case() {
+        let runner = FakeGhRunner::new();
+        runner.respond(
+            &["pr", "view"],
+            r#"{"number":42,"state":"OPEN","url":"https://github.com/o/r/pull/42","isDraft":true}"#,
+        );
+
+        let status = get_pr_status(&runner, cwd(), "feature").unwrap().unwrap();
+        assert_eq!(status.number, 42);
+        assert!(status.is_draft);
+        assert_eq!(
+            runner.calls()[0],
+            vec![
+                "pr",
+                "view",
+                "feature",
+                "--json",
+                "number,state,url,isDraft"
+            ]
+        );
+
+        let json = serde_json::to_string(&status).unwrap();
+        assert!(json.contains("\"is_draft\":true"));
+    }
+
+    #[test]
+    fn pr_status_without_pr_is_none() {
+        let runner = FakeGhRunner::new();
+        runner.fail(
+            &["pr", "view"],
+            "no pull requests found for branch \"feature\"",
+        );
+
+        assert_eq!(get_pr_status(&runner, cwd(), "feature").unwrap(), None);
+    }
+
+    #[test]
+    fn pr_status_surfaces_auth_errors() {
+        let runner = FakeGhRunner::new();
+        runner.fail(
+            &["pr", "view"],
+            "To get started with GitHub CLI, please run:  gh auth login",
+        );
+
+        let err = get_pr_status(&runner, cwd(), "feature").unwrap_err();
+        assert!(err.to_string().contains("gh auth login"), "{err}");
+    }
+
+    #[test]
+    fn pr_checks_accepts_json_from_failing_exit() {
+        let runner = FakeGhRunner::new();
+        runner.respond_with(
+            &["pr", "checks"],
+            false,
+            r#"[
+                {"name":"test","state":"FAILURE","bucket":"fail","workflow":"CI","link":"https://x/1","startedAt":"2026-01-01T00:00:00Z","completedAt":"2026-01-01T00:05:00Z"},
+                {"name":"lint","state":"IN_PROGRESS","bucket":"pending","workflow":"","link":"","startedAt":"","completedAt":""}
+            ]"#,
+            "",
+        );
+
+        let checks = list_pr_checks(&runner, cwd(), 7).unwrap();
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].bucket, "fail");
+        assert_eq!(checks[0].workflow.as_deref(), Some("CI"));
+        assert_eq!(checks[1].workflow, None);
+        assert_eq!(checks[1].completed_at, None);
+        assert_eq!(runner.calls()[0][2], "7");
+    }
+
+    #[test]
+    fn pr_checks_none_reported_is_empty() {
+        let runner = FakeGhRunner::new();
+        runner.fail(
+            &["pr", "checks"],
+            "no checks reported on the 'feature' branch",
+        );
+
+        assert!(list_pr_checks(&runner, cwd(), 7).unwrap().is_empty());
+    }
+
+    #[test]
+    fn review_comments_flatten_paginated_pages() {
+        let runner = FakeGhRunner::new();
+        runner.respond(
+            &["api"],
+            r#"[{"id":1,"path":"src/a.rs","line":10,"original_line":10,"diff_hunk":"@@ -1 +1 @@","body":"Rename this","user":{"login":"alice"},"created_at":"2026-01-01T00:00:00Z","html_url":"https://x/c1"}]
+[{"id":2,"path":"src/a.rs","line":null,"original_line":12,"diff_hunk":"@@ -1 +1 @@","body":"Agreed","user":{"login":"bob"},"created_at":"2026-01-02T00:00:00Z","html_url":"https://x/c2","in_reply_to_id":1}]"#,
+        );
+
+        let comments = list_review_comments(&runner, cwd(), 3).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].author, "alice");
+        assert_eq!(comments[1].line, None);
+        assert_eq!(comments[1].in_reply_to_id, Some(1));
+        assert_eq!(
+            runner.calls()[0][1],
+            "repos/{owner}/{repo}/pulls/3/comments"
+        );
+    }
+
+    #[test]
+    fn review_prs_parse_author_login() {
+        let runner = FakeGhRunner::new();
+        runner.respond(
+            &["pr", "list"],
+            r#"[{"number":5,"title":"Fix it","headRefName":"fix-it","author":{"login":"carol"}}]"#,
+        );
+
+        let prs = list_review_prs(&runner, cwd(), 20).unwrap();
+        assert_eq!(prs.len(), 1);
+        assert_eq!(prs[0].author_login, "carol");
+
+        let json = serde_json::to_string(&prs[0]).unwrap();
+        assert!(json.contains("\"headRefName\":\"fix-it\""));
+        assert!(json.contains("\"authorLogin\":\"carol\""));
+    }
+
+    #[test]
+    fn review_prs_surface_errors_instead_of_empty() {
+        let runner = FakeGhRunner::new();
+        runner.fail(&["pr", "list"], "HTTP 401: Bad credentials");
+
+        let err = list_review_prs(&runner, cwd(), 20).unwrap_err();
+        assert!(err.to_string().contains("Bad credentials"));
+    }
+
+    #[test]
+    fn review_threads_parse_graphql_nodes() {
+        let runner = FakeGhRunner::new();
+        runner.respond(
+            &["api", "graphql"],
+            r#"{"data":{"repository":{"pullRequest":{"reviewThreads":{"nodes":[
+                {"id":"T1","path":"src/a.rs","line":10,"originalLine":9,"isResolved":false,"isOutdated":false,
+                 "comments":{"nodes":[
+                    {"body":"Use a constant","createdAt":"2026-01-01T00:00:00Z","url":"https://x/1","diffHunk":"@@ -1,2 +1,3 @@\n+let x = 5;","author":{"login":"alice"}},
+                    {"body":"+1","createdAt":"2026-01-02T00:00:00Z","url":"https://x/2","diffHunk":"@@ -1,2 +1,3 @@","author":{"login":"bob"}}
+                 ]}},
+                {"id":"T2","path":"src/b.rs","line":null,"originalLine":3,"isResolved":true,"isOutdated":true,
+                 "comments":{"nodes":[]}}
+            ]}}}}}"#,
+        );
+
+        let threads = list_review_threads(&runner, cwd(), 12).unwrap();
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].id, "T1");
+        assert_eq!(threads[0].comments.len(), 2);
+        assert_eq!(threads[0].comments[1].author, "bob");
+        assert!(threads[0].diff_hunk.contains("let x = 5"));
+        assert!(threads[1].is_resolved);
+        assert_eq!(threads[1].line, None);
+        assert!(runner.calls()[0].contains(&"number=12".to_string()));
+    }
+
+    #[test]
+    fn review_threads_surface_graphql_errors() {
+        let err = parse_review_threads(
+            r#"{"errors":[{"message":"Could not resolve to a PullRequest with the number of 99."}]}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Could not resolve"));
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        assert!(parse_pr_status("not json").is_err());
+        assert!(parse_review_comments("{").is_err());
+    }
+
+    #[test]
+    fn pr_head_parses_fork_owner() {
+        let runner = FakeGhRunner::new();
+        runner.respond(
+            &["pr", "view"],
+            r#"{"number":9,"title":"Fix it","url":"https://x/9","headRefName":"patch-1","headRepositoryOwner":{"id":"U1","login":"contributor"},"isCrossRepository":true}"#,
+        );
+
+        let head = get_pr_head(&runner, cwd(), 9).unwrap();
+        assert_eq!(head.head_ref_name, "patch-1");
+        assert_eq!(head.head_owner, "contributor");
+        assert!(head.is_cross_repository);
+        assert_eq!(runner.calls()[0][2], "9");
+    }
+
+    #[test]
+    fn issue_with_null_body_is_empty() {
+        let runner = FakeGhRunner::new();
+        runner.respond(
+            &["issue", "view"],
+            r#"{"number":5,"title":"Crash on start","body":null,"url":"https://x/5"}"#,
+        );
+
+        let issue = get_issue(&runner, cwd(), 5).unwrap();
+        assert_eq!(issue.title, "Crash on start");
+        assert_eq!(issue.body, "");
+    }
+}
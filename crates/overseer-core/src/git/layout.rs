@@ -0,0 +1,297 @@
+//! Where new workspaces are created and what they're called.
+//!
+//! # Overview
+//!
+//! A [`WorkspaceLayout`] is a root directory plus a naming template. Both
+//! can be set globally (`workspaceRoot` / `workspaceNaming` in
+//! `config.json`) and per project (same keys in `projects.json`); a project
+//! value wins over the global one, which wins over the defaults.
+//!
+//! # Naming Templates
+//!
+//! The template is a relative path under the root. Placeholders:
+//!
+//! - `{repo}` - the repository's directory name
+//! - `{branch}` - the branch, with `/` and other unsafe characters as `-`
+//! - `{animal}` - a random animal name
+//! - `{n}` - a counter, the lowest that gives an unused directory
+//!
+//! ```text
+//! {repo}/{animal}      ~/overseer/workspaces/my-repo/narwhal   (default)
+//! {repo}/{branch}      /scratch/ws/my-repo/feature-login
+//! {repo}-{n}           /scratch/ws/my-repo-3
+//! ```
+//!
+//! The last path component names the workspace's chat directory, so it must
+//! contain `{animal}`, `{branch}` or `{n}` to stay unique within a project.
+
+use super::GitError;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Naming template used when none is configured.
+pub const DEFAULT_NAMING: &str = "{repo}/{animal}";
+
+const PLACEHOLDERS: &[&str] = &["{repo}", "{branch}", "{animal}", "{n}"];
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Root directory and naming template for new workspaces.
+///
+/// Fields hold the raw user values; `None` or blank means "not set here".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkspaceLayout {
+    /// Directory workspaces are created under. A leading `~` or `$HOME` is
+    /// expanded.
+    pub root: Option<String>,
+    /// Naming template, relative to `root`.
+    pub naming: Option<String>,
+}
+
+impl WorkspaceLayout {
+    /// This layout's fields, falling back to `fallback`'s where unset.
+    pub fn or(self, fallback: WorkspaceLayout) -> WorkspaceLayout {
+        WorkspaceLayout {
+            root: non_blank(self.root).or(non_blank(fallback.root)),
+            naming: non_blank(self.naming).or(non_blank(fallback.naming)),
+        }
+    }
+
+    /// The naming template, or [`DEFAULT_NAMING`].
+    pub fn naming_template(&self) -> &str {
+        self.naming
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .unwrap_or(DEFAULT_NAMING)
+    }
+
+    /// The root directory, expanded against `home`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the root needs the home directory (unset, or
+    /// starts with `~`) and `home` is `None`, or if it isn't absolute.
+    pub fn root_dir(&self, home: Option<&str>) -> Result<PathBuf, GitError> {
+        let home_required = || GitError::PathError("Home directory not set".to_string());
+        let Some(raw) = self
+            .root
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+        else {
+            return Ok(default_workspace_root(home.ok_or_else(home_required)?));
+        };
+
+        let expanded = if raw.starts_with('~') || raw.starts_with("$HOME") {
+            crate::paths::expand_home(raw, home.ok_or_else(home_required)?)
+        } else {
+            raw.to_string()
+        };
+        let root = PathBuf::from(expanded);
+        if !root.is_absolute() {
+            return Err(GitError::PathError(format!(
+                "Workspace root must be an absolute path: {raw}"
+            )));
+        }
+        Ok(root)
+    }
+}
+
+fn non_blank(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
+/// `~/overseer/workspaces`, or `workspaces-dev` in development builds.
+pub fn default_workspace_root(home: &str) -> PathBuf {
+    let workspaces_dir = if cfg!(debug_assertions) {
+        "workspaces-dev"
+    } else {
+        "workspaces"
+    };
+    PathBuf::from(home).join("overseer").join(workspaces_dir)
+}
+
+// ============================================================================
+// TEMPLATES
+// ============================================================================
+
+/// Check a naming template before it's saved or used.
+///
+/// # Errors
+///
+/// Returns an error if the template is absolute, escapes the root with `..`,
+/// uses an unknown `{placeholder}`, or its last component has none of
+/// `{animal}`, `{branch}` or `{n}`.
+pub fn validate_naming(template: &str) -> Result<(), GitError> {
+    let invalid = |reason: &str| {
+        GitError::Other(format!(
+            "Invalid workspace naming template '{template}': {reason}"
+        ))
+    };
+
+    if template.starts_with('/') || template.starts_with('\\') {
+        return Err(invalid("it must be relative to the workspace root"));
+    }
+    let components: Vec<&str> = template.split('/').collect();
+    if components
+        .iter()
+        .any(|c| c.is_empty() || *c == "." || *c == "..")
+    {
+        return Err(invalid(
+            "empty, '.' and '..' path components aren't allowed",
+        ));
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| invalid("unclosed '{'"))?;
+        let placeholder = &rest[start..start + end + 1];
+        if !PLACEHOLDERS.contains(&placeholder) {
+            return Err(invalid(&format!("unknown placeholder {placeholder}")));
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    let last = components.last().copied().unwrap_or_default();
+    if !["{animal}", "{branch}", "{n}"]
+        .iter()
+        .any(|p| last.contains(p))
+    {
+        return Err(invalid(
+            "the last component needs {animal}, {branch} or {n} to be unique",
+        ));
+    }
+    Ok(())
+}
+
+/// Values substituted into a naming template.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NamingVars<'a> {
+    pub repo: &'a str,
+    pub branch: &'a str,
+    pub animal: &'a str,
+    pub n: u32,
+}
+
+/// Substitute `vars` into `template`.
+pub(crate) fn render_naming(template: &str, vars: NamingVars<'_>) -> String {
+    template
+        .replace("{repo}", vars.repo)
+        .replace("{branch}", &branch_slug(vars.branch))
+        .replace("{animal}", vars.animal)
+        .replace("{n}", &vars.n.to_string())
+}
+
+/// A branch name safe to use as a single directory name.
+///
+/// `feature/Login page` becomes `feature-Login-page`.
+pub fn branch_slug(branch: &str) -> String {
+    let mut slug = String::with_capacity(branch.len());
+    for c in branch.chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches(|c| c == '-' || c == '.');
+    if slug.is_empty() {
+        "workspace".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(root: Option<&str>, naming: Option<&str>) -> WorkspaceLayout {
+        WorkspaceLayout {
+            root: root.map(str::to_string),
+            naming: naming.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn project_layout_overrides_global_field_by_field() {
+        let project = layout(None, Some("{repo}/{branch}"));
+        let global = layout(Some("/scratch"), Some("{repo}/{n}"));
+
+        let resolved = project.or(global);
+
+        assert_eq!(resolved, layout(Some("/scratch"), Some("{repo}/{branch}")));
+        assert_eq!(
+            layout(Some("  "), None).or(WorkspaceLayout::default()),
+            WorkspaceLayout::default()
+        );
+    }
+
+    #[test]
+    fn root_dir_expands_home_and_requires_absolute() {
+        let home = Some("/home/dev");
+
+        assert_eq!(
+            layout(None, None).root_dir(home).unwrap(),
+            default_workspace_root("/home/dev")
+        );
+        assert_eq!(
+            layout(Some("~/ws"), None).root_dir(home).unwrap(),
+            PathBuf::from("/home/dev/ws")
+        );
+        assert_eq!(
+            layout(Some("/scratch/ws"), None).root_dir(None).unwrap(),
+            PathBuf::from("/scratch/ws")
+        );
+        assert!(layout(Some("scratch"), None).root_dir(home).is_err());
+        assert!(layout(None, None).root_dir(None).is_err());
+    }
+
+    #[test]
+    fn validate_naming_rejects_unsafe_templates() {
+        assert!(validate_naming(DEFAULT_NAMING).is_ok());
+        assert!(validate_naming("{repo}/{branch}").is_ok());
+        assert!(validate_naming("{repo}-{n}").is_ok());
+
+        assert!(validate_naming("/{animal}").is_err());
+        assert!(validate_naming("../{animal}").is_err());
+        assert!(validate_naming("{repo}/{color}").is_err());
+        assert!(validate_naming("{animal}/{repo}").is_err());
+        assert!(validate_naming("{repo}/{animal").is_err());
+    }
+
+    #[test]
+    fn render_substitutes_placeholders() {
+        let vars = NamingVars {
+            repo: "my-repo",
+            branch: "feature/Login page",
+            animal: "narwhal",
+            n: 3,
+        };
+
+        assert_eq!(
+            render_naming("{repo}/{branch}", vars),
+            "my-repo/feature-Login-page"
+        );
+        assert_eq!(
+            render_naming("{repo}/{animal}-{n}", vars),
+            "my-repo/narwhal-3"
+        );
+    }
+
+    #[test]
+    fn branch_slug_never_empty() {
+        assert_eq!(branch_slug("fix/#12"), "fix-12");
+        assert_eq!(branch_slug("/"), "workspace");
+    }
+}
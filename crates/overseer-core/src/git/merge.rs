@@ -14,7 +14,7 @@
 //! - [`check_merge`] - Check if a merge would succeed (dry run)
 //! - [`merge_into_main`] - Actually perform the merge
 //!
-//! # Merge Strategies
+//! # Merge Scenarios
 //!
 //! The module detects different merge scenarios:
 //!
@@ -22,21 +22,68 @@
 //! - **Clean merge**: Branches diverged but no conflicts
 //! - **Conflict**: Changes overlap and need manual resolution
 //!
+//! # Strategies
+//!
+//! How the feature branch lands is chosen with [`MergeStrategy`]:
+//!
+//! - **Merge** (default): `git merge`, fast-forwarding when possible and
+//!   otherwise creating a merge commit
+//! - **Squash**: all of the branch's changes as one commit on the default
+//!   branch, with a message generated from the branch's commits
+//! - **Rebase**: the branch is rebased onto the default branch in its
+//!   workspace, then the default branch is fast-forwarded to it
+//!
+//! [`MergeOptions::push`] pushes the default branch to `origin` afterwards.
+//!
 //! # Safety
 //!
 //! When merges fail due to conflicts, the module automatically aborts
-//! the merge to leave the repository in a clean state.
+//! the merge (or rebase) to leave the repository in a clean state.
 
 use super::{
     get_current_branch, is_default_branch_name, resolve_default_branch, run_git, GitError,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 // ============================================================================
 // TYPES
 // ============================================================================
 
+/// How a feature branch is brought into the default branch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Fast-forward if possible, otherwise a merge commit.
+    #[default]
+    Merge,
+    /// One commit on the default branch holding all of the branch's changes.
+    Squash,
+    /// Rebase the branch onto the default branch, then fast-forward.
+    Rebase,
+}
+
+impl MergeStrategy {
+    /// Noun used in result messages ("Clean squash of ...").
+    pub(crate) fn noun(self) -> &'static str {
+        match self {
+            MergeStrategy::Merge => "merge",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::Rebase => "rebase",
+        }
+    }
+}
+
+/// Options for [`merge_into_main`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MergeOptions {
+    /// How the branch lands on the default branch.
+    pub strategy: MergeStrategy,
+    /// Push the default branch to `origin` after a successful merge.
+    pub push: bool,
+}
+
 /// Result of a merge check or merge operation.
 ///
 /// Indicates whether the merge succeeded or failed, and provides
@@ -60,6 +107,36 @@ pub struct MergeResult {
 
     /// Human-readable message describing the result
     pub message: String,
+
+    /// The strategy that was checked or used
+    pub strategy: MergeStrategy,
+
+    /// Whether the default branch was pushed to `origin` afterwards
+    pub pushed: bool,
+}
+
+impl MergeResult {
+    pub(crate) fn new(strategy: MergeStrategy, success: bool, message: impl Into<String>) -> Self {
+        MergeResult {
+            success,
+            already_up_to_date: false,
+            conflicts: vec![],
+            message: message.into(),
+            strategy,
+            pushed: false,
+        }
+    }
+
+    pub(crate) fn conflicts(
+        strategy: MergeStrategy,
+        conflicts: Vec<String>,
+        message: String,
+    ) -> Self {
+        MergeResult {
+            conflicts,
+            ..MergeResult::new(strategy, false, message)
+        }
+    }
 }
 
 // ============================================================================
@@ -74,6 +151,8 @@ pub struct MergeResult {
 /// # Arguments
 ///
 /// * `workspace_path` - Path to the workspace (must be on feature branch)
+/// * `main_branch` - The project's default branch, if configured
+/// * `strategy` - The strategy the merge will use
 ///
 /// # Returns
 ///
@@ -89,10 +168,14 @@ pub struct MergeResult {
 /// 3. **Clean merge**: Branches diverged but no conflicts
 /// 4. **Conflicts**: Overlapping changes detected
 ///
+/// The check compares the two branch tips, which is exact for merge and
+/// squash. A rebase replays commits one at a time and can still stop on an
+/// intermediate commit; [`merge_into_main`] aborts and reports that case.
+///
 /// # Example
 ///
 /// ```ignore
-/// let result = check_merge(workspace_path).await?;
+/// let result = check_merge(workspace_path, None, MergeStrategy::Merge).await?;
 /// if result.success {
 ///     println!("Merge would succeed: {}", result.message);
 /// } else if !result.conflicts.is_empty() {
@@ -102,26 +185,22 @@ pub struct MergeResult {
 pub async fn check_merge(
     workspace_path: &Path,
     main_branch: Option<&str>,
+    strategy: MergeStrategy,
 ) -> Result<MergeResult, GitError> {
     // Get current branch (the feature branch)
     let feature_branch = get_current_branch(workspace_path).await?;
 
     // Check if already on default branch
     if is_default_branch_name(&feature_branch, main_branch) {
-        return Ok(MergeResult {
-            success: false,
-            already_up_to_date: false,
-            conflicts: vec![],
-            message: "Already on the default branch, nothing to merge.".to_string(),
-        });
+        return Ok(MergeResult::new(
+            strategy,
+            false,
+            "Already on the default branch, nothing to merge.",
+        ));
     }
 
-    // Get default branch, stripping origin/ prefix if present
-    let default_remote = resolve_default_branch(workspace_path, main_branch).await;
-    let default_branch = default_remote
-        .strip_prefix("origin/")
-        .unwrap_or(&default_remote)
-        .to_string();
+    let default_branch = local_default_branch(workspace_path, main_branch).await;
+    let noun = strategy.noun();
 
     // Check if fast-forward is possible
     // (default branch is an ancestor of feature branch)
@@ -137,14 +216,13 @@ pub async fn check_merge(
     .await?;
 
     if is_ancestor.success {
-        return Ok(MergeResult {
-            success: true,
-            already_up_to_date: false,
-            conflicts: vec![],
-            message: format!(
-                "Clean fast-forward merge of '{feature_branch}' into '{default_branch}'."
-            ),
-        });
+        let message = match strategy {
+            MergeStrategy::Squash => {
+                format!("Clean squash of '{feature_branch}' into '{default_branch}'.")
+            }
+            _ => format!("Clean fast-forward merge of '{feature_branch}' into '{default_branch}'."),
+        };
+        return Ok(MergeResult::new(strategy, true, message));
     }
 
     // Use merge-tree to simulate merge and check for conflicts
@@ -161,12 +239,11 @@ pub async fn check_merge(
     .await?;
 
     if merge_tree.success {
-        return Ok(MergeResult {
-            success: true,
-            already_up_to_date: false,
-            conflicts: vec![],
-            message: format!("Clean merge of '{feature_branch}' into '{default_branch}'."),
-        });
+        return Ok(MergeResult::new(
+            strategy,
+            true,
+            format!("Clean {noun} of '{feature_branch}' into '{default_branch}'."),
+        ));
     }
 
     // Merge would have conflicts - extract conflicting file names
@@ -180,14 +257,14 @@ pub async fn check_merge(
         .filter_map(|l| l.split('\t').next_back().map(|s| s.to_string()))
         .collect();
 
-    Ok(MergeResult {
-        success: false,
-        already_up_to_date: false,
+    Ok(MergeResult::conflicts(
+        strategy,
         conflicts,
-        message: format!(
-            "Merge of '{feature_branch}' into '{default_branch}' has conflicts that need resolution."
+        format!(
+            "{} of '{feature_branch}' into '{default_branch}' has conflicts that need resolution.",
+            capitalize(noun)
         ),
-    })
+    ))
 }
 
 // ============================================================================
@@ -202,13 +279,21 @@ pub async fn check_merge(
 /// # Arguments
 ///
 /// * `workspace_path` - Path to the feature branch workspace
+/// * `main_branch` - The project's default branch, if configured
+/// * `options` - Strategy, and whether to push afterwards
 ///
 /// # Process
 ///
 /// 1. Identifies the current feature branch
 /// 2. Finds the workspace on the default branch (main/master)
-/// 3. Runs `git merge <feature-branch>` from that workspace
+/// 3. Lands the branch there with the chosen [`MergeStrategy`]
 /// 4. Aborts if conflicts occur (leaves repo clean)
+/// 5. Pushes the default branch if [`MergeOptions::push`] is set
+///
+/// A rebase rewrites the feature branch itself (uncommitted changes in its
+/// workspace are autostashed). A squash leaves the feature branch unmerged
+/// as far as git is concerned, so deleting it afterwards needs
+/// [`force_delete_branch`](super::force_delete_branch).
 ///
 /// # Returns
 ///
@@ -216,40 +301,40 @@ pub async fn check_merge(
 /// - `success: true` if merge completed
 /// - `success: false` if conflicts occurred (merge aborted)
 ///
+/// A failed push doesn't undo the merge: `success` stays true, `pushed` is
+/// false and the message says why.
+///
 /// # Errors
 ///
 /// Returns an error if:
-/// - Already on the default branch
 /// - No workspace exists on the default branch
 /// - Git merge command fails for non-conflict reasons
 ///
 /// # Safety
 ///
 /// If the merge fails due to conflicts, the function automatically
-/// runs `git merge --abort` to leave the main workspace clean.
+/// runs `git merge --abort` (or `git rebase --abort`) to leave the
+/// workspaces clean.
 pub async fn merge_into_main(
     workspace_path: &Path,
     main_branch: Option<&str>,
+    options: &MergeOptions,
 ) -> Result<MergeResult, GitError> {
+    let strategy = options.strategy;
+
     // Get current branch (the feature branch)
     let feature_branch = get_current_branch(workspace_path).await?;
 
     // Check if already on default branch
     if is_default_branch_name(&feature_branch, main_branch) {
-        return Ok(MergeResult {
-            success: false,
-            already_up_to_date: false,
-            conflicts: vec![],
-            message: "Already on the default branch, nothing to merge.".to_string(),
-        });
+        return Ok(MergeResult::new(
+            strategy,
+            false,
+            "Already on the default branch, nothing to merge.",
+        ));
     }
 
-    // Get default branch name (without origin/ prefix)
-    let default_remote = resolve_default_branch(workspace_path, main_branch).await;
-    let default_branch = default_remote
-        .strip_prefix("origin/")
-        .unwrap_or(&default_remote)
-        .to_string();
+    let default_branch = local_default_branch(workspace_path, main_branch).await;
 
     // Guard against a no-op merge. `git merge` exits 0 for "Already up to date"
     // when the feature branch has no commits beyond the default branch (empty
@@ -271,14 +356,7 @@ pub async fn merge_into_main(
         .parse::<u64>()
         .unwrap_or(0);
     if rev_list.success && commits_ahead == 0 {
-        return Ok(MergeResult {
-            success: false,
-            already_up_to_date: true,
-            conflicts: vec![],
-            message: format!(
-                "Nothing to merge — '{feature_branch}' has no commits that aren't already on '{default_branch}'."
-            ),
-        });
+        return Ok(nothing_to_merge(strategy, &feature_branch, &default_branch));
     }
 
     // Find the workspace checked out on the default branch
@@ -314,11 +392,34 @@ pub async fn merge_into_main(
 
     let main_path = Path::new(&main_path);
 
+    let mut result = match strategy {
+        MergeStrategy::Merge => merge_commit(main_path, &feature_branch, &default_branch).await?,
+        MergeStrategy::Squash => squash_merge(main_path, &feature_branch, &default_branch).await?,
+        MergeStrategy::Rebase => {
+            rebase_merge(workspace_path, main_path, &feature_branch, &default_branch).await?
+        }
+    };
+
+    if result.success && options.push {
+        push_default_branch(main_path, &default_branch, &mut result).await;
+    }
+
+    Ok(result)
+}
+
+/// `git merge` from the default branch's workspace.
+async fn merge_commit(
+    main_path: &Path,
+    feature_branch: &str,
+    default_branch: &str,
+) -> Result<MergeResult, GitError> {
+    let strategy = MergeStrategy::Merge;
+
     // Perform the merge from the main branch workspace
     let merge_output = run_git(
         &[
             "merge",
-            &feature_branch,
+            feature_branch,
             "--no-edit",
             "-m",
             &format!("Merge branch '{feature_branch}'"),
@@ -328,12 +429,11 @@ pub async fn merge_into_main(
     .await?;
 
     if merge_output.success {
-        return Ok(MergeResult {
-            success: true,
-            already_up_to_date: false,
-            conflicts: vec![],
-            message: format!("Successfully merged '{feature_branch}' into '{default_branch}'."),
-        });
+        return Ok(MergeResult::new(
+            strategy,
+            true,
+            format!("Successfully merged '{feature_branch}' into '{default_branch}'."),
+        ));
     }
 
     // Merge failed - check if due to conflicts
@@ -341,33 +441,247 @@ pub async fn merge_into_main(
     let stdout = String::from_utf8_lossy(&merge_output.stdout).to_string();
 
     // Extract conflict descriptions from output
-    let conflicts: Vec<String> = stdout
-        .lines()
-        .filter(|l| l.starts_with("CONFLICT"))
-        .map(|l| l.to_string())
-        .collect();
+    let conflicts = conflict_lines(&stdout);
 
     // Abort the merge to leave the repository clean
     let _ = run_git(&["merge", "--abort"], main_path).await;
 
     if !conflicts.is_empty() {
-        return Ok(MergeResult {
-            success: false,
-            already_up_to_date: false,
+        return Ok(MergeResult::conflicts(
+            strategy,
             conflicts,
-            message: format!(
+            format!(
                 "Merge of '{feature_branch}' into '{default_branch}' has conflicts that need resolution."
             ),
-        });
+        ));
     }
 
     // Non-conflict failure
-    Ok(MergeResult {
-        success: false,
-        already_up_to_date: false,
-        conflicts: vec![],
-        message: format!("Merge failed: {stderr} {stdout}"),
-    })
+    Ok(MergeResult::new(
+        strategy,
+        false,
+        format!("Merge failed: {stderr} {stdout}"),
+    ))
+}
+
+/// `git merge --squash` from the default branch's workspace, committed with
+/// a message generated from the branch's commits.
+async fn squash_merge(
+    main_path: &Path,
+    feature_branch: &str,
+    default_branch: &str,
+) -> Result<MergeResult, GitError> {
+    let strategy = MergeStrategy::Squash;
+
+    let squash_output = run_git(&["merge", "--squash", feature_branch], main_path).await?;
+    if !squash_output.success {
+        let stderr = String::from_utf8_lossy(&squash_output.stderr).to_string();
+        let stdout = String::from_utf8_lossy(&squash_output.stdout).to_string();
+        let conflicts = conflict_lines(&stdout);
+
+        // A squash leaves no MERGE_HEAD, so `merge --abort` doesn't apply;
+        // `reset --merge` drops the staged result and conflicted files.
+        let _ = run_git(&["reset", "--merge"], main_path).await;
+
+        if !conflicts.is_empty() {
+            return Ok(MergeResult::conflicts(
+                strategy,
+                conflicts,
+                format!(
+                    "Squash of '{feature_branch}' into '{default_branch}' has conflicts that need resolution."
+                ),
+            ));
+        }
+        return Ok(MergeResult::new(
+            strategy,
+            false,
+            format!("Squash failed: {stderr} {stdout}"),
+        ));
+    }
+
+    // The branch's commits may cancel out; there is nothing to commit then.
+    let staged = run_git(&["diff", "--cached", "--quiet"], main_path).await?;
+    if staged.success {
+        return Ok(nothing_to_merge(strategy, feature_branch, default_branch));
+    }
+
+    let log = run_git(
+        &[
+            "log",
+            "--reverse",
+            "--format=%B%x00",
+            &format!("{default_branch}..{feature_branch}"),
+        ],
+        main_path,
+    )
+    .await?;
+    let log = String::from_utf8_lossy(&log.stdout);
+    let messages: Vec<&str> = log
+        .split('\0')
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .collect();
+    let message = squash_commit_message(feature_branch, &messages);
+
+    let commit_output = run_git(&["commit", "-m", &message], main_path).await?;
+    if !commit_output.success {
+        let stderr = String::from_utf8_lossy(&commit_output.stderr).to_string();
+        let _ = run_git(&["reset", "--merge"], main_path).await;
+        return Ok(MergeResult::new(
+            strategy,
+            false,
+            format!("Squash commit failed: {stderr}"),
+        ));
+    }
+
+    Ok(MergeResult::new(
+        strategy,
+        true,
+        format!("Squashed '{feature_branch}' into '{default_branch}'."),
+    ))
+}
+
+/// Rebase the feature branch onto the default branch in its own workspace,
+/// then fast-forward the default branch to it.
+async fn rebase_merge(
+    workspace_path: &Path,
+    main_path: &Path,
+    feature_branch: &str,
+    default_branch: &str,
+) -> Result<MergeResult, GitError> {
+    let strategy = MergeStrategy::Rebase;
+
+    let rebase_output = run_git(&["rebase", "--autostash", default_branch], workspace_path).await?;
+    if !rebase_output.success {
+        let stderr = String::from_utf8_lossy(&rebase_output.stderr).to_string();
+        let unmerged = run_git(&["diff", "--name-only", "--diff-filter=U"], workspace_path).await?;
+        let conflicts: Vec<String> = String::from_utf8_lossy(&unmerged.stdout)
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+
+        // Abort the rebase to put the branch (and any autostash) back
+        let _ = run_git(&["rebase", "--abort"], workspace_path).await;
+
+        if !conflicts.is_empty() {
+            return Ok(MergeResult::conflicts(
+                strategy,
+                conflicts,
+                format!(
+                    "Rebase of '{feature_branch}' onto '{default_branch}' has conflicts that need resolution."
+                ),
+            ));
+        }
+        return Ok(MergeResult::new(
+            strategy,
+            false,
+            format!("Rebase failed: {stderr}"),
+        ));
+    }
+
+    let ff_output = run_git(&["merge", "--ff-only", feature_branch], main_path).await?;
+    if !ff_output.success {
+        let stderr = String::from_utf8_lossy(&ff_output.stderr).to_string();
+        return Ok(MergeResult::new(
+            strategy,
+            false,
+            format!(
+                "Rebased '{feature_branch}' onto '{default_branch}', but fast-forwarding '{default_branch}' failed: {stderr}"
+            ),
+        ));
+    }
+
+    Ok(MergeResult::new(
+        strategy,
+        true,
+        format!("Rebased '{feature_branch}' and fast-forwarded '{default_branch}' to it."),
+    ))
+}
+
+/// Push the default branch to `origin`, recording the outcome on `result`.
+async fn push_default_branch(main_path: &Path, default_branch: &str, result: &mut MergeResult) {
+    match run_git(&["push", "origin", default_branch], main_path).await {
+        Ok(output) if output.success => {
+            result.pushed = true;
+            result.message = format!("{} Pushed '{default_branch}'.", result.message);
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            result.message = format!(
+                "{} Pushing '{default_branch}' failed: {}",
+                result.message,
+                stderr.trim()
+            );
+        }
+        Err(e) => {
+            result.message = format!("{} Pushing '{default_branch}' failed: {e}", result.message);
+        }
+    }
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// The default branch as a local branch name (without `origin/`).
+async fn local_default_branch(workspace_path: &Path, main_branch: Option<&str>) -> String {
+    let default_remote = resolve_default_branch(workspace_path, main_branch).await;
+    default_remote
+        .strip_prefix("origin/")
+        .unwrap_or(&default_remote)
+        .to_string()
+}
+
+pub(crate) fn nothing_to_merge(
+    strategy: MergeStrategy,
+    feature_branch: &str,
+    default_branch: &str,
+) -> MergeResult {
+    MergeResult {
+        already_up_to_date: true,
+        ..MergeResult::new(
+            strategy,
+            false,
+            format!(
+                "Nothing to merge — '{feature_branch}' has no commits that aren't already on '{default_branch}'."
+            ),
+        )
+    }
+}
+
+/// `CONFLICT ...` lines from `git merge` output.
+fn conflict_lines(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter(|l| l.starts_with("CONFLICT"))
+        .map(|l| l.to_string())
+        .collect()
+}
+
+/// Commit message for a squash merge of `feature_branch`.
+///
+/// A single commit keeps its message as is. Several commits get a title
+/// naming the branch and a bullet list of their subjects, oldest first.
+pub fn squash_commit_message(feature_branch: &str, messages: &[&str]) -> String {
+    match messages {
+        [] => format!("Squash branch '{feature_branch}'"),
+        [only] => only.to_string(),
+        _ => {
+            let bullets: Vec<String> = messages
+                .iter()
+                .map(|m| format!("* {}", m.lines().next().unwrap_or_default()))
+                .collect();
+            format!("Squash branch '{feature_branch}'\n\n{}", bullets.join("\n"))
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
 
 // ============================================================================
@@ -385,12 +699,16 @@ mod tests {
             already_up_to_date: false,
             conflicts: vec![],
             message: "Clean merge".to_string(),
+            strategy: MergeStrategy::Squash,
+            pushed: false,
         };
 
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("\"success\":true"));
         assert!(json.contains("\"alreadyUpToDate\":false"));
         assert!(json.contains("Clean merge"));
+        assert!(json.contains("\"strategy\":\"squash\""));
+        assert!(json.contains("\"pushed\":false"));
     }
 
     #[test]
@@ -400,6 +718,8 @@ mod tests {
             already_up_to_date: false,
             conflicts: vec!["file1.rs".to_string(), "file2.rs".to_string()],
             message: "Has conflicts".to_string(),
+            strategy: MergeStrategy::Merge,
+            pushed: false,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -490,7 +810,9 @@ mod tests {
             String::from_utf8_lossy(&checkout.stderr)
         );
 
-        let result = merge_into_main(path, None).await.unwrap();
+        let result = merge_into_main(path, None, &MergeOptions::default())
+            .await
+            .unwrap();
         assert!(!result.success);
         assert!(result.already_up_to_date);
         assert!(result.conflicts.is_empty());
@@ -509,4 +831,138 @@ mod tests {
             .unwrap();
         assert_eq!(main_head.stdout, feature_head.stdout);
     }
+
+    #[test]
+    fn squash_message_from_branch_commits() {
+        assert_eq!(
+            squash_commit_message("feature", &["Add login\n\nWith a form."]),
+            "Add login\n\nWith a form."
+        );
+        assert_eq!(
+            squash_commit_message("feature", &["Add login\n\nBody", "Fix typo"]),
+            "Squash branch 'feature'\n\n* Add login\n* Fix typo"
+        );
+    }
+
+    #[test]
+    fn merge_options_deserialize_with_defaults() {
+        let options: MergeOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(options.strategy, MergeStrategy::Merge);
+        assert!(!options.push);
+
+        let options: MergeOptions =
+            serde_json::from_str(r#"{"strategy":"rebase","push":true}"#).unwrap();
+        assert_eq!(options.strategy, MergeStrategy::Rebase);
+        assert!(options.push);
+    }
+
+    /// A repo on `main` plus a `feature` worktree with two commits, while
+    /// `main` has moved on with an unrelated commit.
+    fn diverged_repo() -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = init_temp_repo("main");
+        let main_path = dir.path().join("repo");
+        std::fs::create_dir(&main_path).unwrap();
+        let feature_path = dir.path().join("feature");
+
+        let git = |args: &[&str], cwd: &Path| {
+            let out = std::process::Command::new("git")
+                .args(args)
+                .env("GIT_CONFIG_GLOBAL", dir.path().join(".gitconfig-empty"))
+                .current_dir(cwd)
+                .output()
+                .unwrap();
+            assert!(
+                out.status.success(),
+                "git {args:?} failed: {}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+        };
+
+        // Keep the worktrees out of the main checkout.
+        git(&["clone", "-q", ".", "repo"], dir.path());
+        git(&["config", "user.email", "test@test.com"], &main_path);
+        git(&["config", "user.name", "Test"], &main_path);
+        git(
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                feature_path.to_str().unwrap(),
+            ],
+            &main_path,
+        );
+
+        std::fs::write(feature_path.join("a.txt"), "a\n").unwrap();
+        git(&["add", "."], &feature_path);
+        git(&["commit", "-q", "-m", "Add a"], &feature_path);
+        std::fs::write(feature_path.join("b.txt"), "b\n").unwrap();
+        git(&["add", "."], &feature_path);
+        git(&["commit", "-q", "-m", "Add b"], &feature_path);
+
+        std::fs::write(main_path.join("c.txt"), "c\n").unwrap();
+        git(&["add", "."], &main_path);
+        git(&["commit", "-q", "-m", "Add c"], &main_path);
+
+        (dir, feature_path)
+    }
+
+    fn git_output(args: &[&str], cwd: &Path) -> String {
+        let out = std::process::Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+
+    #[tokio::test]
+    async fn squash_lands_branch_as_one_commit() {
+        let (_dir, feature_path) = diverged_repo();
+        let options = MergeOptions {
+            strategy: MergeStrategy::Squash,
+            push: false,
+        };
+
+        let result = merge_into_main(&feature_path, Some("main"), &options)
+            .await
+            .unwrap();
+        assert!(result.success, "{}", result.message);
+        assert_eq!(result.strategy, MergeStrategy::Squash);
+
+        // One new commit on main, not a merge commit, holding both files.
+        assert_eq!(
+            git_output(&["log", "--format=%s", "main"], &feature_path),
+            "Squash branch 'feature'\nAdd c\ninit"
+        );
+        assert_eq!(
+            git_output(&["show", "--format=", "--name-only", "main"], &feature_path),
+            "a.txt\nb.txt"
+        );
+    }
+
+    #[tokio::test]
+    async fn rebase_fast_forwards_main_to_rebased_branch() {
+        let (_dir, feature_path) = diverged_repo();
+        let options = MergeOptions {
+            strategy: MergeStrategy::Rebase,
+            push: false,
+        };
+
+        let result = merge_into_main(&feature_path, Some("main"), &options)
+            .await
+            .unwrap();
+        assert!(result.success, "{}", result.message);
+
+        // Linear history, and main is exactly the rebased branch.
+        assert_eq!(
+            git_output(&["log", "--format=%s", "main"], &feature_path),
+            "Add b\nAdd a\nAdd c\ninit"
+        );
+        assert_eq!(
+            git_output(&["rev-parse", "main"], &feature_path),
+            git_output(&["rev-parse", "feature"], &feature_path)
+        );
+    }
 }
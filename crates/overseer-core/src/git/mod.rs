@@ -18,10 +18,17 @@
 //!
 //! # Modules
 //!
-//! - [`worktree`] - Git worktree management (list, add, remove)
+//! - [`worktree`] - Git worktree management (list, add, move, remove)
+//! - [`layout`] - Workspace root directory and naming templates
 //! - [`diff`] - Diff operations (changed files, file diffs)
+//! - [`patch`] - Parsed unified diffs (files, hunks, lines, word changes)
 //! - [`merge`] - Merge checking and execution
-//! - [`branch`] - Branch operations (rename, delete)
+//! - [`branch`] - Branch operations (rename, delete, list merged)
+//! - [`checkpoint`] - Per-turn snapshots of a workspace under hidden refs
+//! - [`sync`] - Bringing the default branch into a workspace (merge or rebase)
+//! - [`github`] - Pull requests, checks, review comments and issues (via `gh`)
+//! - [`plain`] - Copied workspaces for projects that aren't git repositories
+//! - [`multi`] - Workspaces with a worktree of each of several repositories
 //!
 //! # Error Handling
 //!
@@ -44,12 +51,19 @@
 //! let changes = list_changed_files("/path/to/workspace").await?;
 //!
 //! // Check if merge would succeed
-//! let result = check_merge("/path/to/workspace").await?;
+//! let result = check_merge("/path/to/workspace", None, MergeStrategy::Merge).await?;
 //! ```
 
 pub mod branch;
+pub mod checkpoint;
 pub mod diff;
+pub mod github;
+pub mod layout;
 pub mod merge;
+pub mod multi;
+pub mod patch;
+pub mod plain;
+pub mod sync;
 pub mod worktree;
 
 use std::path::{Path, PathBuf};
@@ -61,15 +75,42 @@ const GIT_TIMEOUT: Duration = Duration::from_secs(30);
 const GIT_REF_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
 
 // Re-export commonly used items
-pub use branch::{delete_branch, list_recent_branches, rename_branch};
+pub use branch::{
+    delete_branch, force_delete_branch, list_merged_branches, list_recent_branches, rename_branch,
+};
+pub use checkpoint::{
+    create_checkpoint, delete_checkpoints, diff_checkpoint, find_checkpoint, list_checkpoints,
+    restore_checkpoint, Checkpoint,
+};
 pub use diff::{
-    get_commit_diff, get_file_diff, get_submodule_file_diff, get_submodule_uncommitted_diff,
+    get_commit_diff, get_file_diff, get_structured_commit_diff, get_structured_file_diff,
+    get_structured_uncommitted_diff, get_submodule_file_diff, get_submodule_uncommitted_diff,
     get_uncommitted_diff, list_changed_files, list_commit_files, list_commits_on_branch,
-    parse_diff_name_status, ChangedFile, ChangedFilesResult, Commit, SubmoduleResult,
+    parse_diff_name_status, ChangedFile, ChangedFilesResult, Commit, DiffOptions, SubmoduleResult,
+    WhitespaceMode,
+};
+pub use github::{
+    get_issue, get_pr_head, get_pr_status, list_pr_checks, list_review_comments, list_review_prs,
+    list_review_threads, GhRunner, Issue, PrCheck, PrHead, PrStatus, ReviewComment, ReviewPr,
+    ReviewThread, ReviewThreadComment, ShellGhRunner,
+};
+pub use layout::{branch_slug, validate_naming, WorkspaceLayout, DEFAULT_NAMING};
+pub use merge::{
+    check_merge, merge_into_main, squash_commit_message, MergeOptions, MergeResult, MergeStrategy,
+};
+pub use multi::{
+    add_multi_workspace, is_multi_workspace, list_repo_pr_statuses, validate_repos,
+    workspace_repos, RepoMember, RepoPrStatus,
+};
+pub use patch::{parse_unified_diff, DiffHunk, DiffLine, DiffLineKind, DiffSegment, FileDiff};
+pub use plain::{add_plain_workspace, copy_back, is_plain_workspace, source_dir};
+pub use sync::{
+    abort_sync, continue_sync, find_conflict_markers, get_sync_state, sync_with_default_branch,
+    ConflictFile, SyncOptions, SyncResult, SyncState, SyncStatus, SyncStrategy,
 };
-pub use merge::{check_merge, merge_into_main, MergeResult};
 pub use worktree::{
-    add_workspace, archive_workspace, list_workspaces, pick_workspace_dir, WorkspaceInfo,
+    add_workspace, add_workspace_from, archive_workspace, list_workspaces, move_workspace,
+    pick_workspace_dir, plan_workspace_dir, prune_workspaces, WorkspaceInfo,
 };
 
 // ============================================================================
@@ -105,6 +146,12 @@ pub enum GitError {
         path: String,
     },
 
+    /// The workspace's project has `allowMergeToMain: false`.
+    MergeDisabled {
+        /// Name of the project
+        project: String,
+    },
+
     /// Other error with message
     Other(String),
 }
@@ -118,6 +165,10 @@ impl std::fmt::Display for GitError {
             GitError::WorktreeDirty { path } => {
                 write!(f, "WorktreeDirty: workspace '{path}' has uncommitted or untracked changes")
             }
+            GitError::MergeDisabled { project } => write!(
+                f,
+                "Merging into the default branch is disabled for project '{project}'"
+            ),
             GitError::Other(msg) => write!(f, "{msg}"),
         }
     }
@@ -180,8 +231,21 @@ impl GitOutput {
 ///
 /// The `GitOutput` from the command, or `GitError` if the command failed to run.
 pub async fn run_git(args: &[&str], cwd: &Path) -> Result<GitOutput, GitError> {
+    run_git_with_env(args, cwd, &[]).await
+}
+
+/// Like [`run_git`], with extra environment variables for the git process.
+///
+/// Used to point git at an alternate index (`GIT_INDEX_FILE`) or to supply
+/// an identity for plumbing commits.
+pub async fn run_git_with_env(
+    args: &[&str],
+    cwd: &Path,
+    env: &[(&str, &str)],
+) -> Result<GitOutput, GitError> {
     let fut = Command::new("git")
         .args(args)
+        .envs(env.iter().copied())
         .current_dir(cwd)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -357,6 +421,13 @@ pub async fn get_git_common_dir(cwd: &Path) -> Result<PathBuf, GitError> {
     Ok(std::fs::canonicalize(&absolute).unwrap_or(absolute))
 }
 
+/// Resolve a path inside the worktree's git dir (`git rev-parse --git-path`).
+pub(crate) async fn git_path(workspace_path: &Path, name: &str) -> Result<PathBuf, GitError> {
+    let path = run_git_success(&["rev-parse", "--git-path", name], workspace_path).await?;
+    // Relative paths are relative to the cwd git ran in.
+    Ok(workspace_path.join(path))
+}
+
 // ============================================================================
 // ANIMAL NAMES FOR WORKSPACES
 // ============================================================================
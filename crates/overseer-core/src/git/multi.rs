@@ -0,0 +1,772 @@
+//! Workspaces spanning several repositories.
+//!
+//! # Overview
+//!
+//! A multi-repo project lists its repositories in `repos` (see
+//! [`RepoMember`]). Its workspace is a parent directory holding one worktree
+//! per repository, all on the same branch and named after the repository:
+//!
+//! ```text
+//! ~/overseer/workspaces/app/feature-x/
+//! ├── .overseer-repos.json   # manifest
+//! ├── backend/               # worktree of ~/code/backend on feature-x
+//! └── frontend/              # worktree of ~/code/frontend on feature-x
+//! ```
+//!
+//! The agent runs in the parent, so it sees every repository at once. The
+//! manifest ([`MANIFEST_FILE`]) records the branch and where each worktree
+//! came from; it is what marks a directory as a multi-repo workspace, which
+//! [`Vcs::resolve`](crate::vcs::Vcs::resolve) turns into
+//! [`Vcs::MultiRepo`](crate::vcs::Vcs::MultiRepo), the backend that goes
+//! through this module.
+//!
+//! # Aggregation
+//!
+//! - Changed files come back with one [`SubmoduleResult`] per repository
+//!   that has changes, so they are grouped like submodules and the submodule
+//!   diff functions work with the repository name as the submodule path.
+//!   File paths prefixed with a repository name (`backend/src/lib.rs`) are
+//!   routed to that repository by the workspace-level diff functions too.
+//! - Merges are checked in every repository first and nothing lands while
+//!   any of them would conflict. Conflicts are prefixed with the repository
+//!   name and the message has a line per repository.
+//! - Pull requests are looked up per repository ([`list_repo_pr_statuses`]).
+
+use super::diff::{list_changed_files, ChangedFilesResult, SubmoduleResult};
+use super::github::{get_pr_status, GhRunner, PrStatus};
+use super::layout::WorkspaceLayout;
+use super::merge::{check_merge, merge_into_main, MergeOptions, MergeResult, MergeStrategy};
+use super::worktree::{archive_workspace, create_worktree, pick_workspace_dir};
+use super::{run_git, GitError};
+use crate::paths::{expand_home, get_home_dir};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest at the root of a multi-repo workspace.
+pub const MANIFEST_FILE: &str = ".overseer-repos.json";
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// One repository of a multi-repo project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoMember {
+    /// Directory name of the repository's worktree inside the workspace.
+    pub name: String,
+
+    /// Path to the repository's main checkout. A leading `~` is expanded and
+    /// relative paths are resolved against the project directory.
+    pub path: String,
+
+    /// Default branch of this repository, overriding the project's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_branch: Option<String>,
+}
+
+/// The manifest stored in [`MANIFEST_FILE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    /// The branch every worktree was created on.
+    branch: String,
+    /// The repositories, with absolute paths.
+    repos: Vec<RepoMember>,
+}
+
+/// The pull request of one repository in a multi-repo workspace.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoPrStatus {
+    /// Repository name (its directory in the workspace)
+    pub repo: String,
+    /// The PR opened from the workspace's branch, if any
+    pub pr: Option<PrStatus>,
+}
+
+// ============================================================================
+// WORKSPACES
+// ============================================================================
+
+/// Whether `workspace_path` is a multi-repo workspace.
+pub fn is_multi_workspace(workspace_path: &Path) -> bool {
+    workspace_path.join(MANIFEST_FILE).is_file()
+}
+
+/// The repositories of a multi-repo workspace, with absolute paths.
+pub fn workspace_repos(workspace_path: &Path) -> Result<Vec<RepoMember>, GitError> {
+    load_manifest(workspace_path).map(|manifest| manifest.repos)
+}
+
+/// Check that `repos` can share one workspace: at least one, with unique
+/// names that are plain directory names.
+pub fn validate_repos(repos: &[RepoMember]) -> Result<(), GitError> {
+    if repos.is_empty() {
+        return Err(GitError::Other(
+            "A multi-repo project needs at least one repository".to_string(),
+        ));
+    }
+    let mut seen = HashSet::new();
+    for repo in repos {
+        let name = repo.name.as_str();
+        if name.is_empty() || name.starts_with('.') || name.contains('/') || name.contains('\\') {
+            return Err(GitError::Other(format!(
+                "Invalid repository name '{name}': use a plain directory name"
+            )));
+        }
+        if !seen.insert(name) {
+            return Err(GitError::Other(format!(
+                "Repository name '{name}' is used twice"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Create a workspace with a worktree of every repository in `repos`, all
+/// on `branch`.
+///
+/// The parent directory is picked like a worktree's (see
+/// [`pick_workspace_dir`]), with the project directory standing in for the
+/// repository.
+///
+/// # Returns
+///
+/// The absolute path to the parent directory.
+///
+/// # Errors
+///
+/// Returns an error if `repos` is invalid (see [`validate_repos`]) or any
+/// worktree can't be created. The worktrees already made are removed again.
+pub async fn add_multi_workspace(
+    project_path: &Path,
+    repos: &[RepoMember],
+    branch: &str,
+    layout: &WorkspaceLayout,
+) -> Result<PathBuf, GitError> {
+    validate_repos(repos)?;
+    let home = get_home_dir().ok();
+    let repos: Vec<RepoMember> = repos
+        .iter()
+        .map(|repo| {
+            let path = match &home {
+                Some(home) => expand_home(&repo.path, home),
+                None => repo.path.clone(),
+            };
+            RepoMember {
+                path: project_path.join(path).to_string_lossy().to_string(),
+                ..repo.clone()
+            }
+        })
+        .collect();
+
+    let workspace_path = pick_workspace_dir(project_path, branch, layout)?;
+    fs::create_dir_all(&workspace_path)
+        .map_err(|e| GitError::PathError(format!("Failed to create workspace: {e}")))?;
+
+    let mut created: Vec<&RepoMember> = Vec::new();
+    for repo in &repos {
+        let worktree = workspace_path.join(&repo.name);
+        if let Err(e) = create_worktree(Path::new(&repo.path), &worktree, branch, None, false).await
+        {
+            for done in created {
+                let _ = archive_workspace(
+                    Path::new(&done.path),
+                    &workspace_path.join(&done.name),
+                    true,
+                )
+                .await;
+            }
+            let _ = fs::remove_dir_all(&workspace_path);
+            return Err(GitError::Other(format!("{}: {e}", repo.name)));
+        }
+        created.push(repo);
+    }
+
+    save_manifest(
+        &workspace_path,
+        &Manifest {
+            branch: branch.to_string(),
+            repos,
+        },
+    )?;
+
+    fs::canonicalize(&workspace_path)
+        .map_err(|e| GitError::PathError(format!("Failed to resolve path: {e}")))
+}
+
+/// Remove every worktree of a multi-repo workspace, then the workspace.
+///
+/// # Errors
+///
+/// Returns [`GitError::WorktreeDirty`] when `force` is false and a worktree
+/// has uncommitted changes or files were added next to the worktrees.
+/// Nothing is removed in that case.
+pub async fn remove_multi_workspace(workspace_path: &Path, force: bool) -> Result<(), GitError> {
+    if !force && has_changes(workspace_path).await {
+        return Err(GitError::WorktreeDirty {
+            path: workspace_path.to_string_lossy().to_string(),
+        });
+    }
+
+    for repo in workspace_repos(workspace_path)? {
+        let worktree = workspace_path.join(&repo.name);
+        if worktree.exists() {
+            archive_workspace(Path::new(&repo.path), &worktree, force)
+                .await
+                .map_err(|e| match e {
+                    GitError::WorktreeDirty { .. } => e,
+                    other => GitError::Other(format!("{}: {other}", repo.name)),
+                })?;
+        }
+    }
+
+    fs::remove_dir_all(workspace_path)
+        .map_err(|e| GitError::PathError(format!("Failed to remove workspace: {e}")))
+}
+
+/// Whether any worktree has uncommitted changes, or the workspace has
+/// files outside the worktrees.
+pub async fn has_changes(workspace_path: &Path) -> bool {
+    let Ok(repos) = workspace_repos(workspace_path) else {
+        return false;
+    };
+    let names: HashSet<&str> = repos.iter().map(|r| r.name.as_str()).collect();
+    let stray = fs::read_dir(workspace_path)
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name != MANIFEST_FILE && !names.contains(name.as_str())
+            })
+        })
+        .unwrap_or(false);
+    if stray {
+        return true;
+    }
+
+    for repo in &repos {
+        let worktree = workspace_path.join(&repo.name);
+        if let Ok(output) = run_git(&["status", "--porcelain"], &worktree).await {
+            if output.success && !output.stdout_str().trim().is_empty() {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// The repository worktree and repository-relative path for a
+/// workspace-relative `file_path` (`backend/src/lib.rs`), with the
+/// repository's default branch if it has its own.
+pub(crate) fn route_path(
+    workspace_path: &Path,
+    file_path: &str,
+) -> Option<(PathBuf, String, Option<String>)> {
+    let (name, rest) = file_path.split_once('/')?;
+    let repo = workspace_repos(workspace_path)
+        .ok()?
+        .into_iter()
+        .find(|r| r.name == name)?;
+    Some((
+        workspace_path.join(name),
+        rest.to_string(),
+        repo.main_branch,
+    ))
+}
+
+/// [`route_path`], or an error for a path outside every repository.
+pub(crate) fn route_file(
+    workspace_path: &Path,
+    file_path: &str,
+) -> Result<(PathBuf, String, Option<String>), GitError> {
+    route_path(workspace_path, file_path).ok_or_else(|| {
+        GitError::PathError(format!(
+            "'{file_path}' is not inside a repository of this workspace"
+        ))
+    })
+}
+
+// ============================================================================
+// CHANGES
+// ============================================================================
+
+/// Changed files of every repository, one [`SubmoduleResult`] each.
+///
+/// Repositories without changes are left out. `is_default_branch` is true
+/// only when every worktree is on its default branch. A repository whose
+/// changes can't be listed is reported as not initialized.
+pub async fn list_multi_changes(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+) -> Result<ChangedFilesResult, GitError> {
+    let repos = workspace_repos(workspace_path)?;
+    let mut submodules = Vec::new();
+    let mut is_default_branch = true;
+
+    for repo in repos {
+        let worktree = workspace_path.join(&repo.name);
+        let repo_main = repo.main_branch.as_deref().or(main_branch);
+        match list_changed_files(&worktree, repo_main).await {
+            Ok(result) => {
+                is_default_branch &= result.is_default_branch;
+                let sub = SubmoduleResult {
+                    name: repo.name.clone(),
+                    path: repo.name,
+                    is_initialized: true,
+                    files: result.files,
+                    uncommitted: result.uncommitted,
+                    submodules: result.submodules,
+                };
+                if sub.has_changes() {
+                    submodules.push(sub);
+                }
+            }
+            Err(e) => {
+                log::warn!("Listing changes in {} failed: {e}", repo.name);
+                is_default_branch = false;
+                submodules.push(SubmoduleResult {
+                    name: repo.name.clone(),
+                    path: repo.name,
+                    is_initialized: false,
+                    files: Vec::new(),
+                    uncommitted: Vec::new(),
+                    submodules: Vec::new(),
+                });
+            }
+        }
+    }
+
+    Ok(ChangedFilesResult {
+        files: Vec::new(),
+        uncommitted: Vec::new(),
+        is_default_branch,
+        submodules,
+    })
+}
+
+// ============================================================================
+// MERGING
+// ============================================================================
+
+/// Check whether every repository's branch would land on its default
+/// branch cleanly.
+///
+/// Succeeds when at least one repository has something to merge and none
+/// would conflict.
+pub async fn check_multi_merge(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    strategy: MergeStrategy,
+) -> Result<MergeResult, GitError> {
+    let checks = check_each(workspace_path, main_branch, strategy).await?;
+    Ok(combine(strategy, &checks))
+}
+
+/// Land every repository's branch on its default branch.
+///
+/// All repositories are checked first; if any would conflict, nothing is
+/// merged. Repositories with nothing to merge don't fail the merge. Should a merge
+/// still fail or error part way, it stops there: the repositories merged
+/// before it stay merged and the message says which.
+pub async fn merge_multi_into_main(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    options: &MergeOptions,
+) -> Result<MergeResult, GitError> {
+    let strategy = options.strategy;
+    let checks = check_each(workspace_path, main_branch, strategy).await?;
+    let checked = combine(strategy, &checks);
+    if !checked.success {
+        return Ok(checked);
+    }
+
+    let repos = workspace_repos(workspace_path)?;
+    let mut results = Vec::new();
+    for (repo, (_, check)) in repos.iter().zip(&checks) {
+        if check.already_up_to_date {
+            results.push((repo.name.clone(), check.clone()));
+            continue;
+        }
+        let worktree = workspace_path.join(&repo.name);
+        let repo_main = repo.main_branch.as_deref().or(main_branch);
+        // An error still reports what the repositories before it did.
+        let result = merge_into_main(&worktree, repo_main, options)
+            .await
+            .unwrap_or_else(|e| MergeResult::new(strategy, false, e.to_string()));
+        let failed = !result.success && !result.already_up_to_date;
+        results.push((repo.name.clone(), result));
+        if failed {
+            break;
+        }
+    }
+
+    let mut combined = combine(strategy, &results);
+    combined.pushed = results
+        .iter()
+        .filter(|(_, r)| r.success)
+        .all(|(_, r)| r.pushed)
+        && results.iter().any(|(_, r)| r.success);
+    Ok(combined)
+}
+
+/// [`check_merge`] in every repository, in manifest order.
+async fn check_each(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    strategy: MergeStrategy,
+) -> Result<Vec<(String, MergeResult)>, GitError> {
+    let mut checks = Vec::new();
+    for repo in workspace_repos(workspace_path)? {
+        let worktree = workspace_path.join(&repo.name);
+        let repo_main = repo.main_branch.as_deref().or(main_branch);
+        let result = check_merge(&worktree, repo_main, strategy)
+            .await
+            .map_err(|e| GitError::Other(format!("{}: {e}", repo.name)))?;
+        checks.push((repo.name, result));
+    }
+    Ok(checks)
+}
+
+/// One result for several repositories.
+///
+/// Fails if any repository failed for a reason other than having nothing to
+/// merge, and when all of them had nothing to merge.
+fn combine(strategy: MergeStrategy, results: &[(String, MergeResult)]) -> MergeResult {
+    let already_up_to_date = results.iter().all(|(_, r)| r.already_up_to_date);
+    let success = !already_up_to_date
+        && results
+            .iter()
+            .all(|(_, r)| r.success || r.already_up_to_date);
+    let conflicts = results
+        .iter()
+        .flat_map(|(name, r)| r.conflicts.iter().map(move |c| format!("{name}: {c}")))
+        .collect();
+    let message = results
+        .iter()
+        .map(|(name, r)| format!("{name}: {}", r.message))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    MergeResult {
+        already_up_to_date,
+        conflicts,
+        ..MergeResult::new(strategy, success, message)
+    }
+}
+
+// ============================================================================
+// PULL REQUESTS
+// ============================================================================
+
+/// The pull request opened from the workspace's branch in each repository.
+///
+/// Synchronous like the rest of [`super::github`]; async callers should wrap
+/// it in `spawn_blocking`.
+pub fn list_repo_pr_statuses(
+    runner: &dyn GhRunner,
+    workspace_path: &Path,
+) -> Result<Vec<RepoPrStatus>, GitError> {
+    let manifest = load_manifest(workspace_path)?;
+    manifest
+        .repos
+        .into_iter()
+        .map(|repo| {
+            let worktree = workspace_path.join(&repo.name);
+            let pr = get_pr_status(runner, &worktree, &manifest.branch)
+                .map_err(|e| GitError::Other(format!("{}: {e}", repo.name)))?;
+            Ok(RepoPrStatus {
+                repo: repo.name,
+                pr,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+fn load_manifest(workspace_path: &Path) -> Result<Manifest, GitError> {
+    let text = fs::read_to_string(workspace_path.join(MANIFEST_FILE)).map_err(|e| {
+        GitError::PathError(format!(
+            "Not a multi-repo workspace ({MANIFEST_FILE} unreadable): {e}"
+        ))
+    })?;
+    serde_json::from_str(&text)
+        .map_err(|e| GitError::Other(format!("Invalid {MANIFEST_FILE}: {e}")))
+}
+
+fn save_manifest(workspace_path: &Path, manifest: &Manifest) -> Result<(), GitError> {
+    let text = serde_json::to_string_pretty(manifest)
+        .map_err(|e| GitError::Other(format!("Failed to serialize {MANIFEST_FILE}: {e}")))?;
+    fs::write(workspace_path.join(MANIFEST_FILE), text)
+        .map_err(|e| GitError::PathError(format!("Failed to write {MANIFEST_FILE}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::GitOutput;
+    use tempfile::TempDir;
+
+    fn git(args: &[&str], cwd: &Path) {
+        let out = std::process::Command::new("git")
+            .args(args)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    fn git_output(args: &[&str], cwd: &Path) -> String {
+        let out = std::process::Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+
+    fn init_repo(path: &Path) {
+        fs::create_dir_all(path).unwrap();
+        git(&["init", "-q", "-b", "main"], path);
+        git(&["config", "user.email", "test@test.com"], path);
+        git(&["config", "user.name", "Test"], path);
+        git(&["config", "commit.gpgsign", "false"], path);
+        fs::write(path.join("README.md"), "readme\n").unwrap();
+        git(&["add", "."], path);
+        git(&["commit", "-q", "-m", "init"], path);
+    }
+
+    fn member(name: &str) -> RepoMember {
+        RepoMember {
+            name: name.to_string(),
+            path: name.to_string(),
+            main_branch: None,
+        }
+    }
+
+    /// A project directory holding `backend` and `frontend` repositories,
+    /// and a workspace on `feature` under a temp root.
+    async fn setup() -> (TempDir, PathBuf, PathBuf) {
+        let root = TempDir::new().unwrap();
+        let project = root.path().join("app");
+        init_repo(&project.join("backend"));
+        init_repo(&project.join("frontend"));
+
+        let layout = WorkspaceLayout {
+            root: Some(root.path().join("ws").to_string_lossy().to_string()),
+            naming: Some("{repo}/{branch}".to_string()),
+        };
+        let workspace = add_multi_workspace(
+            &project,
+            &[member("backend"), member("frontend")],
+            "feature",
+            &layout,
+        )
+        .await
+        .unwrap();
+        (root, project, workspace)
+    }
+
+    fn commit_file(worktree: &Path, file: &str, content: &str) {
+        fs::write(worktree.join(file), content).unwrap();
+        git(&["add", "."], worktree);
+        git(&["commit", "-q", "-m", &format!("Add {file}")], worktree);
+    }
+
+    #[tokio::test]
+    async fn creates_a_worktree_per_repo_on_one_branch() {
+        let (_root, _project, workspace) = setup().await;
+
+        assert!(is_multi_workspace(&workspace));
+        for name in ["backend", "frontend"] {
+            let worktree = workspace.join(name);
+            assert!(worktree.join("README.md").is_file());
+            assert_eq!(
+                git_output(&["rev-parse", "--abbrev-ref", "HEAD"], &worktree),
+                "feature"
+            );
+        }
+        let repos = workspace_repos(&workspace).unwrap();
+        assert!(Path::new(&repos[0].path).is_absolute());
+        assert!(!has_changes(&workspace).await);
+    }
+
+    #[tokio::test]
+    async fn failed_repo_rolls_back_the_workspace() {
+        let root = TempDir::new().unwrap();
+        let project = root.path().join("app");
+        init_repo(&project.join("backend"));
+        fs::create_dir_all(project.join("missing")).unwrap();
+        let layout = WorkspaceLayout {
+            root: Some(root.path().join("ws").to_string_lossy().to_string()),
+            naming: Some("{repo}/{branch}".to_string()),
+        };
+
+        let result = add_multi_workspace(
+            &project,
+            &[member("backend"), member("missing")],
+            "feature",
+            &layout,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(!root.path().join("ws/app/feature").exists());
+        assert_eq!(
+            git_output(&["worktree", "list"], &project.join("backend"))
+                .lines()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_and_nested_names() {
+        assert!(validate_repos(&[]).is_err());
+        assert!(validate_repos(&[member("a"), member("a")]).is_err());
+        assert!(validate_repos(&[member("a/b")]).is_err());
+        assert!(validate_repos(&[member(".git")]).is_err());
+        assert!(validate_repos(&[member("api"), member("web")]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn changes_are_grouped_per_repo() {
+        let (_root, _project, workspace) = setup().await;
+        commit_file(&workspace.join("backend"), "api.rs", "fn main() {}\n");
+        fs::write(workspace.join("frontend/app.ts"), "x\n").unwrap();
+
+        let result = list_multi_changes(&workspace, None).await.unwrap();
+
+        assert!(result.files.is_empty() && result.uncommitted.is_empty());
+        assert!(!result.is_default_branch);
+        let repos: Vec<_> = result
+            .submodules
+            .iter()
+            .map(|s| {
+                let files: Vec<_> = s
+                    .files
+                    .iter()
+                    .chain(&s.uncommitted)
+                    .map(|f| f.path.as_str())
+                    .collect();
+                (s.path.as_str(), files.join(","))
+            })
+            .collect();
+        assert_eq!(
+            repos,
+            vec![
+                ("backend", "api.rs".to_string()),
+                ("frontend", "app.ts".to_string())
+            ]
+        );
+        assert!(has_changes(&workspace).await);
+
+        let (worktree, file, _) = route_path(&workspace, "backend/api.rs").unwrap();
+        assert_eq!(worktree, workspace.join("backend"));
+        assert_eq!(file, "api.rs");
+        assert!(route_path(&workspace, "docs/api.rs").is_none());
+    }
+
+    #[tokio::test]
+    async fn merges_every_repo_with_changes() {
+        let (_root, project, workspace) = setup().await;
+        commit_file(&workspace.join("backend"), "api.rs", "fn main() {}\n");
+
+        let check = check_multi_merge(&workspace, None, MergeStrategy::Merge)
+            .await
+            .unwrap();
+        assert!(check.success, "{}", check.message);
+
+        let result = merge_multi_into_main(&workspace, None, &MergeOptions::default())
+            .await
+            .unwrap();
+        assert!(result.success, "{}", result.message);
+        assert!(result.message.lines().any(|l| l.starts_with("backend: ")));
+        assert!(result.message.lines().any(|l| l.starts_with("frontend: ")));
+        assert!(project.join("backend/api.rs").is_file());
+
+        let again = merge_multi_into_main(&workspace, None, &MergeOptions::default())
+            .await
+            .unwrap();
+        assert!(!again.success && again.already_up_to_date, "{again:?}");
+    }
+
+    #[tokio::test]
+    async fn conflict_in_one_repo_merges_nothing() {
+        let (_root, project, workspace) = setup().await;
+        commit_file(&workspace.join("backend"), "api.rs", "fn main() {}\n");
+        commit_file(&workspace.join("frontend"), "README.md", "workspace\n");
+        commit_file(&project.join("frontend"), "README.md", "main\n");
+
+        let result = merge_multi_into_main(&workspace, None, &MergeOptions::default())
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(!result.conflicts.is_empty());
+        assert!(result.conflicts.iter().all(|c| c.starts_with("frontend: ")));
+        assert!(!project.join("backend/api.rs").exists());
+    }
+
+    #[tokio::test]
+    async fn removing_needs_force_for_uncommitted_work() {
+        let (_root, project, workspace) = setup().await;
+        fs::write(workspace.join("frontend/wip.ts"), "wip\n").unwrap();
+
+        let result = remove_multi_workspace(&workspace, false).await;
+        assert!(matches!(result, Err(GitError::WorktreeDirty { .. })));
+        assert!(workspace.join("backend").exists());
+
+        remove_multi_workspace(&workspace, true).await.unwrap();
+        assert!(!workspace.exists());
+        assert_eq!(
+            git_output(&["worktree", "list"], &project.join("frontend"))
+                .lines()
+                .count(),
+            1
+        );
+    }
+
+    struct FakeGh;
+
+    impl GhRunner for FakeGh {
+        fn run(&self, _args: &[String], cwd: &Path) -> Result<GitOutput, GitError> {
+            if cwd.ends_with("backend") {
+                Ok(GitOutput {
+                    success: true,
+                    stdout: br#"{"number":7,"state":"OPEN","url":"https://github.com/o/backend/pull/7","isDraft":false}"#.to_vec(),
+                    stderr: Vec::new(),
+                })
+            } else {
+                Ok(GitOutput {
+                    success: false,
+                    stdout: Vec::new(),
+                    stderr: b"no pull requests found for branch \"feature\"".to_vec(),
+                })
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn pr_status_per_repo() {
+        let (_root, _project, workspace) = setup().await;
+
+        let statuses = list_repo_pr_statuses(&FakeGh, &workspace).unwrap();
+
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].repo, "backend");
+        assert_eq!(statuses[0].pr.as_ref().map(|p| p.number), Some(7));
+        assert_eq!(statuses[1].repo, "frontend");
+        assert!(statuses[1].pr.is_none());
+    }
+}
@@ -0,0 +1,723 @@
+//! Parsed unified diffs.
+//!
+//! # Overview
+//!
+//! The raw diff functions in [`diff`](super::diff) return `git diff` text,
+//! which every client used to re-parse. This module turns that text into a
+//! serializable model:
+//!
+//! ```text
+//! FileDiff ─┬─ DiffHunk ─┬─ DiffLine (context / added / removed)
+//!           │            │    └─ DiffSegment (intraline change)
+//!           │            └─ ...
+//!           └─ ...
+//! ```
+//!
+//! Every line carries its old and new line numbers, which is what anchoring
+//! a review comment to a line needs: a comment on an added or context line
+//! anchors to `newLine`, one on a removed line to `oldLine`.
+//!
+//! # Intraline Changes
+//!
+//! Within a hunk, a run of removed lines directly followed by a run of added
+//! lines is treated as an edit. The lines are paired up in order and each pair
+//! is diffed word by word (see [`word_diff`]), so the UI can highlight just
+//! the words that changed. Lines that share nothing, or are too long to diff
+//! cheaply, get no segments and should be highlighted as a whole.
+
+use serde::Serialize;
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// One file's section of a unified diff.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiff {
+    /// Path before the change (`None` for added files)
+    pub old_path: Option<String>,
+    /// Path after the change (`None` for deleted files)
+    pub new_path: Option<String>,
+    /// Status code, as in [`ChangedFile::status`](super::ChangedFile::status)
+    pub status: String,
+    /// Whether git reported the file as binary (no hunks)
+    pub is_binary: bool,
+    /// Changed regions, in file order
+    pub hunks: Vec<DiffHunk>,
+}
+
+impl FileDiff {
+    /// The path to show for this file: the new path, or the old one if the
+    /// file was deleted.
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// A `@@ -a,b +c,d @@` block.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    /// First line of the hunk in the old file
+    pub old_start: u32,
+    /// Number of old-file lines the hunk covers
+    pub old_lines: u32,
+    /// First line of the hunk in the new file
+    pub new_start: u32,
+    /// Number of new-file lines the hunk covers
+    pub new_lines: u32,
+    /// Text after the closing `@@` (usually the enclosing function)
+    pub section: String,
+    /// The hunk's lines
+    pub lines: Vec<DiffLine>,
+}
+
+/// Which side(s) of the diff a line belongs to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffLineKind {
+    /// Unchanged, present on both sides
+    #[default]
+    Context,
+    /// Only in the new file
+    Added,
+    /// Only in the old file
+    Removed,
+}
+
+/// A single line of a hunk.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    /// Context, added or removed
+    pub kind: DiffLineKind,
+    /// Line text without the leading ` `/`+`/`-` marker or trailing newline
+    pub content: String,
+    /// Line number in the old file (`None` for added lines)
+    pub old_line: Option<u32>,
+    /// Line number in the new file (`None` for removed lines)
+    pub new_line: Option<u32>,
+    /// Git printed `\ No newline at end of file` after this line
+    pub no_newline: bool,
+    /// Word-level breakdown of `content`; empty when the whole line changed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<DiffSegment>,
+}
+
+/// A run of text within a changed line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSegment {
+    /// The text of this run
+    pub text: String,
+    /// Whether this run differs from the paired line
+    pub changed: bool,
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+/// Parse `git diff` output (one or more files) into [`FileDiff`]s.
+///
+/// Understands the extended headers git emits (`new file mode`,
+/// `rename from`, `Binary files ... differ`, ...) and fills in intraline
+/// segments for edited lines. Anything unrecognised outside a hunk is
+/// ignored, so output from `git show --format=` parses the same way.
+pub fn parse_unified_diff(text: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut current: Option<FileDiff> = None;
+    // Hunk lines still expected on each side; while either is non-zero,
+    // lines belong to the hunk even if they look like headers (`--- x`).
+    let mut old_remaining = 0u32;
+    let mut new_remaining = 0u32;
+    let mut old_line = 0u32;
+    let mut new_line = 0u32;
+
+    for line in text.lines() {
+        if old_remaining > 0 || new_remaining > 0 {
+            let Some(file) = current.as_mut() else {
+                break;
+            };
+            let Some(hunk) = file.hunks.last_mut() else {
+                break;
+            };
+            // Some tools strip the trailing space off empty context lines.
+            let (marker, content) = match line.chars().next() {
+                Some(c @ (' ' | '+' | '-')) => (c, &line[1..]),
+                None => (' ', ""),
+                Some('\\') => {
+                    if let Some(last) = hunk.lines.last_mut() {
+                        last.no_newline = true;
+                    }
+                    continue;
+                }
+                Some(_) => {
+                    // Truncated hunk; fall through to header parsing.
+                    old_remaining = 0;
+                    new_remaining = 0;
+                    ('\0', "")
+                }
+            };
+            let diff_line = match marker {
+                ' ' => {
+                    let l = DiffLine {
+                        kind: DiffLineKind::Context,
+                        content: content.to_string(),
+                        old_line: Some(old_line),
+                        new_line: Some(new_line),
+                        ..Default::default()
+                    };
+                    old_line += 1;
+                    new_line += 1;
+                    old_remaining = old_remaining.saturating_sub(1);
+                    new_remaining = new_remaining.saturating_sub(1);
+                    Some(l)
+                }
+                '-' => {
+                    let l = DiffLine {
+                        kind: DiffLineKind::Removed,
+                        content: content.to_string(),
+                        old_line: Some(old_line),
+                        ..Default::default()
+                    };
+                    old_line += 1;
+                    old_remaining = old_remaining.saturating_sub(1);
+                    Some(l)
+                }
+                '+' => {
+                    let l = DiffLine {
+                        kind: DiffLineKind::Added,
+                        content: content.to_string(),
+                        new_line: Some(new_line),
+                        ..Default::default()
+                    };
+                    new_line += 1;
+                    new_remaining = new_remaining.saturating_sub(1);
+                    Some(l)
+                }
+                _ => None,
+            };
+            if let Some(diff_line) = diff_line {
+                hunk.lines.push(diff_line);
+                continue;
+            }
+        }
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            files.extend(current.take());
+            let (old_path, new_path) = parse_git_header_paths(rest);
+            current = Some(FileDiff {
+                old_path,
+                new_path,
+                status: "M".to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("\\ ") {
+            // "\ No newline at end of file" right after a hunk's last line
+            if rest.starts_with("No newline") {
+                if let Some(last) = current
+                    .as_mut()
+                    .and_then(|f| f.hunks.last_mut())
+                    .and_then(|h| h.lines.last_mut())
+                {
+                    last.no_newline = true;
+                }
+            }
+            continue;
+        }
+
+        if line.starts_with("@@") {
+            let Some(hunk) = parse_hunk_header(line) else {
+                continue;
+            };
+            let file = current.get_or_insert_with(|| FileDiff {
+                status: "M".to_string(),
+                ..Default::default()
+            });
+            old_line = hunk.old_start;
+            new_line = hunk.new_start;
+            old_remaining = hunk.old_lines;
+            new_remaining = hunk.new_lines;
+            file.hunks.push(hunk);
+            continue;
+        }
+
+        let Some(file) = current.as_mut() else {
+            continue;
+        };
+
+        if line.starts_with("new file mode") {
+            file.status = "A".to_string();
+            file.old_path = None;
+        } else if line.starts_with("deleted file mode") {
+            file.status = "D".to_string();
+            file.new_path = None;
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            file.status = "R".to_string();
+            file.old_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            file.new_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("copy from ") {
+            file.status = "C".to_string();
+            file.old_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("copy to ") {
+            file.new_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            file.old_path = parse_marker_path(path, "a/");
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            file.new_path = parse_marker_path(path, "b/");
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.is_binary = true;
+        }
+    }
+
+    files.extend(current);
+    for file in &mut files {
+        for hunk in &mut file.hunks {
+            add_intraline_segments(&mut hunk.lines);
+        }
+    }
+    files
+}
+
+/// Parse `@@ -a[,b] +c[,d] @@ section`. Counts default to 1 when omitted.
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, section) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+
+    fn range(s: &str) -> Option<(u32, u32)> {
+        match s.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((s.parse().ok()?, 1)),
+        }
+    }
+
+    let (old_start, old_lines) = range(old)?;
+    let (new_start, new_lines) = range(new)?;
+    Some(DiffHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        section: section.trim_start().to_string(),
+        lines: Vec::new(),
+    })
+}
+
+/// Best-effort paths from `diff --git a/<old> b/<new>`.
+///
+/// Ambiguous when paths contain ` b/`; the `---`/`+++` and rename headers
+/// that follow override these when present.
+fn parse_git_header_paths(rest: &str) -> (Option<String>, Option<String>) {
+    if rest.starts_with('"') {
+        // Quoted paths: "a/x y" "b/x y"
+        let mut parts = rest.splitn(2, "\" ");
+        let old = parts.next().map(|p| format!("{p}\""));
+        let new = parts.next();
+        return (
+            old.and_then(|p| parse_marker_path(&p, "a/")),
+            new.and_then(|p| parse_marker_path(p, "b/")),
+        );
+    }
+    match rest.split_once(" b/") {
+        Some((old, new)) => (
+            Some(old.strip_prefix("a/").unwrap_or(old).to_string()),
+            Some(new.to_string()),
+        ),
+        None => (None, None),
+    }
+}
+
+/// Path from a `--- a/x` / `+++ b/x` line; `None` for `/dev/null`.
+fn parse_marker_path(path: &str, prefix: &str) -> Option<String> {
+    // git appends a tab when the path contains spaces
+    let path = path.trim_end_matches('\t');
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = unquote_path(path);
+    Some(
+        path.strip_prefix(prefix)
+            .map(str::to_string)
+            .unwrap_or(path),
+    )
+}
+
+/// Undo git's C-style quoting of unusual paths (`"t\303\251st.txt"`).
+fn unquote_path(path: &str) -> String {
+    let Some(inner) = path.strip_prefix('"').and_then(|p| p.strip_suffix('"')) else {
+        return path.to_string();
+    };
+
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.bytes().peekable();
+    while let Some(b) = chars.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        match chars.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(d @ b'0'..=b'7') => {
+                let mut value = u32::from(d - b'0');
+                for _ in 0..2 {
+                    match chars.peek() {
+                        Some(&o @ b'0'..=b'7') => {
+                            value = value * 8 + u32::from(o - b'0');
+                            chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => bytes.push(other),
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+// ============================================================================
+// INTRALINE DIFF
+// ============================================================================
+
+/// Longest line (in bytes) that gets a word diff.
+const MAX_INTRALINE_LEN: usize = 1000;
+
+/// Largest token-count product the LCS table may have.
+const MAX_INTRALINE_CELLS: usize = 40_000;
+
+/// Pair each run of removed lines with the added lines right after it and
+/// fill in their segments.
+fn add_intraline_segments(lines: &mut [DiffLine]) {
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].kind != DiffLineKind::Removed {
+            i += 1;
+            continue;
+        }
+        let removed_start = i;
+        while i < lines.len() && lines[i].kind == DiffLineKind::Removed {
+            i += 1;
+        }
+        let added_start = i;
+        while i < lines.len() && lines[i].kind == DiffLineKind::Added {
+            i += 1;
+        }
+
+        let pairs = (added_start - removed_start).min(i - added_start);
+        for k in 0..pairs {
+            let (old, new) = (removed_start + k, added_start + k);
+            if let Some((old_segments, new_segments)) =
+                word_diff(&lines[old].content, &lines[new].content)
+            {
+                lines[old].segments = old_segments;
+                lines[new].segments = new_segments;
+            }
+        }
+    }
+}
+
+/// Diff two lines word by word.
+///
+/// Returns the segments of `old` and of `new`, or `None` when the lines are
+/// too long or share no words (highlighting the whole line is clearer then).
+pub fn word_diff(old: &str, new: &str) -> Option<(Vec<DiffSegment>, Vec<DiffSegment>)> {
+    if old.len() > MAX_INTRALINE_LEN || new.len() > MAX_INTRALINE_LEN {
+        return None;
+    }
+    let a = tokenize(old);
+    let b = tokenize(new);
+    if a.len() * b.len() > MAX_INTRALINE_CELLS {
+        return None;
+    }
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u16; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut old_segments = Vec::new();
+    let mut new_segments = Vec::new();
+    let mut shared_word = false;
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            shared_word |= !a[i].trim().is_empty();
+            push_segment(&mut old_segments, a[i], false);
+            push_segment(&mut new_segments, b[j], false);
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            push_segment(&mut new_segments, b[j], true);
+            j += 1;
+        } else {
+            push_segment(&mut old_segments, a[i], true);
+            i += 1;
+        }
+    }
+
+    shared_word.then_some((old_segments, new_segments))
+}
+
+/// Split a line into words, whitespace runs and single punctuation chars.
+fn tokenize(line: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    fn class(c: char) -> Class {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<Class> = None;
+    for (idx, c) in line.char_indices() {
+        let cls = class(c);
+        let joins = prev.as_ref() == Some(&cls) && cls != Class::Other;
+        if !joins && idx > start {
+            tokens.push(&line[start..idx]);
+            start = idx;
+        }
+        prev = Some(cls);
+    }
+    if start < line.len() {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+/// Append `text`, merging with the previous segment if `changed` matches.
+fn push_segment(segments: &mut Vec<DiffSegment>, text: &str, changed: bool) {
+    match segments.last_mut() {
+        Some(last) if last.changed == changed => last.text.push_str(text),
+        _ => segments.push(DiffSegment {
+            text: text.to_string(),
+            changed,
+        }),
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODIFIED: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,4 +1,5 @@ mod foo;
+ fn main() {
+-    let x = 1;
++    let x = 2;
++    println!(\"{x}\");
+ }
+
+";
+
+    #[test]
+    fn parses_modified_file_with_line_numbers() {
+        let files = parse_unified_diff(MODIFIED);
+        assert_eq!(files.len(), 1);
+        let file = &files[0];
+        assert_eq!(file.status, "M");
+        assert_eq!(file.old_path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(file.new_path.as_deref(), Some("src/lib.rs"));
+
+        let hunk = &file.hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines), (1, 4));
+        assert_eq!((hunk.new_start, hunk.new_lines), (1, 5));
+        assert_eq!(hunk.section, "mod foo;");
+
+        let numbers: Vec<_> = hunk
+            .lines
+            .iter()
+            .map(|l| (l.kind, l.old_line, l.new_line))
+            .collect();
+        assert_eq!(
+            numbers,
+            vec![
+                (DiffLineKind::Context, Some(1), Some(1)),
+                (DiffLineKind::Removed, Some(2), None),
+                (DiffLineKind::Added, None, Some(2)),
+                (DiffLineKind::Added, None, Some(3)),
+                (DiffLineKind::Context, Some(3), Some(4)),
+                (DiffLineKind::Context, Some(4), Some(5)),
+            ]
+        );
+        assert_eq!(hunk.lines[4].content, "}");
+        assert_eq!(hunk.lines[5].content, "");
+    }
+
+    #[test]
+    fn edited_lines_get_word_segments() {
+        let files = parse_unified_diff(MODIFIED);
+        let lines = &files[0].hunks[0].lines;
+
+        let changed: Vec<_> = lines[1]
+            .segments
+            .iter()
+            .filter(|s| s.changed)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(changed, vec!["1"]);
+        let changed: Vec<_> = lines[2]
+            .segments
+            .iter()
+            .filter(|s| s.changed)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(changed, vec!["2"]);
+        // The unpaired added line is highlighted whole.
+        assert!(lines[3].segments.is_empty());
+    }
+
+    #[test]
+    fn parses_added_deleted_and_binary_files() {
+        let text = "\
+diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..3b18e51
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1 @@
++hello
+\\ No newline at end of file
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+index 3b18e51..0000000
+--- a/old.txt
++++ /dev/null
+@@ -1,2 +0,0 @@
+-one
+-two
+diff --git a/logo.png b/logo.png
+index 1111111..2222222 100644
+Binary files a/logo.png and b/logo.png differ
+";
+        let files = parse_unified_diff(text);
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(files[0].status, "A");
+        assert_eq!(files[0].old_path, None);
+        assert_eq!(files[0].path(), "new.txt");
+        let line = &files[0].hunks[0].lines[0];
+        assert_eq!((line.new_line, line.no_newline), (Some(1), true));
+
+        assert_eq!(files[1].status, "D");
+        assert_eq!(files[1].new_path, None);
+        assert_eq!(files[1].path(), "old.txt");
+        assert_eq!(files[1].hunks[0].lines.len(), 2);
+
+        assert!(files[2].is_binary);
+        assert!(files[2].hunks.is_empty());
+    }
+
+    #[test]
+    fn parses_renames_and_quoted_paths() {
+        let text = "\
+diff --git a/old name.rs b/new name.rs
+similarity index 90%
+rename from old name.rs
+rename to new name.rs
+diff --git \"a/t\\303\\251st.txt\" \"b/t\\303\\251st.txt\"
+index 1111111..2222222 100644
+--- \"a/t\\303\\251st.txt\"
++++ \"b/t\\303\\251st.txt\"
+@@ -1 +1 @@
+-a
++b
+";
+        let files = parse_unified_diff(text);
+        assert_eq!(files[0].status, "R");
+        assert_eq!(files[0].old_path.as_deref(), Some("old name.rs"));
+        assert_eq!(files[0].new_path.as_deref(), Some("new name.rs"));
+        assert_eq!(files[1].path(), "tést.txt");
+    }
+
+    #[test]
+    fn header_lookalikes_inside_hunks_are_content() {
+        let text = "\
+diff --git a/a.md b/a.md
+--- a/a.md
++++ b/a.md
+@@ -1,2 +1,2 @@
+--- a/heading
++++ b/heading
+ same
+";
+        let lines = &parse_unified_diff(text)[0].hunks[0].lines;
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].kind, DiffLineKind::Removed);
+        assert_eq!(lines[0].content, "-- a/heading");
+        assert_eq!(lines[1].kind, DiffLineKind::Added);
+    }
+
+    #[test]
+    fn word_diff_skips_unrelated_lines() {
+        assert!(word_diff("alpha beta", "gamma delta").is_none());
+
+        let (old, new) = word_diff("foo(bar, baz)", "foo(bar, qux)").unwrap();
+        assert_eq!(
+            old,
+            vec![
+                DiffSegment {
+                    text: "foo(bar, ".to_string(),
+                    changed: false
+                },
+                DiffSegment {
+                    text: "baz".to_string(),
+                    changed: true
+                },
+                DiffSegment {
+                    text: ")".to_string(),
+                    changed: false
+                },
+            ]
+        );
+        assert_eq!(new[1].text, "qux");
+    }
+
+    #[test]
+    fn serializes_camel_case() {
+        let json = serde_json::to_value(&parse_unified_diff(MODIFIED)[0]).unwrap();
+        let line = &json["hunks"][0]["lines"][1];
+        assert_eq!(line["kind"], "removed");
+        assert_eq!(line["oldLine"], 2);
+        assert!(line["newLine"].is_null());
+        assert!(json["hunks"][0]["lines"][0].get("segments").is_none());
+    }
+}
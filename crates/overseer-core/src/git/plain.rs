@@ -0,0 +1,904 @@
+//! Workspaces for projects that aren't git repositories.
+//!
+//! # Overview
+//!
+//! A plain directory has no branches or worktrees, so a workspace is a copy
+//! of the project directory. The copy is copy-on-write where the filesystem
+//! supports it (`cp -c` on APFS, `cp --reflink=auto` on Btrfs/XFS) and a
+//! regular recursive copy otherwise.
+//!
+//! Next to the copied files, the workspace holds a base manifest
+//! ([`BASE_FILE`]): the source directory and the size and modification time
+//! of every file on both sides at copy time. The manifest is what marks a
+//! directory as a plain workspace, which
+//! [`Vcs::resolve`](crate::vcs::Vcs::resolve) turns into
+//! [`Vcs::Plain`](crate::vcs::Vcs::Plain), the backend that goes through
+//! this module.
+//!
+//! # Changes
+//!
+//! A file is changed when its contents differ from the same file in the
+//! source directory. The manifest only short-cuts the comparison: a file
+//! whose size and modification time still match the copy is skipped without
+//! being read, which matters for large data folders.
+//!
+//! # Copying Back
+//!
+//! "Merging" copies added and modified files back to the source directory
+//! and deletes the files removed in the workspace. A change is a conflict
+//! when its source file was also touched since the copy (its size or
+//! modification time no longer match the manifest), or appeared there since,
+//! and nothing is copied while there are conflicts.
+//!
+//! Reported conflicts are noted in the manifest with the source's stamp and a
+//! digest of the workspace's version. Rewriting the workspace's file (with
+//! the source's changes folded in) resolves it: its contents no longer match
+//! the digest. A source that changes again is a new conflict.
+
+use super::diff::{untracked_file, ChangedFile, ChangedFilesResult, DiffOptions};
+use super::layout::WorkspaceLayout;
+use super::merge::{MergeResult, MergeStrategy};
+use super::worktree::pick_workspace_dir;
+use super::{run_git, GitError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use sha2::{Digest, Sha256};
+
+/// Name of the base manifest at the root of a plain workspace.
+pub const BASE_FILE: &str = ".overseer-base.json";
+
+/// Bytes compared at a time when checking whether two files differ.
+const COMPARE_CHUNK: usize = 64 * 1024;
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Size and modification time (nanoseconds since the epoch) of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Stamp {
+    size: u64,
+    modified: u64,
+}
+
+/// A file as it was in the source directory and in the copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct BaseEntry {
+    source: Stamp,
+    copy: Stamp,
+}
+
+/// The base manifest stored in [`BASE_FILE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Base {
+    /// The project directory the workspace was copied from.
+    source: PathBuf,
+    /// Files at copy time, keyed by `/`-separated relative path.
+    files: BTreeMap<String, BaseEntry>,
+    /// Conflicts reported so far, keyed like `files`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    conflicts: BTreeMap<String, SeenConflict>,
+}
+
+/// A conflict as it was when it was reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SeenConflict {
+    /// The source file then (`None` if it was deleted).
+    source: Option<Stamp>,
+    /// SHA-256 of the workspace's file then, hex encoded.
+    copy: String,
+}
+
+/// A file that differs from the source directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Change {
+    /// `A`, `M` or `D`.
+    status: &'static str,
+    path: String,
+    /// The source file was also touched since the copy.
+    conflict: bool,
+}
+
+// ============================================================================
+// WORKSPACES
+// ============================================================================
+
+/// Whether `workspace_path` is a plain (copied) workspace.
+pub fn is_plain_workspace(workspace_path: &Path) -> bool {
+    workspace_path.join(BASE_FILE).is_file()
+}
+
+/// The directory a plain workspace was copied from.
+pub fn source_dir(workspace_path: &Path) -> Option<PathBuf> {
+    load_base(workspace_path).ok().map(|base| base.source)
+}
+
+/// Create a workspace for the plain directory `project_path` by copying it.
+///
+/// The directory is picked like a worktree's (see [`pick_workspace_dir`]),
+/// with `name` standing in for the branch.
+///
+/// # Returns
+///
+/// The absolute path to the new workspace.
+///
+/// # Errors
+///
+/// Returns an error if no directory can be picked or the copy fails. A
+/// half-made copy is removed.
+pub async fn add_plain_workspace(
+    project_path: &Path,
+    name: &str,
+    layout: &WorkspaceLayout,
+) -> Result<PathBuf, GitError> {
+    let source = fs::canonicalize(project_path)
+        .map_err(|e| GitError::PathError(format!("Failed to resolve project path: {e}")))?;
+    let workspace_path = pick_workspace_dir(&source, name, layout)?;
+
+    if let Err(e) = copy_tree(&source, &workspace_path).await {
+        let _ = fs::remove_dir_all(&workspace_path);
+        return Err(e);
+    }
+
+    let workspace = workspace_path.clone();
+    blocking(move || {
+        let base = Base {
+            files: snapshot(&source, &workspace),
+            conflicts: BTreeMap::new(),
+            source,
+        };
+        save_base(&workspace, &base)
+    })
+    .await?;
+
+    fs::canonicalize(&workspace_path)
+        .map_err(|e| GitError::PathError(format!("Failed to resolve path: {e}")))
+}
+
+/// Delete a plain workspace.
+///
+/// # Errors
+///
+/// Returns [`GitError::WorktreeDirty`] when `force` is false and the
+/// workspace has changes that weren't copied back.
+pub async fn remove_plain_workspace(workspace_path: &Path, force: bool) -> Result<(), GitError> {
+    if !force && has_changes(workspace_path).await {
+        return Err(GitError::WorktreeDirty {
+            path: workspace_path.to_string_lossy().to_string(),
+        });
+    }
+    fs::remove_dir_all(workspace_path)
+        .map_err(|e| GitError::PathError(format!("Failed to remove workspace: {e}")))
+}
+
+/// Whether a plain workspace has changes that weren't copied back.
+pub async fn has_changes(workspace_path: &Path) -> bool {
+    let workspace = workspace_path.to_path_buf();
+    blocking(move || {
+        let base = load_base(&workspace)?;
+        Ok(!changes(&workspace, &base).is_empty())
+    })
+    .await
+    .unwrap_or(false)
+}
+
+// ============================================================================
+// CHANGES AND DIFFS
+// ============================================================================
+
+/// The files that differ from the source directory.
+///
+/// All of them are reported as branch changes (`files`), sorted by path,
+/// with line counts for text files.
+pub async fn list_plain_changes(workspace_path: &Path) -> Result<ChangedFilesResult, GitError> {
+    let workspace = workspace_path.to_path_buf();
+    let (source, found) = blocking(move || {
+        let base = load_base(&workspace)?;
+        let found = changes(&workspace, &base);
+        Ok((base.source, found))
+    })
+    .await?;
+
+    let mut files = Vec::with_capacity(found.len());
+    for change in found {
+        files.push(changed_file(workspace_path, &source, &change).await);
+    }
+
+    Ok(ChangedFilesResult {
+        files,
+        uncommitted: Vec::new(),
+        is_default_branch: false,
+        submodules: Vec::new(),
+    })
+}
+
+/// Unified diff of one file against the source directory.
+///
+/// Uses `git diff --no-index`, which needs no repository. Paths in the
+/// headers are relative to the workspace, as in a worktree diff.
+pub async fn plain_file_diff(
+    workspace_path: &Path,
+    file_path: &str,
+    file_status: &str,
+    options: &DiffOptions,
+) -> Result<String, GitError> {
+    let base = load_base(workspace_path)?;
+    let source_file = base.source.join(file_path).to_string_lossy().to_string();
+    let (old, new) = match file_status {
+        "A" | "?" => ("/dev/null", file_path),
+        "D" => (source_file.as_str(), "/dev/null"),
+        _ => (source_file.as_str(), file_path),
+    };
+
+    let mut args = vec!["diff", "--no-index"];
+    let option_args = options.git_args();
+    args.extend(option_args.iter().map(String::as_str));
+    args.extend(["--", old, new]);
+
+    // Exits with 1 when the files differ (expected).
+    let output = run_git(&args, workspace_path).await?;
+    let source = base.source.to_string_lossy();
+    let source = source.trim_start_matches('/');
+    Ok(output
+        .stdout_str()
+        .replace(&format!("a/{source}/"), "a/")
+        .replace(&format!("b/{source}/"), "b/"))
+}
+
+/// A [`ChangedFile`] for `change`, with line counts where they're cheap.
+async fn changed_file(workspace_path: &Path, source: &Path, change: &Change) -> ChangedFile {
+    match change.status {
+        "A" => ChangedFile {
+            status: "A".to_string(),
+            ..untracked_file(workspace_path, &change.path)
+        },
+        "D" => {
+            let counted = untracked_file(source, &change.path);
+            ChangedFile {
+                status: "D".to_string(),
+                additions: counted.additions.map(|_| 0),
+                deletions: counted.additions,
+                ..counted
+            }
+        }
+        _ => {
+            let mut file = ChangedFile::new(change.status, &change.path);
+            let source_file = source.join(&change.path).to_string_lossy().to_string();
+            let numstat = run_git(
+                &[
+                    "diff",
+                    "--no-index",
+                    "--numstat",
+                    "--",
+                    &source_file,
+                    &change.path,
+                ],
+                workspace_path,
+            )
+            .await;
+            if let Ok(output) = numstat {
+                let stdout = output.stdout_str();
+                let mut fields = stdout.split('\t');
+                match (fields.next(), fields.next()) {
+                    (Some("-"), Some("-")) => file.is_binary = true,
+                    (Some(added), Some(removed)) => {
+                        file.additions = added.parse().ok();
+                        file.deletions = removed.parse().ok();
+                    }
+                    _ => {}
+                }
+            }
+            file
+        }
+    }
+}
+
+// ============================================================================
+// COPYING BACK
+// ============================================================================
+
+/// Check whether the workspace's changes can be copied back.
+///
+/// Fails with the conflicting paths when a changed file was also touched in
+/// the source directory since the copy.
+pub async fn check_copy_back(workspace_path: &Path) -> Result<MergeResult, GitError> {
+    let workspace = workspace_path.to_path_buf();
+    blocking(move || {
+        let mut base = load_base(&workspace)?;
+        let found = changes(&workspace, &base);
+        if found.is_empty() {
+            return Ok(nothing_to_copy(&base.source));
+        }
+        if let Some(result) = conflicts(&workspace, &mut base, &found)? {
+            return Ok(result);
+        }
+        let source = base.source.display();
+        Ok(MergeResult::new(
+            MergeStrategy::Merge,
+            true,
+            format!(
+                "{} changed file(s) can be copied back to '{source}'.",
+                found.len()
+            ),
+        ))
+    })
+    .await
+}
+
+/// Copy the workspace's changes back to the source directory.
+///
+/// Added and modified files are copied over, deleted ones removed. Nothing
+/// is copied while there are conflicts. Afterwards the copied files count as
+/// unchanged, so the workspace can be archived without a prompt.
+pub async fn copy_back(workspace_path: &Path) -> Result<MergeResult, GitError> {
+    let workspace = workspace_path.to_path_buf();
+    blocking(move || {
+        let mut base = load_base(&workspace)?;
+        let found = changes(&workspace, &base);
+        if found.is_empty() {
+            return Ok(nothing_to_copy(&base.source));
+        }
+        if let Some(result) = conflicts(&workspace, &mut base, &found)? {
+            return Ok(result);
+        }
+
+        base.conflicts.clear();
+        for change in &found {
+            let source_file = base.source.join(&change.path);
+            let copy_file = workspace.join(&change.path);
+            if change.status == "D" {
+                fs::remove_file(&source_file).map_err(|e| {
+                    GitError::PathError(format!("Failed to delete {}: {e}", change.path))
+                })?;
+                base.files.remove(&change.path);
+                continue;
+            }
+            if let Some(parent) = source_file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&copy_file, &source_file)
+                .map_err(|e| GitError::PathError(format!("Failed to copy {}: {e}", change.path)))?;
+            if let (Some(source), Some(copy)) = (stamp(&source_file), stamp(&copy_file)) {
+                base.files
+                    .insert(change.path.clone(), BaseEntry { source, copy });
+            }
+        }
+        save_base(&workspace, &base)?;
+
+        Ok(MergeResult::new(
+            MergeStrategy::Merge,
+            true,
+            format!(
+                "Copied {} changed file(s) back to '{}'.",
+                found.len(),
+                base.source.display()
+            ),
+        ))
+    })
+    .await
+}
+
+fn nothing_to_copy(source: &Path) -> MergeResult {
+    MergeResult {
+        already_up_to_date: true,
+        ..MergeResult::new(
+            MergeStrategy::Merge,
+            false,
+            format!(
+                "Nothing to copy back — no files differ from '{}'.",
+                source.display()
+            ),
+        )
+    }
+}
+
+/// A failed result listing the conflicting paths, if there are any. New
+/// conflicts are noted in the manifest (see the module docs).
+fn conflicts(
+    workspace: &Path,
+    base: &mut Base,
+    found: &[Change],
+) -> Result<Option<MergeResult>, GitError> {
+    let paths: Vec<String> = found
+        .iter()
+        .filter(|c| c.conflict)
+        .map(|c| c.path.clone())
+        .collect();
+    if paths.is_empty() {
+        return Ok(None);
+    }
+
+    let mut noted = false;
+    for path in &paths {
+        let source = stamp(&base.source.join(path));
+        if base
+            .conflicts
+            .get(path)
+            .is_some_and(|seen| seen.source == source)
+        {
+            continue;
+        }
+        let Some(copy) = digest(&workspace.join(path)) else {
+            continue;
+        };
+        base.conflicts
+            .insert(path.clone(), SeenConflict { source, copy });
+        noted = true;
+    }
+    if noted {
+        save_base(workspace, base)?;
+    }
+
+    Ok(Some(MergeResult::conflicts(
+        MergeStrategy::Merge,
+        paths,
+        format!(
+            "Files changed in the workspace were also changed in '{}' since it was copied.",
+            base.source.display()
+        ),
+    )))
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// Compare the workspace with the source directory.
+fn changes(workspace: &Path, base: &Base) -> Vec<Change> {
+    let mut copy_files = BTreeMap::new();
+    walk(workspace, workspace, &mut copy_files);
+    copy_files.remove(BASE_FILE);
+
+    let mut found = Vec::new();
+    for (path, copy) in &copy_files {
+        let entry = base.files.get(path);
+        if entry.is_some_and(|e| e.copy == *copy) {
+            continue;
+        }
+        let source_file = base.source.join(path);
+        if same_content(&source_file, &workspace.join(path)) {
+            continue;
+        }
+        let current = stamp(&source_file);
+        // Touched in the source since the copy, or added there too.
+        let source_changed = match entry {
+            Some(entry) => current != Some(entry.source),
+            None => current.is_some(),
+        };
+        found.push(Change {
+            status: if entry.is_some() { "M" } else { "A" },
+            path: path.clone(),
+            conflict: source_changed && !resolved(base, path, current, &workspace.join(path)),
+        });
+    }
+    for (path, entry) in &base.files {
+        if copy_files.contains_key(path) {
+            continue;
+        }
+        let current = stamp(&base.source.join(path));
+        // Deleted on both sides: nothing left to do.
+        if current.is_none() {
+            continue;
+        }
+        found.push(Change {
+            status: "D",
+            path: path.clone(),
+            conflict: current != Some(entry.source),
+        });
+    }
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found
+}
+
+/// Whether the conflict at `path` was reported with the source as it is now
+/// (`current`) and the workspace's file was rewritten since.
+fn resolved(base: &Base, path: &str, current: Option<Stamp>, copy_file: &Path) -> bool {
+    base.conflicts.get(path).is_some_and(|seen| {
+        seen.source == current && digest(copy_file).is_some_and(|d| d != seen.copy)
+    })
+}
+
+/// SHA-256 of a file's contents, hex encoded.
+fn digest(path: &Path) -> Option<String> {
+    let mut reader = BufReader::with_capacity(COMPARE_CHUNK, fs::File::open(path).ok()?);
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; COMPARE_CHUNK];
+    loop {
+        let n = reader.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Some(
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect(),
+    )
+}
+
+/// Entries for every file present in both directories after a copy.
+fn snapshot(source: &Path, workspace: &Path) -> BTreeMap<String, BaseEntry> {
+    let mut copy_files = BTreeMap::new();
+    walk(workspace, workspace, &mut copy_files);
+    copy_files
+        .into_iter()
+        .filter_map(|(path, copy)| {
+            let source = stamp(&source.join(&path))?;
+            Some((path, BaseEntry { source, copy }))
+        })
+        .collect()
+}
+
+/// Collect the regular files under `dir`, keyed by path relative to `root`.
+/// Symlinks are copied but not followed or compared.
+fn walk(root: &Path, dir: &Path, out: &mut BTreeMap<String, Stamp>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            walk(root, &path, out);
+        } else if file_type.is_file() {
+            if let (Ok(relative), Some(stamp)) = (path.strip_prefix(root), stamp(&path)) {
+                let key = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                out.insert(key, stamp);
+            }
+        }
+    }
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let meta = fs::symlink_metadata(path).ok()?;
+    if !meta.is_file() {
+        return None;
+    }
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos() as u64);
+    Some(Stamp {
+        size: meta.len(),
+        modified,
+    })
+}
+
+/// Whether two files exist and hold the same bytes.
+fn same_content(a: &Path, b: &Path) -> bool {
+    let (Some(stamp_a), Some(stamp_b)) = (stamp(a), stamp(b)) else {
+        return false;
+    };
+    if stamp_a.size != stamp_b.size {
+        return false;
+    }
+    let (Ok(file_a), Ok(file_b)) = (fs::File::open(a), fs::File::open(b)) else {
+        return false;
+    };
+    let mut reader_a = BufReader::with_capacity(COMPARE_CHUNK, file_a);
+    let mut reader_b = BufReader::with_capacity(COMPARE_CHUNK, file_b);
+    let mut buf_a = vec![0u8; COMPARE_CHUNK];
+    let mut buf_b = vec![0u8; COMPARE_CHUNK];
+    loop {
+        let Ok(n) = reader_a.read(&mut buf_a) else {
+            return false;
+        };
+        if n == 0 {
+            return true;
+        }
+        if reader_b.read_exact(&mut buf_b[..n]).is_err() || buf_a[..n] != buf_b[..n] {
+            return false;
+        }
+    }
+}
+
+/// Copy `source` to the new directory `target`, cloning where possible.
+async fn copy_tree(source: &Path, target: &Path) -> Result<(), GitError> {
+    let clone_flag = if cfg!(target_os = "macos") {
+        "-c"
+    } else {
+        "--reflink=auto"
+    };
+    let cloned = tokio::process::Command::new("cp")
+        .args(["-R", "-p", clone_flag])
+        .arg(source)
+        .arg(target)
+        .output()
+        .await;
+    match cloned {
+        Ok(output) if output.status.success() => return Ok(()),
+        Ok(output) => log::info!(
+            "cp {clone_flag} failed, copying {} file by file: {}",
+            source.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => log::info!(
+            "cp unavailable, copying {} file by file: {e}",
+            source.display()
+        ),
+    }
+
+    let _ = fs::remove_dir_all(target);
+    let (source, target) = (source.to_path_buf(), target.to_path_buf());
+    blocking(move || copy_dir(&source, &target).map_err(GitError::from)).await
+}
+
+/// Recursive copy keeping modification times, like `cp -Rp`.
+fn copy_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let from = entry.path();
+        let to = target.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&from, &to)?;
+        } else if file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(fs::read_link(&from)?, &to)?;
+        } else {
+            fs::copy(&from, &to)?;
+            if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                fs::File::options()
+                    .write(true)
+                    .open(&to)?
+                    .set_modified(modified)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load_base(workspace_path: &Path) -> Result<Base, GitError> {
+    let text = fs::read_to_string(workspace_path.join(BASE_FILE)).map_err(|e| {
+        GitError::PathError(format!(
+            "Not a copied workspace ({BASE_FILE} unreadable): {e}"
+        ))
+    })?;
+    serde_json::from_str(&text).map_err(|e| GitError::Other(format!("Invalid {BASE_FILE}: {e}")))
+}
+
+fn save_base(workspace_path: &Path, base: &Base) -> Result<(), GitError> {
+    let text = serde_json::to_string(base)
+        .map_err(|e| GitError::Other(format!("Failed to serialize {BASE_FILE}: {e}")))?;
+    fs::write(workspace_path.join(BASE_FILE), text)
+        .map_err(|e| GitError::PathError(format!("Failed to write {BASE_FILE}: {e}")))
+}
+
+/// Run filesystem work off the async runtime.
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, GitError> + Send + 'static,
+) -> Result<T, GitError> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| GitError::Other(format!("Workspace copy task failed: {e}")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A project directory and a workspace copied from it, under a temp root.
+    async fn setup() -> (TempDir, PathBuf, PathBuf) {
+        let root = TempDir::new().unwrap();
+        let project = root.path().join("data");
+        fs::create_dir_all(project.join("notebooks")).unwrap();
+        fs::write(project.join("README.md"), "one\ntwo\n").unwrap();
+        fs::write(project.join("notebooks/a.py"), "print(1)\n").unwrap();
+        fs::write(project.join("old.csv"), "x,y\n1,2\n").unwrap();
+
+        let layout = WorkspaceLayout {
+            root: Some(root.path().join("ws").to_string_lossy().to_string()),
+            naming: Some("{repo}/{branch}".to_string()),
+        };
+        let workspace = add_plain_workspace(&project, "experiment", &layout)
+            .await
+            .unwrap();
+        (root, project, workspace)
+    }
+
+    fn statuses(result: &ChangedFilesResult) -> Vec<(String, String)> {
+        result
+            .files
+            .iter()
+            .map(|f| (f.status.clone(), f.path.clone()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn copy_starts_clean() {
+        let (_root, project, workspace) = setup().await;
+
+        assert!(is_plain_workspace(&workspace));
+        assert_eq!(
+            source_dir(&workspace),
+            Some(fs::canonicalize(&project).unwrap())
+        );
+        assert_eq!(
+            fs::read_to_string(workspace.join("notebooks/a.py")).unwrap(),
+            "print(1)\n"
+        );
+        assert!(list_plain_changes(&workspace)
+            .await
+            .unwrap()
+            .files
+            .is_empty());
+        assert!(!has_changes(&workspace).await);
+    }
+
+    #[tokio::test]
+    async fn lists_content_changes_with_line_counts() {
+        let (_root, _project, workspace) = setup().await;
+        fs::write(workspace.join("README.md"), "one\nthree\nfour\n").unwrap();
+        fs::write(workspace.join("new.txt"), "hello\n").unwrap();
+        fs::remove_file(workspace.join("old.csv")).unwrap();
+        // Rewritten with the same bytes: not a change.
+        fs::write(workspace.join("notebooks/a.py"), "print(1)\n").unwrap();
+
+        let result = list_plain_changes(&workspace).await.unwrap();
+
+        assert_eq!(
+            statuses(&result),
+            vec![
+                ("M".to_string(), "README.md".to_string()),
+                ("A".to_string(), "new.txt".to_string()),
+                ("D".to_string(), "old.csv".to_string()),
+            ]
+        );
+        let counts: Vec<_> = result
+            .files
+            .iter()
+            .map(|f| (f.additions, f.deletions))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![(Some(2), Some(1)), (Some(1), Some(0)), (Some(0), Some(2))]
+        );
+    }
+
+    #[tokio::test]
+    async fn file_diff_uses_workspace_relative_paths() {
+        let (_root, _project, workspace) = setup().await;
+        fs::write(workspace.join("README.md"), "one\n2\n").unwrap();
+
+        let diff = plain_file_diff(&workspace, "README.md", "M", &DiffOptions::default())
+            .await
+            .unwrap();
+
+        assert!(diff.contains("--- a/README.md"), "{diff}");
+        assert!(diff.contains("+++ b/README.md"), "{diff}");
+        assert!(diff.contains("-two") && diff.contains("+2"), "{diff}");
+    }
+
+    #[tokio::test]
+    async fn copy_back_applies_changes_then_workspace_is_clean() {
+        let (_root, project, workspace) = setup().await;
+        fs::write(workspace.join("README.md"), "changed\n").unwrap();
+        fs::create_dir_all(workspace.join("out")).unwrap();
+        fs::write(workspace.join("out/result.csv"), "1\n").unwrap();
+        fs::remove_file(workspace.join("old.csv")).unwrap();
+
+        assert!(check_copy_back(&workspace).await.unwrap().success);
+        let result = copy_back(&workspace).await.unwrap();
+
+        assert!(result.success, "{}", result.message);
+        assert_eq!(
+            fs::read_to_string(project.join("README.md")).unwrap(),
+            "changed\n"
+        );
+        assert_eq!(
+            fs::read_to_string(project.join("out/result.csv")).unwrap(),
+            "1\n"
+        );
+        assert!(!project.join("old.csv").exists());
+        assert!(!has_changes(&workspace).await);
+        assert!(copy_back(&workspace).await.unwrap().already_up_to_date);
+        remove_plain_workspace(&workspace, false).await.unwrap();
+        assert!(!workspace.exists());
+    }
+
+    #[tokio::test]
+    async fn source_edits_since_the_copy_are_conflicts() {
+        let (_root, project, workspace) = setup().await;
+        fs::write(workspace.join("README.md"), "from workspace\n").unwrap();
+        fs::write(workspace.join("notebooks/a.py"), "print(2)\n").unwrap();
+        fs::write(project.join("README.md"), "from elsewhere\n").unwrap();
+
+        let result = check_copy_back(&workspace).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.conflicts, vec!["README.md".to_string()]);
+
+        let result = copy_back(&workspace).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            fs::read_to_string(project.join("notebooks/a.py")).unwrap(),
+            "print(1)\n"
+        );
+
+        // Rewriting the workspace's copy after the source change resolves it.
+        fs::write(
+            workspace.join("README.md"),
+            "from elsewhere\nand workspace\n",
+        )
+        .unwrap();
+        assert!(copy_back(&workspace).await.unwrap().success);
+        assert_eq!(
+            fs::read_to_string(project.join("README.md")).unwrap(),
+            "from elsewhere\nand workspace\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn conflicts_dont_depend_on_which_side_was_written_last() {
+        let (_root, project, workspace) = setup().await;
+        fs::write(project.join("README.md"), "from elsewhere\n").unwrap();
+        fs::write(project.join("new.txt"), "theirs\n").unwrap();
+        // The source edit carries an older modification time than the copy.
+        let past = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(project.join("README.md"))
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+        fs::write(workspace.join("README.md"), "from workspace\n").unwrap();
+        fs::write(workspace.join("new.txt"), "ours\n").unwrap();
+
+        let result = check_copy_back(&workspace).await.unwrap();
+        assert_eq!(
+            result.conflicts,
+            vec!["README.md".to_string(), "new.txt".to_string()]
+        );
+
+        // Still conflicts until the workspace's files are rewritten.
+        assert!(!copy_back(&workspace).await.unwrap().success);
+        fs::write(workspace.join("new.txt"), "theirs\nours\n").unwrap();
+        let result = check_copy_back(&workspace).await.unwrap();
+        assert_eq!(result.conflicts, vec!["README.md".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn removing_a_changed_workspace_needs_force() {
+        let (_root, _project, workspace) = setup().await;
+        fs::write(workspace.join("scratch.txt"), "wip").unwrap();
+
+        let result = remove_plain_workspace(&workspace, false).await;
+        assert!(matches!(result, Err(GitError::WorktreeDirty { .. })));
+        assert!(workspace.exists());
+
+        remove_plain_workspace(&workspace, true).await.unwrap();
+        assert!(!workspace.exists());
+    }
+
+    #[test]
+    fn fallback_copy_keeps_modification_times() {
+        let root = TempDir::new().unwrap();
+        let source = root.path().join("src");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("nested/file.txt"), "data").unwrap();
+        let target = root.path().join("copy");
+
+        copy_dir(&source, &target).unwrap();
+
+        assert_eq!(
+            stamp(&source.join("nested/file.txt")),
+            stamp(&target.join("nested/file.txt"))
+        );
+    }
+}
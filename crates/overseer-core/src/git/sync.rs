@@ -0,0 +1,656 @@
+//! Syncing a workspace with the default branch.
+//!
+//! # Overview
+//!
+//! A long-lived workspace drifts away from the default branch. This module
+//! brings the default branch's new commits into the workspace's branch, the
+//! opposite direction of [`merge_into_main`](super::merge_into_main):
+//!
+//! - [`sync_with_default_branch`] - merge the default branch in, or rebase
+//!   onto it
+//! - [`get_sync_state`] - whether a sync is stopped on conflicts, and which
+//! - [`continue_sync`] - check the conflicts are resolved, then commit the
+//!   merge or continue the rebase
+//! - [`abort_sync`] - give up and return to the state before the sync
+//!
+//! # Conflicts
+//!
+//! Unlike merging into the default branch, a sync that hits conflicts is
+//! **not** aborted: the worktree is left mid-merge (or mid-rebase) so the
+//! conflicts can be resolved in place, by hand or by the workspace's agent
+//! (see [`crate::conflicts`]). Each [`ConflictFile`] carries both sides of
+//! the file and the lines where conflict markers remain. [`continue_sync`]
+//! refuses to go on while any marker is left.
+
+use super::{
+    get_current_branch, git_path, is_default_branch_name, ref_exists, resolve_default_branch,
+    run_git, run_git_with_env, GitError,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Sides larger than this are left out of [`ConflictFile`].
+const MAX_SIDE_BYTES: usize = 256 * 1024;
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// How the default branch is brought into the workspace's branch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncStrategy {
+    /// `git merge <default>`: keeps the branch's history, adds a merge commit.
+    #[default]
+    Merge,
+    /// `git rebase <default>`: replays the branch's commits on top.
+    Rebase,
+}
+
+/// Options for [`sync_with_default_branch`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SyncOptions {
+    /// Merge or rebase.
+    pub strategy: SyncStrategy,
+    /// Fetch `origin` first and sync from `origin/<default>` when it exists.
+    pub fetch: bool,
+}
+
+/// Outcome of a sync step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncStatus {
+    /// The branch already contained the default branch; nothing changed.
+    UpToDate,
+    /// The default branch was merged in (or the branch rebased onto it).
+    Synced,
+    /// Stopped on conflicts; the worktree is mid-merge or mid-rebase.
+    Conflicts,
+}
+
+/// A file with unresolved conflicts.
+///
+/// The sides are named after the workspace, not git's ours/theirs, which
+/// swap meaning between merge and rebase. A side is `None` when the file
+/// doesn't exist on it, is binary or is too large.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictFile {
+    /// Path relative to the workspace root
+    pub path: String,
+    /// The common ancestor's version
+    pub base: Option<String>,
+    /// The workspace branch's version
+    pub branch: Option<String>,
+    /// The default branch's version
+    pub upstream: Option<String>,
+    /// 1-based lines of the worktree file that still hold conflict markers
+    pub marker_lines: Vec<usize>,
+}
+
+/// Result of [`sync_with_default_branch`] or [`continue_sync`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncResult {
+    pub status: SyncStatus,
+    pub strategy: SyncStrategy,
+    /// The ref that was synced from (e.g. `main` or `origin/main`)
+    pub upstream: String,
+    /// Files still conflicted (empty unless `status` is `conflicts`)
+    pub conflicts: Vec<ConflictFile>,
+    /// Human-readable message describing the result
+    pub message: String,
+}
+
+/// A sync stopped on conflicts, as found on disk by [`get_sync_state`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncState {
+    pub strategy: SyncStrategy,
+    /// The commit being merged in, or rebased onto
+    pub upstream: String,
+    pub conflicts: Vec<ConflictFile>,
+}
+
+// ============================================================================
+// SYNCING
+// ============================================================================
+
+/// Bring the default branch's new commits into the workspace's branch.
+///
+/// The default branch comes from [`resolve_default_branch`]. With
+/// [`SyncOptions::fetch`], `origin` is fetched first and `origin/<default>`
+/// is preferred, so the workspace gets what was pushed, not just what the
+/// local default branch happens to hold.
+///
+/// Rebasing autostashes uncommitted changes; a merge keeps them in place
+/// (git refuses if the merge would overwrite them).
+///
+/// # Returns
+///
+/// A [`SyncResult`]; on conflicts the worktree is left mid-sync for
+/// [`continue_sync`] or [`abort_sync`].
+///
+/// # Errors
+///
+/// Returns an error if the workspace is on the default branch, a sync is
+/// already in progress, or git fails for reasons other than conflicts.
+pub async fn sync_with_default_branch(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    options: &SyncOptions,
+) -> Result<SyncResult, GitError> {
+    let strategy = options.strategy;
+    let branch = get_current_branch(workspace_path).await?;
+    if is_default_branch_name(&branch, main_branch) {
+        return Err(GitError::Other(
+            "Already on the default branch, nothing to sync.".to_string(),
+        ));
+    }
+    if let Some(state) = get_sync_state(workspace_path).await? {
+        return Err(GitError::Other(format!(
+            "A {} is already in progress with {} conflicted file(s); continue or abort it first.",
+            strategy_noun(state.strategy),
+            state.conflicts.len()
+        )));
+    }
+
+    let upstream = upstream_ref(workspace_path, main_branch, options.fetch).await?;
+
+    let behind = run_git(
+        &["rev-list", "--count", &format!("HEAD..{upstream}")],
+        workspace_path,
+    )
+    .await?;
+    if behind.success && behind.stdout_str().trim() == "0" {
+        return Ok(SyncResult {
+            status: SyncStatus::UpToDate,
+            strategy,
+            conflicts: vec![],
+            message: format!("'{branch}' is already up to date with '{upstream}'."),
+            upstream,
+        });
+    }
+
+    let output = match strategy {
+        SyncStrategy::Merge => {
+            run_git(
+                &[
+                    "merge",
+                    "--no-edit",
+                    "-m",
+                    &format!("Merge '{upstream}' into '{branch}'"),
+                    &upstream,
+                ],
+                workspace_path,
+            )
+            .await?
+        }
+        SyncStrategy::Rebase => {
+            run_git_with_env(
+                &["rebase", "--autostash", &upstream],
+                workspace_path,
+                &[("GIT_EDITOR", "true")],
+            )
+            .await?
+        }
+    };
+
+    if output.success {
+        return Ok(SyncResult {
+            status: SyncStatus::Synced,
+            strategy,
+            conflicts: vec![],
+            message: format!("Synced '{branch}' with '{upstream}'."),
+            upstream,
+        });
+    }
+
+    let conflicts = list_conflicts(workspace_path, strategy).await?;
+    if conflicts.is_empty() {
+        // Not a conflict: make sure nothing is left half done.
+        let _ = abort_in_progress(workspace_path, strategy).await;
+        return Err(GitError::GitFailed {
+            stderr: output.stderr_str().trim().to_string(),
+            stdout: output.stdout_str().trim().to_string(),
+        });
+    }
+
+    Ok(conflicted(strategy, upstream, conflicts))
+}
+
+/// The sync currently stopped on conflicts in `workspace_path`, if any.
+pub async fn get_sync_state(workspace_path: &Path) -> Result<Option<SyncState>, GitError> {
+    let Some((strategy, upstream)) = in_progress(workspace_path).await? else {
+        return Ok(None);
+    };
+    let conflicts = list_conflicts(workspace_path, strategy).await?;
+    Ok(Some(SyncState {
+        strategy,
+        upstream,
+        conflicts,
+    }))
+}
+
+/// Finish a sync that stopped on conflicts.
+///
+/// Refuses while conflict markers remain in any file that was conflicted,
+/// returning `status: conflicts` with the offending lines. Otherwise stages
+/// those files and commits the merge, or continues the rebase, which may
+/// stop again on the next commit's conflicts.
+pub async fn continue_sync(workspace_path: &Path) -> Result<SyncResult, GitError> {
+    let Some((strategy, upstream)) = in_progress(workspace_path).await? else {
+        return Err(GitError::Other("No sync in progress.".to_string()));
+    };
+
+    let unmerged = unmerged_paths(workspace_path).await?;
+    let mut remaining = Vec::new();
+    for path in unmerged.keys() {
+        let markers = marker_lines_in(workspace_path, path);
+        if !markers.is_empty() {
+            remaining.push(path.clone());
+        }
+    }
+    if !remaining.is_empty() {
+        let conflicts = list_conflicts(workspace_path, strategy).await?;
+        let mut result = conflicted(strategy, upstream, conflicts);
+        result.message = format!(
+            "Conflict markers remain in: {}. Resolve them before continuing.",
+            remaining.join(", ")
+        );
+        return Ok(result);
+    }
+
+    if !unmerged.is_empty() {
+        let mut args = vec!["add", "--"];
+        args.extend(unmerged.keys().map(String::as_str));
+        let add = run_git(&args, workspace_path).await?;
+        if !add.success {
+            return Err(GitError::GitFailed {
+                stderr: add.stderr_str().trim().to_string(),
+                stdout: add.stdout_str().trim().to_string(),
+            });
+        }
+    }
+
+    let output = match strategy {
+        SyncStrategy::Merge => run_git(&["commit", "--no-edit"], workspace_path).await?,
+        SyncStrategy::Rebase => {
+            run_git_with_env(
+                &["rebase", "--continue"],
+                workspace_path,
+                &[("GIT_EDITOR", "true")],
+            )
+            .await?
+        }
+    };
+
+    if output.success {
+        return Ok(SyncResult {
+            status: SyncStatus::Synced,
+            strategy,
+            conflicts: vec![],
+            message: format!("Finished syncing with '{upstream}'."),
+            upstream,
+        });
+    }
+
+    // A rebase can stop again on a later commit.
+    let conflicts = list_conflicts(workspace_path, strategy).await?;
+    if !conflicts.is_empty() {
+        return Ok(conflicted(strategy, upstream, conflicts));
+    }
+    Err(GitError::GitFailed {
+        stderr: output.stderr_str().trim().to_string(),
+        stdout: output.stdout_str().trim().to_string(),
+    })
+}
+
+/// Abandon a sync that stopped on conflicts, restoring the branch (and, for
+/// a rebase, any autostashed changes).
+pub async fn abort_sync(workspace_path: &Path) -> Result<(), GitError> {
+    let Some((strategy, _)) = in_progress(workspace_path).await? else {
+        return Err(GitError::Other("No sync in progress.".to_string()));
+    };
+    abort_in_progress(workspace_path, strategy).await
+}
+
+// ============================================================================
+// CONFLICT MARKERS
+// ============================================================================
+
+/// 1-based line numbers of conflict markers in `content`.
+///
+/// Matches the `<<<<<<<`, `|||||||`, `=======` and `>>>>>>>` lines git
+/// writes; a bare `=======` only counts inside a `<<<<<<<` block, so
+/// Markdown/RST underlines aren't flagged.
+pub fn find_conflict_markers(content: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut in_block = false;
+    for (i, line) in content.lines().enumerate() {
+        let is_marker = if line.starts_with("<<<<<<< ") || line == "<<<<<<<" {
+            in_block = true;
+            true
+        } else if line.starts_with(">>>>>>> ") || line == ">>>>>>>" {
+            in_block = false;
+            true
+        } else {
+            in_block && (line == "=======" || line.starts_with("||||||| ") || line == "|||||||")
+        };
+        if is_marker {
+            lines.push(i + 1);
+        }
+    }
+    lines
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+fn strategy_noun(strategy: SyncStrategy) -> &'static str {
+    match strategy {
+        SyncStrategy::Merge => "merge",
+        SyncStrategy::Rebase => "rebase",
+    }
+}
+
+fn conflicted(
+    strategy: SyncStrategy,
+    upstream: String,
+    conflicts: Vec<ConflictFile>,
+) -> SyncResult {
+    SyncResult {
+        status: SyncStatus::Conflicts,
+        strategy,
+        message: format!(
+            "Syncing with '{upstream}' stopped on conflicts in {} file(s).",
+            conflicts.len()
+        ),
+        upstream,
+        conflicts,
+    }
+}
+
+/// The ref to sync from: the default branch, or `origin/<default>` after a
+/// fetch.
+async fn upstream_ref(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    fetch: bool,
+) -> Result<String, GitError> {
+    let resolved = resolve_default_branch(workspace_path, main_branch).await;
+    if !fetch {
+        return Ok(resolved);
+    }
+
+    let local = resolved.strip_prefix("origin/").unwrap_or(&resolved);
+    let output = run_git(&["fetch", "origin", local], workspace_path).await?;
+    if !output.success {
+        return Err(GitError::GitFailed {
+            stderr: output.stderr_str().trim().to_string(),
+            stdout: output.stdout_str().trim().to_string(),
+        });
+    }
+    let remote = format!("origin/{local}");
+    if ref_exists(&remote, workspace_path).await {
+        Ok(remote)
+    } else {
+        Ok(resolved)
+    }
+}
+
+/// Which kind of sync is stopped in the worktree, and what it syncs from.
+async fn in_progress(workspace_path: &Path) -> Result<Option<(SyncStrategy, String)>, GitError> {
+    for dir in ["rebase-merge", "rebase-apply"] {
+        let path = git_path(workspace_path, dir).await?;
+        if path.is_dir() {
+            let onto = std::fs::read_to_string(path.join("onto")).unwrap_or_default();
+            let upstream = commit_name(workspace_path, onto.trim()).await;
+            return Ok(Some((SyncStrategy::Rebase, upstream)));
+        }
+    }
+
+    let merge_head = run_git(
+        &["rev-parse", "-q", "--verify", "MERGE_HEAD"],
+        workspace_path,
+    )
+    .await?;
+    if merge_head.success {
+        let upstream = commit_name(workspace_path, merge_head.stdout_str().trim()).await;
+        return Ok(Some((SyncStrategy::Merge, upstream)));
+    }
+    Ok(None)
+}
+
+/// A branch name for `commit` if one points at it, else the commit id.
+async fn commit_name(workspace_path: &Path, commit: &str) -> String {
+    match run_git(
+        &[
+            "name-rev",
+            "--name-only",
+            "--no-undefined",
+            "--exclude=refs/overseer/*",
+            commit,
+        ],
+        workspace_path,
+    )
+    .await
+    {
+        Ok(output) if output.success => {
+            let name = output.stdout_str().trim().to_string();
+            // Exact matches only; "main~2" isn't what the sync started from.
+            if name.contains(['~', '^']) {
+                commit.to_string()
+            } else {
+                name.strip_prefix("remotes/").unwrap_or(&name).to_string()
+            }
+        }
+        _ => commit.to_string(),
+    }
+}
+
+async fn abort_in_progress(workspace_path: &Path, strategy: SyncStrategy) -> Result<(), GitError> {
+    let args: &[&str] = match strategy {
+        SyncStrategy::Merge => &["merge", "--abort"],
+        SyncStrategy::Rebase => &["rebase", "--abort"],
+    };
+    let output = run_git(args, workspace_path).await?;
+    if output.success {
+        Ok(())
+    } else {
+        Err(GitError::GitFailed {
+            stderr: output.stderr_str().trim().to_string(),
+            stdout: output.stdout_str().trim().to_string(),
+        })
+    }
+}
+
+/// Unmerged index entries: path → (stage → blob id).
+async fn unmerged_paths(
+    workspace_path: &Path,
+) -> Result<BTreeMap<String, BTreeMap<u8, String>>, GitError> {
+    let output = run_git(&["ls-files", "-u", "-z"], workspace_path).await?;
+    let mut paths: BTreeMap<String, BTreeMap<u8, String>> = BTreeMap::new();
+    for entry in output.stdout_str().split('\0') {
+        // "<mode> <object> <stage>\t<path>"
+        let Some((info, path)) = entry.split_once('\t') else {
+            continue;
+        };
+        let mut fields = info.split(' ');
+        let (Some(_mode), Some(object), Some(stage)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if let Ok(stage) = stage.parse() {
+            paths
+                .entry(path.to_string())
+                .or_default()
+                .insert(stage, object.to_string());
+        }
+    }
+    Ok(paths)
+}
+
+async fn list_conflicts(
+    workspace_path: &Path,
+    strategy: SyncStrategy,
+) -> Result<Vec<ConflictFile>, GitError> {
+    let mut conflicts = Vec::new();
+    for (path, stages) in unmerged_paths(workspace_path).await? {
+        let side = |stage: u8| stages.get(&stage).cloned();
+        // Stage 2 is HEAD. Mid-rebase, HEAD is the upstream being rebased
+        // onto and stage 3 the branch's commit being replayed.
+        let (branch, upstream) = match strategy {
+            SyncStrategy::Merge => (side(2), side(3)),
+            SyncStrategy::Rebase => (side(3), side(2)),
+        };
+        let base = side(1);
+        conflicts.push(ConflictFile {
+            marker_lines: marker_lines_in(workspace_path, &path),
+            base: read_blob(workspace_path, base).await,
+            branch: read_blob(workspace_path, branch).await,
+            upstream: read_blob(workspace_path, upstream).await,
+            path,
+        });
+    }
+    Ok(conflicts)
+}
+
+/// Text of a blob, unless it's missing, binary or too large.
+async fn read_blob(workspace_path: &Path, object: Option<String>) -> Option<String> {
+    let object = object?;
+    let output = run_git(&["cat-file", "blob", &object], workspace_path)
+        .await
+        .ok()?;
+    if !output.success || output.stdout.len() > MAX_SIDE_BYTES || output.stdout.contains(&0) {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+fn marker_lines_in(workspace_path: &Path, path: &str) -> Vec<usize> {
+    std::fs::read(workspace_path.join(path))
+        .map(|bytes| find_conflict_markers(&String::from_utf8_lossy(&bytes)))
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn finds_conflict_markers() {
+        let content =
+            "a\n<<<<<<< HEAD\nours\n||||||| base\nold\n=======\ntheirs\n>>>>>>> main\nb\n";
+        assert_eq!(find_conflict_markers(content), vec![2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn ignores_separator_outside_conflict() {
+        let content = "Title\n=======\n\ntext\n";
+        assert!(find_conflict_markers(content).is_empty());
+    }
+
+    fn git(args: &[&str], cwd: &Path) {
+        let out = std::process::Command::new("git")
+            .args(args)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .current_dir(cwd)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    /// A repo on `feature` whose `main` changed the same line of `a.txt`.
+    fn conflicting_repo() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_path_buf();
+        git(&["init", "-q", "-b", "main"], &path);
+        git(&["config", "user.email", "test@test.com"], &path);
+        git(&["config", "user.name", "Test"], &path);
+        std::fs::write(path.join("a.txt"), "base\n").unwrap();
+        git(&["add", "."], &path);
+        git(&["commit", "-q", "-m", "init"], &path);
+
+        git(&["checkout", "-q", "-b", "feature"], &path);
+        std::fs::write(path.join("a.txt"), "branch\n").unwrap();
+        git(&["commit", "-q", "-am", "branch change"], &path);
+
+        git(&["checkout", "-q", "main"], &path);
+        std::fs::write(path.join("a.txt"), "upstream\n").unwrap();
+        git(&["commit", "-q", "-am", "upstream change"], &path);
+        git(&["checkout", "-q", "feature"], &path);
+
+        (dir, path)
+    }
+
+    #[tokio::test]
+    async fn merge_sync_stops_on_conflicts_and_continues_once_resolved() {
+        let (_dir, path) = conflicting_repo();
+
+        let result = sync_with_default_branch(&path, Some("main"), &SyncOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(result.status, SyncStatus::Conflicts);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.path, "a.txt");
+        assert_eq!(conflict.branch.as_deref(), Some("branch\n"));
+        assert_eq!(conflict.upstream.as_deref(), Some("upstream\n"));
+        assert_eq!(conflict.base.as_deref(), Some("base\n"));
+        assert!(!conflict.marker_lines.is_empty());
+
+        // Markers still there: refuse.
+        let refused = continue_sync(&path).await.unwrap();
+        assert_eq!(refused.status, SyncStatus::Conflicts);
+
+        std::fs::write(path.join("a.txt"), "both\n").unwrap();
+        let done = continue_sync(&path).await.unwrap();
+        assert_eq!(done.status, SyncStatus::Synced);
+        assert!(get_sync_state(&path).await.unwrap().is_none());
+
+        let up_to_date = sync_with_default_branch(&path, Some("main"), &SyncOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(up_to_date.status, SyncStatus::UpToDate);
+    }
+
+    #[tokio::test]
+    async fn rebase_sync_names_sides_after_the_workspace() {
+        let (_dir, path) = conflicting_repo();
+        let options = SyncOptions {
+            strategy: SyncStrategy::Rebase,
+            fetch: false,
+        };
+
+        let result = sync_with_default_branch(&path, Some("main"), &options)
+            .await
+            .unwrap();
+        assert_eq!(result.status, SyncStatus::Conflicts);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.branch.as_deref(), Some("branch\n"));
+        assert_eq!(conflict.upstream.as_deref(), Some("upstream\n"));
+
+        let state = get_sync_state(&path).await.unwrap().unwrap();
+        assert_eq!(state.strategy, SyncStrategy::Rebase);
+
+        abort_sync(&path).await.unwrap();
+        assert!(get_sync_state(&path).await.unwrap().is_none());
+        assert_eq!(
+            std::fs::read_to_string(path.join("a.txt")).unwrap(),
+            "branch\n"
+        );
+    }
+}
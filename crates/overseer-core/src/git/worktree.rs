@@ -10,12 +10,15 @@
 //!
 //! - [`list_workspaces`] - List all worktrees in a repository
 //! - [`add_workspace`] - Create a new worktree for a branch
+//! - [`add_workspace_from`] - Create a new worktree for a branch at a given start point
 //! - [`archive_workspace`] - Remove a worktree (but keep the branch)
 //! - [`pick_workspace_dir`] - Generate a unique directory name for a new workspace
+//! - [`move_workspace`] - Move a worktree, e.g. to a new workspace root
+//! - [`prune_workspaces`] - Drop git's records of worktrees deleted from disk
 //!
 //! # Workspace Directory Structure
 //!
-//! Workspaces are created under `~/overseer/workspaces/<repo-name>/`:
+//! By default, workspaces are created under `~/overseer/workspaces/<repo-name>/`:
 //!
 //! ```text
 //! ~/overseer/workspaces/
@@ -26,9 +29,13 @@
 //! ```
 //!
 //! In development mode (`debug_assertions`), uses `workspaces-dev/` instead.
+//! The root and naming scheme are configurable; see [`super::layout`].
 
+use super::layout::{render_naming, validate_naming, NamingVars, WorkspaceLayout};
 use super::{run_git, GitError, ANIMALS};
+use crate::paths::get_home_dir;
 use serde::Serialize;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -137,21 +144,25 @@ pub async fn list_workspaces(repo_path: &Path) -> Result<Vec<WorkspaceInfo>, Git
 
 /// Pick a unique directory path for a new workspace.
 ///
-/// Generates workspace paths using animal names under the Overseer
-/// workspaces directory structure. Uses a time-based shuffle to pick
-/// names randomly but deterministically.
+/// The path is `layout`'s root joined with its naming template (see
+/// [`layout`](super::layout)). Animal names are picked with a time-based
+/// shuffle; `{n}` takes the lowest free number. If the rendered name is
+/// taken anyway, a `-v<n>` suffix is added.
 ///
 /// # Arguments
 ///
 /// * `repo_path` - Path to the repository (used to derive the repo name)
+/// * `branch` - Branch the workspace is for (used by `{branch}`)
+/// * `layout` - Root directory and naming template
 ///
 /// # Returns
 ///
 /// A `PathBuf` to a non-existent directory suitable for a new workspace.
+/// Its parent directory is created.
 ///
 /// # Directory Structure
 ///
-/// Returns paths like:
+/// With the default layout, returns paths like:
 /// - `~/overseer/workspaces/my-repo/narwhal/`
 /// - `~/overseer/workspaces/my-repo/capybara/`
 /// - `~/overseer/workspaces/my-repo/narwhal-v2/` (if `narwhal` exists)
@@ -159,38 +170,167 @@ pub async fn list_workspaces(repo_path: &Path) -> Result<Vec<WorkspaceInfo>, Git
 /// # Errors
 ///
 /// Returns an error if:
-/// - HOME environment variable is not set
+/// - The root needs the home directory and it isn't set
+/// - The naming template is invalid
 /// - Cannot create the workspaces directory
 /// - All possible names are exhausted (unlikely)
-pub fn pick_workspace_dir(repo_path: &Path) -> Result<PathBuf, GitError> {
-    // Extract repo name from path
-    let repo_name = repo_path
+pub fn pick_workspace_dir(
+    repo_path: &Path,
+    branch: &str,
+    layout: &WorkspaceLayout,
+) -> Result<PathBuf, GitError> {
+    let template = layout.naming_template();
+    validate_naming(template)?;
+    let root = layout.root_dir(get_home_dir().ok().as_deref())?;
+    let repo_name = repo_name(repo_path);
+
+    let animals = if template.contains("{animal}") {
+        shuffled_animals()
+    } else {
+        vec![""]
+    };
+    let max_n = if template.contains("{n}") { 1000 } else { 1 };
+
+    let candidate = |animal: &str, n: u32| {
+        root.join(render_naming(
+            template,
+            NamingVars {
+                repo: &repo_name,
+                branch,
+                animal,
+                n,
+            },
+        ))
+    };
+
+    // Try each animal name (or counter value)
+    for animal in &animals {
+        for n in 1..=max_n {
+            let dir = candidate(animal, n);
+            if !dir.exists() {
+                return ensure_parent(dir);
+            }
+        }
+    }
+
+    // All base names taken - append version suffix
+    for animal in &animals {
+        for v in 1u32..10_000 {
+            let base = candidate(animal, 1);
+            let dir = base.with_file_name(format!(
+                "{}-v{v}",
+                base.file_name().unwrap_or_default().to_string_lossy()
+            ));
+            if !dir.exists() {
+                return ensure_parent(dir);
+            }
+        }
+    }
+
+    Err(GitError::Other(
+        "Could not find available workspace name".to_string(),
+    ))
+}
+
+/// Where an existing workspace belongs under `layout`, if it isn't there.
+///
+/// Like [`pick_workspace_dir`], except an `{animal}` keeps the workspace's
+/// current directory name, so a migration only moves it. Paths in
+/// `reserved` count as taken, so a batch of planned moves can't collide.
+/// Nothing is created on disk.
+///
+/// # Returns
+///
+/// `None` if the workspace already sits where `layout` would put it.
+pub fn plan_workspace_dir(
+    repo_path: &Path,
+    workspace_path: &Path,
+    branch: &str,
+    layout: &WorkspaceLayout,
+    reserved: &HashSet<PathBuf>,
+) -> Result<Option<PathBuf>, GitError> {
+    let template = layout.naming_template();
+    validate_naming(template)?;
+    let root = layout.root_dir(get_home_dir().ok().as_deref())?;
+    // Workspace paths are canonical; compare against a canonical root.
+    let root = std::fs::canonicalize(&root).unwrap_or(root);
+    let repo_name = repo_name(repo_path);
+    let current_name = workspace_path
         .file_name()
         .unwrap_or_default()
         .to_string_lossy()
         .to_string();
 
-    // Get home directory
-    let home =
-        std::env::var("HOME").map_err(|_| GitError::PathError("HOME not set".to_string()))?;
-
-    // Use different directory in development vs production
-    let workspaces_dir = if cfg!(debug_assertions) {
-        "workspaces-dev"
-    } else {
-        "workspaces"
-    };
+    let max_n = if template.contains("{n}") { 1000 } else { 1 };
+    let mut first_free = None;
+    for n in 1..=max_n {
+        let dir = root.join(render_naming(
+            template,
+            NamingVars {
+                repo: &repo_name,
+                branch,
+                animal: &current_name,
+                n,
+            },
+        ));
+        if dir == workspace_path {
+            return Ok(None);
+        }
+        if first_free.is_none() && !dir.exists() && !reserved.contains(&dir) {
+            first_free = Some(dir);
+        }
+    }
+    if let Some(dir) = first_free {
+        return Ok(Some(dir));
+    }
+    let base = root.join(render_naming(
+        template,
+        NamingVars {
+            repo: &repo_name,
+            branch,
+            animal: &current_name,
+            n: 1,
+        },
+    ));
+    let name = base
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    for v in 1u32..10_000 {
+        let dir = base.with_file_name(format!("{name}-v{v}"));
+        if dir == workspace_path {
+            return Ok(None);
+        }
+        if !dir.exists() && !reserved.contains(&dir) {
+            return Ok(Some(dir));
+        }
+    }
+    Err(GitError::Other(
+        "Could not find available workspace name".to_string(),
+    ))
+}
 
-    // Build base path: ~/overseer/workspaces[-dev]/repo-name/
-    let base = PathBuf::from(home)
-        .join("overseer")
-        .join(workspaces_dir)
-        .join(&repo_name);
+/// The repository's directory name.
+fn repo_name(repo_path: &Path) -> String {
+    repo_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
 
-    // Ensure base directory exists
-    std::fs::create_dir_all(&base)
-        .map_err(|e| GitError::PathError(format!("Failed to create workspaces dir: {e}")))?;
+/// Create `dir`'s parent so the worktree can be added there.
+fn ensure_parent(dir: PathBuf) -> Result<PathBuf, GitError> {
+    if let Some(parent) = dir.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| GitError::PathError(format!("Failed to create workspaces dir: {e}")))?;
+    }
+    Ok(dir)
+}
 
+/// The animal names in a time-seeded random order.
+fn shuffled_animals() -> Vec<&'static str> {
     // Generate a seed from current time for shuffling
     let seed = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -209,28 +349,7 @@ pub fn pick_workspace_dir(repo_path: &Path) -> Result<PathBuf, GitError> {
         let j = s % (i + 1);
         candidates.swap(i, j);
     }
-
-    // Try each animal name
-    for name in &candidates {
-        let dir = base.join(name);
-        if !dir.exists() {
-            return Ok(dir);
-        }
-    }
-
-    // All base names taken - append version suffix
-    for name in &candidates {
-        for v in 1u32.. {
-            let dir = base.join(format!("{name}-v{v}"));
-            if !dir.exists() {
-                return Ok(dir);
-            }
-        }
-    }
-
-    Err(GitError::Other(
-        "Could not find available workspace name".to_string(),
-    ))
+    candidates
 }
 
 /// Add a new workspace (worktree) for a branch.
@@ -242,6 +361,7 @@ pub fn pick_workspace_dir(repo_path: &Path) -> Result<PathBuf, GitError> {
 ///
 /// * `repo_path` - Path to the repository
 /// * `branch` - Branch name to check out in the new workspace
+/// * `layout` - Where to put it and what to call it
 ///
 /// # Returns
 ///
@@ -260,16 +380,64 @@ pub fn pick_workspace_dir(repo_path: &Path) -> Result<PathBuf, GitError> {
 /// - Cannot pick a workspace directory
 /// - Git worktree creation fails
 /// - Cannot resolve the absolute path
-pub async fn add_workspace(repo_path: &Path, branch: &str) -> Result<PathBuf, GitError> {
-    let workspace_path = pick_workspace_dir(repo_path)?;
+pub async fn add_workspace(
+    repo_path: &Path,
+    branch: &str,
+    layout: &WorkspaceLayout,
+) -> Result<PathBuf, GitError> {
+    let workspace_path = pick_workspace_dir(repo_path, branch, layout)?;
+    create_worktree(repo_path, &workspace_path, branch, None, false).await?;
+
+    // Resolve to absolute path
+    std::fs::canonicalize(&workspace_path)
+        .map_err(|e| GitError::PathError(format!("Failed to resolve path: {e}")))
+}
+
+/// Add a new workspace for a branch that starts at `start_point`.
+///
+/// Like [`add_workspace`], but a newly created branch points at
+/// `start_point` (e.g. `origin/feature`) instead of HEAD. With `track`, the
+/// new branch's upstream is set to `start_point` so pull/push work as usual.
+/// If the branch already exists it is checked out as-is.
+///
+/// # Returns
+///
+/// The absolute path to the newly created workspace.
+pub async fn add_workspace_from(
+    repo_path: &Path,
+    branch: &str,
+    start_point: &str,
+    track: bool,
+    layout: &WorkspaceLayout,
+) -> Result<PathBuf, GitError> {
+    let workspace_path = pick_workspace_dir(repo_path, branch, layout)?;
+    create_worktree(repo_path, &workspace_path, branch, Some(start_point), track).await?;
+
+    std::fs::canonicalize(&workspace_path)
+        .map_err(|e| GitError::PathError(format!("Failed to resolve path: {e}")))
+}
+
+/// Create a worktree at `workspace_path` on a new branch, falling back to an
+/// existing branch of the same name.
+pub(super) async fn create_worktree(
+    repo_path: &Path,
+    workspace_path: &Path,
+    branch: &str,
+    start_point: Option<&str>,
+    track: bool,
+) -> Result<(), GitError> {
     let workspace_str = workspace_path.to_string_lossy();
 
     // Try to create with new branch first
-    let output = run_git(
-        &["worktree", "add", &workspace_str, "-b", branch],
-        repo_path,
-    )
-    .await?;
+    let mut args = vec!["worktree", "add"];
+    if track {
+        args.push("--track");
+    }
+    args.extend(["-b", branch, &workspace_str]);
+    if let Some(start_point) = start_point {
+        args.push(start_point);
+    }
+    let output = run_git(&args, repo_path).await?;
 
     if !output.success {
         // Branch might already exist - try without -b
@@ -283,8 +451,41 @@ pub async fn add_workspace(repo_path: &Path, branch: &str) -> Result<PathBuf, Gi
         }
     }
 
-    // Resolve to absolute path
-    std::fs::canonicalize(&workspace_path)
+    Ok(())
+}
+
+/// Move a workspace to `new_path` with `git worktree move`.
+///
+/// The parent of `new_path` is created first. Git refuses to move the main
+/// worktree, a locked worktree, or one with submodules.
+///
+/// # Returns
+///
+/// The canonicalized new path.
+pub async fn move_workspace(
+    repo_path: &Path,
+    workspace_path: &Path,
+    new_path: &Path,
+) -> Result<PathBuf, GitError> {
+    if new_path.exists() {
+        return Err(GitError::PathError(format!(
+            "Destination already exists: {}",
+            new_path.display()
+        )));
+    }
+    let new_path = ensure_parent(new_path.to_path_buf())?;
+
+    let from = workspace_path.to_string_lossy();
+    let to = new_path.to_string_lossy();
+    let output = run_git(&["worktree", "move", &from, &to], repo_path).await?;
+    if !output.success {
+        return Err(GitError::GitFailed {
+            stderr: output.stderr_str().trim().to_string(),
+            stdout: output.stdout_str().trim().to_string(),
+        });
+    }
+
+    std::fs::canonicalize(&new_path)
         .map_err(|e| GitError::PathError(format!("Failed to resolve path: {e}")))
 }
 
@@ -363,6 +564,38 @@ pub async fn archive_workspace(
     })
 }
 
+/// Drop git's records of worktrees whose directories no longer exist
+/// (`git worktree prune`).
+///
+/// With `dry_run`, nothing is removed and only the stale entries are reported.
+///
+/// # Returns
+///
+/// The administrative names of the stale worktrees (e.g. `narwhal`).
+pub async fn prune_workspaces(repo_path: &Path, dry_run: bool) -> Result<Vec<String>, GitError> {
+    let mut args = vec!["worktree", "prune", "--verbose"];
+    if dry_run {
+        args.push("--dry-run");
+    }
+    let output = run_git(&args, repo_path).await?;
+    if !output.success {
+        return Err(GitError::GitFailed {
+            stderr: output.stderr_str(),
+            stdout: output.stdout_str(),
+        });
+    }
+
+    // "Removing worktrees/<name>: <reason>", on stdout or stderr depending
+    // on the git version.
+    let text = format!("{}\n{}", output.stdout_str(), output.stderr_str());
+    Ok(text
+        .lines()
+        .filter_map(|line| line.strip_prefix("Removing worktrees/"))
+        .filter_map(|rest| rest.split(':').next())
+        .map(str::to_string)
+        .collect())
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -491,7 +724,7 @@ mod tests {
         // This test requires HOME to be set, which it should be in normal environments
         if std::env::var("HOME").is_ok() {
             let dir = tempdir().unwrap();
-            let result = pick_workspace_dir(dir.path());
+            let result = pick_workspace_dir(dir.path(), "feature", &WorkspaceLayout::default());
 
             assert!(result.is_ok());
             let path = result.unwrap();
@@ -507,7 +740,8 @@ mod tests {
     fn pick_workspace_dir_uses_correct_workspaces_dir() {
         if std::env::var("HOME").is_ok() {
             let dir = tempdir().unwrap();
-            let result = pick_workspace_dir(dir.path()).unwrap();
+            let result =
+                pick_workspace_dir(dir.path(), "feature", &WorkspaceLayout::default()).unwrap();
             let path_str = result.to_string_lossy();
 
             // In test mode (debug_assertions = true), should use workspaces-dev
@@ -519,6 +753,86 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pick_workspace_dir_follows_branch_and_counter_templates() {
+        let root = tempdir().unwrap();
+        let repo = Path::new("/repos/my-repo");
+        let layout = |naming: &str| WorkspaceLayout {
+            root: Some(root.path().to_string_lossy().to_string()),
+            naming: Some(naming.to_string()),
+        };
+
+        let by_branch =
+            pick_workspace_dir(repo, "feature/login", &layout("{repo}/{branch}")).unwrap();
+        assert_eq!(by_branch, root.path().join("my-repo/feature-login"));
+        std::fs::create_dir(&by_branch).unwrap();
+        let taken = pick_workspace_dir(repo, "feature/login", &layout("{repo}/{branch}")).unwrap();
+        assert_eq!(taken, root.path().join("my-repo/feature-login-v1"));
+
+        std::fs::create_dir(root.path().join("my-repo-1")).unwrap();
+        let counted = pick_workspace_dir(repo, "x", &layout("{repo}-{n}")).unwrap();
+        assert_eq!(counted, root.path().join("my-repo-2"));
+    }
+
+    #[tokio::test]
+    async fn move_workspace_relocates_worktree() {
+        let dir = init_temp_repo();
+        let repo = dir.path();
+        let wt = repo.join("wt");
+        let add = Command::new("git")
+            .args(["worktree", "add", wt.to_str().unwrap(), "-b", "feature"])
+            .current_dir(repo)
+            .output()
+            .unwrap();
+        assert!(add.status.success());
+
+        let root = tempdir().unwrap();
+        let layout = WorkspaceLayout {
+            root: Some(root.path().to_string_lossy().to_string()),
+            naming: Some("{repo}/{branch}".to_string()),
+        };
+        let wt = std::fs::canonicalize(&wt).unwrap();
+        let target = plan_workspace_dir(repo, &wt, "feature", &layout, &HashSet::new())
+            .unwrap()
+            .expect("workspace is outside the root");
+
+        let moved = move_workspace(repo, &wt, &target).await.unwrap();
+
+        assert!(!wt.exists());
+        assert!(moved.ends_with("feature"));
+        let listed = list_workspaces(repo).await.unwrap();
+        assert!(listed.iter().any(|w| Path::new(&w.path) == moved));
+        // Already in place now.
+        assert_eq!(
+            plan_workspace_dir(repo, &moved, "feature", &layout, &HashSet::new()).unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn prune_workspaces_reports_then_removes_stale_entries() {
+        let dir = init_temp_repo();
+        let repo = dir.path();
+        let stale = repo.join("stale-ws");
+        let out = Command::new("git")
+            .args(["worktree", "add", "-b", "stale", &stale.to_string_lossy()])
+            .current_dir(repo)
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+        std::fs::remove_dir_all(&stale).unwrap();
+
+        assert_eq!(
+            prune_workspaces(repo, true).await.unwrap(),
+            vec!["stale-ws"]
+        );
+        assert_eq!(
+            prune_workspaces(repo, false).await.unwrap(),
+            vec!["stale-ws"]
+        );
+        assert!(prune_workspaces(repo, true).await.unwrap().is_empty());
+    }
+
     // Note: Tests for list_workspaces, add_workspace, and archive_workspace
     // would require a real git repository, so they're better suited for
     // integration tests. Unit tests here focus on pure functions.
@@ -552,4 +866,55 @@ mod tests {
         assert_eq!(workspaces[0].branch, "main");
         assert_eq!(workspaces[1].branch, "feature");
     }
+
+    #[tokio::test]
+    async fn create_worktree_tracks_start_point() {
+        let dir = init_temp_repo();
+        let repo = dir.path();
+        let git = |args: &[&str]| {
+            let out = Command::new("git")
+                .args(args)
+                .current_dir(repo)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&out.stdout).trim().to_string()
+        };
+
+        // Simulate a fetched remote branch that is ahead of main.
+        git(&["remote", "add", "origin", "https://example.com/repo.git"]);
+        git(&["commit", "--allow-empty", "-m", "remote work"]);
+        git(&["update-ref", "refs/remotes/origin/feature", "HEAD"]);
+        git(&["reset", "--hard", "HEAD~1"]);
+
+        let wt = repo.join("wt");
+        create_worktree(repo, &wt, "feature", Some("origin/feature"), true)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            git(&["rev-parse", "feature"]),
+            git(&["rev-parse", "origin/feature"])
+        );
+        assert_eq!(
+            git(&["config", "branch.feature.merge"]),
+            "refs/heads/feature"
+        );
+    }
+
+    #[tokio::test]
+    async fn create_worktree_reuses_existing_branch() {
+        let dir = init_temp_repo();
+        let repo = dir.path();
+        Command::new("git")
+            .args(["branch", "existing"])
+            .current_dir(repo)
+            .output()
+            .unwrap();
+
+        let wt = repo.join("wt");
+        create_worktree(repo, &wt, "existing", Some("main"), false)
+            .await
+            .unwrap();
+        assert!(wt.join(".git").exists());
+    }
 }
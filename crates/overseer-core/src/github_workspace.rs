@@ -0,0 +1,329 @@
+//! Starting a workspace from a GitHub pull request or issue.
+//!
+//! # Overview
+//!
+//! [`add_workspace`](crate::git::add_workspace) only creates a worktree on a
+//! new branch name. This module adds two shortcuts:
+//!
+//! - **Pull request**: check out the PR's head branch. Same-repository PRs
+//!   get a local branch tracking `origin/<head>`; PRs from forks are fetched
+//!   via `refs/pull/<n>/head` onto a local `<owner>/<head>` branch.
+//! - **Issue**: create a branch named after the issue (`issue-<n>-<slug>`)
+//!   and seed the workspace's init prompt with the issue's title and body,
+//!   after the project's own [`init_prompt`](crate::persistence::Project::init_prompt).
+//!
+//! Creation is split in two so callers can keep the blocking `gh` calls off
+//! the async runtime:
+//!
+//! ```ignore
+//! let plan = spawn_blocking(move || plan_github_workspace(&runner, &repo, source, init)).await??;
+//! let workspace = create_github_workspace(&repo, plan, &layout).await?;
+//! ```
+//!
+//! The `origin` remote is assumed to point at the GitHub repository, the same
+//! assumption `gh` makes when resolving `{owner}/{repo}`.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::git::github::{get_issue, get_pr_head, GhRunner, Issue};
+use crate::git::{add_workspace, add_workspace_from, run_git_success, GitError, WorkspaceLayout};
+
+/// Longest slug taken from an issue title for its branch name.
+const MAX_SLUG_LEN: usize = 40;
+
+/// What to start a workspace from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum GithubSource {
+    /// Check out an existing pull request's head branch
+    PullRequest { number: u64 },
+    /// Start a new branch for an issue
+    Issue { number: u64 },
+}
+
+/// Everything needed to create the worktree, resolved from GitHub.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GithubWorkspacePlan {
+    /// Local branch to check out
+    pub branch: String,
+    /// Refspec to fetch from `origin` before creating the worktree
+    pub fetch_refspec: Option<String>,
+    /// Commit-ish the new branch starts at (HEAD when `None`)
+    pub start_point: Option<String>,
+    /// Whether the new branch tracks `start_point`
+    pub track: bool,
+    /// Prompt to prepend to the first message in the workspace
+    pub init_prompt: Option<String>,
+    /// PR the workspace was started from
+    pub pr_number: Option<u64>,
+    /// Web URL of that PR
+    pub pr_url: Option<String>,
+    /// State of that PR ("OPEN", "CLOSED" or "MERGED") when it was fetched
+    pub pr_state: Option<String>,
+}
+
+/// A workspace created from GitHub.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubWorkspace {
+    /// Absolute path to the new worktree
+    pub path: String,
+    /// Branch checked out in it
+    pub branch: String,
+    /// Prompt to prepend to the first message in the workspace
+    pub init_prompt: Option<String>,
+    /// PR the workspace was started from
+    pub pr_number: Option<u64>,
+    /// Web URL of that PR
+    pub pr_url: Option<String>,
+    /// State of that PR ("OPEN", "CLOSED" or "MERGED") when it was fetched
+    pub pr_state: Option<String>,
+}
+
+/// Look up `source` on GitHub and work out how to check it out.
+///
+/// Blocking: runs `gh`. `project_init_prompt` is the project's init prompt,
+/// placed ahead of the issue text for issue workspaces.
+pub fn plan_github_workspace(
+    runner: &dyn GhRunner,
+    repo_path: &Path,
+    source: GithubSource,
+    project_init_prompt: Option<&str>,
+) -> Result<GithubWorkspacePlan, GitError> {
+    match source {
+        GithubSource::PullRequest { number } => {
+            let head = get_pr_head(runner, repo_path, number)?;
+            let plan = if head.is_cross_repository {
+                // Fork branches aren't reachable through origin's heads, but
+                // GitHub mirrors every PR head as refs/pull/<n>/head.
+                let remote_ref = format!("origin/pull/{number}/head");
+                GithubWorkspacePlan {
+                    branch: format!("{}/{}", head.head_owner, head.head_ref_name),
+                    fetch_refspec: Some(format!(
+                        "+refs/pull/{number}/head:refs/remotes/{remote_ref}"
+                    )),
+                    start_point: Some(remote_ref),
+                    track: false,
+                    init_prompt: None,
+                    pr_number: Some(head.number),
+                    pr_url: Some(head.url),
+                    pr_state: Some(head.state),
+                }
+            } else {
+                let branch = head.head_ref_name;
+                GithubWorkspacePlan {
+                    fetch_refspec: Some(format!(
+                        "+refs/heads/{branch}:refs/remotes/origin/{branch}"
+                    )),
+                    start_point: Some(format!("origin/{branch}")),
+                    track: true,
+                    init_prompt: None,
+                    pr_number: Some(head.number),
+                    pr_url: Some(head.url),
+                    pr_state: Some(head.state),
+                    branch,
+                }
+            };
+            Ok(plan)
+        }
+        GithubSource::Issue { number } => {
+            let issue = get_issue(runner, repo_path, number)?;
+            Ok(GithubWorkspacePlan {
+                branch: issue_branch_name(&issue),
+                fetch_refspec: None,
+                start_point: None,
+                track: false,
+                init_prompt: Some(issue_init_prompt(project_init_prompt, &issue)),
+                pr_number: None,
+                pr_url: None,
+                pr_state: None,
+            })
+        }
+    }
+}
+
+/// Fetch what `plan` needs and create the worktree.
+pub async fn create_github_workspace(
+    repo_path: &Path,
+    plan: GithubWorkspacePlan,
+    layout: &WorkspaceLayout,
+) -> Result<GithubWorkspace, GitError> {
+    if let Some(refspec) = &plan.fetch_refspec {
+        run_git_success(&["fetch", "origin", refspec], repo_path).await?;
+    }
+
+    let path = match &plan.start_point {
+        Some(start_point) => {
+            add_workspace_from(repo_path, &plan.branch, start_point, plan.track, layout).await?
+        }
+        None => add_workspace(repo_path, &plan.branch, layout).await?,
+    };
+
+    Ok(GithubWorkspace {
+        path: path.to_string_lossy().to_string(),
+        branch: plan.branch,
+        init_prompt: plan.init_prompt,
+        pr_number: plan.pr_number,
+        pr_url: plan.pr_url,
+        pr_state: plan.pr_state,
+    })
+}
+
+/// `issue-<n>-<slug>`, where the slug is the lowercased title with runs of
+/// non-alphanumerics collapsed to `-`.
+pub fn issue_branch_name(issue: &Issue) -> String {
+    let mut slug = String::new();
+    for c in issue.title.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= MAX_SLUG_LEN {
+            break;
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+
+    if slug.is_empty() {
+        format!("issue-{}", issue.number)
+    } else {
+        format!("issue-{}-{slug}", issue.number)
+    }
+}
+
+/// The project's init prompt followed by the issue to work on.
+pub fn issue_init_prompt(project_init_prompt: Option<&str>, issue: &Issue) -> String {
+    let mut prompt = String::new();
+    if let Some(init) = project_init_prompt.map(str::trim).filter(|p| !p.is_empty()) {
+        prompt.push_str(init);
+        prompt.push_str("\n\n");
+    }
+
+    prompt.push_str(&format!(
+        "This workspace is for GitHub issue #{}: {}",
+        issue.number,
+        issue.title.trim()
+    ));
+    if !issue.url.is_empty() {
+        prompt.push_str(&format!("\n{}", issue.url));
+    }
+    let body = issue.body.trim();
+    if !body.is_empty() {
+        prompt.push_str(&format!("\n\n{body}"));
+    }
+
+    prompt
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FakeGhRunner;
+
+    fn issue(title: &str) -> Issue {
+        Issue {
+            number: 12,
+            title: title.to_string(),
+            body: "It crashes.\n".to_string(),
+            url: "https://github.com/o/r/issues/12".to_string(),
+        }
+    }
+
+    #[test]
+    fn same_repo_pr_tracks_origin_branch() {
+        let runner = FakeGhRunner::new();
+        runner.respond(
+            &["pr", "view"],
+            r#"{"number":7,"title":"T","url":"https://x/7","headRefName":"feature/login","headRepositoryOwner":{"login":"me"},"isCrossRepository":false}"#,
+        );
+
+        let plan = plan_github_workspace(
+            &runner,
+            Path::new("/r"),
+            GithubSource::PullRequest { number: 7 },
+            None,
+        )
+        .unwrap();
+        assert_eq!(plan.branch, "feature/login");
+        assert_eq!(
+            plan.fetch_refspec.as_deref(),
+            Some("+refs/heads/feature/login:refs/remotes/origin/feature/login")
+        );
+        assert_eq!(plan.start_point.as_deref(), Some("origin/feature/login"));
+        assert!(plan.track);
+        assert_eq!(plan.pr_number, Some(7));
+    }
+
+    #[test]
+    fn fork_pr_fetches_pull_ref() {
+        let runner = FakeGhRunner::new();
+        runner.respond(
+            &["pr", "view"],
+            r#"{"number":8,"title":"T","url":"https://x/8","headRefName":"main","headRepositoryOwner":{"login":"someone"},"isCrossRepository":true,"state":"MERGED"}"#,
+        );
+
+        let plan = plan_github_workspace(
+            &runner,
+            Path::new("/r"),
+            GithubSource::PullRequest { number: 8 },
+            None,
+        )
+        .unwrap();
+        assert_eq!(plan.branch, "someone/main");
+        assert_eq!(
+            plan.fetch_refspec.as_deref(),
+            Some("+refs/pull/8/head:refs/remotes/origin/pull/8/head")
+        );
+        assert!(!plan.track);
+        assert_eq!(plan.pr_state.as_deref(), Some("MERGED"));
+    }
+
+    #[test]
+    fn issue_plan_seeds_init_prompt() {
+        let runner = FakeGhRunner::new();
+        runner.respond(
+            &["issue", "view"],
+            r#"{"number":12,"title":"Crash on start","body":"It crashes.","url":"https://x/12"}"#,
+        );
+
+        let plan = plan_github_workspace(
+            &runner,
+            Path::new("/r"),
+            GithubSource::Issue { number: 12 },
+            Some("Run tests before committing."),
+        )
+        .unwrap();
+        assert_eq!(plan.branch, "issue-12-crash-on-start");
+        assert_eq!(plan.fetch_refspec, None);
+        let prompt = plan.init_prompt.unwrap();
+        assert!(prompt.starts_with("Run tests before committing.\n\n"));
+        assert!(prompt.contains("GitHub issue #12: Crash on start"));
+        assert!(prompt.ends_with("It crashes."));
+    }
+
+    #[test]
+    fn issue_branch_names_are_slugged() {
+        assert_eq!(
+            issue_branch_name(&issue("Fix: login fails (again!)")),
+            "issue-12-fix-login-fails-again"
+        );
+        assert_eq!(issue_branch_name(&issue("日本語")), "issue-12");
+        let long = issue_branch_name(&issue(&"word ".repeat(30)));
+        assert!(long.len() <= "issue-12-".len() + MAX_SLUG_LEN);
+        assert!(!long.ends_with('-'));
+    }
+
+    #[test]
+    fn source_deserializes_tagged() {
+        let source: GithubSource =
+            serde_json::from_str(r#"{"kind":"pullRequest","number":3}"#).unwrap();
+        assert_eq!(source, GithubSource::PullRequest { number: 3 });
+    }
+}
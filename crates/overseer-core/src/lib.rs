@@ -15,20 +15,37 @@
 
 pub mod agents;
 pub mod approval;
+pub mod chat_export;
+pub mod chat_fork;
+pub mod chat_handoff;
+pub mod chat_search;
+pub mod chat_view;
+pub mod checkpoints;
+pub mod conflicts;
 pub mod context;
 pub mod event_bus;
 pub mod git;
+pub mod github_workspace;
 pub mod logging;
 pub mod managers;
 pub mod overseer_actions;
 pub mod paths;
 pub mod persistence;
+pub mod pr_review;
+pub mod processes;
+pub mod redaction;
 pub mod sandbox;
 pub mod session;
 pub mod shell;
 pub mod skills;
 pub mod spawn;
+pub mod transcript_import;
 pub mod usage;
+pub mod vcs;
+pub mod workspace_gc;
+pub mod workspace_layout;
+pub mod workspace_setup;
+pub mod workspace_teardown;
 
 // Test support module - only compiled during tests
 #[cfg(test)]
@@ -8,7 +8,10 @@ use std::path::PathBuf;
 use std::sync::Mutex;
 
 use crate::approval::ApprovalContext;
-use crate::persistence::approvals::{load_approvals, save_approvals};
+use crate::persistence::approvals::{
+    add_command_prefix, add_tool_name, load_approvals, remove_command_prefix, remove_tool_name,
+    update_approvals,
+};
 use crate::persistence::types::ApprovalsData;
 
 /// Manages approval contexts for all projects.
@@ -83,14 +86,7 @@ impl ProjectApprovalManager {
                         data.command_prefixes.len(),
                         data.command_prefixes
                     );
-                    let mut ctx = ApprovalContext::new();
-                    for tool in data.tool_names {
-                        ctx.add_tool(tool);
-                    }
-                    for prefix in data.command_prefixes {
-                        ctx.add_prefix(prefix);
-                    }
-                    ctx
+                    context_from(data)
                 }
                 Err(e) => {
                     log::warn!("Failed to load approvals for {}: {}", project_name, e);
@@ -106,6 +102,12 @@ impl ProjectApprovalManager {
         ctx
     }
 
+    /// Drop a project's cached context so the next use loads it from disk
+    /// (after another process changed its approvals).
+    pub fn forget(&self, project_name: &str) {
+        self.contexts.lock().unwrap().remove(project_name);
+    }
+
     /// Check if a tool should auto-approve.
     pub fn should_auto_approve(
         &self,
@@ -164,7 +166,13 @@ impl ProjectApprovalManager {
             }
         }
 
-        self.save(project_name)
+        self.save(project_name, |data| {
+            if is_prefix {
+                add_command_prefix(data, tool_or_prefix);
+            } else {
+                add_tool_name(data, tool_or_prefix);
+            }
+        })
     }
 
     /// Remove a tool or prefix approval and save to disk.
@@ -202,7 +210,13 @@ impl ProjectApprovalManager {
             }
         }
 
-        self.save(project_name)
+        self.save(project_name, |data| {
+            if is_prefix {
+                remove_command_prefix(data, tool_or_prefix);
+            } else {
+                remove_tool_name(data, tool_or_prefix);
+            }
+        })
     }
 
     /// Clear all approvals for a project and save to disk.
@@ -214,28 +228,31 @@ impl ProjectApprovalManager {
             }
         }
 
-        self.save(project_name)
+        self.save(project_name, |data| *data = ApprovalsData::default())
     }
 
-    /// Save approvals for a project to disk.
-    fn save(&self, project_name: &str) -> Result<(), String> {
+    /// Apply a change to a project's approvals on disk.
+    ///
+    /// The change is made to what's on disk rather than the cached context,
+    /// so approvals another process saved in the meantime aren't lost. The
+    /// cache is then replaced with the result.
+    fn save(
+        &self,
+        project_name: &str,
+        change: impl FnMut(&mut ApprovalsData),
+    ) -> Result<(), String> {
         let dir = self
             .get_project_dir(project_name)
             .ok_or_else(|| "Config directory not set".to_string())?;
 
-        let data = {
-            let contexts = self.contexts.lock().unwrap();
-            if let Some(ctx) = contexts.get(project_name) {
-                ApprovalsData {
-                    tool_names: ctx.approved_tools.iter().cloned().collect(),
-                    command_prefixes: ctx.approved_prefixes.iter().cloned().collect(),
-                }
-            } else {
-                ApprovalsData::default()
-            }
-        };
+        let data = update_approvals(&dir, change)
+            .map_err(|e| format!("Failed to save approvals: {}", e))?;
 
-        save_approvals(&dir, &data).map_err(|e| format!("Failed to save approvals: {}", e))
+        self.contexts
+            .lock()
+            .unwrap()
+            .insert(project_name.to_string(), context_from(data));
+        Ok(())
     }
 
     /// Load approvals for a project (for frontend display).
@@ -248,6 +265,18 @@ impl ProjectApprovalManager {
     }
 }
 
+/// An approval context holding `data`.
+fn context_from(data: ApprovalsData) -> ApprovalContext {
+    let mut ctx = ApprovalContext::new();
+    for tool in data.tool_names {
+        ctx.add_tool(tool);
+    }
+    for prefix in data.command_prefixes {
+        ctx.add_prefix(prefix);
+    }
+    ctx
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
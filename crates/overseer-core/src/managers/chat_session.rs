@@ -5,7 +5,7 @@
 use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::{Component, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -13,13 +13,21 @@ use chrono::Utc;
 use uuid::Uuid;
 
 use crate::agents::event::AgentEvent;
+use crate::chat_export::{self, ExportFormat};
+use crate::chat_fork::{self, ChatFork, ForkedChat};
+use crate::chat_handoff::{self, ChatHandoff};
+use crate::chat_search::{ChatSearchIndex, SearchQuery, SearchResults};
+use crate::chat_view::View;
 use crate::persistence::chat_jsonl::{
     count_events, load_chat_events as load_chat_events_jsonl,
     load_chat_events_since_seq as load_events_since_seq_jsonl,
     load_chat_events_with_seq as load_events_with_seq_jsonl,
-    load_chat_metadata as load_chat_metadata_jsonl, save_chat_metadata as save_chat_metadata_jsonl,
-    serialize_event_for_storage, SeqEvent,
+    load_chat_metadata as load_chat_metadata_jsonl, repair_torn_tail,
+    save_chat_metadata as save_chat_metadata_jsonl, serialize_event_for_storage,
+    truncate_chat_events, SeqEvent,
 };
+use crate::persistence::encryption::{encode_line, release_readable_attachments};
+use crate::persistence::index::update_chat_index;
 use crate::persistence::types::ChatMetadata;
 
 const MAX_PENDING_EVENTS: usize = 10;
@@ -31,12 +39,17 @@ const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
 /// - Session registration and unregistration
 /// - Event appending with automatic flushing
 /// - Chat metadata and event loading
+/// - Full-text search over all chats, fed by appended events
+/// - Exporting chats and importing chat bundles
+/// - Forking chats
 #[derive(Default)]
 pub struct ChatSessionManager {
     /// Active chat sessions: chat_id -> Arc<Mutex<ChatSession>>
     sessions: Mutex<HashMap<String, Arc<Mutex<ChatSession>>>>,
     /// Config directory for persistence
     config_dir: Mutex<Option<PathBuf>>,
+    /// Search index over every chat
+    search: ChatSearchIndex,
 }
 
 impl ChatSessionManager {
@@ -47,6 +60,7 @@ impl ChatSessionManager {
 
     /// Set the config directory for persistence.
     pub fn set_config_dir(&self, dir: PathBuf) {
+        self.search.set_config_dir(dir.clone());
         *self.config_dir.lock().unwrap() = Some(dir);
     }
 
@@ -98,22 +112,40 @@ impl ChatSessionManager {
         let dir = self.get_chat_dir(&project_name, &workspace_name)?;
         save_chat_metadata_jsonl(&dir, &metadata).map_err(|e| e.to_string())?;
 
-        let mut sessions = self.sessions.lock().unwrap();
-        if sessions.contains_key(&chat_id) {
+        if self.sessions.lock().unwrap().contains_key(&chat_id) {
             return Ok(());
         }
 
+        // The file is read without holding the sessions lock; nothing is
+        // appended to this chat until its session is inserted below.
+        // New events must not be glued onto a line a crash left unfinished.
+        if let Err(e) = repair_torn_tail(&dir, &chat_id) {
+            log::warn!("Failed to repair chat {chat_id}: {e}");
+        }
+
         // Count existing events to initialize seq counter
         let initial_event_count = count_events(&dir, &chat_id).unwrap_or(0);
 
-        sessions.insert(
-            chat_id.clone(),
-            Arc::new(Mutex::new(ChatSession::new(
-                chat_id,
-                dir,
-                initial_event_count,
-            ))),
-        );
+        if let Err(e) = self.search.track_chat(
+            &chat_id,
+            &project_name,
+            &workspace_name,
+            metadata.agent_type.clone(),
+        ) {
+            log::warn!("Failed to index chat {chat_id} for search: {e}");
+        }
+
+        self.sessions
+            .lock()
+            .unwrap()
+            .entry(chat_id.clone())
+            .or_insert_with(|| {
+                Arc::new(Mutex::new(ChatSession::new(
+                    chat_id,
+                    dir,
+                    initial_event_count,
+                )))
+            });
 
         Ok(())
     }
@@ -129,6 +161,7 @@ impl ChatSessionManager {
         if let Some(session) = session_opt {
             let mut session = session.lock().unwrap();
             session.flush().map_err(|e| e.to_string())?;
+            self.search.untrack_chat(chat_id);
         }
         Ok(())
     }
@@ -144,6 +177,7 @@ impl ChatSessionManager {
     /// Append an event to a chat session and return its sequence number.
     ///
     /// The sequence number is the 1-indexed line number in the JSONL file.
+    /// The end of a turn also removes the decrypted attachments made for it.
     pub fn append_event_with_seq(&self, chat_id: &str, event: AgentEvent) -> Result<u64, String> {
         // Look up session under global lock, then release before I/O
         let session = {
@@ -154,8 +188,32 @@ impl ChatSessionManager {
                 .ok_or_else(|| format!("Chat session not registered: {chat_id}"))?
         };
 
-        let mut session = session.lock().unwrap();
-        session.append_event(event).map_err(|e| e.to_string())
+        let turn_over = matches!(event, AgentEvent::TurnComplete | AgentEvent::Done);
+        let seq = {
+            let mut session = session.lock().unwrap();
+            self.search.index_event(chat_id, session.next_seq, &event);
+            session.append_event(event).map_err(|e| e.to_string())?
+        };
+        if turn_over {
+            if let Err(e) = release_readable_attachments(chat_id) {
+                log::warn!("Failed to remove decrypted attachments of chat {chat_id}: {e}");
+            }
+        }
+        Ok(seq)
+    }
+
+    /// Write a session's buffered events to disk now.
+    ///
+    /// A no-op for sessions that aren't registered.
+    pub fn flush(&self, chat_id: &str) -> Result<(), String> {
+        let session = {
+            let sessions = self.sessions.lock().unwrap();
+            sessions.get(chat_id).cloned()
+        };
+        match session {
+            Some(session) => session.lock().unwrap().flush().map_err(|e| e.to_string()),
+            None => Ok(()),
+        }
     }
 
     /// Load all events from a chat session.
@@ -214,6 +272,230 @@ impl ChatSessionManager {
         save_chat_metadata_jsonl(&dir, &metadata).map_err(|e| e.to_string())
     }
 
+    /// Drop every event after `keep_through_seq` (e.g. when rewinding a chat).
+    ///
+    /// Pending events are flushed first, and a registered session continues
+    /// numbering from the truncated length. Returns the number removed.
+    pub fn truncate_events(
+        &self,
+        project_name: &str,
+        workspace_name: &str,
+        chat_id: &str,
+        keep_through_seq: u64,
+    ) -> Result<u64, String> {
+        let dir = self.get_chat_dir(project_name, workspace_name)?;
+        let session = {
+            let sessions = self.sessions.lock().unwrap();
+            sessions.get(chat_id).cloned()
+        };
+
+        let Some(session) = session else {
+            let removed = truncate_chat_events(&dir, chat_id, keep_through_seq)
+                .map_err(|e| e.to_string())?;
+            self.search
+                .truncate_chat(chat_id, keep_through_seq)
+                .map_err(|e| e.to_string())?;
+            return Ok(removed);
+        };
+
+        // Hold the session lock so nothing is appended mid-rewrite.
+        let mut session = session.lock().unwrap();
+        session.flush().map_err(|e| e.to_string())?;
+        // The rewrite replaces the file; reopen it on the next flush.
+        session.file_handle = None;
+        let removed =
+            truncate_chat_events(&dir, chat_id, keep_through_seq).map_err(|e| e.to_string())?;
+        session.next_seq = count_events(&dir, chat_id).map_err(|e| e.to_string())? + 1;
+        self.search
+            .truncate_chat(chat_id, keep_through_seq)
+            .map_err(|e| e.to_string())?;
+        Ok(removed)
+    }
+
+    /// Search every chat's messages, tool calls and tool results.
+    ///
+    /// Covers events of registered sessions that are still buffered.
+    pub fn search(&self, query: &SearchQuery) -> Result<SearchResults, String> {
+        self.search.search(query).map_err(|e| e.to_string())
+    }
+
+    /// Export a chat as Markdown, HTML or a bundle (as JSON).
+    ///
+    /// A registered session's buffered events are flushed first.
+    pub fn export_chat(
+        &self,
+        project_name: &str,
+        workspace_name: &str,
+        chat_id: &str,
+        format: ExportFormat,
+        view: View,
+    ) -> Result<String, String> {
+        let dir = self.get_chat_dir(project_name, workspace_name)?;
+        let config_dir = self
+            .config_dir()
+            .ok_or_else(|| "Config directory not set".to_string())?;
+        self.flush(chat_id)?;
+        chat_export::export_chat(&config_dir, &dir, chat_id, format, view)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Import a `.overseer-chat` bundle into a workspace's chats.
+    ///
+    /// Returns the imported chat's metadata (its id changes if the workspace
+    /// already has a chat with the bundled id).
+    pub fn import_chat(
+        &self,
+        project_name: &str,
+        workspace_name: &str,
+        workspace_id: &str,
+        bundle_json: &str,
+    ) -> Result<ChatMetadata, String> {
+        let dir = self.get_chat_dir(project_name, workspace_name)?;
+        let config_dir = self
+            .config_dir()
+            .ok_or_else(|| "Config directory not set".to_string())?;
+        let bundle = chat_export::parse_bundle(bundle_json).map_err(|e| e.to_string())?;
+        chat_export::import_bundle(&config_dir, &dir, workspace_id, bundle)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Fork a chat at `at_seq` into a new chat in the same workspace (see
+    /// [`chat_fork`]).
+    ///
+    /// `claude_root` is Claude Code's config directory, used to cut a Claude
+    /// session at the fork point. A registered session's buffered events are
+    /// flushed first.
+    pub fn fork_chat(
+        &self,
+        project_name: &str,
+        workspace_name: &str,
+        chat_id: &str,
+        at_seq: u64,
+        claude_root: Option<&Path>,
+    ) -> Result<ForkedChat, String> {
+        let dir = self.get_chat_dir(project_name, workspace_name)?;
+        let config_dir = self
+            .config_dir()
+            .ok_or_else(|| "Config directory not set".to_string())?;
+        self.flush(chat_id)?;
+        chat_fork::fork_chat(&config_dir, &dir, chat_id, at_seq, claude_root)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Where a chat was forked from, if it is a fork.
+    pub fn load_chat_fork(
+        &self,
+        project_name: &str,
+        workspace_name: &str,
+        chat_id: &str,
+    ) -> Result<Option<ChatFork>, String> {
+        let dir = self.get_chat_dir(project_name, workspace_name)?;
+        chat_fork::load_chat_fork(&dir, chat_id).map_err(|e| e.to_string())
+    }
+
+    /// Plan how a chat's agent resumes once the chat is cut back to
+    /// `through_seq` (see [`chat_fork::plan_rewind`]).
+    pub fn plan_rewind(
+        &self,
+        project_name: &str,
+        workspace_name: &str,
+        chat_id: &str,
+        through_seq: u64,
+        claude_root: Option<&Path>,
+    ) -> Result<Option<ChatFork>, String> {
+        let dir = self.get_chat_dir(project_name, workspace_name)?;
+        self.flush(chat_id)?;
+        chat_fork::plan_rewind(&dir, chat_id, through_seq, claude_root).map_err(|e| e.to_string())
+    }
+
+    /// Take a registered chat's pending fork plan, for its agent's first
+    /// start. `None` if the chat isn't registered or isn't a fork, or the
+    /// plan was already taken.
+    pub fn take_pending_fork(&self, chat_id: &str) -> Result<Option<ChatFork>, String> {
+        let Some(dir) = self.chat_dir(chat_id) else {
+            return Ok(None);
+        };
+        chat_fork::take_pending_fork(&dir, chat_id).map_err(|e| e.to_string())
+    }
+
+    /// The directory a registered session's chat is stored in.
+    pub fn chat_dir(&self, chat_id: &str) -> Option<PathBuf> {
+        let session = {
+            let sessions = self.sessions.lock().unwrap();
+            sessions.get(chat_id).cloned()
+        };
+        session.and_then(|session| {
+            let session = session.lock().unwrap();
+            session.jsonl_path.parent().map(Path::to_path_buf)
+        })
+    }
+
+    /// Hand a chat over to `to_agent` (see [`chat_handoff`]).
+    ///
+    /// The chat switches to the new agent with a fresh session: no agent
+    /// session, model or permission mode carries over. Its index entry
+    /// follows, and the context transfer prompt is recorded as a handoff
+    /// message. `touched` is the workspace's changed files. The caller stops
+    /// the previous agent first and starts the new one with the prompt.
+    pub fn hand_off(
+        &self,
+        project_name: &str,
+        workspace_name: &str,
+        chat_id: &str,
+        to_agent: &str,
+        touched: &[crate::git::ChangedFile],
+    ) -> Result<ChatHandoff, String> {
+        let dir = self.get_chat_dir(project_name, workspace_name)?;
+        self.flush(chat_id)?;
+        let mut metadata = load_chat_metadata_jsonl(&dir, chat_id).map_err(|e| e.to_string())?;
+        let from_agent = metadata
+            .agent_type
+            .clone()
+            .unwrap_or_else(|| "claude".to_string());
+        if from_agent == to_agent {
+            return Err(format!(
+                "Chat is already on {}",
+                chat_handoff::agent_display_name(Some(to_agent))
+            ));
+        }
+        let events = load_events_with_seq_jsonl(&dir, chat_id).map_err(|e| e.to_string())?;
+        let handoff = chat_handoff::handoff_prompt(Some(&from_agent), to_agent, &events, touched);
+
+        let now = Utc::now();
+        metadata.agent_type = Some(to_agent.to_string());
+        metadata.agent_session_id = None;
+        metadata.model_version = None;
+        metadata.permission_mode = None;
+        metadata.updated_at = now;
+        self.register_session(
+            chat_id.to_string(),
+            project_name.to_string(),
+            workspace_name.to_string(),
+            metadata.clone(),
+        )?;
+        update_chat_index(&dir, |index| {
+            if let Some(entry) = index.chats.iter_mut().find(|e| e.id == chat_id) {
+                entry.agent_type = Some(to_agent.to_string());
+                entry.updated_at = now;
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+        let event = AgentEvent::UserMessage {
+            id: Uuid::new_v4().to_string(),
+            content: handoff.prompt.clone(),
+            timestamp: now,
+            meta: Some(chat_handoff::handoff_meta(&handoff, &from_agent, to_agent)),
+        };
+        let seq = self.append_event_with_seq(chat_id, event.clone())?;
+        Ok(ChatHandoff {
+            metadata,
+            handoff,
+            event,
+            seq,
+        })
+    }
+
     /// Add a user message to a chat session.
     pub fn add_user_message(
         &self,
@@ -288,10 +570,11 @@ impl ChatSession {
             .as_mut()
             .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "Missing file handle"))?;
 
+        let dir = self.jsonl_path.parent().unwrap_or(Path::new(""));
         for event in self.pending_events.drain(..) {
-            let line = serialize_event_for_storage(&event)
+            let line = serialize_event_for_storage(dir, &event)
                 .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-            writeln!(writer, "{line}")?;
+            writeln!(writer, "{}", encode_line(dir, &line)?)?;
         }
 
         writer.flush()?;
@@ -916,6 +1199,131 @@ mod tests {
         }
     }
 
+    #[test]
+    fn truncate_events_flushes_and_renumbers() {
+        let test_dir = TestChatDir::new();
+        let manager = ChatSessionManager::new();
+        manager.set_config_dir(test_dir.path().to_path_buf());
+        manager
+            .register_session(
+                "chat-123".to_string(),
+                "test-project".to_string(),
+                "test-workspace".to_string(),
+                sample_chat_metadata("chat-123"),
+            )
+            .unwrap();
+
+        for i in 0..4 {
+            manager
+                .append_event("chat-123", sample_user_message(&format!("msg {i}")))
+                .unwrap();
+        }
+
+        // Events are still buffered; truncation must flush them first.
+        let removed = manager
+            .truncate_events("test-project", "test-workspace", "chat-123", 2)
+            .unwrap();
+        assert_eq!(removed, 2);
+
+        let seq = manager
+            .append_event_with_seq("chat-123", sample_user_message("after"))
+            .unwrap();
+        assert_eq!(seq, 3);
+        manager.unregister_session("chat-123").unwrap();
+
+        let events = manager
+            .load_events_with_seq("test-project", "test-workspace", "chat-123")
+            .unwrap();
+        assert_eq!(
+            events.iter().map(|e| e.seq).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn search_sees_buffered_and_rewound_events() {
+        let test_dir = TestChatDir::new();
+        let manager = ChatSessionManager::new();
+        manager.set_config_dir(test_dir.path().to_path_buf());
+        manager
+            .register_session(
+                "chat-123".to_string(),
+                "test-project".to_string(),
+                "test-workspace".to_string(),
+                sample_chat_metadata("chat-123"),
+            )
+            .unwrap();
+        manager
+            .append_event("chat-123", sample_user_message("find the flaky test"))
+            .unwrap();
+        manager
+            .append_event("chat-123", sample_user_message("flaky again"))
+            .unwrap();
+
+        let query = SearchQuery {
+            query: "flaky".to_string(),
+            ..SearchQuery::default()
+        };
+        // Both events are still buffered.
+        assert_eq!(manager.search(&query).unwrap().total, 2);
+
+        manager
+            .truncate_events("test-project", "test-workspace", "chat-123", 1)
+            .unwrap();
+        let results = manager.search(&query).unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].seq, 1);
+    }
+
+    #[test]
+    fn hand_off_switches_the_agent_and_records_the_prompt() {
+        let test_dir = TestChatDir::new();
+        let manager = ChatSessionManager::new();
+        manager.set_config_dir(test_dir.path().to_path_buf());
+        let mut metadata = sample_chat_metadata("chat-123");
+        metadata.agent_session_id = Some("claude-session".to_string());
+        manager
+            .register_session(
+                "chat-123".to_string(),
+                "test-project".to_string(),
+                "test-workspace".to_string(),
+                metadata,
+            )
+            .unwrap();
+        manager
+            .append_event("chat-123", sample_user_message("Build a CSV importer"))
+            .unwrap();
+
+        let handoff = manager
+            .hand_off("test-project", "test-workspace", "chat-123", "codex", &[])
+            .unwrap();
+
+        assert_eq!(handoff.seq, 2);
+        let loaded = manager
+            .load_metadata("test-project", "test-workspace", "chat-123")
+            .unwrap();
+        assert_eq!(loaded.agent_type.as_deref(), Some("codex"));
+        assert_eq!(loaded.agent_session_id, None);
+        assert_eq!(loaded.model_version, None);
+
+        manager.flush("chat-123").unwrap();
+        let events = manager
+            .load_events("test-project", "test-workspace", "chat-123")
+            .unwrap();
+        let Some(AgentEvent::UserMessage { content, meta, .. }) = events.last() else {
+            panic!("Expected the handoff message last");
+        };
+        assert!(content.contains("## Goal\n\nBuild a CSV importer"));
+        let meta = meta.as_ref().unwrap();
+        assert_eq!(meta["type"], "handoff");
+        assert_eq!(meta["label"], "Handed off from Claude to Codex");
+        assert_eq!(meta["fromAgent"], "claude");
+        assert_eq!(meta["toAgent"], "codex");
+
+        let again = manager.hand_off("test-project", "test-workspace", "chat-123", "codex", &[]);
+        assert!(again.unwrap_err().contains("already on Codex"));
+    }
+
     #[test]
     fn config_dir_not_set_returns_error() {
         // If config_dir isn't set, operations that need it should fail
@@ -13,6 +13,7 @@ use uuid::Uuid;
 
 use crate::agents::claude::{ClaudeConfig, ClaudeParser};
 use crate::agents::event::AgentEvent;
+use crate::chat_fork::{ChatFork, ForkResume};
 use crate::event_bus::EventBus;
 use crate::logging::{log_line, open_log_file, LogHandle};
 use crate::managers::{ChatSessionManager, ProjectApprovalManager};
@@ -68,6 +69,13 @@ pub struct ClaudeStartConfig {
     pub working_dir: String,
     pub agent_path: String,
     pub session_id: Option<String>,
+    /// Resume `session_id` as a new session. Set by [`send_message`] for a
+    /// forked chat's first start (see [`crate::chat_fork`]).
+    ///
+    /// [`send_message`]: ClaudeAgentManager::send_message
+    pub fork_session: bool,
+    /// Resume `session_id` only up to this assistant message id.
+    pub resume_session_at: Option<String>,
     pub model_version: Option<String>,
     pub log_dir: Option<String>,
     pub log_id: Option<String>,
@@ -164,6 +172,8 @@ impl ClaudeAgentManager {
             working_dir: config.working_dir,
             prompt: config.prompt.clone(),
             session_id: config.session_id,
+            fork_session: config.fork_session,
+            resume_session_at: config.resume_session_at,
             model: config.model_version,
             permission_mode: config.permission_mode,
             shell_prefix: config.agent_shell,
@@ -389,13 +399,29 @@ impl ClaudeAgentManager {
     ///
     /// The user message is emitted as an event so all connected clients
     /// (both Tauri windows and web clients) can update their state.
+    ///
+    /// A forked chat's first start picks up from the fork point: a native
+    /// fork of the source session, or the replayed conversation ahead of the
+    /// prompt.
     pub fn send_message(
         &self,
-        config: ClaudeStartConfig,
+        mut config: ClaudeStartConfig,
         event_bus: Arc<EventBus>,
         approval_manager: Arc<ProjectApprovalManager>,
         chat_sessions: Arc<ChatSessionManager>,
     ) -> Result<(), String> {
+        if config.session_id.is_none() && !self.is_running(&config.conversation_id) {
+            match chat_sessions.take_pending_fork(&config.conversation_id) {
+                Ok(Some(fork)) => apply_fork(&mut config, fork),
+                Ok(None) => {}
+                Err(err) => log::warn!(
+                    "Failed to read fork plan for {}: {}",
+                    config.conversation_id,
+                    err
+                ),
+            }
+        }
+
         // Create and emit user message event so all clients can see it.
         // Mark as "system" so UI knows to hide it (the actual user message
         // was already persisted by the frontend before calling send_message).
@@ -459,6 +485,24 @@ impl ClaudeAgentManager {
     }
 }
 
+/// Point a forked chat's first start at the fork point.
+fn apply_fork(config: &mut ClaudeStartConfig, fork: ChatFork) {
+    match fork.resume {
+        ForkResume::Claude {
+            session_id,
+            resume_at,
+        } => {
+            config.session_id = Some(session_id);
+            config.fork_session = true;
+            config.resume_session_at = resume_at;
+        }
+        // Forked from another agent's chat, or nothing native to resume.
+        ForkResume::Codex { .. } | ForkResume::Replay => {
+            config.prompt = format!("{}\n\n{}", fork.replay_prompt, config.prompt);
+        }
+    }
+}
+
 /// Build a control_response JSON to send approval to the agent.
 fn build_approval_response(request_id: &str, input: &serde_json::Value) -> String {
     let response = serde_json::json!({
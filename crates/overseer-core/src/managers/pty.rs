@@ -41,6 +41,7 @@ use std::{
 };
 
 use crate::event_bus::EventBus;
+use crate::processes::{self, ProcessSource};
 
 // ============================================================================
 // PUBLIC TYPES
@@ -168,6 +169,11 @@ pub trait PtyHandle: Send {
 
     /// Kill the PTY process.
     fn kill(&mut self) -> Result<(), String>;
+
+    /// OS process ID of the shell, if known.
+    fn process_id(&self) -> Option<u32> {
+        None
+    }
 }
 
 // ============================================================================
@@ -327,6 +333,10 @@ impl PtyHandle for NativePtyHandle {
         }
         Ok(())
     }
+
+    fn process_id(&self) -> Option<u32> {
+        self.child.as_ref().and_then(|child| child.process_id())
+    }
 }
 
 // ============================================================================
@@ -420,6 +430,10 @@ impl PtyManager {
         let pty_pair = self.backend.open_pty(config.cols, config.rows)?;
 
         // Build environment variables
+        let workspace = config
+            .workspace_root
+            .clone()
+            .unwrap_or_else(|| config.cwd.clone());
         let mut env = Vec::new();
         if let Some(root) = config.workspace_root {
             env.push(("WORKSPACE_ROOT".to_string(), root));
@@ -428,6 +442,12 @@ impl PtyManager {
         // Spawn shell
         let mut handle = pty_pair.spawn_shell(&config.shell, &config.cwd, env)?;
 
+        // Track the shell so archiving the workspace stops it and what it started
+        let pid = handle.process_id();
+        if let Some(pid) = pid {
+            processes::tracker().track(pid, workspace, ProcessSource::Terminal);
+        }
+
         // Get reader and writer
         let reader = handle.take_reader()?;
         let writer = handle.take_writer()?;
@@ -460,6 +480,9 @@ impl PtyManager {
                 }
             }
             // Emit exit event when reader closes
+            if let Some(pid) = pid {
+                processes::tracker().untrack(pid);
+            }
             event_bus.emit(&format!("pty:exit:{}", read_id), &PtyExit { code: None });
         });
 
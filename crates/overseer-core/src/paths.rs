@@ -30,17 +30,23 @@ pub fn expand_config_dir(raw: Option<&str>, home: &str) -> Option<String> {
         return None;
     }
 
+    Some(expand_home(trimmed, home))
+}
+
+/// Replace a leading `~` or `$HOME` in `path` with `home`.
+///
+/// Anything else passes through unchanged.
+pub fn expand_home(path: &str, home: &str) -> String {
     let home = home.trim_end_matches('/');
-    let expanded = if trimmed == "~" || trimmed == "$HOME" {
+    if path == "~" || path == "$HOME" {
         home.to_string()
-    } else if let Some(rest) = trimmed.strip_prefix("~/") {
+    } else if let Some(rest) = path.strip_prefix("~/") {
         format!("{home}/{rest}")
-    } else if let Some(rest) = trimmed.strip_prefix("$HOME/") {
+    } else if let Some(rest) = path.strip_prefix("$HOME/") {
         format!("{home}/{rest}")
     } else {
-        trimmed.to_string()
-    };
-    Some(expanded)
+        path.to_string()
+    }
 }
 
 /// The effective Claude config dir: the expanded `CLAUDE_CONFIG_DIR` override, or
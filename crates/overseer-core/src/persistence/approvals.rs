@@ -28,24 +28,31 @@
 //! use overseer_core::persistence::approvals::*;
 //!
 //! // Load approvals (returns empty if file doesn't exist)
-//! let approvals = load_approvals(workspace_dir)?;
+//! let loaded = load_approvals_versioned(workspace_dir)?;
 //!
 //! // Add a trusted tool
-//! let mut approvals = approvals;
+//! let mut approvals = loaded.value;
 //! add_tool_name(&mut approvals, "Read");
 //!
 //! // Add a trusted command prefix
 //! add_command_prefix(&mut approvals, "git ");
 //!
-//! // Save back
-//! save_approvals(workspace_dir, &approvals)?;
+//! // Save back, unless someone else changed them since
+//! save_approvals(workspace_dir, &approvals, loaded.version.as_ref())?;
 //! ```
 
 use std::fs;
 use std::path::Path;
 
+use super::locking::{
+    check_unchanged, lock_exclusive, lock_shared, record_seen, Version, Versioned,
+};
+use super::storage::{into_io_error, storage_for, StorageError};
 use super::types::ApprovalsData;
 
+/// The approvals file in a project's chat directory.
+pub const APPROVALS_FILE: &str = "approvals.json";
+
 // ============================================================================
 // FILE OPERATIONS
 // ============================================================================
@@ -62,29 +69,28 @@ use super::types::ApprovalsData;
 ///
 /// * `dir` - Directory to save approvals to (usually workspace data dir)
 /// * `approvals` - The approvals data to save
+/// * `base` - Version of the approvals `approvals` was made from (see
+///   [`load_approvals_versioned`]; `None` if there were none)
+///
+/// # Compare-and-swap
+///
+/// If the approvals changed since `base`, the save fails with a
+/// [`StorageError::Conflict`] (see [`is_conflict`](super::storage::is_conflict))
+/// instead of overwriting them. [`update_approvals`] doesn't have to worry
+/// about that.
 ///
 /// # Errors
 ///
 /// Returns an error if the directory doesn't exist and can't be created,
 /// or if file operations fail.
-pub fn save_approvals(dir: &Path, approvals: &ApprovalsData) -> Result<(), std::io::Error> {
-    // Ensure the directory exists before writing
-    fs::create_dir_all(dir)?;
-
-    let file_path = dir.join("approvals.json");
-    let temp_path = dir.join("approvals.json.tmp");
-
-    // Serialize to pretty JSON for readability (users may inspect this file)
-    let json = serde_json::to_string_pretty(approvals)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-
-    // Write to temp file first
-    fs::write(&temp_path, json)?;
-
-    // Atomic rename (on Unix, this is guaranteed atomic)
-    fs::rename(&temp_path, &file_path)?;
-
-    Ok(())
+pub fn save_approvals(
+    dir: &Path,
+    approvals: &ApprovalsData,
+    base: Option<&Version>,
+) -> Result<Version, std::io::Error> {
+    storage_for(dir)
+        .save_approvals(dir, approvals, base)
+        .map_err(into_io_error)
 }
 
 /// Load approvals data from disk.
@@ -103,18 +109,25 @@ pub fn save_approvals(dir: &Path, approvals: &ApprovalsData) -> Result<(), std::
 /// Returns an error only if the file exists but can't be read or parsed.
 /// Missing file is not an error (returns empty approvals).
 pub fn load_approvals(dir: &Path) -> Result<ApprovalsData, std::io::Error> {
-    let file_path = dir.join("approvals.json");
-
-    // If file doesn't exist, return empty approvals (not an error)
-    if !file_path.exists() {
-        return Ok(ApprovalsData::default());
-    }
+    Ok(load_approvals_versioned(dir)?.value)
+}
 
-    let contents = fs::read_to_string(&file_path)?;
-    let approvals: ApprovalsData = serde_json::from_str(&contents)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+/// Load approvals data with its version, to save changes to it with.
+pub fn load_approvals_versioned(dir: &Path) -> Result<Versioned<ApprovalsData>, std::io::Error> {
+    storage_for(dir).load_approvals(dir).map_err(into_io_error)
+}
 
-    Ok(approvals)
+/// Load, change and save approvals under an exclusive lock (one transaction
+/// with SQLite), so approvals added by another process aren't lost.
+///
+/// Returns the saved approvals.
+pub fn update_approvals(
+    dir: &Path,
+    mut update: impl FnMut(&mut ApprovalsData),
+) -> Result<ApprovalsData, std::io::Error> {
+    storage_for(dir)
+        .update_approvals(dir, &mut update)
+        .map_err(into_io_error)
 }
 
 /// Delete approvals file from disk.
@@ -129,7 +142,59 @@ pub fn load_approvals(dir: &Path) -> Result<ApprovalsData, std::io::Error> {
 ///
 /// Returns an error if deletion fails. Does not error if file doesn't exist.
 pub fn delete_approvals(dir: &Path) -> Result<(), std::io::Error> {
-    let file_path = dir.join("approvals.json");
+    storage_for(dir)
+        .delete_approvals(dir)
+        .map_err(into_io_error)
+}
+
+pub(super) fn write_approvals_file(
+    dir: &Path,
+    approvals: &ApprovalsData,
+    base: Option<&Version>,
+) -> Result<Version, StorageError> {
+    let file_path = dir.join(APPROVALS_FILE);
+    let temp_path = dir.join("approvals.json.tmp");
+
+    // Locking creates the directory. Hold the lock until the rename so no
+    // other process writes in between.
+    let _lock = lock_exclusive(&file_path)?;
+    let current = parse_approvals_file(&file_path)?;
+    check_unchanged(&file_path, base, current.as_ref())?;
+
+    // Serialize to pretty JSON for readability (users may inspect this file)
+    let json = serde_json::to_string_pretty(approvals)?;
+
+    // Write to temp file first
+    fs::write(&temp_path, json)?;
+
+    // Atomic rename (on Unix, this is guaranteed atomic)
+    fs::rename(&temp_path, &file_path)?;
+
+    record_seen(&file_path, Some(approvals))?;
+    Ok(Version::of(approvals)?)
+}
+
+pub(super) fn read_approvals_file(dir: &Path) -> Result<Versioned<ApprovalsData>, StorageError> {
+    let file_path = dir.join(APPROVALS_FILE);
+
+    let _lock = lock_shared(&file_path)?;
+    // If file doesn't exist, return empty approvals (not an error)
+    Versioned::loaded(&file_path, parse_approvals_file(&file_path)?)
+}
+
+pub(super) fn parse_approvals_file(
+    file_path: &Path,
+) -> Result<Option<ApprovalsData>, StorageError> {
+    if !file_path.exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(file_path)?;
+    Ok(Some(serde_json::from_str(&contents)?))
+}
+
+pub(super) fn delete_approvals_file(dir: &Path) -> Result<(), StorageError> {
+    let file_path = dir.join(APPROVALS_FILE);
 
     // Only try to delete if it exists
     if file_path.exists() {
@@ -293,7 +358,7 @@ mod tests {
             command_prefixes: vec!["git ".to_string()],
         };
 
-        save_approvals(dir.path(), &approvals).unwrap();
+        save_approvals(dir.path(), &approvals, None).unwrap();
         let loaded = load_approvals(dir.path()).unwrap();
 
         assert_eq!(loaded.tool_names.len(), 2);
@@ -320,7 +385,7 @@ mod tests {
             command_prefixes: vec![],
         };
 
-        save_approvals(dir.path(), &approvals).unwrap();
+        save_approvals(dir.path(), &approvals, None).unwrap();
         assert!(dir.path().join("approvals.json").exists());
 
         delete_approvals(dir.path()).unwrap();
@@ -462,7 +527,7 @@ mod tests {
         add_command_prefix(&mut approvals, "cargo ");
 
         // Save
-        save_approvals(dir.path(), &approvals).unwrap();
+        let base = save_approvals(dir.path(), &approvals, None).unwrap();
 
         // Reload and verify
         let loaded = load_approvals(dir.path()).unwrap();
@@ -476,7 +541,7 @@ mod tests {
         let mut approvals = loaded;
         remove_tool_name(&mut approvals, "Glob");
         remove_command_prefix(&mut approvals, "git ");
-        save_approvals(dir.path(), &approvals).unwrap();
+        save_approvals(dir.path(), &approvals, Some(&base)).unwrap();
 
         // Verify removal
         let loaded = load_approvals(dir.path()).unwrap();
@@ -16,6 +16,7 @@
 use std::fs;
 use std::path::Path;
 
+use super::storage::{into_io_error, storage_for};
 use super::types::ChatFile;
 
 /// Error type for chat persistence operations.
@@ -126,7 +127,7 @@ pub fn load_chat(dir: &Path, chat_id: &str) -> Result<ChatFile, ChatError> {
 pub fn delete_chat(dir: &Path, chat_id: &str) -> Result<(), ChatError> {
     let file_path = dir.join(format!("{chat_id}.json"));
     let jsonl_path = dir.join(format!("{chat_id}.jsonl"));
-    let meta_path = dir.join(format!("{chat_id}.meta.json"));
+    let fork_path = dir.join(format!("{chat_id}.fork.json"));
 
     if file_path.exists() {
         fs::remove_file(&file_path)?;
@@ -134,8 +135,11 @@ pub fn delete_chat(dir: &Path, chat_id: &str) -> Result<(), ChatError> {
     if jsonl_path.exists() {
         fs::remove_file(&jsonl_path)?;
     }
-    if meta_path.exists() {
-        fs::remove_file(&meta_path)?;
+    storage_for(dir)
+        .delete_chat_metadata(dir, chat_id)
+        .map_err(into_io_error)?;
+    if fork_path.exists() {
+        fs::remove_file(&fork_path)?;
     }
 
     Ok(())
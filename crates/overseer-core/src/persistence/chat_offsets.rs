@@ -0,0 +1,262 @@
+//! Seq → byte offset sidecar for chat JSONL files.
+//!
+//! Long chats have tens of thousands of events, and finding event N in
+//! `{chat_id}.jsonl` means reading every line before it. The sidecar,
+//! `{chat_id}.offsets.json`, records where line `seq + 1` starts for every
+//! [`CHECKPOINT_INTERVAL`]th seq, so a read can seek to the nearest
+//! checkpoint and only parse the lines after it.
+//!
+//! Checkpoints are added as lines are read (see
+//! [`chat_jsonl`](super::chat_jsonl)); nothing is written on append.
+//!
+//! # Staleness
+//!
+//! The sidecar is thrown away and rebuilt from the start of the file when:
+//!
+//! - it can't be parsed
+//! - the file's first line changed (the chat was deleted and recreated)
+//! - its last checkpoint is past the end of the file, or doesn't sit right
+//!   after a newline (the file was rewritten or shrank)
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A checkpoint is recorded every this many lines.
+pub(crate) const CHECKPOINT_INTERVAL: u64 = 512;
+
+/// Bytes of the first line hashed to recognise the file.
+const HEAD_BYTES: usize = 4096;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OffsetFile {
+    /// Hash of the start of the first line
+    head: String,
+    /// `(seq, offset)`: line `seq + 1` starts at byte `offset`, ascending
+    checkpoints: Vec<(u64, u64)>,
+}
+
+/// The offset sidecar of one chat, loaded and checked against its file.
+#[derive(Debug)]
+pub(crate) struct OffsetIndex {
+    path: PathBuf,
+    head: String,
+    checkpoints: Vec<(u64, u64)>,
+    dirty: bool,
+}
+
+impl OffsetIndex {
+    /// Load the sidecar of `{chat_id}.jsonl`, which is open as `file`.
+    ///
+    /// A missing or stale sidecar loads as empty. Leaves `file`'s position
+    /// unspecified.
+    pub(crate) fn load(dir: &Path, chat_id: &str, file: &mut fs::File) -> std::io::Result<Self> {
+        let path = dir.join(format!("{chat_id}.offsets.json"));
+        let head = read_head(file)?;
+        let stored = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<OffsetFile>(&contents).ok())
+            .unwrap_or_default();
+
+        let mut index = Self {
+            path,
+            head,
+            checkpoints: Vec::new(),
+            dirty: false,
+        };
+        if stored.head != index.head || stored.checkpoints.is_empty() {
+            index.dirty = !stored.checkpoints.is_empty();
+            return Ok(index);
+        }
+        let &(_, last_offset) = stored.checkpoints.last().expect("checked non-empty");
+        if ends_line_at(file, last_offset)? {
+            index.checkpoints = stored.checkpoints;
+        } else {
+            index.dirty = true;
+        }
+        Ok(index)
+    }
+
+    /// Where to start reading to see every line after `since_seq`:
+    /// `(seq of the line before, byte offset)`.
+    pub(crate) fn start_for(&self, since_seq: u64) -> (u64, u64) {
+        let before = self
+            .checkpoints
+            .partition_point(|&(seq, _)| seq <= since_seq);
+        match before {
+            0 => (0, 0),
+            n => self.checkpoints[n - 1],
+        }
+    }
+
+    /// Note that line `seq` (its bytes without the newline are `line`) ends
+    /// at `offset`.
+    pub(crate) fn record(&mut self, seq: u64, line: &[u8], offset: u64) {
+        if seq == 1 {
+            let head = hash_head(line);
+            if head != self.head {
+                self.head = head;
+                self.checkpoints.clear();
+                self.dirty = true;
+            }
+        }
+        if !seq.is_multiple_of(CHECKPOINT_INTERVAL) {
+            return;
+        }
+        if self.checkpoints.last().is_none_or(|&(last, _)| last < seq) {
+            self.checkpoints.push((seq, offset));
+            self.dirty = true;
+        }
+    }
+
+    /// Drop checkpoints past `keep_through_seq` (the file was cut there).
+    pub(crate) fn truncate(&mut self, keep_through_seq: u64) {
+        let keep = self
+            .checkpoints
+            .partition_point(|&(seq, _)| seq <= keep_through_seq);
+        if keep < self.checkpoints.len() {
+            self.checkpoints.truncate(keep);
+            self.dirty = true;
+        }
+        if keep_through_seq == 0 {
+            self.head.clear();
+            self.dirty = true;
+        }
+    }
+
+    /// Write the sidecar if it changed. Failures are logged; the sidecar is
+    /// only a cache.
+    pub(crate) fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        if self.checkpoints.is_empty() {
+            if self.path.exists() {
+                if let Err(e) = fs::remove_file(&self.path) {
+                    log::warn!("Failed to remove {}: {e}", self.path.display());
+                }
+            }
+            return;
+        }
+
+        let stored = OffsetFile {
+            head: self.head.clone(),
+            checkpoints: self.checkpoints.clone(),
+        };
+        let temp_path = self.path.with_extension("json.tmp");
+        let result = serde_json::to_string(&stored)
+            .map_err(std::io::Error::other)
+            .and_then(|json| fs::write(&temp_path, json))
+            .and_then(|()| fs::rename(&temp_path, &self.path));
+        if let Err(e) = result {
+            log::warn!("Failed to write {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Hash of the first line of `file`, or empty if it has no complete line.
+fn read_head(file: &mut fs::File) -> std::io::Result<String> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut buf = Vec::with_capacity(HEAD_BYTES + 1);
+    file.by_ref()
+        .take(HEAD_BYTES as u64 + 1)
+        .read_to_end(&mut buf)?;
+    Ok(match buf.iter().position(|&b| b == b'\n') {
+        Some(end) => hash_head(&buf[..end]),
+        // Longer than what's hashed, so at least that much is there.
+        None if buf.len() > HEAD_BYTES => hash_head(&buf),
+        None => String::new(),
+    })
+}
+
+fn hash_head(line: &[u8]) -> String {
+    let digest = Sha256::digest(&line[..line.len().min(HEAD_BYTES)]);
+    digest.iter().take(8).map(|b| format!("{b:02x}")).collect()
+}
+
+/// True if `offset` is within `file` and right after a newline.
+fn ends_line_at(file: &mut fs::File, offset: u64) -> std::io::Result<bool> {
+    if offset == 0 || offset > file.metadata()?.len() {
+        return Ok(false);
+    }
+    file.seek(SeekFrom::Start(offset - 1))?;
+    let mut byte = [0u8];
+    file.read_exact(&mut byte)?;
+    Ok(byte[0] == b'\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn write_lines(dir: &Path, count: u64) -> fs::File {
+        let path = dir.join("chat.jsonl");
+        let mut file = fs::File::create(&path).unwrap();
+        for i in 1..=count {
+            writeln!(file, "{{\"line\":{i}}}").unwrap();
+        }
+        fs::File::open(path).unwrap()
+    }
+
+    #[test]
+    fn start_for_picks_nearest_checkpoint_at_or_before() {
+        let index = OffsetIndex {
+            path: PathBuf::new(),
+            head: String::new(),
+            checkpoints: vec![(512, 1000), (1024, 2000)],
+            dirty: false,
+        };
+        assert_eq!(index.start_for(0), (0, 0));
+        assert_eq!(index.start_for(511), (0, 0));
+        assert_eq!(index.start_for(512), (512, 1000));
+        assert_eq!(index.start_for(5000), (1024, 2000));
+    }
+
+    #[test]
+    fn saved_checkpoints_load_back_while_file_matches() {
+        let dir = tempdir().unwrap();
+        let mut file = write_lines(dir.path(), 3);
+        let mut index = OffsetIndex::load(dir.path(), "chat", &mut file).unwrap();
+        index.record(1, b"{\"line\":1}", 11);
+        index.checkpoints.push((2, 22));
+        index.dirty = true;
+        index.save();
+
+        let loaded = OffsetIndex::load(dir.path(), "chat", &mut file).unwrap();
+        assert_eq!(loaded.checkpoints, vec![(2, 22)]);
+    }
+
+    #[test]
+    fn stale_sidecar_loads_empty() {
+        let dir = tempdir().unwrap();
+        let mut file = write_lines(dir.path(), 3);
+        let mut index = OffsetIndex::load(dir.path(), "chat", &mut file).unwrap();
+        // Not a line boundary.
+        index.checkpoints.push((2, 20));
+        index.dirty = true;
+        index.save();
+        assert!(OffsetIndex::load(dir.path(), "chat", &mut file)
+            .unwrap()
+            .checkpoints
+            .is_empty());
+
+        // Recreated with a different first line.
+        let mut index = OffsetIndex::load(dir.path(), "chat", &mut file).unwrap();
+        index.checkpoints.push((2, 22));
+        index.dirty = true;
+        index.save();
+        let path = dir.path().join("chat.jsonl");
+        fs::write(&path, "{\"other\":1}\n{\"line\":2}\n{\"line\":3}\n").unwrap();
+        let mut file = fs::File::open(path).unwrap();
+        assert!(OffsetIndex::load(dir.path(), "chat", &mut file)
+            .unwrap()
+            .checkpoints
+            .is_empty());
+    }
+}
@@ -0,0 +1,961 @@
+//! Encrypting chats at rest.
+//!
+//! A config dir can keep its chat event logs, chat metadata, attachments and
+//! the search log encrypted. Everything else (projects, chat indexes,
+//! approvals) stays plain: it's needed to find chats and holds no messages.
+//!
+//! # Keys
+//!
+//! `encryption.json` in the config dir says where the key comes from:
+//!
+//! - **Keyfile.** 32 random bytes in `chat.key` next to it, readable only by
+//!   the owner. Anyone who can read the config dir can read the chats, but a
+//!   copy of the chats alone (a backup, a synced folder) can't be read.
+//! - **Passphrase.** The key is derived from a passphrase with
+//!   PBKDF2-HMAC-SHA256. The passphrase comes from `OVERSEER_CHAT_PASSPHRASE`
+//!   when the app or daemon starts, or from the app's unlock prompt, and is
+//!   never stored.
+//!
+//! [`open_encryption`] reads the key at startup and registers it for the
+//! config dir, the same way [`open_storage`](super::open_storage) registers a
+//! database. The chat functions pick it up from the directory they're given,
+//! so [`ChatSessionManager`](crate::managers::ChatSessionManager) and the
+//! other callers don't change. If it can't open the key, the config dir is
+//! locked: chat data under it can't be written until a later
+//! [`open_encryption`] succeeds, rather than being written in plain.
+//!
+//! # Format
+//!
+//! Data is sealed with ChaCha20-Poly1305 under a random nonce.
+//!
+//! - A JSONL line becomes `enc1:` and the base64 of nonce and ciphertext, so
+//!   logs stay line-based: appends, the offset sidecar and torn-line repair
+//!   work as before. A log can mix plain and encrypted lines.
+//! - A metadata file (or SQLite row) is one such line.
+//! - An attachment is [`ATTACHMENT_MAGIC`], the nonce and the ciphertext.
+//!   Agents read attachments by path, so [`readable_attachment_path`] puts a
+//!   decrypted copy in a private temp dir for them. A chat's copies are
+//!   removed when its turn ends, the rest when the process exits.
+//!
+//! [`encrypt_config_dir`] and [`decrypt_config_dir`] convert an existing
+//! config dir in place. Stop the app and the daemon first.
+
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Read};
+use std::num::NonZeroU32;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use super::chat_jsonl::{load_chat_metadata, save_chat_metadata, ChatJsonlError};
+use super::index::load_chat_index;
+use super::types::ChatMetadata;
+
+/// The config dir's encryption settings.
+pub const ENCRYPTION_FILE: &str = "encryption.json";
+
+/// The keyfile, in the config dir.
+pub const KEY_FILE: &str = "chat.key";
+
+/// Environment variable holding the passphrase.
+pub const PASSPHRASE_ENV: &str = "OVERSEER_CHAT_PASSPHRASE";
+
+/// Start of an encrypted line.
+const LINE_PREFIX: &str = "enc1:";
+
+/// Start of an encrypted attachment.
+pub const ATTACHMENT_MAGIC: &[u8] = b"OVRENC1\n";
+
+/// PBKDF2 rounds for new passphrase keys.
+const PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Sealed with a passphrase key to tell a wrong passphrase from a right one.
+const CHECK_PLAINTEXT: &str = "overseer";
+
+/// Start of the name of each process's temp dir for decrypted attachments.
+const READABLE_ATTACHMENTS_DIR: &str = "overseer-attachments";
+
+/// Keys opened by [`open_encryption`], one per config dir.
+static KEYS: RwLock<Vec<Arc<ChatKey>>> = RwLock::new(Vec::new());
+
+/// Encrypted config dirs whose key [`open_encryption`] couldn't open.
+static LOCKED: RwLock<Vec<PathBuf>> = RwLock::new(Vec::new());
+
+/// This process's temp dir for decrypted attachments, once it has made one.
+static READABLE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Error type for chat encryption.
+#[derive(Debug)]
+pub enum EncryptionError {
+    /// IO error
+    Io(io::Error),
+    /// JSON error
+    Json(serde_json::Error),
+    /// The key is derived from a passphrase and none was given
+    PassphraseRequired,
+    /// The passphrase doesn't match the one the chats were encrypted with
+    WrongPassphrase,
+    /// The config dir isn't encrypted
+    NotEncrypted(PathBuf),
+}
+
+impl std::fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptionError::Io(e) => write!(f, "IO error: {e}"),
+            EncryptionError::Json(e) => write!(f, "JSON error: {e}"),
+            EncryptionError::PassphraseRequired => {
+                write!(
+                    f,
+                    "Chats are encrypted with a passphrase: set {PASSPHRASE_ENV}"
+                )
+            }
+            EncryptionError::WrongPassphrase => write!(f, "Wrong chat passphrase"),
+            EncryptionError::NotEncrypted(dir) => {
+                write!(f, "Chats in {} are not encrypted", dir.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncryptionError {}
+
+impl From<io::Error> for EncryptionError {
+    fn from(e: io::Error) -> Self {
+        EncryptionError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for EncryptionError {
+    fn from(e: serde_json::Error) -> Self {
+        EncryptionError::Json(e)
+    }
+}
+
+impl From<ChatJsonlError> for EncryptionError {
+    fn from(e: ChatJsonlError) -> Self {
+        match e {
+            ChatJsonlError::Io(e) => EncryptionError::Io(e),
+            ChatJsonlError::Json(e) => EncryptionError::Json(e),
+            other => EncryptionError::Io(io::Error::other(other)),
+        }
+    }
+}
+
+/// Contents of `encryption.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "keySource", rename_all = "camelCase")]
+enum EncryptionConfig {
+    /// The key is in [`KEY_FILE`].
+    Keyfile,
+    /// The key is derived from a passphrase.
+    #[serde(rename_all = "camelCase")]
+    Passphrase {
+        /// Base64 PBKDF2 salt.
+        salt: String,
+        iterations: u32,
+        /// [`CHECK_PLAINTEXT`] as an encrypted line.
+        check: String,
+    },
+}
+
+/// What [`encrypt_config_dir`] or [`decrypt_config_dir`] rewrote.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EncryptionReport {
+    pub chats: usize,
+    pub metadata: usize,
+    pub attachments: usize,
+}
+
+/// The key for a config dir's chats.
+pub struct ChatKey {
+    root: PathBuf,
+    key: LessSafeKey,
+}
+
+impl ChatKey {
+    fn new(root: &Path, bytes: &[u8]) -> io::Result<Self> {
+        let key = UnboundKey::new(&CHACHA20_POLY1305, bytes)
+            .map_err(|_| invalid_data("Chat key has the wrong length"))?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            key: LessSafeKey::new(key),
+        })
+    }
+
+    /// Nonce followed by the ciphertext and tag.
+    fn seal(&self, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| io::Error::other("No random numbers for a nonce"))?;
+        let mut sealed = Vec::with_capacity(NONCE_LEN + plaintext.len() + 16);
+        sealed.extend_from_slice(&nonce);
+        let mut in_out = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut in_out,
+            )
+            .map_err(|_| io::Error::other("Failed to encrypt chat data"))?;
+        sealed.extend_from_slice(&in_out);
+        Ok(sealed)
+    }
+
+    fn open(&self, sealed: &[u8]) -> io::Result<Vec<u8>> {
+        let undecryptable = || invalid_data("Can't decrypt chat data: wrong key or corrupted");
+        if sealed.len() < NONCE_LEN {
+            return Err(undecryptable());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| undecryptable())?;
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut in_out)
+            .map_err(|_| undecryptable())?;
+        Ok(plaintext.to_vec())
+    }
+
+    fn encode_line(&self, line: &str) -> io::Result<String> {
+        let sealed = self.seal(line.as_bytes())?;
+        Ok(format!(
+            "{LINE_PREFIX}{}",
+            base64::engine::general_purpose::STANDARD.encode(sealed)
+        ))
+    }
+
+    fn decode_line(&self, line: &[u8]) -> io::Result<Vec<u8>> {
+        let encoded = &line[LINE_PREFIX.len()..];
+        let sealed = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim_ascii())
+            .map_err(|_| invalid_data("Can't decrypt chat data: bad encoding"))?;
+        self.open(&sealed)
+    }
+}
+
+/// Open the key for `config_dir` if its chats are encrypted, so reads and
+/// writes under it decrypt and encrypt. `passphrase` is needed if the key
+/// is derived from one. Returns whether the chats are encrypted.
+///
+/// On failure the config dir stays locked (see [`is_locked`]). Call it
+/// again with the right passphrase to unlock it.
+pub fn open_encryption(
+    config_dir: &Path,
+    passphrase: Option<&str>,
+) -> Result<bool, EncryptionError> {
+    let key = read_config(config_dir).and_then(|config| {
+        config
+            .map(|config| load_key(config_dir, &config, passphrase))
+            .transpose()
+    });
+    match key {
+        Ok(Some(key)) => {
+            register(key);
+            Ok(true)
+        }
+        Ok(None) => Ok(false),
+        Err(e) => {
+            let mut locked = LOCKED.write().unwrap();
+            if !locked.iter().any(|dir| dir == config_dir) {
+                locked.push(config_dir.to_path_buf());
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Whether `config_dir`'s chats are encrypted.
+pub fn is_encrypted(config_dir: &Path) -> bool {
+    config_dir.join(ENCRYPTION_FILE).exists()
+}
+
+/// Whether `config_dir`'s chats are encrypted and [`open_encryption`]
+/// couldn't open their key, so they can't be read or written.
+pub fn is_locked(config_dir: &Path) -> bool {
+    LOCKED.read().unwrap().iter().any(|dir| dir == config_dir)
+}
+
+/// The passphrase from [`PASSPHRASE_ENV`], if set.
+pub fn passphrase_from_env() -> Option<String> {
+    std::env::var(PASSPHRASE_ENV)
+        .ok()
+        .filter(|passphrase| !passphrase.is_empty())
+}
+
+/// Encode a JSONL line (without its newline) for a file in `dir`: encrypted
+/// if `dir` has an open key, unchanged otherwise. Fails if `dir` is in a
+/// locked config dir.
+pub fn encode_line(dir: &Path, line: &str) -> io::Result<String> {
+    match key_for(dir) {
+        Some(key) => key.encode_line(line),
+        None => {
+            check_unlocked(dir)?;
+            Ok(line.to_string())
+        }
+    }
+}
+
+/// Decode a line read from a file in `dir`. Plain lines are returned as
+/// they are.
+pub fn decode_line<'a>(dir: &Path, line: &'a [u8]) -> io::Result<Cow<'a, [u8]>> {
+    if !line.starts_with(LINE_PREFIX.as_bytes()) {
+        return Ok(Cow::Borrowed(line));
+    }
+    let key = key_for(dir).ok_or_else(|| no_key(dir))?;
+    Ok(Cow::Owned(key.decode_line(line)?))
+}
+
+/// Write an attachment, encrypted if its directory has an open key. Fails
+/// if it's in a locked config dir.
+pub fn write_attachment(path: &Path, data: &[u8]) -> io::Result<()> {
+    match key_for(path) {
+        Some(key) => fs::write(path, encode_attachment(&key, data)?),
+        None => {
+            check_unlocked(path)?;
+            fs::write(path, data)
+        }
+    }
+}
+
+/// Read an attachment, decrypting it if needed.
+pub fn read_attachment(path: &Path) -> io::Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    match data.strip_prefix(ATTACHMENT_MAGIC) {
+        Some(sealed) => key_for(path).ok_or_else(|| no_key(path))?.open(sealed),
+        None => Ok(data),
+    }
+}
+
+/// A path where the attachment at `path` can be read as is: `path` itself if
+/// it isn't encrypted, else a decrypted copy readable only by the owner. For
+/// agents and viewers that read attachments by path.
+///
+/// Copies go in a temp dir of this process's own. Those made for `chat_id`'s
+/// agent are removed when its turn ends ([`release_readable_attachments`]),
+/// the others when the process exits ([`remove_readable_attachments`]).
+pub fn readable_attachment_path(path: &Path, chat_id: Option<&str>) -> io::Result<PathBuf> {
+    let mut magic = [0u8; ATTACHMENT_MAGIC.len()];
+    let mut file = fs::File::open(path)?;
+    if file.read_exact(&mut magic).is_err() || magic != ATTACHMENT_MAGIC {
+        return Ok(path.to_path_buf());
+    }
+    let data = read_attachment(path)?;
+
+    // Attachments live in `attachments/{id}/{filename}`; keep both.
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| invalid_data("Attachment path has no file name"))?;
+    let mut dir = readable_dir()?;
+    match chat_id {
+        Some(chat_id) => dir.push(chat_copies(chat_id)?),
+        None => dir.push("shared"),
+    }
+    if let Some(id) = path.parent().and_then(Path::file_name) {
+        dir.push(id);
+    }
+    create_private_dir(&dir)?;
+    let readable = dir.join(file_name);
+    write_private(&readable, &data)?;
+    Ok(readable)
+}
+
+/// Remove the decrypted attachments made for `chat_id`, once its turn is
+/// over.
+pub fn release_readable_attachments(chat_id: &str) -> io::Result<()> {
+    let Some(dir) = READABLE_DIR.lock().unwrap().clone() else {
+        return Ok(());
+    };
+    remove_dir_if_exists(&dir.join(chat_copies(chat_id)?))
+}
+
+/// Remove this process's decrypted attachments. Call it on exit.
+pub fn remove_readable_attachments() -> io::Result<()> {
+    match READABLE_DIR.lock().unwrap().take() {
+        Some(dir) => remove_dir_if_exists(&dir),
+        None => Ok(()),
+    }
+}
+
+/// This process's temp dir for decrypted attachments, made on first use.
+/// The name is random and the directory must not exist yet, so nobody else
+/// can have made it (or made it readable).
+fn readable_dir() -> io::Result<PathBuf> {
+    let mut readable_dir = READABLE_DIR.lock().unwrap();
+    if let Some(dir) = &*readable_dir {
+        return Ok(dir.clone());
+    }
+    let mut suffix = [0u8; 8];
+    SystemRandom::new()
+        .fill(&mut suffix)
+        .map_err(|_| io::Error::other("No random numbers for a temp dir"))?;
+    let name = format!(
+        "{READABLE_ATTACHMENTS_DIR}-{}-{}",
+        std::process::id(),
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(suffix)
+    );
+    let dir = std::env::temp_dir().join(name);
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&dir)?;
+    *readable_dir = Some(dir.clone());
+    Ok(dir)
+}
+
+/// Where a chat's copies go in the readable dir.
+fn chat_copies(chat_id: &str) -> io::Result<PathBuf> {
+    let mut components = Path::new(chat_id).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(Path::new("chats").join(chat_id)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid chat id: {chat_id}"),
+        )),
+    }
+}
+
+fn remove_dir_if_exists(dir: &Path) -> io::Result<()> {
+    match fs::remove_dir_all(dir) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Encrypt `config_dir`'s chats in place, creating the key: derived from
+/// `passphrase` if given, else a new keyfile. Running it again on an
+/// encrypted config dir (e.g. after an interruption) finishes the job with
+/// the existing key.
+pub fn encrypt_config_dir(
+    config_dir: &Path,
+    passphrase: Option<&str>,
+) -> Result<EncryptionReport, EncryptionError> {
+    let key = match read_config(config_dir)? {
+        Some(config) => load_key(config_dir, &config, passphrase)?,
+        None => create_key(config_dir, passphrase, PBKDF2_ITERATIONS)?,
+    };
+    let key = register(key);
+    let encrypt = |line: &[u8]| -> io::Result<Vec<u8>> {
+        let plain = decode_with(&key, line)?;
+        let plain = std::str::from_utf8(&plain).map_err(invalid_data)?;
+        Ok(key.encode_line(plain)?.into_bytes())
+    };
+
+    let mut report = EncryptionReport::default();
+    let chat_dirs = chat_dirs(config_dir);
+    for log in chat_logs(&chat_dirs) {
+        rewrite_lines(&log, encrypt)?;
+        report.chats += 1;
+    }
+    // Loaded through the storage backend and saved again, now encrypted.
+    for (dir, metadata) in chat_metadata(&chat_dirs)? {
+        save_chat_metadata(&dir, &metadata)?;
+        report.metadata += 1;
+    }
+    for attachment in attachments(config_dir) {
+        let data = read_attachment(&attachment)?;
+        write_private(&attachment, &encode_attachment(&key, &data)?)?;
+        report.attachments += 1;
+    }
+    drop_derived_files(config_dir, &chat_dirs)?;
+    Ok(report)
+}
+
+/// Decrypt `config_dir`'s chats in place and remove the key.
+pub fn decrypt_config_dir(
+    config_dir: &Path,
+    passphrase: Option<&str>,
+) -> Result<EncryptionReport, EncryptionError> {
+    let Some(config) = read_config(config_dir)? else {
+        return Err(EncryptionError::NotEncrypted(config_dir.to_path_buf()));
+    };
+    let key = register(load_key(config_dir, &config, passphrase)?);
+    let decrypt = |line: &[u8]| decode_with(&key, line).map(Cow::into_owned);
+
+    let mut report = EncryptionReport::default();
+    let chat_dirs = chat_dirs(config_dir);
+    for log in chat_logs(&chat_dirs) {
+        rewrite_lines(&log, decrypt)?;
+        report.chats += 1;
+    }
+    for attachment in attachments(config_dir) {
+        let data = read_attachment(&attachment)?;
+        write_private(&attachment, &data)?;
+        report.attachments += 1;
+    }
+    // Read while the key is open, saved plain once it's closed.
+    let metadata = chat_metadata(&chat_dirs)?;
+    unregister(config_dir);
+    for (dir, metadata) in metadata {
+        save_chat_metadata(&dir, &metadata)?;
+        report.metadata += 1;
+    }
+    drop_derived_files(config_dir, &chat_dirs)?;
+
+    fs::remove_file(config_dir.join(ENCRYPTION_FILE))?;
+    let key_file = config_dir.join(KEY_FILE);
+    if key_file.exists() {
+        fs::remove_file(key_file)?;
+    }
+    Ok(report)
+}
+
+/// The open key for files in `dir`.
+fn key_for(dir: &Path) -> Option<Arc<ChatKey>> {
+    let keys = KEYS.read().unwrap();
+    keys.iter().find(|key| dir.starts_with(&key.root)).cloned()
+}
+
+/// Fail if `path` is in a locked config dir: without the key, what's
+/// written there would be in plain.
+fn check_unlocked(path: &Path) -> io::Result<()> {
+    if LOCKED
+        .read()
+        .unwrap()
+        .iter()
+        .any(|dir| path.starts_with(dir))
+    {
+        return Err(no_key(path));
+    }
+    Ok(())
+}
+
+fn register(key: ChatKey) -> Arc<ChatKey> {
+    let key = Arc::new(key);
+    LOCKED.write().unwrap().retain(|dir| *dir != key.root);
+    let mut keys = KEYS.write().unwrap();
+    keys.retain(|open| open.root != key.root);
+    keys.push(key.clone());
+    key
+}
+
+fn unregister(config_dir: &Path) {
+    KEYS.write().unwrap().retain(|key| key.root != config_dir);
+}
+
+fn read_config(config_dir: &Path) -> Result<Option<EncryptionConfig>, EncryptionError> {
+    let path = config_dir.join(ENCRYPTION_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+}
+
+fn load_key(
+    config_dir: &Path,
+    config: &EncryptionConfig,
+    passphrase: Option<&str>,
+) -> Result<ChatKey, EncryptionError> {
+    match config {
+        EncryptionConfig::Keyfile => {
+            let encoded = fs::read_to_string(config_dir.join(KEY_FILE))?;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(encoded.trim())
+                .map_err(|_| invalid_data("Chat keyfile is not base64"))?;
+            Ok(ChatKey::new(config_dir, &bytes)?)
+        }
+        EncryptionConfig::Passphrase {
+            salt,
+            iterations,
+            check,
+        } => {
+            let passphrase = passphrase.ok_or(EncryptionError::PassphraseRequired)?;
+            let salt = base64::engine::general_purpose::STANDARD
+                .decode(salt)
+                .map_err(|_| invalid_data("Passphrase salt is not base64"))?;
+            let key = derive_key(config_dir, passphrase, &salt, *iterations)?;
+            match key.decode_line(check.as_bytes()) {
+                Ok(plain) if plain == CHECK_PLAINTEXT.as_bytes() => Ok(key),
+                _ => Err(EncryptionError::WrongPassphrase),
+            }
+        }
+    }
+}
+
+/// Create a new key and write `encryption.json` (and the keyfile).
+fn create_key(
+    config_dir: &Path,
+    passphrase: Option<&str>,
+    iterations: u32,
+) -> Result<ChatKey, EncryptionError> {
+    let rng = SystemRandom::new();
+    let (key, config) = match passphrase {
+        Some(passphrase) => {
+            let mut salt = [0u8; 16];
+            rng.fill(&mut salt)
+                .map_err(|_| io::Error::other("No random numbers for a salt"))?;
+            let key = derive_key(config_dir, passphrase, &salt, iterations)?;
+            let config = EncryptionConfig::Passphrase {
+                salt: base64::engine::general_purpose::STANDARD.encode(salt),
+                iterations,
+                check: key.encode_line(CHECK_PLAINTEXT)?,
+            };
+            (key, config)
+        }
+        None => {
+            let mut bytes = [0u8; 32];
+            rng.fill(&mut bytes)
+                .map_err(|_| io::Error::other("No random numbers for a key"))?;
+            fs::create_dir_all(config_dir)?;
+            write_private(
+                &config_dir.join(KEY_FILE),
+                base64::engine::general_purpose::STANDARD
+                    .encode(bytes)
+                    .as_bytes(),
+            )?;
+            (ChatKey::new(config_dir, &bytes)?, EncryptionConfig::Keyfile)
+        }
+    };
+    fs::write(
+        config_dir.join(ENCRYPTION_FILE),
+        serde_json::to_string_pretty(&config)?,
+    )?;
+    Ok(key)
+}
+
+fn derive_key(
+    config_dir: &Path,
+    passphrase: &str,
+    salt: &[u8],
+    iterations: u32,
+) -> Result<ChatKey, EncryptionError> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| invalid_data("Passphrase iterations must not be zero"))?;
+    let mut bytes = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut bytes,
+    );
+    Ok(ChatKey::new(config_dir, &bytes)?)
+}
+
+fn decode_with<'a>(key: &ChatKey, line: &'a [u8]) -> io::Result<Cow<'a, [u8]>> {
+    if line.starts_with(LINE_PREFIX.as_bytes()) {
+        Ok(Cow::Owned(key.decode_line(line)?))
+    } else {
+        Ok(Cow::Borrowed(line))
+    }
+}
+
+fn encode_attachment(key: &ChatKey, data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoded = ATTACHMENT_MAGIC.to_vec();
+    encoded.extend(key.seal(data)?);
+    Ok(encoded)
+}
+
+/// Every directory under `chats/` (workspaces, archived workspaces).
+fn chat_dirs(config_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut pending = vec![config_dir.join("chats")];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                pending.push(entry.path());
+            }
+        }
+        dirs.push(dir);
+    }
+    dirs
+}
+
+fn files_in(dir: &Path) -> Vec<(PathBuf, String)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            Some((entry.path(), name))
+        })
+        .collect()
+}
+
+fn chat_logs(chat_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    chat_dirs
+        .iter()
+        .flat_map(|dir| files_in(dir))
+        .filter(|(_, name)| name.ends_with(".jsonl"))
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Metadata of every chat with a log, a metadata document or an index entry.
+fn chat_metadata(chat_dirs: &[PathBuf]) -> Result<Vec<(PathBuf, ChatMetadata)>, EncryptionError> {
+    let mut found = Vec::new();
+    for dir in chat_dirs {
+        let mut chat_ids: Vec<String> = files_in(dir)
+            .into_iter()
+            .filter_map(|(_, name)| {
+                let id = name
+                    .strip_suffix(".meta.json")
+                    .or_else(|| name.strip_suffix(".jsonl"))?;
+                Some(id.to_string())
+            })
+            .collect();
+        let index = load_chat_index(dir).map_err(io::Error::other)?;
+        chat_ids.extend(index.chats.into_iter().map(|entry| entry.id));
+        chat_ids.sort();
+        chat_ids.dedup();
+        for chat_id in chat_ids {
+            match load_chat_metadata(dir, &chat_id) {
+                Ok(metadata) => found.push((dir.clone(), metadata)),
+                Err(ChatJsonlError::NotFound(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn attachments(config_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(config_dir.join("attachments")) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .flat_map(|entry| files_in(&entry.path()))
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Rewrite each complete line of `path` through a temp file. A torn last
+/// line is dropped.
+fn rewrite_lines(path: &Path, convert: impl Fn(&[u8]) -> io::Result<Vec<u8>>) -> io::Result<()> {
+    let contents = fs::read(path)?;
+    let mut rewritten = Vec::with_capacity(contents.len());
+    for line in contents.split_inclusive(|&b| b == b'\n') {
+        let Some(line) = line.strip_suffix(b"\n") else {
+            log::warn!("Dropping torn last line of {}", path.display());
+            break;
+        };
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            rewritten.extend(convert(line)?);
+        }
+        rewritten.push(b'\n');
+    }
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    write_private(Path::new(&temp_path), &rewritten)?;
+    fs::rename(&temp_path, path)
+}
+
+/// Remove what's rebuilt from the chats: offset sidecars (they point into the
+/// old lines) and the search log (it holds message text).
+fn drop_derived_files(config_dir: &Path, chat_dirs: &[PathBuf]) -> io::Result<()> {
+    for dir in chat_dirs {
+        for (path, name) in files_in(dir) {
+            if name.ends_with(".offsets.json") {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+    let search_log = config_dir.join("search").join("chats.jsonl");
+    if search_log.exists() {
+        fs::remove_file(search_log)?;
+    }
+    Ok(())
+}
+
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
+
+/// Write a file only the owner can read.
+fn write_private(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    io::Write::write_all(&mut options.open(path)?, data)
+}
+
+fn no_key(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!(
+            "{} is encrypted and no chat key is open (give the chat passphrase or restore {KEY_FILE})",
+            path.display()
+        ),
+    )
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::event::AgentEvent;
+    use crate::persistence::chat_jsonl::{append_chat_event, load_chat_events};
+    use chrono::Utc;
+    use tempfile::tempdir;
+
+    fn metadata(id: &str) -> ChatMetadata {
+        ChatMetadata {
+            id: id.to_string(),
+            workspace_id: "ws-1".to_string(),
+            label: "Secret plans".to_string(),
+            agent_type: Some("claude".to_string()),
+            agent_session_id: None,
+            model_version: None,
+            permission_mode: None,
+            sandboxed: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn text(text: &str) -> AgentEvent {
+        AgentEvent::Text {
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn chats_round_trip_in_place() {
+        let config = tempdir().unwrap();
+        let dir = config.path().join("chats").join("repo").join("main");
+        append_chat_event(&dir, "chat-1", &text("before")).unwrap();
+        save_chat_metadata(&dir, &metadata("chat-1")).unwrap();
+        let attachment_dir = config.path().join("attachments").join("a1");
+        fs::create_dir_all(&attachment_dir).unwrap();
+        let attachment = attachment_dir.join("notes.md");
+        fs::write(&attachment, "# notes").unwrap();
+
+        let report = encrypt_config_dir(config.path(), None).unwrap();
+        assert_eq!(
+            report,
+            EncryptionReport {
+                chats: 1,
+                metadata: 1,
+                attachments: 1,
+            }
+        );
+        append_chat_event(&dir, "chat-1", &text("after")).unwrap();
+
+        let log = fs::read_to_string(dir.join("chat-1.jsonl")).unwrap();
+        assert!(log.lines().all(|line| line.starts_with(LINE_PREFIX)));
+        assert!(!log.contains("before") && !log.contains("after"));
+        let meta = fs::read_to_string(dir.join("chat-1.meta.json")).unwrap();
+        assert!(!meta.contains("Secret plans"));
+        assert!(fs::read(&attachment).unwrap().starts_with(ATTACHMENT_MAGIC));
+
+        let events = load_chat_events(&dir, "chat-1").unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            load_chat_metadata(&dir, "chat-1").unwrap().label,
+            "Secret plans"
+        );
+        assert_eq!(read_attachment(&attachment).unwrap(), b"# notes");
+        let readable = readable_attachment_path(&attachment, None).unwrap();
+        assert_ne!(readable, attachment);
+        assert_eq!(fs::read(&readable).unwrap(), b"# notes");
+
+        decrypt_config_dir(config.path(), None).unwrap();
+        assert!(!is_encrypted(config.path()));
+        assert!(!config.path().join(KEY_FILE).exists());
+        let log = fs::read_to_string(dir.join("chat-1.jsonl")).unwrap();
+        assert!(log.contains("before") && log.contains("after"));
+        assert_eq!(load_chat_events(&dir, "chat-1").unwrap().len(), 2);
+        assert_eq!(fs::read(&attachment).unwrap(), b"# notes");
+        assert_eq!(
+            readable_attachment_path(&attachment, None).unwrap(),
+            attachment
+        );
+    }
+
+    #[test]
+    fn a_chats_decrypted_attachments_go_when_its_turn_ends() {
+        let config = tempdir().unwrap();
+        register(create_key(config.path(), None, 1).unwrap());
+        let attachment_dir = config.path().join("attachments").join("a1");
+        fs::create_dir_all(&attachment_dir).unwrap();
+        let attachment = attachment_dir.join("plan.md");
+        write_attachment(&attachment, b"# plan").unwrap();
+
+        let readable = readable_attachment_path(&attachment, Some("chat-1")).unwrap();
+        assert_eq!(fs::read(&readable).unwrap(), b"# plan");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let run_dir = readable.ancestors().nth(4).unwrap();
+            assert!(run_dir.starts_with(std::env::temp_dir()));
+            let mode = fs::metadata(run_dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        assert!(readable_attachment_path(&attachment, Some("../chat-1")).is_err());
+
+        release_readable_attachments("chat-1").unwrap();
+        assert!(!readable.exists());
+        release_readable_attachments("chat-1").unwrap();
+        unregister(config.path());
+    }
+
+    #[test]
+    fn passphrase_keys_need_the_right_passphrase() {
+        let config = tempdir().unwrap();
+        register(create_key(config.path(), Some("hunter2"), 1_000).unwrap());
+        let dir = config.path().join("chats").join("repo").join("main");
+        append_chat_event(&dir, "chat-1", &text("hello")).unwrap();
+        unregister(config.path());
+
+        // Without the key the chat can't be read.
+        assert!(load_chat_events(&dir, "chat-1").is_err());
+        assert!(matches!(
+            open_encryption(config.path(), None),
+            Err(EncryptionError::PassphraseRequired)
+        ));
+        assert!(matches!(
+            open_encryption(config.path(), Some("wrong")),
+            Err(EncryptionError::WrongPassphrase)
+        ));
+        assert!(open_encryption(config.path(), Some("hunter2")).unwrap());
+        assert_eq!(load_chat_events(&dir, "chat-1").unwrap().len(), 1);
+        unregister(config.path());
+    }
+
+    #[test]
+    fn nothing_is_written_in_plain_until_a_locked_dir_is_unlocked() {
+        let config = tempdir().unwrap();
+        register(create_key(config.path(), Some("hunter2"), 1_000).unwrap());
+        unregister(config.path());
+        let dir = config.path().join("chats").join("repo").join("main");
+        let attachment = config.path().join("attachments").join("a1").join("plan.md");
+        fs::create_dir_all(attachment.parent().unwrap()).unwrap();
+
+        assert!(open_encryption(config.path(), None).is_err());
+        assert!(is_locked(config.path()));
+        assert!(append_chat_event(&dir, "chat-1", &text("hello")).is_err());
+        assert!(save_chat_metadata(&dir, &metadata("chat-1")).is_err());
+        assert!(write_attachment(&attachment, b"# plan").is_err());
+        assert!(!dir.join("chat-1.jsonl").exists());
+        assert!(!attachment.exists());
+
+        assert!(open_encryption(config.path(), Some("hunter2")).unwrap());
+        assert!(!is_locked(config.path()));
+        append_chat_event(&dir, "chat-1", &text("hello")).unwrap();
+        let log = fs::read_to_string(dir.join("chat-1.jsonl")).unwrap();
+        assert!(log.starts_with(LINE_PREFIX));
+        unregister(config.path());
+    }
+}
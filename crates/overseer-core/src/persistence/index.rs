@@ -13,8 +13,15 @@
 use std::fs;
 use std::path::Path;
 
+use super::locking::{
+    check_unchanged, lock_exclusive, lock_shared, record_seen, Version, Versioned,
+};
+use super::storage::{storage_for, StorageError};
 use super::types::{ChatIndex, ChatIndexEntry, WorkspaceState};
 
+/// The chat index's file in a workspace's chat directory.
+pub const INDEX_FILE: &str = "chats.json";
+
 /// Error type for index operations.
 #[derive(Debug)]
 pub enum IndexError {
@@ -22,6 +29,8 @@ pub enum IndexError {
     Io(std::io::Error),
     /// JSON error
     Json(serde_json::Error),
+    /// Storage backend error
+    Storage(StorageError),
 }
 
 impl std::fmt::Display for IndexError {
@@ -29,6 +38,7 @@ impl std::fmt::Display for IndexError {
         match self {
             IndexError::Io(e) => write!(f, "IO error: {e}"),
             IndexError::Json(e) => write!(f, "JSON error: {e}"),
+            IndexError::Storage(e) => write!(f, "Storage error: {e}"),
         }
     }
 }
@@ -47,40 +57,91 @@ impl From<serde_json::Error> for IndexError {
     }
 }
 
+impl From<StorageError> for IndexError {
+    fn from(e: StorageError) -> Self {
+        match e {
+            StorageError::Io(e) => IndexError::Io(e),
+            StorageError::Json(e) => IndexError::Json(e),
+            other => IndexError::Storage(other),
+        }
+    }
+}
+
 // ============================================================================
 // Chat Index Operations
 // ============================================================================
 
-/// Save the chat index to disk.
+/// Save the chat index to disk, returning its new version.
 ///
-/// Writes to `chats.json` in the workspace directory.
-pub fn save_chat_index(dir: &Path, index: &ChatIndex) -> Result<(), IndexError> {
-    fs::create_dir_all(dir)?;
+/// Writes to `chats.json` in the workspace directory (or the config dir's
+/// database, see [`storage`](super::storage)). `base` is the version of the
+/// index `index` was made from (see [`load_chat_index_versioned`]; `None`
+/// if there was none). Fails with [`StorageError::Conflict`] if it has
+/// changed since. Prefer [`update_chat_index`] for read-modify-writes.
+pub fn save_chat_index(
+    dir: &Path,
+    index: &ChatIndex,
+    base: Option<&Version>,
+) -> Result<Version, IndexError> {
+    Ok(storage_for(dir).save_chat_index(dir, index, base)?)
+}
 
-    let file_path = dir.join("chats.json");
+/// Load the chat index from disk.
+///
+/// Returns an empty index if the file doesn't exist.
+pub fn load_chat_index(dir: &Path) -> Result<ChatIndex, IndexError> {
+    Ok(load_chat_index_versioned(dir)?.value)
+}
+
+/// Load the chat index with its version, to save changes to it with.
+pub fn load_chat_index_versioned(dir: &Path) -> Result<Versioned<ChatIndex>, IndexError> {
+    Ok(storage_for(dir).load_chat_index(dir)?)
+}
+
+/// Load, change and save the chat index under an exclusive lock (one
+/// transaction with SQLite), so concurrent updates don't overwrite each
+/// other.
+pub fn update_chat_index(
+    dir: &Path,
+    mut update: impl FnMut(&mut ChatIndex),
+) -> Result<ChatIndex, IndexError> {
+    Ok(storage_for(dir).update_chat_index(dir, &mut update)?)
+}
+
+pub(super) fn write_index_file(
+    dir: &Path,
+    index: &ChatIndex,
+    base: Option<&Version>,
+) -> Result<Version, StorageError> {
+    let file_path = dir.join(INDEX_FILE);
     let temp_path = dir.join("chats.json.tmp");
 
+    let _lock = lock_exclusive(&file_path)?;
+    let current = parse_index_file(&file_path)?;
+    check_unchanged(&file_path, base, current.as_ref())?;
+
     let json = serde_json::to_string_pretty(index)?;
     fs::write(&temp_path, json)?;
     fs::rename(&temp_path, &file_path)?;
 
-    Ok(())
+    record_seen(&file_path, Some(index))?;
+    Ok(Version::of(index)?)
 }
 
-/// Load the chat index from disk.
-///
-/// Returns an empty index if the file doesn't exist.
-pub fn load_chat_index(dir: &Path) -> Result<ChatIndex, IndexError> {
-    let file_path = dir.join("chats.json");
+pub(super) fn read_index_file(dir: &Path) -> Result<Versioned<ChatIndex>, StorageError> {
+    let file_path = dir.join(INDEX_FILE);
+
+    let _lock = lock_shared(&file_path)?;
+    Versioned::loaded(&file_path, parse_index_file(&file_path)?)
+}
 
+pub(super) fn parse_index_file(file_path: &Path) -> Result<Option<ChatIndex>, StorageError> {
     if !file_path.exists() {
-        return Ok(ChatIndex::default());
+        return Ok(None);
     }
 
-    let contents = fs::read_to_string(&file_path)?;
-    let index: ChatIndex = serde_json::from_str(&contents)?;
-
-    Ok(index)
+    let contents = fs::read_to_string(file_path)?;
+    Ok(Some(serde_json::from_str(&contents)?))
 }
 
 /// Add or update a chat entry in the index.
@@ -139,6 +200,21 @@ pub fn get_archived_chats(index: &ChatIndex) -> Vec<&ChatIndexEntry> {
 ///
 /// Writes to `workspace.json` in the workspace directory.
 pub fn save_workspace_state(dir: &Path, state: &WorkspaceState) -> Result<(), IndexError> {
+    Ok(storage_for(dir).save_workspace_state(dir, state)?)
+}
+
+/// Load workspace state from disk.
+///
+/// Returns default state if file doesn't exist.
+/// Also supports legacy `index.json` format.
+pub fn load_workspace_state(dir: &Path) -> Result<WorkspaceState, IndexError> {
+    Ok(storage_for(dir).load_workspace_state(dir)?)
+}
+
+pub(super) fn write_workspace_state_file(
+    dir: &Path,
+    state: &WorkspaceState,
+) -> Result<(), StorageError> {
     fs::create_dir_all(dir)?;
 
     let file_path = dir.join("workspace.json");
@@ -151,11 +227,7 @@ pub fn save_workspace_state(dir: &Path, state: &WorkspaceState) -> Result<(), In
     Ok(())
 }
 
-/// Load workspace state from disk.
-///
-/// Returns default state if file doesn't exist.
-/// Also supports legacy `index.json` format.
-pub fn load_workspace_state(dir: &Path) -> Result<WorkspaceState, IndexError> {
+pub(super) fn read_workspace_state_file(dir: &Path) -> Result<WorkspaceState, StorageError> {
     let file_path = dir.join("workspace.json");
     let legacy_path = dir.join("index.json");
 
@@ -208,13 +280,73 @@ mod tests {
             chats: vec![make_entry("chat-1", "Test Chat")],
         };
 
-        save_chat_index(dir.path(), &index).unwrap();
+        save_chat_index(dir.path(), &index, None).unwrap();
         let loaded = load_chat_index(dir.path()).unwrap();
 
         assert_eq!(loaded.chats.len(), 1);
         assert_eq!(loaded.chats[0].id, "chat-1");
     }
 
+    #[test]
+    fn stale_save_conflicts_but_update_merges() {
+        let dir = tempdir().unwrap();
+        let mut index = ChatIndex {
+            chats: vec![make_entry("chat-1", "Ours")],
+        };
+        let base = save_chat_index(dir.path(), &index, None).unwrap();
+
+        // Another process adds a chat.
+        let theirs = ChatIndex {
+            chats: vec![make_entry("chat-1", "Ours"), make_entry("chat-2", "Theirs")],
+        };
+        fs::write(
+            dir.path().join(INDEX_FILE),
+            serde_json::to_string(&theirs).unwrap(),
+        )
+        .unwrap();
+
+        // Loading it elsewhere in this process doesn't make our copy current.
+        load_chat_index(dir.path()).unwrap();
+        index.chats.push(make_entry("chat-3", "Stale"));
+        assert!(matches!(
+            save_chat_index(dir.path(), &index, Some(&base)),
+            Err(IndexError::Storage(StorageError::Conflict(_)))
+        ));
+
+        let merged = update_chat_index(dir.path(), |index| {
+            upsert_chat_entry(index, make_entry("chat-3", "Fresh"));
+        })
+        .unwrap();
+        let ids: Vec<&str> = merged.chats.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["chat-1", "chat-2", "chat-3"]);
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = tempdir().unwrap();
+        let handles: Vec<_> = ["app", "daemon"]
+            .into_iter()
+            .map(|writer| {
+                let dir = dir.path().to_path_buf();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        update_chat_index(&dir, |index| {
+                            index
+                                .chats
+                                .push(make_entry(&format!("{writer}-{i}"), writer));
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(load_chat_index(dir.path()).unwrap().chats.len(), 50);
+    }
+
     #[test]
     fn load_nonexistent_index_returns_empty() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,241 @@
+//! Sharing the config dir's documents between processes.
+//!
+//! The desktop app and `overseer-daemon` can use the same config dir, and
+//! both read-modify-write `projects.json`, `chats.json` and `approvals.json`.
+//! Two things keep one from overwriting the other's changes:
+//!
+//! - **Advisory locks.** Each JSON file has a `{name}.lock` file next to it.
+//!   Reads hold a shared lock on it, writes an exclusive one. The `update_*`
+//!   functions hold the exclusive lock across their load and save.
+//! - **Compare-and-swap.** A document's [`Version`] is the SHA-256 of its
+//!   JSON. Loading returns it ([`Versioned`]), and saving takes the version
+//!   the saved document was made from: the save fails with
+//!   [`StorageError::Conflict`] if the document has changed since. The
+//!   caller loads it again, reapplies its change and saves.
+//!
+//! The SQLite backend does the same check inside its write transaction, so
+//! it needs no lock files. [`watch_config_dir`](super::watch::watch_config_dir)
+//! reports changes that another process made.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::storage::StorageError;
+
+/// The version of each document this process last loaded or saved, by the
+/// path of its JSON file. `None` if the document didn't exist. Caches of
+/// what's derived from a document use it to notice changes; saves don't,
+/// since any load refreshes it.
+static SEEN: LazyLock<Mutex<HashMap<PathBuf, Option<Version>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+thread_local! {
+    /// Lock files this thread holds, so nested locking doesn't deadlock.
+    static HELD: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A document's version: the SHA-256 of its JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Version(String);
+
+impl Version {
+    /// The version of `value`.
+    pub fn of<T: Serialize>(value: &T) -> Result<Self, serde_json::Error> {
+        let json = serde_json::to_vec(value)?;
+        Ok(Self(format!("{:x}", Sha256::digest(json))))
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A loaded document and the version it had, to save changes to it with.
+/// Serializes as the document with a `version` field.
+#[derive(Debug, Clone, Serialize)]
+pub struct Versioned<T> {
+    #[serde(flatten)]
+    pub value: T,
+    /// `None` if the document didn't exist (`value` is then the default).
+    pub version: Option<Version>,
+}
+
+impl<T: Serialize + Default> Versioned<T> {
+    /// `value` as loaded from `path`, remembered as seen there.
+    pub(super) fn loaded(path: &Path, value: Option<T>) -> Result<Self, StorageError> {
+        record_seen(path, value.as_ref())?;
+        let version = value.as_ref().map(Version::of).transpose()?;
+        Ok(Self {
+            value: value.unwrap_or_default(),
+            version,
+        })
+    }
+}
+
+/// An advisory lock on a document, released on drop.
+pub(super) struct FileLock {
+    /// `None` if this thread already held the lock.
+    file: Option<File>,
+    path: PathBuf,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            let _ = file.unlock();
+            HELD.with(|held| held.borrow_mut().retain(|path| *path != self.path));
+        }
+    }
+}
+
+/// Take a shared lock on the document at `path`. Nothing is locked if its
+/// directory doesn't exist, since there is nothing to read.
+pub(super) fn lock_shared(path: &Path) -> io::Result<FileLock> {
+    let lock_path = lock_path(path);
+    if lock_path.parent().is_some_and(|dir| !dir.exists()) {
+        return Ok(FileLock {
+            file: None,
+            path: lock_path,
+        });
+    }
+    lock(lock_path, false)
+}
+
+/// Take an exclusive lock on the document at `path`, creating its directory.
+pub(super) fn lock_exclusive(path: &Path) -> io::Result<FileLock> {
+    let lock_path = lock_path(path);
+    if let Some(dir) = lock_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    lock(lock_path, true)
+}
+
+fn lock(lock_path: PathBuf, exclusive: bool) -> io::Result<FileLock> {
+    if HELD.with(|held| held.borrow().contains(&lock_path)) {
+        return Ok(FileLock {
+            file: None,
+            path: lock_path,
+        });
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)?;
+    if exclusive {
+        file.lock()?;
+    } else {
+        file.lock_shared()?;
+    }
+    HELD.with(|held| held.borrow_mut().push(lock_path.clone()));
+    Ok(FileLock {
+        file: Some(file),
+        path: lock_path,
+    })
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    PathBuf::from(lock_path)
+}
+
+/// Record that this process has seen `value` (`None`: no document) at `path`.
+pub(super) fn record_seen<T: Serialize>(
+    path: &Path,
+    value: Option<&T>,
+) -> Result<(), StorageError> {
+    let version = value.map(Version::of).transpose()?;
+    SEEN.lock().unwrap().insert(path.to_path_buf(), version);
+    Ok(())
+}
+
+/// The version of the document at `path` this process last loaded or saved.
+/// `None` if it hasn't; `Some(None)` if the document didn't exist then.
+pub fn last_seen(path: &Path) -> Option<Option<Version>> {
+    SEEN.lock().unwrap().get(path).cloned()
+}
+
+/// Fail with [`StorageError::Conflict`] if `current`, the document now at
+/// `path`, isn't at `base`, the version the caller loaded. A `base` of
+/// `None` (it didn't exist) matches an empty document.
+///
+/// Saving is allowed when the document no longer exists (a deleted
+/// workspace's chats may be recreated).
+pub(super) fn check_unchanged<T: Serialize + Default>(
+    path: &Path,
+    base: Option<&Version>,
+    current: Option<&T>,
+) -> Result<(), StorageError> {
+    let Some(current) = current else {
+        return Ok(());
+    };
+    let base = match base {
+        Some(base) => base.clone(),
+        None => Version::of(&T::default())?,
+    };
+    if base == Version::of(current)? {
+        Ok(())
+    } else {
+        Err(StorageError::Conflict(path.to_path_buf()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn changes_since_the_callers_load_conflict() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("doc.json");
+        let base = Version::of(&vec!["a"]).unwrap();
+
+        check_unchanged(&path, Some(&base), Some(&vec!["a"])).unwrap();
+        assert!(matches!(
+            check_unchanged(&path, Some(&base), Some(&vec!["a", "b"])),
+            Err(StorageError::Conflict(p)) if p == path
+        ));
+        check_unchanged::<Vec<&str>>(&path, Some(&base), None).unwrap();
+
+        // Loaded while missing, created since.
+        assert!(check_unchanged(&path, None, Some(&vec!["c"])).is_err());
+        check_unchanged::<Vec<&str>>(&path, None, Some(&vec![])).unwrap();
+        check_unchanged::<Vec<&str>>(&path, None, None).unwrap();
+
+        // What this process saw last doesn't matter.
+        record_seen(&path, Some(&vec!["a", "b"])).unwrap();
+        assert!(check_unchanged(&path, Some(&base), Some(&vec!["a", "b"])).is_err());
+    }
+
+    #[test]
+    fn locks_are_reentrant_within_a_thread() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("doc.json");
+
+        let outer = lock_exclusive(&path).unwrap();
+        let inner = lock_shared(&path).unwrap();
+        assert!(inner.file.is_none());
+        drop(inner);
+        drop(outer);
+
+        // Another thread can take it once released.
+        let path2 = path.clone();
+        std::thread::spawn(move || lock_exclusive(&path2).map(|_| ()))
+            .join()
+            .unwrap()
+            .unwrap();
+        assert!(dir.path().join("doc.json.lock").exists());
+    }
+}
@@ -8,6 +8,7 @@
 //! - **Index** - Quick-lookup chat index and workspace state
 //! - **Projects** - Project registry with workspace definitions
 //! - **Approvals** - User-approved tools and command prefixes
+//! - **Reviews** - PR review threads already handed to an agent
 //!
 //! # File Locations
 //!
@@ -35,6 +36,25 @@
 //! 1. Write to `file.json.tmp`
 //! 2. Rename to `file.json` (atomic on Unix)
 //!
+//! ## Sharing With Other Processes
+//!
+//! The app and the daemon can share a config dir. Files are locked while
+//! they're read and written, a save takes the [`Version`] of the document
+//! it was made from and fails with [`StorageError::Conflict`] if the
+//! document has changed since, and [`watch_config_dir`] reports changes
+//! with their new version. See [`locking`] and [`watch`].
+//!
+//! ## Storage Backends
+//!
+//! The JSON documents above can live in an SQLite database (`overseer.db`)
+//! instead, after a one-shot migration. See [`storage`].
+//!
+//! ## Encryption
+//!
+//! Chat logs, chat metadata and attachments can be encrypted at rest with a
+//! keyfile or a passphrase. [`open_encryption`] opens the key at startup and
+//! the chat functions encrypt and decrypt transparently. See [`encryption`].
+//!
 //! ## Lazy Loading
 //!
 //! Chat messages are loaded on-demand, not at startup. The index
@@ -54,29 +74,55 @@
 pub mod approvals;
 pub mod chat;
 pub mod chat_jsonl;
+mod chat_offsets;
+pub mod encryption;
 pub mod index;
+pub mod locking;
 pub mod projects;
+pub mod reviews;
+mod sqlite;
+pub mod storage;
 pub mod types;
+pub mod watch;
 
 // Re-export commonly used items for convenience
 pub use approvals::{
     add_command_prefix, add_tool_name, command_matches_prefix, delete_approvals,
-    has_command_prefix, has_tool_name, load_approvals, remove_command_prefix, remove_tool_name,
-    save_approvals,
+    has_command_prefix, has_tool_name, load_approvals, load_approvals_versioned,
+    remove_command_prefix, remove_tool_name, save_approvals, update_approvals,
 };
 pub use chat::{chat_exists, delete_chat, list_chat_ids, load_chat, save_chat};
 pub use chat_jsonl::{
     append_chat_event, count_events, load_chat_events, load_chat_events_since_seq,
-    load_chat_events_with_seq, load_chat_metadata, migrate_chat_if_needed, save_chat_metadata,
-    serialize_event_for_storage, ChatJsonlError, SeqEvent,
+    load_chat_events_with_seq, load_chat_metadata, migrate_chat_if_needed, repair_torn_tail,
+    save_chat_events, save_chat_metadata, serialize_event_for_storage, truncate_chat_events,
+    ChatJsonlError, SeqEvent,
+};
+pub use encryption::{
+    decrypt_config_dir, encrypt_config_dir, is_locked, open_encryption, passphrase_from_env,
+    read_attachment, readable_attachment_path, release_readable_attachments,
+    remove_readable_attachments, write_attachment, EncryptionError, EncryptionReport,
 };
 pub use index::{
-    find_chat_entry, get_active_chats, get_archived_chats, load_chat_index, load_workspace_state,
-    remove_chat_entry, save_chat_index, save_workspace_state, upsert_chat_entry, IndexError,
+    find_chat_entry, get_active_chats, get_archived_chats, load_chat_index,
+    load_chat_index_versioned, load_workspace_state, remove_chat_entry, save_chat_index,
+    save_workspace_state, update_chat_index, upsert_chat_entry, IndexError,
 };
+pub use locking::{Version, Versioned};
 pub use projects::{
-    add_workspace, find_project, find_project_by_path, find_workspace, find_workspace_by_branch,
-    get_active_workspaces, get_archived_workspaces, load_project_registry, remove_project,
-    remove_workspace, save_project_registry, upsert_project, ProjectError,
+    add_workspace, find_project, find_project_by_path, find_project_by_workspace_path,
+    find_workspace, find_workspace_by_branch, get_active_workspaces, get_archived_workspaces,
+    load_project_registry, load_project_registry_versioned, remove_project, remove_workspace,
+    save_project_registry, update_project_registry, upsert_project, ProjectError,
+};
+pub use reviews::{
+    load_addressed_review_threads, mark_review_threads_addressed, save_addressed_review_threads,
+};
+pub use storage::{
+    is_conflict, migrate_to_sqlite, open_storage, rename_chat_dir, MigrationReport, Storage,
+    StorageBackend, StorageError,
 };
 pub use types::*;
+pub use watch::{
+    watch_config_dir, ConfigChange, ConfigDocument, ConfigWatcher, CONFIG_CHANGED_EVENT,
+};
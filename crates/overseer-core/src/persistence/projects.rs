@@ -3,8 +3,15 @@
 use std::fs;
 use std::path::Path;
 
+use super::locking::{
+    check_unchanged, lock_exclusive, lock_shared, record_seen, Version, Versioned,
+};
+use super::storage::{storage_for, StorageError};
 use super::types::{Project, ProjectRegistry, Workspace};
 
+/// The project registry's file in the config dir.
+pub const PROJECTS_FILE: &str = "projects.json";
+
 /// Error type for project operations.
 #[derive(Debug)]
 pub enum ProjectError {
@@ -14,6 +21,8 @@ pub enum ProjectError {
     Json(serde_json::Error),
     /// Project not found
     NotFound(String),
+    /// Storage backend error
+    Storage(StorageError),
 }
 
 impl std::fmt::Display for ProjectError {
@@ -22,6 +31,7 @@ impl std::fmt::Display for ProjectError {
             ProjectError::Io(e) => write!(f, "IO error: {e}"),
             ProjectError::Json(e) => write!(f, "JSON error: {e}"),
             ProjectError::NotFound(id) => write!(f, "Project not found: {id}"),
+            ProjectError::Storage(e) => write!(f, "Storage error: {e}"),
         }
     }
 }
@@ -40,18 +50,28 @@ impl From<serde_json::Error> for ProjectError {
     }
 }
 
-/// Save the project registry to disk.
-pub fn save_project_registry(dir: &Path, registry: &ProjectRegistry) -> Result<(), ProjectError> {
-    fs::create_dir_all(dir)?;
-
-    let file_path = dir.join("projects.json");
-    let temp_path = dir.join("projects.json.tmp");
-
-    let json = serde_json::to_string_pretty(registry)?;
-    fs::write(&temp_path, &json)?;
-    fs::rename(&temp_path, &file_path)?;
+impl From<StorageError> for ProjectError {
+    fn from(e: StorageError) -> Self {
+        match e {
+            StorageError::Io(e) => ProjectError::Io(e),
+            StorageError::Json(e) => ProjectError::Json(e),
+            other => ProjectError::Storage(other),
+        }
+    }
+}
 
-    Ok(())
+/// Save the project registry to disk, returning its new version.
+///
+/// `base` is the version of the registry `registry` was made from (see
+/// [`load_project_registry_versioned`]; `None` if there was none). Fails
+/// with [`StorageError::Conflict`] if it has changed since. Prefer
+/// [`update_project_registry`] for read-modify-writes.
+pub fn save_project_registry(
+    dir: &Path,
+    registry: &ProjectRegistry,
+    base: Option<&Version>,
+) -> Result<Version, ProjectError> {
+    Ok(storage_for(dir).save_project_registry(dir, registry, base)?)
 }
 
 /// Load the project registry from disk.
@@ -60,26 +80,74 @@ pub fn save_project_registry(dir: &Path, registry: &ProjectRegistry) -> Result<(
 /// - Raw array: `[{ project... }, ...]`
 /// - Wrapped object: `{ "projects": [...] }`
 pub fn load_project_registry(dir: &Path) -> Result<ProjectRegistry, ProjectError> {
-    let projects_path = dir.join("projects.json");
+    Ok(load_project_registry_versioned(dir)?.value)
+}
+
+/// Load the project registry with its version, to save changes to it with.
+pub fn load_project_registry_versioned(
+    dir: &Path,
+) -> Result<Versioned<ProjectRegistry>, ProjectError> {
+    Ok(storage_for(dir).load_project_registry(dir)?)
+}
+
+/// Load, change and save the project registry under an exclusive lock (one
+/// transaction with SQLite), so concurrent updates don't overwrite each
+/// other.
+pub fn update_project_registry(
+    dir: &Path,
+    mut update: impl FnMut(&mut ProjectRegistry),
+) -> Result<ProjectRegistry, ProjectError> {
+    Ok(storage_for(dir).update_project_registry(dir, &mut update)?)
+}
+
+pub(super) fn write_registry_file(
+    dir: &Path,
+    registry: &ProjectRegistry,
+    base: Option<&Version>,
+) -> Result<Version, StorageError> {
+    let file_path = dir.join(PROJECTS_FILE);
+    let temp_path = dir.join("projects.json.tmp");
+
+    let _lock = lock_exclusive(&file_path)?;
+    let current = parse_registry_file(&file_path)?;
+    check_unchanged(&file_path, base, current.as_ref())?;
+
+    let json = serde_json::to_string_pretty(registry)?;
+    fs::write(&temp_path, &json)?;
+    fs::rename(&temp_path, &file_path)?;
+
+    record_seen(&file_path, Some(registry))?;
+    Ok(Version::of(registry)?)
+}
 
+pub(super) fn read_registry_file(dir: &Path) -> Result<Versioned<ProjectRegistry>, StorageError> {
+    let file_path = dir.join(PROJECTS_FILE);
+
+    let _lock = lock_shared(&file_path)?;
+    // No registry exists: an empty one
+    Versioned::loaded(&file_path, parse_registry_file(&file_path)?)
+}
+
+pub(super) fn parse_registry_file(
+    projects_path: &Path,
+) -> Result<Option<ProjectRegistry>, StorageError> {
     if projects_path.exists() {
-        let contents = fs::read_to_string(&projects_path)?;
+        let contents = fs::read_to_string(projects_path)?;
 
         // Try wrapped format first: { "projects": [...] }
         match serde_json::from_str::<ProjectRegistry>(&contents) {
-            Ok(registry) => return Ok(registry),
+            Ok(registry) => return Ok(Some(registry)),
             Err(_) => {}
         }
 
         // Try raw array format: [...]
         match serde_json::from_str::<Vec<Project>>(&contents) {
-            Ok(projects) => return Ok(ProjectRegistry { projects }),
-            Err(e) => return Err(ProjectError::Json(e)),
+            Ok(projects) => return Ok(Some(ProjectRegistry { projects })),
+            Err(e) => return Err(StorageError::Json(e)),
         }
     }
 
-    // No registry exists, return empty
-    Ok(ProjectRegistry::default())
+    Ok(None)
 }
 
 // ============================================================================
@@ -96,6 +164,22 @@ pub fn find_project_by_path<'a>(registry: &'a ProjectRegistry, path: &str) -> Op
     registry.projects.iter().find(|p| p.path == path)
 }
 
+/// Find the project a workspace belongs to, by the workspace's path.
+///
+/// The project's own checkout counts as one of its workspaces.
+pub fn find_project_by_workspace_path<'a>(
+    registry: &'a ProjectRegistry,
+    path: &str,
+) -> Option<&'a Project> {
+    let path = path.trim_end_matches('/');
+    registry.projects.iter().find(|p| {
+        p.path.trim_end_matches('/') == path
+            || p.get_workspaces()
+                .iter()
+                .any(|w| w.path.trim_end_matches('/') == path)
+    })
+}
+
 /// Add or update a project.
 pub fn upsert_project(registry: &mut ProjectRegistry, project: Project) {
     registry.projects.retain(|p| p.id != project.id);
@@ -170,6 +254,8 @@ mod tests {
             init_prompt: None,
             pr_prompt: None,
             post_create: None,
+            setup_steps: vec![],
+            pre_archive: None,
             workspace_filter: None,
             worktree_filter: None,
             use_github: None,
@@ -177,6 +263,12 @@ mod tests {
             main_branch: None,
             default_sandboxed: None,
             claude_config_dir: None,
+            workspace_root: None,
+            workspace_naming: None,
+            vcs: None,
+            repos: Vec::new(),
+            redact_patterns: Vec::new(),
+            sensitive_env_vars: Vec::new(),
         }
     }
 
@@ -195,6 +287,8 @@ mod tests {
             is_creating: None,
             is_archiving: None,
             ssh_host_id: None,
+            init_prompt: None,
+            setup: None,
         }
     }
 
@@ -205,7 +299,7 @@ mod tests {
             projects: vec![make_project("proj-1", "test-project")],
         };
 
-        save_project_registry(dir.path(), &registry).unwrap();
+        save_project_registry(dir.path(), &registry, None).unwrap();
         let loaded = load_project_registry(dir.path()).unwrap();
 
         assert_eq!(loaded.projects.len(), 1);
@@ -224,7 +318,7 @@ mod tests {
             projects: vec![project],
         };
 
-        save_project_registry(dir.path(), &registry).unwrap();
+        save_project_registry(dir.path(), &registry, None).unwrap();
         // Prove it's on disk under the camelCase key, then that it loads back.
         let raw = std::fs::read_to_string(dir.path().join("projects.json")).unwrap();
         assert!(
@@ -246,11 +340,24 @@ mod tests {
             projects: vec![make_project("proj-1", "test")],
         };
 
-        save_project_registry(dir.path(), &registry).unwrap();
+        save_project_registry(dir.path(), &registry, None).unwrap();
 
         assert!(dir.path().join("projects.json").exists());
     }
 
+    #[test]
+    fn save_refuses_to_overwrite_an_unreadable_registry() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("projects.json"), "{ not json").unwrap();
+        let registry = ProjectRegistry {
+            projects: vec![make_project("proj-1", "test")],
+        };
+
+        assert!(save_project_registry(dir.path(), &registry, None).is_err());
+        let raw = std::fs::read_to_string(dir.path().join("projects.json")).unwrap();
+        assert_eq!(raw, "{ not json");
+    }
+
     #[test]
     fn load_nonexistent_returns_empty() {
         let dir = tempdir().unwrap();
@@ -289,6 +396,20 @@ mod tests {
         assert!(not_found.is_none());
     }
 
+    #[test]
+    fn find_project_by_workspace_path_matches_workspaces() {
+        let mut project = make_project("proj-1", "test");
+        add_workspace(&mut project, make_workspace("ws-1", "feature"));
+        let registry = ProjectRegistry {
+            projects: vec![project],
+        };
+
+        let found = find_project_by_workspace_path(&registry, "/path/to/feature/");
+        assert_eq!(found.map(|p| p.id.as_str()), Some("proj-1"));
+        assert!(find_project_by_workspace_path(&registry, "/path/to/test").is_some());
+        assert!(find_project_by_workspace_path(&registry, "/path/to/other").is_none());
+    }
+
     #[test]
     fn upsert_project_adds() {
         let mut registry = ProjectRegistry::default();
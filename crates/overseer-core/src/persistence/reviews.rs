@@ -0,0 +1,132 @@
+//! Addressed PR review threads persistence.
+//!
+//! # Overview
+//!
+//! When a workspace's PR review comments are fed back to its agent, the ids
+//! of the threads that went into the prompt are recorded here. The next time
+//! review feedback is gathered, those threads are skipped unless the caller
+//! asks for them again.
+//!
+//! # File Format
+//!
+//! Stored as `pr_reviews.json` in the workspace's chat directory:
+//!
+//! ```json
+//! {
+//!   "threads": [
+//!     { "threadId": "PRRT_kw...", "prNumber": 42, "chatId": "abc", "addressedAt": "..." }
+//!   ]
+//! }
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use chrono::Utc;
+
+use super::types::{AddressedReviewThread, AddressedReviewThreads};
+
+const FILE_NAME: &str = "pr_reviews.json";
+
+/// Save the addressed review threads for a workspace.
+///
+/// Uses the same write-then-rename pattern as the rest of the persistence layer.
+pub fn save_addressed_review_threads(
+    dir: &Path,
+    data: &AddressedReviewThreads,
+) -> Result<(), std::io::Error> {
+    fs::create_dir_all(dir)?;
+
+    let file_path = dir.join(FILE_NAME);
+    let temp_path = dir.join(format!("{FILE_NAME}.tmp"));
+
+    let json = serde_json::to_string_pretty(data)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    fs::write(&temp_path, json)?;
+    fs::rename(&temp_path, &file_path)?;
+
+    Ok(())
+}
+
+/// Load the addressed review threads for a workspace.
+///
+/// Returns an empty list if nothing has been addressed yet.
+pub fn load_addressed_review_threads(dir: &Path) -> Result<AddressedReviewThreads, std::io::Error> {
+    let file_path = dir.join(FILE_NAME);
+    if !file_path.exists() {
+        return Ok(AddressedReviewThreads::default());
+    }
+
+    let contents = fs::read_to_string(&file_path)?;
+    serde_json::from_str(&contents)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Record that `thread_ids` of PR `pr_number` were sent to chat `chat_id`.
+///
+/// A thread addressed again replaces its previous entry. Returns the updated data.
+pub fn mark_review_threads_addressed(
+    dir: &Path,
+    pr_number: u64,
+    chat_id: &str,
+    thread_ids: &[String],
+) -> Result<AddressedReviewThreads, std::io::Error> {
+    let mut data = load_addressed_review_threads(dir)?;
+    let now = Utc::now();
+
+    data.threads
+        .retain(|t| !(t.pr_number == pr_number && thread_ids.contains(&t.thread_id)));
+    data.threads
+        .extend(thread_ids.iter().map(|id| AddressedReviewThread {
+            thread_id: id.clone(),
+            pr_number,
+            chat_id: chat_id.to_string(),
+            addressed_at: now,
+        }));
+
+    save_addressed_review_threads(dir, &data)?;
+    Ok(data)
+}
+
+impl AddressedReviewThreads {
+    /// Whether thread `thread_id` of PR `pr_number` has been addressed.
+    pub fn is_addressed(&self, pr_number: u64, thread_id: &str) -> bool {
+        self.threads
+            .iter()
+            .any(|t| t.pr_number == pr_number && t.thread_id == thread_id)
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn load_missing_file_is_empty() {
+        let dir = tempdir().unwrap();
+        let data = load_addressed_review_threads(dir.path()).unwrap();
+        assert!(data.threads.is_empty());
+    }
+
+    #[test]
+    fn mark_records_and_replaces_entries() {
+        let dir = tempdir().unwrap();
+        mark_review_threads_addressed(dir.path(), 7, "chat-1", &["T1".into(), "T2".into()])
+            .unwrap();
+        let data = mark_review_threads_addressed(dir.path(), 7, "chat-2", &["T2".into()]).unwrap();
+
+        assert_eq!(data.threads.len(), 2);
+        assert!(data.is_addressed(7, "T1"));
+        assert!(!data.is_addressed(8, "T1"));
+        let t2 = data.threads.iter().find(|t| t.thread_id == "T2").unwrap();
+        assert_eq!(t2.chat_id, "chat-2");
+
+        let reloaded = load_addressed_review_threads(dir.path()).unwrap();
+        assert_eq!(reloaded.threads, data.threads);
+    }
+}
@@ -0,0 +1,878 @@
+//! SQLite storage backend (`overseer.db`).
+//!
+//! Two tables hold everything, keyed by `scope`: the directory the JSON
+//! layout would use, relative to the config dir (`""` for the config dir
+//! itself, `chats/<project>/<workspace>` for a workspace's chats).
+//!
+//! - `entries`: list documents, one row per item in list order. These are
+//!   projects (`project`) and chat index entries (`chat`). A save writes only
+//!   the rows whose position or body changed.
+//! - `documents`: single documents (`workspace_state`, `approvals`,
+//!   `chat_metadata`). `name` is the chat id for chat metadata and empty
+//!   otherwise.
+//!
+//! Bodies are the same JSON the file layout writes, so a migrated document
+//! reads back unchanged. Chat metadata is encrypted like its file when the
+//! config dir's chats are (see [`encryption`](super::encryption)). Writes run in `IMMEDIATE` transactions. The app and
+//! the daemon may share one database, and a read-modify-write in one process
+//! can't interleave with another's. Saving projects, a chat index or approvals
+//! checks the stored document is still the one this process last loaded,
+//! as the file layout does (see [`locking`](super::locking)).
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::encryption::{decode_line, encode_line};
+use super::locking::{check_unchanged, record_seen, Version, Versioned};
+use super::storage::{open_storage, scope_of, Storage, StorageError, DB_FILE};
+use super::types::{ApprovalsData, ChatIndex, ChatMetadata, ProjectRegistry, WorkspaceState};
+use super::watch::ConfigDocument;
+use super::{approvals, chat_jsonl, index, projects};
+
+/// `PRAGMA user_version` of the current schema.
+const SCHEMA_VERSION: i32 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS entries (
+    scope TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    body TEXT NOT NULL,
+    PRIMARY KEY (scope, kind, id)
+);
+CREATE TABLE IF NOT EXISTS documents (
+    scope TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    body TEXT NOT NULL,
+    PRIMARY KEY (scope, kind, name)
+);
+";
+
+const PROJECT: &str = "project";
+const CHAT: &str = "chat";
+const WORKSPACE_STATE: &str = "workspace_state";
+const APPROVALS: &str = "approvals";
+const CHAT_METADATA: &str = "chat_metadata";
+
+/// How long a write waits for another connection's transaction.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The config dir's documents in an SQLite database.
+pub struct SqliteStorage {
+    root: PathBuf,
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Open (creating if needed) the database at `path` for the config dir
+    /// `root`.
+    pub fn open(root: &Path, path: &Path) -> Result<Self, StorageError> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        init_schema(&conn)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The config dir this database serves.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn scope(&self, dir: &Path) -> String {
+        scope_of(&self.root, dir)
+    }
+
+    fn read<T>(
+        &self,
+        read: impl FnOnce(&Connection) -> Result<T, StorageError>,
+    ) -> Result<T, StorageError> {
+        read(&self.conn.lock().unwrap())
+    }
+
+    fn write<T>(
+        &self,
+        write: impl FnOnce(&Transaction) -> Result<T, StorageError>,
+    ) -> Result<T, StorageError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let value = write(&tx)?;
+        tx.commit()?;
+        Ok(value)
+    }
+
+    /// Save a document stored for `dir`. The directory is still created:
+    /// code that archives or renames a workspace's chats checks for it.
+    fn save_document<T: Serialize>(
+        &self,
+        dir: &Path,
+        kind: &str,
+        name: &str,
+        value: &T,
+    ) -> Result<(), StorageError> {
+        fs::create_dir_all(dir)?;
+        let scope = self.scope(dir);
+        self.write(|tx| save_document(tx, &scope, kind, name, value))
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load_project_registry(
+        &self,
+        dir: &Path,
+    ) -> Result<Versioned<ProjectRegistry>, StorageError> {
+        let scope = self.scope(dir);
+        let registry = self.read(|conn| load_projects(conn, &scope))?;
+        Versioned::loaded(&dir.join(projects::PROJECTS_FILE), Some(registry))
+    }
+
+    fn save_project_registry(
+        &self,
+        dir: &Path,
+        registry: &ProjectRegistry,
+        base: Option<&Version>,
+    ) -> Result<Version, StorageError> {
+        let scope = self.scope(dir);
+        let path = dir.join(projects::PROJECTS_FILE);
+        self.write(|tx| {
+            check_unchanged(&path, base, Some(&load_projects(tx, &scope)?))?;
+            save_projects(tx, &scope, registry)
+        })?;
+        record_seen(&path, Some(registry))?;
+        Ok(Version::of(registry)?)
+    }
+
+    fn load_chat_index(&self, dir: &Path) -> Result<Versioned<ChatIndex>, StorageError> {
+        let scope = self.scope(dir);
+        let index = self.read(|conn| load_chats(conn, &scope))?;
+        Versioned::loaded(&dir.join(index::INDEX_FILE), Some(index))
+    }
+
+    fn save_chat_index(
+        &self,
+        dir: &Path,
+        index: &ChatIndex,
+        base: Option<&Version>,
+    ) -> Result<Version, StorageError> {
+        fs::create_dir_all(dir)?;
+        let scope = self.scope(dir);
+        let path = dir.join(index::INDEX_FILE);
+        self.write(|tx| {
+            check_unchanged(&path, base, Some(&load_chats(tx, &scope)?))?;
+            save_chats(tx, &scope, index)
+        })?;
+        record_seen(&path, Some(index))?;
+        Ok(Version::of(index)?)
+    }
+
+    fn load_workspace_state(&self, dir: &Path) -> Result<WorkspaceState, StorageError> {
+        let scope = self.scope(dir);
+        let state = self.read(|conn| load_document(conn, &scope, WORKSPACE_STATE, ""))?;
+        Ok(state.unwrap_or_default())
+    }
+
+    fn save_workspace_state(&self, dir: &Path, state: &WorkspaceState) -> Result<(), StorageError> {
+        self.save_document(dir, WORKSPACE_STATE, "", state)
+    }
+
+    fn load_approvals(&self, dir: &Path) -> Result<Versioned<ApprovalsData>, StorageError> {
+        let scope = self.scope(dir);
+        let approvals = self.read(|conn| load_document(conn, &scope, APPROVALS, ""))?;
+        Versioned::loaded(&dir.join(approvals::APPROVALS_FILE), approvals)
+    }
+
+    fn save_approvals(
+        &self,
+        dir: &Path,
+        approvals: &ApprovalsData,
+        base: Option<&Version>,
+    ) -> Result<Version, StorageError> {
+        fs::create_dir_all(dir)?;
+        let scope = self.scope(dir);
+        let path = dir.join(approvals::APPROVALS_FILE);
+        self.write(|tx| {
+            let current: Option<ApprovalsData> = load_document(tx, &scope, APPROVALS, "")?;
+            check_unchanged(&path, base, current.as_ref())?;
+            save_document(tx, &scope, APPROVALS, "", approvals)
+        })?;
+        record_seen(&path, Some(approvals))?;
+        Ok(Version::of(approvals)?)
+    }
+
+    fn delete_approvals(&self, dir: &Path) -> Result<(), StorageError> {
+        let scope = self.scope(dir);
+        self.write(|tx| delete_document(tx, &scope, APPROVALS, ""))
+    }
+
+    fn load_chat_metadata(
+        &self,
+        dir: &Path,
+        chat_id: &str,
+    ) -> Result<Option<ChatMetadata>, StorageError> {
+        let scope = self.scope(dir);
+        self.read(|conn| load_chat_metadata(conn, dir, &scope, chat_id))
+    }
+
+    fn save_chat_metadata(&self, dir: &Path, metadata: &ChatMetadata) -> Result<(), StorageError> {
+        fs::create_dir_all(dir)?;
+        let scope = self.scope(dir);
+        self.write(|tx| save_chat_metadata(tx, dir, &scope, metadata))
+    }
+
+    fn delete_chat_metadata(&self, dir: &Path, chat_id: &str) -> Result<(), StorageError> {
+        let scope = self.scope(dir);
+        self.write(|tx| delete_document(tx, &scope, CHAT_METADATA, chat_id))
+    }
+
+    fn update_project_registry(
+        &self,
+        dir: &Path,
+        update: &mut dyn FnMut(&mut ProjectRegistry),
+    ) -> Result<ProjectRegistry, StorageError> {
+        let scope = self.scope(dir);
+        let registry = self.write(|tx| {
+            let mut registry = load_projects(tx, &scope)?;
+            update(&mut registry);
+            save_projects(tx, &scope, &registry)?;
+            Ok(registry)
+        })?;
+        record_seen(&dir.join(projects::PROJECTS_FILE), Some(&registry))?;
+        Ok(registry)
+    }
+
+    fn update_chat_index(
+        &self,
+        dir: &Path,
+        update: &mut dyn FnMut(&mut ChatIndex),
+    ) -> Result<ChatIndex, StorageError> {
+        fs::create_dir_all(dir)?;
+        let scope = self.scope(dir);
+        let index = self.write(|tx| {
+            let mut index = load_chats(tx, &scope)?;
+            update(&mut index);
+            save_chats(tx, &scope, &index)?;
+            Ok(index)
+        })?;
+        record_seen(&dir.join(index::INDEX_FILE), Some(&index))?;
+        Ok(index)
+    }
+
+    fn update_approvals(
+        &self,
+        dir: &Path,
+        update: &mut dyn FnMut(&mut ApprovalsData),
+    ) -> Result<ApprovalsData, StorageError> {
+        fs::create_dir_all(dir)?;
+        let scope = self.scope(dir);
+        let approvals = self.write(|tx| {
+            let mut approvals: ApprovalsData =
+                load_document(tx, &scope, APPROVALS, "")?.unwrap_or_default();
+            update(&mut approvals);
+            save_document(tx, &scope, APPROVALS, "", &approvals)?;
+            Ok(approvals)
+        })?;
+        record_seen(&dir.join(approvals::APPROVALS_FILE), Some(&approvals))?;
+        Ok(approvals)
+    }
+
+    fn document_version(
+        &self,
+        dir: &Path,
+        document: ConfigDocument,
+    ) -> Result<Option<Version>, StorageError> {
+        let scope = self.scope(dir);
+        self.read(|conn| {
+            let version = match document {
+                ConfigDocument::Projects => Some(Version::of(&load_projects(conn, &scope)?)?),
+                ConfigDocument::ChatIndex => Some(Version::of(&load_chats(conn, &scope)?)?),
+                ConfigDocument::Approvals => {
+                    load_document::<ApprovalsData>(conn, &scope, APPROVALS, "")?
+                        .map(|approvals| Version::of(&approvals))
+                        .transpose()?
+                }
+            };
+            Ok(version)
+        })
+    }
+
+    /// Moves the rows with the directory. The rename happens inside the
+    /// transaction, so a failed rename leaves the rows where they were.
+    fn rename_dir(&self, from: &Path, to: &Path) -> Result<(), StorageError> {
+        let from_scope = self.scope(from);
+        let to_scope = self.scope(to);
+        // substr() counts characters from 1.
+        let tail_start = from_scope.chars().count() as i64 + 1;
+        self.write(|tx| {
+            for table in ["entries", "documents"] {
+                tx.execute(
+                    &format!(
+                        "UPDATE {table} SET scope = ?2 || substr(scope, ?3)
+                         WHERE scope = ?1 OR substr(scope, 1, ?3) = ?1 || '/'"
+                    ),
+                    params![from_scope, to_scope, tail_start],
+                )?;
+            }
+            fs::rename(from, to)?;
+            Ok(())
+        })
+    }
+}
+
+/// What [`migrate_to_sqlite`] imported.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub projects: usize,
+    pub chat_indexes: usize,
+    pub chats: usize,
+    pub workspace_states: usize,
+    pub approvals: usize,
+}
+
+/// Import the config dir's JSON layout into a new `overseer.db` and switch
+/// the config dir to it.
+///
+/// Runs once: a config dir that already has a database is refused. The
+/// database is built under a temporary name in one transaction and renamed
+/// into place, so an interrupted migration leaves the JSON backend in use.
+/// The JSON files are left where they are, but nothing updates them from
+/// then on.
+pub fn migrate_to_sqlite(config_dir: &Path) -> Result<MigrationReport, StorageError> {
+    let db_path = config_dir.join(DB_FILE);
+    if db_path.exists() {
+        return Err(StorageError::AlreadyMigrated(db_path));
+    }
+
+    let temp_path = config_dir.join(format!("{DB_FILE}.tmp"));
+    if temp_path.exists() {
+        fs::remove_file(&temp_path)?;
+    }
+    let report = {
+        let mut conn = Connection::open(&temp_path)?;
+        init_schema(&conn)?;
+        let tx = conn.transaction()?;
+        let report = import_json(&tx, config_dir)?;
+        tx.commit()?;
+        report
+    };
+    fs::rename(&temp_path, &db_path)?;
+
+    open_storage(config_dir)?;
+    log::info!(
+        "Migrated {} to SQLite: {} projects, {} chat indexes, {} chats",
+        config_dir.display(),
+        report.projects,
+        report.chat_indexes,
+        report.chats
+    );
+    Ok(report)
+}
+
+fn import_json(tx: &Transaction, config_dir: &Path) -> Result<MigrationReport, StorageError> {
+    let mut report = MigrationReport::default();
+
+    let registry = projects::read_registry_file(config_dir)?.value;
+    report.projects = registry.projects.len();
+    save_projects(tx, "", &registry)?;
+
+    let mut pending = vec![config_dir.join("chats")];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let scope = scope_of(config_dir, &dir);
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            match file_name {
+                "chats.json" => {
+                    save_chats(tx, &scope, &index::read_index_file(&dir)?.value)?;
+                    report.chat_indexes += 1;
+                }
+                "workspace.json" => {
+                    let state = index::read_workspace_state_file(&dir)?;
+                    save_document(tx, &scope, WORKSPACE_STATE, "", &state)?;
+                    report.workspace_states += 1;
+                }
+                "approvals.json" => {
+                    let approvals = approvals::read_approvals_file(&dir)?.value;
+                    save_document(tx, &scope, APPROVALS, "", &approvals)?;
+                    report.approvals += 1;
+                }
+                name => {
+                    let Some(chat_id) = name.strip_suffix(".meta.json") else {
+                        continue;
+                    };
+                    if let Some(metadata) = chat_jsonl::read_metadata_file(&dir, chat_id)? {
+                        save_chat_metadata(tx, &dir, &scope, &metadata)?;
+                        report.chats += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn init_schema(conn: &Connection) -> Result<(), StorageError> {
+    let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version < SCHEMA_VERSION {
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    }
+    Ok(())
+}
+
+fn load_projects(conn: &Connection, scope: &str) -> Result<ProjectRegistry, StorageError> {
+    Ok(ProjectRegistry {
+        projects: load_entries(conn, scope, PROJECT)?,
+    })
+}
+
+fn load_chats(conn: &Connection, scope: &str) -> Result<ChatIndex, StorageError> {
+    Ok(ChatIndex {
+        chats: load_entries(conn, scope, CHAT)?,
+    })
+}
+
+fn save_projects(
+    tx: &Transaction,
+    scope: &str,
+    registry: &ProjectRegistry,
+) -> Result<(), StorageError> {
+    let items: Vec<_> = registry
+        .projects
+        .iter()
+        .map(|p| (p.id.as_str(), p))
+        .collect();
+    save_entries(tx, scope, PROJECT, &items)
+}
+
+fn save_chats(tx: &Transaction, scope: &str, index: &ChatIndex) -> Result<(), StorageError> {
+    let items: Vec<_> = index.chats.iter().map(|c| (c.id.as_str(), c)).collect();
+    save_entries(tx, scope, CHAT, &items)
+}
+
+fn load_entries<T: DeserializeOwned>(
+    conn: &Connection,
+    scope: &str,
+    kind: &str,
+) -> Result<Vec<T>, StorageError> {
+    let mut stmt =
+        conn.prepare("SELECT body FROM entries WHERE scope = ?1 AND kind = ?2 ORDER BY position")?;
+    let bodies = stmt
+        .query_map(params![scope, kind], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    bodies
+        .iter()
+        .map(|body| Ok(serde_json::from_str(body)?))
+        .collect()
+}
+
+/// Make the `kind` rows of `scope` match `items`, writing only what changed.
+fn save_entries<T: Serialize>(
+    tx: &Transaction,
+    scope: &str,
+    kind: &str,
+    items: &[(&str, &T)],
+) -> Result<(), StorageError> {
+    let mut existing: HashMap<String, (i64, String)> = {
+        let mut stmt =
+            tx.prepare("SELECT id, position, body FROM entries WHERE scope = ?1 AND kind = ?2")?;
+        let rows = stmt.query_map(params![scope, kind], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?)))
+        })?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    for (position, (id, item)) in items.iter().enumerate() {
+        let row = (position as i64, serde_json::to_string(item)?);
+        if existing.remove(*id).as_ref() != Some(&row) {
+            tx.execute(
+                "INSERT OR REPLACE INTO entries (scope, kind, id, position, body)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![scope, kind, id, row.0, row.1],
+            )?;
+        }
+    }
+    for id in existing.keys() {
+        tx.execute(
+            "DELETE FROM entries WHERE scope = ?1 AND kind = ?2 AND id = ?3",
+            params![scope, kind, id],
+        )?;
+    }
+    Ok(())
+}
+
+fn load_document<T: DeserializeOwned>(
+    conn: &Connection,
+    scope: &str,
+    kind: &str,
+    name: &str,
+) -> Result<Option<T>, StorageError> {
+    load_body(conn, scope, kind, name)?
+        .map(|body| Ok(serde_json::from_str(&body)?))
+        .transpose()
+}
+
+fn load_body(
+    conn: &Connection,
+    scope: &str,
+    kind: &str,
+    name: &str,
+) -> Result<Option<String>, StorageError> {
+    Ok(conn
+        .query_row(
+            "SELECT body FROM documents WHERE scope = ?1 AND kind = ?2 AND name = ?3",
+            params![scope, kind, name],
+            |row| row.get(0),
+        )
+        .optional()?)
+}
+
+fn save_document<T: Serialize>(
+    tx: &Transaction,
+    scope: &str,
+    kind: &str,
+    name: &str,
+    value: &T,
+) -> Result<(), StorageError> {
+    save_body(tx, scope, kind, name, &serde_json::to_string(value)?)
+}
+
+fn save_body(
+    tx: &Transaction,
+    scope: &str,
+    kind: &str,
+    name: &str,
+    body: &str,
+) -> Result<(), StorageError> {
+    tx.execute(
+        "INSERT OR REPLACE INTO documents (scope, kind, name, body) VALUES (?1, ?2, ?3, ?4)",
+        params![scope, kind, name, body],
+    )?;
+    Ok(())
+}
+
+/// Load a chat's metadata, decrypting it if needed. `dir` is the chat's
+/// directory, for the key.
+fn load_chat_metadata(
+    conn: &Connection,
+    dir: &Path,
+    scope: &str,
+    chat_id: &str,
+) -> Result<Option<ChatMetadata>, StorageError> {
+    load_body(conn, scope, CHAT_METADATA, chat_id)?
+        .map(|body| Ok(serde_json::from_slice(&decode_line(dir, body.as_bytes())?)?))
+        .transpose()
+}
+
+/// Save a chat's metadata, encrypted if `dir`'s chats are.
+fn save_chat_metadata(
+    tx: &Transaction,
+    dir: &Path,
+    scope: &str,
+    metadata: &ChatMetadata,
+) -> Result<(), StorageError> {
+    let body = encode_line(dir, &serde_json::to_string(metadata)?)?;
+    save_body(tx, scope, CHAT_METADATA, &metadata.id, &body)
+}
+
+fn delete_document(
+    tx: &Transaction,
+    scope: &str,
+    kind: &str,
+    name: &str,
+) -> Result<(), StorageError> {
+    tx.execute(
+        "DELETE FROM documents WHERE scope = ?1 AND kind = ?2 AND name = ?3",
+        params![scope, kind, name],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::storage::{backend_for, StorageBackend};
+    use crate::persistence::types::ChatIndexEntry;
+    use crate::persistence::{
+        load_approvals, load_chat_index, load_chat_metadata, load_project_registry, save_approvals,
+        save_chat_index, save_chat_metadata, save_project_registry, update_chat_index, Project,
+    };
+    use crate::test_support::sample_chat_metadata;
+    use chrono::Utc;
+    use tempfile::tempdir;
+
+    fn entry(id: &str) -> ChatIndexEntry {
+        ChatIndexEntry {
+            id: id.to_string(),
+            label: format!("Chat {id}"),
+            agent_type: Some("claude".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            is_archived: None,
+            archived_at: None,
+        }
+    }
+
+    fn project(id: &str) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": id,
+            "path": format!("/repos/{id}"),
+            "isGitRepo": true,
+            "workspaces": [],
+        }))
+        .unwrap()
+    }
+
+    fn chat_ids(index: &ChatIndex) -> Vec<&str> {
+        index.chats.iter().map(|c| c.id.as_str()).collect()
+    }
+
+    #[test]
+    fn documents_and_entries_round_trip() {
+        let config = tempdir().unwrap();
+        let db = SqliteStorage::open(config.path(), &config.path().join(DB_FILE)).unwrap();
+        let chat_dir = config.path().join("chats").join("proj").join("ws");
+
+        let registry = ProjectRegistry {
+            projects: vec![project("b"), project("a")],
+        };
+        db.save_project_registry(config.path(), &registry, None)
+            .unwrap();
+        let loaded = db.load_project_registry(config.path()).unwrap().value;
+        let ids: Vec<_> = loaded.projects.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["b", "a"]);
+
+        let base = db
+            .save_chat_index(
+                &chat_dir,
+                &ChatIndex {
+                    chats: vec![entry("1"), entry("2")],
+                },
+                None,
+            )
+            .unwrap();
+        db.save_chat_index(
+            &chat_dir,
+            &ChatIndex {
+                chats: vec![entry("2")],
+            },
+            Some(&base),
+        )
+        .unwrap();
+        assert_eq!(
+            chat_ids(&db.load_chat_index(&chat_dir).unwrap().value),
+            ["2"]
+        );
+        assert!(chat_dir.is_dir());
+
+        db.save_chat_metadata(&chat_dir, &sample_chat_metadata("2"))
+            .unwrap();
+        assert_eq!(
+            db.load_chat_metadata(&chat_dir, "2").unwrap().unwrap().id,
+            "2"
+        );
+        db.delete_chat_metadata(&chat_dir, "2").unwrap();
+        assert!(db.load_chat_metadata(&chat_dir, "2").unwrap().is_none());
+
+        let approvals = ApprovalsData {
+            tool_names: vec!["Read".to_string()],
+            command_prefixes: Vec::new(),
+        };
+        db.save_approvals(&chat_dir, &approvals, None).unwrap();
+        assert_eq!(
+            db.load_approvals(&chat_dir).unwrap().value.tool_names,
+            ["Read"]
+        );
+        db.delete_approvals(&chat_dir).unwrap();
+        assert!(db
+            .load_approvals(&chat_dir)
+            .unwrap()
+            .value
+            .tool_names
+            .is_empty());
+    }
+
+    #[test]
+    fn concurrent_updates_from_two_connections_are_not_lost() {
+        let config = tempdir().unwrap();
+        let db_path = config.path().join(DB_FILE);
+        let chat_dir = config.path().join("chats").join("proj").join("ws");
+        let handles: Vec<_> = ["app", "daemon"]
+            .into_iter()
+            .map(|writer| {
+                let db = SqliteStorage::open(config.path(), &db_path).unwrap();
+                let chat_dir = chat_dir.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        db.update_chat_index(&chat_dir, &mut |index| {
+                            index.chats.push(entry(&format!("{writer}-{i}")));
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let db = SqliteStorage::open(config.path(), &db_path).unwrap();
+        assert_eq!(db.load_chat_index(&chat_dir).unwrap().value.chats.len(), 50);
+    }
+
+    #[test]
+    fn stale_save_conflicts() {
+        let config = tempdir().unwrap();
+        let db_path = config.path().join(DB_FILE);
+        let db = SqliteStorage::open(config.path(), &db_path).unwrap();
+        let chat_dir = config.path().join("chats").join("proj").join("ws");
+
+        let loaded = db.load_chat_index(&chat_dir).unwrap();
+        let mut index = loaded.value;
+
+        // Another process adds a chat.
+        let mut conn = Connection::open(&db_path).unwrap();
+        let tx = conn.transaction().unwrap();
+        let theirs = ChatIndex {
+            chats: vec![entry("theirs")],
+        };
+        save_chats(&tx, "chats/proj/ws", &theirs).unwrap();
+        tx.commit().unwrap();
+
+        index.chats.push(entry("ours"));
+        assert!(matches!(
+            db.save_chat_index(&chat_dir, &index, loaded.version.as_ref()),
+            Err(StorageError::Conflict(_))
+        ));
+        let merged = db
+            .update_chat_index(&chat_dir, &mut |index| index.chats.push(entry("ours")))
+            .unwrap();
+        assert_eq!(chat_ids(&merged), ["theirs", "ours"]);
+    }
+
+    #[test]
+    fn rename_dir_moves_the_rows_under_it() {
+        let config = tempdir().unwrap();
+        let db = SqliteStorage::open(config.path(), &config.path().join(DB_FILE)).unwrap();
+        let chats = config.path().join("chats").join("proj");
+        let (old, new) = (chats.join("ws"), chats.join("archived").join("ws-1"));
+        let sibling = chats.join("ws2");
+        db.save_chat_index(
+            &old,
+            &ChatIndex {
+                chats: vec![entry("1")],
+            },
+            None,
+        )
+        .unwrap();
+        db.save_chat_index(
+            &sibling,
+            &ChatIndex {
+                chats: vec![entry("2")],
+            },
+            None,
+        )
+        .unwrap();
+        db.save_chat_metadata(&old, &sample_chat_metadata("1"))
+            .unwrap();
+
+        fs::create_dir_all(new.parent().unwrap()).unwrap();
+        db.rename_dir(&old, &new).unwrap();
+
+        assert!(new.is_dir() && !old.exists());
+        assert!(db.load_chat_index(&old).unwrap().value.chats.is_empty());
+        assert_eq!(chat_ids(&db.load_chat_index(&new).unwrap().value), ["1"]);
+        assert!(db.load_chat_metadata(&new, "1").unwrap().is_some());
+        assert_eq!(
+            chat_ids(&db.load_chat_index(&sibling).unwrap().value),
+            ["2"]
+        );
+    }
+
+    #[test]
+    fn migration_imports_the_json_layout_and_switches_backends() {
+        let config = tempdir().unwrap();
+        let chat_dir = config.path().join("chats").join("proj").join("ws");
+        let project_dir = config.path().join("chats").join("proj");
+        save_project_registry(
+            config.path(),
+            &ProjectRegistry {
+                projects: vec![project("p1")],
+            },
+            None,
+        )
+        .unwrap();
+        save_chat_index(
+            &chat_dir,
+            &ChatIndex {
+                chats: vec![entry("1")],
+            },
+            None,
+        )
+        .unwrap();
+        save_chat_metadata(&chat_dir, &sample_chat_metadata("1")).unwrap();
+        save_approvals(
+            &project_dir,
+            &ApprovalsData {
+                tool_names: vec!["Bash".to_string()],
+                command_prefixes: Vec::new(),
+            },
+            None,
+        )
+        .unwrap();
+        assert_eq!(backend_for(&chat_dir), StorageBackend::Json);
+
+        let report = migrate_to_sqlite(config.path()).unwrap();
+        assert_eq!(
+            report,
+            MigrationReport {
+                projects: 1,
+                chat_indexes: 1,
+                chats: 1,
+                workspace_states: 0,
+                approvals: 1,
+            }
+        );
+        assert_eq!(backend_for(&chat_dir), StorageBackend::Sqlite);
+
+        // The free functions now read and write the database, not the files.
+        fs::remove_file(chat_dir.join("chats.json")).unwrap();
+        update_chat_index(&chat_dir, |index| index.chats.push(entry("2"))).unwrap();
+        assert_eq!(chat_ids(&load_chat_index(&chat_dir).unwrap()), ["1", "2"]);
+        assert!(!chat_dir.join("chats.json").exists());
+        assert_eq!(load_chat_metadata(&chat_dir, "1").unwrap().id, "1");
+        assert_eq!(load_approvals(&project_dir).unwrap().tool_names, ["Bash"]);
+        assert_eq!(
+            load_project_registry(config.path()).unwrap().projects.len(),
+            1
+        );
+
+        assert!(matches!(
+            migrate_to_sqlite(config.path()),
+            Err(StorageError::AlreadyMigrated(_))
+        ));
+    }
+}
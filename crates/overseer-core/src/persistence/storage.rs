@@ -0,0 +1,408 @@
+//! Storage backends for the config dir's JSON documents.
+//!
+//! Projects, chat indexes, workspace state, approvals and chat metadata are
+//! kept by a [`Storage`] backend:
+//!
+//! - [`JsonStorage`] (the default) writes the original file layout:
+//!   `projects.json`, `chats.json`, `workspace.json`, `approvals.json` and
+//!   `{chat_id}.meta.json`, each rewritten whole on save.
+//! - [`SqliteStorage`] keeps them in `overseer.db` in the config dir. It
+//!   stores one row per project and per chat index entry and only writes the
+//!   rows that changed. `update_*` read-modify-writes run in one transaction.
+//!
+//! A config dir uses SQLite once `overseer.db` exists in it. That file is
+//! created by the one-shot migrator ([`migrate_to_sqlite`]). [`open_storage`]
+//! is called at startup. It registers the database so that the module's free
+//! functions (`save_chat_index(dir, ..)` and the others) pick the backend from
+//! the directory they're given. Callers don't change.
+//!
+//! Chat event logs (`{chat_id}.jsonl`) stay files in both backends. They are
+//! append-only already, and search and the offset sidecar read them directly.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use super::locking::{lock_exclusive, lock_shared, Version, Versioned};
+use super::types::{ApprovalsData, ChatIndex, ChatMetadata, ProjectRegistry, WorkspaceState};
+use super::watch::ConfigDocument;
+use super::{approvals, chat_jsonl, index, projects};
+
+pub use super::sqlite::{migrate_to_sqlite, MigrationReport, SqliteStorage};
+
+/// The SQLite database file in the config dir.
+pub const DB_FILE: &str = "overseer.db";
+
+/// Databases opened by [`open_storage`], one per config dir.
+static DATABASES: RwLock<Vec<Arc<SqliteStorage>>> = RwLock::new(Vec::new());
+
+/// Error type for storage backends.
+#[derive(Debug)]
+pub enum StorageError {
+    /// IO error
+    Io(std::io::Error),
+    /// JSON error
+    Json(serde_json::Error),
+    /// SQLite error
+    Sqlite(rusqlite::Error),
+    /// The config dir already has a database
+    AlreadyMigrated(PathBuf),
+    /// The document changed since the caller loaded it
+    Conflict(PathBuf),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::Io(e) => write!(f, "IO error: {e}"),
+            StorageError::Json(e) => write!(f, "JSON error: {e}"),
+            StorageError::Sqlite(e) => write!(f, "SQLite error: {e}"),
+            StorageError::AlreadyMigrated(path) => {
+                write!(f, "Already migrated: {} exists", path.display())
+            }
+            StorageError::Conflict(path) => write!(
+                f,
+                "Conflict: {} was changed by another process, reload and try again",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<std::io::Error> for StorageError {
+    fn from(e: std::io::Error) -> Self {
+        StorageError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(e: serde_json::Error) -> Self {
+        StorageError::Json(e)
+    }
+}
+
+impl From<rusqlite::Error> for StorageError {
+    fn from(e: rusqlite::Error) -> Self {
+        StorageError::Sqlite(e)
+    }
+}
+
+/// Which backend a config dir uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    Json,
+    Sqlite,
+}
+
+/// A place to keep the config dir's documents.
+///
+/// Every method takes the directory the JSON layout would put the file in,
+/// so callers address documents the same way whatever the backend.
+///
+/// The shared documents load with their version, and saving one takes the
+/// version it was made from: the save fails with [`StorageError::Conflict`]
+/// if the document has changed since (see [`locking`](super::locking)).
+pub trait Storage: Send + Sync {
+    fn load_project_registry(&self, dir: &Path)
+        -> Result<Versioned<ProjectRegistry>, StorageError>;
+    fn save_project_registry(
+        &self,
+        dir: &Path,
+        registry: &ProjectRegistry,
+        base: Option<&Version>,
+    ) -> Result<Version, StorageError>;
+
+    fn load_chat_index(&self, dir: &Path) -> Result<Versioned<ChatIndex>, StorageError>;
+    fn save_chat_index(
+        &self,
+        dir: &Path,
+        index: &ChatIndex,
+        base: Option<&Version>,
+    ) -> Result<Version, StorageError>;
+
+    fn load_workspace_state(&self, dir: &Path) -> Result<WorkspaceState, StorageError>;
+    fn save_workspace_state(&self, dir: &Path, state: &WorkspaceState) -> Result<(), StorageError>;
+
+    fn load_approvals(&self, dir: &Path) -> Result<Versioned<ApprovalsData>, StorageError>;
+    fn save_approvals(
+        &self,
+        dir: &Path,
+        approvals: &ApprovalsData,
+        base: Option<&Version>,
+    ) -> Result<Version, StorageError>;
+    fn delete_approvals(&self, dir: &Path) -> Result<(), StorageError>;
+
+    /// `None` if the chat has no metadata.
+    fn load_chat_metadata(
+        &self,
+        dir: &Path,
+        chat_id: &str,
+    ) -> Result<Option<ChatMetadata>, StorageError>;
+    fn save_chat_metadata(&self, dir: &Path, metadata: &ChatMetadata) -> Result<(), StorageError>;
+    fn delete_chat_metadata(&self, dir: &Path, chat_id: &str) -> Result<(), StorageError>;
+
+    /// Load, change and save the project registry. The SQLite backend does
+    /// this in one transaction.
+    fn update_project_registry(
+        &self,
+        dir: &Path,
+        update: &mut dyn FnMut(&mut ProjectRegistry),
+    ) -> Result<ProjectRegistry, StorageError> {
+        let mut registry = self.load_project_registry(dir)?;
+        update(&mut registry.value);
+        self.save_project_registry(dir, &registry.value, registry.version.as_ref())?;
+        Ok(registry.value)
+    }
+
+    /// Load, change and save a chat index. The SQLite backend does this in
+    /// one transaction.
+    fn update_chat_index(
+        &self,
+        dir: &Path,
+        update: &mut dyn FnMut(&mut ChatIndex),
+    ) -> Result<ChatIndex, StorageError> {
+        let mut index = self.load_chat_index(dir)?;
+        update(&mut index.value);
+        self.save_chat_index(dir, &index.value, index.version.as_ref())?;
+        Ok(index.value)
+    }
+
+    /// Load, change and save approvals. The SQLite backend does this in one
+    /// transaction.
+    fn update_approvals(
+        &self,
+        dir: &Path,
+        update: &mut dyn FnMut(&mut ApprovalsData),
+    ) -> Result<ApprovalsData, StorageError> {
+        let mut approvals = self.load_approvals(dir)?;
+        update(&mut approvals.value);
+        self.save_approvals(dir, &approvals.value, approvals.version.as_ref())?;
+        Ok(approvals.value)
+    }
+
+    /// The current version of a shared document (`None` if there isn't
+    /// one), without recording it as seen. For the config watcher.
+    fn document_version(
+        &self,
+        dir: &Path,
+        document: ConfigDocument,
+    ) -> Result<Option<Version>, StorageError>;
+
+    /// Move a directory and everything stored under it (archiving or
+    /// renaming a workspace's chats).
+    fn rename_dir(&self, from: &Path, to: &Path) -> Result<(), StorageError> {
+        std::fs::rename(from, to)?;
+        Ok(())
+    }
+}
+
+/// The original file layout. Files are locked while they're read and
+/// written, and saves are compare-and-swap (see [`locking`](super::locking)).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonStorage;
+
+impl Storage for JsonStorage {
+    fn load_project_registry(
+        &self,
+        dir: &Path,
+    ) -> Result<Versioned<ProjectRegistry>, StorageError> {
+        projects::read_registry_file(dir)
+    }
+
+    fn save_project_registry(
+        &self,
+        dir: &Path,
+        registry: &ProjectRegistry,
+        base: Option<&Version>,
+    ) -> Result<Version, StorageError> {
+        projects::write_registry_file(dir, registry, base)
+    }
+
+    fn load_chat_index(&self, dir: &Path) -> Result<Versioned<ChatIndex>, StorageError> {
+        index::read_index_file(dir)
+    }
+
+    fn save_chat_index(
+        &self,
+        dir: &Path,
+        index: &ChatIndex,
+        base: Option<&Version>,
+    ) -> Result<Version, StorageError> {
+        index::write_index_file(dir, index, base)
+    }
+
+    fn load_workspace_state(&self, dir: &Path) -> Result<WorkspaceState, StorageError> {
+        index::read_workspace_state_file(dir)
+    }
+
+    fn save_workspace_state(&self, dir: &Path, state: &WorkspaceState) -> Result<(), StorageError> {
+        index::write_workspace_state_file(dir, state)
+    }
+
+    fn load_approvals(&self, dir: &Path) -> Result<Versioned<ApprovalsData>, StorageError> {
+        approvals::read_approvals_file(dir)
+    }
+
+    fn save_approvals(
+        &self,
+        dir: &Path,
+        approvals: &ApprovalsData,
+        base: Option<&Version>,
+    ) -> Result<Version, StorageError> {
+        approvals::write_approvals_file(dir, approvals, base)
+    }
+
+    fn delete_approvals(&self, dir: &Path) -> Result<(), StorageError> {
+        approvals::delete_approvals_file(dir)
+    }
+
+    fn load_chat_metadata(
+        &self,
+        dir: &Path,
+        chat_id: &str,
+    ) -> Result<Option<ChatMetadata>, StorageError> {
+        chat_jsonl::read_metadata_file(dir, chat_id)
+    }
+
+    fn save_chat_metadata(&self, dir: &Path, metadata: &ChatMetadata) -> Result<(), StorageError> {
+        chat_jsonl::write_metadata_file(dir, metadata)
+    }
+
+    fn delete_chat_metadata(&self, dir: &Path, chat_id: &str) -> Result<(), StorageError> {
+        let path = dir.join(format!("{chat_id}.meta.json"));
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn update_project_registry(
+        &self,
+        dir: &Path,
+        update: &mut dyn FnMut(&mut ProjectRegistry),
+    ) -> Result<ProjectRegistry, StorageError> {
+        let _lock = lock_exclusive(&dir.join(projects::PROJECTS_FILE))?;
+        let mut registry = self.load_project_registry(dir)?;
+        update(&mut registry.value);
+        self.save_project_registry(dir, &registry.value, registry.version.as_ref())?;
+        Ok(registry.value)
+    }
+
+    fn update_chat_index(
+        &self,
+        dir: &Path,
+        update: &mut dyn FnMut(&mut ChatIndex),
+    ) -> Result<ChatIndex, StorageError> {
+        let _lock = lock_exclusive(&dir.join(index::INDEX_FILE))?;
+        let mut index = self.load_chat_index(dir)?;
+        update(&mut index.value);
+        self.save_chat_index(dir, &index.value, index.version.as_ref())?;
+        Ok(index.value)
+    }
+
+    fn update_approvals(
+        &self,
+        dir: &Path,
+        update: &mut dyn FnMut(&mut ApprovalsData),
+    ) -> Result<ApprovalsData, StorageError> {
+        let _lock = lock_exclusive(&dir.join(approvals::APPROVALS_FILE))?;
+        let mut approvals = self.load_approvals(dir)?;
+        update(&mut approvals.value);
+        self.save_approvals(dir, &approvals.value, approvals.version.as_ref())?;
+        Ok(approvals.value)
+    }
+
+    fn document_version(
+        &self,
+        dir: &Path,
+        document: ConfigDocument,
+    ) -> Result<Option<Version>, StorageError> {
+        let path = dir.join(document.file_name());
+        let _lock = lock_shared(&path)?;
+        let version = match document {
+            ConfigDocument::Projects => projects::parse_registry_file(&path)?
+                .map(|registry| Version::of(&registry))
+                .transpose()?,
+            ConfigDocument::ChatIndex => index::parse_index_file(&path)?
+                .map(|index| Version::of(&index))
+                .transpose()?,
+            ConfigDocument::Approvals => approvals::parse_approvals_file(&path)?
+                .map(|approvals| Version::of(&approvals))
+                .transpose()?,
+        };
+        Ok(version)
+    }
+}
+
+/// Open the storage for `config_dir`: SQLite if `overseer.db` exists there,
+/// JSON otherwise. An opened database serves every directory under
+/// `config_dir` from then on.
+pub fn open_storage(config_dir: &Path) -> Result<StorageBackend, StorageError> {
+    let db_path = config_dir.join(DB_FILE);
+    if !db_path.exists() {
+        return Ok(StorageBackend::Json);
+    }
+    let mut databases = DATABASES.write().unwrap();
+    if !databases.iter().any(|db| db.root() == config_dir) {
+        databases.push(Arc::new(SqliteStorage::open(config_dir, &db_path)?));
+    }
+    Ok(StorageBackend::Sqlite)
+}
+
+/// The backend that keeps the documents of `dir`.
+pub fn storage_for(dir: &Path) -> Arc<dyn Storage> {
+    let databases = DATABASES.read().unwrap();
+    match databases.iter().find(|db| dir.starts_with(db.root())) {
+        Some(db) => db.clone(),
+        None => Arc::new(JsonStorage),
+    }
+}
+
+/// Which backend keeps the documents of `dir`.
+pub fn backend_for(dir: &Path) -> StorageBackend {
+    let databases = DATABASES.read().unwrap();
+    if databases.iter().any(|db| dir.starts_with(db.root())) {
+        StorageBackend::Sqlite
+    } else {
+        StorageBackend::Json
+    }
+}
+
+/// Move a chat directory (and, with SQLite, the documents stored for it).
+pub fn rename_chat_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    storage_for(from)
+        .rename_dir(from, to)
+        .map_err(into_io_error)
+}
+
+/// `dir` relative to `root`, with `/` separators. The key a database stores
+/// a directory's documents under.
+pub(super) fn scope_of(root: &Path, dir: &Path) -> String {
+    let relative = dir.strip_prefix(root).unwrap_or(dir);
+    relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether an `io::Error` from [`into_io_error`] is a
+/// [`StorageError::Conflict`].
+pub fn is_conflict(e: &std::io::Error) -> bool {
+    e.get_ref()
+        .and_then(|e| e.downcast_ref::<StorageError>())
+        .is_some_and(|e| matches!(e, StorageError::Conflict(_)))
+}
+
+/// A storage error as an `io::Error`, for the functions that return one.
+pub(super) fn into_io_error(e: StorageError) -> std::io::Error {
+    match e {
+        StorageError::Io(e) => e,
+        StorageError::Json(e) => std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+        other => std::io::Error::other(other),
+    }
+}
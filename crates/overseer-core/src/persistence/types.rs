@@ -29,6 +29,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::agents::event::ToolMeta;
+use crate::git::{RepoMember, WorkspaceLayout};
+use crate::vcs::Vcs;
 
 // ============================================================================
 // Chat Types
@@ -283,10 +285,19 @@ pub struct Project {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pr_prompt: Option<String>,
 
-    /// Post-create shell command.
+    /// Post-create shell command, run after `setup_steps`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub post_create: Option<String>,
 
+    /// Named steps run in each new workspace. See [`crate::workspace_setup`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub setup_steps: Vec<SetupStep>,
+
+    /// Shell command run in a workspace before it is archived, while its
+    /// processes are still running. See [`crate::workspace_teardown`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_archive: Option<String>,
+
     /// Workspace filter pattern.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workspace_filter: Option<String>,
@@ -316,6 +327,36 @@ pub struct Project {
     /// here so a Rust-side save of projects.json does not strip it.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub claude_config_dir: Option<String>,
+
+    /// Directory new workspaces are created under, overriding the global
+    /// `workspaceRoot` setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_root: Option<String>,
+
+    /// Naming template for new workspaces, overriding the global
+    /// `workspaceNaming` setting. See [`crate::git::layout`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_naming: Option<String>,
+
+    /// Version control backend. When `None`, it's detected from the
+    /// checkout. See [`crate::vcs`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vcs: Option<Vcs>,
+
+    /// Repositories of a multi-repo project. When set, a workspace has a
+    /// worktree of each one under a shared parent directory and `path` is
+    /// only the project's home. See [`crate::git::multi`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repos: Vec<RepoMember>,
+
+    /// Regexes for secrets redacted from this project's chats, besides the
+    /// built-in detectors. See [`crate::redaction`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact_patterns: Vec<String>,
+
+    /// Env vars whose values are redacted from this project's chats.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_env_vars: Vec<String>,
 }
 
 impl Project {
@@ -328,6 +369,34 @@ impl Project {
         }
     }
 
+    /// Mutable access to the workspaces list in use (see [`Self::get_workspaces`]).
+    pub fn get_workspaces_mut(&mut self) -> &mut Vec<Workspace> {
+        if !self.workspaces.is_empty() {
+            &mut self.workspaces
+        } else {
+            &mut self.worktrees
+        }
+    }
+
+    /// Whether workspaces may be merged into the default branch. Unset means
+    /// allowed.
+    pub fn allows_merge_to_main(&self) -> bool {
+        self.allow_merge_to_main != Some(false)
+    }
+
+    /// Whether this project spans several repositories.
+    pub fn is_multi_repo(&self) -> bool {
+        !self.repos.is_empty()
+    }
+
+    /// This project's own workspace layout settings.
+    pub fn workspace_layout(&self) -> WorkspaceLayout {
+        WorkspaceLayout {
+            root: self.workspace_root.clone(),
+            naming: self.workspace_naming.clone(),
+        }
+    }
+
     /// Get workspace filter (from either field).
     pub fn get_workspace_filter(&self) -> Option<&str> {
         self.workspace_filter
@@ -395,6 +464,16 @@ pub struct Workspace {
     /// SSH host ID for remote workspaces.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ssh_host_id: Option<String>,
+
+    /// Initial prompt for new chats in this workspace, overriding the
+    /// project's (e.g. seeded from a GitHub issue).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_prompt: Option<String>,
+
+    /// Progress of the project's setup steps in this workspace. Unset if the
+    /// project has none or they haven't run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup: Option<WorkspaceSetup>,
 }
 
 impl Workspace {
@@ -404,6 +483,89 @@ impl Workspace {
     }
 }
 
+// ============================================================================
+// Workspace Setup Types
+// ============================================================================
+
+/// A named step run when a workspace is created.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetupStep {
+    /// Shown in progress and in failures.
+    pub name: String,
+
+    /// What the step does.
+    #[serde(flatten)]
+    pub action: SetupAction,
+}
+
+/// What a [`SetupStep`] does, tagged by `kind`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SetupAction {
+    /// Copy files or directories (e.g. `.env`) from the project's main
+    /// checkout. Paths are relative; missing ones are skipped and existing
+    /// ones in the workspace are kept.
+    CopyFiles { paths: Vec<String> },
+
+    /// Install dependencies with the package manager the lockfile points to,
+    /// or with `command` if set.
+    Install {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        command: Option<String>,
+    },
+
+    /// Run a shell command in the workspace.
+    Shell { command: String },
+}
+
+/// Where a setup run, or one of its steps, is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SetupStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    /// Not run: an earlier step failed, or there was nothing to do.
+    Skipped,
+}
+
+/// Outcome of one setup step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetupStepResult {
+    pub name: String,
+
+    pub status: SetupStatus,
+
+    /// Exit code, for steps that run a command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+
+    /// Why the step failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// The last lines of output, kept for failed steps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+/// Setup progress recorded on a [`Workspace`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSetup {
+    pub status: SetupStatus,
+
+    pub steps: Vec<SetupStepResult>,
+
+    pub started_at: DateTime<Utc>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
 // ============================================================================
 // Approvals Types
 // ============================================================================
@@ -423,6 +585,39 @@ pub struct ApprovalsData {
     pub command_prefixes: Vec<String>,
 }
 
+// ============================================================================
+// PR Review Types
+// ============================================================================
+
+/// Review threads that have already been sent to an agent.
+///
+/// Saved to `pr_reviews.json` in the workspace's chat directory, so feeding
+/// review comments back twice doesn't repeat threads the agent already handled.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressedReviewThreads {
+    /// One entry per addressed thread.
+    #[serde(default)]
+    pub threads: Vec<AddressedReviewThread>,
+}
+
+/// A review thread that was handed to an agent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressedReviewThread {
+    /// GitHub GraphQL id of the review thread.
+    pub thread_id: String,
+
+    /// The PR the thread belongs to.
+    pub pr_number: u64,
+
+    /// The chat whose agent was asked to address it.
+    pub chat_id: String,
+
+    /// When the prompt was sent.
+    pub addressed_at: DateTime<Utc>,
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -510,6 +705,8 @@ mod tests {
                 init_prompt: None,
                 pr_prompt: None,
                 post_create: None,
+                setup_steps: vec![],
+                pre_archive: None,
                 workspace_filter: None,
                 worktree_filter: None,
                 use_github: Some(true),
@@ -517,6 +714,12 @@ mod tests {
                 main_branch: None,
                 default_sandboxed: None,
                 claude_config_dir: None,
+                workspace_root: None,
+                workspace_naming: None,
+                vcs: None,
+                repos: Vec::new(),
+                redact_patterns: Vec::new(),
+                sensitive_env_vars: Vec::new(),
             }],
         };
 
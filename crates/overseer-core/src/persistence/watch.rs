@@ -0,0 +1,317 @@
+//! Watching the config dir for changes to its shared documents.
+//!
+//! When the desktop app and `overseer-daemon` share a config dir, each needs
+//! to hear about the other's changes to projects, chat indexes and approvals,
+//! and clients need to hear about the backend's own (workspace setup, garbage
+//! collection, ...). [`watch_config_dir`] polls those documents and emits a
+//! [`ConfigChange`] on [`CONFIG_CHANGED_EVENT`] when one changes, with its new
+//! version, so clients that don't have that version can reload it. That also
+//! keeps their next save from failing with a conflict (see
+//! [`locking`](super::locking)).
+//!
+//! Files are only loaded when their modification time or size changes. With
+//! the SQLite backend that means the database's.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+
+use super::approvals::APPROVALS_FILE;
+use super::index::INDEX_FILE;
+use super::locking::Version;
+use super::projects::PROJECTS_FILE;
+use super::storage::{backend_for, storage_for, StorageBackend, DB_FILE};
+use crate::event_bus::EventBus;
+
+/// Event type of a [`ConfigChange`].
+pub const CONFIG_CHANGED_EVENT: &str = "config:changed";
+
+/// How often the config dir is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A document shared between processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigDocument {
+    /// `projects.json` in the config dir.
+    Projects,
+    /// A workspace's `chats.json`.
+    ChatIndex,
+    /// A project's `approvals.json`.
+    Approvals,
+}
+
+impl ConfigDocument {
+    /// The document's file name in the JSON layout.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ConfigDocument::Projects => PROJECTS_FILE,
+            ConfigDocument::ChatIndex => INDEX_FILE,
+            ConfigDocument::Approvals => APPROVALS_FILE,
+        }
+    }
+}
+
+/// A document that changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    pub document: ConfigDocument,
+    /// The project's chat directory name, for chat indexes and approvals.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// The workspace's chat directory name, for chat indexes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// The document's new version. A client that saved it has it already.
+    pub version: Option<Version>,
+}
+
+/// Stops watching when dropped.
+pub struct ConfigWatcher {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Poll `config_dir` in the background, emitting a [`ConfigChange`] on
+/// `event_bus` for each document that changes. `on_change` sees
+/// each change first, to drop anything cached from the document. Keep the
+/// returned watcher alive for as long as changes should be reported.
+pub fn watch_config_dir(
+    config_dir: PathBuf,
+    event_bus: Arc<EventBus>,
+    mut on_change: impl FnMut(&ConfigChange) + Send + 'static,
+) -> ConfigWatcher {
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = stop.clone();
+    std::thread::spawn(move || {
+        let mut poller = ConfigPoller::new(config_dir);
+        while !stopped.load(Ordering::Relaxed) {
+            for change in poller.poll() {
+                on_change(&change);
+                event_bus.emit(CONFIG_CHANGED_EVENT, &change);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+    ConfigWatcher { stop }
+}
+
+/// What a document looked like on the previous poll.
+struct Stamp {
+    /// Modification time and size of the file it's stored in.
+    file: Vec<(Option<SystemTime>, u64)>,
+    version: Option<Version>,
+}
+
+/// Compares the config dir's documents with the previous poll.
+struct ConfigPoller {
+    config_dir: PathBuf,
+    stamps: HashMap<PathBuf, Stamp>,
+    first: bool,
+}
+
+impl ConfigPoller {
+    fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            stamps: HashMap::new(),
+            first: true,
+        }
+    }
+
+    /// Documents changed since the previous poll, by this process or
+    /// another. The first poll only records where things stand.
+    fn poll(&mut self) -> Vec<ConfigChange> {
+        let mut changes = Vec::new();
+        let documents = documents(&self.config_dir);
+        for (change, dir) in &documents {
+            let path = dir.join(change.document.file_name());
+            let file = file_stamp(dir, &path);
+            if self
+                .stamps
+                .get(&path)
+                .is_some_and(|stamp| stamp.file == file)
+            {
+                continue;
+            }
+            let version = match storage_for(dir).document_version(dir, change.document) {
+                Ok(version) => version,
+                Err(e) => {
+                    log::warn!("Failed to check {} for changes: {e}", path.display());
+                    continue;
+                }
+            };
+            let previous = self.stamps.insert(path.clone(), Stamp { file, version });
+            let version = &self.stamps[&path].version;
+            let changed = match previous {
+                Some(previous) => previous.version != *version,
+                None => !self.first,
+            };
+            if changed && version.is_some() {
+                changes.push(ConfigChange {
+                    version: version.clone(),
+                    ..change.clone()
+                });
+            }
+        }
+        let paths: Vec<PathBuf> = documents
+            .iter()
+            .map(|(change, dir)| dir.join(change.document.file_name()))
+            .collect();
+        self.stamps.retain(|path, _| paths.contains(path));
+        self.first = false;
+        changes
+    }
+}
+
+/// The shared documents under `config_dir`, with the directory of each.
+fn documents(config_dir: &Path) -> Vec<(ConfigChange, PathBuf)> {
+    let mut documents = vec![(
+        ConfigChange {
+            document: ConfigDocument::Projects,
+            project: None,
+            workspace: None,
+            version: None,
+        },
+        config_dir.to_path_buf(),
+    )];
+    for (project, project_dir) in subdirs(&config_dir.join("chats")) {
+        documents.push((
+            ConfigChange {
+                document: ConfigDocument::Approvals,
+                project: Some(project.clone()),
+                workspace: None,
+                version: None,
+            },
+            project_dir.clone(),
+        ));
+        for (workspace, workspace_dir) in subdirs(&project_dir) {
+            documents.push((
+                ConfigChange {
+                    document: ConfigDocument::ChatIndex,
+                    project: Some(project.clone()),
+                    workspace: Some(workspace),
+                    version: None,
+                },
+                workspace_dir,
+            ));
+        }
+    }
+    documents
+}
+
+fn subdirs(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().into_owned(),
+                entry.path(),
+            )
+        })
+        .collect()
+}
+
+/// Modification time and size of the files a document at `path` is stored
+/// in: the file itself, or the database and its write-ahead log.
+fn file_stamp(dir: &Path, path: &Path) -> Vec<(Option<SystemTime>, u64)> {
+    let files = match backend_for(dir) {
+        StorageBackend::Json => vec![path.to_path_buf()],
+        StorageBackend::Sqlite => {
+            let db = storage_root(dir).join(DB_FILE);
+            let mut wal = db.clone().into_os_string();
+            wal.push("-wal");
+            vec![db, PathBuf::from(wal)]
+        }
+    };
+    files
+        .iter()
+        .map(|file| match fs::metadata(file) {
+            Ok(meta) => (meta.modified().ok(), meta.len()),
+            Err(_) => (None, 0),
+        })
+        .collect()
+}
+
+/// The config dir holding `dir`'s database.
+fn storage_root(dir: &Path) -> PathBuf {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(DB_FILE).exists())
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::types::ApprovalsData;
+    use crate::persistence::{load_approvals_versioned, save_approvals};
+    use tempfile::tempdir;
+
+    /// Write approvals the way another process would, without this
+    /// process seeing them.
+    fn write_externally(dir: &Path, tools: &[&str]) {
+        let approvals = ApprovalsData {
+            tool_names: tools.iter().map(|t| t.to_string()).collect(),
+            command_prefixes: Vec::new(),
+        };
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join(APPROVALS_FILE),
+            serde_json::to_string(&approvals).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn approvals_changed(version: Version) -> Vec<ConfigChange> {
+        vec![ConfigChange {
+            document: ConfigDocument::Approvals,
+            project: Some("repo".to_string()),
+            workspace: None,
+            version: Some(version),
+        }]
+    }
+
+    #[test]
+    fn reports_changes_with_their_version() {
+        let config = tempdir().unwrap();
+        let project_dir = config.path().join("chats").join("repo");
+        write_externally(&project_dir, &["Read"]);
+
+        let mut poller = ConfigPoller::new(config.path().to_path_buf());
+        assert!(poller.poll().is_empty());
+
+        // Our own save is reported too, with the version the saver has.
+        let loaded = load_approvals_versioned(&project_dir).unwrap();
+        let mut approvals = loaded.value;
+        approvals.tool_names.push("Glob".to_string());
+        let base = save_approvals(&project_dir, &approvals, loaded.version.as_ref()).unwrap();
+        assert_eq!(poller.poll(), approvals_changed(base.clone()));
+        assert!(poller.poll().is_empty());
+
+        // So is another process's, and then our stale save conflicts.
+        std::thread::sleep(Duration::from_millis(20));
+        write_externally(&project_dir, &["Read", "Glob", "Bash"]);
+        let external = load_approvals_versioned(&project_dir).unwrap();
+        assert_eq!(poller.poll(), approvals_changed(external.version.unwrap()));
+        assert!(poller.poll().is_empty());
+
+        let err = save_approvals(&project_dir, &approvals, Some(&base)).unwrap_err();
+        assert!(crate::persistence::storage::is_conflict(&err));
+    }
+}
@@ -0,0 +1,275 @@
+//! Feeding PR review comments back to the agent.
+//!
+//! # Overview
+//!
+//! When reviewers leave comments on a workspace's pull request, this module
+//! gathers the unresolved review threads (file, line, diff hunk and the
+//! conversation) and turns them into a single structured prompt for the
+//! workspace's agent. After the prompt is sent, the caller records the thread
+//! ids with [`mark_review_threads_addressed`](crate::persistence::mark_review_threads_addressed)
+//! so the same threads aren't sent twice.
+//!
+//! ```ignore
+//! let runner = ShellGhRunner::new(agent_shell);
+//! if let Some(feedback) = prepare_review_feedback(&runner, &workspace, &branch, &chat_dir, false)? {
+//!     send_to_agent(&feedback.prompt);
+//!     mark_review_threads_addressed(&chat_dir, feedback.pr_number, chat_id, &feedback.thread_ids)?;
+//! }
+//! ```
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::git::github::{get_pr_status, list_review_threads, GhRunner, PrStatus, ReviewThread};
+use crate::git::GitError;
+use crate::persistence::load_addressed_review_threads;
+
+/// Diff hunks are trimmed to their last lines; GitHub's hunk ends at the
+/// commented line, so the tail is the relevant context.
+const MAX_HUNK_LINES: usize = 12;
+
+/// A prompt built from a PR's pending review threads.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewFeedback {
+    /// The PR the threads belong to
+    pub pr_number: u64,
+    /// Web URL of the PR
+    pub pr_url: String,
+    /// Ids of the threads included in the prompt
+    pub thread_ids: Vec<String>,
+    /// The prompt to send to the agent
+    pub prompt: String,
+}
+
+/// Gather pending review threads for `branch`'s PR and build the prompt.
+///
+/// Pending means unresolved on GitHub and, unless `include_addressed` is set,
+/// not already recorded as addressed in `chat_dir`.
+///
+/// # Returns
+///
+/// `Ok(None)` if the branch has no PR or there is nothing pending.
+pub fn prepare_review_feedback(
+    runner: &dyn GhRunner,
+    workspace_path: &Path,
+    branch: &str,
+    chat_dir: &Path,
+    include_addressed: bool,
+) -> Result<Option<ReviewFeedback>, GitError> {
+    let Some(pr) = get_pr_status(runner, workspace_path, branch)? else {
+        return Ok(None);
+    };
+
+    let addressed = load_addressed_review_threads(chat_dir)
+        .map_err(|e| GitError::Other(format!("Failed to load addressed review threads: {e}")))?;
+
+    let threads: Vec<ReviewThread> = list_review_threads(runner, workspace_path, pr.number)?
+        .into_iter()
+        .filter(|t| !t.is_resolved && !t.comments.is_empty())
+        .filter(|t| include_addressed || !addressed.is_addressed(pr.number, &t.id))
+        .collect();
+
+    if threads.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(ReviewFeedback {
+        pr_number: pr.number,
+        pr_url: pr.url.clone(),
+        thread_ids: threads.iter().map(|t| t.id.clone()).collect(),
+        prompt: build_review_prompt(&pr, &threads),
+    }))
+}
+
+/// Format review threads into a prompt for the agent.
+///
+/// Each thread becomes a numbered section with its location, the tail of the
+/// diff hunk it was left on, and every comment in order.
+pub fn build_review_prompt(pr: &PrStatus, threads: &[ReviewThread]) -> String {
+    let mut prompt = format!(
+        "Reviewers left {} unresolved comment thread{} on PR #{}",
+        threads.len(),
+        if threads.len() == 1 { "" } else { "s" },
+        pr.number
+    );
+    if !pr.url.is_empty() {
+        prompt.push_str(&format!(" ({})", pr.url));
+    }
+    prompt.push_str(
+        ".\n\nAddress each thread: make the requested change, or explain why you \
+         disagree. When you're done, summarize what you did per thread.\n",
+    );
+
+    for (i, thread) in threads.iter().enumerate() {
+        prompt.push_str(&format!("\n## {}. {}", i + 1, thread_location(thread)));
+        if thread.is_outdated {
+            prompt.push_str(" (outdated: the code has changed since)");
+        }
+        prompt.push('\n');
+
+        let hunk = trim_hunk(&thread.diff_hunk);
+        if !hunk.is_empty() {
+            prompt.push_str(&format!("\n```diff\n{hunk}\n```\n"));
+        }
+
+        prompt.push('\n');
+        for comment in &thread.comments {
+            let author = if comment.author.is_empty() {
+                "reviewer"
+            } else {
+                &comment.author
+            };
+            prompt.push_str(&format!("**{author}:** {}\n", comment.body.trim()));
+        }
+    }
+
+    prompt
+}
+
+/// `path:line`, falling back to the original line for outdated threads.
+fn thread_location(thread: &ReviewThread) -> String {
+    match thread.line.or(thread.original_line) {
+        Some(line) => format!("{}:{line}", thread.path),
+        None => thread.path.clone(),
+    }
+}
+
+/// Keep the last [`MAX_HUNK_LINES`] lines of a diff hunk.
+fn trim_hunk(hunk: &str) -> String {
+    let lines: Vec<&str> = hunk.trim_end().lines().collect();
+    let start = lines.len().saturating_sub(MAX_HUNK_LINES);
+    lines[start..].join("\n")
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::github::ReviewThreadComment;
+    use crate::persistence::mark_review_threads_addressed;
+    use crate::test_support::FakeGhRunner;
+    use tempfile::tempdir;
+
+    const PR_VIEW: &str =
+        r#"{"number":42,"state":"OPEN","url":"https://github.com/o/r/pull/42","isDraft":false}"#;
+
+    const THREADS: &str = r#"{"data":{"repository":{"pullRequest":{"reviewThreads":{"nodes":[
+        {"id":"T1","path":"src/a.rs","line":10,"originalLine":10,"isResolved":false,"isOutdated":false,
+         "comments":{"nodes":[{"body":"Use a constant","createdAt":"","url":"","diffHunk":"@@ -1 +1 @@\n+let x = 5;","author":{"login":"alice"}}]}},
+        {"id":"T2","path":"src/b.rs","line":3,"originalLine":3,"isResolved":true,"isOutdated":false,
+         "comments":{"nodes":[{"body":"Done","createdAt":"","url":"","diffHunk":"","author":{"login":"bob"}}]}},
+        {"id":"T3","path":"src/c.rs","line":null,"originalLine":7,"isResolved":false,"isOutdated":true,
+         "comments":{"nodes":[{"body":"Typo","createdAt":"","url":"","diffHunk":"","author":{"login":"carol"}}]}}
+    ]}}}}}"#;
+
+    fn runner() -> FakeGhRunner {
+        let runner = FakeGhRunner::new();
+        runner.respond(&["pr", "view"], PR_VIEW);
+        runner.respond(&["api", "graphql"], THREADS);
+        runner
+    }
+
+    #[test]
+    fn prepare_skips_resolved_threads() {
+        let chat_dir = tempdir().unwrap();
+        let feedback = prepare_review_feedback(
+            &runner(),
+            Path::new("/w"),
+            "feature",
+            chat_dir.path(),
+            false,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(feedback.pr_number, 42);
+        assert_eq!(feedback.thread_ids, vec!["T1", "T3"]);
+        assert!(feedback
+            .prompt
+            .contains("2 unresolved comment threads on PR #42"));
+        assert!(feedback.prompt.contains("## 1. src/a.rs:10"));
+        assert!(feedback.prompt.contains("+let x = 5;"));
+        assert!(feedback.prompt.contains("**alice:** Use a constant"));
+        assert!(feedback.prompt.contains("## 2. src/c.rs:7 (outdated"));
+        assert!(!feedback.prompt.contains("bob"));
+    }
+
+    #[test]
+    fn prepare_skips_addressed_threads_unless_asked() {
+        let chat_dir = tempdir().unwrap();
+        mark_review_threads_addressed(chat_dir.path(), 42, "chat-1", &["T1".into()]).unwrap();
+
+        let feedback = prepare_review_feedback(
+            &runner(),
+            Path::new("/w"),
+            "feature",
+            chat_dir.path(),
+            false,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(feedback.thread_ids, vec!["T3"]);
+
+        let feedback =
+            prepare_review_feedback(&runner(), Path::new("/w"), "feature", chat_dir.path(), true)
+                .unwrap()
+                .unwrap();
+        assert_eq!(feedback.thread_ids, vec!["T1", "T3"]);
+    }
+
+    #[test]
+    fn prepare_without_pr_is_none() {
+        let runner = FakeGhRunner::new();
+        runner.fail(
+            &["pr", "view"],
+            "no pull requests found for branch \"feature\"",
+        );
+        let chat_dir = tempdir().unwrap();
+
+        let feedback =
+            prepare_review_feedback(&runner, Path::new("/w"), "feature", chat_dir.path(), false)
+                .unwrap();
+        assert!(feedback.is_none());
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn hunks_are_trimmed_to_the_tail() {
+        let hunk = (0..30)
+            .map(|i| format!("+line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let pr = PrStatus {
+            number: 1,
+            state: "OPEN".to_string(),
+            url: String::new(),
+            is_draft: false,
+        };
+        let thread = ReviewThread {
+            id: "T".to_string(),
+            path: "a.rs".to_string(),
+            line: Some(30),
+            original_line: None,
+            is_resolved: false,
+            is_outdated: false,
+            diff_hunk: hunk,
+            comments: vec![ReviewThreadComment {
+                author: String::new(),
+                body: "Why?".to_string(),
+                created_at: String::new(),
+                url: String::new(),
+            }],
+        };
+
+        let prompt = build_review_prompt(&pr, &[thread]);
+        assert!(prompt.contains("+line 29"));
+        assert!(!prompt.contains("+line 17\n"));
+        assert!(prompt.contains("1 unresolved comment thread on PR #1."));
+        assert!(prompt.contains("**reviewer:** Why?"));
+    }
+}
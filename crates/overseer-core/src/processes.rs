@@ -0,0 +1,512 @@
+//! Processes running in workspaces.
+//!
+//! # Overview
+//!
+//! Terminals ([`crate::managers::PtyManager`]) and agents
+//! ([`crate::spawn::AgentProcess`]) register the process they start with the
+//! global [`tracker`], tagged with the workspace it runs in. That covers the
+//! shells and agent CLIs Overseer started itself, but not what those start:
+//! dev servers, watchers and databases launched from a terminal or by an
+//! agent's tool call.
+//!
+//! [`find_workspace_processes`] takes a snapshot of the process table and
+//! returns everything still running under a workspace:
+//!
+//! - tracked processes and all their descendants,
+//! - any other process whose working directory is inside the workspace (this
+//!   catches servers that daemonized and were reparented away from the shell),
+//!
+//! along with the TCP ports each one listens on. [`terminate`] stops them with
+//! `SIGTERM`, then `SIGKILL` for those still running after [`TERM_GRACE`].
+//! Only the first kind is Overseer's to stop unasked: the others
+//! ([`ProcessSource::Workspace`]) may be anything the user runs from that
+//! directory, so callers list them instead.
+//!
+//! Overseer's own process and its ancestors are never returned.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// How long [`terminate`] waits after `SIGTERM` before sending `SIGKILL`.
+pub const TERM_GRACE: Duration = Duration::from_secs(3);
+
+/// Who started a process found in a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProcessSource {
+    /// A terminal shell, or something started from one.
+    Terminal,
+    /// An agent CLI, or something its tools started.
+    Agent,
+    /// Not started by Overseer, but running in the workspace directory.
+    Workspace,
+}
+
+/// A process running under a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceProcess {
+    pub pid: u32,
+    /// Command line, as `ps` reports it.
+    pub command: String,
+    pub source: ProcessSource,
+    /// TCP ports the process listens on.
+    pub ports: Vec<u16>,
+}
+
+/// A process [`terminate`] stopped, or failed to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoppedProcess {
+    #[serde(flatten)]
+    pub process: WorkspaceProcess,
+    /// True when it ignored `SIGTERM` and needed `SIGKILL`.
+    pub forced: bool,
+    /// Why it couldn't be stopped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// ============================================================================
+// TRACKER
+// ============================================================================
+
+/// A process Overseer started in a workspace.
+#[derive(Debug, Clone)]
+struct TrackedProcess {
+    workspace: PathBuf,
+    source: ProcessSource,
+}
+
+/// Processes Overseer started, keyed by pid.
+#[derive(Default)]
+pub struct ProcessTracker {
+    processes: Mutex<HashMap<u32, TrackedProcess>>,
+}
+
+impl ProcessTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `pid` runs in `workspace`.
+    pub fn track(&self, pid: u32, workspace: impl Into<PathBuf>, source: ProcessSource) {
+        self.processes.lock().unwrap().insert(
+            pid,
+            TrackedProcess {
+                workspace: workspace.into(),
+                source,
+            },
+        );
+    }
+
+    /// Forget `pid`, once it has exited.
+    pub fn untrack(&self, pid: u32) {
+        self.processes.lock().unwrap().remove(&pid);
+    }
+
+    /// Tracked processes running in `workspace` or a directory below it.
+    pub fn tracked_in(&self, workspace: &Path) -> Vec<(u32, ProcessSource)> {
+        let mut tracked: Vec<_> = self
+            .processes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, p)| p.workspace.starts_with(workspace))
+            .map(|(pid, p)| (*pid, p.source))
+            .collect();
+        tracked.sort_by_key(|(pid, _)| *pid);
+        tracked
+    }
+}
+
+static TRACKER: LazyLock<ProcessTracker> = LazyLock::new(ProcessTracker::new);
+
+/// The process tracker shared by terminals and agents.
+pub fn tracker() -> &'static ProcessTracker {
+    &TRACKER
+}
+
+// ============================================================================
+// FINDING
+// ============================================================================
+
+/// One row of `ps` output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProcessRow {
+    pid: u32,
+    ppid: u32,
+    command: String,
+}
+
+/// Everything running under `workspace`, ordered by pid. Blocking: runs `ps`
+/// and `lsof`.
+pub fn find_workspace_processes(workspace: &Path) -> Vec<WorkspaceProcess> {
+    let table = process_table();
+    let mut found = collect_workspace_pids(
+        workspace,
+        &table,
+        &tracker().tracked_in(workspace),
+        &process_cwds(),
+    );
+
+    // Never stop Overseer itself, or whatever launched it.
+    let parents: HashMap<u32, u32> = table.iter().map(|row| (row.pid, row.ppid)).collect();
+    let mut pid = std::process::id();
+    loop {
+        found.remove(&pid);
+        match parents.get(&pid) {
+            Some(&ppid) if ppid != pid && ppid != 0 => pid = ppid,
+            _ => break,
+        }
+    }
+
+    let commands: HashMap<u32, &str> = table
+        .iter()
+        .map(|row| (row.pid, row.command.as_str()))
+        .collect();
+    let mut ports = listening_ports();
+    found
+        .into_iter()
+        .map(|(pid, source)| WorkspaceProcess {
+            pid,
+            command: commands.get(&pid).copied().unwrap_or_default().to_string(),
+            source,
+            ports: ports.remove(&pid).unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Pids under `workspace`: the tracked ones that are still in `table`, the
+/// ones whose working directory is in the workspace, and all descendants.
+fn collect_workspace_pids(
+    workspace: &Path,
+    table: &[ProcessRow],
+    tracked: &[(u32, ProcessSource)],
+    cwds: &HashMap<u32, PathBuf>,
+) -> BTreeMap<u32, ProcessSource> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for row in table {
+        children.entry(row.ppid).or_default().push(row.pid);
+    }
+    let running: HashSet<u32> = table.iter().map(|row| row.pid).collect();
+    let canonical = workspace.canonicalize().ok();
+    let in_workspace = |dir: &PathBuf| {
+        dir.starts_with(workspace) || canonical.as_ref().is_some_and(|c| dir.starts_with(c))
+    };
+
+    let mut roots: Vec<(u32, ProcessSource)> = tracked
+        .iter()
+        .filter(|(pid, _)| running.contains(pid))
+        .copied()
+        .collect();
+    let mut cwd_pids: Vec<u32> = cwds
+        .iter()
+        .filter(|(pid, dir)| running.contains(pid) && in_workspace(dir))
+        .map(|(pid, _)| *pid)
+        .collect();
+    cwd_pids.sort_unstable();
+    roots.extend(
+        cwd_pids
+            .into_iter()
+            .map(|pid| (pid, ProcessSource::Workspace)),
+    );
+
+    // Tracked roots come first, so their descendants keep the tracked source.
+    let mut found = BTreeMap::new();
+    for (root, source) in roots {
+        let mut stack = vec![root];
+        while let Some(pid) = stack.pop() {
+            if found.contains_key(&pid) {
+                continue;
+            }
+            found.insert(pid, source);
+            if let Some(kids) = children.get(&pid) {
+                stack.extend(kids);
+            }
+        }
+    }
+    found
+}
+
+/// All processes, from `ps`. Empty if `ps` can't run.
+fn process_table() -> Vec<ProcessRow> {
+    Command::new("ps")
+        .args(["-A", "-o", "pid=", "-o", "ppid=", "-o", "command="])
+        .output()
+        .map(|output| parse_ps(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+fn parse_ps(output: &str) -> Vec<ProcessRow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            Some(ProcessRow {
+                pid,
+                ppid,
+                command: fields.collect::<Vec<_>>().join(" "),
+            })
+        })
+        .collect()
+}
+
+/// Working directory of every process we may inspect.
+#[cfg(target_os = "linux")]
+fn process_cwds() -> HashMap<u32, PathBuf> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse().ok()?;
+            let cwd = std::fs::read_link(entry.path().join("cwd")).ok()?;
+            Some((pid, cwd))
+        })
+        .collect()
+}
+
+/// Working directory of every process we may inspect.
+#[cfg(not(target_os = "linux"))]
+fn process_cwds() -> HashMap<u32, PathBuf> {
+    run_lsof(&["-nP", "-d", "cwd", "-Fpn"])
+        .into_iter()
+        .filter_map(|(pid, names)| Some((pid, PathBuf::from(names.into_iter().next()?))))
+        .collect()
+}
+
+/// TCP ports each process listens on. Empty if `lsof` isn't installed.
+fn listening_ports() -> HashMap<u32, Vec<u16>> {
+    run_lsof(&["-nP", "-iTCP", "-sTCP:LISTEN", "-Fpn"])
+        .into_iter()
+        .map(|(pid, names)| {
+            let mut ports: Vec<u16> = names
+                .iter()
+                .filter_map(|name| name.rsplit(':').next()?.parse().ok())
+                .collect();
+            ports.sort_unstable();
+            ports.dedup();
+            (pid, ports)
+        })
+        .collect()
+}
+
+fn run_lsof(args: &[&str]) -> HashMap<u32, Vec<String>> {
+    Command::new("lsof")
+        .args(args)
+        .output()
+        .map(|output| parse_lsof(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// Parse `lsof -F pn` output: a `p<pid>` line, then `n<name>` lines for it.
+fn parse_lsof(output: &str) -> HashMap<u32, Vec<String>> {
+    let mut names: HashMap<u32, Vec<String>> = HashMap::new();
+    let mut pid = None;
+    for line in output.lines() {
+        if let Some(value) = line.strip_prefix('p') {
+            pid = value.parse().ok();
+        } else if let (Some(name), Some(pid)) = (line.strip_prefix('n'), pid) {
+            names.entry(pid).or_default().push(name.to_string());
+        }
+    }
+    names
+}
+
+// ============================================================================
+// STOPPING
+// ============================================================================
+
+/// Stop `processes`: `SIGTERM` first, `SIGKILL` for any still running after
+/// [`TERM_GRACE`]. Blocking.
+#[cfg(unix)]
+pub fn terminate(processes: Vec<WorkspaceProcess>) -> Vec<StoppedProcess> {
+    let mut stopped: Vec<StoppedProcess> = processes
+        .into_iter()
+        .map(|process| {
+            let error = signal(process.pid, libc::SIGTERM).err();
+            StoppedProcess {
+                process,
+                forced: false,
+                error,
+            }
+        })
+        .collect();
+
+    let mut waited = Duration::ZERO;
+    let step = Duration::from_millis(100);
+    let mut running = alive(&stopped);
+    while !running.is_empty() && waited < TERM_GRACE {
+        std::thread::sleep(step);
+        waited += step;
+        running = alive(&stopped);
+    }
+
+    for entry in stopped.iter_mut() {
+        if running.contains(&entry.process.pid) {
+            entry.forced = true;
+            entry.error = signal(entry.process.pid, libc::SIGKILL).err();
+        }
+    }
+    stopped
+}
+
+/// Stopping processes relies on Unix signals.
+#[cfg(not(unix))]
+pub fn terminate(processes: Vec<WorkspaceProcess>) -> Vec<StoppedProcess> {
+    processes
+        .into_iter()
+        .map(|process| StoppedProcess {
+            process,
+            forced: false,
+            error: Some("Stopping processes isn't supported on this platform".to_string()),
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn signal(pid: u32, signal: libc::c_int) -> Result<(), String> {
+    // SAFETY: kill(2) has no memory-safety requirements.
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        // Already gone.
+        Ok(())
+    } else {
+        Err(err.to_string())
+    }
+}
+
+/// Pids among `stopped` that are still running. Zombies count as exited.
+#[cfg(unix)]
+fn alive(stopped: &[StoppedProcess]) -> HashSet<u32> {
+    if stopped.is_empty() {
+        return HashSet::new();
+    }
+    let pids: Vec<String> = stopped.iter().map(|s| s.process.pid.to_string()).collect();
+    let Ok(output) = Command::new("ps")
+        .args(["-o", "pid=", "-o", "stat=", "-p", &pids.join(",")])
+        .output()
+    else {
+        return HashSet::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let stat = fields.next()?;
+            (!stat.starts_with('Z')).then_some(pid)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(pid: u32, ppid: u32, command: &str) -> ProcessRow {
+        ProcessRow {
+            pid,
+            ppid,
+            command: command.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_ps_and_lsof_output() {
+        let rows = parse_ps("    1     0 /sbin/init\n  420   1 node  server.js --port 3000\n");
+        assert_eq!(
+            rows,
+            vec![
+                row(1, 0, "/sbin/init"),
+                row(420, 1, "node server.js --port 3000")
+            ]
+        );
+
+        let names = parse_lsof("p420\nn*:3000\nn[::1]:3001\np77\nn127.0.0.1:5432\n");
+        assert_eq!(names[&420], vec!["*:3000", "[::1]:3001"]);
+        assert_eq!(names[&77], vec!["127.0.0.1:5432"]);
+    }
+
+    #[test]
+    fn collects_descendants_of_tracked_and_cwd_matched_processes() {
+        let workspace = Path::new("/ws/koala");
+        let table = vec![
+            row(1, 0, "init"),
+            row(10, 1, "zsh"),
+            row(11, 10, "npm run dev"),
+            row(12, 11, "vite"),
+            row(20, 1, "claude"),
+            row(30, 1, "postgres"),
+            row(31, 30, "postgres: writer"),
+            row(40, 1, "other"),
+            row(50, 1, "gone"),
+        ];
+        let tracked = [(10, ProcessSource::Terminal), (99, ProcessSource::Agent)];
+        let cwds = HashMap::from([
+            (30, PathBuf::from("/ws/koala/db")),
+            (40, PathBuf::from("/ws/dugong")),
+            (12, PathBuf::from("/ws/koala")),
+        ]);
+
+        let found = collect_workspace_pids(workspace, &table, &tracked, &cwds);
+        assert_eq!(
+            found.into_iter().collect::<Vec<_>>(),
+            vec![
+                (10, ProcessSource::Terminal),
+                (11, ProcessSource::Terminal),
+                (12, ProcessSource::Terminal),
+                (30, ProcessSource::Workspace),
+                (31, ProcessSource::Workspace),
+            ]
+        );
+    }
+
+    #[test]
+    fn tracker_matches_workspace_and_subdirectories() {
+        let tracker = ProcessTracker::new();
+        tracker.track(1, "/ws/koala", ProcessSource::Terminal);
+        tracker.track(2, "/ws/koala/web", ProcessSource::Agent);
+        tracker.track(3, "/ws/koala-2", ProcessSource::Agent);
+        assert_eq!(
+            tracker.tracked_in(Path::new("/ws/koala")),
+            vec![(1, ProcessSource::Terminal), (2, ProcessSource::Agent)]
+        );
+        tracker.untrack(1);
+        assert_eq!(tracker.tracked_in(Path::new("/ws/koala")).len(), 1);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn finds_and_stops_a_process_running_in_the_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .current_dir(dir.path())
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+
+        let found = find_workspace_processes(dir.path());
+        let process = found.iter().find(|p| p.pid == pid).expect("sleep found");
+        assert_eq!(process.source, ProcessSource::Workspace);
+        assert!(process.command.starts_with("sleep"));
+
+        let stopped = terminate(vec![process.clone()]);
+        assert_eq!(stopped[0].error, None);
+        assert!(!stopped[0].forced);
+        assert!(child.wait().unwrap().code().is_none(), "killed by a signal");
+    }
+}
@@ -58,6 +58,35 @@ pub fn run_shell_command(
     shell_prefix: Option<&str>,
     env_vars: &[(&str, &str)],
 ) -> Result<ShellCommandResult, String> {
+    let mut cmd = build_shell_script_command(command, working_dir, shell_prefix, env_vars)?;
+
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run command: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let exit_code = output.status.code().unwrap_or(-1);
+
+    Ok(ShellCommandResult {
+        exit_code,
+        stdout,
+        stderr,
+        success: output.status.success(),
+    })
+}
+
+/// Build a command that runs `command` as a script in a login shell, with
+/// stdout and stderr piped.
+///
+/// For callers that read the output as it arrives; [`run_shell_command`]
+/// waits for it instead.
+pub fn build_shell_script_command(
+    command: &str,
+    working_dir: &str,
+    shell_prefix: Option<&str>,
+    env_vars: &[(&str, &str)],
+) -> Result<Command, String> {
     // Get the shell prefix (either custom or default)
     let prefix = get_shell_prefix(shell_prefix);
 
@@ -77,19 +106,7 @@ pub fn run_shell_command(
         .envs(env_vars.iter().copied())
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped());
-
-    let output = cmd.output().map_err(|e| format!("Failed to run command: {}", e))?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let exit_code = output.status.code().unwrap_or(-1);
-
-    Ok(ShellCommandResult {
-        exit_code,
-        stdout,
-        stderr,
-        success: output.status.success(),
-    })
+    Ok(cmd)
 }
 
 /// Async version of `run_shell_command`.
@@ -36,6 +36,7 @@
 //! - [`crate::agents::gemini::GeminiConfig`]
 //! - [`crate::agents::opencode::OpenCodeConfig`]
 
+use crate::processes::{self, ProcessSource};
 use crate::shell::{build_login_shell_command, AgentExit};
 use std::io::{BufRead, BufReader, Write};
 use std::process::{Child, ChildStdin, Stdio};
@@ -328,6 +329,12 @@ impl AgentProcess {
 
         let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn: {}", e))?;
 
+        // Track the agent so archiving its workspace stops it and its tools
+        let pid = child.id();
+        if let Some(dir) = &config.working_dir {
+            processes::tracker().track(pid, dir, ProcessSource::Agent);
+        }
+
         // Take stdin if available
         let child_stdin = if config.uses_stdin {
             child.stdin.take()
@@ -389,31 +396,34 @@ impl AgentProcess {
         let child_arc_exit = Arc::clone(&child_arc);
         let stdin_arc_exit = Arc::clone(&stdin_arc);
         let tx_exit = tx;
-        thread::spawn(move || loop {
-            let mut guard = child_arc_exit.lock().unwrap();
-            if let Some(child) = guard.as_mut() {
-                match child.try_wait() {
-                    Ok(Some(status)) => {
-                        let _ = tx_exit.send(ProcessEvent::Exit(AgentExit {
-                            code: status.code().unwrap_or_default(),
-                            signal: None,
-                        }));
-                        guard.take();
-                        stdin_arc_exit.lock().unwrap().take();
-                        break;
-                    }
-                    Ok(None) => {}
-                    Err(_) => {
-                        guard.take();
-                        stdin_arc_exit.lock().unwrap().take();
-                        break;
+        thread::spawn(move || {
+            loop {
+                let mut guard = child_arc_exit.lock().unwrap();
+                if let Some(child) = guard.as_mut() {
+                    match child.try_wait() {
+                        Ok(Some(status)) => {
+                            let _ = tx_exit.send(ProcessEvent::Exit(AgentExit {
+                                code: status.code().unwrap_or_default(),
+                                signal: None,
+                            }));
+                            guard.take();
+                            stdin_arc_exit.lock().unwrap().take();
+                            break;
+                        }
+                        Ok(None) => {}
+                        Err(_) => {
+                            guard.take();
+                            stdin_arc_exit.lock().unwrap().take();
+                            break;
+                        }
                     }
+                } else {
+                    break;
                 }
-            } else {
-                break;
+                drop(guard);
+                thread::sleep(Duration::from_millis(100));
             }
-            drop(guard);
-            thread::sleep(Duration::from_millis(100));
+            processes::tracker().untrack(pid);
         });
 
         Ok(Self {
@@ -932,6 +932,103 @@ impl AgentProcessHandle for ArcWrapper {
     }
 }
 
+// ============================================================================
+// FAKE GH RUNNER - Canned `gh` CLI output
+// ============================================================================
+//
+// The real ShellGhRunner (in git/github.rs) shells out to `gh`, which needs
+// network access and GitHub credentials. FakeGhRunner answers from a table
+// of responses matched by argument prefix and records every call.
+
+use crate::git::github::GhRunner;
+use crate::git::{GitError, GitOutput};
+
+/// A fake `gh` runner for testing.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let runner = FakeGhRunner::new();
+/// runner.respond(&["pr", "view"], r#"{"number":1,"state":"OPEN","url":"","isDraft":false}"#);
+///
+/// let status = get_pr_status(&runner, path, "feature")?;
+/// assert_eq!(runner.calls()[0][0], "pr");
+/// ```
+pub struct FakeGhRunner {
+    /// Configured responses, checked in insertion order.
+    responses: Mutex<Vec<FakeGhResponse>>,
+
+    /// Arguments of every call, in order.
+    calls: Mutex<Vec<Vec<String>>>,
+}
+
+/// A canned `gh` answer for calls whose arguments start with `prefix`.
+struct FakeGhResponse {
+    prefix: Vec<String>,
+    success: bool,
+    stdout: String,
+    stderr: String,
+}
+
+impl FakeGhRunner {
+    /// Create a runner with no configured responses.
+    pub fn new() -> Self {
+        Self {
+            responses: Mutex::new(Vec::new()),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answer calls starting with `prefix` successfully with `stdout`.
+    pub fn respond(&self, prefix: &[&str], stdout: &str) {
+        self.respond_with(prefix, true, stdout, "");
+    }
+
+    /// Answer calls starting with `prefix` with a failed exit and `stderr`.
+    pub fn fail(&self, prefix: &[&str], stderr: &str) {
+        self.respond_with(prefix, false, "", stderr);
+    }
+
+    /// Answer calls starting with `prefix` with full control over the output.
+    pub fn respond_with(&self, prefix: &[&str], success: bool, stdout: &str, stderr: &str) {
+        self.responses.lock().unwrap().push(FakeGhResponse {
+            prefix: prefix.iter().map(|s| s.to_string()).collect(),
+            success,
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+        });
+    }
+
+    /// Arguments of every call made so far.
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl Default for FakeGhRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GhRunner for FakeGhRunner {
+    fn run(&self, args: &[String], _cwd: &std::path::Path) -> Result<GitOutput, GitError> {
+        self.calls.lock().unwrap().push(args.to_vec());
+
+        let responses = self.responses.lock().unwrap();
+        let response = responses
+            .iter()
+            .find(|r| args.starts_with(&r.prefix))
+            .ok_or_else(|| GitError::Other(format!("no fake gh response for {args:?}")))?;
+
+        Ok(GitOutput {
+            success: response.success,
+            stdout: response.stdout.as_bytes().to_vec(),
+            stderr: response.stderr.as_bytes().to_vec(),
+        })
+    }
+}
+
 // ============================================================================
 // TESTS FOR TEST SUPPORT
 // ============================================================================
@@ -0,0 +1,1013 @@
+//! Importing Claude Code and Codex transcripts as Overseer chats.
+//!
+//! # Sources
+//!
+//! - **Claude Code** keeps one JSONL file per session in
+//!   `~/.claude/projects/<encoded cwd>/<session id>.jsonl` (or under
+//!   `$CLAUDE_CONFIG_DIR`). Assistant lines have the same shape as the
+//!   `stream-json` output, so they go through [`ClaudeParser`] as-is; user
+//!   prompts become [`AgentEvent::UserMessage`]s, as Overseer records them.
+//! - **Codex** keeps rollout files in
+//!   `~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl` (or under `$CODEX_HOME`).
+//!   Their items are restated as the app-server notifications
+//!   [`CodexParser`] reads (`item/started`, `item/completed`, ...).
+//!
+//! Either way the result is what a live chat with that agent would have
+//! recorded.
+//!
+//! # Where Chats Land
+//!
+//! A transcript is attached to the workspace whose directory contains the
+//! session's cwd (the deepest one, if workspaces nest). Transcripts with no
+//! matching workspace are reported and skipped. The agent's session id is
+//! kept in the chat's metadata, so sending a message resumes the original
+//! session; a session already imported into that workspace is skipped.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::agents::claude::ClaudeParser;
+use crate::agents::codex::CodexParser;
+use crate::agents::event::AgentEvent;
+use crate::persistence::chat_jsonl::{
+    load_chat_metadata, save_chat_events, save_chat_metadata, ChatJsonlError,
+};
+use crate::persistence::index::{update_chat_index, upsert_chat_entry, IndexError};
+use crate::persistence::projects::{load_project_registry, ProjectError};
+use crate::persistence::types::{ChatIndexEntry, ChatMetadata};
+
+/// Longest label taken from a chat's first message.
+const LABEL_CHARS: usize = 60;
+
+/// Codex user messages that are context Codex injects, not what was typed.
+const CODEX_CONTEXT_PREFIXES: [&str; 4] = [
+    "<environment_context>",
+    "<user_instructions>",
+    "<turn_aborted>",
+    "# AGENTS.md instructions",
+];
+
+/// Errors from importing transcripts.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Chat error: {0}")]
+    Chat(#[from] ChatJsonlError),
+
+    #[error("Chat index error: {0}")]
+    Index(#[from] IndexError),
+
+    #[error("Project registry error: {0}")]
+    Projects(#[from] ProjectError),
+}
+
+/// Which agent wrote a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TranscriptSource {
+    Claude,
+    Codex,
+}
+
+impl TranscriptSource {
+    /// The chat's `agent_type`.
+    pub fn agent_type(self) -> &'static str {
+        match self {
+            TranscriptSource::Claude => "claude",
+            TranscriptSource::Codex => "codex",
+        }
+    }
+}
+
+/// Where to look for transcripts. A missing directory is skipped.
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptRoots {
+    /// Claude Code's config directory (holds `projects/`).
+    pub claude: Option<PathBuf>,
+    /// Codex's home directory (holds `sessions/`).
+    pub codex: Option<PathBuf>,
+}
+
+impl TranscriptRoots {
+    /// The agents' own defaults: `$CLAUDE_CONFIG_DIR` or `~/.claude`, and
+    /// `$CODEX_HOME` or `~/.codex`.
+    pub fn from_env() -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let dir = |var: &str, default: &str| {
+            std::env::var_os(var)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| home.as_ref().map(|h| h.join(default)))
+        };
+        Self {
+            claude: dir("CLAUDE_CONFIG_DIR", ".claude"),
+            codex: dir("CODEX_HOME", ".codex"),
+        }
+    }
+}
+
+/// A transcript converted to Overseer events.
+#[derive(Debug, Clone)]
+pub struct ParsedTranscript {
+    pub source: TranscriptSource,
+    /// The agent's session (Claude) or thread (Codex) id.
+    pub session_id: Option<String>,
+    /// Directory the agent ran in.
+    pub cwd: Option<String>,
+    /// Title the agent gave the session, if any.
+    pub title: Option<String>,
+    pub model: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub events: Vec<AgentEvent>,
+}
+
+impl ParsedTranscript {
+    fn new(source: TranscriptSource) -> Self {
+        Self {
+            source,
+            session_id: None,
+            cwd: None,
+            title: None,
+            model: None,
+            started_at: None,
+            ended_at: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Note a line's `timestamp`.
+    fn saw_time(&mut self, line: &Value) -> Option<DateTime<Utc>> {
+        let time = line
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc))?;
+        self.started_at.get_or_insert(time);
+        self.ended_at = Some(time);
+        Some(time)
+    }
+
+    /// Record a typed prompt, closing the previous turn first.
+    fn push_user(&mut self, text: &str, time: Option<DateTime<Utc>>) {
+        self.end_turn();
+        self.events.push(AgentEvent::UserMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            content: text.to_string(),
+            timestamp: time.or(self.ended_at).unwrap_or_else(Utc::now),
+            meta: None,
+        });
+    }
+
+    /// Close the current turn, unless it is already closed. Transcripts don't
+    /// always record the end of a turn; live chats always do.
+    fn end_turn(&mut self) {
+        let open = self
+            .events
+            .iter()
+            .rev()
+            .find(|e| matches!(e, AgentEvent::UserMessage { .. } | AgentEvent::TurnComplete))
+            .is_some_and(|e| matches!(e, AgentEvent::UserMessage { .. }));
+        if open {
+            self.events.push(AgentEvent::TurnComplete);
+        }
+    }
+
+    /// Number of typed prompts.
+    pub fn prompt_count(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|e| matches!(e, AgentEvent::UserMessage { .. }))
+            .count()
+    }
+
+    /// The chat's label: the agent's title, else the start of the first
+    /// prompt.
+    pub fn label(&self) -> String {
+        if let Some(title) = self.title.as_deref().filter(|t| !t.trim().is_empty()) {
+            return title.trim().to_string();
+        }
+        let first_prompt = self.events.iter().find_map(|e| match e {
+            AgentEvent::UserMessage { content, .. } => {
+                content.lines().find(|l| !l.trim().is_empty())
+            }
+            _ => None,
+        });
+        match first_prompt {
+            Some(line) if line.trim().chars().count() > LABEL_CHARS => {
+                let cut: String = line.trim().chars().take(LABEL_CHARS).collect();
+                format!("{}…", cut.trim_end())
+            }
+            Some(line) => line.trim().to_string(),
+            None => "Imported chat".to_string(),
+        }
+    }
+}
+
+// ============================================================================
+// CLAUDE
+// ============================================================================
+
+/// Convert a Claude Code session transcript.
+pub fn parse_claude_transcript(contents: &str) -> ParsedTranscript {
+    let mut transcript = ParsedTranscript::new(TranscriptSource::Claude);
+    let mut parser = ClaudeParser::new();
+
+    for raw in contents.lines() {
+        let Ok(line) = serde_json::from_str::<Value>(raw) else {
+            continue;
+        };
+        let flag = |name: &str| line.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+        // Subagent conversations are shown through their parent's Task call.
+        if flag("isSidechain") {
+            continue;
+        }
+        if transcript.session_id.is_none() {
+            transcript.session_id = str_field(&line, "sessionId");
+        }
+        if transcript.cwd.is_none() {
+            transcript.cwd = str_field(&line, "cwd");
+        }
+
+        match line.get("type").and_then(|t| t.as_str()) {
+            Some("summary") => transcript.title = str_field(&line, "summary"),
+            Some("user") => {
+                let time = transcript.saw_time(&line);
+                // Meta lines (command caveats) and compaction summaries are
+                // written by Claude Code, not typed.
+                if flag("isMeta") || flag("isCompactSummary") {
+                    continue;
+                }
+                if let Some(text) = claude_prompt_text(&line) {
+                    transcript.push_user(&text, time);
+                }
+            }
+            Some(_) => {
+                transcript.saw_time(&line);
+                if let Some(model) = line
+                    .pointer("/message/model")
+                    .and_then(|m| m.as_str())
+                    .filter(|m| !m.starts_with('<'))
+                {
+                    transcript.model = Some(model.to_string());
+                }
+                transcript.events.extend(parser.feed(&format!("{raw}\n")));
+            }
+            None => {}
+        }
+    }
+    transcript.events.extend(parser.flush());
+    transcript.end_turn();
+    transcript
+}
+
+/// The typed text of a Claude `user` line. Lines carrying only tool results
+/// have none (the live parser doesn't record them either).
+pub(crate) fn claude_prompt_text(line: &Value) -> Option<String> {
+    let text = match line.pointer("/message/content")? {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+// ============================================================================
+// CODEX
+// ============================================================================
+
+/// Convert a Codex rollout file.
+///
+/// Handles both the current layout (`{timestamp, type, payload}` lines) and
+/// older rollouts whose lines are the bare items.
+pub fn parse_codex_rollout(contents: &str) -> ParsedTranscript {
+    let mut transcript = ParsedTranscript::new(TranscriptSource::Codex);
+    let mut parser = CodexParser::new();
+    let mut feed = |transcript: &mut ParsedTranscript, method: &str, params: Value| {
+        let line = json!({ "method": method, "params": params });
+        let (events, _) = parser.feed(&format!("{line}\n"));
+        transcript.events.extend(events);
+    };
+
+    for raw in contents.lines() {
+        let Ok(line) = serde_json::from_str::<Value>(raw) else {
+            continue;
+        };
+        let time = transcript.saw_time(&line);
+        let (kind, item) = match line.get("type").and_then(|t| t.as_str()) {
+            Some(
+                kind @ ("session_meta" | "response_item" | "event_msg" | "turn_context"
+                | "compacted"),
+            ) => (kind, line.get("payload").cloned().unwrap_or(Value::Null)),
+            // Older rollouts: a bare meta line first, then bare items.
+            Some(_) => ("response_item", line.clone()),
+            None if line.get("id").is_some() => ("session_meta", line.clone()),
+            None => continue,
+        };
+
+        match kind {
+            "session_meta" => {
+                transcript.session_id = transcript.session_id.take().or(str_field(&item, "id"));
+                transcript.cwd = transcript.cwd.take().or(str_field(&item, "cwd"));
+            }
+            "turn_context" => {
+                if let Some(model) = str_field(&item, "model") {
+                    transcript.model = Some(model);
+                }
+                transcript.cwd = transcript.cwd.take().or(str_field(&item, "cwd"));
+            }
+            "event_msg" if item.get("type").and_then(|t| t.as_str()) == Some("task_complete") => {
+                feed(&mut transcript, "turn/completed", json!({}));
+            }
+            "response_item" => codex_item(&mut transcript, &item, time, &mut feed),
+            _ => {}
+        }
+    }
+    transcript.end_turn();
+    transcript
+}
+
+/// Restate one rollout item as the notifications a live session sends.
+fn codex_item(
+    transcript: &mut ParsedTranscript,
+    item: &Value,
+    time: Option<DateTime<Utc>>,
+    feed: &mut impl FnMut(&mut ParsedTranscript, &str, Value),
+) {
+    match item.get("type").and_then(|t| t.as_str()) {
+        Some("message") => {
+            let text = item
+                .get("content")
+                .and_then(|c| c.as_array())
+                .map(|parts| {
+                    parts
+                        .iter()
+                        .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default();
+            if text.trim().is_empty() {
+                return;
+            }
+            match item.get("role").and_then(|r| r.as_str()) {
+                Some("user") => {
+                    if let Some(cwd) = environment_cwd(&text) {
+                        transcript.cwd.get_or_insert(cwd);
+                    }
+                    let injected = CODEX_CONTEXT_PREFIXES
+                        .iter()
+                        .any(|prefix| text.trim_start().starts_with(prefix));
+                    if !injected {
+                        transcript.push_user(&text, time);
+                    }
+                }
+                Some("assistant") => feed(
+                    transcript,
+                    "item/completed",
+                    json!({ "item": { "type": "agentMessage", "text": text } }),
+                ),
+                _ => {}
+            }
+        }
+        Some("function_call") => {
+            let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("Tool");
+            let arguments = item
+                .get("arguments")
+                .and_then(|a| a.as_str())
+                .and_then(|a| serde_json::from_str::<Value>(a).ok())
+                .unwrap_or(Value::Null);
+            match shell_command(&arguments) {
+                Some(command) if is_shell_tool(name) => feed(
+                    transcript,
+                    "item/started",
+                    json!({ "item": { "type": "commandExecution", "command": command } }),
+                ),
+                _ => feed(
+                    transcript,
+                    "item/started",
+                    json!({ "item": {
+                        "type": "mcpToolCall",
+                        "toolName": name,
+                        "arguments": arguments,
+                    } }),
+                ),
+            }
+        }
+        Some("local_shell_call") => {
+            if let Some(command) = item.get("action").and_then(shell_command) {
+                feed(
+                    transcript,
+                    "item/started",
+                    json!({ "item": { "type": "commandExecution", "command": command } }),
+                );
+            }
+        }
+        Some("custom_tool_call") => {
+            let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("Tool");
+            let input = item.get("input").and_then(|i| i.as_str()).unwrap_or("");
+            if name == "apply_patch" {
+                feed(
+                    transcript,
+                    "item/started",
+                    json!({ "item": {
+                        "type": "fileChange",
+                        "diff": input,
+                        "filePath": patch_file_path(input),
+                    } }),
+                );
+            } else {
+                feed(
+                    transcript,
+                    "item/started",
+                    json!({ "item": {
+                        "type": "mcpToolCall",
+                        "toolName": name,
+                        "arguments": input,
+                    } }),
+                );
+            }
+        }
+        Some("function_call_output") => {
+            let output = item.get("output").map(tool_output).unwrap_or_default();
+            if !output.is_empty() {
+                feed(
+                    transcript,
+                    "item/commandExecution/outputDelta",
+                    json!({ "delta": output }),
+                );
+            }
+            feed(
+                transcript,
+                "item/completed",
+                json!({ "item": { "type": "commandExecution" } }),
+            );
+        }
+        _ => {}
+    }
+}
+
+fn is_shell_tool(name: &str) -> bool {
+    matches!(
+        name,
+        "shell" | "shell_command" | "exec_command" | "container.exec" | "local_shell"
+    )
+}
+
+/// The command of a shell call: `command` as an argv array or a string, or
+/// `cmd`.
+fn shell_command(arguments: &Value) -> Option<String> {
+    match arguments.get("command").or_else(|| arguments.get("cmd"))? {
+        Value::String(command) => Some(command.clone()),
+        Value::Array(argv) => {
+            let argv: Vec<&str> = argv.iter().filter_map(|a| a.as_str()).collect();
+            shlex::try_join(argv).ok()
+        }
+        _ => None,
+    }
+}
+
+/// A tool output is either plain text or JSON with an `output` field.
+fn tool_output(output: &Value) -> String {
+    let text = match output {
+        Value::String(text) => text.clone(),
+        other => return other.to_string(),
+    };
+    serde_json::from_str::<Value>(&text)
+        .ok()
+        .and_then(|v| v.get("output").and_then(|o| o.as_str()).map(str::to_string))
+        .unwrap_or(text)
+}
+
+/// The first file an `apply_patch` input touches.
+fn patch_file_path(patch: &str) -> String {
+    patch
+        .lines()
+        .find_map(|line| {
+            ["*** Update File: ", "*** Add File: ", "*** Delete File: "]
+                .iter()
+                .find_map(|prefix| line.strip_prefix(prefix))
+        })
+        .unwrap_or("")
+        .trim()
+        .to_string()
+}
+
+/// The `<cwd>` of an environment context message.
+fn environment_cwd(text: &str) -> Option<String> {
+    let start = text.find("<cwd>")? + "<cwd>".len();
+    let end = start + text[start..].find("</cwd>")?;
+    Some(text[start..end].trim().to_string()).filter(|cwd| !cwd.is_empty())
+}
+
+fn str_field(value: &Value, name: &str) -> Option<String> {
+    value
+        .get(name)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+// ============================================================================
+// DISCOVERY AND IMPORT
+// ============================================================================
+
+/// A transcript found on disk, and where it would be imported.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptCandidate {
+    pub source: TranscriptSource,
+    pub path: String,
+    pub session_id: Option<String>,
+    pub cwd: Option<String>,
+    pub label: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub prompt_count: usize,
+    /// The matching workspace; all three are absent when none matches.
+    pub project_name: Option<String>,
+    pub workspace_name: Option<String>,
+    pub workspace_id: Option<String>,
+    /// A chat in that workspace already has this session id.
+    pub already_imported: bool,
+}
+
+/// A chat created by [`import_transcripts`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedChat {
+    pub path: String,
+    pub chat_id: String,
+    pub project_name: String,
+    pub workspace_name: String,
+}
+
+/// A transcript [`import_transcripts`] left alone, and why.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedTranscript {
+    pub path: String,
+    pub reason: String,
+}
+
+/// The outcome of [`import_transcripts`].
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub imported: Vec<ImportedChat>,
+    pub skipped: Vec<SkippedTranscript>,
+}
+
+/// A registered workspace a transcript can be attached to.
+struct WorkspaceTarget {
+    project_name: String,
+    workspace_name: String,
+    workspace_id: String,
+    path: PathBuf,
+}
+
+/// List the transcripts under `roots`, newest first, with the workspace each
+/// would be imported into.
+pub fn find_transcripts(
+    config_dir: &Path,
+    roots: &TranscriptRoots,
+) -> Result<Vec<TranscriptCandidate>, ImportError> {
+    let targets = workspace_targets(config_dir)?;
+    let mut candidates = Vec::new();
+    for (source, path) in transcript_files(roots) {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                log::warn!("Failed to read transcript {}: {e}", path.display());
+                continue;
+            }
+        };
+        let transcript = parse(source, &contents);
+        if transcript.prompt_count() == 0 {
+            continue;
+        }
+        candidates.push(candidate(config_dir, &targets, &path, &transcript));
+    }
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.started_at));
+    Ok(candidates)
+}
+
+/// Import transcripts under `roots` into their workspaces' chats.
+///
+/// With `only`, imports just those transcript paths. Transcripts without a
+/// matching workspace, without prompts, or already imported are skipped.
+pub fn import_transcripts(
+    config_dir: &Path,
+    roots: &TranscriptRoots,
+    only: Option<&[String]>,
+) -> Result<ImportReport, ImportError> {
+    let targets = workspace_targets(config_dir)?;
+    let mut report = ImportReport::default();
+
+    for (source, path) in transcript_files(roots) {
+        let display = path.to_string_lossy().to_string();
+        if only.is_some_and(|only| !only.contains(&display)) {
+            continue;
+        }
+        let skip = |report: &mut ImportReport, reason: String| {
+            report.skipped.push(SkippedTranscript {
+                path: display.clone(),
+                reason,
+            })
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                skip(&mut report, format!("Failed to read: {e}"));
+                continue;
+            }
+        };
+        let transcript = parse(source, &contents);
+        if transcript.prompt_count() == 0 {
+            skip(&mut report, "No messages".to_string());
+            continue;
+        }
+        let found = candidate(config_dir, &targets, &path, &transcript);
+        let (Some(project_name), Some(workspace_name), Some(workspace_id)) =
+            (found.project_name, found.workspace_name, found.workspace_id)
+        else {
+            let cwd = found.cwd.unwrap_or_else(|| "unknown".to_string());
+            skip(&mut report, format!("No workspace contains {cwd}"));
+            continue;
+        };
+        if found.already_imported {
+            skip(&mut report, "Already imported".to_string());
+            continue;
+        }
+
+        let chat_dir = config_dir
+            .join("chats")
+            .join(&project_name)
+            .join(&workspace_name);
+        let chat_id = write_chat(&chat_dir, &workspace_id, transcript)?;
+        report.imported.push(ImportedChat {
+            path: display,
+            chat_id,
+            project_name,
+            workspace_name,
+        });
+    }
+    Ok(report)
+}
+
+fn parse(source: TranscriptSource, contents: &str) -> ParsedTranscript {
+    match source {
+        TranscriptSource::Claude => parse_claude_transcript(contents),
+        TranscriptSource::Codex => parse_codex_rollout(contents),
+    }
+}
+
+fn candidate(
+    config_dir: &Path,
+    targets: &[WorkspaceTarget],
+    path: &Path,
+    transcript: &ParsedTranscript,
+) -> TranscriptCandidate {
+    let target = transcript
+        .cwd
+        .as_deref()
+        .and_then(|cwd| match_workspace(targets, Path::new(cwd)));
+    let already_imported = match (target, transcript.session_id.as_deref()) {
+        (Some(target), Some(session_id)) => has_session(
+            &config_dir
+                .join("chats")
+                .join(&target.project_name)
+                .join(&target.workspace_name),
+            session_id,
+        ),
+        _ => false,
+    };
+    TranscriptCandidate {
+        source: transcript.source,
+        path: path.to_string_lossy().to_string(),
+        session_id: transcript.session_id.clone(),
+        cwd: transcript.cwd.clone(),
+        label: transcript.label(),
+        started_at: transcript.started_at,
+        prompt_count: transcript.prompt_count(),
+        project_name: target.map(|t| t.project_name.clone()),
+        workspace_name: target.map(|t| t.workspace_name.clone()),
+        workspace_id: target.map(|t| t.workspace_id.clone()),
+        already_imported,
+    }
+}
+
+/// Every live workspace, with the name of its chat directory.
+fn workspace_targets(config_dir: &Path) -> Result<Vec<WorkspaceTarget>, ImportError> {
+    let registry = load_project_registry(config_dir)?;
+    let mut targets = Vec::new();
+    for project in &registry.projects {
+        for workspace in project.get_workspaces() {
+            if workspace.is_archived {
+                continue;
+            }
+            let path = PathBuf::from(&workspace.path);
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            targets.push(WorkspaceTarget {
+                project_name: project.name.clone(),
+                workspace_name: name.to_string(),
+                workspace_id: workspace.id.clone(),
+                path,
+            });
+        }
+    }
+    Ok(targets)
+}
+
+/// The deepest workspace containing `cwd`.
+fn match_workspace<'a>(targets: &'a [WorkspaceTarget], cwd: &Path) -> Option<&'a WorkspaceTarget> {
+    targets
+        .iter()
+        .filter(|t| cwd.starts_with(&t.path))
+        .max_by_key(|t| t.path.components().count())
+}
+
+/// True if a chat in `chat_dir` already carries `session_id`.
+fn has_session(chat_dir: &Path, session_id: &str) -> bool {
+    let Ok(entries) = fs::read_dir(chat_dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let name = entry.file_name().to_string_lossy().to_string();
+        name.strip_suffix(".meta.json")
+            .and_then(|chat_id| load_chat_metadata(chat_dir, chat_id).ok())
+            .is_some_and(|meta| meta.agent_session_id.as_deref() == Some(session_id))
+    })
+}
+
+/// Transcript files under `roots`, Claude's then Codex's.
+fn transcript_files(roots: &TranscriptRoots) -> Vec<(TranscriptSource, PathBuf)> {
+    let mut files = Vec::new();
+    if let Some(claude) = &roots.claude {
+        // projects/<encoded cwd>/<session>.jsonl; subagent transcripts live
+        // deeper and are skipped.
+        for project in sorted_entries(&claude.join("projects")) {
+            for file in sorted_entries(&project) {
+                if file.extension().is_some_and(|e| e == "jsonl") && file.is_file() {
+                    files.push((TranscriptSource::Claude, file));
+                }
+            }
+        }
+    }
+    if let Some(codex) = &roots.codex {
+        let mut stack = vec![codex.join("sessions")];
+        let mut rollouts = Vec::new();
+        while let Some(dir) = stack.pop() {
+            for entry in sorted_entries(&dir) {
+                if entry.is_dir() {
+                    stack.push(entry);
+                } else if entry
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("rollout-") && n.ends_with(".jsonl"))
+                {
+                    rollouts.push(entry);
+                }
+            }
+        }
+        rollouts.sort();
+        files.extend(rollouts.into_iter().map(|p| (TranscriptSource::Codex, p)));
+    }
+    files
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// Write a transcript as a new chat in `chat_dir` and list it in the chat
+/// index. Returns the chat id.
+fn write_chat(
+    chat_dir: &Path,
+    workspace_id: &str,
+    transcript: ParsedTranscript,
+) -> Result<String, ImportError> {
+    let now = Utc::now();
+    let created_at = transcript.started_at.unwrap_or(now);
+    let metadata = ChatMetadata {
+        id: uuid::Uuid::new_v4().to_string(),
+        workspace_id: workspace_id.to_string(),
+        label: transcript.label(),
+        agent_type: Some(transcript.source.agent_type().to_string()),
+        agent_session_id: transcript.session_id.clone(),
+        model_version: transcript.model.clone(),
+        permission_mode: None,
+        sandboxed: false,
+        created_at,
+        updated_at: transcript.ended_at.unwrap_or(created_at),
+    };
+
+    save_chat_events(chat_dir, &metadata.id, &transcript.events)?;
+    save_chat_metadata(chat_dir, &metadata)?;
+
+    let entry = ChatIndexEntry {
+        id: metadata.id.clone(),
+        label: metadata.label.clone(),
+        agent_type: metadata.agent_type.clone(),
+        created_at: metadata.created_at,
+        updated_at: metadata.updated_at,
+        is_archived: None,
+        archived_at: None,
+    };
+    update_chat_index(chat_dir, |index| upsert_chat_entry(index, entry.clone()))?;
+
+    Ok(metadata.id)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::chat_jsonl::load_chat_events;
+    use crate::persistence::index::load_chat_index;
+    use crate::persistence::projects::save_project_registry;
+    use crate::persistence::types::ProjectRegistry;
+    use tempfile::tempdir;
+
+    const CLAUDE: &str = r#"{"type":"summary","summary":"Fix the flaky test","leafUuid":"u3"}
+{"type":"user","sessionId":"claude-1","cwd":"/work/otter/sub","timestamp":"2026-01-02T10:00:00Z","message":{"role":"user","content":"Why is the test flaky?"}}
+{"type":"user","isMeta":true,"sessionId":"claude-1","timestamp":"2026-01-02T10:00:00Z","message":{"role":"user","content":[{"type":"text","text":"Caveat: ignore"}]}}
+{"type":"assistant","sessionId":"claude-1","timestamp":"2026-01-02T10:00:05Z","message":{"role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"thinking","thinking":"Look at timing."}]}}
+{"type":"assistant","sessionId":"claude-1","timestamp":"2026-01-02T10:00:06Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test"}}]}}
+{"type":"user","sessionId":"claude-1","timestamp":"2026-01-02T10:00:09Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}
+{"type":"assistant","isSidechain":true,"sessionId":"claude-1","message":{"role":"assistant","content":[{"type":"text","text":"subagent"}]}}
+{"type":"assistant","sessionId":"claude-1","timestamp":"2026-01-02T10:00:10Z","message":{"role":"assistant","content":[{"type":"text","text":"It races the timer."}]}}
+{"type":"user","sessionId":"claude-1","timestamp":"2026-01-02T10:01:00Z","message":{"role":"user","content":"Fix it"}}
+"#;
+
+    const CODEX: &str = r#"{"timestamp":"2026-01-03T09:00:00Z","type":"session_meta","payload":{"id":"thread-9","cwd":"/work/otter","timestamp":"2026-01-03T09:00:00Z"}}
+{"timestamp":"2026-01-03T09:00:00Z","type":"turn_context","payload":{"cwd":"/work/otter","model":"gpt-5-codex"}}
+{"timestamp":"2026-01-03T09:00:00Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"<environment_context>\n  <cwd>/work/otter</cwd>\n</environment_context>"}]}}
+{"timestamp":"2026-01-03T09:00:01Z","type":"response_item","payload":{"type":"message","role":"user","content":[{"type":"input_text","text":"List the files"}]}}
+{"timestamp":"2026-01-03T09:00:02Z","type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{\"command\":[\"bash\",\"-lc\",\"ls -a\"]}","call_id":"c1"}}
+{"timestamp":"2026-01-03T09:00:03Z","type":"response_item","payload":{"type":"function_call_output","call_id":"c1","output":"{\"output\":\"README.md\\n\",\"metadata\":{\"exit_code\":0}}"}}
+{"timestamp":"2026-01-03T09:00:04Z","type":"response_item","payload":{"type":"custom_tool_call","name":"apply_patch","call_id":"c2","input":"*** Begin Patch\n*** Update File: src/lib.rs\n@@\n-a\n+b\n*** End Patch"}}
+{"timestamp":"2026-01-03T09:00:05Z","type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"Just README.md."}]}}
+{"timestamp":"2026-01-03T09:00:05Z","type":"event_msg","payload":{"type":"task_complete"}}
+"#;
+
+    fn contents(events: &[AgentEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|e| match e {
+                AgentEvent::UserMessage { content, .. } => format!("user: {content}"),
+                AgentEvent::Message { content, .. } => format!("message: {content}"),
+                AgentEvent::BashOutput { text } => format!("output: {text}"),
+                AgentEvent::TurnComplete => "turn complete".to_string(),
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn claude_transcript_runs_through_the_parser() {
+        let transcript = parse_claude_transcript(CLAUDE);
+        assert_eq!(transcript.session_id.as_deref(), Some("claude-1"));
+        assert_eq!(transcript.cwd.as_deref(), Some("/work/otter/sub"));
+        assert_eq!(transcript.model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(transcript.label(), "Fix the flaky test");
+        assert_eq!(
+            contents(&transcript.events),
+            vec![
+                "user: Why is the test flaky?",
+                "message: Look at timing.",
+                "message: [Bash]\n{\n  \"command\": \"cargo test\"\n}",
+                "message: It races the timer.",
+                "turn complete",
+                "user: Fix it",
+                "turn complete",
+            ]
+        );
+        let AgentEvent::UserMessage { timestamp, .. } = &transcript.events[0] else {
+            panic!("expected user message");
+        };
+        assert_eq!(timestamp.to_rfc3339(), "2026-01-02T10:00:00+00:00");
+    }
+
+    #[test]
+    fn codex_rollout_is_restated_as_notifications() {
+        let transcript = parse_codex_rollout(CODEX);
+        assert_eq!(transcript.session_id.as_deref(), Some("thread-9"));
+        assert_eq!(transcript.cwd.as_deref(), Some("/work/otter"));
+        assert_eq!(transcript.model.as_deref(), Some("gpt-5-codex"));
+        assert_eq!(transcript.label(), "List the files");
+        let events = contents(&transcript.events);
+        assert_eq!(events[0], "user: List the files");
+        assert_eq!(events[1], "message: [Bash]\n{\n  \"command\": \"ls -a\"\n}");
+        assert_eq!(events[2], "output: README.md\n");
+        assert!(events[3].starts_with("message: [Edit]"));
+        assert!(events[3].contains("src/lib.rs"));
+        assert_eq!(events[4], "message: Just README.md.");
+        assert_eq!(events[5], "turn complete");
+        assert_eq!(events.len(), 6);
+    }
+
+    #[test]
+    fn label_falls_back_to_first_prompt() {
+        let mut transcript = ParsedTranscript::new(TranscriptSource::Codex);
+        transcript.push_user(&"word ".repeat(30), None);
+        let label = transcript.label();
+        assert!(label.ends_with('…'));
+        assert!(label.chars().count() <= LABEL_CHARS + 1);
+    }
+
+    #[test]
+    fn imports_into_the_workspace_containing_the_cwd() {
+        let config = tempdir().unwrap();
+        let home = tempdir().unwrap();
+        let claude_dir = home.path().join(".claude/projects/-work-otter-sub");
+        fs::create_dir_all(&claude_dir).unwrap();
+        let codex_dir = home.path().join(".codex/sessions/2026/01/03");
+        fs::create_dir_all(&codex_dir).unwrap();
+
+        let workspace = config.path().join("workspaces/otter");
+        let claude = CLAUDE.replace("/work/otter", &workspace.to_string_lossy());
+        fs::write(claude_dir.join("claude-1.jsonl"), claude).unwrap();
+        // No workspace contains /work/otter.
+        fs::write(codex_dir.join("rollout-2026-01-03-thread-9.jsonl"), CODEX).unwrap();
+
+        let registry: ProjectRegistry = serde_json::from_value(json!({
+            "projects": [{
+                "id": "p1",
+                "name": "app",
+                "path": "/repo",
+                "isGitRepo": true,
+                "workspaces": [{
+                    "id": "w1",
+                    "branch": "main",
+                    "path": workspace.to_string_lossy(),
+                    "isArchived": false,
+                    "createdAt": "2026-01-01T00:00:00Z"
+                }]
+            }]
+        }))
+        .unwrap();
+        save_project_registry(config.path(), &registry, None).unwrap();
+
+        let roots = TranscriptRoots {
+            claude: Some(home.path().join(".claude")),
+            codex: Some(home.path().join(".codex")),
+        };
+        let found = find_transcripts(config.path(), &roots).unwrap();
+        assert_eq!(found.len(), 2);
+        let claude_found = found
+            .iter()
+            .find(|c| c.source == TranscriptSource::Claude)
+            .unwrap();
+        assert_eq!(claude_found.workspace_id.as_deref(), Some("w1"));
+        assert!(found
+            .iter()
+            .any(|c| c.source == TranscriptSource::Codex && c.workspace_id.is_none()));
+
+        let report = import_transcripts(config.path(), &roots, None).unwrap();
+        assert_eq!(report.imported.len(), 1);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].reason.starts_with("No workspace"));
+
+        let imported = &report.imported[0];
+        assert_eq!(imported.project_name, "app");
+        assert_eq!(imported.workspace_name, "otter");
+        let chat_dir = config.path().join("chats/app/otter");
+        let metadata = load_chat_metadata(&chat_dir, &imported.chat_id).unwrap();
+        assert_eq!(metadata.agent_session_id.as_deref(), Some("claude-1"));
+        assert_eq!(metadata.agent_type.as_deref(), Some("claude"));
+        assert_eq!(metadata.workspace_id, "w1");
+        assert_eq!(
+            load_chat_events(&chat_dir, &imported.chat_id)
+                .unwrap()
+                .len(),
+            7
+        );
+        assert_eq!(load_chat_index(&chat_dir).unwrap().chats.len(), 1);
+
+        // Importing again finds the session already there.
+        let again = import_transcripts(config.path(), &roots, None).unwrap();
+        assert!(again.imported.is_empty());
+        assert!(again.skipped.iter().any(|s| s.reason == "Already imported"));
+    }
+}
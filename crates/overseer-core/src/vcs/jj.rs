@@ -0,0 +1,1048 @@
+//! Jujutsu workspaces, diffs and merges.
+//!
+//! # Overview
+//!
+//! The [`Vcs::Jujutsu`](super::Vcs::Jujutsu) backend. Like [`crate::git`],
+//! it wraps the CLI (`jj` 0.22 or later, for bookmarks) so that the user's
+//! configuration applies.
+//!
+//! # Workspaces
+//!
+//! A workspace is a `jj workspace add` checkout whose working-copy commit
+//! starts out under a bookmark named after the branch. jj snapshots the
+//! working copy on every command, so nothing is ever uncommitted: the
+//! workspace's changes are the commits from where it forked off the default
+//! branch up to `@`. Only the project checkout (the default workspace)
+//! reports its working-copy commit as uncommitted changes.
+//!
+//! # Default Branch
+//!
+//! The default branch is the project's `mainBranch` bookmark, or else
+//! `main`, `master` or jj's `trunk()`.
+//!
+//! # Merging
+//!
+//! Merging moves the default bookmark: to the workspace's newest non-empty
+//! commit when that already descends from it, otherwise to a new merge
+//! commit (merge) or to the rebased commit (rebase). jj records conflicts
+//! in commits instead of stopping, so a conflicted result is reported and
+//! the operation that made it reverted (`jj op revert`, `jj op undo` on
+//! older jj). Only that operation is reverted: whatever else ran in the
+//! repository meanwhile stays. Checks do the same work without touching the
+//! working copy and always revert it. Squash merges aren't supported.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::time::{timeout, Duration};
+
+use crate::git::diff::{ChangedFile, ChangedFilesResult, Commit, DiffOptions, WhitespaceMode};
+use crate::git::merge::{nothing_to_merge, MergeOptions, MergeResult, MergeStrategy};
+use crate::git::patch::{parse_unified_diff, DiffLineKind, FileDiff};
+use crate::git::worktree::pick_workspace_dir;
+use crate::git::{GitError, GitOutput, WorkspaceLayout};
+
+const JJ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `jj log` template for [`parse_commit_log`]: tab-separated fields, one
+/// commit per line, with the description JSON-escaped since it may contain
+/// anything.
+const COMMIT_LOG_TEMPLATE: &str = r#"commit_id ++ "\t" ++ commit_id.short(7) ++ "\t" ++ author.name() ++ "\t" ++ author.email() ++ "\t" ++ author.timestamp().format("%Y-%m-%dT%H:%M:%S%:z") ++ "\t" ++ description.escape_json() ++ "\n""#;
+
+// ============================================================================
+// COMMON UTILITIES
+// ============================================================================
+
+/// Run a `jj` command in `cwd` and return the output.
+///
+/// Colors and the pager are turned off so the output can be parsed.
+///
+/// # Errors
+///
+/// Returns an error if `jj` can't be run or takes longer than 30 seconds.
+/// A non-zero exit is not an error; check [`GitOutput::success`].
+pub async fn run_jj(args: &[&str], cwd: &Path) -> Result<GitOutput, GitError> {
+    let fut = Command::new("jj")
+        .args(["--no-pager", "--color=never"])
+        .args(args)
+        .current_dir(cwd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output();
+
+    let output = timeout(JJ_TIMEOUT, fut)
+        .await
+        .map_err(|_| GitError::Other(format!("jj {} timed out after 30s", args.join(" "))))?
+        .map_err(GitError::CommandFailed)?;
+
+    Ok(GitOutput {
+        success: output.status.success(),
+        stdout: output.stdout,
+        stderr: output.stderr,
+    })
+}
+
+/// Like [`run_jj`], returning stdout, or an error with jj's stderr if the
+/// command failed.
+async fn run_jj_success(args: &[&str], cwd: &Path) -> Result<String, GitError> {
+    let output = run_jj(args, cwd).await?;
+    if output.success {
+        Ok(output.stdout_str())
+    } else {
+        Err(GitError::Other(format!(
+            "jj {} failed: {}",
+            args.first().unwrap_or(&""),
+            output.stderr_str().trim()
+        )))
+    }
+}
+
+/// A revset string literal for `name`, e.g. a bookmark with a `/` in it.
+fn quote(name: &str) -> String {
+    serde_json::to_string(name).unwrap_or_default()
+}
+
+/// Revset for the default branch.
+fn trunk_revset(main_branch: Option<&str>) -> String {
+    match main_branch.filter(|name| !name.is_empty()) {
+        Some(name) => quote(name),
+        None => "latest(present(main) | present(master) | trunk())".to_string(),
+    }
+}
+
+/// Revset for where `@` forked off the default branch.
+fn fork_point_revset(trunk: &str) -> String {
+    format!("heads(::@ & ::({trunk}))")
+}
+
+/// Revset for the newest non-empty commit between the default branch and
+/// `@`: what lands on the default branch.
+fn head_revset(trunk: &str) -> String {
+    format!("latest((({trunk})..@) ~ empty())")
+}
+
+/// The full id of the newest commit in `revset`, or `None` if it's empty.
+async fn first_commit(revset: &str, cwd: &Path) -> Result<Option<String>, GitError> {
+    let stdout = run_jj_success(
+        &[
+            "log",
+            "--no-graph",
+            "-n",
+            "1",
+            "-r",
+            revset,
+            "-T",
+            "commit_id",
+        ],
+        cwd,
+    )
+    .await?;
+    let id = stdout.trim();
+    Ok((!id.is_empty()).then(|| id.to_string()))
+}
+
+/// Whether `workspace_path` is the project checkout rather than a workspace
+/// added with `jj workspace add`. Only the former holds the repository
+/// itself in `.jj/repo`; added workspaces have a file pointing to it.
+fn is_default_workspace(workspace_path: &Path) -> bool {
+    workspace_path.join(".jj").join("repo").is_dir()
+}
+
+/// Extra `jj diff` arguments for `options`.
+///
+/// jj has no end-of-line-only mode, so [`WhitespaceMode::IgnoreAtEol`]
+/// ignores changes in the amount of whitespace anywhere on the line.
+fn diff_args(options: &DiffOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    match options.whitespace {
+        WhitespaceMode::Show => {}
+        WhitespaceMode::IgnoreAtEol | WhitespaceMode::IgnoreChange => {
+            args.push("--ignore-space-change".to_string())
+        }
+        WhitespaceMode::IgnoreAll => args.push("--ignore-all-space".to_string()),
+    }
+    if let Some(lines) = options.context_lines {
+        args.push(format!("--context={lines}"));
+    }
+    args
+}
+
+/// Run `jj diff --git` with `range` (e.g. `-r @`), limited to `file_path`
+/// if given, and return the diff text.
+async fn diff_text(
+    workspace_path: &Path,
+    range: &[&str],
+    file_path: Option<&str>,
+    options: &DiffOptions,
+) -> Result<String, GitError> {
+    let extra = diff_args(options);
+    let fileset = file_path.map(|path| format!("root-file:{}", quote(path)));
+
+    let mut args = vec!["diff", "--git"];
+    args.extend(range);
+    args.extend(extra.iter().map(String::as_str));
+    if let Some(fileset) = &fileset {
+        args.extend(["--", fileset]);
+    }
+    run_jj_success(&args, workspace_path).await
+}
+
+/// Changed files, with line counts, from a parsed diff.
+fn changed_files(diffs: &[FileDiff]) -> Vec<ChangedFile> {
+    diffs
+        .iter()
+        .map(|diff| {
+            let count = |kind: DiffLineKind| {
+                let lines = diff.hunks.iter().flat_map(|h| &h.lines);
+                lines.filter(|line| line.kind == kind).count() as u64
+            };
+            let renamed = diff.status == "R" || diff.status == "C";
+            ChangedFile {
+                status: diff.status.clone(),
+                path: diff.path().to_string(),
+                old_path: if renamed { diff.old_path.clone() } else { None },
+                additions: (!diff.is_binary).then(|| count(DiffLineKind::Added)),
+                deletions: (!diff.is_binary).then(|| count(DiffLineKind::Removed)),
+                is_binary: diff.is_binary,
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// WORKSPACES
+// ============================================================================
+
+/// Create a workspace for `branch` with `jj workspace add`.
+///
+/// The directory is picked like a worktree's (see [`pick_workspace_dir`]).
+/// An existing `branch` bookmark is continued from; otherwise the workspace
+/// starts on the default branch and a new bookmark is created at its
+/// working-copy commit.
+///
+/// # Returns
+///
+/// The absolute path to the new workspace.
+pub async fn add_workspace(
+    repo_path: &Path,
+    branch: &str,
+    layout: &WorkspaceLayout,
+) -> Result<PathBuf, GitError> {
+    let workspace_path = pick_workspace_dir(repo_path, branch, layout)?;
+    let name = workspace_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| branch.to_string());
+    let workspace_str = workspace_path.to_string_lossy();
+
+    let bookmark = quote(branch);
+    let existing = first_commit(&format!("present({bookmark})"), repo_path)
+        .await?
+        .is_some();
+    let start = if existing {
+        bookmark
+    } else {
+        trunk_revset(None)
+    };
+
+    run_jj_success(
+        &[
+            "workspace",
+            "add",
+            "--name",
+            &name,
+            "-r",
+            &start,
+            &workspace_str,
+        ],
+        repo_path,
+    )
+    .await?;
+
+    let action = if existing { "set" } else { "create" };
+    run_jj_success(&["bookmark", action, branch, "-r", "@"], &workspace_path).await?;
+
+    std::fs::canonicalize(&workspace_path)
+        .map_err(|e| GitError::PathError(format!("Failed to resolve path: {e}")))
+}
+
+/// Forget a workspace and delete its directory.
+///
+/// Its commits stay in the repository, so unlike a git worktree there is
+/// nothing to lose and no dirtiness check.
+pub async fn archive_workspace(workspace_path: &Path) -> Result<(), GitError> {
+    if workspace_path.exists() {
+        run_jj_success(&["workspace", "forget"], workspace_path).await?;
+        std::fs::remove_dir_all(workspace_path)
+            .map_err(|e| GitError::PathError(format!("Failed to remove workspace: {e}")))?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// CHANGES
+// ============================================================================
+
+/// Files changed in a workspace since it forked off the default branch.
+///
+/// In the project checkout, the working-copy commit's files are reported as
+/// uncommitted and `is_default_branch` is set.
+pub async fn list_changed_files(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+) -> Result<ChangedFilesResult, GitError> {
+    let options = DiffOptions::default();
+    if is_default_workspace(workspace_path) {
+        let text = diff_text(workspace_path, &["-r", "@"], None, &options).await?;
+        return Ok(ChangedFilesResult {
+            files: Vec::new(),
+            uncommitted: changed_files(&parse_unified_diff(&text)),
+            is_default_branch: true,
+            submodules: Vec::new(),
+        });
+    }
+
+    let from = fork_point_revset(&trunk_revset(main_branch));
+    let text = diff_text(
+        workspace_path,
+        &["--from", &from, "--to", "@"],
+        None,
+        &options,
+    )
+    .await?;
+    Ok(ChangedFilesResult {
+        files: changed_files(&parse_unified_diff(&text)),
+        uncommitted: Vec::new(),
+        is_default_branch: false,
+        submodules: Vec::new(),
+    })
+}
+
+/// Diff text for one file listed by [`list_changed_files`].
+pub async fn get_file_diff(
+    workspace_path: &Path,
+    file_path: &str,
+    main_branch: Option<&str>,
+    options: &DiffOptions,
+) -> Result<String, GitError> {
+    if is_default_workspace(workspace_path) {
+        return get_uncommitted_diff(workspace_path, file_path, options).await;
+    }
+    let from = fork_point_revset(&trunk_revset(main_branch));
+    diff_text(
+        workspace_path,
+        &["--from", &from, "--to", "@"],
+        Some(file_path),
+        options,
+    )
+    .await
+}
+
+/// Diff text for one file in the working-copy commit.
+pub async fn get_uncommitted_diff(
+    workspace_path: &Path,
+    file_path: &str,
+    options: &DiffOptions,
+) -> Result<String, GitError> {
+    diff_text(workspace_path, &["-r", "@"], Some(file_path), options).await
+}
+
+// ============================================================================
+// COMMITS
+// ============================================================================
+
+/// Non-empty commits between the default branch and `@`, oldest first.
+pub async fn list_commits_on_branch(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+) -> Result<Vec<Commit>, GitError> {
+    if is_default_workspace(workspace_path) {
+        return Ok(Vec::new());
+    }
+    let revset = format!("({}..@) ~ empty()", trunk_revset(main_branch));
+    let stdout = run_jj_success(
+        &[
+            "log",
+            "--no-graph",
+            "--reversed",
+            "-r",
+            &revset,
+            "-T",
+            COMMIT_LOG_TEMPLATE,
+        ],
+        workspace_path,
+    )
+    .await?;
+    Ok(parse_commit_log(&stdout))
+}
+
+/// Parse the output of `jj log` with [`COMMIT_LOG_TEMPLATE`].
+///
+/// Each line is:
+/// ```text
+/// id\tshort id\tauthor name\tauthor email\tdate\t"description as JSON"
+/// ```
+///
+/// A commit without a description gets jj's `(no description set)`.
+fn parse_commit_log(stdout: &str) -> Vec<Commit> {
+    let mut commits = Vec::new();
+
+    for line in stdout.lines() {
+        let fields: Vec<&str> = line.splitn(6, '\t').collect();
+        let [id, short_id, author_name, author_email, date, description] = fields[..] else {
+            continue;
+        };
+        let description: String = serde_json::from_str(description).unwrap_or_default();
+        let full_message = description.trim_end().to_string();
+        let message = match full_message.lines().next() {
+            Some(subject) if !subject.is_empty() => subject.to_string(),
+            _ => "(no description set)".to_string(),
+        };
+        commits.push(Commit {
+            short_id: short_id.to_string(),
+            id: id.to_string(),
+            message,
+            full_message,
+            author_name: author_name.to_string(),
+            author_email: author_email.to_string(),
+            date: date.to_string(),
+        });
+    }
+
+    commits
+}
+
+/// Files changed in one commit.
+pub async fn list_commit_files(
+    workspace_path: &Path,
+    commit_sha: &str,
+) -> Result<Vec<ChangedFile>, GitError> {
+    let text = diff_text(
+        workspace_path,
+        &["-r", commit_sha],
+        None,
+        &DiffOptions::default(),
+    )
+    .await?;
+    Ok(changed_files(&parse_unified_diff(&text)))
+}
+
+/// Diff text for one file in one commit.
+pub async fn get_commit_diff(
+    workspace_path: &Path,
+    commit_sha: &str,
+    file_path: &str,
+    options: &DiffOptions,
+) -> Result<String, GitError> {
+    diff_text(
+        workspace_path,
+        &["-r", commit_sha],
+        Some(file_path),
+        options,
+    )
+    .await
+}
+
+// ============================================================================
+// MERGING
+// ============================================================================
+
+/// What lands where, for [`check_merge`] and [`merge_into_main`].
+struct Landing {
+    /// Revset for the default branch
+    trunk: String,
+    /// The default bookmark's name
+    bookmark: String,
+    /// The commit that lands
+    head: String,
+    /// Name for the workspace in messages: its bookmark or directory name
+    branch: String,
+    /// The default branch is already an ancestor of `head`
+    fast_forward: bool,
+}
+
+/// Either the work to do, or a result that needs no work.
+enum Prepared {
+    Ready(Landing),
+    Done(MergeResult),
+}
+
+async fn prepare(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    strategy: MergeStrategy,
+) -> Result<Prepared, GitError> {
+    if strategy == MergeStrategy::Squash {
+        return Ok(Prepared::Done(MergeResult::new(
+            strategy,
+            false,
+            "Squash merges aren't supported for Jujutsu projects; use merge or rebase.",
+        )));
+    }
+    if is_default_workspace(workspace_path) {
+        return Ok(Prepared::Done(MergeResult::new(
+            strategy,
+            false,
+            "Already on the default branch, nothing to merge.",
+        )));
+    }
+
+    let trunk = trunk_revset(main_branch);
+    let bookmark = default_bookmark(workspace_path, main_branch).await?;
+    let branch = workspace_branch(workspace_path, &trunk).await?;
+    let Some(head) = first_commit(&head_revset(&trunk), workspace_path).await? else {
+        return Ok(Prepared::Done(nothing_to_merge(
+            strategy, &branch, &bookmark,
+        )));
+    };
+    let fast_forward = first_commit(&format!("({trunk}) & ::{head}"), workspace_path)
+        .await?
+        .is_some();
+
+    Ok(Prepared::Ready(Landing {
+        trunk,
+        bookmark,
+        head,
+        branch,
+        fast_forward,
+    }))
+}
+
+/// The name of the default bookmark.
+async fn default_bookmark(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+) -> Result<String, GitError> {
+    if let Some(name) = main_branch.filter(|name| !name.is_empty()) {
+        return Ok(name.to_string());
+    }
+    for name in ["main", "master"] {
+        if first_commit(&format!("present({name})"), workspace_path)
+            .await?
+            .is_some()
+        {
+            return Ok(name.to_string());
+        }
+    }
+    Err(GitError::Other(
+        "No main or master bookmark; set the project's default branch.".to_string(),
+    ))
+}
+
+/// The workspace's bookmark (the newest one past the default branch), or
+/// its directory name if it has none.
+async fn workspace_branch(workspace_path: &Path, trunk: &str) -> Result<String, GitError> {
+    let revset = format!("latest(({trunk})..@ & bookmarks())");
+    let stdout = run_jj_success(
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            &revset,
+            "-T",
+            r#"local_bookmarks.map(|b| b.name()).join(" ")"#,
+        ],
+        workspace_path,
+    )
+    .await?;
+    let fallback = || {
+        workspace_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    Ok(stdout
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+        .unwrap_or_else(fallback))
+}
+
+/// The id of the newest operation, read without snapshotting the working
+/// copy so that it's the one the previous command made.
+async fn current_operation(workspace_path: &Path) -> Result<String, GitError> {
+    let stdout = run_jj_success(
+        &[
+            "op",
+            "log",
+            "--no-graph",
+            "--ignore-working-copy",
+            "-n",
+            "1",
+            "-T",
+            "id",
+        ],
+        workspace_path,
+    )
+    .await?;
+    Ok(stdout.trim().to_string())
+}
+
+/// Revert `operation` alone, keeping the operations that came after it.
+async fn revert_operation(
+    workspace_path: &Path,
+    operation: &str,
+    extra: &[&str],
+) -> Result<(), GitError> {
+    let mut args = vec!["op", "revert", operation];
+    args.extend(extra);
+    if run_jj(&args, workspace_path).await?.success {
+        return Ok(());
+    }
+    // Older jj only has `op undo`, which takes the same arguments.
+    args[1] = "undo";
+    run_jj_success(&args, workspace_path).await.map(|_| ())
+}
+
+/// What [`land`] made.
+struct Landed {
+    /// The commit the default bookmark moves to
+    head: String,
+    /// The operation that made it; `None` for a fast-forward, which lands an
+    /// existing commit
+    operation: Option<String>,
+}
+
+impl Landed {
+    /// Revert the operation that made the commit, if any.
+    async fn undo(&self, workspace_path: &Path, extra: &[&str]) -> Result<(), GitError> {
+        match &self.operation {
+            Some(operation) => revert_operation(workspace_path, operation, extra).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Make the commit the default bookmark moves to.
+///
+/// `extra` is passed to the commands that rewrite history, e.g.
+/// `--ignore-working-copy` for a check. If the commit can't be found after
+/// all, the operation is reverted before returning the error.
+async fn land(
+    workspace_path: &Path,
+    landing: &Landing,
+    strategy: MergeStrategy,
+    extra: &[&str],
+) -> Result<Landed, GitError> {
+    if landing.fast_forward {
+        return Ok(Landed {
+            head: landing.head.clone(),
+            operation: None,
+        });
+    }
+    let Landing { trunk, head, .. } = landing;
+
+    let revset = if strategy == MergeStrategy::Rebase {
+        let mut args = vec!["rebase", "-b", head, "-d", trunk];
+        args.extend(extra);
+        run_jj_success(&args, workspace_path).await?;
+        head_revset(trunk)
+    } else {
+        let message = format!("Merge '{}'", landing.branch);
+        let mut args = vec!["new", "--no-edit", trunk, head, "-m", &message];
+        args.extend(extra);
+        run_jj_success(&args, workspace_path).await?;
+        format!("latest(children({trunk}) & children({head}) & merges())")
+    };
+    let operation = current_operation(workspace_path).await?;
+
+    let mut args = vec![
+        "log",
+        "--no-graph",
+        "-n",
+        "1",
+        "-r",
+        &revset,
+        "-T",
+        "commit_id",
+    ];
+    args.extend(extra);
+    let head = run_jj_success(&args, workspace_path)
+        .await
+        .and_then(|stdout| match stdout.trim() {
+            "" => Err(GitError::Other(
+                "jj didn't produce a commit to land".to_string(),
+            )),
+            id => Ok(id.to_string()),
+        });
+    match head {
+        Ok(head) => Ok(Landed {
+            head,
+            operation: Some(operation),
+        }),
+        Err(e) => {
+            revert_operation(workspace_path, &operation, extra).await?;
+            Err(e)
+        }
+    }
+}
+
+/// Files with conflicts in commits between the default branch and `head`.
+async fn conflicted_files(
+    workspace_path: &Path,
+    trunk: &str,
+    head: &str,
+    extra: &[&str],
+) -> Result<Vec<String>, GitError> {
+    let revset = format!("(({trunk})..{head}) & conflicts()");
+    let mut args = vec![
+        "log",
+        "--no-graph",
+        "-r",
+        &revset,
+        "-T",
+        r#"commit_id ++ "\n""#,
+    ];
+    args.extend(extra);
+    let commits = run_jj_success(&args, workspace_path).await?;
+
+    let mut files = BTreeSet::new();
+    for commit in commits.lines().filter(|l| !l.is_empty()) {
+        let mut args = vec!["resolve", "--list", "-r", commit];
+        args.extend(extra);
+        let stdout = run_jj_success(&args, workspace_path).await?;
+        files.extend(parse_resolve_list(&stdout));
+    }
+    Ok(files.into_iter().collect())
+}
+
+/// Paths from `jj resolve --list`, whose lines are the path, a run of
+/// spaces and a description such as `2-sided conflict`.
+fn parse_resolve_list(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter_map(|line| line.rsplit_once("  ").map(|(path, _)| path.trim_end()))
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Check whether the workspace would land on the default branch cleanly.
+///
+/// Non-trivial cases are tried for real without updating the working copy,
+/// then the operation that tried them is reverted.
+pub async fn check_merge(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    strategy: MergeStrategy,
+) -> Result<MergeResult, GitError> {
+    let landing = match prepare(workspace_path, main_branch, strategy).await? {
+        Prepared::Ready(landing) => landing,
+        Prepared::Done(result) => return Ok(result),
+    };
+    let Landing {
+        branch, bookmark, ..
+    } = &landing;
+
+    if landing.fast_forward {
+        return Ok(MergeResult::new(
+            strategy,
+            true,
+            format!("Clean fast-forward merge of '{branch}' into '{bookmark}'."),
+        ));
+    }
+
+    let extra = ["--ignore-working-copy"];
+    let landed = land(workspace_path, &landing, strategy, &extra).await?;
+    let conflicts = conflicted_files(workspace_path, &landing.trunk, &landed.head, &extra).await;
+    landed.undo(workspace_path, &extra).await?;
+    let conflicts = conflicts?;
+
+    let noun = strategy.noun();
+    if conflicts.is_empty() {
+        Ok(MergeResult::new(
+            strategy,
+            true,
+            format!("Clean {noun} of '{branch}' into '{bookmark}'."),
+        ))
+    } else {
+        Ok(MergeResult::conflicts(
+            strategy,
+            conflicts,
+            format!(
+                "The {noun} of '{branch}' into '{bookmark}' has conflicts that need resolution."
+            ),
+        ))
+    }
+}
+
+/// Land the workspace on the default branch and move its bookmark there,
+/// pushing it with `jj git push` if asked.
+///
+/// A result with conflicts is undone by reverting the operation that made it.
+pub async fn merge_into_main(
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    options: &MergeOptions,
+) -> Result<MergeResult, GitError> {
+    let strategy = options.strategy;
+    let landing = match prepare(workspace_path, main_branch, strategy).await? {
+        Prepared::Ready(landing) => landing,
+        Prepared::Done(result) => return Ok(result),
+    };
+    let Landing {
+        branch, bookmark, ..
+    } = &landing;
+
+    let landed = land(workspace_path, &landing, strategy, &[]).await?;
+    let head = match conflicted_files(workspace_path, &landing.trunk, &landed.head, &[]).await {
+        Ok(conflicts) if conflicts.is_empty() => landed.head,
+        Ok(conflicts) => {
+            landed.undo(workspace_path, &[]).await?;
+            return Ok(MergeResult::conflicts(
+                strategy,
+                conflicts,
+                format!(
+                    "The {} of '{branch}' into '{bookmark}' has conflicts; nothing was merged.",
+                    strategy.noun()
+                ),
+            ));
+        }
+        Err(e) => {
+            landed.undo(workspace_path, &[]).await?;
+            return Err(e);
+        }
+    };
+
+    run_jj_success(&["bookmark", "set", bookmark, "-r", &head], workspace_path).await?;
+
+    let message = match strategy {
+        MergeStrategy::Rebase if !landing.fast_forward => {
+            format!("Rebased '{branch}' and moved '{bookmark}' to it.")
+        }
+        _ => format!("Successfully merged '{branch}' into '{bookmark}'."),
+    };
+    let mut result = MergeResult::new(strategy, true, message);
+
+    if options.push {
+        match run_jj(&["git", "push", "-b", bookmark], workspace_path).await {
+            Ok(output) if output.success => {
+                result.pushed = true;
+                result.message = format!("{} Pushed '{bookmark}'.", result.message);
+            }
+            Ok(output) => {
+                result.message = format!(
+                    "{} Pushing '{bookmark}' failed: {}",
+                    result.message,
+                    output.stderr_str().trim()
+                );
+            }
+            Err(e) => {
+                result.message = format!("{} Pushing '{bookmark}' failed: {e}", result.message);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commit_log() {
+        let stdout = concat!(
+            "abc123\tabc\tAda\tada@example.com\t2026-01-02T03:04:05+00:00\t\"Add login\\n\\nWith a form.\\n\"\n",
+            "def456\tdef\tAda\tada@example.com\t2026-01-02T04:00:00+00:00\t\"\"\n",
+        );
+        let commits = parse_commit_log(stdout);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].id, "abc123");
+        assert_eq!(commits[0].short_id, "abc");
+        assert_eq!(commits[0].message, "Add login");
+        assert_eq!(commits[0].full_message, "Add login\n\nWith a form.");
+        assert_eq!(commits[0].date, "2026-01-02T03:04:05+00:00");
+        assert_eq!(commits[1].message, "(no description set)");
+        assert_eq!(commits[1].full_message, "");
+    }
+
+    #[test]
+    fn changed_files_from_git_style_diff() {
+        let text = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@
+-old
++new
++more
+ same
+diff --git a/notes.md b/notes.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/notes.md
+@@ -0,0 +1 @@
++hello
+";
+        let files = changed_files(&parse_unified_diff(text));
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].status, "M");
+        assert_eq!(files[0].path, "src/lib.rs");
+        assert_eq!(files[0].additions, Some(2));
+        assert_eq!(files[0].deletions, Some(1));
+        assert_eq!(files[1].status, "A");
+        assert_eq!(files[1].path, "notes.md");
+        assert_eq!(files[1].old_path, None);
+    }
+
+    #[test]
+    fn parses_resolve_list() {
+        let stdout =
+            "src/a b.rs    2-sided conflict\nREADME.md    2-sided conflict including 1 deletion\n";
+        assert_eq!(parse_resolve_list(stdout), vec!["src/a b.rs", "README.md"]);
+    }
+
+    #[test]
+    fn revsets_quote_configured_bookmarks() {
+        assert_eq!(trunk_revset(Some("release/1.0")), "\"release/1.0\"");
+        assert_eq!(
+            trunk_revset(Some("")),
+            "latest(present(main) | present(master) | trunk())"
+        );
+        assert_eq!(head_revset("\"main\""), "latest(((\"main\")..@) ~ empty())");
+    }
+
+    #[test]
+    fn whitespace_options_map_to_jj_flags() {
+        let options = DiffOptions {
+            whitespace: WhitespaceMode::IgnoreAll,
+            context_lines: Some(10),
+        };
+        assert_eq!(
+            diff_args(&options),
+            vec!["--ignore-all-space", "--context=10"]
+        );
+    }
+
+    // Integration tests against a real repository, skipped when `jj` isn't
+    // installed.
+
+    fn jj_installed() -> bool {
+        std::process::Command::new("jj")
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    async fn jj(args: &[&str], cwd: &Path) -> String {
+        run_jj_success(args, cwd).await.unwrap()
+    }
+
+    /// A repository with `README.md` committed under `main`, and a layout
+    /// putting workspaces next to it.
+    async fn repo() -> (tempfile::TempDir, PathBuf, WorkspaceLayout) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        jj(&["git", "init"], &repo).await;
+        jj(&["config", "set", "--repo", "user.name", "T"], &repo).await;
+        jj(&["config", "set", "--repo", "user.email", "t@t"], &repo).await;
+        std::fs::write(repo.join("README.md"), "hello\n").unwrap();
+        jj(&["commit", "-m", "init"], &repo).await;
+        jj(&["bookmark", "create", "main", "-r", "@-"], &repo).await;
+        let layout = WorkspaceLayout {
+            root: Some(dir.path().join("ws").to_string_lossy().to_string()),
+            naming: Some("{repo}/{branch}".to_string()),
+        };
+        (dir, repo, layout)
+    }
+
+    /// Commit everything in `cwd`'s working copy, with `file` set to
+    /// `contents`.
+    async fn commit(cwd: &Path, file: &str, contents: &str) {
+        std::fs::write(cwd.join(file), contents).unwrap();
+        jj(&["commit", "-m", &format!("Edit {file}")], cwd).await;
+    }
+
+    /// Every visible commit's id.
+    async fn all_commits(repo: &Path) -> String {
+        jj(
+            &[
+                "log",
+                "--no-graph",
+                "-r",
+                "all()",
+                "-T",
+                r#"commit_id ++ "\n""#,
+            ],
+            repo,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn adds_a_workspace_under_a_bookmark() {
+        if !jj_installed() {
+            return;
+        }
+        let (_dir, repo, layout) = repo().await;
+
+        let workspace = add_workspace(&repo, "feature", &layout).await.unwrap();
+
+        assert!(workspace.ends_with("repo/feature"));
+        assert_eq!(
+            std::fs::read_to_string(workspace.join("README.md")).unwrap(),
+            "hello\n"
+        );
+        assert!(!is_default_workspace(&workspace));
+        assert!(first_commit("present(feature)", &repo)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn merges_a_workspace_into_main() {
+        if !jj_installed() {
+            return;
+        }
+        let (_dir, repo, layout) = repo().await;
+        let workspace = add_workspace(&repo, "feature", &layout).await.unwrap();
+        commit(&workspace, "feature.txt", "new\n").await;
+        commit(&repo, "notes.txt", "meanwhile\n").await;
+        jj(&["bookmark", "set", "main", "-r", "@-"], &repo).await;
+
+        let check = check_merge(&workspace, None, MergeStrategy::Merge)
+            .await
+            .unwrap();
+        assert!(check.success, "{}", check.message);
+        let result = merge_into_main(&workspace, None, &MergeOptions::default())
+            .await
+            .unwrap();
+
+        assert!(result.success, "{}", result.message);
+        let files = jj(&["file", "list", "-r", "main"], &repo).await;
+        assert!(files.contains("feature.txt"), "{files}");
+        assert!(files.contains("notes.txt"), "{files}");
+    }
+
+    #[tokio::test]
+    async fn checking_a_conflicting_merge_leaves_the_repository_alone() {
+        if !jj_installed() {
+            return;
+        }
+        let (_dir, repo, layout) = repo().await;
+        let workspace = add_workspace(&repo, "feature", &layout).await.unwrap();
+        commit(&workspace, "README.md", "from the workspace\n").await;
+        commit(&repo, "README.md", "from main\n").await;
+        jj(&["bookmark", "set", "main", "-r", "@-"], &repo).await;
+        let before = all_commits(&repo).await;
+
+        let check = check_merge(&workspace, None, MergeStrategy::Merge)
+            .await
+            .unwrap();
+
+        assert!(!check.success);
+        assert_eq!(check.conflicts, vec!["README.md"]);
+        assert_eq!(all_commits(&repo).await, before);
+        let merged = merge_into_main(&workspace, None, &MergeOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(merged.conflicts, vec!["README.md"]);
+        assert_eq!(all_commits(&repo).await, before);
+    }
+}
@@ -0,0 +1,734 @@
+//! Version control backends.
+//!
+//! # Overview
+//!
+//! Workspaces, changed files, diffs, branch commits and merges go through
+//! [`Vcs`], which picks the implementation for a project:
+//!
+//! - [`Vcs::Git`] (the default) - [`crate::git`], worktrees and `git` CLI.
+//! - [`Vcs::Jujutsu`] - [`jj`], `jj workspace add` and the `jj` CLI. Works
+//!   for colocated repositories (`.jj` next to `.git`) and native ones.
+//! - [`Vcs::Plain`] - [`git::plain`], copies of a project directory that
+//!   isn't under version control.
+//! - [`Vcs::MultiRepo`] - [`git::multi`], a git worktree of each of a
+//!   project's repositories.
+//!
+//! # Selecting a Backend
+//!
+//! A project picks its backend with `vcs` (`"git"` or `"jj"`) in
+//! `projects.json`. Without it, a checkout with a `.jj` directory and no
+//! `.git` is Jujutsu and everything else is git, so colocated repositories
+//! keep using git until the project opts in.
+//!
+//! Copied and multi-repo workspaces are recognized by their marker files and
+//! are always [`Vcs::Plain`] and [`Vcs::MultiRepo`]. Neither can be set in
+//! `projects.json`: a project directory that isn't a git repository gets
+//! copied workspaces from [`add_project_workspace`], a project with `repos`
+//! multi-repo ones.
+//!
+//! # Multi-Repo Projects
+//!
+//! A project with `repos` gets a worktree of each repository per workspace
+//! ([`add_project_workspace`]). [`Vcs::MultiRepo`] aggregates the
+//! repositories' changes and merges, and hands a file's diff to the
+//! repository it's in (`backend/src/lib.rs` is `src/lib.rs` in `backend`).
+//!
+//! ```ignore
+//! let vcs = Vcs::resolve(Some(&config_dir), &workspace_path);
+//! let changes = vcs.list_changed_files(&workspace_path, None).await?;
+//! ```
+
+pub mod jj;
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::git::{
+    self, ChangedFile, ChangedFilesResult, Commit, DiffOptions, FileDiff, GitError, MergeOptions,
+    MergeResult, MergeStrategy, SyncState, WorkspaceLayout,
+};
+use crate::persistence::{
+    find_project_by_path, find_project_by_workspace_path, load_project_registry,
+};
+
+/// A version control backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Vcs {
+    /// Git worktrees (the default)
+    #[default]
+    Git,
+    /// Jujutsu workspaces
+    #[serde(rename = "jj")]
+    Jujutsu,
+    /// Copied workspaces of a directory outside version control. Never
+    /// configured; see [`Vcs::detect`].
+    #[serde(skip)]
+    Plain,
+    /// Workspaces with a git worktree of each of a project's repositories.
+    /// Never configured; see [`Vcs::detect`].
+    #[serde(skip)]
+    MultiRepo,
+}
+
+impl Vcs {
+    /// The backend for a checkout going by what's on disk: plain or
+    /// multi-repo for those workspaces, Jujutsu if it has a `.jj` directory and no `.git`,
+    /// git otherwise.
+    pub fn detect(path: &Path) -> Vcs {
+        if let Some(vcs) = Vcs::from_marker(path) {
+            vcs
+        } else if path.join(".jj").is_dir() && !path.join(".git").exists() {
+            Vcs::Jujutsu
+        } else {
+            Vcs::Git
+        }
+    }
+
+    /// The backend for a project checkout or one of its workspaces.
+    ///
+    /// A copied or multi-repo workspace is always [`Vcs::Plain`] or
+    /// [`Vcs::MultiRepo`]. Otherwise the owning
+    /// project's `vcs` setting wins; without a config directory, for
+    /// unregistered paths and for projects without the setting, the backend
+    /// is detected from `path` (see [`Vcs::detect`]).
+    pub fn resolve(config_dir: Option<&Path>, path: &Path) -> Vcs {
+        if let Some(vcs) = Vcs::from_marker(path) {
+            return vcs;
+        }
+        let configured = config_dir.and_then(|dir| {
+            let registry = load_project_registry(dir).ok()?;
+            find_project_by_workspace_path(&registry, &path.to_string_lossy())?.vcs
+        });
+        configured.unwrap_or_else(|| Vcs::detect(path))
+    }
+
+    /// The backend a workspace's marker file asks for, whatever the project
+    /// is configured with.
+    fn from_marker(path: &Path) -> Option<Vcs> {
+        if git::is_plain_workspace(path) {
+            Some(Vcs::Plain)
+        } else if git::is_multi_workspace(path) {
+            Some(Vcs::MultiRepo)
+        } else {
+            None
+        }
+    }
+
+    /// Create a workspace for `branch`.
+    ///
+    /// See [`git::add_workspace`], [`jj::add_workspace`] and
+    /// [`git::add_plain_workspace`], which names the copy after `branch`.
+    /// Multi-repo workspaces need the project's repositories and are created
+    /// by [`add_project_workspace`].
+    pub async fn add_workspace(
+        self,
+        repo_path: &Path,
+        branch: &str,
+        layout: &WorkspaceLayout,
+    ) -> Result<PathBuf, GitError> {
+        match self {
+            Vcs::Git => git::add_workspace(repo_path, branch, layout).await,
+            Vcs::Jujutsu => jj::add_workspace(repo_path, branch, layout).await,
+            Vcs::Plain => git::add_plain_workspace(repo_path, branch, layout).await,
+            Vcs::MultiRepo => Err(GitError::Other(
+                "Multi-repo workspaces are created from their project's repositories".to_string(),
+            )),
+        }
+    }
+
+    /// Remove a workspace from the repository and delete its directory.
+    ///
+    /// See [`git::archive_workspace`] and [`jj::archive_workspace`]. A
+    /// copied workspace is just deleted and a multi-repo one has each
+    /// worktree removed from its repository; `repo_path` isn't used for
+    /// either.
+    pub async fn archive_workspace(
+        self,
+        repo_path: &Path,
+        workspace_path: &Path,
+        force: bool,
+    ) -> Result<(), GitError> {
+        match self {
+            Vcs::Git => git::archive_workspace(repo_path, workspace_path, force).await,
+            Vcs::Jujutsu => jj::archive_workspace(workspace_path).await,
+            Vcs::Plain => git::plain::remove_plain_workspace(workspace_path, force).await,
+            Vcs::MultiRepo => git::multi::remove_multi_workspace(workspace_path, force).await,
+        }
+    }
+
+    /// Files changed in a workspace compared to the default branch.
+    ///
+    /// A copied workspace reports its differences from the project directory
+    /// as branch changes and never has uncommitted ones. A multi-repo
+    /// workspace reports each repository's changes as a submodule.
+    pub async fn list_changed_files(
+        self,
+        workspace_path: &Path,
+        main_branch: Option<&str>,
+    ) -> Result<ChangedFilesResult, GitError> {
+        match self {
+            Vcs::Git => git::list_changed_files(workspace_path, main_branch).await,
+            Vcs::Jujutsu => jj::list_changed_files(workspace_path, main_branch).await,
+            Vcs::Plain => git::plain::list_plain_changes(workspace_path).await,
+            Vcs::MultiRepo => git::multi::list_multi_changes(workspace_path, main_branch).await,
+        }
+    }
+
+    /// Raw diff text for one changed file.
+    pub async fn get_file_diff(
+        self,
+        workspace_path: &Path,
+        file_path: &str,
+        file_status: &str,
+        main_branch: Option<&str>,
+    ) -> Result<String, GitError> {
+        match self {
+            Vcs::Git => {
+                git::get_file_diff(workspace_path, file_path, file_status, main_branch).await
+            }
+            Vcs::Jujutsu => {
+                jj::get_file_diff(
+                    workspace_path,
+                    file_path,
+                    main_branch,
+                    &DiffOptions::default(),
+                )
+                .await
+            }
+            Vcs::Plain => {
+                git::plain::plain_file_diff(
+                    workspace_path,
+                    file_path,
+                    file_status,
+                    &DiffOptions::default(),
+                )
+                .await
+            }
+            Vcs::MultiRepo => {
+                let (worktree, file_path, repo_main) =
+                    git::multi::route_file(workspace_path, file_path)?;
+                let main_branch = repo_main.as_deref().or(main_branch);
+                git::get_file_diff(&worktree, &file_path, file_status, main_branch).await
+            }
+        }
+    }
+
+    /// Parsed diff for one changed file.
+    pub async fn get_structured_file_diff(
+        self,
+        workspace_path: &Path,
+        file_path: &str,
+        file_status: &str,
+        main_branch: Option<&str>,
+        options: &DiffOptions,
+    ) -> Result<Vec<FileDiff>, GitError> {
+        match self {
+            Vcs::Git => {
+                git::get_structured_file_diff(
+                    workspace_path,
+                    file_path,
+                    file_status,
+                    main_branch,
+                    options,
+                )
+                .await
+            }
+            Vcs::Jujutsu => {
+                let text =
+                    jj::get_file_diff(workspace_path, file_path, main_branch, options).await?;
+                Ok(git::parse_unified_diff(&text))
+            }
+            Vcs::Plain => {
+                let text =
+                    git::plain::plain_file_diff(workspace_path, file_path, file_status, options)
+                        .await?;
+                Ok(git::parse_unified_diff(&text))
+            }
+            Vcs::MultiRepo => {
+                let (worktree, file_path, repo_main) =
+                    git::multi::route_file(workspace_path, file_path)?;
+                let main_branch = repo_main.as_deref().or(main_branch);
+                git::get_structured_file_diff(
+                    &worktree,
+                    &file_path,
+                    file_status,
+                    main_branch,
+                    options,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Raw diff text for one uncommitted file.
+    pub async fn get_uncommitted_diff(
+        self,
+        workspace_path: &Path,
+        file_path: &str,
+        file_status: &str,
+    ) -> Result<String, GitError> {
+        match self {
+            Vcs::Git => git::get_uncommitted_diff(workspace_path, file_path, file_status).await,
+            Vcs::Jujutsu => {
+                jj::get_uncommitted_diff(workspace_path, file_path, &DiffOptions::default()).await
+            }
+            Vcs::Plain => Ok(String::new()),
+            Vcs::MultiRepo => {
+                let (worktree, file_path, _) = git::multi::route_file(workspace_path, file_path)?;
+                git::get_uncommitted_diff(&worktree, &file_path, file_status).await
+            }
+        }
+    }
+
+    /// Parsed diff for one uncommitted file.
+    pub async fn get_structured_uncommitted_diff(
+        self,
+        workspace_path: &Path,
+        file_path: &str,
+        file_status: &str,
+        options: &DiffOptions,
+    ) -> Result<Vec<FileDiff>, GitError> {
+        match self {
+            Vcs::Git => {
+                git::get_structured_uncommitted_diff(
+                    workspace_path,
+                    file_path,
+                    file_status,
+                    options,
+                )
+                .await
+            }
+            Vcs::Jujutsu => {
+                let text = jj::get_uncommitted_diff(workspace_path, file_path, options).await?;
+                Ok(git::parse_unified_diff(&text))
+            }
+            Vcs::Plain => Ok(Vec::new()),
+            Vcs::MultiRepo => {
+                let (worktree, file_path, _) = git::multi::route_file(workspace_path, file_path)?;
+                git::get_structured_uncommitted_diff(&worktree, &file_path, file_status, options)
+                    .await
+            }
+        }
+    }
+
+    /// Commits in the workspace that aren't on the default branch, oldest
+    /// first. Copied and multi-repo workspaces have none of their own.
+    pub async fn list_commits_on_branch(
+        self,
+        workspace_path: &Path,
+        main_branch: Option<&str>,
+    ) -> Result<Vec<Commit>, GitError> {
+        match self {
+            Vcs::Git => git::list_commits_on_branch(workspace_path, main_branch).await,
+            Vcs::Jujutsu => jj::list_commits_on_branch(workspace_path, main_branch).await,
+            Vcs::Plain | Vcs::MultiRepo => Ok(Vec::new()),
+        }
+    }
+
+    /// Files changed in one commit.
+    pub async fn list_commit_files(
+        self,
+        workspace_path: &Path,
+        commit_sha: &str,
+    ) -> Result<Vec<ChangedFile>, GitError> {
+        match self {
+            Vcs::Git => git::list_commit_files(workspace_path, commit_sha).await,
+            Vcs::Jujutsu => jj::list_commit_files(workspace_path, commit_sha).await,
+            Vcs::Plain | Vcs::MultiRepo => Err(no_commits(commit_sha)),
+        }
+    }
+
+    /// Raw diff text for one file in one commit.
+    pub async fn get_commit_diff(
+        self,
+        workspace_path: &Path,
+        commit_sha: &str,
+        file_path: &str,
+        file_status: &str,
+    ) -> Result<String, GitError> {
+        match self {
+            Vcs::Git => {
+                git::get_commit_diff(workspace_path, commit_sha, file_path, file_status).await
+            }
+            Vcs::Jujutsu => {
+                jj::get_commit_diff(
+                    workspace_path,
+                    commit_sha,
+                    file_path,
+                    &DiffOptions::default(),
+                )
+                .await
+            }
+            Vcs::Plain | Vcs::MultiRepo => Err(no_commits(commit_sha)),
+        }
+    }
+
+    /// Parsed diff for one file in one commit.
+    pub async fn get_structured_commit_diff(
+        self,
+        workspace_path: &Path,
+        commit_sha: &str,
+        file_path: &str,
+        file_status: &str,
+        options: &DiffOptions,
+    ) -> Result<Vec<FileDiff>, GitError> {
+        match self {
+            Vcs::Git => {
+                git::get_structured_commit_diff(
+                    workspace_path,
+                    commit_sha,
+                    file_path,
+                    file_status,
+                    options,
+                )
+                .await
+            }
+            Vcs::Jujutsu => {
+                let text =
+                    jj::get_commit_diff(workspace_path, commit_sha, file_path, options).await?;
+                Ok(git::parse_unified_diff(&text))
+            }
+            Vcs::Plain | Vcs::MultiRepo => Err(no_commits(commit_sha)),
+        }
+    }
+
+    /// Check whether the workspace would land on the default branch cleanly.
+    /// For a copied workspace: whether its changes can be copied back; for a
+    /// multi-repo one: whether every repository would.
+    ///
+    /// Callers go through [`check_workspace_merge`], which applies the
+    /// project's merge settings.
+    pub(crate) async fn check_merge(
+        self,
+        workspace_path: &Path,
+        main_branch: Option<&str>,
+        strategy: MergeStrategy,
+    ) -> Result<MergeResult, GitError> {
+        match self {
+            Vcs::Git => git::check_merge(workspace_path, main_branch, strategy).await,
+            Vcs::Jujutsu => jj::check_merge(workspace_path, main_branch, strategy).await,
+            Vcs::Plain => git::plain::check_copy_back(workspace_path).await,
+            Vcs::MultiRepo => {
+                git::multi::check_multi_merge(workspace_path, main_branch, strategy).await
+            }
+        }
+    }
+
+    /// Land the workspace's changes on the default branch. A copied
+    /// workspace copies them back to the project directory and ignores
+    /// `options`; a multi-repo one merges repository by repository.
+    ///
+    /// Callers go through [`merge_workspace_into_main`], which applies the
+    /// project's merge settings.
+    pub(crate) async fn merge_into_main(
+        self,
+        workspace_path: &Path,
+        main_branch: Option<&str>,
+        options: &MergeOptions,
+    ) -> Result<MergeResult, GitError> {
+        match self {
+            Vcs::Git => git::merge_into_main(workspace_path, main_branch, options).await,
+            Vcs::Jujutsu => jj::merge_into_main(workspace_path, main_branch, options).await,
+            Vcs::Plain => git::copy_back(workspace_path).await,
+            Vcs::MultiRepo => {
+                git::multi::merge_multi_into_main(workspace_path, main_branch, options).await
+            }
+        }
+    }
+
+    /// The sync stopped on conflicts in the workspace, if any. Only git
+    /// workspaces are synced with [`git::sync_with_default_branch`].
+    pub async fn get_sync_state(
+        self,
+        workspace_path: &Path,
+    ) -> Result<Option<SyncState>, GitError> {
+        match self {
+            Vcs::Git => git::get_sync_state(workspace_path).await,
+            Vcs::Jujutsu | Vcs::Plain | Vcs::MultiRepo => Ok(None),
+        }
+    }
+}
+
+/// The error for commit lookups in a workspace without commits.
+fn no_commits(commit_sha: &str) -> GitError {
+    GitError::Other(format!(
+        "Commit {commit_sha} not found: the workspace has no commits of its own"
+    ))
+}
+
+/// Create a workspace for the project at `project_path`.
+///
+/// A multi-repo project gets a worktree of each of its repositories (see
+/// [`git::multi`]) and a git project directory that isn't a repository gets
+/// a copy ([`Vcs::Plain`]); any other project goes through its backend's
+/// [`Vcs::add_workspace`].
+pub async fn add_project_workspace(
+    config_dir: Option<&Path>,
+    project_path: &Path,
+    branch: &str,
+    layout: &WorkspaceLayout,
+) -> Result<PathBuf, GitError> {
+    let repos = config_dir
+        .and_then(|dir| load_project_registry(dir).ok())
+        .and_then(|registry| {
+            find_project_by_path(&registry, &project_path.to_string_lossy())
+                .map(|project| project.repos.clone())
+        })
+        .unwrap_or_default();
+    if !repos.is_empty() {
+        return git::add_multi_workspace(project_path, &repos, branch, layout).await;
+    }
+    let vcs = match Vcs::resolve(config_dir, project_path) {
+        Vcs::Git if !git::is_git_repo(project_path) => Vcs::Plain,
+        vcs => vcs,
+    };
+    vcs.add_workspace(project_path, branch, layout).await
+}
+
+/// Check whether a workspace would land on its project's default branch
+/// cleanly.
+///
+/// Refused with [`GitError::MergeDisabled`] for projects with
+/// `allowMergeToMain: false`. Without `main_branch`, the project's configured
+/// default branch is used.
+pub async fn check_workspace_merge(
+    config_dir: Option<&Path>,
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    strategy: MergeStrategy,
+) -> Result<MergeResult, GitError> {
+    let project_main_branch = merge_settings(config_dir, workspace_path)?;
+    Vcs::resolve(config_dir, workspace_path)
+        .check_merge(
+            workspace_path,
+            main_branch.or(project_main_branch.as_deref()),
+            strategy,
+        )
+        .await
+}
+
+/// Land a workspace's changes on its project's default branch.
+///
+/// Refused like [`check_workspace_merge`].
+pub async fn merge_workspace_into_main(
+    config_dir: Option<&Path>,
+    workspace_path: &Path,
+    main_branch: Option<&str>,
+    options: &MergeOptions,
+) -> Result<MergeResult, GitError> {
+    let project_main_branch = merge_settings(config_dir, workspace_path)?;
+    Vcs::resolve(config_dir, workspace_path)
+        .merge_into_main(
+            workspace_path,
+            main_branch.or(project_main_branch.as_deref()),
+            options,
+        )
+        .await
+}
+
+/// The configured default branch of the project owning `workspace_path`, or
+/// [`GitError::MergeDisabled`] if the project doesn't allow merging into it.
+fn merge_settings(
+    config_dir: Option<&Path>,
+    workspace_path: &Path,
+) -> Result<Option<String>, GitError> {
+    let Some(registry) = config_dir.and_then(|dir| load_project_registry(dir).ok()) else {
+        return Ok(None);
+    };
+    match find_project_by_workspace_path(&registry, &workspace_path.to_string_lossy()) {
+        Some(project) if !project.allows_merge_to_main() => Err(GitError::MergeDisabled {
+            project: project.name.clone(),
+        }),
+        Some(project) => Ok(project.main_branch.clone()),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{save_project_registry, ProjectRegistry};
+
+    #[test]
+    fn serializes_as_lowercase_names() {
+        assert_eq!(serde_json::to_string(&Vcs::Git).unwrap(), "\"git\"");
+        assert_eq!(serde_json::to_string(&Vcs::Jujutsu).unwrap(), "\"jj\"");
+        assert_eq!(serde_json::from_str::<Vcs>("\"jj\"").unwrap(), Vcs::Jujutsu);
+    }
+
+    #[test]
+    fn detect_prefers_git_for_colocated_repos() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(Vcs::detect(dir.path()), Vcs::Git);
+
+        std::fs::create_dir(dir.path().join(".jj")).unwrap();
+        assert_eq!(Vcs::detect(dir.path()), Vcs::Jujutsu);
+
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        assert_eq!(Vcs::detect(dir.path()), Vcs::Git);
+    }
+
+    #[test]
+    fn resolve_uses_the_project_setting() {
+        let config = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir(repo.path().join(".git")).unwrap();
+        let repo_path = repo.path().to_string_lossy().to_string();
+
+        let registry: ProjectRegistry = serde_json::from_value(serde_json::json!({
+            "projects": [{ "id": "p1", "name": "repo", "path": repo_path, "vcs": "jj" }]
+        }))
+        .unwrap();
+        save_project_registry(config.path(), &registry, None).unwrap();
+
+        assert_eq!(Vcs::resolve(Some(config.path()), repo.path()), Vcs::Jujutsu);
+        assert_eq!(Vcs::resolve(None, repo.path()), Vcs::Git);
+    }
+
+    #[tokio::test]
+    async fn projects_outside_git_get_plain_workspaces() {
+        let config = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("notes");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join("todo.md"), "- write tests\n").unwrap();
+        let layout = WorkspaceLayout {
+            root: Some(root.path().join("ws").to_string_lossy().to_string()),
+            naming: Some("{repo}/{branch}".to_string()),
+        };
+
+        let workspace = add_project_workspace(Some(config.path()), &project, "draft", &layout)
+            .await
+            .unwrap();
+        assert_eq!(Vcs::resolve(Some(config.path()), &workspace), Vcs::Plain);
+        assert_eq!(Vcs::detect(&workspace), Vcs::Plain);
+
+        std::fs::write(workspace.join("todo.md"), "- write more tests\n").unwrap();
+        let changes = Vcs::Plain
+            .list_changed_files(&workspace, None)
+            .await
+            .unwrap();
+        assert_eq!(changes.files.len(), 1);
+        assert!(Vcs::Plain
+            .list_commits_on_branch(&workspace, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn merges_are_refused_when_the_project_disallows_them() {
+        let config = tempfile::tempdir().unwrap();
+        let registry: ProjectRegistry = serde_json::from_value(serde_json::json!({
+            "projects": [{
+                "id": "p1",
+                "name": "demo",
+                "path": "/repos/demo",
+                "allowMergeToMain": false,
+                "workspaces": [{
+                    "id": "w1",
+                    "branch": "feature",
+                    "path": "/workspaces/demo/feature",
+                    "isArchived": false,
+                    "createdAt": "2026-01-01T00:00:00Z"
+                }]
+            }]
+        }))
+        .unwrap();
+        save_project_registry(config.path(), &registry, None).unwrap();
+        let workspace = Path::new("/workspaces/demo/feature");
+
+        let checked = check_workspace_merge(
+            Some(config.path()),
+            workspace,
+            None,
+            MergeStrategy::default(),
+        )
+        .await;
+        assert!(
+            matches!(checked, Err(GitError::MergeDisabled { ref project }) if project == "demo")
+        );
+        let merged = merge_workspace_into_main(
+            Some(config.path()),
+            workspace,
+            Some("main"),
+            &MergeOptions::default(),
+        )
+        .await;
+        assert!(matches!(merged, Err(GitError::MergeDisabled { .. })));
+    }
+
+    #[tokio::test]
+    async fn multi_repo_projects_get_a_worktree_per_repo() {
+        let config = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("app");
+        let api = project.join("api");
+        std::fs::create_dir_all(&api).unwrap();
+        for args in [
+            &["init", "-q", "-b", "main"][..],
+            &[
+                "-c",
+                "user.name=T",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        ] {
+            let out = std::process::Command::new("git")
+                .args(args)
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .current_dir(&api)
+                .output()
+                .unwrap();
+            assert!(
+                out.status.success(),
+                "{}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+        }
+
+        let registry: ProjectRegistry = serde_json::from_value(serde_json::json!({
+            "projects": [{
+                "id": "p1",
+                "name": "app",
+                "path": project.to_string_lossy(),
+                "repos": [{ "name": "api", "path": "api" }]
+            }]
+        }))
+        .unwrap();
+        save_project_registry(config.path(), &registry, None).unwrap();
+        let layout = WorkspaceLayout {
+            root: Some(root.path().join("ws").to_string_lossy().to_string()),
+            naming: Some("{repo}/{branch}".to_string()),
+        };
+
+        let workspace = add_project_workspace(Some(config.path()), &project, "feature", &layout)
+            .await
+            .unwrap();
+
+        assert!(git::is_multi_workspace(&workspace));
+        assert!(workspace.join("api/.git").exists());
+        assert_eq!(
+            Vcs::resolve(Some(config.path()), &workspace),
+            Vcs::MultiRepo
+        );
+
+        std::fs::write(workspace.join("api/routes.rs"), "fn main() {}\n").unwrap();
+        let diff = Vcs::MultiRepo
+            .get_uncommitted_diff(&workspace, "api/routes.rs", "?")
+            .await
+            .unwrap();
+        assert!(diff.contains("+fn main() {}"), "{diff}");
+        assert!(Vcs::MultiRepo
+            .get_uncommitted_diff(&workspace, "docs/routes.rs", "?")
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,511 @@
+//! Disk usage reporting and garbage collection for a project's workspaces.
+//!
+//! # Overview
+//!
+//! Workspaces whose work is done stay on disk until someone archives them,
+//! and chat directories outlive the workspaces they belonged to.
+//! [`collect_workspace_garbage`] reports how much space each workspace and
+//! its chats take, and collects:
+//!
+//! - Workspaces whose branch is merged into the default branch, or whose
+//!   pull request (`prState`) is merged or closed: the worktree is torn down
+//!   and removed like any archive (see [`crate::workspace_teardown`]; never
+//!   forced, so uncommitted changes are kept), the workspace marked
+//!   archived, its chats archived and, optionally, its branch deleted
+//! - Stale `git worktree` entries for directories deleted by hand
+//! - Chat directories that belong to no active workspace, which are moved to
+//!   the project's `archived` chats like an archived workspace's
+//!
+//! With `dry_run`, the report is built but nothing changes.
+//!
+//! ```ignore
+//! let plan = collect_workspace_garbage(&config_dir, &project_id, GcOptions {
+//!     dry_run: true,
+//!     ..Default::default()
+//! }).await?;
+//! println!("{} bytes reclaimable", plan.reclaimable_bytes);
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::git::{
+    delete_branch, list_merged_branches, prune_workspaces, resolve_default_branch, GitError,
+};
+use crate::persistence::{
+    load_project_registry_versioned, rename_chat_dir, save_project_registry, Versioned, Workspace,
+};
+use crate::processes::StoppedProcess;
+use crate::workspace_teardown::archive_workspace_with_teardown;
+
+/// Name of the directory archived chats are moved into, per project.
+const ARCHIVED_CHATS_DIR: &str = "archived";
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// What [`collect_workspace_garbage`] does.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GcOptions {
+    /// Only report; change nothing
+    pub dry_run: bool,
+    /// Also delete collected workspaces' branches (`git branch -d`, so a
+    /// branch with commits missing from the default branch is kept)
+    pub delete_branches: bool,
+}
+
+/// Why a workspace can be collected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GcReason {
+    /// The branch is merged into the default branch
+    Merged,
+    /// The workspace's pull request was merged
+    PrMerged,
+    /// The workspace's pull request was closed without merging
+    PrClosed,
+}
+
+/// One active workspace in a [`GcReport`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceUsage {
+    pub workspace_id: String,
+    pub branch: String,
+    pub path: String,
+    /// Size of the worktree on disk
+    pub bytes: u64,
+    /// Size of the workspace's chat directory
+    pub chat_bytes: u64,
+    /// Set if the workspace can be collected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<GcReason>,
+    /// True once the workspace was archived
+    pub archived: bool,
+    /// True once the branch was deleted
+    pub branch_deleted: bool,
+    /// Processes stopped before the workspace was archived
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stopped_processes: Vec<StoppedProcess>,
+    /// Set if archiving failed; the workspace was left as it was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A chat directory no active workspace owns.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedChatDir {
+    pub name: String,
+    pub bytes: u64,
+    /// True once it was moved to the archived chats
+    pub archived: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of [`collect_workspace_garbage`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    /// True if nothing was changed, only reported
+    pub dry_run: bool,
+    /// The branch workspaces were checked against
+    pub default_branch: String,
+    pub workspaces: Vec<WorkspaceUsage>,
+    pub orphaned_chats: Vec<OrphanedChatDir>,
+    /// Stale `git worktree` entries (pruned unless `dry_run`)
+    pub stale_worktrees: Vec<String>,
+    /// Size of the project's archived chats
+    pub archived_chat_bytes: u64,
+    /// Size of everything above
+    pub total_bytes: u64,
+    /// Size of the worktrees that can be collected
+    pub reclaimable_bytes: u64,
+}
+
+// ============================================================================
+// COLLECTING
+// ============================================================================
+
+/// Report disk usage for a project's workspaces and collect the ones that
+/// are no longer needed.
+///
+/// The main worktree, remote workspaces and ones being created or archived
+/// are never touched. A workspace that fails to archive (e.g. it has
+/// uncommitted changes) is reported with an `error` and the rest carry on.
+///
+/// # Errors
+///
+/// Returns an error if the project doesn't exist, isn't a git repository,
+/// or `projects.json` can't be read or written.
+pub async fn collect_workspace_garbage(
+    config_dir: &Path,
+    project_id: &str,
+    options: GcOptions,
+) -> Result<GcReport, GitError> {
+    let Versioned {
+        value: mut registry,
+        version: base,
+    } = load_project_registry_versioned(config_dir).map_err(|e| GitError::Other(e.to_string()))?;
+    let project = registry
+        .projects
+        .iter_mut()
+        .find(|p| p.id == project_id)
+        .ok_or_else(|| GitError::Other(format!("Project not found: {project_id}")))?;
+    if !project.is_git_repo {
+        return Err(GitError::Other(format!(
+            "{} isn't a git repository; its workspaces can't be collected.",
+            project.name
+        )));
+    }
+
+    let repo_path = PathBuf::from(&project.path);
+    let chats_dir = config_dir.join("chats").join(&project.name);
+    let default_branch = resolve_default_branch(&repo_path, project.main_branch.as_deref()).await;
+    let merged: HashSet<String> = list_merged_branches(&repo_path, &default_branch)
+        .await?
+        .into_iter()
+        .collect();
+    let stale_worktrees = prune_workspaces(&repo_path, options.dry_run).await?;
+
+    let mut workspaces = Vec::new();
+    for workspace in project.get_workspaces_mut() {
+        if workspace.is_archived
+            || workspace.is_creating == Some(true)
+            || workspace.is_archiving == Some(true)
+            || workspace.ssh_host_id.is_some()
+            || workspace.path.is_empty()
+            || Path::new(&workspace.path) == repo_path
+        {
+            continue;
+        }
+
+        let path = PathBuf::from(&workspace.path);
+        let chat_name = leaf_name(&path);
+        let mut usage = WorkspaceUsage {
+            workspace_id: workspace.id.clone(),
+            branch: workspace.branch.clone(),
+            path: workspace.path.clone(),
+            bytes: disk_usage(path.clone()).await,
+            chat_bytes: disk_usage(chats_dir.join(&chat_name)).await,
+            reason: gc_reason(workspace, &merged),
+            archived: false,
+            branch_deleted: false,
+            stopped_processes: Vec::new(),
+            error: None,
+        };
+
+        if !options.dry_run && usage.reason.is_some() {
+            match archive_workspace_with_teardown(Some(config_dir), &repo_path, &path, false).await
+            {
+                Ok(teardown) => {
+                    workspace.is_archived = true;
+                    usage.archived = true;
+                    usage.stopped_processes = teardown.processes;
+                    let archive_name =
+                        format!("{}-{}", workspace.branch.replace('/', "-"), stamp());
+                    if let Err(e) = archive_chat_dir(&chats_dir, &chat_name, &archive_name) {
+                        log::warn!("Archived workspace {} but not its chats: {e}", workspace.id);
+                    }
+                    if options.delete_branches {
+                        match delete_branch(&repo_path, &workspace.branch).await {
+                            Ok(()) => usage.branch_deleted = true,
+                            Err(e) => log::info!("Kept branch {}: {e}", workspace.branch),
+                        }
+                    }
+                }
+                Err(e) => usage.error = Some(e.to_string()),
+            }
+        }
+        workspaces.push(usage);
+    }
+
+    // Chat directories of workspaces that are still active, after the above.
+    let mut owned: HashSet<String> = project
+        .get_workspaces()
+        .iter()
+        .filter(|w| !w.is_archived)
+        .map(|w| leaf_name(Path::new(&w.path)))
+        .collect();
+    owned.insert(leaf_name(&repo_path));
+    owned.insert(ARCHIVED_CHATS_DIR.to_string());
+
+    let mut orphaned_chats = Vec::new();
+    for name in list_dirs(&chats_dir) {
+        if owned.contains(&name) {
+            continue;
+        }
+        let mut orphan = OrphanedChatDir {
+            bytes: disk_usage(chats_dir.join(&name)).await,
+            name,
+            archived: false,
+            error: None,
+        };
+        if !options.dry_run {
+            let archive_name = format!("{}-{}", orphan.name, stamp());
+            match archive_chat_dir(&chats_dir, &orphan.name, &archive_name) {
+                Ok(()) => orphan.archived = true,
+                Err(e) => orphan.error = Some(e.to_string()),
+            }
+        }
+        orphaned_chats.push(orphan);
+    }
+
+    if workspaces.iter().any(|w| w.archived) {
+        save_project_registry(config_dir, &registry, base.as_ref())
+            .map_err(|e| GitError::Other(e.to_string()))?;
+    }
+
+    let archived_chat_bytes = disk_usage(chats_dir.join(ARCHIVED_CHATS_DIR)).await;
+    let reclaimable_bytes = workspaces
+        .iter()
+        .filter(|w| w.reason.is_some() && w.error.is_none())
+        .map(|w| w.bytes)
+        .sum();
+    let total_bytes = workspaces
+        .iter()
+        .map(|w| w.bytes + w.chat_bytes)
+        .chain(orphaned_chats.iter().map(|o| o.bytes))
+        .sum::<u64>()
+        + archived_chat_bytes;
+
+    Ok(GcReport {
+        dry_run: options.dry_run,
+        default_branch,
+        workspaces,
+        orphaned_chats,
+        stale_worktrees,
+        archived_chat_bytes,
+        total_bytes,
+        reclaimable_bytes,
+    })
+}
+
+fn gc_reason(workspace: &Workspace, merged: &HashSet<String>) -> Option<GcReason> {
+    if merged.contains(&workspace.branch) {
+        return Some(GcReason::Merged);
+    }
+    match workspace.pr_state.as_deref() {
+        Some(state) if state.eq_ignore_ascii_case("merged") => Some(GcReason::PrMerged),
+        Some(state) if state.eq_ignore_ascii_case("closed") => Some(GcReason::PrClosed),
+        _ => None,
+    }
+}
+
+/// Move `chats_dir/name` to `chats_dir/archived/archive_name`, the same place
+/// archiving a workspace puts its chats.
+fn archive_chat_dir(chats_dir: &Path, name: &str, archive_name: &str) -> std::io::Result<()> {
+    let source = chats_dir.join(name);
+    if !source.exists() {
+        return Ok(());
+    }
+    let archive_parent = chats_dir.join(ARCHIVED_CHATS_DIR);
+    std::fs::create_dir_all(&archive_parent)?;
+    let dest = archive_parent.join(archive_name);
+    if dest.exists() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists", dest.display()),
+        ));
+    }
+    rename_chat_dir(&source, &dest)
+}
+
+/// Local time as `YYYY-MM-DD-HH-MM-SS`, matching archived chat names.
+fn stamp() -> String {
+    chrono::Local::now().format("%Y-%m-%d-%H-%M-%S").to_string()
+}
+
+fn leaf_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn list_dirs(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+// ============================================================================
+// DISK USAGE
+// ============================================================================
+
+/// Size in bytes of the files under `path`, off the async runtime.
+async fn disk_usage(path: PathBuf) -> u64 {
+    tokio::task::spawn_blocking(move || dir_size(&path))
+        .await
+        .unwrap_or(0)
+}
+
+/// Size in bytes of the files under `path`. Symlinks aren't followed and
+/// unreadable entries count as empty; a missing path is 0.
+pub fn dir_size(path: &Path) -> u64 {
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(meta) = std::fs::symlink_metadata(&current) else {
+            continue;
+        };
+        if meta.is_dir() {
+            if let Ok(entries) = std::fs::read_dir(&current) {
+                pending.extend(entries.flatten().map(|e| e.path()));
+            }
+        } else {
+            total += meta.len();
+        }
+    }
+    total
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{load_project_registry, Project, ProjectRegistry};
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let out = Command::new("git")
+            .args(["-c", "user.name=T", "-c", "user.email=t@t"])
+            .args(args)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "git {args:?}: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    fn workspace(id: &str, branch: &str, path: &Path) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "branch": branch,
+            "path": path.to_string_lossy(),
+            "createdAt": "2026-01-01T00:00:00Z",
+        })
+    }
+
+    #[test]
+    fn dir_size_counts_nested_files() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::write(dir.path().join("a/one"), "12345").unwrap();
+        std::fs::write(dir.path().join("a/b/two"), "123").unwrap();
+
+        assert_eq!(dir_size(dir.path()), 8);
+        assert_eq!(dir_size(&dir.path().join("missing")), 0);
+    }
+
+    #[tokio::test]
+    async fn collects_merged_workspaces_and_orphaned_chats() {
+        let repo_dir = TempDir::new().unwrap();
+        let repo = std::fs::canonicalize(repo_dir.path()).unwrap();
+        git(&repo, &["init", "-b", "main"]);
+        git(&repo, &["commit", "--allow-empty", "-m", "init"]);
+        let done = repo.join("narwhal");
+        let fresh = repo.join("dugong");
+        let stale = repo.join("okapi");
+        git(
+            &repo,
+            &["worktree", "add", "-b", "done", done.to_str().unwrap()],
+        );
+        git(
+            &repo,
+            &["worktree", "add", "-b", "fresh", fresh.to_str().unwrap()],
+        );
+        git(
+            &repo,
+            &["worktree", "add", "-b", "stale", stale.to_str().unwrap()],
+        );
+        git(&done, &["commit", "--allow-empty", "-m", "work"]);
+        git(&repo, &["merge", "--ff-only", "done"]);
+        std::fs::remove_dir_all(&stale).unwrap();
+
+        let config = TempDir::new().unwrap();
+        let project: Project = serde_json::from_value(serde_json::json!({
+            "id": "p1",
+            "name": "demo",
+            "path": repo.to_string_lossy(),
+            "workspaces": [workspace("w1", "done", &done), workspace("w2", "fresh", &fresh)],
+        }))
+        .unwrap();
+        save_project_registry(
+            config.path(),
+            &ProjectRegistry {
+                projects: vec![project],
+            },
+            None,
+        )
+        .unwrap();
+        let chats = config.path().join("chats/demo");
+        for name in ["narwhal", "dugong", "gone"] {
+            std::fs::create_dir_all(chats.join(name)).unwrap();
+            std::fs::write(chats.join(name).join("chats.json"), "{}").unwrap();
+        }
+
+        let options = GcOptions {
+            dry_run: true,
+            delete_branches: true,
+        };
+        let plan = collect_workspace_garbage(config.path(), "p1", options)
+            .await
+            .unwrap();
+        let reasons: Vec<_> = plan.workspaces.iter().map(|w| w.reason).collect();
+        assert_eq!(reasons, vec![Some(GcReason::Merged), None]);
+        assert_eq!(plan.stale_worktrees, vec!["okapi"]);
+        assert_eq!(plan.orphaned_chats.len(), 1);
+        assert_eq!(plan.orphaned_chats[0].name, "gone");
+        assert!(
+            done.exists() && chats.join("gone").exists(),
+            "dry run changed nothing"
+        );
+
+        let options = GcOptions {
+            dry_run: false,
+            ..options
+        };
+        let report = collect_workspace_garbage(config.path(), "p1", options)
+            .await
+            .unwrap();
+        assert!(report.workspaces[0].archived && report.workspaces[0].branch_deleted);
+        assert!(!done.exists() && fresh.exists());
+        assert!(!chats.join("narwhal").exists() && !chats.join("gone").exists());
+        assert!(chats.join("dugong").exists());
+        assert_eq!(list_dirs(&chats.join(ARCHIVED_CHATS_DIR)).len(), 2);
+
+        let registry = load_project_registry(config.path()).unwrap();
+        let archived: Vec<_> = registry.projects[0]
+            .workspaces
+            .iter()
+            .map(|w| w.is_archived)
+            .collect();
+        assert_eq!(archived, vec![true, false]);
+
+        let again = collect_workspace_garbage(config.path(), "p1", options)
+            .await
+            .unwrap();
+        assert_eq!(again.workspaces.len(), 1);
+        assert!(again.stale_worktrees.is_empty() && again.orphaned_chats.is_empty());
+    }
+}
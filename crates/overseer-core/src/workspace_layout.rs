@@ -0,0 +1,349 @@
+//! Resolving a project's workspace layout and moving existing workspaces to
+//! match it.
+//!
+//! # Overview
+//!
+//! New workspaces go where [`WorkspaceLayout`] says (see
+//! [`crate::git::layout`]). The layout is the project's `workspaceRoot` /
+//! `workspaceNaming` from `projects.json`, falling back to the same keys in
+//! `config.json`, then to the defaults.
+//!
+//! Changing the layout only affects new workspaces. [`migrate_workspaces`]
+//! moves a project's existing ones with `git worktree move`, updates their
+//! paths in `projects.json` and renames their chat directories, which are
+//! keyed by the workspace's directory name.
+//!
+//! ```ignore
+//! let layout = resolve_workspace_layout(Some(&config_dir), &repo_path);
+//! let path = add_workspace(&repo_path, "feature", &layout).await?;
+//!
+//! let report = migrate_workspaces(&config_dir, &project_id, true).await?;
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::git::{move_workspace, plan_workspace_dir, validate_naming, GitError, WorkspaceLayout};
+use crate::persistence::{
+    find_project_by_path, load_project_registry, load_project_registry_versioned, rename_chat_dir,
+    save_project_registry, Versioned,
+};
+
+/// One workspace's move in a migration.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceMove {
+    pub workspace_id: String,
+    pub branch: String,
+    pub from: String,
+    pub to: String,
+    /// Set if the move failed; the workspace stays where it was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of [`migrate_workspaces`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    /// True if nothing was moved, only planned
+    pub dry_run: bool,
+    /// The layout workspaces were moved to
+    pub layout: WorkspaceLayout,
+    pub moves: Vec<WorkspaceMove>,
+    /// Workspaces already in place
+    pub unchanged: usize,
+}
+
+// ============================================================================
+// RESOLVING
+// ============================================================================
+
+/// The global layout: `workspaceRoot` and `workspaceNaming` in `config.json`.
+///
+/// A missing or unreadable `config.json` yields the defaults.
+pub fn load_global_layout(config_dir: &Path) -> WorkspaceLayout {
+    let config = std::fs::read_to_string(config_dir.join("config.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok());
+    let field = |key: &str| {
+        config
+            .as_ref()
+            .and_then(|c| c.get(key))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    WorkspaceLayout {
+        root: field("workspaceRoot"),
+        naming: field("workspaceNaming"),
+    }
+}
+
+/// The layout for new workspaces of the project at `repo_path`.
+///
+/// Without a config directory, or for a repository that isn't a registered
+/// project, the global layout (or the defaults) applies.
+pub fn resolve_workspace_layout(config_dir: Option<&Path>, repo_path: &Path) -> WorkspaceLayout {
+    let Some(config_dir) = config_dir else {
+        return WorkspaceLayout::default();
+    };
+    let global = load_global_layout(config_dir);
+    let registry = load_project_registry(config_dir).unwrap_or_default();
+    match find_project_by_path(&registry, &repo_path.to_string_lossy()) {
+        Some(project) => project.workspace_layout().or(global),
+        None => global,
+    }
+}
+
+// ============================================================================
+// MIGRATING
+// ============================================================================
+
+/// Move a project's workspaces to where its current layout puts them.
+///
+/// Archived workspaces, the main worktree, remote workspaces and ones still
+/// being created are left alone. A workspace that fails to move (e.g. it has
+/// submodules, which `git worktree move` refuses) is reported and the rest
+/// carry on. With `dry_run`, the moves are planned but nothing changes.
+///
+/// # Errors
+///
+/// Returns an error if the project doesn't exist, isn't a git repository,
+/// its naming template is invalid, or `projects.json` can't be read or
+/// written.
+pub async fn migrate_workspaces(
+    config_dir: &Path,
+    project_id: &str,
+    dry_run: bool,
+) -> Result<MigrationReport, GitError> {
+    let Versioned {
+        value: mut registry,
+        version: base,
+    } = load_project_registry_versioned(config_dir).map_err(|e| GitError::Other(e.to_string()))?;
+    let project = registry
+        .projects
+        .iter_mut()
+        .find(|p| p.id == project_id)
+        .ok_or_else(|| GitError::Other(format!("Project not found: {project_id}")))?;
+    if !project.is_git_repo {
+        return Err(GitError::Other(format!(
+            "{} isn't a git repository; its workspaces can't be moved.",
+            project.name
+        )));
+    }
+
+    let layout = project
+        .workspace_layout()
+        .or(load_global_layout(config_dir));
+    validate_naming(layout.naming_template())?;
+
+    let repo_path = PathBuf::from(&project.path);
+    let chats_dir = config_dir.join("chats").join(&project.name);
+    let mut report = MigrationReport {
+        dry_run,
+        layout: layout.clone(),
+        moves: Vec::new(),
+        unchanged: 0,
+    };
+    let mut reserved = HashSet::new();
+
+    for workspace in project.get_workspaces_mut() {
+        if workspace.is_archived
+            || workspace.is_creating == Some(true)
+            || workspace.ssh_host_id.is_some()
+            || workspace.path.is_empty()
+            || Path::new(&workspace.path) == repo_path
+        {
+            continue;
+        }
+
+        let from = PathBuf::from(&workspace.path);
+        let Some(to) =
+            plan_workspace_dir(&repo_path, &from, &workspace.branch, &layout, &reserved)?
+        else {
+            report.unchanged += 1;
+            continue;
+        };
+        reserved.insert(to.clone());
+
+        let mut entry = WorkspaceMove {
+            workspace_id: workspace.id.clone(),
+            branch: workspace.branch.clone(),
+            from: workspace.path.clone(),
+            to: to.to_string_lossy().to_string(),
+            error: None,
+        };
+
+        if !dry_run {
+            match move_workspace(&repo_path, &from, &to).await {
+                Ok(moved) => {
+                    entry.to = moved.to_string_lossy().to_string();
+                    workspace.path = entry.to.clone();
+                    if let Err(e) = move_chat_dir(&chats_dir, &from, &moved) {
+                        log::warn!("Moved workspace {} but not its chats: {e}", workspace.id);
+                    }
+                }
+                Err(e) => entry.error = Some(e.to_string()),
+            }
+        }
+        report.moves.push(entry);
+    }
+
+    let moved_any = report.moves.iter().any(|m| m.error.is_none());
+    if !dry_run && moved_any {
+        save_project_registry(config_dir, &registry, base.as_ref())
+            .map_err(|e| GitError::Other(e.to_string()))?;
+    }
+    Ok(report)
+}
+
+/// Rename a workspace's chat directory after its directory name changed.
+fn move_chat_dir(chats_dir: &Path, from: &Path, to: &Path) -> std::io::Result<()> {
+    let (Some(old_name), Some(new_name)) = (from.file_name(), to.file_name()) else {
+        return Ok(());
+    };
+    if old_name == new_name {
+        return Ok(());
+    }
+    let old_dir = chats_dir.join(old_name);
+    let new_dir = chats_dir.join(new_name);
+    if !old_dir.exists() {
+        return Ok(());
+    }
+    if new_dir.exists() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists", new_dir.display()),
+        ));
+    }
+    rename_chat_dir(&old_dir, &new_dir)
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{Project, ProjectRegistry, Workspace};
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let out = Command::new("git")
+            .args(args)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "git {args:?}: {}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    fn project(repo: &Path, workspaces: Vec<Workspace>) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "id": "p1",
+            "name": "demo",
+            "path": repo.to_string_lossy(),
+            "workspaces": workspaces,
+        }))
+        .unwrap()
+    }
+
+    fn workspace(id: &str, branch: &str, path: &Path) -> Workspace {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "branch": branch,
+            "path": path.to_string_lossy(),
+            "createdAt": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn project_layout_wins_over_config_json() {
+        let config = TempDir::new().unwrap();
+        std::fs::write(
+            config.path().join("config.json"),
+            r#"{"workspaceRoot": "/scratch", "workspaceNaming": "{repo}/{n}"}"#,
+        )
+        .unwrap();
+        let mut p = project(Path::new("/repos/demo"), vec![]);
+        p.workspace_naming = Some("{repo}/{branch}".to_string());
+        let registry = ProjectRegistry { projects: vec![p] };
+        save_project_registry(config.path(), &registry, None).unwrap();
+
+        let layout = resolve_workspace_layout(Some(config.path()), Path::new("/repos/demo"));
+        assert_eq!(layout.root.as_deref(), Some("/scratch"));
+        assert_eq!(layout.naming.as_deref(), Some("{repo}/{branch}"));
+
+        let other = resolve_workspace_layout(Some(config.path()), Path::new("/repos/other"));
+        assert_eq!(other.naming.as_deref(), Some("{repo}/{n}"));
+    }
+
+    #[tokio::test]
+    async fn migrate_moves_worktrees_chats_and_registry() {
+        let repo_dir = TempDir::new().unwrap();
+        let repo = std::fs::canonicalize(repo_dir.path()).unwrap();
+        git(&repo, &["init", "-b", "main"]);
+        git(
+            &repo,
+            &[
+                "-c",
+                "user.name=T",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        );
+        let old = repo.join("narwhal");
+        git(
+            &repo,
+            &["worktree", "add", "-b", "feature/x", old.to_str().unwrap()],
+        );
+
+        let config = TempDir::new().unwrap();
+        let root = TempDir::new().unwrap();
+        let root_path = std::fs::canonicalize(root.path()).unwrap();
+        let mut p = project(&repo, vec![workspace("w1", "feature/x", &old)]);
+        p.workspace_root = Some(root_path.to_string_lossy().to_string());
+        p.workspace_naming = Some("{repo}/{branch}".to_string());
+        save_project_registry(config.path(), &ProjectRegistry { projects: vec![p] }, None).unwrap();
+        let chat_dir = config.path().join("chats/demo/narwhal");
+        std::fs::create_dir_all(&chat_dir).unwrap();
+
+        let plan = migrate_workspaces(config.path(), "p1", true).await.unwrap();
+        let repo_name = repo.file_name().unwrap().to_string_lossy().to_string();
+        let target = root_path.join(&repo_name).join("feature-x");
+        assert_eq!(plan.moves.len(), 1);
+        assert_eq!(plan.moves[0].to, target.to_string_lossy());
+        assert!(old.exists(), "dry run must not move anything");
+
+        let report = migrate_workspaces(config.path(), "p1", false)
+            .await
+            .unwrap();
+        assert_eq!(report.moves[0].error, None);
+        assert!(target.join(".git").exists());
+        assert!(!old.exists());
+        assert!(config.path().join("chats/demo/feature-x").exists());
+
+        let registry = load_project_registry(config.path()).unwrap();
+        let moved = &registry.projects[0].workspaces[0];
+        assert_eq!(moved.path, target.to_string_lossy());
+
+        let again = migrate_workspaces(config.path(), "p1", false)
+            .await
+            .unwrap();
+        assert!(again.moves.is_empty());
+        assert_eq!(again.unchanged, 1);
+    }
+}
@@ -0,0 +1,595 @@
+//! Setting up a new workspace: the project's setup steps and `postCreate`.
+//!
+//! # Overview
+//!
+//! A project lists named [`SetupStep`]s (`setupSteps` in `projects.json`):
+//! copy files such as `.env` from the main checkout, install dependencies, or
+//! run a shell command. The legacy `postCreate` command runs last, as a shell
+//! step named `postCreate`. Shell commands get `WORKSPACE_ROOT` and
+//! `PROJECT_ROOT` in their environment and run in the `agentShell` from
+//! `config.json`.
+//!
+//! [`run_workspace_setup`] runs the steps in order and stops at the first
+//! failure. Progress is recorded on the workspace ([`WorkspaceSetup`]) and
+//! streamed over the [`EventBus`] as [`SetupEvent`]s on
+//! `workspace:setup:{workspace_id}`, so the desktop app and HTTP clients see
+//! output as it arrives.
+//!
+//! ```ignore
+//! let setup = run_workspace_setup(event_bus, &config_dir, &project_id, &workspace_id).await?;
+//! if let Some(setup) = setup.filter(|s| s.status == SetupStatus::Failed) {
+//!     report(setup.steps.iter().find(|s| s.status == SetupStatus::Failed));
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::event_bus::EventBus;
+use crate::persistence::{
+    load_project_registry, update_project_registry, Project, SetupAction, SetupStatus, SetupStep,
+    SetupStepResult, WorkspaceSetup,
+};
+use crate::shell::build_shell_script_command;
+
+/// Event type prefix; the workspace ID follows.
+pub const SETUP_EVENT_PREFIX: &str = "workspace:setup:";
+
+/// Name of the step that runs the project's `postCreate` command.
+pub const POST_CREATE_STEP: &str = "postCreate";
+
+/// Lines of output kept on a failed step.
+const OUTPUT_TAIL_LINES: usize = 40;
+
+/// Lockfiles, most specific first, and the command that installs from them.
+const INSTALLERS: &[(&str, &str)] = &[
+    ("bun.lockb", "bun install"),
+    ("bun.lock", "bun install"),
+    ("pnpm-lock.yaml", "pnpm install"),
+    ("yarn.lock", "yarn install"),
+    ("package-lock.json", "npm install"),
+    ("package.json", "npm install"),
+    ("uv.lock", "uv sync"),
+    ("poetry.lock", "poetry install"),
+    ("Gemfile.lock", "bundle install"),
+    ("Gemfile", "bundle install"),
+];
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+/// Error type for workspace setup.
+#[derive(Debug)]
+pub enum SetupError {
+    /// Project or workspace not found
+    NotFound(String),
+    /// `projects.json` couldn't be read
+    Registry(String),
+}
+
+impl std::fmt::Display for SetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetupError::NotFound(what) => write!(f, "{what} not found"),
+            SetupError::Registry(e) => write!(f, "Failed to read projects: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SetupError {}
+
+/// Which stream a line of output came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Emitted on `workspace:setup:{workspace_id}` while setup runs.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SetupEvent {
+    /// Setup began; `steps` are the step names in order.
+    Started {
+        steps: Vec<String>,
+    },
+    StepStarted {
+        index: usize,
+        name: String,
+    },
+    Output {
+        index: usize,
+        stream: OutputStream,
+        line: String,
+    },
+    StepFinished {
+        index: usize,
+        result: SetupStepResult,
+    },
+    /// Setup ended; `setup` is what was recorded on the workspace.
+    Finished {
+        setup: WorkspaceSetup,
+    },
+}
+
+// ============================================================================
+// RUNNING
+// ============================================================================
+
+/// The steps to run for `project`: its `setup_steps`, then `postCreate`.
+pub fn setup_steps(project: &Project) -> Vec<SetupStep> {
+    let mut steps = project.setup_steps.clone();
+    if let Some(command) = project
+        .post_create
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+    {
+        steps.push(SetupStep {
+            name: POST_CREATE_STEP.to_string(),
+            action: SetupAction::Shell {
+                command: command.to_string(),
+            },
+        });
+    }
+    steps
+}
+
+/// Run the project's setup steps in a workspace.
+///
+/// Each step's status is saved to `projects.json` as it finishes; failing to
+/// save is logged, not fatal. Steps after a failed one are skipped.
+///
+/// # Returns
+///
+/// The recorded setup, or `None` if the project has no steps.
+///
+/// # Errors
+///
+/// Returns an error if the project or workspace can't be found. Step failures
+/// are reported in the result, not as errors.
+pub async fn run_workspace_setup(
+    event_bus: Arc<EventBus>,
+    config_dir: &Path,
+    project_id: &str,
+    workspace_id: &str,
+) -> Result<Option<WorkspaceSetup>, SetupError> {
+    let registry =
+        load_project_registry(config_dir).map_err(|e| SetupError::Registry(e.to_string()))?;
+    let project = registry
+        .projects
+        .iter()
+        .find(|p| p.id == project_id)
+        .ok_or_else(|| SetupError::NotFound(format!("Project {project_id}")))?;
+    let workspace = project
+        .get_workspaces()
+        .iter()
+        .find(|w| w.id == workspace_id)
+        .ok_or_else(|| SetupError::NotFound(format!("Workspace {workspace_id}")))?;
+
+    let steps = setup_steps(project);
+    if steps.is_empty() {
+        return Ok(None);
+    }
+
+    let run = StepContext {
+        event_bus,
+        event_type: format!("{SETUP_EVENT_PREFIX}{workspace_id}"),
+        project_root: PathBuf::from(&project.path),
+        workspace_root: PathBuf::from(&workspace.path),
+        shell_prefix: load_agent_shell(config_dir),
+    };
+
+    let mut setup = WorkspaceSetup {
+        status: SetupStatus::Running,
+        steps: steps
+            .iter()
+            .map(|step| SetupStepResult {
+                name: step.name.clone(),
+                status: SetupStatus::Pending,
+                exit_code: None,
+                error: None,
+                output: None,
+            })
+            .collect(),
+        started_at: Utc::now(),
+        finished_at: None,
+    };
+    record_setup(config_dir, project_id, workspace_id, &setup);
+    run.emit(&SetupEvent::Started {
+        steps: steps.iter().map(|s| s.name.clone()).collect(),
+    });
+
+    let mut failed = false;
+    for (index, step) in steps.into_iter().enumerate() {
+        if failed {
+            setup.steps[index].status = SetupStatus::Skipped;
+            continue;
+        }
+        run.emit(&SetupEvent::StepStarted {
+            index,
+            name: step.name.clone(),
+        });
+        setup.steps[index].status = SetupStatus::Running;
+
+        let result = run.run_step(index, step).await;
+        failed = result.status == SetupStatus::Failed;
+        setup.steps[index] = result.clone();
+        record_setup(config_dir, project_id, workspace_id, &setup);
+        run.emit(&SetupEvent::StepFinished { index, result });
+    }
+
+    setup.status = if failed {
+        SetupStatus::Failed
+    } else {
+        SetupStatus::Succeeded
+    };
+    setup.finished_at = Some(Utc::now());
+    record_setup(config_dir, project_id, workspace_id, &setup);
+    run.emit(&SetupEvent::Finished {
+        setup: setup.clone(),
+    });
+    Ok(Some(setup))
+}
+
+/// Save `setup` on the workspace, re-reading `projects.json` so concurrent
+/// edits aren't lost.
+fn record_setup(config_dir: &Path, project_id: &str, workspace_id: &str, setup: &WorkspaceSetup) {
+    let saved = update_project_registry(config_dir, |registry| {
+        let workspace = registry
+            .projects
+            .iter_mut()
+            .filter(|p| p.id == project_id)
+            .flat_map(|p| p.get_workspaces_mut().iter_mut())
+            .find(|w| w.id == workspace_id);
+        if let Some(workspace) = workspace {
+            workspace.setup = Some(setup.clone());
+        }
+    });
+    if let Err(e) = saved {
+        log::warn!("Failed to record setup of workspace {workspace_id}: {e}");
+    }
+}
+
+/// `agentShell` from `config.json`, used as the shell prefix.
+pub(crate) fn load_agent_shell(config_dir: &Path) -> Option<String> {
+    let config = std::fs::read_to_string(config_dir.join("config.json")).ok()?;
+    let config: serde_json::Value = serde_json::from_str(&config).ok()?;
+    config
+        .get("agentShell")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// The install command for the lockfile in `workspace_root`, if any.
+pub fn detect_install_command(workspace_root: &Path) -> Option<&'static str> {
+    INSTALLERS
+        .iter()
+        .find(|(file, _)| workspace_root.join(file).exists())
+        .map(|(_, command)| *command)
+}
+
+// ============================================================================
+// STEPS
+// ============================================================================
+
+/// What every step of one setup run shares.
+struct StepContext {
+    event_bus: Arc<EventBus>,
+    event_type: String,
+    project_root: PathBuf,
+    workspace_root: PathBuf,
+    shell_prefix: Option<String>,
+}
+
+impl StepContext {
+    fn emit(&self, event: &SetupEvent) {
+        self.event_bus.emit(&self.event_type, event);
+    }
+
+    async fn run_step(&self, index: usize, step: SetupStep) -> SetupStepResult {
+        let mut result = SetupStepResult {
+            name: step.name,
+            status: SetupStatus::Succeeded,
+            exit_code: None,
+            error: None,
+            output: None,
+        };
+
+        let command = match step.action {
+            SetupAction::CopyFiles { paths } => {
+                if let Err(e) = self.copy_files(index, &paths) {
+                    result.status = SetupStatus::Failed;
+                    result.error = Some(e);
+                }
+                return result;
+            }
+            SetupAction::Install { command } => {
+                match command
+                    .or_else(|| detect_install_command(&self.workspace_root).map(str::to_string))
+                {
+                    Some(command) => command,
+                    None => {
+                        self.output(index, OutputStream::Stdout, "No lockfile found".into());
+                        result.status = SetupStatus::Skipped;
+                        return result;
+                    }
+                }
+            }
+            SetupAction::Shell { command } => command,
+        };
+
+        let tail = Arc::new(Mutex::new(VecDeque::new()));
+        match self.run_command(index, command, Arc::clone(&tail)).await {
+            Ok(0) => result.exit_code = Some(0),
+            Ok(code) => {
+                result.status = SetupStatus::Failed;
+                result.exit_code = Some(code);
+                result.error = Some(format!("Exited with code {code}"));
+            }
+            Err(e) => {
+                result.status = SetupStatus::Failed;
+                result.error = Some(e);
+            }
+        }
+        if result.status == SetupStatus::Failed {
+            let lines = tail
+                .lock()
+                .map(|t| Vec::from(t.clone()))
+                .unwrap_or_default();
+            if !lines.is_empty() {
+                result.output = Some(lines.join("\n"));
+            }
+        }
+        result
+    }
+
+    fn output(&self, index: usize, stream: OutputStream, line: String) {
+        self.emit(&SetupEvent::Output {
+            index,
+            stream,
+            line,
+        });
+    }
+
+    /// Copy `paths` from the main checkout into the workspace.
+    fn copy_files(&self, index: usize, paths: &[String]) -> Result<(), String> {
+        for path in paths {
+            let relative = Path::new(path);
+            if !relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+            {
+                return Err(format!(
+                    "{path}: only paths inside the project can be copied"
+                ));
+            }
+            let source = self.project_root.join(relative);
+            let dest = self.workspace_root.join(relative);
+            let note = if !source.exists() {
+                "missing in the main checkout, skipped"
+            } else if dest.exists() {
+                "already in the workspace, kept"
+            } else {
+                copy_recursively(&source, &dest).map_err(|e| format!("{path}: {e}"))?;
+                "copied"
+            };
+            self.output(index, OutputStream::Stdout, format!("{path}: {note}"));
+        }
+        Ok(())
+    }
+
+    /// Run `command` in the workspace, streaming its output.
+    ///
+    /// # Returns
+    ///
+    /// The exit code (-1 if killed by a signal).
+    async fn run_command(
+        &self,
+        index: usize,
+        command: String,
+        tail: Arc<Mutex<VecDeque<String>>>,
+    ) -> Result<i32, String> {
+        let workspace_root = self.workspace_root.to_string_lossy().to_string();
+        let project_root = self.project_root.to_string_lossy().to_string();
+        let mut cmd = build_shell_script_command(
+            &command,
+            &workspace_root,
+            self.shell_prefix.as_deref(),
+            &[
+                ("WORKSPACE_ROOT", workspace_root.as_str()),
+                ("PROJECT_ROOT", project_root.as_str()),
+            ],
+        )?;
+        cmd.stdin(std::process::Stdio::null());
+
+        let event_bus = Arc::clone(&self.event_bus);
+        let event_type = self.event_type.clone();
+        let on_line = move |stream: OutputStream, line: String| {
+            if let Ok(mut tail) = tail.lock() {
+                if tail.len() == OUTPUT_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line.clone());
+            }
+            event_bus.emit(
+                &event_type,
+                &SetupEvent::Output {
+                    index,
+                    stream,
+                    line,
+                },
+            );
+        };
+
+        tokio::task::spawn_blocking(move || {
+            let mut child = cmd
+                .spawn()
+                .map_err(|e| format!("Failed to run command: {e}"))?;
+            let stdout = child.stdout.take();
+            let stderr = child.stderr.take();
+            let on_stderr = on_line.clone();
+            let stderr_reader = std::thread::spawn(move || {
+                if let Some(stderr) = stderr {
+                    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                        on_stderr(OutputStream::Stderr, line);
+                    }
+                }
+            });
+            if let Some(stdout) = stdout {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    on_line(OutputStream::Stdout, line);
+                }
+            }
+            let _ = stderr_reader.join();
+            let status = child
+                .wait()
+                .map_err(|e| format!("Failed to run command: {e}"))?;
+            Ok(status.code().unwrap_or(-1))
+        })
+        .await
+        .map_err(|e| format!("Setup step panicked: {e}"))?
+    }
+}
+
+fn copy_recursively(source: &Path, dest: &Path) -> std::io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if !source.is_dir() {
+        std::fs::copy(source, dest)?;
+        return Ok(());
+    }
+    std::fs::create_dir_all(dest)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        copy_recursively(&entry.path(), &dest.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{save_project_registry, ProjectRegistry};
+    use tempfile::TempDir;
+
+    fn project(root: &Path, workspace: &Path, steps: serde_json::Value) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "id": "p1",
+            "name": "demo",
+            "path": root.to_string_lossy(),
+            "postCreate": "echo done from $PROJECT_ROOT",
+            "setupSteps": steps,
+            "workspaces": [{
+                "id": "w1",
+                "branch": "feature",
+                "path": workspace.to_string_lossy(),
+                "createdAt": "2026-01-01T00:00:00Z",
+            }],
+        }))
+        .unwrap()
+    }
+
+    fn drain(
+        rx: &mut tokio::sync::broadcast::Receiver<crate::event_bus::BroadcastEvent>,
+    ) -> Vec<serde_json::Value> {
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            assert_eq!(event.event_type, "workspace:setup:w1");
+            events.push(event.payload);
+        }
+        events
+    }
+
+    #[test]
+    fn post_create_runs_after_setup_steps() {
+        let steps = serde_json::json!([{ "name": "deps", "kind": "install" }]);
+        let p = project(Path::new("/repo"), Path::new("/ws"), steps);
+
+        let steps = setup_steps(&p);
+
+        let names: Vec<_> = steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["deps", POST_CREATE_STEP]);
+        assert_eq!(steps[0].action, SetupAction::Install { command: None });
+    }
+
+    #[test]
+    fn install_command_follows_lockfile() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(detect_install_command(dir.path()), None);
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        assert_eq!(detect_install_command(dir.path()), Some("npm install"));
+        std::fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        assert_eq!(detect_install_command(dir.path()), Some("pnpm install"));
+    }
+
+    #[tokio::test]
+    async fn runs_steps_streams_output_and_records_failure() {
+        let root = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        let config = TempDir::new().unwrap();
+        std::fs::write(root.path().join(".env"), "KEY=1").unwrap();
+        std::fs::write(
+            config.path().join("config.json"),
+            r#"{"agentShell": "/bin/sh -c"}"#,
+        )
+        .unwrap();
+        let steps = serde_json::json!([
+            { "name": "env", "kind": "copyFiles", "paths": [".env", "missing.txt"] },
+            { "name": "greet", "kind": "shell", "command": "echo hi; echo oops >&2" },
+            { "name": "break", "kind": "shell", "command": "echo failing; exit 3" },
+        ]);
+        let registry = ProjectRegistry {
+            projects: vec![project(root.path(), workspace.path(), steps)],
+        };
+        save_project_registry(config.path(), &registry, None).unwrap();
+
+        let bus = Arc::new(EventBus::new());
+        let mut rx = bus.subscribe();
+        let setup = run_workspace_setup(Arc::clone(&bus), config.path(), "p1", "w1")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(setup.status, SetupStatus::Failed);
+        let statuses: Vec<_> = setup.steps.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                SetupStatus::Succeeded,
+                SetupStatus::Succeeded,
+                SetupStatus::Failed,
+                SetupStatus::Skipped
+            ]
+        );
+        assert_eq!(setup.steps[2].exit_code, Some(3));
+        assert_eq!(setup.steps[2].output.as_deref(), Some("failing"));
+        assert_eq!(
+            std::fs::read_to_string(workspace.path().join(".env")).unwrap(),
+            "KEY=1"
+        );
+
+        let events = drain(&mut rx);
+        assert_eq!(events[0]["kind"], "started");
+        assert!(events
+            .iter()
+            .any(|e| e["kind"] == "output" && e["stream"] == "stderr" && e["line"] == "oops"));
+        assert_eq!(events.last().unwrap()["kind"], "finished");
+
+        let saved = load_project_registry(config.path()).unwrap();
+        assert_eq!(saved.projects[0].workspaces[0].setup.as_ref(), Some(&setup));
+    }
+}
@@ -0,0 +1,346 @@
+//! Tearing down a workspace before it is archived.
+//!
+//! Removing a worktree doesn't stop what runs in it: dev servers started in a
+//! terminal or by an agent keep running and holding their ports. Before the
+//! worktree goes, [`archive_workspace_with_teardown`]:
+//!
+//! 1. runs the project's `preArchive` hook in the workspace, while everything
+//!    is still up (e.g. `docker compose down`), with `WORKSPACE_ROOT` and
+//!    `PROJECT_ROOT` set and in the `agentShell` from `config.json`;
+//! 2. stops the processes Overseer started in the workspace (terminals,
+//!    agents and everything they started; see [`crate::processes`]);
+//! 3. removes the worktree with [`crate::git::archive_workspace`] (or the
+//!    project's other [`Vcs`] backend).
+//!
+//! A failing hook is reported but doesn't stop the archive. The returned
+//! [`TeardownReport`] lists what was stopped and the ports it held, and the
+//! processes Overseer didn't start that still run in the workspace directory.
+//! Those are left alone: they may be the user's own.
+//!
+//! Without `force`, a dirty worktree is refused *before* anything is torn
+//! down, so the confirm-and-retry flow runs the hook only once. Jujutsu
+//! workspaces are never dirty: their changes are already in commits.
+
+use std::collections::VecDeque;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::git::multi;
+use crate::git::plain;
+use crate::git::{run_git, GitError};
+use crate::persistence::{find_project_by_workspace_path, load_project_registry};
+use crate::processes::{
+    find_workspace_processes, terminate, ProcessSource, StoppedProcess, WorkspaceProcess,
+};
+use crate::shell::run_shell_command_async;
+use crate::vcs::Vcs;
+use crate::workspace_setup::load_agent_shell;
+
+/// Lines of hook output kept when the hook fails.
+const HOOK_OUTPUT_TAIL_LINES: usize = 40;
+
+/// Outcome of the project's `preArchive` hook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookResult {
+    pub command: String,
+    pub success: bool,
+    /// Exit code (-1 if the hook couldn't run or was killed by a signal).
+    pub exit_code: i32,
+    /// The last lines of output, kept when the hook failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+/// What tearing down a workspace did.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeardownReport {
+    /// `None` when the project has no `preArchive` hook.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook: Option<HookResult>,
+    /// Processes Overseer started under the workspace, now stopped.
+    pub processes: Vec<StoppedProcess>,
+    /// Processes running in the workspace directory that Overseer didn't
+    /// start. They are left running.
+    pub left_running: Vec<WorkspaceProcess>,
+}
+
+/// Run the `preArchive` hook and stop the workspace's processes, then remove
+/// its worktree.
+///
+/// `config_dir` locates `projects.json` (for the hook) and `config.json` (for
+/// the shell); without it only processes are stopped.
+///
+/// # Errors
+///
+/// [`GitError::WorktreeDirty`] when `force` is false and the worktree has
+/// changes (nothing is torn down), or any error from
+/// [`Vcs::archive_workspace`].
+pub async fn archive_workspace_with_teardown(
+    config_dir: Option<&Path>,
+    repo_path: &Path,
+    workspace_path: &Path,
+    force: bool,
+) -> Result<TeardownReport, GitError> {
+    let vcs = Vcs::resolve(config_dir, workspace_path);
+    if !force && has_changes(vcs, workspace_path).await {
+        return Err(GitError::WorktreeDirty {
+            path: workspace_path.to_string_lossy().to_string(),
+        });
+    }
+
+    let report = teardown_workspace(config_dir, workspace_path).await;
+    vcs.archive_workspace(repo_path, workspace_path, force)
+        .await?;
+    Ok(report)
+}
+
+/// Run the `preArchive` hook, then stop the processes Overseer started under
+/// `workspace_path`.
+pub async fn teardown_workspace(
+    config_dir: Option<&Path>,
+    workspace_path: &Path,
+) -> TeardownReport {
+    let hook = match config_dir {
+        Some(config_dir) => run_pre_archive_hook(config_dir, workspace_path).await,
+        None => None,
+    };
+
+    let workspace = workspace_path.to_path_buf();
+    let (processes, left_running) = tokio::task::spawn_blocking(move || {
+        let (ours, others): (Vec<_>, Vec<_>) = find_workspace_processes(&workspace)
+            .into_iter()
+            .partition(|p| p.source != ProcessSource::Workspace);
+        if !ours.is_empty() {
+            log::info!(
+                "Stopping {} process(es) in {}",
+                ours.len(),
+                workspace.display()
+            );
+        }
+        if !others.is_empty() {
+            log::info!(
+                "Leaving {} process(es) Overseer didn't start running in {}",
+                others.len(),
+                workspace.display()
+            );
+        }
+        (terminate(ours), others)
+    })
+    .await
+    .unwrap_or_else(|e| {
+        log::warn!("Stopping workspace processes panicked: {e}");
+        (Vec::new(), Vec::new())
+    });
+
+    TeardownReport {
+        hook,
+        processes,
+        left_running,
+    }
+}
+
+/// Whether the backend would refuse to remove the workspace without
+/// `--force`. Jujutsu snapshots the working copy, so it never does.
+async fn has_changes(vcs: Vcs, workspace_path: &Path) -> bool {
+    match vcs {
+        Vcs::Git => {}
+        Vcs::Jujutsu => return false,
+        Vcs::Plain => return plain::has_changes(workspace_path).await,
+        Vcs::MultiRepo => return multi::has_changes(workspace_path).await,
+    }
+    match run_git(&["status", "--porcelain"], workspace_path).await {
+        Ok(output) => output.success && !output.stdout_str().trim().is_empty(),
+        // Let `git worktree remove` report a missing or broken worktree.
+        Err(_) => false,
+    }
+}
+
+async fn run_pre_archive_hook(config_dir: &Path, workspace_path: &Path) -> Option<HookResult> {
+    let registry = load_project_registry(config_dir).ok()?;
+    let workspace = workspace_path.to_string_lossy().to_string();
+    let project = find_project_by_workspace_path(&registry, &workspace)?;
+    let command = project
+        .pre_archive
+        .clone()
+        .filter(|c| !c.trim().is_empty())?;
+    let shell_prefix = load_agent_shell(config_dir);
+
+    let result = run_shell_command_async(
+        &command,
+        &workspace,
+        shell_prefix.as_deref(),
+        &[
+            ("WORKSPACE_ROOT", workspace.as_str()),
+            ("PROJECT_ROOT", project.path.as_str()),
+        ],
+    )
+    .await;
+
+    Some(match result {
+        Ok(result) if result.success => HookResult {
+            command,
+            success: true,
+            exit_code: result.exit_code,
+            output: None,
+        },
+        Ok(result) => {
+            log::warn!(
+                "preArchive hook failed in {workspace} (exit {})",
+                result.exit_code
+            );
+            HookResult {
+                command,
+                success: false,
+                exit_code: result.exit_code,
+                output: Some(tail(&format!("{}{}", result.stdout, result.stderr))),
+            }
+        }
+        Err(e) => HookResult {
+            command,
+            success: false,
+            exit_code: -1,
+            output: Some(e),
+        },
+    })
+}
+
+/// The last [`HOOK_OUTPUT_TAIL_LINES`] lines of `output`.
+fn tail(output: &str) -> String {
+    let mut lines = VecDeque::with_capacity(HOOK_OUTPUT_TAIL_LINES);
+    for line in output.lines() {
+        if lines.len() == HOOK_OUTPUT_TAIL_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+    Vec::from(lines).join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{save_project_registry, ProjectRegistry};
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    /// A repo with one commit and a worktree for `feature`, registered in
+    /// `config` with `pre_archive`.
+    fn setup(pre_archive: &str) -> (TempDir, TempDir, std::path::PathBuf) {
+        let repo = TempDir::new().unwrap();
+        let config = TempDir::new().unwrap();
+        git(repo.path(), &["init", "-q", "-b", "main"]);
+        git(repo.path(), &["config", "user.email", "t@example.com"]);
+        git(repo.path(), &["config", "user.name", "Test"]);
+        std::fs::write(repo.path().join("README.md"), "hi").unwrap();
+        git(repo.path(), &["add", "."]);
+        git(repo.path(), &["commit", "-q", "-m", "init"]);
+        let workspace = repo.path().join("ws-feature");
+        git(
+            repo.path(),
+            &["worktree", "add", "-q", "-b", "feature", "ws-feature"],
+        );
+
+        let project = serde_json::json!({
+            "id": "p1",
+            "name": "demo",
+            "path": repo.path().to_string_lossy(),
+            "preArchive": pre_archive,
+            "workspaces": [{
+                "id": "w1",
+                "branch": "feature",
+                "path": workspace.to_string_lossy(),
+                "createdAt": "2026-01-01T00:00:00Z",
+            }],
+        });
+        let registry = ProjectRegistry {
+            projects: vec![serde_json::from_value(project).unwrap()],
+        };
+        std::fs::write(
+            config.path().join("config.json"),
+            r#"{"agentShell": "/bin/sh -c"}"#,
+        )
+        .unwrap();
+        save_project_registry(config.path(), &registry, None).unwrap();
+        (repo, config, workspace)
+    }
+
+    #[tokio::test]
+    async fn dirty_worktree_is_refused_before_the_hook_runs() {
+        let (repo, config, workspace) = setup("touch $PROJECT_ROOT/hook-ran");
+        std::fs::write(workspace.join("scratch.txt"), "wip").unwrap();
+
+        let result =
+            archive_workspace_with_teardown(Some(config.path()), repo.path(), &workspace, false)
+                .await;
+
+        assert!(matches!(result, Err(GitError::WorktreeDirty { .. })));
+        assert!(!repo.path().join("hook-ran").exists());
+        assert!(workspace.exists());
+    }
+
+    #[tokio::test]
+    async fn runs_hook_and_reports_failure_then_archives() {
+        let (repo, config, workspace) =
+            setup("touch $PROJECT_ROOT/hook-ran; echo cleaning up; exit 2");
+
+        let report =
+            archive_workspace_with_teardown(Some(config.path()), repo.path(), &workspace, false)
+                .await
+                .unwrap();
+
+        let hook = report.hook.unwrap();
+        assert!(!hook.success);
+        assert_eq!(hook.exit_code, 2);
+        assert_eq!(hook.output.as_deref(), Some("cleaning up"));
+        assert!(repo.path().join("hook-ran").exists());
+        assert!(!workspace.exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn stops_only_the_processes_overseer_started() {
+        let (repo, config, workspace) = setup("");
+        let spawn_sleep = || {
+            Command::new("sleep")
+                .arg("30")
+                .current_dir(&workspace)
+                .spawn()
+                .unwrap()
+        };
+        let mut server = spawn_sleep();
+        crate::processes::tracker().track(server.id(), &workspace, ProcessSource::Terminal);
+        let mut unrelated = spawn_sleep();
+
+        let report =
+            archive_workspace_with_teardown(Some(config.path()), repo.path(), &workspace, false)
+                .await
+                .unwrap();
+        crate::processes::tracker().untrack(server.id());
+
+        assert_eq!(report.hook, None);
+        let stopped = report
+            .processes
+            .iter()
+            .find(|p| p.process.pid == server.id())
+            .expect("sleep was stopped");
+        assert_eq!(stopped.error, None);
+        assert!(server.wait().unwrap().code().is_none());
+
+        assert!(report.left_running.iter().any(|p| p.pid == unrelated.id()));
+        assert!(unrelated.try_wait().unwrap().is_none(), "left running");
+        unrelated.kill().unwrap();
+        unrelated.wait().unwrap();
+    }
+}
@@ -15,7 +15,13 @@
 //!       --token <TOKEN>       Use a specific auth token (implies --auth)
 //!       --config-dir <DIR>    Config directory (overrides default)
 //!       --dev                 Use dev config paths (~/.config/overseer-dev/)
+//!       --migrate-to-sqlite   Move the config dir's JSON documents into overseer.db first
+//!       --encrypt-chats       Encrypt the config dir's chats in place first
+//!       --decrypt-chats       Decrypt the config dir's chats in place first
 //! ```
+//!
+//! Encrypted chats use the keyfile in the config dir, or a passphrase from
+//! `OVERSEER_CHAT_PASSPHRASE` if it's set when they're encrypted.
 
 use axum::{
     body::Body,
@@ -53,6 +59,21 @@ struct Args {
     /// Use dev config paths (~/.config/overseer-dev/ and ~/overseer/workspaces-dev/)
     #[arg(long)]
     dev: bool,
+
+    /// Move projects, chat indexes and chat metadata from the JSON files into
+    /// an SQLite database (overseer.db) before starting. Runs once per config dir.
+    #[arg(long)]
+    migrate_to_sqlite: bool,
+
+    /// Encrypt chat logs, metadata and attachments in place before starting,
+    /// with a passphrase from OVERSEER_CHAT_PASSPHRASE or a new keyfile.
+    #[arg(long, conflicts_with = "decrypt_chats")]
+    encrypt_chats: bool,
+
+    /// Decrypt chat logs, metadata and attachments in place before starting
+    /// and remove the key.
+    #[arg(long)]
+    decrypt_chats: bool,
 }
 
 // Embed the compiled frontend from the dist/ directory at build time.
@@ -153,6 +174,58 @@ async fn main() {
     let log_dir = config_dir.join("logs");
     let _ = std::fs::create_dir_all(&log_dir);
 
+    // Open the key for encrypted chats, so they can be read and written
+    let passphrase = overseer_core::persistence::passphrase_from_env();
+    match overseer_core::persistence::open_encryption(&config_dir, passphrase.as_deref()) {
+        Ok(true) => log::info!("Chats are encrypted"),
+        Ok(false) => {}
+        Err(e) => {
+            eprintln!("Failed to open the chat key: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if args.migrate_to_sqlite {
+        match overseer_core::persistence::migrate_to_sqlite(&config_dir) {
+            Ok(report) => println!(
+                "Migrated to SQLite: {} projects, {} chat indexes, {} chats, {} approval sets",
+                report.projects, report.chat_indexes, report.chats, report.approvals
+            ),
+            Err(e) => {
+                eprintln!("Failed to migrate to SQLite: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Use the config dir's SQLite database if it was migrated to one
+    match overseer_core::persistence::open_storage(&config_dir) {
+        Ok(backend) => log::info!("Storage backend: {:?}", backend),
+        Err(e) => {
+            eprintln!("Failed to open storage: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if args.encrypt_chats || args.decrypt_chats {
+        let result = if args.encrypt_chats {
+            overseer_core::persistence::encrypt_config_dir(&config_dir, passphrase.as_deref())
+        } else {
+            overseer_core::persistence::decrypt_config_dir(&config_dir, passphrase.as_deref())
+        };
+        let action = if args.encrypt_chats { "Encrypted" } else { "Decrypted" };
+        match result {
+            Ok(report) => println!(
+                "{} {} chat logs, {} chat metadata documents, {} attachments",
+                action, report.chats, report.metadata, report.attachments
+            ),
+            Err(e) => {
+                eprintln!("Failed to convert chats: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     // Create OverseerContext (the central shared state)
     let context = Arc::new(
         overseer_core::OverseerContext::builder()
@@ -164,6 +237,10 @@ async fn main() {
     context.approval_manager.set_config_dir(config_dir.clone());
     context.chat_sessions.set_config_dir(config_dir.clone());
 
+    // Report changes another process (the desktop app) makes to projects,
+    // chat indexes and approvals to connected clients
+    let _config_watcher = context.watch_config_dir();
+
     // Determine auth token: explicit --token takes priority, --auth auto-generates
     let auth_token = if let Some(token) = args.token.clone() {
         Some(token)
@@ -211,6 +288,9 @@ async fn main() {
 
     println!("\nShutting down...");
     handle.stop();
+    if let Err(e) = overseer_core::persistence::remove_readable_attachments() {
+        eprintln!("Failed to remove decrypted attachments: {}", e);
+    }
 }
 
 #[cfg(test)]
@@ -248,6 +328,9 @@ mod tests {
             token: Some("my-secret-token".to_string()),
             config_dir: None,
             dev: false,
+            migrate_to_sqlite: false,
+            encrypt_chats: false,
+            decrypt_chats: false,
         };
         let auth_token = if let Some(token) = args.token.clone() {
             Some(token)
@@ -268,6 +351,9 @@ mod tests {
             token: None,
             config_dir: None,
             dev: false,
+            migrate_to_sqlite: false,
+            encrypt_chats: false,
+            decrypt_chats: false,
         };
         let auth_token = if let Some(token) = args.token.clone() {
             Some(token)
@@ -290,6 +376,9 @@ mod tests {
             token: None,
             config_dir,
             dev,
+            migrate_to_sqlite: false,
+            encrypt_chats: false,
+            decrypt_chats: false,
         }
     }
 
@@ -81,7 +81,7 @@ pub(crate) async fn upload_attachment(
     std::fs::create_dir_all(&dir)
         .map_err(|e| ApiError::internal(format!("Failed to create attachment directory: {e}")))?;
     let path = dir.join(&filename);
-    std::fs::write(&path, &body)
+    overseer_core::persistence::write_attachment(&path, &body)
         .map_err(|e| ApiError::internal(format!("Failed to write attachment: {e}")))?;
 
     Ok(ApiEnvelope::ok(AttachmentDto {
@@ -127,13 +127,19 @@ pub(crate) struct AttachmentInput {
 
 /// Build the prompt the agent actually receives: the attachment paths prepended
 /// to the message, so the agent can read the files. Matches `ChatStore.ts`.
-pub(crate) fn build_agent_prompt(text: &str, attachments: &[AttachmentInput]) -> String {
+/// Encrypted attachments are given as the path of a decrypted copy, removed
+/// when the session's turn ends.
+pub(crate) fn build_agent_prompt(
+    text: &str,
+    attachments: &[AttachmentInput],
+    session_id: &str,
+) -> String {
     if attachments.is_empty() {
         return text.to_string();
     }
     let path_list = attachments
         .iter()
-        .map(|attachment| format!("- {}", attachment.path))
+        .map(|attachment| format!("- {}", readable_path(&attachment.path, session_id)))
         .collect::<Vec<_>>()
         .join("\n");
     format!("[Attached files:\n{path_list}]\n\n{text}")
@@ -167,6 +173,19 @@ pub(crate) fn attachments_meta(attachments: &[AttachmentInput]) -> Option<serde_
     Some(serde_json::json!({ "attachments": list }))
 }
 
+/// Where the agent can read the attachment at `path`. Falls back to `path`
+/// itself (e.g. a file outside the attachments directory that isn't there).
+fn readable_path(path: &str, session_id: &str) -> String {
+    let path_buf = std::path::Path::new(path);
+    match overseer_core::persistence::readable_attachment_path(path_buf, Some(session_id)) {
+        Ok(readable) => readable.to_string_lossy().to_string(),
+        Err(e) => {
+            log::warn!("Can't prepare attachment {path} for the agent: {e}");
+            path.to_string()
+        }
+    }
+}
+
 fn basename(path: &str) -> String {
     std::path::Path::new(path)
         .file_name()
@@ -195,7 +214,10 @@ mod tests {
 
     #[test]
     fn prompt_unchanged_without_attachments() {
-        assert_eq!(build_agent_prompt("do the thing", &[]), "do the thing");
+        assert_eq!(
+            build_agent_prompt("do the thing", &[], "s1"),
+            "do the thing"
+        );
     }
 
     #[test]
@@ -204,7 +226,7 @@ mod tests {
             attachment("/cfg/attachments/a/spec.md"),
             attachment("/cfg/attachments/b/log.txt"),
         ];
-        let prompt = build_agent_prompt("summarize these", &attachments);
+        let prompt = build_agent_prompt("summarize these", &attachments, "s1");
         assert_eq!(
             prompt,
             "[Attached files:\n- /cfg/attachments/a/spec.md\n- /cfg/attachments/b/log.txt]\n\nsummarize these"
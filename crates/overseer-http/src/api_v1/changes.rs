@@ -0,0 +1,203 @@
+//! What changed in a workspace: files with line counts, the branch's
+//! commits, and parsed per-file diffs.
+//!
+//! Lets a driver summarize "what did the agent do" without fetching diffs,
+//! then fetch a file's hunks (with line numbers to anchor comments on) when
+//! it needs the detail.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+
+use overseer_core::git::{ChangedFile, Commit, DiffOptions, FileDiff, WhitespaceMode};
+use overseer_core::persistence::load_project_registry;
+use overseer_core::vcs::Vcs;
+
+use super::{resolve_workspace, ApiEnvelope, ApiError};
+use crate::HttpSharedState;
+
+#[derive(Serialize, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChangeSummary {
+    files_changed: usize,
+    additions: u64,
+    deletions: u64,
+    /// Files whose line counts are unknown (binary or too large).
+    binary_files: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChangesResponse {
+    /// Committed on the branch, compared to the default branch.
+    files: Vec<ChangedFile>,
+    /// Staged, unstaged and untracked.
+    uncommitted: Vec<ChangedFile>,
+    /// Commits on the branch, oldest first.
+    commits: Vec<Commit>,
+    /// Totals over `files` and `uncommitted` together.
+    summary: ChangeSummary,
+}
+
+/// GET /api/v1/workspaces/{workspaceId}/changes
+pub(crate) async fn get_changes(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiEnvelope<ChangesResponse>>, ApiError> {
+    let location = resolve_workspace(&state, &workspace_id)?;
+    let working_dir = PathBuf::from(&location.working_dir);
+    let main_branch = project_main_branch(&state, &location.project_name);
+    let vcs = Vcs::resolve(state.get_config_dir().as_deref(), &working_dir);
+
+    let changed = vcs
+        .list_changed_files(&working_dir, main_branch.as_deref())
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let commits = vcs
+        .list_commits_on_branch(&working_dir, main_branch.as_deref())
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    let summary = summarize(changed.files.iter().chain(&changed.uncommitted));
+    Ok(ApiEnvelope::ok(ChangesResponse {
+        files: changed.files,
+        uncommitted: changed.uncommitted,
+        commits,
+        summary,
+    }))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct DiffQuery {
+    /// File to diff, relative to the workspace root.
+    path: Option<String>,
+    /// Status code from the changes listing (`M` when omitted).
+    status: Option<String>,
+    /// Diff the file as changed by this commit.
+    commit: Option<String>,
+    /// Diff uncommitted changes instead of the branch's committed ones.
+    uncommitted: bool,
+    whitespace: WhitespaceMode,
+    context_lines: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffResponse {
+    files: Vec<FileDiff>,
+}
+
+/// GET /api/v1/workspaces/{workspaceId}/diff?path=&status=&commit=&uncommitted=
+///
+/// The file's diff parsed into hunks and lines. `whitespace` and
+/// `contextLines` are passed through as [`DiffOptions`].
+pub(crate) async fn get_diff(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<ApiEnvelope<DiffResponse>>, ApiError> {
+    let file_path = query
+        .path
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| ApiError::bad_request("Missing required query parameter: path"))?;
+    if query.commit.is_some() && query.uncommitted {
+        return Err(ApiError::bad_request(
+            "commit and uncommitted are mutually exclusive",
+        ));
+    }
+
+    let location = resolve_workspace(&state, &workspace_id)?;
+    let working_dir = PathBuf::from(&location.working_dir);
+    let status = query.status.as_deref().unwrap_or("M");
+    let options = DiffOptions {
+        whitespace: query.whitespace,
+        context_lines: query.context_lines,
+    };
+    let vcs = Vcs::resolve(state.get_config_dir().as_deref(), &working_dir);
+
+    let files = match (&query.commit, query.uncommitted) {
+        (Some(commit), _) => {
+            vcs.get_structured_commit_diff(&working_dir, commit, &file_path, status, &options)
+                .await
+        }
+        (None, true) => {
+            vcs.get_structured_uncommitted_diff(&working_dir, &file_path, status, &options)
+                .await
+        }
+        (None, false) => {
+            let main_branch = project_main_branch(&state, &location.project_name);
+            vcs.get_structured_file_diff(
+                &working_dir,
+                &file_path,
+                status,
+                main_branch.as_deref(),
+                &options,
+            )
+            .await
+        }
+    }
+    .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(ApiEnvelope::ok(DiffResponse { files }))
+}
+
+fn summarize<'a>(files: impl Iterator<Item = &'a ChangedFile>) -> ChangeSummary {
+    let mut summary = ChangeSummary::default();
+    for file in files {
+        summary.files_changed += 1;
+        match (file.additions, file.deletions) {
+            (Some(added), Some(deleted)) => {
+                summary.additions += added;
+                summary.deletions += deleted;
+            }
+            _ => summary.binary_files += 1,
+        }
+    }
+    summary
+}
+
+/// The project's configured default branch, if set.
+pub(super) fn project_main_branch(state: &HttpSharedState, project_name: &str) -> Option<String> {
+    let config_dir = state.get_config_dir()?;
+    let registry = load_project_registry(&config_dir).ok()?;
+    registry
+        .projects
+        .into_iter()
+        .find(|p| p.name == project_name)
+        .and_then(|p| p.main_branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_counts_unknown_stats_as_binary() {
+        let text = ChangedFile {
+            additions: Some(3),
+            deletions: Some(1),
+            ..ChangedFile::new("M", "a.rs")
+        };
+        let binary = ChangedFile {
+            is_binary: true,
+            ..ChangedFile::new("M", "logo.png")
+        };
+
+        let summary = summarize([text.clone(), text, binary].iter());
+        assert_eq!(
+            summary,
+            ChangeSummary {
+                files_changed: 3,
+                additions: 6,
+                deletions: 2,
+                binary_files: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Per-turn workspace checkpoints: list them, diff one against now, and
+//! rewind the workspace (and optionally the conversation) to one.
+//!
+//! Checkpoints are taken automatically when a message is sent; see
+//! `overseer_core::checkpoints`.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+
+use overseer_core::chat_fork::claude_root;
+use overseer_core::checkpoints::{self, RewindResult, TurnCheckpoint};
+use overseer_core::git::{self, DiffOptions, FileDiff, WhitespaceMode};
+
+use super::{resolve_session, ApiEnvelope, ApiError};
+use crate::HttpSharedState;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CheckpointsResponse {
+    /// Every user message of the session, oldest first, with its checkpoint
+    /// (`null` if none was taken, e.g. for a non-git workspace).
+    turns: Vec<TurnCheckpoint>,
+}
+
+/// GET /api/v1/sessions/{sessionId}/checkpoints
+pub(crate) async fn list_checkpoints(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<ApiEnvelope<CheckpointsResponse>>, ApiError> {
+    let location = resolve_session(&state, &session_id)?.location;
+
+    let turns = checkpoints::list_turn_checkpoints(
+        &state.context.chat_sessions,
+        &location.project_name,
+        &location.workspace_name,
+        &session_id,
+        &PathBuf::from(&location.working_dir),
+    )
+    .await
+    .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(ApiEnvelope::ok(CheckpointsResponse { turns }))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct DiffQuery {
+    whitespace: WhitespaceMode,
+    context_lines: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffResponse {
+    files: Vec<FileDiff>,
+}
+
+/// GET /api/v1/sessions/{sessionId}/checkpoints/{turnId}/diff
+///
+/// What changed in the workspace since the checkpoint, uncommitted and
+/// untracked files included.
+pub(crate) async fn diff_checkpoint(
+    State(state): State<Arc<HttpSharedState>>,
+    Path((session_id, turn_id)): Path<(String, String)>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<ApiEnvelope<DiffResponse>>, ApiError> {
+    let location = resolve_session(&state, &session_id)?.location;
+    let working_dir = PathBuf::from(&location.working_dir);
+
+    let checkpoint = git::find_checkpoint(&working_dir, &session_id, &turn_id)
+        .await
+        .map_err(|e| ApiError::bad_request(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found(format!("No checkpoint for turn {turn_id}")))?;
+    let options = DiffOptions {
+        whitespace: query.whitespace,
+        context_lines: query.context_lines,
+    };
+    let files = git::diff_checkpoint(&working_dir, &checkpoint, &options)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(ApiEnvelope::ok(DiffResponse { files }))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct RestoreBody {
+    /// Also cut the conversation back to just before this turn.
+    truncate_chat: bool,
+}
+
+/// POST /api/v1/sessions/{sessionId}/checkpoints/{turnId}/restore
+///
+/// Refused with 409 while the session's agent is running, since it would
+/// keep writing to the workspace being restored.
+pub(crate) async fn restore_checkpoint(
+    State(state): State<Arc<HttpSharedState>>,
+    Path((session_id, turn_id)): Path<(String, String)>,
+    body: Option<Json<RestoreBody>>,
+) -> Result<Json<ApiEnvelope<RewindResult>>, ApiError> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let location = resolve_session(&state, &session_id)?.location;
+
+    if state.context.claude_agents.is_running(&session_id) {
+        return Err(ApiError::conflict(
+            "The session's agent is running; stop it before restoring a checkpoint",
+        ));
+    }
+
+    let working_dir = PathBuf::from(&location.working_dir);
+    if git::find_checkpoint(&working_dir, &session_id, &turn_id)
+        .await
+        .map_err(|e| ApiError::bad_request(e.to_string()))?
+        .is_none()
+    {
+        return Err(ApiError::not_found(format!(
+            "No checkpoint for turn {turn_id}"
+        )));
+    }
+
+    let root = claude_root(
+        crate::routes::project_claude_config_dir(&state, &location.project_name).as_deref(),
+    );
+    let result = checkpoints::rewind_to_turn(
+        &state.context.chat_sessions,
+        &location.project_name,
+        &location.workspace_name,
+        &session_id,
+        &working_dir,
+        &turn_id,
+        body.truncate_chat,
+        root.as_deref(),
+    )
+    .await
+    .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(ApiEnvelope::ok(result))
+}
@@ -0,0 +1,108 @@
+//! Exporting a session as Markdown, HTML or a `.overseer-chat` bundle, and
+//! importing a bundle into a workspace; see `overseer_core::chat_export`.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use overseer_core::chat_export::ExportFormat;
+use overseer_core::chat_view::View;
+
+use super::{resolve_session, resolve_workspace, ApiEnvelope, ApiError};
+use crate::HttpSharedState;
+
+/// Max bundle size on import (256 MiB). Bundles carry their attachments
+/// base64-encoded, so they can be much bigger than a single upload.
+pub(crate) const MAX_BUNDLE_BYTES: usize = 256 * 1024 * 1024;
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub(crate) struct ExportQuery {
+    format: Option<String>,
+    view: Option<String>,
+}
+
+/// GET /api/v1/sessions/{sessionId}/export?format=markdown|html|bundle&view=text|full
+///
+/// Returns the file itself (not the JSON envelope) as an attachment download.
+/// `format` defaults to `markdown`, `view` to `full`.
+pub(crate) async fn export_session(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(session_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    let format = ExportFormat::parse(query.format.as_deref().unwrap_or("markdown"))
+        .map_err(ApiError::bad_request)?;
+    let view = View::from_query(Some(query.view.as_deref().unwrap_or("full")))
+        .map_err(ApiError::bad_request)?;
+    let resolved = resolve_session(&state, &session_id)?;
+
+    let sessions = Arc::clone(&state.context.chat_sessions);
+    let location = resolved.location;
+    let id = session_id.clone();
+    let body = tokio::task::spawn_blocking(move || {
+        sessions.export_chat(
+            &location.project_name,
+            &location.workspace_name,
+            &id,
+            format,
+            view,
+        )
+    })
+    .await
+    .map_err(|e| ApiError::internal(format!("Task join error: {e}")))?
+    .map_err(ApiError::internal)?;
+
+    let disposition = format!(
+        "attachment; filename=\"{session_id}.{}\"",
+        format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.mime_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImportSessionResponse {
+    session_id: String,
+}
+
+/// POST /api/v1/workspaces/{workspaceId}/sessions/import
+///
+/// Body is a `.overseer-chat` bundle. The imported session keeps its id
+/// unless the workspace already has a session with that id.
+pub(crate) async fn import_session(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+    body: String,
+) -> Result<Json<ApiEnvelope<ImportSessionResponse>>, ApiError> {
+    let location = resolve_workspace(&state, &workspace_id)?;
+
+    let sessions = Arc::clone(&state.context.chat_sessions);
+    let metadata = tokio::task::spawn_blocking(move || {
+        sessions.import_chat(
+            &location.project_name,
+            &location.workspace_name,
+            &workspace_id,
+            &body,
+        )
+    })
+    .await
+    .map_err(|e| ApiError::internal(format!("Task join error: {e}")))?
+    .map_err(ApiError::bad_request)?;
+
+    Ok(ApiEnvelope::ok(ImportSessionResponse {
+        session_id: metadata.id,
+    }))
+}
@@ -10,10 +10,11 @@ use serde::{Deserialize, Serialize};
 
 use overseer_core::agents::event::AgentEvent;
 use overseer_core::managers::ClaudeStartConfig;
+use overseer_core::persistence::ChatMetadata;
 
 use super::attachments::{attachments_meta, build_agent_prompt, AttachmentInput};
 use super::views::{fold_events, ApiMessage, View};
-use super::{resolve_session, ApiEnvelope, ApiError};
+use super::{resolve_session, ApiEnvelope, ApiError, WorkspaceLocation};
 use crate::HttpSharedState;
 
 #[derive(Deserialize, Default)]
@@ -50,17 +51,49 @@ pub(crate) async fn send_message(
         .ok_or_else(|| ApiError::bad_request("Missing required field: text"))?;
 
     let resolved = resolve_session(&state, &session_id)?;
-    let location = &resolved.location;
 
+    // The agent receives the attachment paths prepended so it can read the files;
+    // the persisted user message keeps the raw text.
+    let agent_prompt = build_agent_prompt(&text, &body.attachments, &session_id);
+    let user_seq = deliver_message(
+        &state,
+        &session_id,
+        &resolved.location,
+        text,
+        attachments_meta(&body.attachments),
+        agent_prompt,
+    )
+    .await?;
+
+    Ok(ApiEnvelope::ok(SendMessageResponse {
+        accepted: true,
+        last_seq: user_seq,
+    }))
+}
+
+/// Persist a user message and hand `agent_prompt` to the session's Claude agent.
+///
+/// `text` is what the UI shows; `agent_prompt` is what the agent receives (it
+/// may carry extra context such as attachment paths). Returns the seq of the
+/// persisted user message, the driver's poll cursor.
+///
+/// On the session's first turn, the workspace's init prompt (if it has one,
+/// e.g. seeded from a GitHub issue) is prepended to `agent_prompt`, matching
+/// what the desktop app does. The workspace is checkpointed before the agent
+/// starts, so the turn can be rewound.
+pub(crate) async fn deliver_message(
+    state: &Arc<HttpSharedState>,
+    session_id: &str,
+    location: &WorkspaceLocation,
+    text: String,
+    meta: Option<serde_json::Value>,
+    agent_prompt: String,
+) -> Result<u64, ApiError> {
     // Load metadata for Claude session resume, model and permission mode.
     let metadata = state
         .context
         .chat_sessions
-        .load_metadata(
-            &location.project_name,
-            &location.workspace_name,
-            &session_id,
-        )
+        .load_metadata(&location.project_name, &location.workspace_name, session_id)
         .map_err(ApiError::internal)?;
 
     // Ensure the session is registered in memory (idempotent). Needed so event
@@ -70,7 +103,7 @@ pub(crate) async fn send_message(
         .context
         .chat_sessions
         .register_session(
-            session_id.clone(),
+            session_id.to_string(),
             location.project_name.clone(),
             location.workspace_name.clone(),
             metadata.clone(),
@@ -79,36 +112,64 @@ pub(crate) async fn send_message(
 
     // Persist the user message (the one shown in the UI). Mirrors
     // ChatSessionManager::add_user_message but returns the seq so the driver gets
-    // a poll cursor. Attachments are stored in meta for the UI; the displayed
-    // content stays the raw text. send_message separately persists a hidden
-    // "system" echo.
+    // a poll cursor. send_message separately persists a hidden "system" echo.
     let user_event = AgentEvent::UserMessage {
         id: uuid::Uuid::new_v4().to_string(),
-        content: text.clone(),
+        content: text,
         timestamp: chrono::Utc::now(),
-        meta: attachments_meta(&body.attachments),
+        meta,
     };
     let user_seq = state
         .context
         .chat_sessions
-        .append_event_with_seq(&session_id, user_event)
+        .append_event_with_seq(session_id, user_event.clone())
         .map_err(ApiError::internal)?;
 
+    overseer_core::checkpoints::checkpoint_turn(
+        std::path::Path::new(&location.working_dir),
+        session_id,
+        &user_event,
+    )
+    .await;
+
+    start_claude(state, session_id, location, metadata, agent_prompt)?;
+    Ok(user_seq)
+}
+
+/// Spawn (or continue) the session's Claude agent with `agent_prompt`, once
+/// the message it answers is recorded.
+///
+/// On the session's first turn, the workspace's init prompt is prepended.
+pub(crate) fn start_claude(
+    state: &Arc<HttpSharedState>,
+    session_id: &str,
+    location: &WorkspaceLocation,
+    metadata: ChatMetadata,
+    agent_prompt: String,
+) -> Result<(), ApiError> {
+    let agent_prompt = match metadata
+        .agent_session_id
+        .is_none()
+        .then(|| workspace_init_prompt(state, &metadata.workspace_id))
+        .flatten()
+    {
+        Some(init_prompt) => format!("{init_prompt}\n\n{agent_prompt}"),
+        None => agent_prompt,
+    };
+
     // Resolve the Claude binary/shell from config.json (same as /api/invoke).
-    let (config_agent_path, config_agent_shell) = crate::routes::load_agent_config(&state);
+    let (config_agent_path, config_agent_shell) = crate::routes::load_agent_config(state);
     let agent_path = config_agent_path.unwrap_or_else(|| "claude".to_string());
 
-    // The agent receives the attachment paths prepended so it can read the files;
-    // the persisted user message above keeps the raw text.
-    let agent_prompt = build_agent_prompt(&text, &body.attachments);
-
     let config = ClaudeStartConfig {
-        conversation_id: session_id.clone(),
+        conversation_id: session_id.to_string(),
         project_name: location.project_name.clone(),
         prompt: agent_prompt,
         working_dir: location.working_dir.clone(),
         agent_path,
         session_id: metadata.agent_session_id,
+        fork_session: false,
+        resume_session_at: None,
         model_version: metadata.model_version,
         log_dir: None,
         log_id: None,
@@ -120,7 +181,7 @@ pub(crate) async fn send_message(
         git_common_dir: None,
         extra_env: Vec::new(),
         // Honor the project's CLAUDE_CONFIG_DIR override for driving-API chats too.
-        claude_config_dir: crate::routes::project_claude_config_dir(&state, &location.project_name),
+        claude_config_dir: crate::routes::project_claude_config_dir(state, &location.project_name),
     };
 
     // Events flow through the EventBus and are persisted to the JSONL file.
@@ -133,12 +194,19 @@ pub(crate) async fn send_message(
             Arc::clone(&state.context.approval_manager),
             Arc::clone(&state.context.chat_sessions),
         )
-        .map_err(ApiError::internal)?;
+        .map_err(ApiError::internal)
+}
 
-    Ok(ApiEnvelope::ok(SendMessageResponse {
-        accepted: true,
-        last_seq: user_seq,
-    }))
+/// The init prompt stored on a workspace in `projects.json`.
+fn workspace_init_prompt(state: &HttpSharedState, workspace_id: &str) -> Option<String> {
+    let config_dir = state.get_config_dir()?;
+    let registry = overseer_core::persistence::load_project_registry(&config_dir).ok()?;
+    registry
+        .projects
+        .iter()
+        .flat_map(|p| p.get_workspaces())
+        .find(|w| w.id == workspace_id)
+        .and_then(|w| w.init_prompt.clone())
 }
 
 #[derive(Deserialize, Default)]
@@ -9,14 +9,35 @@
 //! Everything is stored in Overseer's normal on-disk format, so a session created
 //! and driven through this API opens in the desktop app like any other.
 //!
-//! - [`workspaces`] — list projects, create a workspace.
-//! - [`sessions`] — start a session, read session status.
+//! - [`workspaces`] — list projects, create a workspace, move workspaces to
+//!   a new root or naming scheme, report disk usage and collect finished
+//!   workspaces.
+//! - [`setup`] — workspace setup steps: read the recorded progress, re-run.
+//! - [`changes`] — changed files with line counts, the branch's commits, and
+//!   parsed per-file diffs.
+//! - [`sessions`] — start a session, fork one at a message, hand one over
+//!   to Claude, read session status.
 //! - [`messages`] — send a message (async), read messages with a poll cursor.
+//! - [`checkpoints`] — per-turn workspace checkpoints: list, diff, restore.
+//! - [`sync`] — sync a workspace with the default branch, hand conflicts to
+//!   the agent, continue or abort.
 //! - [`views`] — fold the persisted event stream into clean messages.
+//! - [`pull_requests`] — PR status, CI checks, review comments, review queue,
+//!   and sending unresolved review threads to a session's agent.
+//! - [`search`] — full-text search across all sessions.
+//! - [`export`] — export a session as Markdown, HTML or a portable bundle;
+//!   import a bundle.
 
 mod attachments;
+mod changes;
+mod checkpoints;
+mod export;
 mod messages;
+mod pull_requests;
+mod search;
 mod sessions;
+mod setup;
+mod sync;
 mod views;
 mod workspaces;
 
@@ -41,19 +62,104 @@ use crate::HttpSharedState;
 pub fn router() -> Router<Arc<HttpSharedState>> {
     Router::new()
         .route("/api/v1/projects", get(workspaces::list_projects))
+        .route("/api/v1/search", get(search::search))
         .route(
             "/api/v1/projects/{projectId}/workspaces",
             post(workspaces::create_workspace),
         )
+        .route(
+            "/api/v1/projects/{projectId}/workspaces/migrate",
+            post(workspaces::migrate_workspaces),
+        )
+        .route(
+            "/api/v1/projects/{projectId}/workspaces/gc",
+            get(workspaces::get_workspace_gc).post(workspaces::collect_workspaces),
+        )
+        .route(
+            "/api/v1/projects/{projectId}/review-prs",
+            get(pull_requests::list_review_prs),
+        )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/setup",
+            get(setup::get_setup).post(setup::run_setup),
+        )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/changes",
+            get(changes::get_changes),
+        )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/diff",
+            get(changes::get_diff),
+        )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/pr",
+            get(pull_requests::get_pr),
+        )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/pr/checks",
+            get(pull_requests::list_checks),
+        )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/pr/comments",
+            get(pull_requests::list_comments),
+        )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/sync",
+            post(sync::sync_workspace).get(sync::get_sync),
+        )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/sync/continue",
+            post(sync::continue_sync),
+        )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/sync/abort",
+            post(sync::abort_sync),
+        )
         .route(
             "/api/v1/workspaces/{workspaceId}/sessions",
             post(sessions::create_session),
         )
+        .route(
+            "/api/v1/workspaces/{workspaceId}/sessions/import",
+            post(export::import_session).layer(DefaultBodyLimit::max(export::MAX_BUNDLE_BYTES)),
+        )
         .route("/api/v1/sessions/{sessionId}", get(sessions::get_session))
+        .route(
+            "/api/v1/sessions/{sessionId}/fork",
+            post(sessions::fork_session),
+        )
+        .route(
+            "/api/v1/sessions/{sessionId}/handoff",
+            post(sessions::hand_off_session),
+        )
         .route(
             "/api/v1/sessions/{sessionId}/messages",
             post(messages::send_message).get(messages::read_messages),
         )
+        .route(
+            "/api/v1/sessions/{sessionId}/export",
+            get(export::export_session),
+        )
+        .route(
+            "/api/v1/sessions/{sessionId}/checkpoints",
+            get(checkpoints::list_checkpoints),
+        )
+        .route(
+            "/api/v1/sessions/{sessionId}/checkpoints/{turnId}/diff",
+            get(checkpoints::diff_checkpoint),
+        )
+        .route(
+            "/api/v1/sessions/{sessionId}/checkpoints/{turnId}/restore",
+            post(checkpoints::restore_checkpoint),
+        )
+        .route(
+            "/api/v1/sessions/{sessionId}/pr-review",
+            post(pull_requests::send_review_feedback),
+        )
+        .route(
+            "/api/v1/sessions/{sessionId}/resolve-conflicts",
+            post(sync::resolve_conflicts),
+        )
         .route(
             "/api/v1/sessions/{sessionId}/attachments",
             // Raise the body limit above axum's 2 MiB default for file uploads.
@@ -109,6 +215,13 @@ impl ApiError {
         }
     }
 
+    pub(crate) fn conflict(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::CONFLICT,
+            message: message.into(),
+        }
+    }
+
     pub(crate) fn internal(message: impl Into<String>) -> Self {
         Self {
             status: StatusCode::INTERNAL_SERVER_ERROR,
@@ -287,6 +400,8 @@ mod tests {
             is_creating: None,
             is_archiving: None,
             ssh_host_id: None,
+            init_prompt: None,
+            setup: None,
         }
     }
 
@@ -301,6 +416,8 @@ mod tests {
             init_prompt: None,
             pr_prompt: None,
             post_create: None,
+            setup_steps: vec![],
+            pre_archive: None,
             workspace_filter: None,
             worktree_filter: None,
             use_github: None,
@@ -308,6 +425,12 @@ mod tests {
             main_branch: None,
             default_sandboxed: None,
             claude_config_dir: None,
+            workspace_root: None,
+            workspace_naming: None,
+            vcs: None,
+            repos: Vec::new(),
+            redact_patterns: Vec::new(),
+            sensitive_env_vars: Vec::new(),
         }
     }
 
@@ -322,7 +445,7 @@ mod tests {
                 "/tmp/overseer/dugong",
             )])],
         };
-        save_project_registry(&temp.path, &registry).unwrap();
+        save_project_registry(&temp.path, &registry, None).unwrap();
         (state, temp)
     }
 
@@ -581,6 +704,27 @@ mod tests {
         assert_eq!(err.status, StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn handoff_is_refused_unless_another_agent_hands_to_claude() {
+        let (state, _temp) = state_with_workspace();
+        let session_id = create_session(&state, "ws-1").await;
+
+        for agent_type in ["codex", "claude"] {
+            let body =
+                serde_json::from_value(serde_json::json!({ "agentType": agent_type })).unwrap();
+            let result = super::sessions::hand_off_session(
+                State(state.clone()),
+                Path(session_id.clone()),
+                Json(body),
+            )
+            .await;
+            let Err(err) = result else {
+                panic!("expected handing off to {agent_type} to fail");
+            };
+            assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        }
+    }
+
     #[tokio::test]
     async fn unknown_session_is_404() {
         let (state, _temp) = state_with_workspace();
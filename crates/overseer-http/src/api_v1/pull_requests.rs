@@ -0,0 +1,263 @@
+//! Pull request status, CI checks, review comments and the review queue, plus
+//! feeding unresolved review threads back to a session's agent.
+//!
+//! All of these shell out to `gh` (see `overseer_core::git::github`) using the
+//! same `agentShell` setting as the agents, so they see the user's GitHub
+//! credentials. Failures come back as errors rather than empty lists.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use serde::{Deserialize, Serialize};
+
+use overseer_core::git::{
+    self, GitError, PrCheck, PrStatus, ReviewComment, ReviewPr, ShellGhRunner,
+};
+use overseer_core::persistence::{load_project_registry, mark_review_threads_addressed};
+use overseer_core::pr_review::prepare_review_feedback;
+
+use super::messages::deliver_message;
+use super::{resolve_session, resolve_workspace, ApiEnvelope, ApiError};
+use crate::HttpSharedState;
+
+/// How many review-requested PRs to return.
+const REVIEW_PR_LIMIT: usize = 20;
+
+/// GET /api/v1/workspaces/{workspaceId}/pr
+///
+/// The PR opened from the workspace's current branch, or `null` if none.
+pub(crate) async fn get_pr(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiEnvelope<Option<PrStatus>>>, ApiError> {
+    let (working_dir, branch) = workspace_branch(&state, &workspace_id).await?;
+    let runner = gh_runner(&state);
+
+    let status = run_blocking(move || git::get_pr_status(&runner, &working_dir, &branch)).await?;
+    Ok(ApiEnvelope::ok(status))
+}
+
+/// GET /api/v1/workspaces/{workspaceId}/pr/checks
+pub(crate) async fn list_checks(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiEnvelope<Vec<PrCheck>>>, ApiError> {
+    let (working_dir, branch) = workspace_branch(&state, &workspace_id).await?;
+    let runner = gh_runner(&state);
+
+    let checks = run_blocking(move || {
+        let pr = require_pr(&runner, &working_dir, &branch)?;
+        Ok::<_, GhCallError>(git::list_pr_checks(&runner, &working_dir, pr.number)?)
+    })
+    .await?;
+    Ok(ApiEnvelope::ok(checks))
+}
+
+/// GET /api/v1/workspaces/{workspaceId}/pr/comments
+pub(crate) async fn list_comments(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiEnvelope<Vec<ReviewComment>>>, ApiError> {
+    let (working_dir, branch) = workspace_branch(&state, &workspace_id).await?;
+    let runner = gh_runner(&state);
+
+    let comments = run_blocking(move || {
+        let pr = require_pr(&runner, &working_dir, &branch)?;
+        Ok::<_, GhCallError>(git::list_review_comments(&runner, &working_dir, pr.number)?)
+    })
+    .await?;
+    Ok(ApiEnvelope::ok(comments))
+}
+
+/// GET /api/v1/projects/{projectId}/review-prs
+///
+/// Open PRs in the project's repository where the user's review is requested.
+pub(crate) async fn list_review_prs(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(project_id): Path<String>,
+) -> Result<Json<ApiEnvelope<Vec<ReviewPr>>>, ApiError> {
+    let config_dir = state
+        .get_config_dir()
+        .ok_or_else(|| ApiError::internal("Config directory not set"))?;
+    let registry =
+        load_project_registry(&config_dir).map_err(|e| ApiError::internal(e.to_string()))?;
+    let project_path = registry
+        .projects
+        .iter()
+        .find(|p| p.id == project_id)
+        .map(|p| PathBuf::from(&p.path))
+        .ok_or_else(|| ApiError::not_found(format!("Project not found: {project_id}")))?;
+    let runner = gh_runner(&state);
+
+    let prs =
+        run_blocking(move || git::list_review_prs(&runner, &project_path, REVIEW_PR_LIMIT)).await?;
+    Ok(ApiEnvelope::ok(prs))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ReviewFeedbackBody {
+    /// Resend threads that were already handed to an agent.
+    include_addressed: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewFeedbackResponse {
+    /// False when there was nothing pending to send.
+    accepted: bool,
+    pr_number: Option<u64>,
+    /// Ids of the review threads included in the prompt.
+    thread_ids: Vec<String>,
+    /// Seq of the persisted prompt message; poll for the reply from here.
+    last_seq: Option<u64>,
+}
+
+/// POST /api/v1/sessions/{sessionId}/pr-review
+///
+/// Gathers the unresolved review threads on the PR of the session's workspace,
+/// sends them to the session's agent as one structured prompt, and records the
+/// threads as addressed. Returns immediately, like sending a message.
+pub(crate) async fn send_review_feedback(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(session_id): Path<String>,
+    body: Option<Json<ReviewFeedbackBody>>,
+) -> Result<Json<ApiEnvelope<ReviewFeedbackResponse>>, ApiError> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let resolved = resolve_session(&state, &session_id)?;
+    let location = resolved.location;
+    let chat_dir = state
+        .get_chat_dir(&location.project_name, &location.workspace_name)
+        .ok_or_else(|| ApiError::internal("Config directory not set"))?;
+    let (working_dir, branch) = workspace_branch(&state, &resolved.workspace_id).await?;
+    let runner = gh_runner(&state);
+
+    let feedback = {
+        let chat_dir = chat_dir.clone();
+        run_blocking(move || {
+            prepare_review_feedback(
+                &runner,
+                &working_dir,
+                &branch,
+                &chat_dir,
+                body.include_addressed,
+            )
+        })
+        .await?
+    };
+
+    let Some(feedback) = feedback else {
+        return Ok(ApiEnvelope::ok(ReviewFeedbackResponse {
+            accepted: false,
+            pr_number: None,
+            thread_ids: Vec::new(),
+            last_seq: None,
+        }));
+    };
+
+    let meta = serde_json::json!({
+        "type": "pr_review",
+        "label": format!("PR #{} review comments", feedback.pr_number),
+        "prNumber": feedback.pr_number,
+        "threadIds": feedback.thread_ids,
+    });
+    let last_seq = deliver_message(
+        &state,
+        &session_id,
+        &location,
+        feedback.prompt.clone(),
+        Some(meta),
+        feedback.prompt,
+    )
+    .await?;
+
+    mark_review_threads_addressed(
+        &chat_dir,
+        feedback.pr_number,
+        &session_id,
+        &feedback.thread_ids,
+    )
+    .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(ApiEnvelope::ok(ReviewFeedbackResponse {
+        accepted: true,
+        pr_number: Some(feedback.pr_number),
+        thread_ids: feedback.thread_ids,
+        last_seq: Some(last_seq),
+    }))
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// Resolve a workspace to its worktree path and currently checked-out branch.
+///
+/// The branch is read from git rather than `projects.json` so a rename made
+/// outside Overseer is still picked up.
+async fn workspace_branch(
+    state: &HttpSharedState,
+    workspace_id: &str,
+) -> Result<(PathBuf, String), ApiError> {
+    let location = resolve_workspace(state, workspace_id)?;
+    let working_dir = PathBuf::from(&location.working_dir);
+    let branch = git::get_current_branch(&working_dir)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    Ok((working_dir, branch))
+}
+
+fn gh_runner(state: &HttpSharedState) -> ShellGhRunner {
+    let (_, agent_shell) = crate::routes::load_agent_config(state);
+    ShellGhRunner::new(agent_shell)
+}
+
+/// Look up the branch's PR, turning "no PR" into a 404.
+fn require_pr(
+    runner: &ShellGhRunner,
+    working_dir: &std::path::Path,
+    branch: &str,
+) -> Result<PrStatus, GhCallError> {
+    git::get_pr_status(runner, working_dir, branch)?
+        .ok_or_else(|| GhCallError::NoPr(branch.to_string()))
+}
+
+/// Error from a blocking `gh` call: either no PR exists or `gh` failed.
+enum GhCallError {
+    NoPr(String),
+    Git(GitError),
+}
+
+impl From<GitError> for GhCallError {
+    fn from(e: GitError) -> Self {
+        GhCallError::Git(e)
+    }
+}
+
+impl From<GhCallError> for ApiError {
+    fn from(e: GhCallError) -> Self {
+        match e {
+            GhCallError::NoPr(branch) => {
+                ApiError::not_found(format!("No pull request for branch: {branch}"))
+            }
+            GhCallError::Git(e) => ApiError::internal(e.to_string()),
+        }
+    }
+}
+
+/// Run a blocking `gh` call on the blocking pool.
+async fn run_blocking<T, E, F>(call: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    E: Into<GhCallError> + Send + 'static,
+    F: FnOnce() -> Result<T, E> + Send + 'static,
+{
+    tokio::task::spawn_blocking(call)
+        .await
+        .map_err(|e| ApiError::internal(format!("Task join error: {e}")))?
+        .map_err(|e| e.into().into())
+}
@@ -0,0 +1,32 @@
+//! Full-text search across every session's messages, tool calls and tool
+//! results; see `overseer_core::chat_search`.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+
+use overseer_core::chat_search::{SearchQuery, SearchResults};
+
+use super::{ApiEnvelope, ApiError};
+use crate::HttpSharedState;
+
+/// GET /api/v1/search?q=...
+///
+/// Optional filters: `project`, `workspace`, `agentType`, `since` and `until`
+/// (RFC 3339), `archived` (`true`/`false`) and `limit`. A hit's `chatId` is
+/// the session id used by the other `/api/v1/sessions` routes.
+pub(crate) async fn search(
+    State(state): State<Arc<HttpSharedState>>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<ApiEnvelope<SearchResults>>, ApiError> {
+    let sessions = Arc::clone(&state.context.chat_sessions);
+    // The first search reads chats on disk that the index hasn't seen.
+    let results = tokio::task::spawn_blocking(move || sessions.search(&query))
+        .await
+        .map_err(|e| ApiError::internal(format!("Task join error: {e}")))?
+        .map_err(ApiError::internal)?;
+    Ok(ApiEnvelope::ok(results))
+}
@@ -1,4 +1,4 @@
-//! Session creation and status.
+//! Session creation, forking, handoff and status.
 
 use std::sync::Arc;
 
@@ -8,10 +8,14 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
+use overseer_core::chat_fork::{claude_root, ForkResume};
+use overseer_core::chat_handoff::touched_files;
 use overseer_core::persistence::{
-    load_chat_index, save_chat_index, upsert_chat_entry, ChatIndexEntry, ChatMetadata,
+    update_chat_index, upsert_chat_entry, ChatIndexEntry, ChatMetadata,
 };
+use overseer_core::vcs::Vcs;
 
+use super::messages::start_claude;
 use super::{resolve_session, resolve_workspace, ApiEnvelope, ApiError};
 use crate::HttpSharedState;
 
@@ -80,24 +84,156 @@ pub(crate) async fn create_session(
     let chat_dir = state
         .get_chat_dir(&location.project_name, &location.workspace_name)
         .ok_or_else(|| ApiError::internal("Config directory not set"))?;
-    let mut index = load_chat_index(&chat_dir).map_err(|e| ApiError::internal(e.to_string()))?;
-    upsert_chat_entry(
-        &mut index,
-        ChatIndexEntry {
-            id: session_id.clone(),
-            label,
-            agent_type: Some("claude".to_string()),
-            created_at: now,
-            updated_at: now,
-            is_archived: None,
-            archived_at: None,
-        },
-    );
-    save_chat_index(&chat_dir, &index).map_err(|e| ApiError::internal(e.to_string()))?;
+    let entry = ChatIndexEntry {
+        id: session_id.clone(),
+        label,
+        agent_type: Some("claude".to_string()),
+        created_at: now,
+        updated_at: now,
+        is_archived: None,
+        archived_at: None,
+    };
+    update_chat_index(&chat_dir, |index| upsert_chat_entry(index, entry.clone()))
+        .map_err(|e| ApiError::internal(e.to_string()))?;
 
     Ok(ApiEnvelope::ok(CreateSessionResponse { session_id }))
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ForkSessionBody {
+    /// Fork after this event; the rest of its turn comes along.
+    seq: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ForkSessionResponse {
+    session_id: String,
+    /// Last event copied from the source session.
+    through_seq: u64,
+    /// How the new session's first message resumes the agent.
+    resume: ForkResume,
+}
+
+/// POST /api/v1/sessions/{sessionId}/fork
+///
+/// Copies the session through the end of the turn holding `seq` into a new
+/// session in the same workspace. The new session's first message resumes
+/// the agent from there; the source is left untouched.
+pub(crate) async fn fork_session(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(session_id): Path<String>,
+    Json(body): Json<ForkSessionBody>,
+) -> Result<Json<ApiEnvelope<ForkSessionResponse>>, ApiError> {
+    let resolved = resolve_session(&state, &session_id)?;
+    let location = resolved.location;
+    let root = claude_root(
+        crate::routes::project_claude_config_dir(&state, &location.project_name).as_deref(),
+    );
+
+    let sessions = Arc::clone(&state.context.chat_sessions);
+    let forked = tokio::task::spawn_blocking(move || {
+        sessions.fork_chat(
+            &location.project_name,
+            &location.workspace_name,
+            &session_id,
+            body.seq,
+            root.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| ApiError::internal(format!("Task join error: {e}")))?
+    .map_err(ApiError::bad_request)?;
+
+    Ok(ApiEnvelope::ok(ForkSessionResponse {
+        session_id: forked.metadata.id,
+        through_seq: forked.fork.through_seq,
+        resume: forked.fork.resume,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HandoffBody {
+    /// The agent taking over. The driving API runs Claude only.
+    agent_type: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HandoffResponse {
+    /// Seq of the recorded handoff message. Poll for the new agent's reply
+    /// with `?sinceSeq=<lastSeq>`.
+    last_seq: u64,
+    /// Timeline label, e.g. "Handed off from Codex to Claude".
+    label: String,
+}
+
+/// POST /api/v1/sessions/{sessionId}/handoff
+///
+/// Hands a session whose agent can't continue (rate limits, a stuck session)
+/// over to Claude. The session switches agents with a fresh agent session,
+/// a context transfer prompt built from the conversation and the workspace
+/// diff is recorded as a handoff message, and Claude starts on it. Returns
+/// immediately, like sending a message.
+pub(crate) async fn hand_off_session(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(session_id): Path<String>,
+    Json(body): Json<HandoffBody>,
+) -> Result<Json<ApiEnvelope<HandoffResponse>>, ApiError> {
+    if body.agent_type != "claude" {
+        return Err(ApiError::bad_request(format!(
+            "The driving API only runs Claude; hand off to {} from the app",
+            body.agent_type
+        )));
+    }
+    let resolved = resolve_session(&state, &session_id)?;
+    let location = resolved.location;
+
+    // A workspace whose changes can't be listed hands off without them.
+    let working_dir = std::path::PathBuf::from(&location.working_dir);
+    let touched = Vcs::resolve(state.get_config_dir().as_deref(), &working_dir)
+        .list_changed_files(&working_dir, None)
+        .await
+        .map(touched_files)
+        .unwrap_or_default();
+
+    let sessions = Arc::clone(&state.context.chat_sessions);
+    let (project_name, workspace_name, chat_id) = (
+        location.project_name.clone(),
+        location.workspace_name.clone(),
+        session_id.clone(),
+    );
+    let handoff = tokio::task::spawn_blocking(move || {
+        sessions.hand_off(
+            &project_name,
+            &workspace_name,
+            &chat_id,
+            &body.agent_type,
+            &touched,
+        )
+    })
+    .await
+    .map_err(|e| ApiError::internal(format!("Task join error: {e}")))?
+    .map_err(ApiError::bad_request)?;
+
+    overseer_core::checkpoints::checkpoint_turn(&working_dir, &session_id, &handoff.event).await;
+
+    start_claude(
+        &state,
+        &session_id,
+        &location,
+        handoff.metadata,
+        handoff.handoff.prompt,
+    )?;
+
+    Ok(ApiEnvelope::ok(HandoffResponse {
+        last_seq: handoff.seq,
+        label: handoff.handoff.label,
+    }))
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct SessionStatus {
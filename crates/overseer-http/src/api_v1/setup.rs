@@ -0,0 +1,104 @@
+//! Workspace setup: the project's setup steps and `postCreate` command.
+//!
+//! Setup runs in the background after a workspace is created. Progress is
+//! recorded on the workspace and streamed over the WebSocket as
+//! `workspace:setup:{workspaceId}` events; `GET` reads the recorded state.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use serde::Serialize;
+
+use overseer_core::persistence::{load_project_registry, SetupStatus, WorkspaceSetup};
+use overseer_core::workspace_setup::{run_workspace_setup, setup_steps};
+
+use super::{ApiEnvelope, ApiError};
+use crate::HttpSharedState;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunSetupResponse {
+    /// False when the project has no setup steps; nothing was started.
+    started: bool,
+}
+
+/// GET /api/v1/workspaces/{workspaceId}/setup
+///
+/// The workspace's recorded setup, or `null` if it never ran.
+pub(crate) async fn get_setup(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiEnvelope<Option<WorkspaceSetup>>>, ApiError> {
+    let (_, _, setup) = find_workspace_setup(&state, &workspace_id)?;
+    Ok(ApiEnvelope::ok(setup))
+}
+
+/// POST /api/v1/workspaces/{workspaceId}/setup
+///
+/// Runs the setup steps again in the background. Refused with 409 while a
+/// run is in progress.
+pub(crate) async fn run_setup(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiEnvelope<RunSetupResponse>>, ApiError> {
+    let (project_id, has_steps, current) = find_workspace_setup(&state, &workspace_id)?;
+    if !has_steps {
+        return Ok(ApiEnvelope::ok(RunSetupResponse { started: false }));
+    }
+    if current.is_some_and(|s| s.status == SetupStatus::Running) {
+        return Err(ApiError::conflict("Setup is already running"));
+    }
+
+    spawn_workspace_setup(&state, project_id, workspace_id);
+    Ok(ApiEnvelope::ok(RunSetupResponse { started: true }))
+}
+
+/// Run a workspace's setup in the background, logging a failure to start.
+pub(super) fn spawn_workspace_setup(
+    state: &HttpSharedState,
+    project_id: String,
+    workspace_id: String,
+) {
+    let Some(config_dir) = state.get_config_dir() else {
+        return;
+    };
+    let event_bus = Arc::clone(&state.context.event_bus);
+    tokio::spawn(async move {
+        if let Err(e) =
+            run_workspace_setup(event_bus, &config_dir, &project_id, &workspace_id).await
+        {
+            log::warn!("Setup of workspace {workspace_id} didn't start: {e}");
+        }
+    });
+}
+
+/// The owning project's id, whether the project has setup steps, and the
+/// workspace's recorded setup.
+fn find_workspace_setup(
+    state: &HttpSharedState,
+    workspace_id: &str,
+) -> Result<(String, bool, Option<WorkspaceSetup>), ApiError> {
+    let config_dir: PathBuf = state
+        .get_config_dir()
+        .ok_or_else(|| ApiError::internal("Config directory not set"))?;
+    let registry =
+        load_project_registry(&config_dir).map_err(|e| ApiError::internal(e.to_string()))?;
+
+    for project in &registry.projects {
+        if let Some(workspace) = project
+            .get_workspaces()
+            .iter()
+            .find(|w| w.id == workspace_id)
+        {
+            let has_steps = !setup_steps(project).is_empty();
+            return Ok((project.id.clone(), has_steps, workspace.setup.clone()));
+        }
+    }
+    Err(ApiError::not_found(format!(
+        "Workspace not found: {workspace_id}"
+    )))
+}
@@ -0,0 +1,151 @@
+//! Keeping a workspace up to date with its project's default branch.
+//!
+//! A sync merges (or rebases onto) the default branch. When it stops on
+//! conflicts the worktree is left mid-sync so the session's agent can resolve
+//! them (`resolve-conflicts`), after which the driver continues or aborts it.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use serde::Serialize;
+
+use overseer_core::conflicts::prepare_conflict_resolution;
+use overseer_core::git::{self, GitError, SyncOptions, SyncResult, SyncState};
+use overseer_core::vcs::Vcs;
+
+use super::changes::project_main_branch;
+use super::messages::deliver_message;
+use super::{resolve_session, resolve_workspace, ApiEnvelope, ApiError};
+use crate::HttpSharedState;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResolveConflictsResponse {
+    /// False when no sync is stopped on conflicts; nothing was sent.
+    accepted: bool,
+    files: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_seq: Option<u64>,
+}
+
+/// POST /api/v1/workspaces/{workspaceId}/sync
+///
+/// Body (optional): `{ strategy?: "merge" | "rebase", fetch?: bool }`.
+/// Conflicts come back as a successful result with `status: "conflicts"`.
+pub(crate) async fn sync_workspace(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+    body: Option<Json<SyncOptions>>,
+) -> Result<Json<ApiEnvelope<SyncResult>>, ApiError> {
+    let options = body.map(|Json(b)| b).unwrap_or_default();
+    let location = resolve_workspace(&state, &workspace_id)?;
+    let main_branch = project_main_branch(&state, &location.project_name);
+
+    let result = git::sync_with_default_branch(
+        &PathBuf::from(&location.working_dir),
+        main_branch.as_deref(),
+        &options,
+    )
+    .await
+    .map_err(sync_error)?;
+    Ok(ApiEnvelope::ok(result))
+}
+
+/// GET /api/v1/workspaces/{workspaceId}/sync
+///
+/// The sync stopped on conflicts, or `null` if none is in progress.
+pub(crate) async fn get_sync(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiEnvelope<Option<SyncState>>>, ApiError> {
+    let location = resolve_workspace(&state, &workspace_id)?;
+    let working_dir = PathBuf::from(&location.working_dir);
+    let sync_state = Vcs::resolve(state.get_config_dir().as_deref(), &working_dir)
+        .get_sync_state(&working_dir)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    Ok(ApiEnvelope::ok(sync_state))
+}
+
+/// POST /api/v1/workspaces/{workspaceId}/sync/continue
+///
+/// Refused with 409 while conflict markers are left in any file.
+pub(crate) async fn continue_sync(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiEnvelope<SyncResult>>, ApiError> {
+    let location = resolve_workspace(&state, &workspace_id)?;
+    let result = git::continue_sync(&PathBuf::from(&location.working_dir))
+        .await
+        .map_err(sync_error)?;
+    Ok(ApiEnvelope::ok(result))
+}
+
+/// POST /api/v1/workspaces/{workspaceId}/sync/abort
+pub(crate) async fn abort_sync(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(workspace_id): Path<String>,
+) -> Result<Json<ApiEnvelope<serde_json::Value>>, ApiError> {
+    let location = resolve_workspace(&state, &workspace_id)?;
+    git::abort_sync(&PathBuf::from(&location.working_dir))
+        .await
+        .map_err(sync_error)?;
+    Ok(ApiEnvelope::ok(serde_json::json!({ "aborted": true })))
+}
+
+/// POST /api/v1/sessions/{sessionId}/resolve-conflicts
+///
+/// Sends the conflicted files of the session's workspace to its agent as a
+/// user turn. The agent resolves and stages them; the driver then calls
+/// `sync/continue`.
+pub(crate) async fn resolve_conflicts(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<ApiEnvelope<ResolveConflictsResponse>>, ApiError> {
+    let location = resolve_session(&state, &session_id)?.location;
+
+    let resolution = prepare_conflict_resolution(&PathBuf::from(&location.working_dir))
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let Some(resolution) = resolution else {
+        return Ok(ApiEnvelope::ok(ResolveConflictsResponse {
+            accepted: false,
+            files: Vec::new(),
+            last_seq: None,
+        }));
+    };
+
+    let meta = serde_json::json!({
+        "type": "sync_conflicts",
+        "label": format!("{} conflicted file(s)", resolution.files.len()),
+        "files": resolution.files,
+    });
+    let last_seq = deliver_message(
+        &state,
+        &session_id,
+        &location,
+        resolution.prompt.clone(),
+        Some(meta),
+        resolution.prompt,
+    )
+    .await?;
+
+    Ok(ApiEnvelope::ok(ResolveConflictsResponse {
+        accepted: true,
+        files: resolution.files,
+        last_seq: Some(last_seq),
+    }))
+}
+
+/// Refusals (wrong branch, sync already running, markers left) are 409s;
+/// anything else is a git failure.
+fn sync_error(error: GitError) -> ApiError {
+    match error {
+        GitError::Other(message) => ApiError::conflict(message),
+        other => ApiError::internal(other.to_string()),
+    }
+}
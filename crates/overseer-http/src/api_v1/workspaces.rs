@@ -8,10 +8,21 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
+use overseer_core::git::{ShellGhRunner, WorkspaceLayout};
+use overseer_core::github_workspace::{
+    create_github_workspace, plan_github_workspace, GithubSource, GithubWorkspace,
+};
 use overseer_core::persistence::{
-    load_project_registry, save_project_registry, Workspace as StoredWorkspace,
+    load_project_registry, update_project_registry, SetupStatus, Workspace as StoredWorkspace,
+};
+use overseer_core::vcs::add_project_workspace;
+use overseer_core::workspace_gc::{collect_workspace_garbage, GcOptions, GcReport};
+use overseer_core::workspace_layout::{
+    migrate_workspaces as migrate, resolve_workspace_layout, MigrationReport,
 };
+use overseer_core::workspace_setup::setup_steps;
 
+use super::setup::spawn_workspace_setup;
 use super::{workspace_name_from_path, ApiEnvelope, ApiError};
 use crate::HttpSharedState;
 
@@ -30,11 +41,23 @@ pub(crate) struct ProjectDto {
 pub(crate) struct WorkspaceDto {
     id: String,
     project_id: String,
-    /// Workspace directory name (the animal folder), used as the second
+    /// Workspace directory name (e.g. the animal folder), used as the second
     /// chat-path segment.
     name: String,
     branch: String,
     path: String,
+    /// Set when the workspace was started from a pull request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pr_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pr_url: Option<String>,
+    /// Prepended to the first message of each new session (issue workspaces).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    init_prompt: Option<String>,
+    /// `pending` when setup steps were scheduled; follow them with
+    /// `GET /api/v1/workspaces/{id}/setup` or `workspace:setup:{id}` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    setup: Option<SetupStatus>,
 }
 
 /// GET /api/v1/projects
@@ -64,44 +87,85 @@ pub(crate) async fn list_projects(
 #[serde(rename_all = "camelCase", default)]
 pub(crate) struct CreateWorkspaceBody {
     branch: Option<String>,
+    /// Check out this pull request's head branch instead.
+    pull_request: Option<u64>,
+    /// Start a branch for this issue and seed the init prompt from it.
+    issue: Option<u64>,
+}
+
+/// Where a new workspace's branch comes from.
+enum WorkspaceSource {
+    Branch(String),
+    Github(GithubSource),
 }
 
 /// POST /api/v1/projects/{projectId}/workspaces
 ///
 /// Creates a git worktree in the project and records it in `projects.json` so the
-/// desktop app lists it.
+/// desktop app lists it. The body names exactly one of `branch`, `pullRequest`
+/// or `issue`. A project that isn't a git repository gets a copy of its
+/// directory instead, named after `branch`, and a multi-repo project a
+/// worktree of each of its repositories.
 pub(crate) async fn create_workspace(
     State(state): State<Arc<HttpSharedState>>,
     Path(project_id): Path<String>,
     Json(body): Json<CreateWorkspaceBody>,
 ) -> Result<Json<ApiEnvelope<WorkspaceDto>>, ApiError> {
-    let branch = body
-        .branch
-        .filter(|b| !b.trim().is_empty())
-        .ok_or_else(|| ApiError::bad_request("Missing required field: branch"))?;
+    let branch = body.branch.filter(|b| !b.trim().is_empty());
+    let source = match (branch, body.pull_request, body.issue) {
+        (Some(branch), None, None) => WorkspaceSource::Branch(branch),
+        (None, Some(number), None) => WorkspaceSource::Github(GithubSource::PullRequest { number }),
+        (None, None, Some(number)) => WorkspaceSource::Github(GithubSource::Issue { number }),
+        (None, None, None) => {
+            return Err(ApiError::bad_request("Missing required field: branch"));
+        }
+        _ => {
+            return Err(ApiError::bad_request(
+                "Only one of branch, pullRequest or issue may be given",
+            ));
+        }
+    };
 
     let config_dir = state
         .get_config_dir()
         .ok_or_else(|| ApiError::internal("Config directory not set"))?;
 
-    // Find the project's root path.
-    let project_path = {
+    // Find the project's root path and init prompt.
+    let (project_path, project_init_prompt) = {
         let registry =
             load_project_registry(&config_dir).map_err(|e| ApiError::internal(e.to_string()))?;
         registry
             .projects
             .iter()
             .find(|p| p.id == project_id)
-            .map(|p| p.path.clone())
+            .map(|p| (p.path.clone(), p.init_prompt.clone()))
             .ok_or_else(|| ApiError::not_found(format!("Project not found: {project_id}")))?
     };
 
-    // Create the git worktree (picks a unique animal-name directory).
-    let worktree_path =
-        overseer_core::git::add_workspace(std::path::Path::new(&project_path), &branch)
-            .await
-            .map_err(|e| ApiError::internal(e.to_string()))?;
-    let worktree_path = worktree_path.to_string_lossy().to_string();
+    // Create the workspace where the project's workspace layout says.
+    let layout = resolve_workspace_layout(Some(&config_dir), std::path::Path::new(&project_path));
+    let created = match source {
+        WorkspaceSource::Branch(branch) => {
+            let project_dir = std::path::Path::new(&project_path);
+            let worktree_path =
+                add_project_workspace(Some(&config_dir), project_dir, &branch, &layout)
+                    .await
+                    .map_err(|e| ApiError::internal(e.to_string()))?;
+            GithubWorkspace {
+                path: worktree_path.to_string_lossy().to_string(),
+                branch,
+                init_prompt: None,
+                pr_number: None,
+                pr_url: None,
+                pr_state: None,
+            }
+        }
+        WorkspaceSource::Github(source) => {
+            create_from_github(&state, &project_path, source, project_init_prompt, &layout).await?
+        }
+    };
+    let worktree_path = created.path;
+    let branch = created.branch;
     let workspace_name = workspace_name_from_path(&worktree_path);
 
     // Record the workspace in the registry so the desktop app shows it. Reload
@@ -114,24 +178,33 @@ pub(crate) async fn create_workspace(
         path: worktree_path.clone(),
         is_archived: false,
         created_at: chrono::Utc::now(),
-        pr_number: None,
-        pr_url: None,
-        pr_state: None,
+        pr_number: created.pr_number,
+        pr_url: created.pr_url.clone(),
+        pr_state: created.pr_state.clone(),
         is_creating: None,
         is_archiving: None,
         ssh_host_id: None,
+        init_prompt: created.init_prompt.clone(),
+        setup: None,
     };
     let workspace_id = workspace.id.clone();
 
-    let mut registry =
-        load_project_registry(&config_dir).map_err(|e| ApiError::internal(e.to_string()))?;
-    let project = registry
-        .projects
-        .iter_mut()
-        .find(|p| p.id == project_id)
-        .ok_or_else(|| ApiError::not_found(format!("Project not found: {project_id}")))?;
-    project.workspaces.push(workspace);
-    save_project_registry(&config_dir, &registry).map_err(|e| ApiError::internal(e.to_string()))?;
+    let mut has_setup = None;
+    update_project_registry(&config_dir, |registry| {
+        if let Some(project) = registry.projects.iter_mut().find(|p| p.id == project_id) {
+            project.workspaces.push(workspace.clone());
+            has_setup = Some(!setup_steps(project).is_empty());
+        }
+    })
+    .map_err(|e| ApiError::internal(e.to_string()))?;
+    let has_setup =
+        has_setup.ok_or_else(|| ApiError::not_found(format!("Project not found: {project_id}")))?;
+
+    // Copy env files, install dependencies and run postCreate in the
+    // background; the workspace is usable while that happens.
+    if has_setup {
+        spawn_workspace_setup(&state, project_id.clone(), workspace_id.clone());
+    }
 
     Ok(ApiEnvelope::ok(WorkspaceDto {
         id: workspace_id,
@@ -139,5 +212,120 @@ pub(crate) async fn create_workspace(
         name: workspace_name,
         branch,
         path: worktree_path,
+        pr_number: created.pr_number,
+        pr_url: created.pr_url,
+        init_prompt: created.init_prompt,
+        setup: has_setup.then_some(SetupStatus::Pending),
     }))
 }
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct MigrateWorkspacesBody {
+    /// Only report where workspaces would move.
+    dry_run: bool,
+}
+
+/// POST /api/v1/projects/{projectId}/workspaces/migrate
+///
+/// Moves the project's workspaces to where its current workspace root and
+/// naming template put them. Per-workspace failures are reported in `moves`.
+pub(crate) async fn migrate_workspaces(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(project_id): Path<String>,
+    body: Option<Json<MigrateWorkspacesBody>>,
+) -> Result<Json<ApiEnvelope<MigrationReport>>, ApiError> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let config_dir = project_config_dir(&state, &project_id)?;
+
+    let report = migrate(&config_dir, &project_id, body.dry_run)
+        .await
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+    Ok(ApiEnvelope::ok(report))
+}
+
+/// GET /api/v1/projects/{projectId}/workspaces/gc
+///
+/// Disk usage of the project's workspaces and what a collection would do.
+/// Changes nothing.
+pub(crate) async fn get_workspace_gc(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(project_id): Path<String>,
+) -> Result<Json<ApiEnvelope<GcReport>>, ApiError> {
+    let options = GcOptions {
+        dry_run: true,
+        ..GcOptions::default()
+    };
+    run_workspace_gc(&state, &project_id, options).await
+}
+
+/// POST /api/v1/projects/{projectId}/workspaces/gc
+///
+/// Body (optional): `{ dryRun?: bool, deleteBranches?: bool }`. Archives
+/// merged and closed-PR workspaces, prunes stale worktrees and archives
+/// orphaned chat directories. Per-workspace failures are reported inline.
+pub(crate) async fn collect_workspaces(
+    State(state): State<Arc<HttpSharedState>>,
+    Path(project_id): Path<String>,
+    body: Option<Json<GcOptions>>,
+) -> Result<Json<ApiEnvelope<GcReport>>, ApiError> {
+    let options = body.map(|Json(b)| b).unwrap_or_default();
+    run_workspace_gc(&state, &project_id, options).await
+}
+
+async fn run_workspace_gc(
+    state: &HttpSharedState,
+    project_id: &str,
+    options: GcOptions,
+) -> Result<Json<ApiEnvelope<GcReport>>, ApiError> {
+    let config_dir = project_config_dir(state, project_id)?;
+    let report = collect_workspace_garbage(&config_dir, project_id, options)
+        .await
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+    Ok(ApiEnvelope::ok(report))
+}
+
+/// The config directory, after checking `project_id` is registered.
+fn project_config_dir(
+    state: &HttpSharedState,
+    project_id: &str,
+) -> Result<std::path::PathBuf, ApiError> {
+    let config_dir = state
+        .get_config_dir()
+        .ok_or_else(|| ApiError::internal("Config directory not set"))?;
+    let registry =
+        load_project_registry(&config_dir).map_err(|e| ApiError::internal(e.to_string()))?;
+    if !registry.projects.iter().any(|p| p.id == project_id) {
+        return Err(ApiError::not_found(format!(
+            "Project not found: {project_id}"
+        )));
+    }
+    Ok(config_dir)
+}
+
+/// Resolve a PR or issue with `gh`, then fetch and create the worktree.
+async fn create_from_github(
+    state: &HttpSharedState,
+    project_path: &str,
+    source: GithubSource,
+    project_init_prompt: Option<String>,
+    layout: &WorkspaceLayout,
+) -> Result<GithubWorkspace, ApiError> {
+    let (_, agent_shell) = crate::routes::load_agent_config(state);
+    let repo_path = std::path::PathBuf::from(project_path);
+
+    let plan = {
+        let repo_path = repo_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let runner = ShellGhRunner::new(agent_shell);
+            plan_github_workspace(&runner, &repo_path, source, project_init_prompt.as_deref())
+        })
+        .await
+        .map_err(|e| ApiError::internal(format!("Task join error: {e}")))?
+        .map_err(|e| ApiError::internal(e.to_string()))?
+    };
+
+    create_github_workspace(&repo_path, plan, layout)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))
+}
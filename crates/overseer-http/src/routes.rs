@@ -8,6 +8,7 @@ use axum::{
     http::StatusCode,
     response::Json,
 };
+use overseer_core::vcs::Vcs;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -102,54 +103,39 @@ pub(crate) fn load_agent_config(state: &HttpSharedState) -> (Option<String>, Opt
 }
 
 // ============================================================================
-// PR STATUS HELPERS (not yet in overseer-core, implemented here)
+// GITHUB HELPERS
 // ============================================================================
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct PrStatus {
-    number: i64,
-    state: String,
-    url: String,
-    is_draft: bool,
-}
-
-async fn get_pr_status(
-    workspace_path: String,
-    branch: String,
-    agent_shell: Option<String>,
-) -> Result<Option<PrStatus>, String> {
-    let args = vec![
-        "pr".to_string(),
-        "view".to_string(),
-        branch,
-        "--json".to_string(),
-        "number,state,url,isDraft".to_string(),
-    ];
-
-    let mut cmd = overseer_core::shell::build_login_shell_command(
-        "gh",
-        &args,
-        Some(&workspace_path),
-        agent_shell.as_deref(),
-    )?;
-
-    let output = cmd.output().map_err(|e| format!("Failed to run gh: {e}"))?;
+/// Run a blocking `gh` query (see `overseer_core::git::github`) off the async
+/// runtime and wrap its result in an invoke response.
+async fn run_gh_query<T, F>(query: F) -> (StatusCode, Json<InvokeResponse>)
+where
+    T: Serialize + Send + 'static,
+    F: FnOnce() -> Result<T, overseer_core::git::GitError> + Send + 'static,
+{
+    let result = tokio::task::spawn_blocking(query)
+        .await
+        .map_err(|e| format!("Task join error: {e}"))
+        .and_then(|r| r.map_err(|e| e.to_string()));
 
-    if !output.status.success() {
-        return Ok(None);
+    match result {
+        Ok(value) => (
+            StatusCode::OK,
+            Json(InvokeResponse {
+                success: true,
+                data: Some(serde_json::json!(value)),
+                error: None,
+            }),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(InvokeResponse {
+                success: false,
+                data: None,
+                error: Some(e),
+            }),
+        ),
     }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let parsed: serde_json::Value =
-        serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse gh output: {e}"))?;
-
-    Ok(Some(PrStatus {
-        number: parsed["number"].as_i64().unwrap_or(0),
-        state: parsed["state"].as_str().unwrap_or("OPEN").to_string(),
-        url: parsed["url"].as_str().unwrap_or("").to_string(),
-        is_draft: parsed["isDraft"].as_bool().unwrap_or(false),
-    }))
 }
 
 /// Response format for command invocation.
@@ -188,23 +174,50 @@ pub async fn invoke_handler(
         // GIT OPERATIONS
         // =====================================================================
         "list_workspaces" => dispatch_list_workspaces(request.args).await,
-        "list_changed_files" => dispatch_list_changed_files(request.args).await,
-        "list_commits" => dispatch_list_commits(request.args).await,
-        "list_commit_files" => dispatch_list_commit_files(request.args).await,
+        "list_changed_files" => dispatch_list_changed_files(&state, request.args).await,
+        "list_commits" => dispatch_list_commits(&state, request.args).await,
+        "list_commit_files" => dispatch_list_commit_files(&state, request.args).await,
         "is_git_repo" => dispatch_is_git_repo(request.args).await,
         "validate_project_path" => dispatch_validate_project_path(request.args).await,
         "detect_default_branch" => dispatch_detect_default_branch(request.args).await,
-        "get_file_diff" => dispatch_get_file_diff(request.args).await,
-        "get_uncommitted_diff" => dispatch_get_uncommitted_diff(request.args).await,
-        "get_commit_diff" => dispatch_get_commit_diff(request.args).await,
-        "add_workspace" => dispatch_add_workspace(request.args).await,
-        "archive_workspace" => dispatch_archive_workspace(request.args).await,
-        "check_merge" => dispatch_check_merge(request.args).await,
-        "merge_into_main" => dispatch_merge_into_main(request.args).await,
+        "get_file_diff" => dispatch_get_file_diff(&state, request.args, false).await,
+        "get_uncommitted_diff" => dispatch_get_uncommitted_diff(&state, request.args, false).await,
+        "get_commit_diff" => dispatch_get_commit_diff(&state, request.args, false).await,
+        "get_structured_file_diff" => dispatch_get_file_diff(&state, request.args, true).await,
+        "get_structured_uncommitted_diff" => {
+            dispatch_get_uncommitted_diff(&state, request.args, true).await
+        }
+        "get_structured_commit_diff" => dispatch_get_commit_diff(&state, request.args, true).await,
+        "add_workspace" => dispatch_add_workspace(&state, request.args).await,
+        "add_workspace_from_github" => {
+            dispatch_add_workspace_from_github(&state, request.args).await
+        }
+        "migrate_workspaces" => dispatch_migrate_workspaces(&state, request.args).await,
+        "collect_workspace_garbage" => {
+            dispatch_collect_workspace_garbage(&state, request.args).await
+        }
+        "run_workspace_setup" => dispatch_run_workspace_setup(&state, request.args).await,
+        "archive_workspace" => dispatch_archive_workspace(&state, request.args).await,
+        "check_merge" => dispatch_check_merge(&state, request.args).await,
+        "merge_into_main" => dispatch_merge_into_main(&state, request.args).await,
+        "sync_workspace" => dispatch_sync_workspace(request.args).await,
+        "get_sync_state" => dispatch_get_sync_state(&state, request.args).await,
+        "continue_sync" => dispatch_continue_sync(request.args).await,
+        "abort_sync" => dispatch_abort_sync(request.args).await,
+        "prepare_conflict_resolution" => dispatch_prepare_conflict_resolution(request.args).await,
         "rename_branch" => dispatch_rename_branch(request.args).await,
         "delete_branch" => dispatch_delete_branch(request.args).await,
         "list_files" => dispatch_list_files(request.args).await,
         "get_pr_status" => dispatch_get_pr_status(request.args).await,
+        "list_repo_pr_statuses" => dispatch_list_repo_pr_statuses(request.args).await,
+        "list_pr_checks" => dispatch_list_pr_checks(request.args).await,
+        "list_pr_review_comments" => dispatch_list_pr_review_comments(request.args).await,
+        "list_pr_review_threads" => dispatch_list_pr_review_threads(request.args).await,
+        "list_review_prs" => dispatch_list_review_prs(request.args).await,
+        "prepare_review_feedback" => dispatch_prepare_review_feedback(&state, request.args).await,
+        "mark_review_threads_addressed" => {
+            dispatch_mark_review_threads_addressed(&state, request.args).await
+        }
 
         // =====================================================================
         // PERSISTENCE OPERATIONS
@@ -256,6 +269,18 @@ pub async fn invoke_handler(
         "load_chat_metadata" => dispatch_load_chat_metadata(&state, request.args).await,
         "save_chat_metadata" => dispatch_save_chat_metadata(&state, request.args).await,
         "add_user_message" => dispatch_add_user_message(&state, request.args).await,
+        "list_turn_checkpoints" => dispatch_list_turn_checkpoints(&state, request.args).await,
+        "diff_turn_checkpoint" => dispatch_diff_turn_checkpoint(request.args).await,
+        "rewind_to_turn" => dispatch_rewind_to_turn(&state, request.args).await,
+        "search_chats" => dispatch_search_chats(&state, request.args).await,
+        "export_chat" => dispatch_export_chat(&state, request.args).await,
+        "import_chat" => dispatch_import_chat(&state, request.args).await,
+        "list_agent_transcripts" => dispatch_list_agent_transcripts(&state).await,
+        "import_agent_transcripts" => dispatch_import_agent_transcripts(&state, request.args).await,
+        "fork_chat" => dispatch_fork_chat(&state, request.args).await,
+        "get_chat_fork" => dispatch_get_chat_fork(&state, request.args).await,
+        "take_chat_fork" => dispatch_take_chat_fork(&state, request.args).await,
+        "hand_off_chat" => dispatch_hand_off_chat(&state, request.args).await,
 
         // =====================================================================
         // AGENTS (Claude)
@@ -355,6 +380,7 @@ pub async fn invoke_handler(
         "save_attachment_from_path" => {
             dispatch_save_attachment_from_path(&state, request.args).await
         }
+        "readable_attachment_path" => dispatch_readable_attachment_path(&state, request.args).await,
 
         // HTTP server commands (these wouldn't make sense via HTTP)
         "start_http_server" | "stop_http_server" | "get_http_server_status" => (
@@ -435,6 +461,7 @@ async fn dispatch_list_workspaces(args: serde_json::Value) -> (StatusCode, Json<
 }
 
 async fn dispatch_list_changed_files(
+    state: &HttpSharedState,
     args: serde_json::Value,
 ) -> (StatusCode, Json<InvokeResponse>) {
     let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
@@ -454,7 +481,10 @@ async fn dispatch_list_changed_files(
     let main_branch = args.get("mainBranch").and_then(|v| v.as_str());
 
     let path = PathBuf::from(workspace_path);
-    match overseer_core::git::list_changed_files(&path, main_branch).await {
+    match vcs_for(state, &path)
+        .list_changed_files(&path, main_branch)
+        .await
+    {
         Ok(result) => (
             StatusCode::OK,
             Json(InvokeResponse {
@@ -568,7 +598,13 @@ async fn dispatch_detect_default_branch(
     )
 }
 
-async fn dispatch_get_file_diff(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+/// `get_file_diff`, or `get_structured_file_diff` when `structured` is set
+/// (parsed hunks, honoring an optional `options` argument).
+async fn dispatch_get_file_diff(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+    structured: bool,
+) -> (StatusCode, Json<InvokeResponse>) {
     let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
         Some(p) => p,
         None => {
@@ -614,12 +650,27 @@ async fn dispatch_get_file_diff(args: serde_json::Value) -> (StatusCode, Json<In
     let main_branch = args.get("mainBranch").and_then(|v| v.as_str());
 
     let path = PathBuf::from(workspace_path);
-    match overseer_core::git::get_file_diff(&path, file_path, file_status, main_branch).await {
-        Ok(diff) => (
+    let result = if structured {
+        let options = match options_arg::<overseer_core::git::DiffOptions>(&args) {
+            Ok(options) => options,
+            Err(response) => return response,
+        };
+        vcs_for(state, &path)
+            .get_structured_file_diff(&path, file_path, file_status, main_branch, &options)
+            .await
+            .map(|files| serde_json::json!(files))
+    } else {
+        vcs_for(state, &path)
+            .get_file_diff(&path, file_path, file_status, main_branch)
+            .await
+            .map(|diff| serde_json::json!(diff))
+    };
+    match result {
+        Ok(data) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
-                data: Some(serde_json::json!(diff)),
+                data: Some(data),
                 error: None,
             }),
         ),
@@ -634,8 +685,12 @@ async fn dispatch_get_file_diff(args: serde_json::Value) -> (StatusCode, Json<In
     }
 }
 
+/// `get_uncommitted_diff`, or `get_structured_uncommitted_diff` when
+/// `structured` is set.
 async fn dispatch_get_uncommitted_diff(
+    state: &HttpSharedState,
     args: serde_json::Value,
+    structured: bool,
 ) -> (StatusCode, Json<InvokeResponse>) {
     let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
         Some(p) => p,
@@ -680,12 +735,27 @@ async fn dispatch_get_uncommitted_diff(
     };
 
     let path = PathBuf::from(workspace_path);
-    match overseer_core::git::get_uncommitted_diff(&path, file_path, file_status).await {
-        Ok(diff) => (
+    let result = if structured {
+        let options = match options_arg::<overseer_core::git::DiffOptions>(&args) {
+            Ok(options) => options,
+            Err(response) => return response,
+        };
+        vcs_for(state, &path)
+            .get_structured_uncommitted_diff(&path, file_path, file_status, &options)
+            .await
+            .map(|files| serde_json::json!(files))
+    } else {
+        vcs_for(state, &path)
+            .get_uncommitted_diff(&path, file_path, file_status)
+            .await
+            .map(|diff| serde_json::json!(diff))
+    };
+    match result {
+        Ok(data) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
-                data: Some(serde_json::json!(diff)),
+                data: Some(data),
                 error: None,
             }),
         ),
@@ -700,7 +770,10 @@ async fn dispatch_get_uncommitted_diff(
     }
 }
 
-async fn dispatch_list_commits(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+async fn dispatch_list_commits(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
     let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
         Some(p) => p,
         None => {
@@ -718,7 +791,10 @@ async fn dispatch_list_commits(args: serde_json::Value) -> (StatusCode, Json<Inv
     let main_branch = args.get("mainBranch").and_then(|v| v.as_str());
 
     let path = PathBuf::from(workspace_path);
-    match overseer_core::git::list_commits_on_branch(&path, main_branch).await {
+    match vcs_for(state, &path)
+        .list_commits_on_branch(&path, main_branch)
+        .await
+    {
         Ok(commits) => (
             StatusCode::OK,
             Json(InvokeResponse {
@@ -738,7 +814,10 @@ async fn dispatch_list_commits(args: serde_json::Value) -> (StatusCode, Json<Inv
     }
 }
 
-async fn dispatch_list_commit_files(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+async fn dispatch_list_commit_files(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
     let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
         Some(p) => p,
         None => {
@@ -768,7 +847,10 @@ async fn dispatch_list_commit_files(args: serde_json::Value) -> (StatusCode, Jso
     };
 
     let path = PathBuf::from(workspace_path);
-    match overseer_core::git::list_commit_files(&path, commit_sha).await {
+    match vcs_for(state, &path)
+        .list_commit_files(&path, commit_sha)
+        .await
+    {
         Ok(files) => (
             StatusCode::OK,
             Json(InvokeResponse {
@@ -788,7 +870,12 @@ async fn dispatch_list_commit_files(args: serde_json::Value) -> (StatusCode, Jso
     }
 }
 
-async fn dispatch_get_commit_diff(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+/// `get_commit_diff`, or `get_structured_commit_diff` when `structured` is set.
+async fn dispatch_get_commit_diff(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+    structured: bool,
+) -> (StatusCode, Json<InvokeResponse>) {
     let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
         Some(p) => p,
         None => {
@@ -846,12 +933,27 @@ async fn dispatch_get_commit_diff(args: serde_json::Value) -> (StatusCode, Json<
     };
 
     let path = PathBuf::from(workspace_path);
-    match overseer_core::git::get_commit_diff(&path, commit_sha, file_path, file_status).await {
-        Ok(diff) => (
+    let result = if structured {
+        let options = match options_arg::<overseer_core::git::DiffOptions>(&args) {
+            Ok(options) => options,
+            Err(response) => return response,
+        };
+        vcs_for(state, &path)
+            .get_structured_commit_diff(&path, commit_sha, file_path, file_status, &options)
+            .await
+            .map(|files| serde_json::json!(files))
+    } else {
+        vcs_for(state, &path)
+            .get_commit_diff(&path, commit_sha, file_path, file_status)
+            .await
+            .map(|diff| serde_json::json!(diff))
+    };
+    match result {
+        Ok(data) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
-                data: Some(serde_json::json!(diff)),
+                data: Some(data),
                 error: None,
             }),
         ),
@@ -866,6 +968,26 @@ async fn dispatch_get_commit_diff(args: serde_json::Value) -> (StatusCode, Json<
     }
 }
 
+/// The optional `options` argument of a command (e.g. the structured diff
+/// and merge commands); defaults when absent.
+fn options_arg<T: serde::de::DeserializeOwned + Default>(
+    args: &serde_json::Value,
+) -> Result<T, (StatusCode, Json<InvokeResponse>)> {
+    match args.get("options").filter(|v| !v.is_null()) {
+        Some(v) => serde_json::from_value(v.clone()).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Invalid options: {e}")),
+                }),
+            )
+        }),
+        None => Ok(Default::default()),
+    }
+}
+
 async fn dispatch_get_pr_status(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
     let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
         Some(p) => p.to_string(),
@@ -900,12 +1022,286 @@ async fn dispatch_get_pr_status(args: serde_json::Value) -> (StatusCode, Json<In
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
 
-    match get_pr_status(workspace_path, branch, agent_shell).await {
-        Ok(status) => (
+    run_gh_query(move || {
+        let runner = overseer_core::git::ShellGhRunner::new(agent_shell);
+        overseer_core::git::get_pr_status(&runner, &PathBuf::from(workspace_path), &branch)
+    })
+    .await
+}
+
+async fn dispatch_list_repo_pr_statuses(
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
+        Some(p) => p.to_string(),
+        None => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some("Missing required argument: workspacePath".to_string()),
+                }),
+            );
+        }
+    };
+
+    let agent_shell = args
+        .get("agentShell")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    run_gh_query(move || {
+        let runner = overseer_core::git::ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_repo_pr_statuses(&runner, &PathBuf::from(workspace_path))
+    })
+    .await
+}
+
+async fn dispatch_list_pr_checks(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+    let (workspace_path, pr_number) = match workspace_pr_args(&args) {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let agent_shell = args
+        .get("agentShell")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    run_gh_query(move || {
+        let runner = overseer_core::git::ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_pr_checks(&runner, &PathBuf::from(workspace_path), pr_number)
+    })
+    .await
+}
+
+async fn dispatch_list_pr_review_comments(
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let (workspace_path, pr_number) = match workspace_pr_args(&args) {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let agent_shell = args
+        .get("agentShell")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    run_gh_query(move || {
+        let runner = overseer_core::git::ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_review_comments(&runner, &PathBuf::from(workspace_path), pr_number)
+    })
+    .await
+}
+
+async fn dispatch_list_pr_review_threads(
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let (workspace_path, pr_number) = match workspace_pr_args(&args) {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let agent_shell = args
+        .get("agentShell")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    run_gh_query(move || {
+        let runner = overseer_core::git::ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_review_threads(&runner, &PathBuf::from(workspace_path), pr_number)
+    })
+    .await
+}
+
+async fn dispatch_prepare_review_feedback(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let chat_dir = match get_chat_dir_from_args(state, &args) {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(v) => PathBuf::from(v),
+        Err(response) => return response,
+    };
+    let include_addressed = args
+        .get("includeAddressed")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let agent_shell = args
+        .get("agentShell")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    let branch = match overseer_core::git::get_current_branch(&workspace_path).await {
+        Ok(branch) => branch,
+        Err(e) => return invoke_result::<()>(Err(e)),
+    };
+    run_gh_query(move || {
+        let runner = overseer_core::git::ShellGhRunner::new(agent_shell);
+        overseer_core::pr_review::prepare_review_feedback(
+            &runner,
+            &workspace_path,
+            &branch,
+            &chat_dir,
+            include_addressed,
+        )
+    })
+    .await
+}
+
+async fn dispatch_mark_review_threads_addressed(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let chat_dir = match get_chat_dir_from_args(state, &args) {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    let chat_id = match required_str_arg(&args, "chatId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let Some(pr_number) = args.get("prNumber").and_then(|v| v.as_u64()) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(InvokeResponse {
+                success: false,
+                data: None,
+                error: Some("Missing required argument: prNumber".to_string()),
+            }),
+        );
+    };
+    let thread_ids: Vec<String> = args
+        .get("threadIds")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
+    invoke_result(
+        overseer_core::persistence::mark_review_threads_addressed(
+            &chat_dir,
+            pr_number,
+            chat_id,
+            &thread_ids,
+        )
+        .map(|_| ()),
+    )
+}
+
+/// Extract the `workspacePath` and `prNumber` arguments shared by the PR
+/// checks, review comments and review threads commands.
+fn workspace_pr_args(
+    args: &serde_json::Value,
+) -> Result<(String, u64), (StatusCode, Json<InvokeResponse>)> {
+    let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
+        Some(p) => p.to_string(),
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some("Missing required argument: workspacePath".to_string()),
+                }),
+            ));
+        }
+    };
+
+    let pr_number = match args.get("prNumber").and_then(|v| v.as_u64()) {
+        Some(n) => n,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some("Missing required argument: prNumber".to_string()),
+                }),
+            ));
+        }
+    };
+
+    Ok((workspace_path, pr_number))
+}
+
+async fn dispatch_list_review_prs(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+    let repo_path = match args.get("repoPath").and_then(|v| v.as_str()) {
+        Some(p) => p.to_string(),
+        None => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some("Missing required argument: repoPath".to_string()),
+                }),
+            );
+        }
+    };
+
+    let agent_shell = args
+        .get("agentShell")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    run_gh_query(move || {
+        let runner = overseer_core::git::ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_review_prs(&runner, &PathBuf::from(repo_path), 20)
+    })
+    .await
+}
+
+async fn dispatch_add_workspace(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let repo_path = match args.get("repoPath").and_then(|v| v.as_str()) {
+        Some(p) => p,
+        None => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some("Missing required argument: repoPath".to_string()),
+                }),
+            );
+        }
+    };
+
+    let branch = match args.get("branch").and_then(|v| v.as_str()) {
+        Some(b) => b,
+        None => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some("Missing required argument: branch".to_string()),
+                }),
+            );
+        }
+    };
+
+    let path = PathBuf::from(repo_path);
+    let layout = overseer_core::workspace_layout::resolve_workspace_layout(
+        state.get_config_dir().as_deref(),
+        &path,
+    );
+    match overseer_core::vcs::add_project_workspace(
+        state.get_config_dir().as_deref(),
+        &path,
+        branch,
+        &layout,
+    )
+    .await
+    {
+        Ok(workspace_path) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
-                data: Some(serde_json::json!(status)),
+                data: Some(serde_json::json!(workspace_path.to_string_lossy())),
                 error: None,
             }),
         ),
@@ -914,15 +1310,18 @@ async fn dispatch_get_pr_status(args: serde_json::Value) -> (StatusCode, Json<In
             Json(InvokeResponse {
                 success: false,
                 data: None,
-                error: Some(e),
+                error: Some(e.to_string()),
             }),
         ),
     }
 }
 
-async fn dispatch_add_workspace(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+async fn dispatch_add_workspace_from_github(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
     let repo_path = match args.get("repoPath").and_then(|v| v.as_str()) {
-        Some(p) => p,
+        Some(p) => PathBuf::from(p),
         None => {
             return (
                 StatusCode::BAD_REQUEST,
@@ -935,42 +1334,164 @@ async fn dispatch_add_workspace(args: serde_json::Value) -> (StatusCode, Json<In
         }
     };
 
-    let branch = match args.get("branch").and_then(|v| v.as_str()) {
-        Some(b) => b,
+    let source: overseer_core::github_workspace::GithubSource = match args
+        .get("source")
+        .map(|v| serde_json::from_value(v.clone()))
+    {
+        Some(Ok(source)) => source,
+        Some(Err(e)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some(format!("Invalid source: {e}")),
+                }),
+            );
+        }
         None => {
             return (
                 StatusCode::BAD_REQUEST,
                 Json(InvokeResponse {
                     success: false,
                     data: None,
-                    error: Some("Missing required argument: branch".to_string()),
+                    error: Some("Missing required argument: source".to_string()),
                 }),
             );
         }
     };
 
-    let path = PathBuf::from(repo_path);
-    match overseer_core::git::add_workspace(&path, branch).await {
-        Ok(workspace_path) => (
-            StatusCode::OK,
-            Json(InvokeResponse {
-                success: true,
-                data: Some(serde_json::json!(workspace_path.to_string_lossy())),
-                error: None,
-            }),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(InvokeResponse {
-                success: false,
-                data: None,
-                error: Some(e.to_string()),
-            }),
-        ),
-    }
+    let init_prompt = args
+        .get("initPrompt")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let agent_shell = args
+        .get("agentShell")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    let plan = {
+        let repo_path = repo_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let runner = overseer_core::git::ShellGhRunner::new(agent_shell);
+            overseer_core::github_workspace::plan_github_workspace(
+                &runner,
+                &repo_path,
+                source,
+                init_prompt.as_deref(),
+            )
+        })
+        .await
+        .map_err(|e| format!("Task join error: {e}"))
+        .and_then(|r| r.map_err(|e| e.to_string()))
+    };
+    let result = match plan {
+        Ok(plan) => {
+            let layout = overseer_core::workspace_layout::resolve_workspace_layout(
+                state.get_config_dir().as_deref(),
+                &repo_path,
+            );
+            overseer_core::github_workspace::create_github_workspace(&repo_path, plan, &layout)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(workspace) => (
+            StatusCode::OK,
+            Json(InvokeResponse {
+                success: true,
+                data: Some(serde_json::json!(workspace)),
+                error: None,
+            }),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(InvokeResponse {
+                success: false,
+                data: None,
+                error: Some(e),
+            }),
+        ),
+    }
+}
+
+async fn dispatch_migrate_workspaces(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_id = match required_str_arg(&args, "projectId") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let dry_run = args
+        .get("dryRun")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let Some(config_dir) = state.get_config_dir() else {
+        return invoke_result(Err::<(), _>("Config directory not set"));
+    };
+
+    invoke_result(
+        overseer_core::workspace_layout::migrate_workspaces(&config_dir, project_id, dry_run).await,
+    )
+}
+
+async fn dispatch_collect_workspace_garbage(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_id = match required_str_arg(&args, "projectId") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let options = match options_arg::<overseer_core::workspace_gc::GcOptions>(&args) {
+        Ok(options) => options,
+        Err(response) => return response,
+    };
+    let Some(config_dir) = state.get_config_dir() else {
+        return invoke_result(Err::<(), _>("Config directory not set"));
+    };
+
+    invoke_result(
+        overseer_core::workspace_gc::collect_workspace_garbage(&config_dir, project_id, options)
+            .await,
+    )
+}
+
+async fn dispatch_run_workspace_setup(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_id = match required_str_arg(&args, "projectId") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let workspace_id = match required_str_arg(&args, "workspaceId") {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+    let Some(config_dir) = state.get_config_dir() else {
+        return invoke_result(Err::<(), _>("Config directory not set"));
+    };
+
+    invoke_result(
+        overseer_core::workspace_setup::run_workspace_setup(
+            Arc::clone(&state.context.event_bus),
+            &config_dir,
+            project_id,
+            workspace_id,
+        )
+        .await,
+    )
 }
 
-async fn dispatch_archive_workspace(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+async fn dispatch_archive_workspace(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
     let repo_path = match args.get("repoPath").and_then(|v| v.as_str()) {
         Some(p) => p,
         None => {
@@ -1003,100 +1524,160 @@ async fn dispatch_archive_workspace(args: serde_json::Value) -> (StatusCode, Jso
 
     let repo = PathBuf::from(repo_path);
     let workspace = PathBuf::from(workspace_path);
-    match overseer_core::git::archive_workspace(&repo, &workspace, force).await {
-        Ok(()) => (
-            StatusCode::OK,
-            Json(InvokeResponse {
-                success: true,
-                data: None,
-                error: None,
-            }),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
+    invoke_result(
+        overseer_core::workspace_teardown::archive_workspace_with_teardown(
+            state.get_config_dir().as_deref(),
+            &repo,
+            &workspace,
+            force,
+        )
+        .await,
+    )
+}
+
+/// The version control backend for a project checkout or workspace.
+fn vcs_for(state: &HttpSharedState, path: &std::path::Path) -> Vcs {
+    Vcs::resolve(state.get_config_dir().as_deref(), path)
+}
+
+/// The result of a merge check or merge, with merges the project disables
+/// refused as forbidden.
+fn merge_response(
+    result: Result<overseer_core::git::MergeResult, overseer_core::git::GitError>,
+) -> (StatusCode, Json<InvokeResponse>) {
+    match result {
+        Err(e @ overseer_core::git::GitError::MergeDisabled { .. }) => (
+            StatusCode::FORBIDDEN,
             Json(InvokeResponse {
                 success: false,
                 data: None,
                 error: Some(e.to_string()),
             }),
         ),
+        result => invoke_result(result),
     }
 }
 
-async fn dispatch_check_merge(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
-    let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
-        Some(p) => p,
-        None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(InvokeResponse {
-                    success: false,
-                    data: None,
-                    error: Some("Missing required argument: workspacePath".to_string()),
-                }),
-            );
-        }
+async fn dispatch_check_merge(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let strategy = match args.get("strategy").filter(|v| !v.is_null()) {
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(strategy) => strategy,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(InvokeResponse {
+                        success: false,
+                        data: None,
+                        error: Some(format!("Invalid strategy: {e}")),
+                    }),
+                );
+            }
+        },
+        None => overseer_core::git::MergeStrategy::default(),
     };
 
     let main_branch = args.get("mainBranch").and_then(|v| v.as_str());
 
     let path = PathBuf::from(workspace_path);
-    match overseer_core::git::check_merge(&path, main_branch).await {
-        Ok(result) => (
-            StatusCode::OK,
-            Json(InvokeResponse {
-                success: true,
-                data: Some(serde_json::to_value(result).unwrap_or_default()),
-                error: None,
-            }),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(InvokeResponse {
-                success: false,
-                data: None,
-                error: Some(e.to_string()),
-            }),
-        ),
-    }
+    merge_response(
+        overseer_core::vcs::check_workspace_merge(
+            state.get_config_dir().as_deref(),
+            &path,
+            main_branch,
+            strategy,
+        )
+        .await,
+    )
 }
 
-async fn dispatch_merge_into_main(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
-    let workspace_path = match args.get("workspacePath").and_then(|v| v.as_str()) {
-        Some(p) => p,
-        None => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(InvokeResponse {
-                    success: false,
-                    data: None,
-                    error: Some("Missing required argument: workspacePath".to_string()),
-                }),
-            );
-        }
+async fn dispatch_merge_into_main(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let options = match options_arg::<overseer_core::git::MergeOptions>(&args) {
+        Ok(options) => options,
+        Err(response) => return response,
     };
 
     let main_branch = args.get("mainBranch").and_then(|v| v.as_str());
 
     let path = PathBuf::from(workspace_path);
-    match overseer_core::git::merge_into_main(&path, main_branch).await {
-        Ok(result) => (
-            StatusCode::OK,
-            Json(InvokeResponse {
-                success: true,
-                data: Some(serde_json::to_value(result).unwrap_or_default()),
-                error: None,
-            }),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(InvokeResponse {
-                success: false,
-                data: None,
-                error: Some(e.to_string()),
-            }),
-        ),
-    }
+    merge_response(
+        overseer_core::vcs::merge_workspace_into_main(
+            state.get_config_dir().as_deref(),
+            &path,
+            main_branch,
+            &options,
+        )
+        .await,
+    )
+}
+
+async fn dispatch_sync_workspace(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let options = match options_arg::<overseer_core::git::SyncOptions>(&args) {
+        Ok(options) => options,
+        Err(response) => return response,
+    };
+    let main_branch = args.get("mainBranch").and_then(|v| v.as_str());
+
+    let path = PathBuf::from(workspace_path);
+    invoke_result(overseer_core::git::sync_with_default_branch(&path, main_branch, &options).await)
+}
+
+async fn dispatch_get_sync_state(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let path = PathBuf::from(workspace_path);
+    invoke_result(vcs_for(state, &path).get_sync_state(&path).await)
+}
+
+async fn dispatch_continue_sync(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let path = PathBuf::from(workspace_path);
+    invoke_result(overseer_core::git::continue_sync(&path).await)
+}
+
+async fn dispatch_abort_sync(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let path = PathBuf::from(workspace_path);
+    invoke_result(overseer_core::git::abort_sync(&path).await)
+}
+
+async fn dispatch_prepare_conflict_resolution(
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let path = PathBuf::from(workspace_path);
+    invoke_result(overseer_core::conflicts::prepare_conflict_resolution(&path).await)
 }
 
 async fn dispatch_rename_branch(args: serde_json::Value) -> (StatusCode, Json<InvokeResponse>) {
@@ -1180,8 +1761,16 @@ async fn dispatch_delete_branch(args: serde_json::Value) -> (StatusCode, Json<In
         }
     };
 
+    // Squash-merged branches look unmerged to git, so callers may force it.
+    let force = args.get("force").and_then(|v| v.as_bool()).unwrap_or(false);
+
     let path = PathBuf::from(repo_path);
-    match overseer_core::git::delete_branch(&path, branch_name).await {
+    let result = if force {
+        overseer_core::git::force_delete_branch(&path, branch_name).await
+    } else {
+        overseer_core::git::delete_branch(&path, branch_name).await
+    };
+    match result {
         Ok(()) => (
             StatusCode::OK,
             Json(InvokeResponse {
@@ -1295,7 +1884,7 @@ async fn dispatch_load_project_registry(
         }
     };
 
-    match overseer_core::persistence::load_project_registry(&config_dir) {
+    match overseer_core::persistence::load_project_registry_versioned(&config_dir) {
         Ok(registry) => (
             StatusCode::OK,
             Json(InvokeResponse {
@@ -1348,12 +1937,27 @@ async fn dispatch_save_project_registry(
             }
         };
 
-    match overseer_core::persistence::save_project_registry(&config_dir, &registry) {
-        Ok(()) => (
+    let base: Option<overseer_core::persistence::Version> =
+        match serde_json::from_value(args.get("version").cloned().unwrap_or_default()) {
+            Ok(v) => v,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(InvokeResponse {
+                        success: false,
+                        data: None,
+                        error: Some(format!("Invalid version: {}", e)),
+                    }),
+                );
+            }
+        };
+
+    match overseer_core::persistence::save_project_registry(&config_dir, &registry, base.as_ref()) {
+        Ok(version) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
-                data: None,
+                data: Some(serde_json::to_value(version).unwrap_or_default()),
                 error: None,
             }),
         ),
@@ -1401,24 +2005,11 @@ async fn dispatch_upsert_project(
             }
         };
 
-    let mut registry = match overseer_core::persistence::load_project_registry(&config_dir) {
-        Ok(r) => r,
-        Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(InvokeResponse {
-                    success: false,
-                    data: None,
-                    error: Some(e.to_string()),
-                }),
-            );
-        }
-    };
-
-    overseer_core::persistence::upsert_project(&mut registry, project);
-
-    match overseer_core::persistence::save_project_registry(&config_dir, &registry) {
-        Ok(()) => (
+    let updated = overseer_core::persistence::update_project_registry(&config_dir, |registry| {
+        overseer_core::persistence::upsert_project(registry, project.clone());
+    });
+    match updated {
+        Ok(_) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
@@ -1469,24 +2060,11 @@ async fn dispatch_remove_project(
         }
     };
 
-    let mut registry = match overseer_core::persistence::load_project_registry(&config_dir) {
-        Ok(r) => r,
-        Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(InvokeResponse {
-                    success: false,
-                    data: None,
-                    error: Some(e.to_string()),
-                }),
-            );
-        }
-    };
-
-    overseer_core::persistence::remove_project(&mut registry, project_id);
-
-    match overseer_core::persistence::save_project_registry(&config_dir, &registry) {
-        Ok(()) => (
+    let updated = overseer_core::persistence::update_project_registry(&config_dir, |registry| {
+        overseer_core::persistence::remove_project(registry, project_id);
+    });
+    match updated {
+        Ok(_) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
@@ -1702,7 +2280,7 @@ async fn dispatch_load_chat_index(
         .join("chats")
         .join(project_name)
         .join(workspace_name);
-    match overseer_core::persistence::load_chat_index(&chat_dir) {
+    match overseer_core::persistence::load_chat_index_versioned(&chat_dir) {
         Ok(index) => (
             StatusCode::OK,
             Json(InvokeResponse {
@@ -1783,16 +2361,31 @@ async fn dispatch_save_chat_index(
             }
         };
 
+    let base: Option<overseer_core::persistence::Version> =
+        match serde_json::from_value(args.get("version").cloned().unwrap_or_default()) {
+            Ok(v) => v,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(InvokeResponse {
+                        success: false,
+                        data: None,
+                        error: Some(format!("Invalid version: {}", e)),
+                    }),
+                );
+            }
+        };
+
     let chat_dir = config_dir
         .join("chats")
         .join(project_name)
         .join(workspace_name);
-    match overseer_core::persistence::save_chat_index(&chat_dir, &index) {
-        Ok(()) => (
+    match overseer_core::persistence::save_chat_index(&chat_dir, &index, base.as_ref()) {
+        Ok(version) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
-                data: None,
+                data: Some(serde_json::to_value(version).unwrap_or_default()),
                 error: None,
             }),
         ),
@@ -1873,24 +2466,11 @@ async fn dispatch_upsert_chat_entry(
         .join(project_name)
         .join(workspace_name);
 
-    let mut index = match overseer_core::persistence::load_chat_index(&chat_dir) {
-        Ok(i) => i,
-        Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(InvokeResponse {
-                    success: false,
-                    data: None,
-                    error: Some(e.to_string()),
-                }),
-            );
-        }
-    };
-
-    overseer_core::persistence::upsert_chat_entry(&mut index, entry);
-
-    match overseer_core::persistence::save_chat_index(&chat_dir, &index) {
-        Ok(()) => (
+    let updated = overseer_core::persistence::update_chat_index(&chat_dir, |index| {
+        overseer_core::persistence::upsert_chat_entry(index, entry.clone());
+    });
+    match updated {
+        Ok(_) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
@@ -1963,35 +2543,22 @@ async fn dispatch_remove_chat_entry(
                 Json(InvokeResponse {
                     success: false,
                     data: None,
-                    error: Some("Missing required argument: chatId".to_string()),
-                }),
-            );
-        }
-    };
-
-    let chat_dir = config_dir
-        .join("chats")
-        .join(project_name)
-        .join(workspace_name);
-
-    let mut index = match overseer_core::persistence::load_chat_index(&chat_dir) {
-        Ok(i) => i,
-        Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(InvokeResponse {
-                    success: false,
-                    data: None,
-                    error: Some(e.to_string()),
+                    error: Some("Missing required argument: chatId".to_string()),
                 }),
             );
         }
     };
 
-    overseer_core::persistence::remove_chat_entry(&mut index, chat_id);
+    let chat_dir = config_dir
+        .join("chats")
+        .join(project_name)
+        .join(workspace_name);
 
-    match overseer_core::persistence::save_chat_index(&chat_dir, &index) {
-        Ok(()) => (
+    let updated = overseer_core::persistence::update_chat_index(&chat_dir, |index| {
+        overseer_core::persistence::remove_chat_entry(index, chat_id);
+    });
+    match updated {
+        Ok(_) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
@@ -2753,7 +3320,7 @@ async fn dispatch_archive_chat_dir(
         );
     }
 
-    if let Err(e) = std::fs::rename(&source, &dest) {
+    if let Err(e) = overseer_core::persistence::rename_chat_dir(&source, &dest) {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(InvokeResponse {
@@ -3671,17 +4238,73 @@ async fn dispatch_add_user_message(
     };
 
     let meta = args.get("meta").cloned();
+    let workspace_path = args.get("workspacePath").and_then(|v| v.as_str());
 
     match state
         .context
         .chat_sessions
         .add_user_message(chat_id, content, meta)
     {
-        Ok(event) => (
+        Ok(event) => {
+            if let Some(workspace_path) = workspace_path {
+                overseer_core::checkpoints::checkpoint_turn(
+                    &PathBuf::from(workspace_path),
+                    chat_id,
+                    &event,
+                )
+                .await;
+            }
+            (
+                StatusCode::OK,
+                Json(InvokeResponse {
+                    success: true,
+                    data: Some(serde_json::to_value(event).unwrap_or_default()),
+                    error: None,
+                }),
+            )
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(InvokeResponse {
+                success: false,
+                data: None,
+                error: Some(e),
+            }),
+        ),
+    }
+}
+
+// ============================================================================
+// UTILITY COMMAND DISPATCHERS
+// ============================================================================
+
+/// A required string argument, or the 400 response for its absence.
+fn required_str_arg<'a>(
+    args: &'a serde_json::Value,
+    name: &str,
+) -> Result<&'a str, (StatusCode, Json<InvokeResponse>)> {
+    args.get(name).and_then(|v| v.as_str()).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(InvokeResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Missing required argument: {name}")),
+            }),
+        )
+    })
+}
+
+/// Turn a core result into an invoke response.
+fn invoke_result<T: serde::Serialize>(
+    result: Result<T, impl std::fmt::Display>,
+) -> (StatusCode, Json<InvokeResponse>) {
+    match result {
+        Ok(data) => (
             StatusCode::OK,
             Json(InvokeResponse {
                 success: true,
-                data: Some(serde_json::to_value(event).unwrap_or_default()),
+                data: Some(serde_json::json!(data)),
                 error: None,
             }),
         ),
@@ -3690,15 +4313,378 @@ async fn dispatch_add_user_message(
             Json(InvokeResponse {
                 success: false,
                 data: None,
-                error: Some(e),
+                error: Some(e.to_string()),
             }),
         ),
     }
 }
 
-// ============================================================================
-// UTILITY COMMAND DISPATCHERS
-// ============================================================================
+async fn dispatch_list_turn_checkpoints(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_name = match required_str_arg(&args, "projectName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_name = match required_str_arg(&args, "workspaceName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let chat_id = match required_str_arg(&args, "chatId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+
+    invoke_result(
+        overseer_core::checkpoints::list_turn_checkpoints(
+            &state.context.chat_sessions,
+            project_name,
+            workspace_name,
+            chat_id,
+            &PathBuf::from(workspace_path),
+        )
+        .await,
+    )
+}
+
+async fn dispatch_diff_turn_checkpoint(
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let chat_id = match required_str_arg(&args, "chatId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let turn_id = match required_str_arg(&args, "turnId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_path = PathBuf::from(workspace_path);
+    let options = match options_arg::<overseer_core::git::DiffOptions>(&args) {
+        Ok(options) => options,
+        Err(response) => return response,
+    };
+
+    let result = async {
+        let checkpoint = overseer_core::git::find_checkpoint(&workspace_path, chat_id, turn_id)
+            .await?
+            .ok_or_else(|| {
+                overseer_core::git::GitError::Other(format!("No checkpoint for turn {turn_id}"))
+            })?;
+        overseer_core::git::diff_checkpoint(&workspace_path, &checkpoint, &options).await
+    }
+    .await;
+    invoke_result(result)
+}
+
+async fn dispatch_rewind_to_turn(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_name = match required_str_arg(&args, "projectName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_name = match required_str_arg(&args, "workspaceName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let chat_id = match required_str_arg(&args, "chatId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let turn_id = match required_str_arg(&args, "turnId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let truncate_chat = args
+        .get("truncateChat")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let claude_config_dir = project_claude_config_dir(state, project_name);
+    let claude_root = overseer_core::chat_fork::claude_root(claude_config_dir.as_deref());
+    invoke_result(
+        overseer_core::checkpoints::rewind_to_turn(
+            &state.context.chat_sessions,
+            project_name,
+            workspace_name,
+            chat_id,
+            &PathBuf::from(workspace_path),
+            turn_id,
+            truncate_chat,
+            claude_root.as_deref(),
+        )
+        .await,
+    )
+}
+
+async fn dispatch_search_chats(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let query: overseer_core::chat_search::SearchQuery =
+        match serde_json::from_value(args.get("query").cloned().unwrap_or_default()) {
+            Ok(q) => q,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(InvokeResponse {
+                        success: false,
+                        data: None,
+                        error: Some(format!("Invalid query: {e}")),
+                    }),
+                );
+            }
+        };
+
+    invoke_result(state.context.chat_sessions.search(&query))
+}
+
+async fn dispatch_export_chat(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_name = match required_str_arg(&args, "projectName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_name = match required_str_arg(&args, "workspaceName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let chat_id = match required_str_arg(&args, "chatId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let format = match required_str_arg(&args, "format") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let format = match overseer_core::chat_export::ExportFormat::parse(format) {
+        Ok(v) => v,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e),
+                }),
+            );
+        }
+    };
+    // Exports default to everything the desktop shows.
+    let view = args.get("view").and_then(|v| v.as_str()).unwrap_or("full");
+    let view = match overseer_core::chat_view::View::from_query(Some(view)) {
+        Ok(v) => v,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(InvokeResponse {
+                    success: false,
+                    data: None,
+                    error: Some(e),
+                }),
+            );
+        }
+    };
+
+    invoke_result(state.context.chat_sessions.export_chat(
+        project_name,
+        workspace_name,
+        chat_id,
+        format,
+        view,
+    ))
+}
+
+async fn dispatch_import_chat(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_name = match required_str_arg(&args, "projectName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_name = match required_str_arg(&args, "workspaceName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_id = match required_str_arg(&args, "workspaceId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let bundle = match required_str_arg(&args, "bundle") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+
+    invoke_result(state.context.chat_sessions.import_chat(
+        project_name,
+        workspace_name,
+        workspace_id,
+        bundle,
+    ))
+}
+
+async fn dispatch_list_agent_transcripts(
+    state: &HttpSharedState,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let Some(config_dir) = state.get_config_dir() else {
+        return invoke_result::<()>(Err("Config directory not set"));
+    };
+    invoke_result(overseer_core::transcript_import::find_transcripts(
+        &config_dir,
+        &overseer_core::transcript_import::TranscriptRoots::from_env(),
+    ))
+}
+
+async fn dispatch_import_agent_transcripts(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let Some(config_dir) = state.get_config_dir() else {
+        return invoke_result::<()>(Err("Config directory not set"));
+    };
+    let paths: Option<Vec<String>> = args
+        .get("paths")
+        .and_then(|v| serde_json::from_value(v.clone()).ok());
+    invoke_result(overseer_core::transcript_import::import_transcripts(
+        &config_dir,
+        &overseer_core::transcript_import::TranscriptRoots::from_env(),
+        paths.as_deref(),
+    ))
+}
+
+async fn dispatch_fork_chat(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_name = match required_str_arg(&args, "projectName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_name = match required_str_arg(&args, "workspaceName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let chat_id = match required_str_arg(&args, "chatId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let Some(seq) = args.get("seq").and_then(|v| v.as_u64()) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(InvokeResponse {
+                success: false,
+                data: None,
+                error: Some("Missing required argument: seq".to_string()),
+            }),
+        );
+    };
+
+    let claude_config_dir = project_claude_config_dir(state, project_name);
+    let claude_root = overseer_core::chat_fork::claude_root(claude_config_dir.as_deref());
+    invoke_result(state.context.chat_sessions.fork_chat(
+        project_name,
+        workspace_name,
+        chat_id,
+        seq,
+        claude_root.as_deref(),
+    ))
+}
+
+async fn dispatch_get_chat_fork(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_name = match required_str_arg(&args, "projectName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_name = match required_str_arg(&args, "workspaceName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let chat_id = match required_str_arg(&args, "chatId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    invoke_result(
+        state
+            .context
+            .chat_sessions
+            .load_chat_fork(project_name, workspace_name, chat_id),
+    )
+}
+
+async fn dispatch_take_chat_fork(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let chat_id = match required_str_arg(&args, "chatId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    invoke_result(state.context.chat_sessions.take_pending_fork(chat_id))
+}
+
+async fn dispatch_hand_off_chat(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let project_name = match required_str_arg(&args, "projectName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_name = match required_str_arg(&args, "workspaceName") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let chat_id = match required_str_arg(&args, "chatId") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let to_agent = match required_str_arg(&args, "toAgent") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let workspace_path = match required_str_arg(&args, "workspacePath") {
+        Ok(v) => PathBuf::from(v),
+        Err(response) => return response,
+    };
+
+    // A workspace whose changes can't be listed hands off without them.
+    let touched = vcs_for(state, &workspace_path)
+        .list_changed_files(&workspace_path, None)
+        .await
+        .map(overseer_core::chat_handoff::touched_files)
+        .unwrap_or_default();
+    let handoff = state.context.chat_sessions.hand_off(
+        project_name,
+        workspace_name,
+        chat_id,
+        to_agent,
+        &touched,
+    );
+    if let Ok(handoff) = &handoff {
+        overseer_core::checkpoints::checkpoint_turn(&workspace_path, chat_id, &handoff.event).await;
+    }
+    invoke_result(handoff)
+}
 
 async fn dispatch_is_debug_mode() -> (StatusCode, Json<InvokeResponse>) {
     let is_debug = std::env::var("OVERSEER_DEBUG").is_ok();
@@ -4007,7 +4993,7 @@ async fn dispatch_save_attachment(
     let path = attachment_dir.join(&filename);
     let size = data.len();
 
-    if let Err(e) = std::fs::write(&path, &data) {
+    if let Err(e) = overseer_core::persistence::write_attachment(&path, &data) {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(InvokeResponse {
@@ -4125,7 +5111,7 @@ async fn dispatch_save_attachment_from_path(
     let dest = attachment_dir.join(&filename);
     let size = data.len();
 
-    if let Err(e) = std::fs::write(&dest, &data) {
+    if let Err(e) = overseer_core::persistence::write_attachment(&dest, &data) {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(InvokeResponse {
@@ -4155,6 +5141,42 @@ async fn dispatch_save_attachment_from_path(
     )
 }
 
+async fn dispatch_readable_attachment_path(
+    state: &HttpSharedState,
+    args: serde_json::Value,
+) -> (StatusCode, Json<InvokeResponse>) {
+    let path = match required_str_arg(&args, "path") {
+        Ok(v) => v,
+        Err(response) => return response,
+    };
+    let Some(config_dir) = state.get_config_dir() else {
+        return invoke_result(Err::<(), _>("Config directory not set"));
+    };
+    let chat_id = args.get("chatId").and_then(|v| v.as_str());
+
+    // Only attachments stored in the config dir, not any file the server
+    // can read.
+    let attachments_dir = config_dir.join("attachments").canonicalize();
+    let stored = match (std::path::Path::new(path).canonicalize(), attachments_dir) {
+        (Ok(path), Ok(dir)) => path.starts_with(dir),
+        _ => false,
+    };
+    if !stored {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(InvokeResponse {
+                success: false,
+                data: None,
+                error: Some(format!("Not a stored attachment: {path}")),
+            }),
+        );
+    }
+    invoke_result(
+        overseer_core::persistence::readable_attachment_path(std::path::Path::new(path), chat_id)
+            .map(|readable| readable.to_string_lossy().to_string()),
+    )
+}
+
 // ============================================================================
 // AGENT COMMAND DISPATCHERS (Claude)
 // ============================================================================
@@ -4365,6 +5387,8 @@ async fn dispatch_send_message(
         working_dir,
         agent_path,
         session_id,
+        fork_session: false,
+        resume_session_at: None,
         model_version,
         log_dir,
         log_id,
@@ -4492,6 +5516,53 @@ mod tests {
         assert!(response.error.as_ref().unwrap().contains("not set"));
     }
 
+    #[tokio::test]
+    async fn dispatch_merge_into_main_refuses_when_project_disallows() {
+        let config_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            config_dir.path().join("projects.json"),
+            r#"{"projects": [{"id": "p1", "name": "demo", "path": "/repos/demo",
+                "allowMergeToMain": false,
+                "workspaces": [{"id": "w1", "branch": "feature",
+                    "path": "/workspaces/demo/feature", "isArchived": false,
+                    "createdAt": "2026-01-01T00:00:00Z"}]}]}"#,
+        )
+        .unwrap();
+        let state = HttpSharedState::with_config_dir(config_dir.path().to_path_buf());
+
+        let args = serde_json::json!({"workspacePath": "/workspaces/demo/feature"});
+        let (status, Json(response)) = dispatch_merge_into_main(&state, args).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let error = response.error.unwrap();
+        assert!(error.contains("disabled for project 'demo'"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn dispatch_readable_attachment_path_only_reads_stored_attachments() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let attachment_dir = config_dir.path().join("attachments").join("a1");
+        std::fs::create_dir_all(&attachment_dir).unwrap();
+        let attachment = attachment_dir.join("notes.md");
+        std::fs::write(&attachment, "# notes").unwrap();
+        std::fs::write(config_dir.path().join("chat.key"), "secret").unwrap();
+        let state = HttpSharedState::with_config_dir(config_dir.path().to_path_buf());
+
+        let args = serde_json::json!({"path": attachment, "chatId": "chat-1"});
+        let (status, Json(response)) = dispatch_readable_attachment_path(&state, args).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response.data, Some(serde_json::json!(attachment)));
+
+        for path in [
+            config_dir.path().join("chat.key"),
+            attachment_dir.join("../../chat.key"),
+        ] {
+            let args = serde_json::json!({ "path": path });
+            let (status, Json(response)) = dispatch_readable_attachment_path(&state, args).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(response.error.unwrap().contains("Not a stored attachment"));
+        }
+    }
+
     #[tokio::test]
     async fn dispatch_get_config_dir_with_config() {
         let state = HttpSharedState::with_config_dir(PathBuf::from("/tmp/test"));
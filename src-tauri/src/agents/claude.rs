@@ -102,6 +102,8 @@ pub async fn send_message(
         working_dir,
         agent_path: resolved_agent_path,
         session_id,
+        fork_session: false,
+        resume_session_at: None,
         model_version,
         log_dir,
         log_id,
@@ -3,12 +3,24 @@
 //! Thin wrapper around overseer-core's ChatSessionManager.
 //! The business logic lives in overseer-core; this module just exposes Tauri commands.
 
+use std::path::Path;
 use std::sync::Arc;
 use tauri::State;
 
 use overseer_core::agents::event::AgentEvent;
+use overseer_core::chat_export::ExportFormat;
+use overseer_core::chat_fork::{claude_root, ChatFork, ForkedChat};
+use overseer_core::chat_handoff::{touched_files, ChatHandoff};
+use overseer_core::chat_search::{SearchQuery, SearchResults};
+use overseer_core::chat_view::View;
+use overseer_core::checkpoints::{checkpoint_turn, RewindResult, TurnCheckpoint};
+use overseer_core::git::{DiffOptions, FileDiff};
 use overseer_core::persistence::types::ChatMetadata;
+use overseer_core::transcript_import::{
+    find_transcripts, import_transcripts, ImportReport, TranscriptCandidate, TranscriptRoots,
+};
 use overseer_core::SeqEvent;
+use overseer_core::vcs::Vcs;
 
 // Re-export for backwards compatibility
 pub use overseer_core::managers::ChatSessionManager;
@@ -127,12 +139,227 @@ pub async fn save_chat_metadata(
 }
 
 /// Persist a user-authored message for a chat session.
+///
+/// With `workspace_path`, the workspace is checkpointed for the turn the
+/// message starts (best effort).
 #[tauri::command]
 pub async fn add_user_message(
     state: State<'_, Arc<ChatSessionManager>>,
     chat_id: String,
     content: String,
     meta: Option<serde_json::Value>,
+    workspace_path: Option<String>,
 ) -> Result<AgentEvent, String> {
-    state.add_user_message(&chat_id, content, meta)
+    let event = state.add_user_message(&chat_id, content, meta)?;
+    if let Some(workspace_path) = workspace_path {
+        checkpoint_turn(Path::new(&workspace_path), &chat_id, &event).await;
+    }
+    Ok(event)
+}
+
+/// List a chat's turns with their workspace checkpoints.
+#[tauri::command]
+pub async fn list_turn_checkpoints(
+    state: State<'_, Arc<ChatSessionManager>>,
+    project_name: String,
+    workspace_name: String,
+    chat_id: String,
+    workspace_path: String,
+) -> Result<Vec<TurnCheckpoint>, String> {
+    overseer_core::checkpoints::list_turn_checkpoints(
+        &state,
+        &project_name,
+        &workspace_name,
+        &chat_id,
+        Path::new(&workspace_path),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Diff a turn's checkpoint against the workspace as it is now.
+#[tauri::command]
+pub async fn diff_turn_checkpoint(
+    chat_id: String,
+    turn_id: String,
+    workspace_path: String,
+    options: Option<DiffOptions>,
+) -> Result<Vec<FileDiff>, String> {
+    let path = Path::new(&workspace_path);
+    let checkpoint = overseer_core::git::find_checkpoint(path, &chat_id, &turn_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No checkpoint for turn {turn_id}"))?;
+    overseer_core::git::diff_checkpoint(path, &checkpoint, &options.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Restore a turn's checkpoint, optionally truncating the chat to before it.
+///
+/// `claude_config_dir` is the project's override, if any, for finding a
+/// Claude session's transcript to resume it at the turn.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn rewind_to_turn(
+    state: State<'_, Arc<ChatSessionManager>>,
+    project_name: String,
+    workspace_name: String,
+    chat_id: String,
+    workspace_path: String,
+    turn_id: String,
+    truncate_chat: bool,
+    claude_config_dir: Option<String>,
+) -> Result<RewindResult, String> {
+    let claude_root = claude_root(claude_config_dir.as_deref());
+    overseer_core::checkpoints::rewind_to_turn(
+        &state,
+        &project_name,
+        &workspace_name,
+        &chat_id,
+        Path::new(&workspace_path),
+        &turn_id,
+        truncate_chat,
+        claude_root.as_deref(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Search every chat's messages, tool calls and tool results.
+#[tauri::command]
+pub async fn search_chats(
+    state: State<'_, Arc<ChatSessionManager>>,
+    query: SearchQuery,
+) -> Result<SearchResults, String> {
+    state.search(&query)
+}
+
+/// Export a chat as Markdown, HTML or a `.overseer-chat` bundle.
+///
+/// `format` is "markdown", "html" or "bundle"; `view` is "text" or "full"
+/// (the default). Returns the file contents.
+#[tauri::command]
+pub async fn export_chat(
+    state: State<'_, Arc<ChatSessionManager>>,
+    project_name: String,
+    workspace_name: String,
+    chat_id: String,
+    format: String,
+    view: Option<String>,
+) -> Result<String, String> {
+    let format = ExportFormat::parse(&format)?;
+    let view = View::from_query(Some(view.as_deref().unwrap_or("full")))?;
+    state.export_chat(&project_name, &workspace_name, &chat_id, format, view)
+}
+
+/// Import a `.overseer-chat` bundle into a workspace.
+#[tauri::command]
+pub async fn import_chat(
+    state: State<'_, Arc<ChatSessionManager>>,
+    project_name: String,
+    workspace_name: String,
+    workspace_id: String,
+    bundle: String,
+) -> Result<ChatMetadata, String> {
+    state.import_chat(&project_name, &workspace_name, &workspace_id, &bundle)
+}
+
+/// List Claude Code and Codex transcripts on this machine, with the
+/// workspace each would be imported into.
+#[tauri::command]
+pub async fn list_agent_transcripts(
+    state: State<'_, Arc<ChatSessionManager>>,
+) -> Result<Vec<TranscriptCandidate>, String> {
+    let config_dir = state.config_dir().ok_or("Config directory not set")?;
+    find_transcripts(&config_dir, &TranscriptRoots::from_env()).map_err(|e| e.to_string())
+}
+
+/// Import Claude Code and Codex transcripts as chats (all of them, or just
+/// `paths`).
+#[tauri::command]
+pub async fn import_agent_transcripts(
+    state: State<'_, Arc<ChatSessionManager>>,
+    paths: Option<Vec<String>>,
+) -> Result<ImportReport, String> {
+    let config_dir = state.config_dir().ok_or("Config directory not set")?;
+    import_transcripts(&config_dir, &TranscriptRoots::from_env(), paths.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Fork a chat after event `seq` into a new chat in the same workspace.
+///
+/// `claude_config_dir` is the project's `CLAUDE_CONFIG_DIR` override, where
+/// a Claude session's transcript is found to cut it at the fork point.
+#[tauri::command]
+pub async fn fork_chat(
+    state: State<'_, Arc<ChatSessionManager>>,
+    project_name: String,
+    workspace_name: String,
+    chat_id: String,
+    seq: u64,
+    claude_config_dir: Option<String>,
+) -> Result<ForkedChat, String> {
+    let claude_root = claude_root(claude_config_dir.as_deref());
+    state.fork_chat(
+        &project_name,
+        &workspace_name,
+        &chat_id,
+        seq,
+        claude_root.as_deref(),
+    )
+}
+
+/// Where a chat was forked from, if it is a fork.
+#[tauri::command]
+pub async fn get_chat_fork(
+    state: State<'_, Arc<ChatSessionManager>>,
+    project_name: String,
+    workspace_name: String,
+    chat_id: String,
+) -> Result<Option<ChatFork>, String> {
+    state.load_chat_fork(&project_name, &workspace_name, &chat_id)
+}
+
+/// Take a registered chat's pending fork plan, for agents whose first start
+/// is driven from the frontend.
+#[tauri::command]
+pub async fn take_chat_fork(
+    state: State<'_, Arc<ChatSessionManager>>,
+    chat_id: String,
+) -> Result<Option<ChatFork>, String> {
+    state.take_pending_fork(&chat_id)
+}
+
+/// Hand a chat over to `to_agent`, recording the context transfer prompt as
+/// a handoff message for the new agent to start with.
+///
+/// Files touched come from the workspace diff; a workspace whose changes
+/// can't be listed hands off without them. The workspace is checkpointed
+/// before the new agent's first turn, as for any user message.
+#[tauri::command]
+pub async fn hand_off_chat(
+    state: State<'_, Arc<ChatSessionManager>>,
+    context_state: State<'_, crate::OverseerContextState>,
+    project_name: String,
+    workspace_name: String,
+    chat_id: String,
+    to_agent: String,
+    workspace_path: String,
+) -> Result<ChatHandoff, String> {
+    let path = Path::new(&workspace_path);
+    let touched = Vcs::resolve(context_state.0.config_dir().as_deref(), path)
+        .list_changed_files(path, None)
+        .await
+        .map(touched_files)
+        .unwrap_or_default();
+    let handoff = state.hand_off(
+        &project_name,
+        &workspace_name,
+        &chat_id,
+        &to_agent,
+        &touched,
+    )?;
+    checkpoint_turn(path, &chat_id, &handoff.event).await;
+    Ok(handoff)
 }
@@ -4,12 +4,29 @@
 //! Since the core functions are already async, these wrappers simply call them directly.
 
 use ignore::WalkBuilder;
-use overseer_core::shell::build_login_shell_command;
+use overseer_core::git::ShellGhRunner;
 use serde::Serialize;
 use std::path::Path;
 
+use crate::persistence::PersistenceConfig;
+
 // Re-export types from overseer-core for use by Tauri commands
-pub use overseer_core::git::{ChangedFile, ChangedFilesResult, Commit, MergeResult, WorkspaceInfo};
+pub use overseer_core::conflicts::ConflictResolution;
+pub use overseer_core::git::{
+    ChangedFile, ChangedFilesResult, Commit, MergeOptions, MergeResult, MergeStrategy, PrCheck,
+    PrStatus, RepoPrStatus, ReviewComment, ReviewPr, ReviewThread, SyncOptions, SyncResult,
+    SyncState, WorkspaceInfo,
+};
+use overseer_core::github_workspace::{create_github_workspace, plan_github_workspace};
+pub use overseer_core::github_workspace::{GithubSource, GithubWorkspace};
+pub use overseer_core::pr_review::ReviewFeedback;
+use overseer_core::vcs::{
+    add_project_workspace, check_workspace_merge, merge_workspace_into_main, Vcs,
+};
+pub use overseer_core::workspace_gc::{GcOptions, GcReport};
+use overseer_core::workspace_layout::resolve_workspace_layout;
+pub use overseer_core::workspace_layout::MigrationReport;
+pub use overseer_core::workspace_teardown::TeardownReport;
 
 // ============================================================================
 // ASYNC WRAPPERS
@@ -27,59 +44,227 @@ pub async fn list_workspaces(repo_path: String) -> Result<Vec<WorkspaceInfo>, St
 /// List all changed files in a workspace.
 #[tauri::command]
 pub async fn list_changed_files(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
     workspace_path: String,
     main_branch: Option<String>,
 ) -> Result<ChangedFilesResult, String> {
     let path = std::path::PathBuf::from(&workspace_path);
-    overseer_core::git::list_changed_files(&path, main_branch.as_deref())
+    vcs_for(&context_state, &path)
+        .list_changed_files(&path, main_branch.as_deref())
         .await
         .map_err(|e| e.to_string())
 }
 
 /// Add a new workspace (worktree) for a branch.
+///
+/// The workspace goes where the project's workspace root and naming template
+/// put it. A multi-repo project gets a worktree of each of its repositories.
 #[tauri::command]
-pub async fn add_workspace(repo_path: String, branch: String) -> Result<String, String> {
+pub async fn add_workspace(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
+    repo_path: String,
+    branch: String,
+) -> Result<String, String> {
     let path = std::path::PathBuf::from(&repo_path);
-    overseer_core::git::add_workspace(&path, &branch)
+    let config_dir = context_state.0.config_dir();
+    let layout = resolve_workspace_layout(config_dir.as_deref(), &path);
+    add_project_workspace(config_dir.as_deref(), &path, &branch, &layout)
         .await
         .map(|p| p.to_string_lossy().to_string())
         .map_err(|e| e.to_string())
 }
 
-/// Remove a workspace (worktree) from the repository.
+/// Add a new workspace from a GitHub pull request or issue.
+///
+/// PRs check out their head branch (forks included); issues get a new branch
+/// and an init prompt built from `init_prompt` and the issue text.
+#[tauri::command]
+pub async fn add_workspace_from_github(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
+    repo_path: String,
+    source: GithubSource,
+    init_prompt: Option<String>,
+    agent_shell: Option<String>,
+) -> Result<GithubWorkspace, String> {
+    let repo = std::path::PathBuf::from(&repo_path);
+    let plan = {
+        let repo = repo.clone();
+        run_gh_query(move || {
+            let runner = ShellGhRunner::new(agent_shell);
+            plan_github_workspace(&runner, &repo, source, init_prompt.as_deref())
+        })
+        .await?
+    };
+    let layout = resolve_workspace_layout(context_state.0.config_dir().as_deref(), &repo);
+    create_github_workspace(&repo, plan, &layout)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Move a project's workspaces to its current workspace root and naming
+/// template, updating `projects.json`. With `dry_run`, only plans the moves.
+#[tauri::command]
+pub async fn migrate_workspaces(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
+    project_id: String,
+    dry_run: Option<bool>,
+) -> Result<MigrationReport, String> {
+    let config_dir = context_state
+        .0
+        .config_dir()
+        .ok_or_else(|| "Config directory not set".to_string())?;
+    overseer_core::workspace_layout::migrate_workspaces(
+        &config_dir,
+        &project_id,
+        dry_run.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Report disk usage for a project's workspaces and archive merged or
+/// closed-PR ones, updating `projects.json`. With `dryRun`, only reports.
+#[tauri::command]
+pub async fn collect_workspace_garbage(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
+    project_id: String,
+    options: Option<GcOptions>,
+) -> Result<GcReport, String> {
+    let config_dir = context_state
+        .0
+        .config_dir()
+        .ok_or_else(|| "Config directory not set".to_string())?;
+    overseer_core::workspace_gc::collect_workspace_garbage(
+        &config_dir,
+        &project_id,
+        options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Remove a workspace (worktree) from the repository, after running the
+/// project's `preArchive` hook and stopping the processes still running in it.
 #[tauri::command]
 pub async fn archive_workspace(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
     repo_path: String,
     workspace_path: String,
     force: Option<bool>,
-) -> Result<(), String> {
+) -> Result<TeardownReport, String> {
+    let config_dir = context_state.0.config_dir();
     let repo = std::path::PathBuf::from(&repo_path);
     let workspace = std::path::PathBuf::from(&workspace_path);
-    overseer_core::git::archive_workspace(&repo, &workspace, force.unwrap_or(false))
-        .await
-        .map_err(|e| e.to_string())
+    overseer_core::workspace_teardown::archive_workspace_with_teardown(
+        config_dir.as_deref(),
+        &repo,
+        &workspace,
+        force.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// The version control backend for a project checkout or workspace.
+fn vcs_for(context_state: &crate::OverseerContextState, path: &Path) -> Vcs {
+    Vcs::resolve(context_state.0.config_dir().as_deref(), path)
 }
 
 /// Check if a merge would succeed without actually performing it.
 #[tauri::command]
 pub async fn check_merge(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
     workspace_path: String,
     main_branch: Option<String>,
+    strategy: Option<MergeStrategy>,
 ) -> Result<MergeResult, String> {
     let path = std::path::PathBuf::from(&workspace_path);
-    overseer_core::git::check_merge(&path, main_branch.as_deref())
-        .await
-        .map_err(|e| e.to_string())
+    check_workspace_merge(
+        context_state.0.config_dir().as_deref(),
+        &path,
+        main_branch.as_deref(),
+        strategy.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.to_string())
 }
 
 /// Merge the current branch into the default branch.
 #[tauri::command]
 pub async fn merge_into_main(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
     workspace_path: String,
     main_branch: Option<String>,
+    options: Option<MergeOptions>,
 ) -> Result<MergeResult, String> {
     let path = std::path::PathBuf::from(&workspace_path);
-    overseer_core::git::merge_into_main(&path, main_branch.as_deref())
+    merge_workspace_into_main(
+        context_state.0.config_dir().as_deref(),
+        &path,
+        main_branch.as_deref(),
+        &options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Merge or rebase the default branch into the workspace's branch.
+///
+/// Conflicts leave the worktree mid-sync; see `continue_sync` / `abort_sync`.
+#[tauri::command]
+pub async fn sync_workspace(
+    workspace_path: String,
+    main_branch: Option<String>,
+    options: Option<SyncOptions>,
+) -> Result<SyncResult, String> {
+    let path = std::path::PathBuf::from(&workspace_path);
+    overseer_core::git::sync_with_default_branch(
+        &path,
+        main_branch.as_deref(),
+        &options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// The sync stopped on conflicts in the workspace, if any.
+#[tauri::command]
+pub async fn get_sync_state(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
+    workspace_path: String,
+) -> Result<Option<SyncState>, String> {
+    let path = std::path::PathBuf::from(&workspace_path);
+    vcs_for(&context_state, &path)
+        .get_sync_state(&path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Finish a sync once its conflicts are resolved.
+#[tauri::command]
+pub async fn continue_sync(workspace_path: String) -> Result<SyncResult, String> {
+    let path = std::path::PathBuf::from(&workspace_path);
+    overseer_core::git::continue_sync(&path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Abandon a sync and restore the branch as it was.
+#[tauri::command]
+pub async fn abort_sync(workspace_path: String) -> Result<(), String> {
+    let path = std::path::PathBuf::from(&workspace_path);
+    overseer_core::git::abort_sync(&path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// The prompt asking the agent to resolve a stopped sync's conflicts.
+#[tauri::command]
+pub async fn prepare_conflict_resolution(
+    workspace_path: String,
+) -> Result<Option<ConflictResolution>, String> {
+    let path = std::path::PathBuf::from(&workspace_path);
+    overseer_core::conflicts::prepare_conflict_resolution(&path)
         .await
         .map_err(|e| e.to_string())
 }
@@ -98,24 +283,36 @@ pub async fn rename_branch(
 }
 
 /// Delete a branch from the repository.
+///
+/// `force` deletes it even if git considers it unmerged (e.g. after a squash
+/// merge).
 #[tauri::command]
-pub async fn delete_branch(repo_path: String, branch_name: String) -> Result<(), String> {
+pub async fn delete_branch(
+    repo_path: String,
+    branch_name: String,
+    force: Option<bool>,
+) -> Result<(), String> {
     let path = std::path::PathBuf::from(&repo_path);
-    overseer_core::git::delete_branch(&path, &branch_name)
-        .await
-        .map_err(|e| e.to_string())
+    let result = if force.unwrap_or(false) {
+        overseer_core::git::force_delete_branch(&path, &branch_name).await
+    } else {
+        overseer_core::git::delete_branch(&path, &branch_name).await
+    };
+    result.map_err(|e| e.to_string())
 }
 
 /// Get the diff for a specific file (branch changes).
 #[tauri::command]
 pub async fn get_file_diff(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
     workspace_path: String,
     file_path: String,
     file_status: String,
     main_branch: Option<String>,
 ) -> Result<String, String> {
     let path = std::path::PathBuf::from(&workspace_path);
-    overseer_core::git::get_file_diff(&path, &file_path, &file_status, main_branch.as_deref())
+    vcs_for(&context_state, &path)
+        .get_file_diff(&path, &file_path, &file_status, main_branch.as_deref())
         .await
         .map_err(|e| e.to_string())
 }
@@ -123,12 +320,58 @@ pub async fn get_file_diff(
 /// Get the diff for uncommitted changes to a file.
 #[tauri::command]
 pub async fn get_uncommitted_diff(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
     workspace_path: String,
     file_path: String,
     file_status: String,
 ) -> Result<String, String> {
     let path = std::path::PathBuf::from(&workspace_path);
-    overseer_core::git::get_uncommitted_diff(&path, &file_path, &file_status)
+    vcs_for(&context_state, &path)
+        .get_uncommitted_diff(&path, &file_path, &file_status)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get the parsed diff for a specific file (branch changes).
+#[tauri::command]
+pub async fn get_structured_file_diff(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
+    workspace_path: String,
+    file_path: String,
+    file_status: String,
+    main_branch: Option<String>,
+    options: Option<overseer_core::git::DiffOptions>,
+) -> Result<Vec<overseer_core::git::FileDiff>, String> {
+    let path = std::path::PathBuf::from(&workspace_path);
+    vcs_for(&context_state, &path)
+        .get_structured_file_diff(
+            &path,
+            &file_path,
+            &file_status,
+            main_branch.as_deref(),
+            &options.unwrap_or_default(),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get the parsed diff for uncommitted changes to a file.
+#[tauri::command]
+pub async fn get_structured_uncommitted_diff(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
+    workspace_path: String,
+    file_path: String,
+    file_status: String,
+    options: Option<overseer_core::git::DiffOptions>,
+) -> Result<Vec<overseer_core::git::FileDiff>, String> {
+    let path = std::path::PathBuf::from(&workspace_path);
+    vcs_for(&context_state, &path)
+        .get_structured_uncommitted_diff(
+            &path,
+            &file_path,
+            &file_status,
+            &options.unwrap_or_default(),
+        )
         .await
         .map_err(|e| e.to_string())
 }
@@ -169,11 +412,13 @@ pub async fn get_submodule_uncommitted_diff(
 /// List commits on this branch vs the default branch.
 #[tauri::command]
 pub async fn list_commits(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
     workspace_path: String,
     main_branch: Option<String>,
 ) -> Result<Vec<Commit>, String> {
     let path = std::path::PathBuf::from(&workspace_path);
-    overseer_core::git::list_commits_on_branch(&path, main_branch.as_deref())
+    vcs_for(&context_state, &path)
+        .list_commits_on_branch(&path, main_branch.as_deref())
         .await
         .map_err(|e| e.to_string())
 }
@@ -181,11 +426,13 @@ pub async fn list_commits(
 /// List files changed in a specific commit.
 #[tauri::command]
 pub async fn list_commit_files(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
     workspace_path: String,
     commit_sha: String,
 ) -> Result<Vec<ChangedFile>, String> {
     let path = std::path::PathBuf::from(&workspace_path);
-    overseer_core::git::list_commit_files(&path, &commit_sha)
+    vcs_for(&context_state, &path)
+        .list_commit_files(&path, &commit_sha)
         .await
         .map_err(|e| e.to_string())
 }
@@ -193,13 +440,38 @@ pub async fn list_commit_files(
 /// Get the diff for a specific file in a specific commit.
 #[tauri::command]
 pub async fn get_commit_diff(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
     workspace_path: String,
     commit_sha: String,
     file_path: String,
     file_status: String,
 ) -> Result<String, String> {
     let path = std::path::PathBuf::from(&workspace_path);
-    overseer_core::git::get_commit_diff(&path, &commit_sha, &file_path, &file_status)
+    vcs_for(&context_state, &path)
+        .get_commit_diff(&path, &commit_sha, &file_path, &file_status)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get the parsed diff for a specific file in a specific commit.
+#[tauri::command]
+pub async fn get_structured_commit_diff(
+    context_state: tauri::State<'_, crate::OverseerContextState>,
+    workspace_path: String,
+    commit_sha: String,
+    file_path: String,
+    file_status: String,
+    options: Option<overseer_core::git::DiffOptions>,
+) -> Result<Vec<overseer_core::git::FileDiff>, String> {
+    let path = std::path::PathBuf::from(&workspace_path);
+    vcs_for(&context_state, &path)
+        .get_structured_commit_diff(
+            &path,
+            &commit_sha,
+            &file_path,
+            &file_status,
+            &options.unwrap_or_default(),
+        )
         .await
         .map_err(|e| e.to_string())
 }
@@ -248,114 +520,156 @@ pub async fn list_recent_branches(repo_path: String) -> Result<Vec<String>, Stri
 }
 
 // ============================================================================
-// PR STATUS (uses gh CLI, not in overseer-core)
+// GITHUB (uses gh CLI via overseer_core::git::github)
 // ============================================================================
 
-#[derive(Serialize)]
-pub struct PrStatus {
-    pub number: i64,
-    pub state: String,
-    pub url: String,
-    pub is_draft: bool,
+/// Run a blocking `gh` query on the blocking thread pool.
+async fn run_gh_query<T, F>(query: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, overseer_core::git::GitError> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(query)
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+        .map_err(|e| e.to_string())
 }
 
+/// Get the pull request opened from a branch, or `None` if there is none.
 #[tauri::command]
 pub async fn get_pr_status(
     workspace_path: String,
     branch: String,
     agent_shell: Option<String>,
 ) -> Result<Option<PrStatus>, String> {
-    let args = vec![
-        "pr".to_string(),
-        "view".to_string(),
-        branch,
-        "--json".to_string(),
-        "number,state,url,isDraft".to_string(),
-    ];
-
-    let mut cmd =
-        build_login_shell_command("gh", &args, Some(&workspace_path), agent_shell.as_deref())?;
-
-    let output = cmd.output().map_err(|e| format!("Failed to run gh: {e}"))?;
-
-    if !output.status.success() {
-        return Ok(None);
-    }
+    run_gh_query(move || {
+        let runner = ShellGhRunner::new(agent_shell);
+        overseer_core::git::get_pr_status(&runner, Path::new(&workspace_path), &branch)
+    })
+    .await
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let parsed: serde_json::Value =
-        serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse gh output: {e}"))?;
+/// Get the pull request opened from a multi-repo workspace's branch in each
+/// of its repositories.
+#[tauri::command]
+pub async fn list_repo_pr_statuses(
+    workspace_path: String,
+    agent_shell: Option<String>,
+) -> Result<Vec<RepoPrStatus>, String> {
+    run_gh_query(move || {
+        let runner = ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_repo_pr_statuses(&runner, Path::new(&workspace_path))
+    })
+    .await
+}
 
-    Ok(Some(PrStatus {
-        number: parsed["number"].as_i64().unwrap_or(0),
-        state: parsed["state"].as_str().unwrap_or("OPEN").to_string(),
-        url: parsed["url"].as_str().unwrap_or("").to_string(),
-        is_draft: parsed["isDraft"].as_bool().unwrap_or(false),
-    }))
+/// List the CI check runs on a pull request.
+#[tauri::command]
+pub async fn list_pr_checks(
+    workspace_path: String,
+    pr_number: u64,
+    agent_shell: Option<String>,
+) -> Result<Vec<PrCheck>, String> {
+    run_gh_query(move || {
+        let runner = ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_pr_checks(&runner, Path::new(&workspace_path), pr_number)
+    })
+    .await
 }
 
-// ============================================================================
-// PR REVIEW LIST (uses gh CLI, not in overseer-core)
-// ============================================================================
+/// List the inline review comments on a pull request.
+#[tauri::command]
+pub async fn list_pr_review_comments(
+    workspace_path: String,
+    pr_number: u64,
+    agent_shell: Option<String>,
+) -> Result<Vec<ReviewComment>, String> {
+    run_gh_query(move || {
+        let runner = ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_review_comments(&runner, Path::new(&workspace_path), pr_number)
+    })
+    .await
+}
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ReviewPr {
-    pub number: i64,
-    pub title: String,
-    pub head_ref_name: String,
-    pub author_login: String,
+/// List the review threads on a pull request, with resolution state.
+#[tauri::command]
+pub async fn list_pr_review_threads(
+    workspace_path: String,
+    pr_number: u64,
+    agent_shell: Option<String>,
+) -> Result<Vec<ReviewThread>, String> {
+    run_gh_query(move || {
+        let runner = ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_review_threads(&runner, Path::new(&workspace_path), pr_number)
+    })
+    .await
 }
 
+/// List open pull requests where the current user's review is requested.
 #[tauri::command]
 pub async fn list_review_prs(
     repo_path: String,
     agent_shell: Option<String>,
 ) -> Result<Vec<ReviewPr>, String> {
-    let args = vec![
-        "pr".to_string(),
-        "list".to_string(),
-        "--review-requested".to_string(),
-        "@me".to_string(),
-        "--json".to_string(),
-        "number,title,headRefName,author".to_string(),
-        "--limit".to_string(),
-        "20".to_string(),
-    ];
-
-    let mut cmd =
-        build_login_shell_command("gh", &args, Some(&repo_path), agent_shell.as_deref())?;
-
-    let output = match cmd.output() {
-        Ok(o) => o,
-        Err(_) => return Ok(vec![]),
-    };
-
-    if !output.status.success() {
-        return Ok(vec![]);
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let parsed: serde_json::Value = match serde_json::from_str(&stdout) {
-        Ok(v) => v,
-        Err(_) => return Ok(vec![]),
-    };
+    run_gh_query(move || {
+        let runner = ShellGhRunner::new(agent_shell);
+        overseer_core::git::list_review_prs(&runner, Path::new(&repo_path), 20)
+    })
+    .await
+}
 
-    let prs = parsed
-        .as_array()
-        .unwrap_or(&vec![])
-        .iter()
-        .filter_map(|item| {
-            Some(ReviewPr {
-                number: item["number"].as_i64()?,
-                title: item["title"].as_str()?.to_string(),
-                head_ref_name: item["headRefName"].as_str()?.to_string(),
-                author_login: item["author"]["login"].as_str().unwrap_or("").to_string(),
-            })
-        })
-        .collect();
+/// Gather the unresolved review threads on a workspace's PR into a prompt for
+/// its agent. `None` if there is no PR or nothing is pending.
+#[tauri::command]
+pub async fn prepare_review_feedback(
+    state: tauri::State<'_, PersistenceConfig>,
+    project_name: String,
+    workspace_name: String,
+    workspace_path: String,
+    include_addressed: Option<bool>,
+    agent_shell: Option<String>,
+) -> Result<Option<ReviewFeedback>, String> {
+    let chat_dir = state.get_chat_dir(&project_name, &workspace_name)?;
+    let workspace_path = std::path::PathBuf::from(workspace_path);
+    let branch = overseer_core::git::get_current_branch(&workspace_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    run_gh_query(move || {
+        let runner = ShellGhRunner::new(agent_shell);
+        overseer_core::pr_review::prepare_review_feedback(
+            &runner,
+            &workspace_path,
+            &branch,
+            &chat_dir,
+            include_addressed.unwrap_or(false),
+        )
+    })
+    .await
+}
 
-    Ok(prs)
+/// Record review threads as handed to a chat's agent.
+#[tauri::command]
+pub async fn mark_review_threads_addressed(
+    state: tauri::State<'_, PersistenceConfig>,
+    project_name: String,
+    workspace_name: String,
+    pr_number: u64,
+    chat_id: String,
+    thread_ids: Vec<String>,
+) -> Result<(), String> {
+    let chat_dir = state.get_chat_dir(&project_name, &workspace_name)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        overseer_core::persistence::mark_review_threads_addressed(
+            &chat_dir,
+            pr_number,
+            &chat_id,
+            &thread_ids,
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
 }
 
 // ============================================================================
@@ -398,28 +712,5 @@ pub async fn list_files(workspace_path: String) -> Result<Vec<String>, String> {
     .map_err(|e| format!("Task join error: {e}"))?
 }
 
-// ============================================================================
-// TESTS
-// ============================================================================
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn pr_status_serializes() {
-        let status = PrStatus {
-            number: 42,
-            state: "OPEN".to_string(),
-            url: "https://github.com/org/repo/pull/42".to_string(),
-            is_draft: false,
-        };
-        let json = serde_json::to_string(&status).unwrap();
-        assert!(json.contains("\"number\":42"));
-        assert!(json.contains("\"state\":\"OPEN\""));
-        assert!(json.contains("\"is_draft\":false"));
-    }
-
-    // Note: Most git tests are now in overseer-core.
-    // Tests here focus on Tauri-specific functionality (PR status, file listing).
-}
+// Note: Git tests live in overseer-core (including the `gh`-backed PR helpers
+// in git::github); these commands are thin wrappers.
@@ -240,7 +240,7 @@ async fn save_attachment(
     let path = attachment_dir.join(&filename);
 
     let size = data.len();
-    std::fs::write(&path, &data)
+    overseer_core::persistence::write_attachment(&path, &data)
         .map_err(|e| format!("Failed to write attachment: {}", e))?;
 
     let mime_type = guess_mime_type(&filename).to_string();
@@ -291,7 +291,8 @@ async fn save_attachment_from_path(
         .map_err(|e| format!("Failed to create attachment directory: {}", e))?;
     let dest = attachment_dir.join(&filename);
     let size = data.len();
-    std::fs::write(&dest, &data).map_err(|e| format!("Failed to write attachment: {}", e))?;
+    overseer_core::persistence::write_attachment(&dest, &data)
+        .map_err(|e| format!("Failed to write attachment: {}", e))?;
 
     let mime_type = guess_mime_type(&filename).to_string();
     let path_str = dest
@@ -308,6 +309,55 @@ async fn save_attachment_from_path(
     })
 }
 
+/// A path the attachment at `path` can be read from as is: a decrypted copy
+/// if chats are encrypted. For the agent prompt and the attachment viewer.
+#[tauri::command]
+async fn readable_attachment_path(path: String, chat_id: Option<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        overseer_core::persistence::readable_attachment_path(
+            std::path::Path::new(&path),
+            chat_id.as_deref(),
+        )
+        .map(|readable| readable.to_string_lossy().to_string())
+        .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Whether chats are encrypted and their key isn't open yet, so the app has
+/// to ask for the passphrase before they can be read or written.
+#[tauri::command]
+async fn chat_encryption_locked(
+    context_state: tauri::State<'_, OverseerContextState>,
+) -> Result<bool, String> {
+    let config_dir = context_state
+        .0
+        .config_dir()
+        .ok_or_else(|| "Config directory not set".to_string())?;
+    Ok(overseer_core::persistence::is_locked(&config_dir))
+}
+
+/// Open the key for encrypted chats with the passphrase the user gave.
+#[tauri::command]
+async fn unlock_chats(
+    context_state: tauri::State<'_, OverseerContextState>,
+    passphrase: String,
+) -> Result<(), String> {
+    let config_dir = context_state
+        .0
+        .config_dir()
+        .ok_or_else(|| "Config directory not set".to_string())?;
+    // Deriving the key takes a while
+    tauri::async_runtime::spawn_blocking(move || {
+        overseer_core::persistence::open_encryption(&config_dir, Some(&passphrase))
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
 #[tauri::command]
 async fn fetch_claude_usage(
     claude_config_dir: Option<String>,
@@ -522,11 +572,31 @@ pub fn run() {
                     .build(),
             )?;
 
+            // Open the key for encrypted chats. Without it they can't be
+            // read or written until the user gives the passphrase
+            // (`unlock_chats`), but the rest of the app still works
+            let passphrase = overseer_core::persistence::passphrase_from_env();
+            if let Err(e) =
+                overseer_core::persistence::open_encryption(&config_dir, passphrase.as_deref())
+            {
+                log::error!("Failed to open the chat key: {}", e);
+            }
+
+            // Keep projects, chat indexes and metadata in the config dir's
+            // SQLite database if it was migrated to one
+            overseer_core::persistence::open_storage(&config_dir)?;
+
             // Set up the config directory on the context itself
             // This is used by the HTTP server's HttpSharedState
             let context_state = app.state::<OverseerContextState>();
             context_state.0.set_config_dir(config_dir.clone());
 
+            // Report changes the daemon makes to projects, chat indexes and
+            // approvals when it shares this config dir
+            if let Some(watcher) = context_state.0.watch_config_dir() {
+                app.manage(watcher);
+            }
+
             // Set up the config directory for approvals persistence
             context_state
                 .0
@@ -590,25 +660,42 @@ pub fn run() {
             git::list_changed_files,
             git::list_files,
             git::add_workspace,
+            git::migrate_workspaces,
+            git::collect_workspace_garbage,
+            git::add_workspace_from_github,
             git::archive_workspace,
             git::check_merge,
             git::merge_into_main,
+            git::sync_workspace,
+            git::get_sync_state,
+            git::continue_sync,
+            git::abort_sync,
+            git::prepare_conflict_resolution,
             git::rename_branch,
             git::get_file_diff,
             git::get_uncommitted_diff,
+            git::get_structured_file_diff,
+            git::get_structured_uncommitted_diff,
             git::get_submodule_file_diff,
             skills::list_skills,
             git::get_submodule_uncommitted_diff,
             git::list_commits,
             git::list_commit_files,
             git::get_commit_diff,
+            git::get_structured_commit_diff,
             git::get_pr_status,
+            git::list_repo_pr_statuses,
+            git::list_pr_checks,
+            git::list_pr_review_comments,
+            git::list_pr_review_threads,
             git::delete_branch,
             git::is_git_repo,
             git::validate_project_path,
             git::detect_default_branch,
             git::list_recent_branches,
             git::list_review_prs,
+            git::prepare_review_feedback,
+            git::mark_review_threads_addressed,
             agents::claude::stop_agent,
             agents::claude::agent_stdin,
             agents::claude::list_running,
@@ -660,6 +747,18 @@ pub fn run() {
             chat_session::load_chat_metadata,
             chat_session::save_chat_metadata,
             chat_session::add_user_message,
+            chat_session::list_turn_checkpoints,
+            chat_session::diff_turn_checkpoint,
+            chat_session::rewind_to_turn,
+            chat_session::search_chats,
+            chat_session::export_chat,
+            chat_session::import_chat,
+            chat_session::list_agent_transcripts,
+            chat_session::import_agent_transcripts,
+            chat_session::fork_chat,
+            chat_session::get_chat_fork,
+            chat_session::take_chat_fork,
+            chat_session::hand_off_chat,
             persistence::save_chat,
             persistence::load_chat,
             persistence::delete_chat,
@@ -675,7 +774,7 @@ pub fn run() {
             persistence::load_project_registry,
             persistence::upsert_project,
             persistence::remove_project,
-            persistence::run_post_create_command,
+            persistence::run_workspace_setup,
             persistence::save_json_config,
             persistence::load_json_config,
             persistence::config_file_exists,
@@ -691,6 +790,9 @@ pub fn run() {
             fetch_claude_usage,
             save_attachment,
             save_attachment_from_path,
+            readable_attachment_path,
+            chat_encryption_locked,
+            unlock_chats,
             start_http_server,
             stop_http_server,
             get_http_server_status,
@@ -706,6 +808,11 @@ pub fn run() {
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|_app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                if let Err(e) = overseer_core::persistence::remove_readable_attachments() {
+                    log::warn!("Failed to remove decrypted attachments: {}", e);
+                }
+            }
             if let tauri::RunEvent::Ready = event {
                 #[cfg(target_os = "macos")]
                 if cfg!(debug_assertions) {
@@ -13,7 +13,8 @@
 //! - Future migration to event-sourced architecture
 
 use overseer_core::persistence::{
-    self, ChatFile, ChatIndex, ChatIndexEntry, Project, ProjectRegistry, WorkspaceState,
+    self, ChatFile, ChatIndex, ChatIndexEntry, Project, ProjectRegistry, Version, Versioned,
+    WorkspaceState,
 };
 use std::path::PathBuf;
 use std::sync::Mutex;
@@ -49,7 +50,11 @@ impl PersistenceConfig {
     }
 
     /// Get the chats directory for a project/workspace.
-    fn get_chat_dir(&self, project_name: &str, workspace_name: &str) -> Result<PathBuf, String> {
+    pub(crate) fn get_chat_dir(
+        &self,
+        project_name: &str,
+        workspace_name: &str,
+    ) -> Result<PathBuf, String> {
         Ok(self
             .get_config_dir()?
             .join("chats")
@@ -125,29 +130,31 @@ pub fn migrate_chat_if_needed(
 // Chat Index Commands
 // ============================================================================
 
-/// Save the chat index.
+/// Save the chat index, made from the one loaded at `version`. Returns its
+/// new version.
 #[tauri::command]
 pub fn save_chat_index(
     state: State<PersistenceConfig>,
     project_name: String,
     workspace_name: String,
     index: ChatIndex,
-) -> Result<(), String> {
+    version: Option<Version>,
+) -> Result<Version, String> {
     let dir = state.get_chat_dir(&project_name, &workspace_name)?;
-    persistence::save_chat_index(&dir, &index).map_err(|e| e.to_string())
+    persistence::save_chat_index(&dir, &index, version.as_ref()).map_err(|e| e.to_string())
 }
 
-/// Load the chat index.
+/// Load the chat index with its version.
 #[tauri::command]
 pub fn load_chat_index(
     state: State<PersistenceConfig>,
     project_name: String,
     workspace_name: String,
-) -> Result<ChatIndex, String> {
+) -> Result<Versioned<ChatIndex>, String> {
     let dir = state.get_chat_dir(&project_name, &workspace_name)?;
-    let index = persistence::load_chat_index(&dir).map_err(|e| e.to_string())?;
+    let index = persistence::load_chat_index_versioned(&dir).map_err(|e| e.to_string())?;
 
-    for entry in &index.chats {
+    for entry in &index.value.chats {
         if let Err(err) = persistence::migrate_chat_if_needed(&dir, &entry.id) {
             eprintln!(
                 "Failed to migrate chat {} in {}/{}: {}",
@@ -168,9 +175,11 @@ pub fn upsert_chat_entry(
     entry: ChatIndexEntry,
 ) -> Result<(), String> {
     let dir = state.get_chat_dir(&project_name, &workspace_name)?;
-    let mut index = persistence::load_chat_index(&dir).map_err(|e| e.to_string())?;
-    persistence::upsert_chat_entry(&mut index, entry);
-    persistence::save_chat_index(&dir, &index).map_err(|e| e.to_string())
+    persistence::update_chat_index(&dir, |index| {
+        persistence::upsert_chat_entry(index, entry.clone());
+    })
+    .map(|_| ())
+    .map_err(|e| e.to_string())
 }
 
 /// Remove a chat entry from the index and save.
@@ -182,9 +191,11 @@ pub fn remove_chat_entry(
     chat_id: String,
 ) -> Result<(), String> {
     let dir = state.get_chat_dir(&project_name, &workspace_name)?;
-    let mut index = persistence::load_chat_index(&dir).map_err(|e| e.to_string())?;
-    persistence::remove_chat_entry(&mut index, &chat_id);
-    persistence::save_chat_index(&dir, &index).map_err(|e| e.to_string())
+    persistence::update_chat_index(&dir, |index| {
+        persistence::remove_chat_entry(index, &chat_id);
+    })
+    .map(|_| ())
+    .map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -218,49 +229,47 @@ pub fn load_workspace_state(
 // Project Registry Commands
 // ============================================================================
 
-/// Save the project registry.
+/// Save the project registry, made from the one loaded at `version`.
+/// Returns its new version.
 #[tauri::command]
 pub fn save_project_registry(
     state: State<PersistenceConfig>,
     registry: ProjectRegistry,
-) -> Result<(), String> {
+    version: Option<Version>,
+) -> Result<Version, String> {
     let dir = state.get_config_dir()?;
-    persistence::save_project_registry(&dir, &registry).map_err(|e| e.to_string())
+    persistence::save_project_registry(&dir, &registry, version.as_ref()).map_err(|e| e.to_string())
 }
 
-/// Load the project registry.
+/// Load the project registry with its version.
 #[tauri::command]
-pub fn load_project_registry(state: State<PersistenceConfig>) -> Result<ProjectRegistry, String> {
+pub fn load_project_registry(
+    state: State<PersistenceConfig>,
+) -> Result<Versioned<ProjectRegistry>, String> {
     let dir = state.get_config_dir()?;
-    persistence::load_project_registry(&dir).map_err(|e| e.to_string())
+    persistence::load_project_registry_versioned(&dir).map_err(|e| e.to_string())
 }
 
 /// Add or update a project and save.
 #[tauri::command]
 pub fn upsert_project(state: State<PersistenceConfig>, project: Project) -> Result<(), String> {
     let dir = state.get_config_dir()?;
-    let mut registry = persistence::load_project_registry(&dir).map_err(|e| e.to_string())?;
-    persistence::upsert_project(&mut registry, project);
-    persistence::save_project_registry(&dir, &registry).map_err(|e| e.to_string())
+    persistence::update_project_registry(&dir, |registry| {
+        persistence::upsert_project(registry, project.clone());
+    })
+    .map(|_| ())
+    .map_err(|e| e.to_string())
 }
 
 /// Remove a project and save.
 #[tauri::command]
 pub fn remove_project(state: State<PersistenceConfig>, project_id: String) -> Result<(), String> {
     let dir = state.get_config_dir()?;
-    let mut registry = persistence::load_project_registry(&dir).map_err(|e| e.to_string())?;
-    persistence::remove_project(&mut registry, &project_id);
-    persistence::save_project_registry(&dir, &registry).map_err(|e| e.to_string())
-}
-
-/// Get a project by ID from the registry.
-fn get_project(config_dir: &std::path::Path, project_id: &str) -> Result<Project, String> {
-    let registry = persistence::load_project_registry(config_dir).map_err(|e| e.to_string())?;
-    registry
-        .projects
-        .into_iter()
-        .find(|p| p.id == project_id)
-        .ok_or_else(|| format!("Project not found: {}", project_id))
+    persistence::update_project_registry(&dir, |registry| {
+        persistence::remove_project(registry, &project_id);
+    })
+    .map(|_| ())
+    .map_err(|e| e.to_string())
 }
 
 /// Expand environment variables in a path string (e.g., $HOME/.local/bin/claude).
@@ -312,56 +321,28 @@ pub fn get_agent_shell_from_config(config_dir: &std::path::Path) -> Option<Strin
     get_shell_prefix_from_config(config_dir)
 }
 
-/// Run the post-create command for a project in a workspace.
+/// Run a workspace's setup steps: the project's `setup_steps`, then its
+/// `post_create` command.
 ///
-/// Reads the postCreate script and shell prefix from config, finds the workspace path,
-/// and executes the command.
-/// Returns the command result with stdout/stderr and exit code.
+/// Progress is emitted as `workspace:setup:{workspace_id}` events and the
+/// outcome is recorded on the workspace. Returns `None` when the project has
+/// nothing to run.
 #[tauri::command]
-pub async fn run_post_create_command(
+pub async fn run_workspace_setup(
     state: State<'_, PersistenceConfig>,
+    context_state: State<'_, crate::OverseerContextState>,
     project_id: String,
     workspace_id: String,
-) -> Result<overseer_core::shell::ShellCommandResult, String> {
+) -> Result<Option<overseer_core::persistence::WorkspaceSetup>, String> {
     let dir = state.get_config_dir()?;
-
-    // Get project and find workspace path
-    let project = get_project(&dir, &project_id)?;
-
-    let workspace = project
-        .workspaces
-        .iter()
-        .find(|w| w.id == workspace_id)
-        .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
-
-    let workspace_path = workspace.path.clone();
-
-    // The project root is where the repository lives; workspaces are copies/worktrees
-    // created under a separate directory. Expose it so post-create commands can pull
-    // files in from the root, e.g. `cp $PROJECT_ROOT/some/file .`.
-    let project_root = project.path.clone();
-
-    // Get post_create command
-    let command = project
-        .post_create
-        .as_ref()
-        .ok_or_else(|| "No post_create command configured for this project".to_string())?
-        .clone();
-
-    // Get shell prefix from config
-    let shell_prefix = get_shell_prefix_from_config(&dir);
-
-    // Run the command asynchronously
-    overseer_core::shell::run_shell_command_async(
-        &command,
-        &workspace_path,
-        shell_prefix.as_deref(),
-        &[
-            ("WORKSPACE_ROOT", workspace_path.as_str()),
-            ("PROJECT_ROOT", project_root.as_str()),
-        ],
+    overseer_core::workspace_setup::run_workspace_setup(
+        std::sync::Arc::clone(&context_state.0.event_bus),
+        &dir,
+        &project_id,
+        &workspace_id,
     )
     .await
+    .map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -448,7 +429,7 @@ pub fn archive_chat_dir(
     }
 
     std::fs::create_dir_all(&archive_parent).map_err(|e| e.to_string())?;
-    std::fs::rename(&source, &dest).map_err(|e| e.to_string())?;
+    persistence::rename_chat_dir(&source, &dest).map_err(|e| e.to_string())?;
 
     Ok(())
 }
@@ -1,4 +1,4 @@
-import { useCallback, useEffect, useRef } from "react"
+import { useCallback, useEffect, useRef, useState } from "react"
 import { useKeyboardShortcuts } from "./hooks/useKeyboardShortcuts"
 import { useEdgeSwipe } from "./hooks/useEdgeSwipe"
 import { observer } from "mobx-react-lite"
@@ -12,6 +12,7 @@ import { GlobalConfirmDialog } from "./components/shared/GlobalConfirmDialog"
 import { SettingsDialog } from "./components/shared/SettingsDialog"
 import { UpdateNotification } from "./components/shared/UpdateNotification"
 import { AuthTokenDialog } from "./components/shared/AuthTokenDialog"
+import { ChatPassphraseDialog } from "./components/shared/ChatPassphraseDialog"
 import { configStore } from "./stores/ConfigStore"
 import { updateStore } from "./stores/UpdateStore"
 import { uiStore } from "./stores/UIStore"
@@ -76,6 +77,7 @@ export default observer(function App() {
   useKeyboardShortcuts()
   const leftWidth = useRef(configStore.leftPaneWidth)
   const rightWidth = useRef(configStore.rightPaneWidth)
+  const [chatsLocked, setChatsLocked] = useState(false)
 
   // Edge swipe gestures for mobile sidebars
   const { onTouchStart, onTouchEnd } = useEdgeSwipe({
@@ -142,6 +144,17 @@ export default observer(function App() {
         })
     }
 
+    // Ask for the chat passphrase if encrypted chats couldn't be opened at
+    // startup (only in Tauri mode: the daemon refuses to start instead)
+    if (backend.type === "tauri") {
+      backend
+        .invoke<boolean>("chat_encryption_locked")
+        .then(setChatsLocked)
+        .catch((err) => {
+          console.error("[App] Failed to check chat encryption:", err)
+        })
+    }
+
     // Auto-start HTTP server if configured (only in Tauri mode)
     if (backend.type === "tauri") {
       configStore.whenLoaded().then(() => {
@@ -266,6 +279,14 @@ export default observer(function App() {
         open={configStore.settingsOpen}
         onOpenChange={(open) => configStore.setSettingsOpen(open)}
       />
+      <ChatPassphraseDialog
+        open={chatsLocked}
+        onUnlocked={() => {
+          setChatsLocked(false)
+          // Reload so chats that failed to load are read again
+          window.location.reload()
+        }}
+      />
       {backend.type === "web" && (
         <AuthTokenDialog
           open={webAuthStore.authRequired}
@@ -51,3 +51,11 @@ function getBackend(): Backend {
  * - backend.listen() for event subscriptions
  */
 export const backend = getBackend()
+
+/**
+ * Whether a save was rejected because another process changed the document
+ * since it was loaded (the backend's `Conflict:` error).
+ */
+export function isConflictError(err: unknown): boolean {
+  return String(err).includes("Conflict:")
+}
@@ -1,8 +1,16 @@
 import { observer } from "mobx-react-lite"
-import { useEffect } from "react"
+import { useEffect, useState } from "react"
 import { open } from "@tauri-apps/plugin-shell"
-import { GitMerge, GitPullRequest, GitPullRequestArrow, GitPullRequestClosed } from "lucide-react"
+import {
+  GitMerge,
+  GitPullRequest,
+  GitPullRequestArrow,
+  GitPullRequestClosed,
+  MessageSquareText,
+  RefreshCcw,
+} from "lucide-react"
 import { projectRegistry } from "../../stores/ProjectRegistry"
+import { toastStore } from "../../stores/ToastStore"
 import { toolAvailabilityStore } from "../../stores/ToolAvailabilityStore"
 import { eventBus } from "../../utils/eventBus"
 import { STATUS_STYLES } from "../../constants/git"
@@ -10,6 +18,7 @@ import { ConfirmDialog } from "../shared/ConfirmDialog"
 import { DiffDialog } from "./DiffDialog"
 import { MergeDialog } from "./MergeDialog"
 import { SubmoduleSection } from "./SubmoduleSection"
+import type { ChangedFile } from "../../types"
 
 interface ChangedFilesPaneProps {
   workspacePath: string
@@ -40,6 +49,8 @@ export const ChangedFilesPane = observer(function ChangedFilesPane({
   // Track running count changes for auto-refresh
   const runningCount = workspaceStore?.runningCount ?? 0
   const isSending = workspaceStore?.isSending ?? false
+  // Non-git projects: the workspace is a copy, and "merging" copies its changes back
+  const isCopy = projectRegistry.selectedProject?.isGitRepo === false
 
   useEffect(() => {
     if (!store) return
@@ -52,6 +63,22 @@ export const ChangedFilesPane = observer(function ChangedFilesPane({
     }
   }
 
+  const [sendingReview, setSendingReview] = useState(false)
+  const handleSendReview = async () => {
+    if (!workspaceStore) return
+    setSendingReview(true)
+    try {
+      const sent = await workspaceStore.sendReviewFeedback()
+      toastStore.show(
+        sent > 0 ? `Sent ${sent} review thread(s) to the agent` : "No unresolved review comments"
+      )
+    } catch (err) {
+      toastStore.show(err instanceof Error ? err.message : String(err))
+    } finally {
+      setSendingReview(false)
+    }
+  }
+
   if (!store) {
     return (
       <div className="flex h-full items-center justify-center text-sm text-ovr-text-muted">
@@ -112,9 +139,13 @@ export const ChangedFilesPane = observer(function ChangedFilesPane({
                     >
                       {style.label}
                     </span>
-                    <span className="min-w-0 flex-1 truncate text-ovr-text-primary">
+                    <span
+                      className="min-w-0 flex-1 truncate text-ovr-text-primary"
+                      title={file.oldPath ? `${file.oldPath} → ${file.path}` : undefined}
+                    >
                       {file.path}
                     </span>
+                    <FileLineStats file={file} />
                   </button>
                 )
               })}
@@ -125,7 +156,7 @@ export const ChangedFilesPane = observer(function ChangedFilesPane({
           {store.files.length > 0 && (
             <>
               <div className="sticky top-0 z-10 border-b border-ovr-border-subtle bg-ovr-bg-panel px-3 py-1.5 text-xs font-medium text-ovr-text-muted">
-                Branch Changes
+                {isCopy ? "Changes" : "Branch Changes"}
               </div>
               {store.files.map((file) => {
                 const style = STATUS_STYLES[file.status] ?? STATUS_STYLES["?"]
@@ -140,9 +171,13 @@ export const ChangedFilesPane = observer(function ChangedFilesPane({
                     >
                       {style.label}
                     </span>
-                    <span className="min-w-0 flex-1 truncate text-ovr-text-primary">
+                    <span
+                      className="min-w-0 flex-1 truncate text-ovr-text-primary"
+                      title={file.oldPath ? `${file.oldPath} → ${file.path}` : undefined}
+                    >
                       {file.path}
                     </span>
+                    <FileLineStats file={file} />
                   </button>
                 )
               })}
@@ -165,29 +200,43 @@ export const ChangedFilesPane = observer(function ChangedFilesPane({
           <div className="flex items-center gap-1">
             {/* Only show PR section if gh CLI is available (or not checked yet) and useGithub is enabled */}
             {!store.isDefaultBranch &&
+              !isCopy &&
               projectRegistry.selectedProject?.useGithub !== false &&
               (toolAvailabilityStore.gh === null || toolAvailabilityStore.gh.available) &&
               (store.prStatus ? (
-                <button
-                  onClick={handleOpenPR}
-                  className={`flex cursor-pointer items-center gap-1 rounded px-2 py-0.5 text-xs transition-colors hover:bg-ovr-bg-elevated ${
-                    store.prStatus.state === "MERGED"
-                      ? "text-ovr-diff-add hover:text-ovr-diff-add"
-                      : store.prStatus.state === "CLOSED"
-                        ? "text-ovr-bad hover:text-ovr-bad"
-                        : "text-ovr-azure-400 hover:text-ovr-azure-300"
-                  }`}
-                  title={`Open PR #${store.prStatus.number}${store.prStatus.state !== "OPEN" ? ` (${store.prStatus.state.toLowerCase()})` : ""}`}
-                >
-                  {store.prStatus.state === "MERGED" ? (
-                    <GitPullRequestArrow size={12} />
-                  ) : store.prStatus.state === "CLOSED" ? (
-                    <GitPullRequestClosed size={12} />
-                  ) : (
-                    <GitPullRequest size={12} />
+                <>
+                  <button
+                    onClick={handleOpenPR}
+                    className={`flex cursor-pointer items-center gap-1 rounded px-2 py-0.5 text-xs transition-colors hover:bg-ovr-bg-elevated ${
+                      store.prStatus.state === "MERGED"
+                        ? "text-ovr-diff-add hover:text-ovr-diff-add"
+                        : store.prStatus.state === "CLOSED"
+                          ? "text-ovr-bad hover:text-ovr-bad"
+                          : "text-ovr-azure-400 hover:text-ovr-azure-300"
+                    }`}
+                    title={`Open PR #${store.prStatus.number}${store.prStatus.state !== "OPEN" ? ` (${store.prStatus.state.toLowerCase()})` : ""}`}
+                  >
+                    {store.prStatus.state === "MERGED" ? (
+                      <GitPullRequestArrow size={12} />
+                    ) : store.prStatus.state === "CLOSED" ? (
+                      <GitPullRequestClosed size={12} />
+                    ) : (
+                      <GitPullRequest size={12} />
+                    )}
+                    PR #{store.prStatus.number}
+                  </button>
+                  {store.prStatus.state === "OPEN" && (
+                    <button
+                      onClick={handleSendReview}
+                      disabled={sendingReview || isSending || !workspaceStore?.activeChat}
+                      className="flex items-center gap-1 rounded px-2 py-0.5 text-xs text-ovr-text-muted transition-colors hover:bg-ovr-bg-elevated hover:text-ovr-text-primary disabled:opacity-50"
+                      title="Send unresolved review comments to the agent"
+                    >
+                      <MessageSquareText size={12} />
+                      {sendingReview ? "..." : "Review"}
+                    </button>
                   )}
-                  PR #{store.prStatus.number}
-                </button>
+                </>
               ) : (
                 <button
                   onClick={() => store.createPR()}
@@ -199,16 +248,55 @@ export const ChangedFilesPane = observer(function ChangedFilesPane({
                   {store.prLoading ? "..." : "Create PR"}
                 </button>
               ))}
+            {!store.isDefaultBranch &&
+              !isCopy &&
+              (store.syncState ? (
+                <>
+                  <button
+                    onClick={() => store.continueSync()}
+                    disabled={store.syncing}
+                    className="flex items-center gap-1 rounded px-2 py-0.5 text-xs text-ovr-warn transition-colors hover:bg-ovr-bg-elevated disabled:opacity-50"
+                    title={`${store.syncState.conflicts.length} conflicted file(s). Continue once they are resolved.`}
+                  >
+                    <RefreshCcw size={12} />
+                    {store.syncing ? "..." : "Continue sync"}
+                  </button>
+                  <button
+                    onClick={() => store.abortSync()}
+                    disabled={store.syncing}
+                    className="rounded px-2 py-0.5 text-xs text-ovr-text-muted transition-colors hover:bg-ovr-bg-elevated hover:text-ovr-text-primary disabled:opacity-50"
+                    title="Abort the sync and restore the branch"
+                  >
+                    Abort
+                  </button>
+                </>
+              ) : (
+                <button
+                  onClick={() => store.syncWithDefault()}
+                  disabled={store.syncing || store.merging || isSending}
+                  className="flex items-center gap-1 rounded px-2 py-0.5 text-xs text-ovr-text-muted transition-colors hover:bg-ovr-bg-elevated hover:text-ovr-text-primary disabled:opacity-50"
+                  title="Bring the default branch's new commits into this workspace"
+                >
+                  <RefreshCcw size={12} />
+                  {store.syncing ? "Syncing..." : "Sync"}
+                </button>
+              ))}
             {!store.isDefaultBranch &&
               projectRegistry.selectedProject?.allowMergeToMain !== false && (
                 <button
                   onClick={() => store.checkMerge()}
                   disabled={store.checking || store.merging || store.totalFileCount === 0}
                   className="flex items-center gap-1 rounded px-2 py-0.5 text-xs text-ovr-text-muted transition-colors hover:bg-ovr-bg-elevated hover:text-ovr-text-primary disabled:opacity-50"
-                  title="Merge into main"
+                  title={isCopy ? "Copy changes back to the project directory" : "Merge into main"}
                 >
                   <GitMerge size={12} />
-                  {store.checking ? "Checking..." : store.merging ? "Merging..." : "Merge"}
+                  {store.checking
+                    ? "Checking..."
+                    : store.merging
+                      ? "Merging..."
+                      : isCopy
+                        ? "Copy back"
+                        : "Merge"}
                 </button>
               )}
             <button
@@ -226,6 +314,11 @@ export const ChangedFilesPane = observer(function ChangedFilesPane({
         open={store.showMergeConfirm}
         onOpenChange={(open) => store.setShowMergeConfirm(open)}
         onMerge={(archiveAfter, deleteBranch) => store.merge(archiveAfter, deleteBranch)}
+        strategy={store.mergeStrategy}
+        onStrategyChange={isCopy ? undefined : (strategy) => store.setMergeStrategy(strategy)}
+        push={store.pushAfterMerge}
+        onPushChange={isCopy ? undefined : (push) => store.setPushAfterMerge(push)}
+        copyBack={isCopy}
       />
 
       <ConfirmDialog
@@ -252,3 +345,17 @@ export const ChangedFilesPane = observer(function ChangedFilesPane({
     </>
   )
 })
+
+/** `+12 −3` for text files, `bin` for binary ones, nothing when unknown. */
+function FileLineStats({ file }: { file: ChangedFile }) {
+  if (file.isBinary) {
+    return <span className="shrink-0 font-mono text-xs text-ovr-text-dim">bin</span>
+  }
+  if (file.additions == null || file.deletions == null) return null
+  return (
+    <span className="shrink-0 font-mono text-xs" data-testid="file-line-stats">
+      <span className="text-ovr-diff-add">+{file.additions}</span>{" "}
+      <span className="text-ovr-diff-del">−{file.deletions}</span>
+    </span>
+  )
+}
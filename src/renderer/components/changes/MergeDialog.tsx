@@ -1,13 +1,37 @@
 import * as AlertDialog from "@radix-ui/react-alert-dialog"
+import * as Select from "@radix-ui/react-select"
+import { ChevronDown } from "lucide-react"
 import { useState } from "react"
+import type { MergeStrategy } from "../../types"
+
+const STRATEGY_LABELS: Record<MergeStrategy, string> = {
+  merge: "Merge commit",
+  squash: "Squash into one commit",
+  rebase: "Rebase and fast-forward",
+}
 
 interface MergeDialogProps {
   open: boolean
   onOpenChange: (open: boolean) => void
   onMerge: (archiveAfter: boolean, deleteBranch: boolean) => void
+  strategy?: MergeStrategy
+  onStrategyChange?: (strategy: MergeStrategy) => void
+  push?: boolean
+  onPushChange?: (push: boolean) => void
+  /** The workspace is a copy of a non-git project: merging copies its changes back. */
+  copyBack?: boolean
 }
 
-export function MergeDialog({ open, onOpenChange, onMerge }: MergeDialogProps) {
+export function MergeDialog({
+  open,
+  onOpenChange,
+  onMerge,
+  strategy = "merge",
+  onStrategyChange,
+  push = false,
+  onPushChange,
+  copyBack = false,
+}: MergeDialogProps) {
   const [deleteBranch, setDeleteBranch] = useState(true)
 
   return (
@@ -16,11 +40,45 @@ export function MergeDialog({ open, onOpenChange, onMerge }: MergeDialogProps) {
         <AlertDialog.Overlay className="fixed inset-0 z-50 bg-black/60" />
         <AlertDialog.Content className="fixed top-1/2 left-1/2 z-50 w-[90vw] max-w-100 -translate-x-1/2 -translate-y-1/2 rounded-xl border border-ovr-border-subtle bg-ovr-bg-panel p-6 shadow-ovr-panel">
           <AlertDialog.Title className="text-sm font-semibold text-ovr-text-strong">
-            Merge into default branch
+            {copyBack ? "Copy changes back" : "Merge into default branch"}
           </AlertDialog.Title>
           <AlertDialog.Description className="mt-2 text-sm text-ovr-text-muted">
-            No conflicts detected. How would you like to proceed?
+            {copyBack
+              ? "No conflicts detected. The changed files will be copied to the project directory."
+              : "No conflicts detected. How would you like to proceed?"}
           </AlertDialog.Description>
+          {onStrategyChange && (
+            <div className="mt-4 flex items-center gap-2">
+              <label className="text-xs text-ovr-text-muted">Strategy</label>
+              <Select.Root
+                value={strategy}
+                onValueChange={(v) => onStrategyChange(v as MergeStrategy)}
+              >
+                <Select.Trigger
+                  className="flex items-center gap-1.5 rounded-lg border border-ovr-border-subtle bg-ovr-bg-panel px-3 py-2 text-xs text-ovr-text-primary outline-none"
+                  data-testid="merge-strategy-select"
+                >
+                  <Select.Value />
+                  <ChevronDown size={12} className="text-ovr-text-muted" />
+                </Select.Trigger>
+                <Select.Portal>
+                  <Select.Content className="z-[200] rounded-lg border border-ovr-border-subtle bg-ovr-bg-elevated shadow-lg">
+                    <Select.Viewport className="p-1">
+                      {(Object.keys(STRATEGY_LABELS) as MergeStrategy[]).map((value) => (
+                        <Select.Item
+                          key={value}
+                          value={value}
+                          className="cursor-pointer rounded px-2 py-1.5 text-xs text-ovr-text-primary outline-none data-[highlighted]:bg-ovr-bg-panel"
+                        >
+                          <Select.ItemText>{STRATEGY_LABELS[value]}</Select.ItemText>
+                        </Select.Item>
+                      ))}
+                    </Select.Viewport>
+                  </Select.Content>
+                </Select.Portal>
+              </Select.Root>
+            </div>
+          )}
           <div className="mt-5 flex justify-end gap-3">
             <AlertDialog.Cancel asChild>
               <button
@@ -37,31 +95,47 @@ export function MergeDialog({ open, onOpenChange, onMerge }: MergeDialogProps) {
                 // just merge cannot delete the branch, so deleteBranch is false here
                 onClick={() => onMerge(false, false)}
               >
-                Just merge
+                {copyBack ? "Just copy back" : "Just merge"}
               </button>
             </AlertDialog.Action>
             <AlertDialog.Action asChild>
               <button
                 data-testid="merge-archive-button"
                 className="ovr-btn-primary cursor-pointer px-3 py-1.5 text-xs"
-                onClick={() => onMerge(true, deleteBranch)}
+                onClick={() => onMerge(true, deleteBranch && !copyBack)}
               >
-                Merge & archive
+                {copyBack ? "Copy back & archive" : "Merge & archive"}
               </button>
             </AlertDialog.Action>
           </div>
-          <div className="mt-3 flex justify-end">
-            <label className="flex cursor-pointer items-center gap-2 text-xs text-ovr-text-muted">
-              <input
-                type="checkbox"
-                data-testid="delete-branch-checkbox"
-                checked={deleteBranch}
-                onChange={(e) => setDeleteBranch(e.target.checked)}
-                className="size-3.5 cursor-pointer accent-ovr-azure-500"
-              />
-              Also remove branch
-            </label>
-          </div>
+          {!copyBack && (
+            <div className="mt-3 flex justify-end">
+              <label className="flex cursor-pointer items-center gap-2 text-xs text-ovr-text-muted">
+                <input
+                  type="checkbox"
+                  data-testid="delete-branch-checkbox"
+                  checked={deleteBranch}
+                  onChange={(e) => setDeleteBranch(e.target.checked)}
+                  className="size-3.5 cursor-pointer accent-ovr-azure-500"
+                />
+                Also remove branch
+              </label>
+            </div>
+          )}
+          {onPushChange && (
+            <div className="mt-2 flex justify-end">
+              <label className="flex cursor-pointer items-center gap-2 text-xs text-ovr-text-muted">
+                <input
+                  type="checkbox"
+                  data-testid="push-after-merge-checkbox"
+                  checked={push}
+                  onChange={(e) => onPushChange(e.target.checked)}
+                  className="size-3.5 cursor-pointer accent-ovr-azure-500"
+                />
+                Push default branch to origin
+              </label>
+            </div>
+          )}
         </AlertDialog.Content>
       </AlertDialog.Portal>
     </AlertDialog.Root>
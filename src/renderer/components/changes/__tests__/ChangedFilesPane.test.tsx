@@ -123,3 +123,32 @@ describe("ChangedFilesPane PR section visibility", () => {
     expect(toolAvailabilityStore.ensureGh).toHaveBeenCalled()
   })
 })
+
+describe("ChangedFilesPane line stats", () => {
+  const originalFiles = mockChangedFilesStore.files
+
+  beforeEach(() => {
+    ;(
+      projectRegistry as unknown as { selectedWorkspaceStore: typeof mockWorkspaceStore | null }
+    ).selectedWorkspaceStore = mockWorkspaceStore
+  })
+
+  afterEach(() => {
+    mockChangedFilesStore.files = originalFiles
+    ;(
+      projectRegistry as unknown as { selectedWorkspaceStore: typeof mockWorkspaceStore | null }
+    ).selectedWorkspaceStore = null
+  })
+
+  it("shows added and removed line counts, and bin for binary files", () => {
+    mockChangedFilesStore.files = [
+      { path: "src/lib.ts", status: "M", additions: 3, deletions: 1, isBinary: false },
+      { path: "logo.png", status: "M", additions: null, deletions: null, isBinary: true },
+    ] as typeof originalFiles
+
+    render(<ChangedFilesPane workspacePath="/test" />)
+
+    expect(screen.getByTestId("file-line-stats")).toHaveTextContent("+3 −1")
+    expect(screen.getByText("bin")).toBeInTheDocument()
+  })
+})
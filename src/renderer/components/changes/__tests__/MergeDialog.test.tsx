@@ -54,4 +54,36 @@ describe("MergeDialog", () => {
     fireEvent.click(screen.getByTestId("just-merge-button"))
     expect(defaultProps.onMerge).toHaveBeenCalledWith(false, false)
   })
+
+  it("hides strategy and push controls without handlers", () => {
+    render(<MergeDialog {...defaultProps} />)
+
+    expect(screen.queryByTestId("merge-strategy-select")).toBeNull()
+    expect(screen.queryByTestId("push-after-merge-checkbox")).toBeNull()
+  })
+
+  it("reports push checkbox changes", () => {
+    const onPushChange = vi.fn()
+    render(
+      <MergeDialog
+        {...defaultProps}
+        onStrategyChange={vi.fn()}
+        push={false}
+        onPushChange={onPushChange}
+      />
+    )
+
+    expect(screen.getByTestId("merge-strategy-select")).toBeInTheDocument()
+    fireEvent.click(screen.getByTestId("push-after-merge-checkbox"))
+    expect(onPushChange).toHaveBeenCalledWith(true)
+  })
+
+  it("offers copying back without branch options for a copied workspace", () => {
+    render(<MergeDialog {...defaultProps} copyBack />)
+
+    expect(screen.getByText("Copy changes back")).toBeInTheDocument()
+    expect(screen.queryByTestId("delete-branch-checkbox")).toBeNull()
+    fireEvent.click(screen.getByTestId("merge-archive-button"))
+    expect(defaultProps.onMerge).toHaveBeenCalledWith(true, false)
+  })
 })
@@ -42,7 +42,10 @@ export function AttachmentChip({ attachment, onRemove }: AttachmentChipProps) {
       if (!imageUrl) {
         setLoading(true)
         try {
-          const bytes = await readFile(attachment.path)
+          const path = await backend.invoke<string>("readable_attachment_path", {
+            path: attachment.path,
+          })
+          const bytes = await readFile(path)
           const blob = new Blob([bytes], { type: attachment.mimeType })
           const url = URL.createObjectURL(blob)
           setImageUrl(url)
@@ -55,9 +58,12 @@ export function AttachmentChip({ attachment, onRemove }: AttachmentChipProps) {
       setDialogOpen(true)
     } else {
       // Open non-image files with the system default handler
+      const path = await backend.invoke<string>("readable_attachment_path", {
+        path: attachment.path,
+      })
       await backend.invoke("open_external", {
         command: "open",
-        path: attachment.path,
+        path,
       })
     }
   }
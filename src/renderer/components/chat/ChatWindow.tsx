@@ -8,6 +8,7 @@ import { terminalStore } from "../../stores/TerminalStore"
 import { debugStore } from "../../stores/DebugStore"
 import { externalService } from "../../services/external"
 import { toastStore } from "../../stores/ToastStore"
+import { confirmDialogStore } from "../../stores/ConfirmDialogStore"
 import { isDefaultBranch } from "../../utils/git"
 import { saveAttachmentFromPath } from "../../services/attachmentService"
 import type { Attachment } from "../../types"
@@ -22,6 +23,7 @@ import { ChatTabs } from "./ChatTabs"
 import { NewChatScreen } from "./NewChatScreen"
 import { QueuedMessagesPanel } from "./QueuedMessagesPanel"
 import { SaveChatButton } from "./SaveChatButton"
+import { HandOffButton } from "./HandOffButton"
 
 interface ChatWindowProps {
   workspace: Workspace
@@ -129,6 +131,41 @@ export const ChatWindow = observer(function ChatWindow({ workspace }: ChatWindow
     }
   }, [workspaceStore])
 
+  const handleRewind = useCallback(
+    async (turnId: string) => {
+      const chat = workspaceStore?.activeChat
+      if (!chat) return
+      const confirmed = await confirmDialogStore.confirm({
+        title: "Rewind to before this message?",
+        description:
+          "The workspace is restored to its checkpoint from before this message, and this message and everything after it are removed from the chat. The agent resumes from that point.",
+        confirmLabel: "Rewind",
+      })
+      if (!confirmed) return
+      try {
+        await chat.rewindToTurn(turnId, true)
+        toastStore.show("Rewound chat and workspace")
+      } catch (err) {
+        toastStore.show(`Rewind failed: ${err instanceof Error ? err.message : String(err)}`)
+      }
+    },
+    [workspaceStore]
+  )
+
+  const handleFork = useCallback(
+    async (seq: number) => {
+      const chatId = workspaceStore?.activeChatId
+      if (!workspaceStore || !chatId) return
+      try {
+        await workspaceStore.forkChat(chatId, seq)
+        toastStore.show("Forked chat")
+      } catch (err) {
+        toastStore.show(`Fork failed: ${err instanceof Error ? err.message : String(err)}`)
+      }
+    },
+    [workspaceStore]
+  )
+
   const commitRename = useCallback(async () => {
     const success = await projectRegistry.renameBranchSafe(
       workspace.id,
@@ -242,6 +279,12 @@ export const ChatWindow = observer(function ChatWindow({ workspace }: ChatWindow
               </DropdownMenu.Portal>
             </DropdownMenu.Root>
           )}
+          {workspaceStore.activeChat?.agentType && (
+            <HandOffButton
+              chat={workspaceStore.activeChat}
+              isRemote={projectRegistry.selectedProject?.isRemote ?? false}
+            />
+          )}
           {workspaceStore.activeChat && <SaveChatButton chat={workspaceStore.activeChat.chat} />}
           <button
             onClick={() => {
@@ -297,6 +340,8 @@ export const ChatWindow = observer(function ChatWindow({ workspace }: ChatWindow
                 <MessageList
                   key={workspaceStore.activeChatId}
                   turns={workspaceStore.currentTurns}
+                  onRewind={handleRewind}
+                  onFork={handleFork}
                 />
               </div>
 
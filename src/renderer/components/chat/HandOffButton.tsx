@@ -0,0 +1,72 @@
+import { observer } from "mobx-react-lite"
+import { useCallback } from "react"
+import * as DropdownMenu from "@radix-ui/react-dropdown-menu"
+import { ArrowRightLeft } from "lucide-react"
+import type { AgentType } from "../../types"
+import type { ChatStore } from "../../stores/ChatStore"
+import { configStore } from "../../stores/ConfigStore"
+import { toastStore } from "../../stores/ToastStore"
+import { AGENT_TITLES } from "../../constants/agents"
+import { AgentIcon } from "./AgentIcon"
+
+interface HandOffButtonProps {
+  chat: ChatStore
+  /** Remote projects only run Claude */
+  isRemote: boolean
+}
+
+/** Menu to hand the chat over to another agent, picking up where it left off. */
+export const HandOffButton = observer(function HandOffButton({
+  chat,
+  isRemote,
+}: HandOffButtonProps) {
+  const current = chat.chat.agentType ?? "claude"
+  const agents = configStore.enabledAgents.filter(
+    (agent) => agent !== current && (!isRemote || agent === "claude")
+  )
+
+  const handleHandOff = useCallback(
+    async (agentType: AgentType) => {
+      try {
+        await chat.handOffTo(agentType)
+        toastStore.show(`Handed off to ${AGENT_TITLES[agentType]}`)
+      } catch (err) {
+        toastStore.show(`Handoff failed: ${err instanceof Error ? err.message : String(err)}`)
+      }
+    },
+    [chat]
+  )
+
+  if (agents.length === 0) return null
+
+  return (
+    <DropdownMenu.Root>
+      <DropdownMenu.Trigger asChild>
+        <button
+          data-testid="hand-off-button"
+          className="rounded border border-ovr-border-subtle p-1 text-ovr-text-muted transition-colors hover:bg-ovr-bg-elevated hover:text-ovr-text-primary"
+          title="Hand off to another agent"
+        >
+          <ArrowRightLeft size={16} />
+        </button>
+      </DropdownMenu.Trigger>
+      <DropdownMenu.Portal>
+        <DropdownMenu.Content
+          className="z-50 min-w-40 rounded-lg border border-ovr-border-subtle bg-ovr-bg-elevated py-1 shadow-lg"
+          align="end"
+          sideOffset={4}
+        >
+          {agents.map((agent) => (
+            <DropdownMenu.Item
+              key={agent}
+              onSelect={() => void handleHandOff(agent)}
+              className="flex cursor-pointer items-center gap-2 whitespace-nowrap px-3 py-1.5 text-xs text-ovr-text-primary outline-none data-[highlighted]:bg-ovr-bg-panel"
+            >
+              <AgentIcon agentType={agent} size={14} /> Hand off to {AGENT_TITLES[agent]}
+            </DropdownMenu.Item>
+          ))}
+        </DropdownMenu.Content>
+      </DropdownMenu.Portal>
+    </DropdownMenu.Root>
+  )
+})
@@ -1,6 +1,6 @@
 import { observer } from "mobx-react-lite"
 import { useState } from "react"
-import { ChevronRight, ChevronDown, Copy, Check } from "lucide-react"
+import { ChevronRight, ChevronDown, Copy, Check, GitFork, History } from "lucide-react"
 import type { Message } from "../../types"
 import {
   parseToolCall,
@@ -25,6 +25,10 @@ interface MessageItemProps {
   message: Message
   /** Render in compact style (smaller, dimmer) for work/thinking messages */
   compact?: boolean
+  /** Rewind the chat and workspace to before this (user) message */
+  onRewind?: () => void
+  /** Fork the chat into a new one, keeping the conversation through this message's turn */
+  onFork?: () => void
 }
 
 function CopyButton({ content }: { content: string }) {
@@ -49,6 +53,32 @@ function CopyButton({ content }: { content: string }) {
   )
 }
 
+function RewindButton({ onRewind }: { onRewind: () => void }) {
+  return (
+    <button
+      onClick={onRewind}
+      data-testid="rewind-message-button"
+      className="flex items-center justify-center rounded p-1 text-ovr-text-muted opacity-0 transition hover:bg-ovr-bg-panel hover:text-ovr-text-primary group-hover:opacity-100"
+      title="Rewind to before this message"
+    >
+      <History size={13} />
+    </button>
+  )
+}
+
+function ForkButton({ onFork }: { onFork: () => void }) {
+  return (
+    <button
+      onClick={onFork}
+      data-testid="fork-message-button"
+      className="flex items-center justify-center rounded p-1 text-ovr-text-muted opacity-0 transition hover:bg-ovr-bg-panel hover:text-ovr-text-primary group-hover:opacity-100"
+      title="Fork chat from here"
+    >
+      <GitFork size={13} />
+    </button>
+  )
+}
+
 const BASH_OUTPUT_LINE_THRESHOLD = 3
 const BASH_OUTPUT_CHAR_THRESHOLD = 500
 
@@ -197,7 +227,12 @@ const compactTools: Record<string, React.ComponentType<{ tool: import("./tools")
   EnterPlanMode: EnterPlanModeToolItem,
 }
 
-export const MessageItem = observer(function MessageItem({ message, compact }: MessageItemProps) {
+export const MessageItem = observer(function MessageItem({
+  message,
+  compact,
+  onRewind,
+  onFork,
+}: MessageItemProps) {
   const isUser = message.role === "user"
   const searchActive = chatSearchStore.active
   const tool = !isUser ? parseToolCall(message.content) : null
@@ -212,6 +247,8 @@ export const MessageItem = observer(function MessageItem({ message, compact }: M
 
     return (
       <div className="group mb-3 flex items-start justify-end gap-1">
+        {onFork && <ForkButton onFork={onFork} />}
+        {onRewind && <RewindButton onRewind={onRewind} />}
         <CopyButton content={message.content} />
         <div className="max-w-[80%] overflow-hidden rounded-lg border-r-2 border-ovr-azure-500 bg-ovr-bg-elevated px-3 py-4 text-sm text-white">
           <MarkdownContent content={message.content} />
@@ -282,6 +319,7 @@ export const MessageItem = observer(function MessageItem({ message, compact }: M
       </div>
       <div className="flex justify-start pl-3">
         <CopyButton content={message.content} />
+        {onFork && <ForkButton onFork={onFork} />}
       </div>
     </div>
   )
@@ -24,9 +24,17 @@ function totalContentChars(turns: MessageTurn[]): number {
 
 interface MessageListProps {
   turns: MessageTurn[]
+  /** Rewind the chat and workspace to before a user message */
+  onRewind?: (turnId: string) => void
+  /** Fork the chat into a new one at a message's seq */
+  onFork?: (seq: number) => void
 }
 
-export const MessageList = observer(function MessageList({ turns }: MessageListProps) {
+export const MessageList = observer(function MessageList({
+  turns,
+  onRewind,
+  onFork,
+}: MessageListProps) {
   const bottomRef = useRef<HTMLDivElement>(null)
   const containerRef = useRef<HTMLDivElement>(null)
   const [visibleCount, setVisibleCount] = useState(TURNS_PER_PAGE)
@@ -144,7 +152,7 @@ export const MessageList = observer(function MessageList({ turns }: MessageListP
         </button>
       )}
       {visibleTurns.map((turn) => (
-        <TurnSection key={turn.userMessage.id} turn={turn} />
+        <TurnSection key={turn.userMessage.id} turn={turn} onRewind={onRewind} onFork={onFork} />
       ))}
       {showNewMessageIndicator && (
         <div className="fixed bottom-4 left-1/2 transform -translate-x-1/2 bg-ovr-bg-elevated text-ovr-text-primary px-4 py-2 rounded shadow">
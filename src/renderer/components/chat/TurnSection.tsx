@@ -8,6 +8,8 @@ import { chatSearchStore } from "../../stores/ChatSearchStore"
 
 interface TurnSectionProps {
   turn: MessageTurn
+  onRewind?: (turnId: string) => void
+  onFork?: (seq: number) => void
 }
 
 /**
@@ -59,7 +61,11 @@ function groupWorkMessages(messages: Message[]): GroupedItem[] {
   return items
 }
 
-export const TurnSection = observer(function TurnSection({ turn }: TurnSectionProps) {
+export const TurnSection = observer(function TurnSection({
+  turn,
+  onRewind,
+  onFork,
+}: TurnSectionProps) {
   const [expanded, setExpanded] = useState(false)
 
   // Force the work section open while search is active so its text is in the DOM to search.
@@ -73,10 +79,20 @@ export const TurnSection = observer(function TurnSection({ turn }: TurnSectionPr
   // Group messages for rendering
   const groupedItems = useMemo(() => groupWorkMessages(turn.workMessages), [turn.workMessages])
 
+  // Messages not yet persisted have no seq to fork at
+  const forkAt = (message: Message) => {
+    const seq = message.seq
+    return onFork && seq !== undefined ? () => onFork(seq) : undefined
+  }
+
   return (
     <div>
       {/* User message */}
-      <MessageItem message={turn.userMessage} />
+      <MessageItem
+        message={turn.userMessage}
+        onRewind={onRewind && (() => onRewind(turn.userMessage.id))}
+        onFork={forkAt(turn.userMessage)}
+      />
 
       {/* Collapsible work section */}
       {hasWork && (
@@ -125,7 +141,9 @@ export const TurnSection = observer(function TurnSection({ turn }: TurnSectionPr
       )}
 
       {/* Result message */}
-      {turn.resultMessage && <MessageItem message={turn.resultMessage} />}
+      {turn.resultMessage && (
+        <MessageItem message={turn.resultMessage} onFork={forkAt(turn.resultMessage)} />
+      )}
     </div>
   )
 })
@@ -118,3 +118,38 @@ describe("MessageItem thinking block", () => {
     expect(screen.getByTestId("thinking-item")).toBeInTheDocument()
   })
 })
+
+describe("MessageItem rewind button", () => {
+  it("shows rewind button on user message when a handler is given", () => {
+    const onRewind = vi.fn()
+    render(<MessageItem message={userMessage("Hello!")} onRewind={onRewind} />)
+    fireEvent.click(screen.getByTestId("rewind-message-button"))
+    expect(onRewind).toHaveBeenCalledOnce()
+  })
+
+  it("does not show rewind button without a handler", () => {
+    render(<MessageItem message={userMessage("Hello!")} />)
+    expect(screen.queryByTestId("rewind-message-button")).not.toBeInTheDocument()
+  })
+})
+
+describe("MessageItem fork button", () => {
+  it("forks from a user message", () => {
+    const onFork = vi.fn()
+    render(<MessageItem message={userMessage("Hello!")} onFork={onFork} />)
+    fireEvent.click(screen.getByTestId("fork-message-button"))
+    expect(onFork).toHaveBeenCalledOnce()
+  })
+
+  it("forks from an assistant reply", () => {
+    const onFork = vi.fn()
+    render(<MessageItem message={assistantMessage("Done.")} onFork={onFork} />)
+    fireEvent.click(screen.getByTestId("fork-message-button"))
+    expect(onFork).toHaveBeenCalledOnce()
+  })
+
+  it("does not show fork button without a handler", () => {
+    render(<MessageItem message={assistantMessage("Done.")} />)
+    expect(screen.queryByTestId("fork-message-button")).not.toBeInTheDocument()
+  })
+})
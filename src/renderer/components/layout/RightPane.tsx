@@ -82,9 +82,11 @@ export const RightPane = observer(function RightPane({ width }: { width: number
   const workspace = projectRegistry.selectedWorkspace
   const project = projectRegistry.selectedProject
   const isGitRepo = project?.isGitRepo ?? true
+  // A workspace of a non-git project is a copy of its directory, with changes but no commits
+  const isCopy = !isGitRepo && !!workspace && workspace.path !== project?.path
   const isRemote = project?.isRemote ?? false
   const changesHeight = useRef(configStore.changesHeight)
-  const selectedTab = configStore.rightPaneTab as RightPaneTab
+  const selectedTab = isGitRepo ? (configStore.rightPaneTab as RightPaneTab) : "changes"
 
   // Terminal open state: use localStorage if set, otherwise use default from config
   const [terminalOpen, setTerminalOpen] = useState(() => {
@@ -127,8 +129,8 @@ export const RightPane = observer(function RightPane({ width }: { width: number
       className="flex h-full flex-col border-l border-ovr-border-subtle bg-ovr-bg-panel"
       style={{ width, minWidth: 200 }}
     >
-      {/* Changes/Commits section (top) - only shown for local git repos */}
-      {isGitRepo && (
+      {/* Changes/Commits section (top) - only shown for git repos and copied workspaces */}
+      {(isGitRepo || isCopy) && (
         <>
           {/* Tab bar */}
           <div className="flex items-center gap-1 border-b border-ovr-border-subtle px-3 py-2">
@@ -141,16 +143,20 @@ export const RightPane = observer(function RightPane({ width }: { width: number
             >
               CHANGES
             </button>
-            <span className="text-ovr-text-dim">|</span>
-            <button
-              onClick={() => handleTabChange("commits")}
-              className={classNames("cursor-pointer text-xs font-semibold transition-colors", {
-                "text-ovr-text-muted": selectedTab === "commits",
-                "text-ovr-text-dim hover:text-ovr-text-muted": selectedTab !== "commits",
-              })}
-            >
-              COMMITS
-            </button>
+            {isGitRepo && (
+              <>
+                <span className="text-ovr-text-dim">|</span>
+                <button
+                  onClick={() => handleTabChange("commits")}
+                  className={classNames("cursor-pointer text-xs font-semibold transition-colors", {
+                    "text-ovr-text-muted": selectedTab === "commits",
+                    "text-ovr-text-dim hover:text-ovr-text-muted": selectedTab !== "commits",
+                  })}
+                >
+                  COMMITS
+                </button>
+              </>
+            )}
           </div>
           <div
             className={classNames("flex flex-col overflow-hidden", {
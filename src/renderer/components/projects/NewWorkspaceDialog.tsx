@@ -5,16 +5,21 @@ import { X, Loader2 } from "lucide-react"
 import { faker } from "@faker-js/faker"
 import { Input } from "../shared/Input"
 import { gitService } from "../../services/git"
-import type { ReviewPr } from "../../services/git"
+import type { GithubSource, ReviewPr } from "../../services/git"
 import { configStore } from "../../stores/ConfigStore"
 
 interface NewWorkspaceDialogProps {
   open: boolean
   onOpenChange: (open: boolean) => void
   onCreate: (branch: string) => void
+  /** Create from a GitHub PR or issue. When set, review PRs check out via
+   *  GitHub (works for forks) and typing `#123` starts from issue 123. */
+  onCreateFromGithub?: (source: GithubSource) => void
   repoPath?: string
   existingBranches?: string[]
   mainBranch?: string
+  /** False for a plain directory: the workspace is a copy and gets a name, not a branch. */
+  isGitRepo?: boolean
 }
 
 function generateRandomName(): string {
@@ -28,9 +33,11 @@ export const NewWorkspaceDialog = observer(function NewWorkspaceDialog({
   open,
   onOpenChange,
   onCreate,
+  onCreateFromGithub,
   repoPath,
   existingBranches = [],
   mainBranch,
+  isGitRepo = true,
 }: NewWorkspaceDialogProps) {
   const [branchName, setBranchName] = useState("")
   const inputRef = useRef<HTMLInputElement>(null)
@@ -99,12 +106,27 @@ export const NewWorkspaceDialog = observer(function NewWorkspaceDialog({
   }, [rawBranches, existingBranches, mainBranch])
 
   const handleCreate = () => {
-    if (!branchName.trim()) return
-    onCreate(branchName.trim())
+    const name = branchName.trim()
+    if (!name) return
+    const issueNumber = /^#(\d+)$/.exec(name)?.[1]
+    if (issueNumber && onCreateFromGithub) {
+      onCreateFromGithub({ kind: "issue", number: Number(issueNumber) })
+    } else {
+      onCreate(name)
+    }
     onOpenChange(false)
     setBranchName("")
   }
 
+  const handleSelectPr = (pr: ReviewPr) => {
+    if (onCreateFromGithub) {
+      onCreateFromGithub({ kind: "pullRequest", number: pr.number })
+      onOpenChange(false)
+    } else {
+      handleSelectBranch(pr.headRefName)
+    }
+  }
+
   const handleSelectBranch = (branch: string) => {
     onCreate(branch)
     onOpenChange(false)
@@ -135,7 +157,7 @@ export const NewWorkspaceDialog = observer(function NewWorkspaceDialog({
 
           <div className="mt-4">
             <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
-              Branch name
+              {isGitRepo ? "Branch name" : "Workspace name"}
             </label>
             <Input
               ref={inputRef}
@@ -143,7 +165,13 @@ export const NewWorkspaceDialog = observer(function NewWorkspaceDialog({
               value={branchName}
               onChange={(e) => setBranchName(e.target.value)}
               onKeyDown={handleKeyDown}
-              placeholder="feature/my-branch"
+              placeholder={
+                !isGitRepo
+                  ? "experiment-1"
+                  : onCreateFromGithub
+                    ? "feature/my-branch or #issue"
+                    : "feature/my-branch"
+              }
               autoFocus
               className="w-full text-xs"
             />
@@ -195,7 +223,7 @@ export const NewWorkspaceDialog = observer(function NewWorkspaceDialog({
                       {reviewPrs.map((pr) => (
                         <button
                           key={pr.number}
-                          onClick={() => handleSelectBranch(pr.headRefName)}
+                          onClick={() => handleSelectPr(pr)}
                           className="rounded px-2 py-1 text-left transition-colors hover:bg-ovr-bg-elevated"
                           data-testid="review-pr-item"
                         >
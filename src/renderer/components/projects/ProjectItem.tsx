@@ -8,6 +8,7 @@ import { WorkspaceList } from "./WorkspaceList"
 import { ProjectSettingsDialog } from "./ProjectSettingsDialog"
 import { NewWorkspaceDialog } from "./NewWorkspaceDialog"
 import { eventBus } from "../../utils/eventBus"
+import type { GithubSource } from "../../services/git"
 
 interface ProjectItemProps {
   project: ProjectStore
@@ -42,12 +43,12 @@ export const ProjectItem = observer(function ProjectItem({ project }: ProjectIte
   // Listen for keyboard shortcut to trigger new workspace dialog
   useEffect(() => {
     return eventBus.on("overseer:new_workspace", () => {
-      if (isSelected && project.isGitRepo) {
+      if (isSelected) {
         setExpanded(true)
         setNewWorkspaceOpen(true)
       }
     })
-  }, [isSelected, project.isGitRepo])
+  }, [isSelected])
 
   const handleSelect = () => {
     projectRegistry.selectProject(project.id)
@@ -59,6 +60,10 @@ export const ProjectItem = observer(function ProjectItem({ project }: ProjectIte
     toastStore.show("Workspace created")
   }
 
+  const handleCreateFromGithub = (source: GithubSource) => {
+    projectRegistry.addWorkspaceFromGithub(project.id, source)
+  }
+
   return (
     <>
       <div className="mx-1 mb-0.5">
@@ -103,14 +108,13 @@ export const ProjectItem = observer(function ProjectItem({ project }: ProjectIte
           <div className="ml-3 mt-0.5">
             <WorkspaceList project={project} />
 
-            {project.isGitRepo && (
-              <button
-                onClick={() => setNewWorkspaceOpen(true)}
-                className="w-full rounded-md px-2 py-1.5 text-left text-xs text-ovr-text-dim transition-colors hover:text-ovr-text-muted"
-              >
-                + Add workspace
-              </button>
-            )}
+            <button
+              onClick={() => setNewWorkspaceOpen(true)}
+              className="w-full rounded-md px-2 py-1.5 text-left text-xs text-ovr-text-dim transition-colors hover:text-ovr-text-muted"
+              title={project.isGitRepo ? undefined : "Creates a copy of the project directory"}
+            >
+              + Add workspace
+            </button>
           </div>
         )}
       </div>
@@ -127,7 +131,13 @@ export const ProjectItem = observer(function ProjectItem({ project }: ProjectIte
         open={newWorkspaceOpen}
         onOpenChange={setNewWorkspaceOpen}
         onCreate={handleCreateWorkspace}
-        repoPath={project.isRemote ? undefined : project.path}
+        onCreateFromGithub={
+          project.isRemote || !project.isGitRepo || project.useGithub === false
+            ? undefined
+            : handleCreateFromGithub
+        }
+        repoPath={project.isRemote || !project.isGitRepo ? undefined : project.path}
+        isGitRepo={project.isGitRepo}
         existingBranches={project.activeWorkspaces.map((w) => w.branch)}
         mainBranch={project.mainBranch}
       />
@@ -1,14 +1,56 @@
 import { useState, useEffect } from "react"
 import * as AlertDialog from "@radix-ui/react-alert-dialog"
 import { observer } from "mobx-react-lite"
-import { X, Trash2 } from "lucide-react"
+import * as Select from "@radix-ui/react-select"
+import { X, Trash2, ChevronDown } from "lucide-react"
 import { projectRegistry } from "../../stores/ProjectRegistry"
 import { toastStore } from "../../stores/ToastStore"
 import type { ProjectStore } from "../../stores/ProjectStore"
+import type { GcReport, MigrationReport, ProjectRepo, Vcs } from "../../types"
+import { formatBytes } from "../../utils/text"
 import { ConfirmDialog } from "../shared/ConfirmDialog"
 import { Input } from "../shared/Input"
 import { Textarea } from "../shared/Textarea"
 import { Checkbox } from "../shared/Checkbox"
+import { SetupStepsEditor, cleanSetupSteps } from "./SetupStepsEditor"
+
+const VCS_OPTIONS: { value: Vcs | "auto"; label: string }[] = [
+  { value: "auto", label: "Detect (git unless only .jj exists)" },
+  { value: "git", label: "Git worktrees" },
+  { value: "jj", label: "Jujutsu workspaces" },
+]
+
+/** One `name = path` line per repository */
+function formatRepos(repos: ProjectRepo[] | undefined): string {
+  return (repos ?? []).map((repo) => `${repo.name} = ${repo.path}`).join("\n")
+}
+
+/**
+ * Parse `name = path` lines; a bare path is named after its last segment.
+ * Per-repo main branches of repos that keep their name are carried over.
+ */
+function parseRepos(text: string, previous: ProjectRepo[] | undefined): ProjectRepo[] {
+  return text
+    .split("\n")
+    .map((line) => line.trim())
+    .filter(Boolean)
+    .map((line) => {
+      const eq = line.indexOf("=")
+      const path = (eq >= 0 ? line.slice(eq + 1) : line).trim()
+      const name =
+        eq >= 0 ? line.slice(0, eq).trim() : (path.replace(/\/+$/, "").split("/").pop() ?? path)
+      const mainBranch = previous?.find((repo) => repo.name === name)?.mainBranch
+      return mainBranch ? { name, path, mainBranch } : { name, path }
+    })
+}
+
+/** Non-empty trimmed lines */
+function parseLines(text: string): string[] {
+  return text
+    .split("\n")
+    .map((line) => line.trim())
+    .filter(Boolean)
+}
 
 interface ProjectSettingsDialogProps {
   open: boolean
@@ -26,6 +68,8 @@ export const ProjectSettingsDialog = observer(function ProjectSettingsDialog({
   const [initPrompt, setInitPrompt] = useState(project.initPrompt || "")
   const [prPrompt, setPrPrompt] = useState(project.prPrompt || "")
   const [postCreate, setPostCreate] = useState(project.postCreate || "")
+  const [setupSteps, setSetupSteps] = useState(project.setupSteps ?? [])
+  const [preArchive, setPreArchive] = useState(project.preArchive || "")
   const [workspaceFilter, setWorkspaceFilter] = useState(project.workspaceFilter || "")
   const [useGithub, setUseGithub] = useState(project.useGithub !== false)
   const [allowMergeToMain, setAllowMergeToMain] = useState(project.allowMergeToMain !== false)
@@ -33,7 +77,19 @@ export const ProjectSettingsDialog = observer(function ProjectSettingsDialog({
   // Unlike useGithub/allowMergeToMain, this defaults to OFF when unset.
   const [defaultSandboxed, setDefaultSandboxed] = useState(project.defaultSandboxed === true)
   const [claudeConfigDir, setClaudeConfigDir] = useState(project.claudeConfigDir || "")
+  const [workspaceRoot, setWorkspaceRoot] = useState(project.workspaceRoot || "")
+  const [workspaceNaming, setWorkspaceNaming] = useState(project.workspaceNaming || "")
+  const [vcs, setVcs] = useState<Vcs | "auto">(project.vcs ?? "auto")
+  const [repos, setRepos] = useState(formatRepos(project.repos))
+  const [redactPatterns, setRedactPatterns] = useState((project.redactPatterns ?? []).join("\n"))
+  const [sensitiveEnvVars, setSensitiveEnvVars] = useState(
+    (project.sensitiveEnvVars ?? []).join(", ")
+  )
   const [pendingArchive, setPendingArchive] = useState(false)
+  /** Dry-run result awaiting confirmation before workspaces are moved */
+  const [pendingMigration, setPendingMigration] = useState<MigrationReport | null>(null)
+  /** Dry-run result awaiting confirmation before workspaces are cleaned up */
+  const [pendingCleanup, setPendingCleanup] = useState<GcReport | null>(null)
 
   // Load fresh approvals from Rust when dialog opens
   useEffect(() => {
@@ -58,12 +114,23 @@ export const ProjectSettingsDialog = observer(function ProjectSettingsDialog({
         initPrompt,
         prPrompt,
         postCreate,
+        setupSteps: cleanSetupSteps(setupSteps),
+        preArchive,
         workspaceFilter,
         useGithub,
         allowMergeToMain,
         mainBranch,
         defaultSandboxed,
         claudeConfigDir,
+        workspaceRoot,
+        workspaceNaming,
+        vcs: vcs === "auto" ? "" : vcs,
+        repos: parseRepos(repos, project.repos),
+        redactPatterns: parseLines(redactPatterns),
+        sensitiveEnvVars: sensitiveEnvVars
+          .split(/[\s,]+/)
+          .map((name) => name.trim())
+          .filter(Boolean),
       })
       toastStore.show("Settings saved")
       onOpenChange(false)
@@ -72,6 +139,68 @@ export const ProjectSettingsDialog = observer(function ProjectSettingsDialog({
     }
   }
 
+  const handlePlanMigration = async () => {
+    projectRegistry.updateProject(project.id, { workspaceRoot, workspaceNaming })
+    try {
+      const plan = await projectRegistry.migrateWorkspaces(project.id, true)
+      if (plan.moves.length === 0) {
+        toastStore.show("All workspaces are already in place")
+      } else {
+        setPendingMigration(plan)
+      }
+    } catch (err) {
+      toastStore.show(err instanceof Error ? err.message : String(err))
+    }
+  }
+
+  const handleMigrate = async () => {
+    setPendingMigration(null)
+    try {
+      const report = await projectRegistry.migrateWorkspaces(project.id, false)
+      const failed = report.moves.filter((m) => m.error)
+      const moved = report.moves.length - failed.length
+      toastStore.show(
+        failed.length > 0
+          ? `Moved ${moved} workspace(s); ${failed.length} failed: ${failed[0].error}`
+          : `Moved ${moved} workspace(s)`
+      )
+    } catch (err) {
+      toastStore.show(err instanceof Error ? err.message : String(err))
+    }
+  }
+
+  const handlePlanCleanup = async () => {
+    try {
+      const plan = await projectRegistry.collectWorkspaceGarbage(project.id, { dryRun: true })
+      const collectable = plan.workspaces.filter((w) => w.reason).length
+      if (collectable === 0 && plan.orphanedChats.length === 0) {
+        toastStore.show(`Nothing to clean up; workspaces use ${formatBytes(plan.totalBytes)}`)
+      } else {
+        setPendingCleanup(plan)
+      }
+    } catch (err) {
+      toastStore.show(err instanceof Error ? err.message : String(err))
+    }
+  }
+
+  const handleCleanup = async () => {
+    setPendingCleanup(null)
+    try {
+      const report = await projectRegistry.collectWorkspaceGarbage(project.id, {
+        deleteBranches: true,
+      })
+      const archived = report.workspaces.filter((w) => w.archived).length
+      const failed = report.workspaces.filter((w) => w.error)
+      toastStore.show(
+        failed.length > 0
+          ? `Archived ${archived} workspace(s); ${failed.length} failed: ${failed[0].error}`
+          : `Archived ${archived} workspace(s), freed ${formatBytes(report.reclaimableBytes)}`
+      )
+    } catch (err) {
+      toastStore.show(err instanceof Error ? err.message : String(err))
+    }
+  }
+
   return (
     <>
       <AlertDialog.Root open={open} onOpenChange={onOpenChange}>
@@ -126,6 +255,41 @@ export const ProjectSettingsDialog = observer(function ProjectSettingsDialog({
                   </p>
                 </div>
 
+                <div>
+                  <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
+                    Redaction patterns
+                  </label>
+                  <Textarea
+                    value={redactPatterns}
+                    onChange={(e) => setRedactPatterns(e.target.value)}
+                    placeholder={"internal-[0-9a-f]{32}\npassword: (?P<secret>\\S+)"}
+                    rows={2}
+                    className="resize-y font-mono text-xs placeholder:text-ovr-text-muted"
+                    data-testid="redact-patterns-input"
+                  />
+                  <p className="mt-1 text-[11px] text-ovr-text-dim">
+                    One regex per line. Matches are removed from chat history and live output,
+                    along with common token formats and random-looking strings. With a{" "}
+                    <code>secret</code> group, only that group is removed.
+                  </p>
+                </div>
+
+                <div>
+                  <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
+                    Sensitive environment variables
+                  </label>
+                  <Input
+                    value={sensitiveEnvVars}
+                    onChange={(e) => setSensitiveEnvVars(e.target.value)}
+                    placeholder="DATABASE_URL, SENTRY_AUTH_TOKEN"
+                    className="font-mono text-xs placeholder:text-ovr-text-muted"
+                    data-testid="sensitive-env-vars-input"
+                  />
+                  <p className="mt-1 text-[11px] text-ovr-text-dim">
+                    Their values are redacted too, besides GH_TOKEN, AWS and AI provider keys.
+                  </p>
+                </div>
+
                 {/* Git-specific settings */}
                 {project.isGitRepo && (
                   <>
@@ -142,6 +306,18 @@ export const ProjectSettingsDialog = observer(function ProjectSettingsDialog({
                       />
                     </div>
 
+                    <div>
+                      <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
+                        Setup steps
+                      </label>
+                      <SetupStepsEditor steps={setupSteps} onChange={setSetupSteps} />
+                      <p className="mt-1 text-[11px] text-ovr-text-dim">
+                        Run in order when a workspace is created: copy files from the project
+                        root, install dependencies, or run a command. Setup stops at the first
+                        failure.
+                      </p>
+                    </div>
+
                     <div>
                       <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
                         Post-create command
@@ -154,12 +330,29 @@ export const ProjectSettingsDialog = observer(function ProjectSettingsDialog({
                         className="w-full text-xs"
                       />
                       <p className="mt-1 text-[11px] text-ovr-text-dim">
-                        Runs in the workspace after a new workspace is created.{" "}
+                        Runs in the workspace after the setup steps.{" "}
                         <code>$WORKSPACE_ROOT</code> is the new workspace,{" "}
                         <code>$PROJECT_ROOT</code> is the project root.
                       </p>
                     </div>
 
+                    <div>
+                      <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
+                        Pre-archive command
+                      </label>
+                      <Input
+                        type="text"
+                        value={preArchive}
+                        onChange={(e) => setPreArchive(e.target.value)}
+                        placeholder="e.g. docker compose down"
+                        className="w-full text-xs"
+                      />
+                      <p className="mt-1 text-[11px] text-ovr-text-dim">
+                        Runs in the workspace before it is archived. Processes still running in
+                        the workspace (dev servers, watchers) are stopped afterwards.
+                      </p>
+                    </div>
+
                     <div>
                       <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
                         Workspace filter
@@ -193,6 +386,110 @@ export const ProjectSettingsDialog = observer(function ProjectSettingsDialog({
                       </p>
                     </div>
 
+                    <div>
+                      <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
+                        Version control
+                      </label>
+                      <Select.Root
+                        value={vcs}
+                        onValueChange={(value) => setVcs(value as Vcs | "auto")}
+                      >
+                        <Select.Trigger
+                          className="flex w-full cursor-pointer items-center justify-between rounded-lg border border-ovr-border-subtle bg-ovr-bg-elevated px-3 py-2 text-xs text-ovr-text-primary focus:border-ovr-azure-500 focus:outline-none"
+                          data-testid="project-vcs-trigger"
+                        >
+                          <Select.Value />
+                          <Select.Icon>
+                            <ChevronDown className="size-3 text-ovr-text-dim" />
+                          </Select.Icon>
+                        </Select.Trigger>
+                        <Select.Portal>
+                          <Select.Content
+                            className="z-[100] overflow-hidden rounded-lg border border-ovr-border-subtle bg-ovr-bg-elevated shadow-lg"
+                            position="popper"
+                            sideOffset={4}
+                          >
+                            <Select.Viewport className="p-1">
+                              {VCS_OPTIONS.map((option) => (
+                                <Select.Item
+                                  key={option.value}
+                                  value={option.value}
+                                  className="flex cursor-pointer items-center gap-2 rounded px-2 py-1.5 text-xs text-ovr-text-primary outline-none data-[highlighted]:bg-ovr-bg-panel"
+                                >
+                                  <Select.ItemText>{option.label}</Select.ItemText>
+                                </Select.Item>
+                              ))}
+                            </Select.Viewport>
+                          </Select.Content>
+                        </Select.Portal>
+                      </Select.Root>
+                      <p className="mt-1 text-[11px] text-ovr-text-dim">
+                        How workspaces, diffs and merges are done. Jujutsu uses{" "}
+                        <code>jj workspace add</code> and works in colocated repositories.
+                      </p>
+                    </div>
+
+                    <div>
+                      <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
+                        Repositories
+                      </label>
+                      <Textarea
+                        value={repos}
+                        onChange={(e) => setRepos(e.target.value)}
+                        placeholder={"backend = ~/code/backend\nfrontend = ~/code/frontend"}
+                        rows={3}
+                        className="resize-y font-mono text-xs placeholder:text-ovr-text-muted"
+                        data-testid="project-repos-input"
+                      />
+                      <p className="mt-1 text-[11px] text-ovr-text-dim">
+                        For projects spanning several repositories, one{" "}
+                        <code>name = path</code> per line. Each workspace gets a worktree of every
+                        repository on the same branch, side by side, and the agent runs in their
+                        parent directory.
+                      </p>
+                    </div>
+
+                    <div>
+                      <label className="mb-1 block text-xs font-medium text-ovr-text-muted">
+                        Workspace location
+                      </label>
+                      <div className="flex gap-2">
+                        <Input
+                          type="text"
+                          value={workspaceRoot}
+                          onChange={(e) => setWorkspaceRoot(e.target.value)}
+                          placeholder="Global setting"
+                          className="flex-1 text-xs"
+                          data-testid="project-workspace-root-input"
+                        />
+                        <Input
+                          type="text"
+                          value={workspaceNaming}
+                          onChange={(e) => setWorkspaceNaming(e.target.value)}
+                          placeholder="{repo}/{animal}"
+                          className="w-36 font-mono text-xs"
+                          data-testid="project-workspace-naming-input"
+                        />
+                      </div>
+                      <p className="mt-1 text-[11px] text-ovr-text-dim">
+                        Root directory and naming template for new workspaces; blank uses the
+                        global setting.{" "}
+                        <button
+                          className="cursor-pointer text-ovr-azure-400 hover:text-ovr-azure-300"
+                          onClick={handlePlanMigration}
+                        >
+                          Move existing workspaces
+                        </button>
+                        {" · "}
+                        <button
+                          className="cursor-pointer text-ovr-azure-400 hover:text-ovr-azure-300"
+                          onClick={handlePlanCleanup}
+                        >
+                          Clean up workspaces
+                        </button>
+                      </p>
+                    </div>
+
                     <div className="space-y-2">
                       <label className="flex cursor-pointer items-center gap-2">
                         <Checkbox
@@ -341,6 +638,41 @@ export const ProjectSettingsDialog = observer(function ProjectSettingsDialog({
         </AlertDialog.Portal>
       </AlertDialog.Root>
 
+      <ConfirmDialog
+        open={pendingMigration !== null}
+        onOpenChange={(migrationOpen) => {
+          if (!migrationOpen) setPendingMigration(null)
+        }}
+        title="Move workspaces"
+        description={
+          pendingMigration
+            ? `${pendingMigration.moves.length} workspace(s) will be moved, ` +
+              `e.g. ${pendingMigration.moves[0].from} → ${pendingMigration.moves[0].to}. ` +
+              "Their chats move with them."
+            : ""
+        }
+        confirmLabel="Move"
+        onConfirm={handleMigrate}
+      />
+
+      <ConfirmDialog
+        open={pendingCleanup !== null}
+        onOpenChange={(cleanupOpen) => {
+          if (!cleanupOpen) setPendingCleanup(null)
+        }}
+        title="Clean up workspaces"
+        description={
+          pendingCleanup
+            ? `${pendingCleanup.workspaces.filter((w) => w.reason).length} workspace(s) with ` +
+              "merged branches or closed pull requests will be archived and their merged " +
+              `branches deleted, freeing ${formatBytes(pendingCleanup.reclaimableBytes)}; ` +
+              `${pendingCleanup.orphanedChats.length} orphaned chat folder(s) will be archived.`
+            : ""
+        }
+        confirmLabel="Clean up"
+        onConfirm={handleCleanup}
+      />
+
       <ConfirmDialog
         open={pendingArchive}
         onOpenChange={(archiveOpen) => {
@@ -0,0 +1,138 @@
+import * as Select from "@radix-ui/react-select"
+import { ChevronDown, Trash2 } from "lucide-react"
+import type { SetupAction, SetupStep } from "../../types"
+import { Input } from "../shared/Input"
+
+const STEP_KINDS: { value: SetupAction["kind"]; label: string; placeholder: string }[] = [
+  { value: "copyFiles", label: "Copy files", placeholder: ".env, config/local.yml" },
+  { value: "install", label: "Install", placeholder: "Detected from lockfile" },
+  { value: "shell", label: "Shell", placeholder: "e.g. make db" },
+]
+
+interface SetupStepsEditorProps {
+  steps: SetupStep[]
+  onChange: (steps: SetupStep[]) => void
+}
+
+/** The text shown in a step's value field: paths for copyFiles, otherwise the command. */
+function stepValue(step: SetupStep): string {
+  return step.kind === "copyFiles" ? step.paths.join(", ") : (step.command ?? "")
+}
+
+function withValue(step: SetupStep, value: string): SetupStep {
+  switch (step.kind) {
+    case "copyFiles":
+      return {
+        name: step.name,
+        kind: "copyFiles",
+        // Empty entries are kept while typing and dropped by cleanSetupSteps.
+        paths: value.split(",").map((p) => p.trim()),
+      }
+    case "install":
+      return { name: step.name, kind: "install", command: value || undefined }
+    case "shell":
+      return { name: step.name, kind: "shell", command: value }
+  }
+}
+
+function withKind(step: SetupStep, kind: SetupAction["kind"]): SetupStep {
+  const name = step.name
+  switch (kind) {
+    case "copyFiles":
+      return { name, kind, paths: [] }
+    case "install":
+      return { name, kind }
+    case "shell":
+      return { name, kind, command: "" }
+  }
+}
+
+/**
+ * Drop empty paths and name unnamed steps after their kind, ready to save.
+ * Shell steps without a command are removed.
+ */
+export function cleanSetupSteps(steps: SetupStep[]): SetupStep[] {
+  return steps
+    .map((step) => {
+      const name = step.name.trim() || STEP_KINDS.find((k) => k.value === step.kind)!.label
+      return step.kind === "copyFiles"
+        ? { ...step, name, paths: step.paths.filter(Boolean) }
+        : { ...step, name }
+    })
+    .filter((step) => step.kind !== "shell" || step.command.trim() !== "")
+}
+
+/**
+ * Edits a project's setup steps: one row per step with its kind, name and
+ * paths or command. Steps run in order when a workspace is created.
+ */
+export function SetupStepsEditor({ steps, onChange }: SetupStepsEditorProps) {
+  const replace = (index: number, step: SetupStep) =>
+    onChange(steps.map((s, i) => (i === index ? step : s)))
+
+  return (
+    <div className="space-y-1.5">
+      {steps.map((step, index) => (
+        <div key={index} className="flex items-center gap-2" data-testid="setup-step-row">
+          <Select.Root
+            value={step.kind}
+            onValueChange={(kind) => replace(index, withKind(step, kind as SetupAction["kind"]))}
+          >
+            <Select.Trigger className="flex w-24 shrink-0 cursor-pointer items-center justify-between gap-1 rounded border border-ovr-border-subtle px-2 py-1 text-xs text-ovr-text-primary focus:outline-none">
+              <Select.Value />
+              <Select.Icon>
+                <ChevronDown className="size-3" />
+              </Select.Icon>
+            </Select.Trigger>
+            <Select.Portal>
+              <Select.Content
+                className="z-[100] overflow-hidden rounded-lg border border-ovr-border-subtle bg-ovr-bg-elevated shadow-lg"
+                position="popper"
+                sideOffset={4}
+              >
+                <Select.Viewport className="p-1">
+                  {STEP_KINDS.map((kind) => (
+                    <Select.Item
+                      key={kind.value}
+                      value={kind.value}
+                      className="flex cursor-pointer items-center gap-2 rounded px-2 py-1.5 text-xs text-ovr-text-primary outline-none data-[highlighted]:bg-ovr-bg-panel data-[state=checked]:text-ovr-azure-400"
+                    >
+                      <Select.ItemText>{kind.label}</Select.ItemText>
+                    </Select.Item>
+                  ))}
+                </Select.Viewport>
+              </Select.Content>
+            </Select.Portal>
+          </Select.Root>
+          <Input
+            type="text"
+            value={step.name}
+            onChange={(e) => replace(index, { ...step, name: e.target.value })}
+            placeholder="Name"
+            className="w-24 text-xs"
+          />
+          <Input
+            type="text"
+            value={stepValue(step)}
+            onChange={(e) => replace(index, withValue(step, e.target.value))}
+            placeholder={STEP_KINDS.find((k) => k.value === step.kind)?.placeholder}
+            className="flex-1 font-mono text-xs"
+          />
+          <button
+            onClick={() => onChange(steps.filter((_, i) => i !== index))}
+            className="cursor-pointer p-1 text-ovr-text-dim hover:text-red-400"
+            title="Remove step"
+          >
+            <Trash2 className="size-3" />
+          </button>
+        </div>
+      ))}
+      <button
+        className="cursor-pointer text-[11px] text-ovr-azure-400 hover:text-ovr-azure-300"
+        onClick={() => onChange([...steps, { name: "", kind: "copyFiles", paths: [] }])}
+      >
+        Add step
+      </button>
+    </div>
+  )
+}
@@ -156,17 +156,19 @@ export const WorkspaceList = observer(function WorkspaceList({ project }: Worksp
                 </button>
               </AlertDialog.Action>
             </div>
-            <div className="mt-3 flex justify-end">
-              <label className="flex cursor-pointer items-center gap-2 text-xs text-ovr-text-muted">
-                <input
-                  type="checkbox"
-                  checked={deleteBranch}
-                  onChange={(e) => setDeleteBranch(e.target.checked)}
-                  className="size-3.5 cursor-pointer accent-ovr-azure-500"
-                />
-                Also remove branch
-              </label>
-            </div>
+            {project.isGitRepo && (
+              <div className="mt-3 flex justify-end">
+                <label className="flex cursor-pointer items-center gap-2 text-xs text-ovr-text-muted">
+                  <input
+                    type="checkbox"
+                    checked={deleteBranch}
+                    onChange={(e) => setDeleteBranch(e.target.checked)}
+                    className="size-3.5 cursor-pointer accent-ovr-azure-500"
+                  />
+                  Also remove branch
+                </label>
+              </div>
+            )}
           </AlertDialog.Content>
         </AlertDialog.Portal>
       </AlertDialog.Root>
@@ -264,6 +264,43 @@ describe("NewWorkspaceDialog", () => {
     expect(defaultProps.onOpenChange).toHaveBeenCalledWith(false)
   })
 
+  it("clicking a review PR creates from GitHub when supported", async () => {
+    configStore.showReviewPrs = true
+    vi.mocked(gitService.listReviewPrs).mockResolvedValue([
+      { number: 7, title: "My PR", headRefName: "feature/my-pr", authorLogin: "carol" },
+    ])
+    const onCreateFromGithub = vi.fn()
+
+    render(
+      <NewWorkspaceDialog
+        {...defaultProps}
+        onCreateFromGithub={onCreateFromGithub}
+        repoPath="/repo"
+      />
+    )
+
+    await waitFor(() => {
+      expect(screen.getByTestId("review-prs-list")).toBeInTheDocument()
+    })
+
+    fireEvent.click(screen.getByTestId("review-pr-item"))
+
+    expect(onCreateFromGithub).toHaveBeenCalledWith({ kind: "pullRequest", number: 7 })
+    expect(defaultProps.onCreate).not.toHaveBeenCalled()
+  })
+
+  it("typing #123 creates from the issue when supported", () => {
+    const onCreateFromGithub = vi.fn()
+    render(<NewWorkspaceDialog {...defaultProps} onCreateFromGithub={onCreateFromGithub} />)
+
+    const input = screen.getByPlaceholderText("feature/my-branch or #issue")
+    fireEvent.change(input, { target: { value: "#123" } })
+    fireEvent.click(screen.getByText("Create"))
+
+    expect(onCreateFromGithub).toHaveBeenCalledWith({ kind: "issue", number: 123 })
+    expect(defaultProps.onCreate).not.toHaveBeenCalled()
+  })
+
   it("hides the review PRs section when list is empty", async () => {
     configStore.showReviewPrs = true
     vi.mocked(gitService.listReviewPrs).mockResolvedValue([])
@@ -0,0 +1,77 @@
+import { useState } from "react"
+import * as AlertDialog from "@radix-ui/react-alert-dialog"
+import { Lock } from "lucide-react"
+import { backend } from "../../backend"
+
+interface ChatPassphraseDialogProps {
+  open: boolean
+  onUnlocked: () => void
+}
+
+/**
+ * Dialog that asks for the chat passphrase when the config dir's chats are
+ * encrypted with one and it wasn't given at startup
+ * (`OVERSEER_CHAT_PASSPHRASE`). Until then chats can't be read or written.
+ */
+export function ChatPassphraseDialog({ open, onUnlocked }: ChatPassphraseDialogProps) {
+  const [passphrase, setPassphrase] = useState("")
+  const [error, setError] = useState<string | null>(null)
+  const [unlocking, setUnlocking] = useState(false)
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault()
+    if (!passphrase) return
+
+    setError(null)
+    setUnlocking(true)
+    try {
+      await backend.invoke("unlock_chats", { passphrase })
+      onUnlocked()
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err))
+    } finally {
+      setUnlocking(false)
+    }
+  }
+
+  return (
+    <AlertDialog.Root open={open}>
+      <AlertDialog.Portal>
+        <AlertDialog.Overlay className="fixed inset-0 z-50 bg-black/70" />
+        <AlertDialog.Content className="fixed left-1/2 top-1/2 z-50 w-[90vw] max-w-sm -translate-x-1/2 -translate-y-1/2 rounded-xl border border-ovr-border-subtle bg-ovr-bg-panel p-6 shadow-ovr-panel">
+          <AlertDialog.Title className="mb-1 flex items-center gap-2 text-base font-semibold text-ovr-text-strong">
+            <Lock className="size-5" />
+            Chats Are Encrypted
+          </AlertDialog.Title>
+          <AlertDialog.Description className="mb-4 text-sm text-ovr-text-muted">
+            Enter the passphrase your chats were encrypted with to open them.
+          </AlertDialog.Description>
+
+          <form onSubmit={handleSubmit}>
+            <input
+              type="password"
+              value={passphrase}
+              onChange={(e) => setPassphrase(e.target.value)}
+              placeholder="Chat passphrase"
+              autoFocus
+              className="ovr-input mb-3 w-full px-3 py-2 text-sm"
+              disabled={unlocking}
+              data-testid="chat-passphrase-input"
+            />
+
+            {error && <p className="mb-3 text-xs text-ovr-error">{error}</p>}
+
+            <button
+              type="submit"
+              disabled={unlocking || !passphrase}
+              className="ovr-btn-primary w-full py-2 text-sm disabled:opacity-50"
+              data-testid="chat-passphrase-submit-btn"
+            >
+              {unlocking ? "Unlocking..." : "Unlock"}
+            </button>
+          </form>
+        </AlertDialog.Content>
+      </AlertDialog.Portal>
+    </AlertDialog.Root>
+  )
+}
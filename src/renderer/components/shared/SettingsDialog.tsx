@@ -779,6 +779,34 @@ const AdvancedTab = observer(function AdvancedTab() {
         </p>
       </div>
 
+      {/* Workspace location */}
+      <div>
+        <label className="mb-2 block text-xs font-medium text-ovr-text-muted">Workspaces</label>
+        <div className="flex max-w-md gap-2">
+          <Input
+            type="text"
+            value={configStore.workspaceRoot}
+            onChange={(e) => configStore.setWorkspaceRoot(e.target.value)}
+            placeholder="~/overseer/workspaces"
+            className="flex-1 px-3 py-2 text-xs"
+            data-testid="workspace-root-input"
+          />
+          <Input
+            type="text"
+            value={configStore.workspaceNaming}
+            onChange={(e) => configStore.setWorkspaceNaming(e.target.value)}
+            placeholder="{repo}/{animal}"
+            className="w-40 px-3 py-2 font-mono text-xs"
+            data-testid="workspace-naming-input"
+          />
+        </div>
+        <p className="mt-2 text-[11px] text-ovr-text-dim">
+          Where new workspaces are created and how they are named. Placeholders: {"{repo}"},{" "}
+          {"{branch}"}, {"{animal}"}, {"{n}"}. Projects can override both; existing workspaces
+          are moved from project settings.
+        </p>
+      </div>
+
       {/* HTTP Server */}
       <div>
         <label className="mb-2 block text-xs font-medium text-ovr-text-muted">HTTP Server</label>
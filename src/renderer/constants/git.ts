@@ -6,6 +6,7 @@ export const STATUS_STYLES: Record<string, { label: string; color: string }> = {
   A: { label: "A", color: "text-ovr-diff-add" },
   D: { label: "D", color: "text-ovr-diff-del" },
   R: { label: "R", color: "text-ovr-azure-400" },
+  C: { label: "C", color: "text-ovr-azure-400" },
   "?": { label: "?", color: "text-ovr-text-dim" },
 }
 
@@ -17,5 +18,6 @@ export const STATUS_LABELS: Record<string, { label: string; color: string }> = {
   A: { label: "Added", color: "text-ovr-diff-add" },
   D: { label: "Deleted", color: "text-ovr-diff-del" },
   R: { label: "Renamed", color: "text-ovr-azure-400" },
+  C: { label: "Copied", color: "text-ovr-azure-400" },
   "?": { label: "Untracked", color: "text-ovr-text-dim" },
 }
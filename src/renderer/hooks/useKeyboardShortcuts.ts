@@ -76,9 +76,8 @@ export function useKeyboardShortcuts() {
             break
           case "n": {
             e.preventDefault()
-            // Open new workspace dialog if a git project is selected
-            const project = projectRegistry.selectedProject
-            if (project?.isGitRepo) {
+            // Open new workspace dialog if a project is selected
+            if (projectRegistry.selectedProject) {
               eventBus.emit("overseer:new_workspace", undefined as never)
             }
             break
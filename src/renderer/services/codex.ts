@@ -1,5 +1,6 @@
 import { backend, type Unsubscribe } from "../backend"
 import type { AgentService, AgentEventCallback, AgentDoneCallback, AgentEvent } from "./types"
+import type { ChatFork } from "../types"
 import { configStore } from "../stores/ConfigStore"
 import { codexUsageStore, type CodexUsageData } from "../stores/CodexUsageStore"
 import { toolAvailabilityStore } from "../stores/ToolAvailabilityStore"
@@ -141,6 +142,8 @@ interface RustAgentEvent {
 interface CodexChat {
   serverId: string
   threadId: string | null
+  /** Fork plan for the next thread (see forkFrom) */
+  pendingFork: ChatFork | null
   running: boolean
   workingDir: string
   unlistenStdout: Unsubscribe | null
@@ -175,6 +178,7 @@ class CodexAgentService implements AgentService {
       chat = {
         serverId: chatId,
         threadId: null,
+        pendingFork: null,
         running: false,
         workingDir: "",
         unlistenStdout: null,
@@ -269,6 +273,20 @@ class CodexAgentService implements AgentService {
     // Use passed permission mode or fall back to configStore
     const approvalPolicy = permissionMode ?? configStore.codexApprovalPolicy
 
+    // A forked chat's first thread forks the source thread; if that fails the
+    // conversation is replayed into a fresh one instead.
+    let forkPreface: string | null = null
+    if (!chat.threadId && chat.pendingFork) {
+      const fork = chat.pendingFork
+      chat.pendingFork = null
+      try {
+        await this.forkThread(chatId, fork)
+      } catch (err) {
+        console.warn(`Failed to fork Codex thread [${chatId}], replaying instead:`, err)
+        forkPreface = fork.replayPrompt
+      }
+    }
+
     // If no thread yet, create one
     if (!chat.threadId) {
       const result = (await this.sendRequest(chatId, "thread/start", {
@@ -285,7 +303,8 @@ class CodexAgentService implements AgentService {
     }
 
     // Prepend initPrompt to the first message of a new session
-    const messageText = isNewSession && initPrompt ? `${initPrompt}\n\n${prompt}` : prompt
+    let messageText = isNewSession && initPrompt ? `${initPrompt}\n\n${prompt}` : prompt
+    if (forkPreface) messageText = `${forkPreface}\n\n${messageText}`
 
     // Send the turn
     await this.sendRequest(chatId, "turn/start", {
@@ -301,6 +320,31 @@ class CodexAgentService implements AgentService {
     })
   }
 
+  forkFrom(chatId: string, fork: ChatFork): boolean {
+    if (fork.resume.mode !== "codex") return false
+    this.getOrCreateChat(chatId).pendingFork = fork
+    return true
+  }
+
+  /** Fork the source thread into this chat, then drop the turns after the fork point. */
+  private async forkThread(chatId: string, fork: ChatFork): Promise<void> {
+    if (fork.resume.mode !== "codex") return
+    const chat = this.getOrCreateChat(chatId)
+    const result = (await this.sendRequest(chatId, "thread/fork", {
+      threadId: fork.resume.threadId,
+    })) as { thread?: { id?: string } }
+    const threadId = result?.thread?.id
+    if (!threadId) throw new Error("thread/fork returned no thread id")
+    if (fork.resume.dropTurns > 0) {
+      await this.sendRequest(chatId, "thread/rollback", {
+        threadId,
+        numTurns: fork.resume.dropTurns,
+      })
+    }
+    chat.threadId = threadId
+    this.emitEvent(chatId, { kind: "sessionId", sessionId: threadId })
+  }
+
   async sendToolApproval(
     chatId: string,
     requestId: string,
@@ -1,7 +1,25 @@
 import { backend as defaultBackend } from "../backend"
 import type { Backend } from "../backend/types"
 import { configStore } from "../stores/ConfigStore"
-import type { ChangedFile, ChangedFilesResult, Commit, MergeResult } from "../types"
+import type {
+  ChangedFile,
+  ChangedFilesResult,
+  Commit,
+  DiffOptions,
+  FileDiff,
+  GcOptions,
+  GcReport,
+  MergeOptions,
+  MergeResult,
+  MergeStrategy,
+  MigrationReport,
+  ConflictResolution,
+  ReviewFeedback,
+  SyncOptions,
+  SyncResult,
+  SyncState,
+  TeardownReport,
+} from "../types"
 
 export interface WorkspaceInfo {
   path: string
@@ -15,6 +33,12 @@ export interface PrStatus {
   is_draft: boolean
 }
 
+/** The pull request of one repository in a multi-repo workspace */
+export interface RepoPrStatus {
+  repo: string
+  pr: PrStatus | null
+}
+
 export interface ReviewPr {
   number: number
   title: string
@@ -22,6 +46,20 @@ export interface ReviewPr {
   authorLogin: string
 }
 
+/** What to start a workspace from on GitHub. */
+export type GithubSource =
+  | { kind: "pullRequest"; number: number }
+  | { kind: "issue"; number: number }
+
+export interface GithubWorkspace {
+  path: string
+  branch: string
+  initPrompt: string | null
+  prNumber: number | null
+  prUrl: string | null
+  prState: "OPEN" | "MERGED" | "CLOSED" | null
+}
+
 /**
  * GitService wraps git operations via the backend.
  * Can be instantiated with a specific backend for remote projects,
@@ -42,8 +80,39 @@ export class GitService {
     return this.backend.invoke<string>("add_workspace", { repoPath, branch })
   }
 
-  async archiveWorkspace(repoPath: string, workspacePath: string, force = false): Promise<void> {
-    return this.backend.invoke<void>("archive_workspace", { repoPath, workspacePath, force })
+  /** Move a project's workspaces to its current workspace root and naming template. */
+  async migrateWorkspaces(projectId: string, dryRun: boolean): Promise<MigrationReport> {
+    return this.backend.invoke<MigrationReport>("migrate_workspaces", { projectId, dryRun })
+  }
+
+  /** Report workspace disk usage and archive merged or closed-PR workspaces. */
+  async collectWorkspaceGarbage(projectId: string, options: GcOptions): Promise<GcReport> {
+    return this.backend.invoke<GcReport>("collect_workspace_garbage", { projectId, options })
+  }
+
+  async addWorkspaceFromGithub(
+    repoPath: string,
+    source: GithubSource,
+    initPrompt?: string
+  ): Promise<GithubWorkspace> {
+    return this.backend.invoke<GithubWorkspace>("add_workspace_from_github", {
+      repoPath,
+      source,
+      initPrompt: initPrompt ?? null,
+      agentShell: configStore.agentShell || null,
+    })
+  }
+
+  async archiveWorkspace(
+    repoPath: string,
+    workspacePath: string,
+    force = false
+  ): Promise<TeardownReport> {
+    return this.backend.invoke<TeardownReport>("archive_workspace", {
+      repoPath,
+      workspacePath,
+      force,
+    })
   }
 
   async listChangedFiles(workspacePath: string, mainBranch?: string): Promise<ChangedFilesResult> {
@@ -65,20 +134,64 @@ export class GitService {
     return this.backend.invoke<string[]>("list_files", { workspacePath })
   }
 
-  async checkMerge(workspacePath: string, mainBranch?: string): Promise<MergeResult> {
-    return this.backend.invoke<MergeResult>("check_merge", { workspacePath, mainBranch })
+  async checkMerge(
+    workspacePath: string,
+    mainBranch?: string,
+    strategy?: MergeStrategy
+  ): Promise<MergeResult> {
+    return this.backend.invoke<MergeResult>("check_merge", { workspacePath, mainBranch, strategy })
   }
 
-  async mergeIntoMain(workspacePath: string, mainBranch?: string): Promise<MergeResult> {
-    return this.backend.invoke<MergeResult>("merge_into_main", { workspacePath, mainBranch })
+  async mergeIntoMain(
+    workspacePath: string,
+    mainBranch?: string,
+    options?: MergeOptions
+  ): Promise<MergeResult> {
+    return this.backend.invoke<MergeResult>("merge_into_main", {
+      workspacePath,
+      mainBranch,
+      options,
+    })
+  }
+
+  /** Merge or rebase the default branch into the workspace. Conflicts leave it mid-sync. */
+  async syncWorkspace(
+    workspacePath: string,
+    mainBranch?: string,
+    options?: SyncOptions
+  ): Promise<SyncResult> {
+    return this.backend.invoke<SyncResult>("sync_workspace", {
+      workspacePath,
+      mainBranch,
+      options,
+    })
+  }
+
+  async getSyncState(workspacePath: string): Promise<SyncState | null> {
+    return this.backend.invoke<SyncState | null>("get_sync_state", { workspacePath })
+  }
+
+  async continueSync(workspacePath: string): Promise<SyncResult> {
+    return this.backend.invoke<SyncResult>("continue_sync", { workspacePath })
+  }
+
+  async abortSync(workspacePath: string): Promise<void> {
+    return this.backend.invoke<void>("abort_sync", { workspacePath })
+  }
+
+  async prepareConflictResolution(workspacePath: string): Promise<ConflictResolution | null> {
+    return this.backend.invoke<ConflictResolution | null>("prepare_conflict_resolution", {
+      workspacePath,
+    })
   }
 
   async renameBranch(workspacePath: string, newName: string, mainBranch?: string): Promise<void> {
     return this.backend.invoke<void>("rename_branch", { workspacePath, newName, mainBranch })
   }
 
-  async deleteBranch(repoPath: string, branchName: string): Promise<void> {
-    return this.backend.invoke<void>("delete_branch", { repoPath, branchName })
+  /** `force` deletes a branch git considers unmerged, e.g. after a squash merge. */
+  async deleteBranch(repoPath: string, branchName: string, force?: boolean): Promise<void> {
+    return this.backend.invoke<void>("delete_branch", { repoPath, branchName, force })
   }
 
   async getFileDiff(
@@ -95,6 +208,22 @@ export class GitService {
     })
   }
 
+  async getStructuredFileDiff(
+    workspacePath: string,
+    filePath: string,
+    fileStatus: string,
+    mainBranch?: string,
+    options?: DiffOptions
+  ): Promise<FileDiff[]> {
+    return this.backend.invoke<FileDiff[]>("get_structured_file_diff", {
+      workspacePath,
+      filePath,
+      fileStatus,
+      mainBranch,
+      options,
+    })
+  }
+
   async detectDefaultBranch(repoPath: string): Promise<string> {
     return this.backend.invoke<string>("detect_default_branch", { repoPath })
   }
@@ -111,6 +240,20 @@ export class GitService {
     })
   }
 
+  async getStructuredUncommittedDiff(
+    workspacePath: string,
+    filePath: string,
+    fileStatus: string,
+    options?: DiffOptions
+  ): Promise<FileDiff[]> {
+    return this.backend.invoke<FileDiff[]>("get_structured_uncommitted_diff", {
+      workspacePath,
+      filePath,
+      fileStatus,
+      options,
+    })
+  }
+
   async getSubmoduleFileDiff(
     workspacePath: string,
     submodulePath: string,
@@ -161,6 +304,22 @@ export class GitService {
     })
   }
 
+  async getStructuredCommitDiff(
+    workspacePath: string,
+    commitSha: string,
+    filePath: string,
+    fileStatus: string,
+    options?: DiffOptions
+  ): Promise<FileDiff[]> {
+    return this.backend.invoke<FileDiff[]>("get_structured_commit_diff", {
+      workspacePath,
+      commitSha,
+      filePath,
+      fileStatus,
+      options,
+    })
+  }
+
   async getPrStatus(workspacePath: string, branch: string): Promise<PrStatus | null> {
     return this.backend.invoke<PrStatus | null>("get_pr_status", {
       workspacePath,
@@ -169,6 +328,14 @@ export class GitService {
     })
   }
 
+  /** PR status of each repository in a multi-repo workspace */
+  async listRepoPrStatuses(workspacePath: string): Promise<RepoPrStatus[]> {
+    return this.backend.invoke<RepoPrStatus[]>("list_repo_pr_statuses", {
+      workspacePath,
+      agentShell: configStore.agentShell || null,
+    })
+  }
+
   async listRecentBranches(repoPath: string): Promise<string[]> {
     return this.backend.invoke<string[]>("list_recent_branches", { repoPath })
   }
@@ -180,6 +347,42 @@ export class GitService {
     })
   }
 
+  /**
+   * Gather the unresolved review threads on a workspace's PR into a prompt.
+   * Threads already handed to an agent are left out unless `includeAddressed`.
+   */
+  async prepareReviewFeedback(
+    projectName: string,
+    workspaceName: string,
+    workspacePath: string,
+    includeAddressed = false
+  ): Promise<ReviewFeedback | null> {
+    return this.backend.invoke<ReviewFeedback | null>("prepare_review_feedback", {
+      projectName,
+      workspaceName,
+      workspacePath,
+      includeAddressed,
+      agentShell: configStore.agentShell || null,
+    })
+  }
+
+  /** Record review threads as handed to a chat's agent. */
+  async markReviewThreadsAddressed(
+    projectName: string,
+    workspaceName: string,
+    prNumber: number,
+    chatId: string,
+    threadIds: string[]
+  ): Promise<void> {
+    return this.backend.invoke<void>("mark_review_threads_addressed", {
+      projectName,
+      workspaceName,
+      prNumber,
+      chatId,
+      threadIds,
+    })
+  }
+
   async isGitRepo(path: string): Promise<boolean> {
     return this.backend.invoke<boolean>("is_git_repo", { path })
   }
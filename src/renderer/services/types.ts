@@ -1,4 +1,4 @@
-import type { Attachment, MessageMeta, QuestionItem, ToolMeta } from "../types"
+import type { Attachment, ChatFork, MessageMeta, QuestionItem, ToolMeta } from "../types"
 
 export type AgentType = "claude" | "codex" | "copilot" | "gemini" | "hermes" | "opencode" | "pi"
 
@@ -95,4 +95,9 @@ export interface AgentService {
   onDone(chatId: string, callback: AgentDoneCallback): void
   /** Attach event listeners for a chat. Call this to receive events from other clients. */
   attachListeners(chatId: string): Promise<void>
+  /**
+   * Start a forked chat's next session from the fork point natively. Returns
+   * false if this agent can't, in which case the conversation is replayed.
+   */
+  forkFrom?(chatId: string, fork: ChatFork): boolean
 }
@@ -4,7 +4,13 @@ import { GitService, isWorktreeDirtyError, type PrStatus } from "../services/git
 import { projectRegistry } from "./ProjectRegistry"
 import { toastStore } from "./ToastStore"
 import { eventBus } from "../utils/eventBus"
-import type { ChangedFile, SubmoduleResult } from "../types"
+import type {
+  ChangedFile,
+  MergeStrategy,
+  SubmoduleResult,
+  SyncState,
+  SyncStrategy,
+} from "../types"
 
 /**
  * Store for managing changed files pane state.
@@ -23,6 +29,13 @@ export class ChangedFilesStore {
   @observable checking = false
   @observable merging = false
   @observable showMergeConfirm = false
+  @observable mergeStrategy: MergeStrategy = "merge"
+  /** Push the default branch to origin after merging */
+  @observable pushAfterMerge = false
+  @observable syncing = false
+  @observable syncStrategy: SyncStrategy = "merge"
+  /** A sync with the default branch stopped on conflicts, waiting to be continued or aborted */
+  @observable syncState: SyncState | null = null
   /** True when a merge succeeded but the workspace has uncommitted changes and we're asking whether to discard them before archiving. */
   @observable showDiscardConfirm = false
   @observable diffFile: ChangedFile | null = null
@@ -79,6 +92,10 @@ export class ChangedFilesStore {
         this.isDefaultBranch = result.is_default_branch
         this.lastLoadTime = Date.now()
       })
+      const syncState = await this.gitService.getSyncState(this.workspacePath)
+      runInAction(() => {
+        this.syncState = syncState
+      })
     } catch (err) {
       runInAction(() => {
         this.error = err instanceof Error ? err.message : String(err)
@@ -115,6 +132,8 @@ export class ChangedFilesStore {
   async refreshPrCall(): Promise<void> {
     // Skip if already refreshing to avoid concurrent gh CLI calls
     if (this.prLoading) return
+    // A copied workspace of a non-git project has no branch to open a PR for
+    if (projectRegistry.selectedProject?.isGitRepo === false) return
 
     // Find this store's workspace to get the branch name
     const workspace = projectRegistry.selectedProject?.workspaces?.find(
@@ -156,7 +175,8 @@ export class ChangedFilesStore {
     try {
       const result = await this.gitService.checkMerge(
         this.workspacePath,
-        projectRegistry.selectedProject?.mainBranch
+        projectRegistry.selectedProject?.mainBranch,
+        this.mergeStrategy
       )
       runInAction(() => {
         if (result.success) {
@@ -164,9 +184,7 @@ export class ChangedFilesStore {
         } else if (result.conflicts.length > 0) {
           const uniqueList = Array.from(new Set(result.conflicts)).join(", ")
           const workspaceStore = projectRegistry.selectedWorkspaceStore
-          workspaceStore?.sendMessage(
-            `There are merge conflicts in the following files that need resolution: ${uniqueList}. Please merge in the local default branch and resolve these merge conflicts.`
-          )
+          workspaceStore?.sendMessage(this.conflictsMessage(uniqueList))
           this.refresh()
         } else {
           this.error = result.message
@@ -183,6 +201,15 @@ export class ChangedFilesStore {
     }
   }
 
+  /** What the agent is asked to do about files that conflict with the default branch. */
+  private conflictsMessage(files: string): string {
+    const project = projectRegistry.selectedProject
+    if (project?.isGitRepo === false) {
+      return `The following files were also changed in the project directory (${project.path}) since this workspace was copied: ${files}. Please bring those changes into the files here, keeping yours, so they can be copied back.`
+    }
+    return `There are merge conflicts in the following files that need resolution: ${files}. Please merge in the local default branch and resolve these merge conflicts.`
+  }
+
   @action
   async merge(archiveAfter: boolean, deleteBranch: boolean): Promise<void> {
     this.showMergeConfirm = false
@@ -197,7 +224,8 @@ export class ChangedFilesStore {
     try {
       const result = await this.gitService.mergeIntoMain(
         this.workspacePath,
-        projectRegistry.selectedProject?.mainBranch
+        projectRegistry.selectedProject?.mainBranch,
+        { strategy: this.mergeStrategy, push: this.pushAfterMerge }
       )
       if (result.success) {
         if (archiveAfter) {
@@ -207,7 +235,9 @@ export class ChangedFilesStore {
           if (project) {
             projectRegistry.switchToMainWorkspace(project.id)
           }
-          toastStore.show("Branch merged successfully")
+          toastStore.show(
+            project?.isGitRepo === false ? result.message : "Branch merged successfully"
+          )
           await this.refresh()
           // Refresh PR state to show merged status
           await this.refreshPr()
@@ -220,9 +250,7 @@ export class ChangedFilesStore {
       } else if (result.conflicts.length > 0) {
         const uniqueList = Array.from(new Set(result.conflicts)).join(", ")
         const workspaceStore = projectRegistry.selectedWorkspaceStore
-        workspaceStore?.sendMessage(
-          `There are merge conflicts in the following files that need resolution: ${uniqueList}. Please merge in the local default branch and resolve these merge conflicts.`
-        )
+        workspaceStore?.sendMessage(this.conflictsMessage(uniqueList))
         this.refresh()
       } else {
         runInAction(() => {
@@ -240,6 +268,96 @@ export class ChangedFilesStore {
     }
   }
 
+  /**
+   * Bring the default branch's new commits into this workspace. On conflicts
+   * the worktree is left mid-sync and the agent is asked to resolve them;
+   * {@link continueSync} finishes once it has.
+   */
+  @action
+  async syncWithDefault(): Promise<void> {
+    this.syncing = true
+    this.error = null
+
+    try {
+      const result = await this.gitService.syncWorkspace(
+        this.workspacePath,
+        projectRegistry.selectedProject?.mainBranch,
+        { strategy: this.syncStrategy, fetch: true }
+      )
+      if (result.status === "conflicts") {
+        await this.sendConflictsToAgent()
+      } else {
+        toastStore.show(result.message)
+      }
+      await this.refresh()
+    } catch (err) {
+      runInAction(() => {
+        this.error = err instanceof Error ? err.message : String(err)
+      })
+    } finally {
+      runInAction(() => {
+        this.syncing = false
+      })
+    }
+  }
+
+  /** Ask the workspace's agent to resolve the conflicts of a stopped sync. */
+  async sendConflictsToAgent(): Promise<void> {
+    const resolution = await this.gitService.prepareConflictResolution(this.workspacePath)
+    if (!resolution) return
+    const workspaceStore = projectRegistry.selectedWorkspaceStore
+    workspaceStore?.sendMessage(resolution.prompt, {
+      type: "sync_conflicts",
+      label: `Resolve ${resolution.files.length} conflicted file(s)`,
+    })
+  }
+
+  /** Commit the merge or continue the rebase once no conflict markers are left. */
+  @action
+  async continueSync(): Promise<void> {
+    this.syncing = true
+    this.error = null
+
+    try {
+      const result = await this.gitService.continueSync(this.workspacePath)
+      if (result.status === "conflicts") {
+        // A rebase stopped again on a later commit
+        await this.sendConflictsToAgent()
+      } else {
+        toastStore.show(result.message)
+      }
+      await this.refresh()
+    } catch (err) {
+      runInAction(() => {
+        this.error = err instanceof Error ? err.message : String(err)
+      })
+    } finally {
+      runInAction(() => {
+        this.syncing = false
+      })
+    }
+  }
+
+  @action
+  async abortSync(): Promise<void> {
+    this.syncing = true
+    this.error = null
+
+    try {
+      await this.gitService.abortSync(this.workspacePath)
+      toastStore.show("Sync aborted")
+      await this.refresh()
+    } catch (err) {
+      runInAction(() => {
+        this.error = err instanceof Error ? err.message : String(err)
+      })
+    } finally {
+      runInAction(() => {
+        this.syncing = false
+      })
+    }
+  }
+
   /**
    * Archive the workspace after a successful merge, then optionally delete the
    * branch. If the workspace has uncommitted changes and `force` is false, the
@@ -267,12 +385,17 @@ export class ChangedFilesStore {
       throw err
     }
 
-    let toastMessage = "Branch merged and workspace archived"
+    const isGitRepo = project?.isGitRepo !== false
+    let toastMessage = isGitRepo
+      ? "Branch merged and workspace archived"
+      : "Changes copied back and workspace archived"
 
     // Delete branch after successful archive
-    if (deleteBranch && branchName && projectPath) {
+    if (isGitRepo && deleteBranch && branchName && projectPath) {
       try {
-        await this.gitService.deleteBranch(projectPath, branchName)
+        // A squashed branch's own commits never reach the default branch, so
+        // git refuses a plain delete even though its work was merged.
+        await this.gitService.deleteBranch(projectPath, branchName, this.mergeStrategy === "squash")
         toastMessage = "Branch merged, workspace archived, and branch deleted"
       } catch {
         // Branch deletion failed but merge and archive succeeded
@@ -368,6 +491,21 @@ export class ChangedFilesStore {
     this.showMergeConfirm = show
   }
 
+  @action
+  setMergeStrategy(strategy: MergeStrategy): void {
+    this.mergeStrategy = strategy
+  }
+
+  @action
+  setSyncStrategy(strategy: SyncStrategy): void {
+    this.syncStrategy = strategy
+  }
+
+  @action
+  setPushAfterMerge(push: boolean): void {
+    this.pushAfterMerge = push
+  }
+
   /** Total count of all changed files (uncommitted + branch changes + submodules) */
   @computed
   get totalFileCount(): number {
@@ -13,6 +13,12 @@ import type {
   AgentType,
   AutonomousMessageType,
   AutonomousReviewConfig,
+  ChatFork,
+  DiffOptions,
+  FileDiff,
+  HandoffPrompt,
+  RewindResult,
+  TurnCheckpoint,
 } from "../types"
 import { groupMessagesIntoTurns } from "../utils/groupMessagesIntoTurns"
 import { createAgentService } from "../services/agentRegistry"
@@ -82,6 +88,15 @@ type BackendAgentEvent = {
   meta?: Record<string, unknown>
 }
 
+/** A chat handed over to another agent (`hand_off_chat`) */
+type ChatHandoff = {
+  handoff: HandoffPrompt
+  /** The recorded handoff message */
+  event: BackendAgentEvent
+  /** Seq of the handoff message */
+  seq: number
+}
+
 type BackendChatMetadata = {
   id: string
   workspaceId: string
@@ -282,6 +297,8 @@ export class ChatStore {
       modelVersion?: string | null
       /** Override the permission mode (used for autonomous review with a different agent) */
       permissionMode?: string | null
+      /** The message is already in the chat (a handoff records its own) */
+      recorded?: boolean
     }
   ): Promise<void> {
     const activeService = opts?.service ?? this.service
@@ -293,8 +310,9 @@ export class ChatStore {
     }
     if (!activeService) return // Can't send without an agent
 
-    // Pass initPrompt only on the first message of a new session
-    const isFirstMessage = this.chat.messages.length === 0
+    // Pass initPrompt only on the first message of a new session (a handoff
+    // starts one too)
+    const isFirstMessage = this.chat.messages.length === 0 || meta?.type === "handoff"
     let initPrompt = isFirstMessage ? this.context?.getInitPrompt() : undefined
 
     // Add agent-specific shell instructions for Codex
@@ -311,7 +329,7 @@ export class ChatStore {
       chatId: this.chat.id,
     })
 
-    await this.persistUserMessage(content, meta, attachments)
+    if (!opts?.recorded) await this.persistUserMessage(content, meta, attachments)
     runInAction(() => {
       this.isSending = true
     })
@@ -337,12 +355,27 @@ export class ChatStore {
         this.chat.agentType === "claude" ? this.context?.getClaudeConfigDir() : undefined
 
       // Prepend attachment paths to the message so the agent can read the files
+      // (decrypted copies when chats are encrypted)
       let messageContent = content
       if (attachments && attachments.length > 0) {
-        const pathList = attachments.map((a) => `- ${a.path}`).join("\n")
+        const paths = await Promise.all(attachments.map((a) => this.readableAttachmentPath(a)))
+        const pathList = paths.map((path) => `- ${path}`).join("\n")
         messageContent = `[Attached files:\n${pathList}]\n\n${content}`
       }
 
+      // A forked chat's first message picks up from the fork point. Claude's
+      // backend applies the fork itself when it starts the session.
+      if (
+        !this.chat.agentSessionId &&
+        (this.chat.agentType ?? "claude") !== "claude" &&
+        !opts?.service
+      ) {
+        const fork = await this.takeChatFork()
+        if (fork && !activeService.forkFrom?.(this.chat.id, fork)) {
+          messageContent = `${fork.replayPrompt}\n\n${messageContent}`
+        }
+      }
+
       await activeService.sendMessage(
         this.chat.id,
         messageContent,
@@ -600,6 +633,107 @@ export class ChatStore {
     })
   }
 
+  /** The chat's user messages, oldest first, with their workspace checkpoints. */
+  async listTurnCheckpoints(): Promise<TurnCheckpoint[]> {
+    return this.backend.invoke<TurnCheckpoint[]>("list_turn_checkpoints", {
+      projectName: this.context.getProjectName(),
+      workspaceName: this.context.getWorkspaceName(),
+      chatId: this.chat.id,
+      workspacePath: this.context.getWorkspacePath(),
+    })
+  }
+
+  /** What changed in the workspace since the checkpoint taken before `turnId`. */
+  async diffTurnCheckpoint(turnId: string, options?: DiffOptions): Promise<FileDiff[]> {
+    return this.backend.invoke<FileDiff[]>("diff_turn_checkpoint", {
+      chatId: this.chat.id,
+      turnId,
+      workspacePath: this.context.getWorkspacePath(),
+      options,
+    })
+  }
+
+  /**
+   * Restore the workspace to its state before `turnId`. With `truncateChat`
+   * the conversation is cut back to just before that message too, and the
+   * next message resumes the agent session as it was at that point. The
+   * agent is stopped first so it can't keep writing to the workspace.
+   */
+  async rewindToTurn(turnId: string, truncateChat: boolean): Promise<RewindResult> {
+    if (this.service) {
+      await this.service.stopChat(this.chat.id)
+      runInAction(() => {
+        this.isSending = false
+        this.pendingFollowUps = []
+      })
+    }
+    const result = await this.backend.invoke<RewindResult>("rewind_to_turn", {
+      projectName: this.context.getProjectName(),
+      workspaceName: this.context.getWorkspaceName(),
+      chatId: this.chat.id,
+      workspacePath: this.context.getWorkspacePath(),
+      turnId,
+      truncateChat,
+      claudeConfigDir:
+        this.chat.agentType === "claude" ? this.context?.getClaudeConfigDir() : undefined,
+    })
+    if (truncateChat) {
+      runInAction(() => {
+        this.chat.messages = []
+        this.chat.agentSessionId = null
+        this.seenSeqs.clear()
+        this.loaded = false
+      })
+      this.service?.setSessionId(this.chat.id, null)
+      await this.loadFromDisk()
+    }
+    return result
+  }
+
+  /**
+   * Hand this chat over to a different agent. The current agent is stopped,
+   * and the backend switches the chat to the new agent with a fresh session
+   * and records a context transfer prompt (goal, progress, files touched,
+   * open todos) as a "handoff" message, which shows the switch in the chat's
+   * timeline. The new agent is then started on that prompt.
+   */
+  async handOffTo(agentType: AgentType): Promise<void> {
+    if (agentType === (this.chat.agentType ?? "claude")) return
+    if (this.isSending) this.stopGeneration()
+
+    const previous = this.service
+    if (previous) {
+      await previous.stopChat(this.chat.id)
+      previous.removeChat(this.chat.id)
+    }
+
+    const workspacePath = this.context.getWorkspacePath()
+    const { handoff, event, seq } = await this.backend.invoke<ChatHandoff>("hand_off_chat", {
+      projectName: this.context.getProjectName(),
+      workspaceName: this.context.getWorkspaceName(),
+      chatId: this.chat.id,
+      toAgent: agentType,
+      workspacePath,
+    })
+    runInAction(() => {
+      this.chat.agentType = agentType
+      this.chat.agentSessionId = null
+      this.chat.modelVersion = configStore.getDefaultModelForAgent(agentType)
+      this.chat.permissionMode = null
+      this.chat.effortLevel = null
+      this.pendingFollowUps = []
+    })
+    // The service getter builds the new agent's service on first access
+    this.registerCallbacks()
+    await this.persistMetadata()
+    this.context.saveIndex()
+
+    const message = this.mapRustEvent(event)
+    if (message) this.handleAgentEvent(message, seq)
+    const meta = message?.kind === "userMessage" ? message.meta : undefined
+    await this.sendMessage(handoff.prompt, workspacePath, meta, undefined, { recorded: true })
+  }
+
   @action
   setModelVersion(model: string | null): void {
     if (this.chat.modelVersion !== model) {
@@ -1157,6 +1291,9 @@ Read \`autonomous-progress.md\` to see what has been accomplished.
     }
     runInAction(() => {
       const messages = this.chat.messages
+      const before = messages.length
+      const tail = messages[before - 1]
+      const tailContent = tail?.content
 
       switch (event.kind) {
         case "sessionId":
@@ -1396,6 +1533,12 @@ Read \`autonomous-progress.md\` to see what has been accomplished.
           // Handled by the onDone callback
           break
       }
+
+      // Record which event each message came from, so a fork can be cut there.
+      // Streamed deltas move their message's seq on to the latest delta.
+      if (seq === undefined) return
+      if (tail && tail.content !== tailContent) tail.seq = seq
+      for (let i = before; i < messages.length; i++) messages[i].seq = seq
     })
   }
 
@@ -1452,6 +1595,18 @@ Read \`autonomous-progress.md\` to see what has been accomplished.
     return false
   }
 
+  private async readableAttachmentPath(attachment: Attachment): Promise<string> {
+    try {
+      return await this.backend.invoke<string>("readable_attachment_path", {
+        path: attachment.path,
+        chatId: this.chat.id,
+      })
+    } catch (err) {
+      console.error("Failed to prepare attachment for the agent:", err)
+      return attachment.path
+    }
+  }
+
   private pushUserMsg(content: string, meta?: MessageMeta, attachments?: Attachment[]): void {
     const id = crypto.randomUUID()
     this.chat.messages.push({
@@ -1525,6 +1680,18 @@ Read \`autonomous-progress.md\` to see what has been accomplished.
 
   // --- Private: Persistence ---
 
+  /** Take this chat's pending fork plan (null if it isn't a fork, or it was taken). */
+  private async takeChatFork(): Promise<ChatFork | null> {
+    try {
+      return await this.backend.invoke<ChatFork | null>("take_chat_fork", {
+        chatId: this.chat.id,
+      })
+    } catch (err) {
+      console.error("Failed to read fork plan:", err)
+      return null
+    }
+  }
+
   private buildMetadata(): BackendChatMetadata {
     return {
       id: this.chat.id,
@@ -1595,6 +1762,8 @@ Read \`autonomous-progress.md\` to see what has been accomplished.
         chatId: this.chat.id,
         content,
         meta: metaWithAttachments,
+        // Lets the backend checkpoint the workspace before the turn starts
+        workspacePath: this.context.getWorkspacePath() || null,
       })
       if (!event) {
         throw new Error("No event returned from add_user_message")
@@ -26,6 +26,10 @@ interface Config {
   opencodePath: string
   piPath: string
   agentShell?: string
+  /** Directory new workspaces are created under (default ~/overseer/workspaces) */
+  workspaceRoot?: string
+  /** Naming template for new workspaces, e.g. "{repo}/{branch}" */
+  workspaceNaming?: string
   leftPaneWidth: number
   rightPaneWidth: number
   changesHeight: number
@@ -234,6 +238,8 @@ class ConfigStore {
   @observable systemNotificationEnabled: boolean = false
   @observable showReviewPrs: boolean = false
   @observable agentShell: string = ""
+  @observable workspaceRoot: string = ""
+  @observable workspaceNaming: string = ""
   @observable settingsOpen: boolean = false
   @observable loaded: boolean = false
 
@@ -355,6 +361,8 @@ class ConfigStore {
         this.systemNotificationEnabled = parsed.systemNotificationEnabled ?? false
         this.showReviewPrs = parsed.showReviewPrs ?? false
         this.agentShell = parsed.agentShell ?? ""
+        this.workspaceRoot = parsed.workspaceRoot ?? ""
+        this.workspaceNaming = parsed.workspaceNaming ?? ""
         // HTTP Server settings
         if (parsed.httpServer) {
           this.httpServerHost = parsed.httpServer.host ?? "127.0.0.1"
@@ -429,6 +437,8 @@ class ConfigStore {
         systemNotificationEnabled: this.systemNotificationEnabled,
         showReviewPrs: this.showReviewPrs,
         agentShell: this.agentShell || undefined,
+        workspaceRoot: this.workspaceRoot || undefined,
+        workspaceNaming: this.workspaceNaming || undefined,
         httpServer: {
           host: this.httpServerHost,
           port: this.httpServerPort,
@@ -646,6 +656,16 @@ class ConfigStore {
     this.save()
   }
 
+  @action setWorkspaceRoot(root: string) {
+    this.workspaceRoot = root
+    this.save()
+  }
+
+  @action setWorkspaceNaming(naming: string) {
+    this.workspaceNaming = naming
+    this.save()
+  }
+
   @action setSettingsOpen(open: boolean) {
     this.settingsOpen = open
   }
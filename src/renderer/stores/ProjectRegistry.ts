@@ -1,32 +1,114 @@
 import { observable, computed, action, makeObservable, runInAction } from "mobx"
-import type { Project, Workspace } from "../types"
-import { gitService } from "../services/git"
+import type {
+  ConfigChange,
+  GcOptions,
+  GcReport,
+  MigrationReport,
+  Project,
+  SetupEvent,
+  SetupStep,
+  ProjectRepo,
+  Vcs,
+  Workspace,
+  WorkspaceSetup,
+} from "../types"
+import { gitService, type GithubSource } from "../services/git"
 import { terminalService } from "../services/terminal"
 import { ProjectStore } from "./ProjectStore"
 import type { WorkspaceStore, WorkspaceStatus } from "./WorkspaceStore"
 import { toastStore } from "./ToastStore"
 import { workspaceHistoryStore } from "./WorkspaceHistoryStore"
-import { backend } from "../backend"
+import { backend, isConflictError } from "../backend"
+import { describeTeardown } from "../utils/git"
 import { restoreFromUrl } from "../utils/urlState"
 import { remoteServerStore } from "./RemoteServerStore"
 
-/** Result from run_shell_command backend call */
-interface ShellCommandResult {
-  exitCode: number
-  stdout: string
-  stderr: string
-  success: boolean
+/** `load_project_registry` result: the registry and its version */
+type VersionedRegistry = { projects: Project[]; version: string | null }
+
+/** A plain copy of `projects` as they're saved */
+function snapshot(projects: Project[]): Project[] {
+  return JSON.parse(JSON.stringify(projects))
+}
+
+/** Whether `a` and `b` save the same, whatever their key order */
+function sameJson(a: unknown, b: unknown): boolean {
+  const sorted = (_key: string, value: unknown) =>
+    value && typeof value === "object" && !Array.isArray(value)
+      ? Object.fromEntries(Object.entries(value).sort(([x], [y]) => x.localeCompare(y)))
+      : value
+  return JSON.stringify(a, sorted) === JSON.stringify(b, sorted)
+}
+
+/** Replace `target`'s fields with `source`'s, keeping the object */
+function assignFields<T extends object>(target: T, source: T): void {
+  for (const key of Object.keys(target)) {
+    if (!(key in source)) delete (target as Record<string, unknown>)[key]
+  }
+  Object.assign(target, source)
+}
+
+/**
+ * Merge two edits of a list made from `base`: items added or removed on one
+ * side are added or removed, and items on both go through `mergeItem`.
+ */
+function mergeById<T extends { id: string }>(
+  base: T[],
+  ours: T[],
+  theirs: T[],
+  mergeItem: (base: T, ours: T, theirs: T) => T
+): T[] {
+  const baseById = new Map(base.map((item) => [item.id, item]))
+  const oursById = new Map(ours.map((item) => [item.id, item]))
+  const theirIds = new Set(theirs.map((item) => item.id))
+  const merged: T[] = []
+  for (const their of theirs) {
+    const was = baseById.get(their.id)
+    const our = oursById.get(their.id)
+    if (!our) {
+      // Added by them, or removed by us
+      if (!was) merged.push(their)
+    } else {
+      merged.push(was ? mergeItem(was, our, their) : our)
+    }
+  }
+  for (const our of ours) {
+    if (!theirIds.has(our.id) && !baseById.has(our.id)) merged.push(our)
+  }
+  return merged
+}
+
+/** Keep our copy if we changed it, else take theirs into it */
+function mergeFields<T extends object>(base: T, ours: T, theirs: T): T {
+  if (sameJson(ours, base)) assignFields(ours, theirs)
+  return ours
+}
+
+function mergeProject(base: Project, ours: Project, theirs: Project): Project {
+  const { workspaces: baseWorkspaces, ...baseFields } = base
+  const { workspaces: ourWorkspaces, ...ourFields } = ours
+  const { workspaces: theirWorkspaces, ...theirFields } = theirs
+  if (sameJson(ourFields, baseFields)) assignFields(ours, { ...theirFields, workspaces: [] })
+  ours.workspaces = mergeById(baseWorkspaces, ourWorkspaces, theirWorkspaces, mergeFields)
+  return ours
 }
 
 class ProjectRegistry {
   @observable private _projects: Project[] = []
   private _projectStoreCache = new Map<string, ProjectStore>()
+  // Version of projects.json `_projects` was loaded or last saved at
+  private _registryVersion: string | null = null
+  // projects.json at that version, to tell our changes from others'
+  private _savedProjects: Project[] = []
+  // Saves go one at a time: each needs the version the previous one wrote
+  private _saving: Promise<void> = Promise.resolve()
   @observable selectedProjectId: string | null = null
   @observable selectedWorkspaceId: string | null = null
 
   constructor() {
     makeObservable(this)
     this.loadFromFile()
+    this.watchConfigChanges()
   }
 
   /**
@@ -242,12 +324,20 @@ class ProjectRegistry {
       initPrompt?: string
       prPrompt?: string
       postCreate?: string
+      setupSteps?: SetupStep[]
+      preArchive?: string
       workspaceFilter?: string
       useGithub?: boolean
       allowMergeToMain?: boolean
       mainBranch?: string
       defaultSandboxed?: boolean
       claudeConfigDir?: string
+      workspaceRoot?: string
+      workspaceNaming?: string
+      vcs?: Vcs | ""
+      repos?: ProjectRepo[]
+      redactPatterns?: string[]
+      sensitiveEnvVars?: string[]
     }
   ): void {
     const project = this._projects.find((r) => r.id === id)
@@ -255,6 +345,9 @@ class ProjectRegistry {
     if (updates.initPrompt !== undefined) project.initPrompt = updates.initPrompt || undefined
     if (updates.prPrompt !== undefined) project.prPrompt = updates.prPrompt || undefined
     if (updates.postCreate !== undefined) project.postCreate = updates.postCreate || undefined
+    if (updates.setupSteps !== undefined)
+      project.setupSteps = updates.setupSteps.length > 0 ? updates.setupSteps : undefined
+    if (updates.preArchive !== undefined) project.preArchive = updates.preArchive || undefined
     if (updates.workspaceFilter !== undefined)
       project.workspaceFilter = updates.workspaceFilter || undefined
     if (updates.useGithub !== undefined) project.useGithub = updates.useGithub
@@ -263,6 +356,19 @@ class ProjectRegistry {
     if (updates.defaultSandboxed !== undefined) project.defaultSandboxed = updates.defaultSandboxed
     if (updates.claudeConfigDir !== undefined)
       project.claudeConfigDir = updates.claudeConfigDir || undefined
+    if (updates.workspaceRoot !== undefined)
+      project.workspaceRoot = updates.workspaceRoot || undefined
+    if (updates.workspaceNaming !== undefined)
+      project.workspaceNaming = updates.workspaceNaming || undefined
+    if (updates.vcs !== undefined) project.vcs = updates.vcs || undefined
+    if (updates.repos !== undefined)
+      project.repos = updates.repos.length > 0 ? updates.repos : undefined
+    if (updates.redactPatterns !== undefined)
+      project.redactPatterns =
+        updates.redactPatterns.length > 0 ? updates.redactPatterns : undefined
+    if (updates.sensitiveEnvVars !== undefined)
+      project.sensitiveEnvVars =
+        updates.sensitiveEnvVars.length > 0 ? updates.sensitiveEnvVars : undefined
     // Also update the cached ProjectStore if it exists
     const store = this._projectStoreCache.get(id)
     if (store) {
@@ -271,6 +377,56 @@ class ProjectRegistry {
     this.saveToFile()
   }
 
+  /**
+   * Move a project's workspaces to its current workspace root and naming
+   * template. The backend rewrites projects.json, so the registry is reloaded
+   * and the project's stores rebuilt with the new paths. With `dryRun`, only
+   * reports where they would go.
+   */
+  async migrateWorkspaces(projectId: string, dryRun: boolean): Promise<MigrationReport> {
+    if (!dryRun && this.hasRunningChats()) {
+      throw new Error("Stop running chats before moving workspaces")
+    }
+    // Make sure the backend sees the latest settings
+    await this.saveToFile()
+    const report = await gitService.migrateWorkspaces(projectId, dryRun)
+    if (!dryRun && report.moves.some((m) => !m.error)) {
+      await this.loadFromFile()
+      runInAction(() => {
+        this._projectStoreCache.delete(projectId)
+      })
+    }
+    return report
+  }
+
+  /**
+   * Report disk usage of a project's workspaces and, unless `options.dryRun`,
+   * archive the ones whose branch or pull request is merged (or closed).
+   * The backend updates `projects.json`, so the registry is reloaded after.
+   */
+  async collectWorkspaceGarbage(projectId: string, options: GcOptions): Promise<GcReport> {
+    if (!options.dryRun && this.hasRunningChats()) {
+      throw new Error("Stop running chats before cleaning up workspaces")
+    }
+    await this.saveToFile()
+    const report = await gitService.collectWorkspaceGarbage(projectId, options)
+    const archived = report.workspaces.filter((w) => w.archived)
+    if (archived.length > 0) {
+      for (const w of archived) {
+        terminalService.destroy(w.path)
+        workspaceHistoryStore.remove(w.workspaceId)
+      }
+      if (archived.some((w) => w.workspaceId === this.selectedWorkspaceId)) {
+        this.switchToMainWorkspace(projectId)
+      }
+      await this.loadFromFile()
+      runInAction(() => {
+        this._projectStoreCache.delete(projectId)
+      })
+    }
+    return report
+  }
+
   @action removeProject(id: string): void {
     this._projects = this._projects.filter((r) => r.id !== id)
     this._projectStoreCache.delete(id)
@@ -321,7 +477,51 @@ class ProjectRegistry {
     const project = this._projects.find((r) => r.id === projectId)
     if (!project) return
 
-    const postCreate = project.postCreate
+    this.createWorkspace(project, branch, async () => ({
+      path: await gitService.addWorkspace(project.path, branch),
+    }))
+  }
+
+  /**
+   * Add a workspace from a GitHub pull request (checks out its head branch)
+   * or issue (new branch, init prompt seeded from the issue).
+   */
+  @action addWorkspaceFromGithub(projectId: string, source: GithubSource): void {
+    const project = this._projects.find((r) => r.id === projectId)
+    if (!project) return
+
+    // Placeholder until gh tells us the real branch name
+    const placeholder =
+      source.kind === "pullRequest" ? `PR #${source.number}` : `Issue #${source.number}`
+
+    this.createWorkspace(project, placeholder, async () => {
+      const created = await gitService.addWorkspaceFromGithub(
+        project.path,
+        source,
+        project.initPrompt
+      )
+      return {
+        path: created.path,
+        branch: created.branch,
+        initPrompt: created.initPrompt ?? undefined,
+        prNumber: created.prNumber ?? undefined,
+        prUrl: created.prUrl ?? undefined,
+        prState: created.prState ?? undefined,
+      }
+    })
+  }
+
+  /**
+   * Show a workspace in isCreating state right away, then fill it in from
+   * `create` (which makes the git worktree) or remove it if that fails.
+   */
+  private createWorkspace(
+    project: Project,
+    branch: string,
+    create: () => Promise<Partial<Workspace> & { path: string }>
+  ): void {
+    const projectId = project.id
+    const hasSetup = !!project.postCreate || (project.setupSteps?.length ?? 0) > 0
     const id = crypto.randomUUID()
 
     // Add workspace immediately with isCreating state
@@ -346,13 +546,12 @@ class ProjectRegistry {
     this.selectedWorkspaceId = id
 
     // Create git worktree in background
-    gitService
-      .addWorkspace(project.path, branch)
-      .then((workspacePath) => {
+    create()
+      .then((created) => {
         runInAction(() => {
           const wt = project.workspaces.find((w) => w.id === id)
           if (wt) {
-            wt.path = workspacePath
+            Object.assign(wt, created)
             wt.isCreating = false
           }
           if (projectStore) {
@@ -361,9 +560,9 @@ class ProjectRegistry {
         })
         this.saveToFile()
 
-        // Run postCreate script in background
-        if (postCreate) {
-          this.runPostCreate(projectId, id)
+        // Run setup steps and postCreate in background
+        if (hasSetup) {
+          this.runSetup(project, id)
         }
       })
       .catch((err) => {
@@ -388,14 +587,43 @@ class ProjectRegistry {
   }
 
   /**
-   * Run postCreate script in background and post result to chat.
+   * Run the project's setup steps in background, following progress on the
+   * workspace, and post the result to chat.
    */
-  private async runPostCreate(projectId: string, workspaceId: string): Promise<void> {
+  private async runSetup(project: Project, workspaceId: string): Promise<void> {
+    const findWorkspace = () => project.workspaces.find((w) => w.id === workspaceId)
+    // The backend records progress in projects.json too; mirror it here so our
+    // own saves don't overwrite it.
+    const unlisten = await backend.listen<SetupEvent>(`workspace:setup:${workspaceId}`, (event) => {
+      runInAction(() => {
+        const wt = findWorkspace()
+        if (!wt) return
+        if (event.kind === "started") {
+          wt.setup = {
+            status: "running",
+            steps: event.steps.map((name) => ({ name, status: "pending" })),
+            startedAt: new Date().toISOString(),
+          }
+        } else if (event.kind === "stepStarted" && wt.setup?.steps[event.index]) {
+          wt.setup.steps[event.index].status = "running"
+        } else if (event.kind === "stepFinished" && wt.setup) {
+          wt.setup.steps[event.index] = event.result
+        } else if (event.kind === "finished") {
+          wt.setup = event.setup
+        }
+      })
+    })
+
     try {
-      const result = await backend.invoke<ShellCommandResult>("run_post_create_command", {
-        projectId,
+      const setup = await backend.invoke<WorkspaceSetup | null>("run_workspace_setup", {
+        projectId: project.id,
         workspaceId,
       })
+      if (!setup) return
+      runInAction(() => {
+        const wt = findWorkspace()
+        if (wt) wt.setup = setup
+      })
 
       // Find the workspace store and active chat to post messages
       const workspaceStore = this.getWorkspaceStoreByWorkspaceId(workspaceId)
@@ -409,21 +637,23 @@ class ProjectRegistry {
         return
       }
 
-      // Get the project to show the command in the message
-      const project = this._projects.find((p) => p.id === projectId)
-      const command = project?.postCreate ?? "setup"
-
-      if (result.success) {
-        chat.addSystemMessage(`Setup complete: \`${command}\``)
+      const failed = setup.steps.find((step) => step.status === "failed")
+      if (!failed) {
+        const names = setup.steps.map((step) => `\`${step.name}\``).join(", ")
+        chat.addSystemMessage(`Setup complete: ${names}`)
       } else {
-        const output = result.stderr || result.stdout || "No output"
+        const exit = failed.exitCode !== undefined ? ` (exit ${failed.exitCode})` : ""
+        const output = failed.output?.trim()
         chat.addSystemMessage(
-          `Setup failed (exit ${result.exitCode}): \`${command}\`\n\n\`\`\`\n${output.trim()}\n\`\`\``
+          `Setup failed at \`${failed.name}\`${exit}: ${failed.error ?? "unknown error"}` +
+            (output ? `\n\n\`\`\`\n${output}\n\`\`\`` : "")
         )
       }
     } catch (err) {
-      console.error("Failed to run postCreate:", err)
-      toastStore.show(`Setup script failed: ${err}`)
+      console.error("Failed to run workspace setup:", err)
+      toastStore.show(`Workspace setup failed: ${err}`)
+    } finally {
+      unlisten()
     }
   }
 
@@ -470,8 +700,12 @@ class ProjectRegistry {
           // and force is false), let the error propagate. Do NOT continue to
           // delete the branch or mark the workspace archived — that would lose
           // the work. The catch below reverts the optimistic state.
-          await gitService.archiveWorkspace(project.path, wt.path, force)
+          const teardown = await gitService.archiveWorkspace(project.path, wt.path, force)
           terminalService.destroy(wt.path)
+          const summary = describeTeardown(teardown)
+          if (summary) {
+            toastStore.show(`Archived ${branchName}: ${summary}`)
+          }
 
           // Removal succeeded — now it is safe to leave the workspace.
           if (wasSelected) {
@@ -682,14 +916,29 @@ class ProjectRegistry {
     })
   }
 
-  private async saveToFile(): Promise<void> {
+  private saveToFile(): Promise<void> {
+    this._saving = this._saving.then(() => this.writeToFile())
+    return this._saving
+  }
+
+  private async writeToFile(retry = true): Promise<void> {
+    const projects = snapshot(this._projects)
     try {
       // Rust handles backward compatibility (writes to both projects.json and repos.json)
-      await backend.invoke("save_project_registry", {
-        registry: { projects: this._projects },
+      this._registryVersion = await backend.invoke<string>("save_project_registry", {
+        registry: { projects },
+        version: this._registryVersion,
       })
+      this._savedProjects = projects
     } catch (err) {
+      if (retry && isConflictError(err)) {
+        await this.reloadProjects()
+        return this.writeToFile(false)
+      }
       console.error("Failed to save projects:", err)
+      if (isConflictError(err)) {
+        toastStore.show("Projects keep changing elsewhere, your last change wasn't saved")
+      }
     }
   }
 
@@ -719,12 +968,59 @@ class ProjectRegistry {
     return
   }
 
+  /**
+   * Reload projects.json and approvals when the backend or another process
+   * sharing the config dir (the daemon) changes them, so our next save isn't
+   * rejected as stale. Changes we saved ourselves are skipped.
+   */
+  private watchConfigChanges(): void {
+    backend.listen<ConfigChange>("config:changed", (change) => {
+      if (change.document === "projects") {
+        // After any save in flight, which may be the change
+        this._saving = this._saving.then(() =>
+          change.version !== this._registryVersion ? this.reloadProjects() : undefined
+        )
+      } else if (change.document === "approvals") {
+        const store = this.localProjects.find((p) => p.name === change.project)
+        store?.loadApprovals(true)
+      }
+    })
+  }
+
+  /**
+   * Load projects.json again, keeping the changes we haven't saved yet and
+   * the project and workspace objects they're made on.
+   */
+  private async reloadProjects(): Promise<void> {
+    try {
+      const registry = await backend.invoke<VersionedRegistry>("load_project_registry")
+      runInAction(() => {
+        this._projects = mergeById(
+          this._savedProjects,
+          this._projects,
+          registry.projects,
+          mergeProject
+        )
+        this._registryVersion = registry.version
+        this._savedProjects = snapshot(registry.projects)
+        for (const project of this._projects) {
+          const store = this._projectStoreCache.get(project.id)
+          if (store) store.workspaces = project.workspaces
+        }
+      })
+    } catch (err) {
+      console.error("Failed to reload projects:", err)
+    }
+  }
+
   private async loadFromFile(): Promise<void> {
     try {
       // Rust handles all migration logic (worktrees→workspaces, repoId→projectId, etc.)
-      const registry = await backend.invoke<{ projects: Project[] }>("load_project_registry")
+      const registry = await backend.invoke<VersionedRegistry>("load_project_registry")
       runInAction(() => {
         this._projects = registry.projects
+        this._registryVersion = registry.version
+        this._savedProjects = snapshot(registry.projects)
       })
 
       // Restore selection state from URL params (e.g., after mobile reload)
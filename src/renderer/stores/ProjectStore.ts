@@ -1,5 +1,5 @@
 import { observable, computed, action, makeObservable, runInAction } from "mobx"
-import type { Project, Workspace } from "../types"
+import type { Project, ProjectRepo, SetupStep, Vcs, Workspace } from "../types"
 import type { Backend } from "../backend/types"
 import { WorkspaceStore, type WorkspaceStatus } from "./WorkspaceStore"
 import { backend } from "../backend"
@@ -34,6 +34,12 @@ export class ProjectStore {
   @observable
   postCreate?: string
 
+  @observable
+  setupSteps?: SetupStep[]
+
+  @observable
+  preArchive?: string
+
   @observable
   workspaceFilter?: string
 
@@ -57,6 +63,24 @@ export class ProjectStore {
   @observable
   claudeConfigDir?: string
 
+  @observable
+  workspaceRoot?: string
+
+  @observable
+  workspaceNaming?: string
+
+  @observable
+  vcs?: Vcs
+
+  @observable
+  repos?: ProjectRepo[]
+
+  @observable
+  redactPatterns?: string[]
+
+  @observable
+  sensitiveEnvVars?: string[]
+
   // --- Approval storage (shared across all workspaces in this project) ---
 
   @observable
@@ -80,6 +104,8 @@ export class ProjectStore {
     this.initPrompt = project.initPrompt
     this.prPrompt = project.prPrompt
     this.postCreate = project.postCreate
+    this.setupSteps = project.setupSteps
+    this.preArchive = project.preArchive
     this.workspaceFilter = project.workspaceFilter
     this.useGithub = project.useGithub
     this.allowMergeToMain = project.allowMergeToMain
@@ -87,6 +113,12 @@ export class ProjectStore {
     this.mainBranch = project.mainBranch
     this.defaultSandboxed = project.defaultSandboxed
     this.claudeConfigDir = project.claudeConfigDir
+    this.workspaceRoot = project.workspaceRoot
+    this.workspaceNaming = project.workspaceNaming
+    this.vcs = project.vcs
+    this.repos = project.repos
+    this.redactPatterns = project.redactPatterns
+    this.sensitiveEnvVars = project.sensitiveEnvVars
     makeObservable(this)
   }
 
@@ -188,12 +220,22 @@ export class ProjectStore {
     // If workspace is still being created, don't cache the store
     // because the path will be updated once the git worktree is ready
     if (!workspace.path) {
-      return new WorkspaceStore(workspace, this.name, this.initPrompt, this.backend)
+      return new WorkspaceStore(
+        workspace,
+        this.name,
+        workspace.initPrompt ?? this.initPrompt,
+        this.backend
+      )
     }
 
     let store = this._workspaceStoreCache.get(workspace.id)
     if (!store) {
-      store = new WorkspaceStore(workspace, this.name, this.initPrompt, this.backend)
+      store = new WorkspaceStore(
+        workspace,
+        this.name,
+        workspace.initPrompt ?? this.initPrompt,
+        this.backend
+      )
       this._workspaceStoreCache.set(workspace.id, store)
     }
     return store
@@ -235,16 +277,27 @@ export class ProjectStore {
     initPrompt?: string
     prPrompt?: string
     postCreate?: string
+    setupSteps?: SetupStep[]
+    preArchive?: string
     workspaceFilter?: string
     useGithub?: boolean
     allowMergeToMain?: boolean
     mainBranch?: string
     defaultSandboxed?: boolean
     claudeConfigDir?: string
+    workspaceRoot?: string
+    workspaceNaming?: string
+    vcs?: Vcs | ""
+    repos?: ProjectRepo[]
+    redactPatterns?: string[]
+    sensitiveEnvVars?: string[]
   }): void {
     if (updates.initPrompt !== undefined) this.initPrompt = updates.initPrompt || undefined
     if (updates.prPrompt !== undefined) this.prPrompt = updates.prPrompt || undefined
     if (updates.postCreate !== undefined) this.postCreate = updates.postCreate || undefined
+    if (updates.setupSteps !== undefined)
+      this.setupSteps = updates.setupSteps.length > 0 ? updates.setupSteps : undefined
+    if (updates.preArchive !== undefined) this.preArchive = updates.preArchive || undefined
     if (updates.workspaceFilter !== undefined)
       this.workspaceFilter = updates.workspaceFilter || undefined
     if (updates.useGithub !== undefined) this.useGithub = updates.useGithub
@@ -253,6 +306,18 @@ export class ProjectStore {
     if (updates.defaultSandboxed !== undefined) this.defaultSandboxed = updates.defaultSandboxed
     if (updates.claudeConfigDir !== undefined)
       this.claudeConfigDir = updates.claudeConfigDir || undefined
+    if (updates.workspaceRoot !== undefined)
+      this.workspaceRoot = updates.workspaceRoot || undefined
+    if (updates.workspaceNaming !== undefined)
+      this.workspaceNaming = updates.workspaceNaming || undefined
+    if (updates.vcs !== undefined) this.vcs = updates.vcs || undefined
+    if (updates.repos !== undefined)
+      this.repos = updates.repos.length > 0 ? updates.repos : undefined
+    if (updates.redactPatterns !== undefined)
+      this.redactPatterns = updates.redactPatterns.length > 0 ? updates.redactPatterns : undefined
+    if (updates.sensitiveEnvVars !== undefined)
+      this.sensitiveEnvVars =
+        updates.sensitiveEnvVars.length > 0 ? updates.sensitiveEnvVars : undefined
   }
 
   // --- Approval persistence ---
@@ -348,12 +413,20 @@ export class ProjectStore {
       initPrompt: this.initPrompt,
       prPrompt: this.prPrompt,
       postCreate: this.postCreate,
+      setupSteps: this.setupSteps,
+      preArchive: this.preArchive,
       workspaceFilter: this.workspaceFilter,
       useGithub: this.useGithub,
       allowMergeToMain: this.allowMergeToMain,
       mainBranch: this.mainBranch,
       defaultSandboxed: this.defaultSandboxed,
       claudeConfigDir: this.claudeConfigDir,
+      workspaceRoot: this.workspaceRoot,
+      workspaceNaming: this.workspaceNaming,
+      vcs: this.vcs,
+      repos: this.repos,
+      redactPatterns: this.redactPatterns,
+      sensitiveEnvVars: this.sensitiveEnvVars,
     }
   }
 }
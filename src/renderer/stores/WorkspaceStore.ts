@@ -5,6 +5,7 @@ import type {
   MessageTurn,
   Chat,
   ChatIndex,
+  ChatIndexEntry,
   WorkspaceState,
   AgentQuestion,
   PendingToolUse,
@@ -12,6 +13,7 @@ import type {
   AgentType,
   Workspace,
   AutonomousReviewConfig,
+  ForkedChat,
 } from "../types"
 import type { Backend } from "../backend/types"
 import { GitService } from "../services/git"
@@ -23,7 +25,7 @@ import { configStore } from "./ConfigStore"
 import { getAgentDisplayName } from "../utils/agentDisplayName"
 import { toastStore } from "./ToastStore"
 import { projectRegistry } from "./ProjectRegistry"
-import { backend as defaultBackend } from "../backend"
+import { backend as defaultBackend, isConflictError } from "../backend"
 import { getConfigPath } from "../utils/paths"
 
 export type { PendingToolUse } from "../types"
@@ -76,6 +78,9 @@ If those variables are not set, use \`git push\` and \`gh pr create\` directly.
 
 export type WorkspaceStatus = "idle" | "running" | "needs_attention" | "done"
 
+/** `load_chat_index` result: the index and its version */
+type VersionedChatIndex = ChatIndex & { version: string | null }
+
 /**
  * WorkspaceStore manages chat state for a single workspace.
  * Each workspace has its own set of chats, active chat, and tool approvals.
@@ -94,6 +99,15 @@ export class WorkspaceStore {
   @observable
   private _chats: ChatStore[] = []
 
+  // Version of chats.json `_chats` was loaded or last saved at
+  private _indexVersion: string | null = null
+
+  // Chats deleted since then, so merging in other processes' chats skips them
+  private _deletedChatIds = new Set<string>()
+
+  // Index saves go one at a time: each needs the version the previous one wrote
+  private _indexSave: Promise<void> = Promise.resolve()
+
   @observable
   private chatDir: string | null = null
 
@@ -411,6 +425,43 @@ export class WorkspaceStore {
     this.switchChat(chats[newIdx].id)
   }
 
+  /**
+   * Fork a chat after message `seq` (keeping the rest of that turn) into a
+   * new chat, and switch to it. The new chat's first message resumes the
+   * agent from the fork point; the original is left as it was.
+   */
+  async forkChat(chatId: string, seq: number): Promise<void> {
+    const { metadata } = await this.backend.invoke<ForkedChat>("fork_chat", {
+      projectName: this.projectName,
+      workspaceName: this.getWorkspaceName(),
+      chatId,
+      seq,
+      claudeConfigDir: projectRegistry.getProjectStore(this.projectId)?.claudeConfigDir ?? null,
+    })
+    const chat: Chat = {
+      id: metadata.id,
+      workspaceId: this.id,
+      label: metadata.label,
+      messages: [],
+      status: "idle",
+      agentType: metadata.agentType ?? "claude",
+      agentSessionId: null,
+      modelVersion: metadata.modelVersion ?? null,
+      permissionMode: metadata.permissionMode ?? null,
+      effortLevel: metadata.effortLevel ?? null,
+      sandboxed: metadata.sandboxed ?? false,
+      createdAt: new Date(metadata.createdAt),
+      updatedAt: new Date(metadata.updatedAt),
+    }
+    const store = new ChatStore(chat, this.createChatContext())
+    runInAction(() => {
+      this._chats.push(store)
+      this.activeChatId = store.id
+    })
+    await store.ensureLoaded()
+    this.saveIndex()
+  }
+
   /**
    * Archive a single chat by marking it as archived.
    */
@@ -472,6 +523,7 @@ export class WorkspaceStore {
     const idx = this._chats.findIndex((c) => c.id === chatId)
     if (idx >= 0) {
       this._chats.splice(idx, 1)
+      this._deletedChatIds.add(chatId)
     }
 
     // If we deleted the active tab, switch to another active chat
@@ -525,6 +577,36 @@ export class WorkspaceStore {
     await active.sendMessage(content, this.path, meta, attachments)
   }
 
+  /**
+   * Send the unresolved review threads on this workspace's PR to the active
+   * chat's agent and record them as addressed. Returns how many were sent.
+   */
+  async sendReviewFeedback(): Promise<number> {
+    const active = this.activeChat
+    if (!active) return 0
+    const git = this.getGitService()
+    const feedback = await git.prepareReviewFeedback(
+      this.projectName,
+      this.getWorkspaceName(),
+      this.path
+    )
+    if (!feedback) return 0
+    await active.sendMessage(feedback.prompt, this.path, {
+      type: "pr_review",
+      label: `PR #${feedback.prNumber} review comments`,
+      prNumber: feedback.prNumber,
+      threadIds: feedback.threadIds,
+    })
+    await git.markReviewThreadsAddressed(
+      this.projectName,
+      this.getWorkspaceName(),
+      feedback.prNumber,
+      active.id,
+      feedback.threadIds
+    )
+    return feedback.threadIds.length
+  }
+
   @action
   stopGeneration(): void {
     this.activeChat?.stopGeneration()
@@ -754,28 +836,9 @@ export class WorkspaceStore {
       const context = this.createChatContext()
       const loaded: ChatStore[] = []
 
+      this._indexVersion = chatIndex.version
       for (const entry of chatIndex.chats) {
-        // Create skeleton chat — messages loaded lazily
-        const chat: Chat = {
-          id: entry.id,
-          workspaceId: this.id,
-          label: entry.label,
-          messages: [],
-          status: "idle",
-          agentType: entry.agentType ?? "claude",
-          agentSessionId: null,
-          modelVersion: null,
-          permissionMode: null,
-          effortLevel: null,
-          // Real value loads lazily from chat metadata (see ChatStore hydrate).
-          sandboxed: false,
-          createdAt: new Date(entry.createdAt),
-          updatedAt: new Date(entry.updatedAt),
-          isArchived: entry.isArchived ?? false,
-          archivedAt: entry.archivedAt ? new Date(entry.archivedAt) : undefined,
-        }
-        const store = new ChatStore(chat, context)
-        loaded.push(store)
+        loaded.push(new ChatStore(this.skeletonChat(entry), context))
       }
 
       let createdDefault = false
@@ -850,16 +913,40 @@ export class WorkspaceStore {
   }
 
   /**
-   * Load chat index from chats.json.
+   * Load chat index from chats.json, with its version.
    */
-  private async loadChatIndex(): Promise<ChatIndex> {
+  private async loadChatIndex(): Promise<VersionedChatIndex> {
     try {
-      return await this.backend.invoke<ChatIndex>("load_chat_index", {
+      return await this.backend.invoke<VersionedChatIndex>("load_chat_index", {
         projectName: this.projectName,
         workspaceName: this.getWorkspaceName(),
       })
     } catch {
-      return { chats: [] }
+      return { chats: [], version: null }
+    }
+  }
+
+  /**
+   * A chat as listed in the index. Messages load lazily.
+   */
+  private skeletonChat(entry: ChatIndexEntry): Chat {
+    return {
+      id: entry.id,
+      workspaceId: this.id,
+      label: entry.label,
+      messages: [],
+      status: "idle",
+      agentType: entry.agentType ?? "claude",
+      agentSessionId: null,
+      modelVersion: null,
+      permissionMode: null,
+      effortLevel: null,
+      // Real value loads lazily from chat metadata (see ChatStore hydrate).
+      sandboxed: false,
+      createdAt: new Date(entry.createdAt),
+      updatedAt: new Date(entry.updatedAt),
+      isArchived: entry.isArchived ?? false,
+      archivedAt: entry.archivedAt ? new Date(entry.archivedAt) : undefined,
     }
   }
 
@@ -888,7 +975,12 @@ export class WorkspaceStore {
     }
   }
 
-  private async saveChatIndex(): Promise<void> {
+  private saveChatIndex(): Promise<void> {
+    this._indexSave = this._indexSave.then(() => this.writeChatIndex())
+    return this._indexSave
+  }
+
+  private async writeChatIndex(retry = true): Promise<void> {
     try {
       const index: ChatIndex = {
         chats: this._chats.map((cs) => ({
@@ -901,16 +993,40 @@ export class WorkspaceStore {
           archivedAt: cs.chat.archivedAt?.toISOString(),
         })),
       }
-      await this.backend.invoke("save_chat_index", {
+      this._indexVersion = await this.backend.invoke<string>("save_chat_index", {
         projectName: this.projectName,
         workspaceName: this.getWorkspaceName(),
         index,
+        version: this._indexVersion,
       })
+      this._deletedChatIds.clear()
     } catch (err) {
+      if (retry && isConflictError(err)) {
+        await this.mergeChatIndex()
+        return this.writeChatIndex(false)
+      }
       console.error("Failed to save chat index:", err)
     }
   }
 
+  /**
+   * Take in the chats another process (the daemon) added to chats.json since
+   * we loaded or saved it, so saving our list doesn't drop them.
+   */
+  private async mergeChatIndex(): Promise<void> {
+    const latest = await this.loadChatIndex()
+    const context = this.createChatContext()
+    runInAction(() => {
+      for (const entry of latest.chats) {
+        const known = this._chats.some((c) => c.id === entry.id)
+        if (!known && !this._deletedChatIds.has(entry.id)) {
+          this._chats.push(new ChatStore(this.skeletonChat(entry), context))
+        }
+      }
+      this._indexVersion = latest.version
+    })
+  }
+
   /**
    * Reopen an archived chat by marking it as not archived.
    */
@@ -12,6 +12,10 @@ const mockGitService = {
   checkMerge: vi.fn(),
   mergeIntoMain: vi.fn(),
   deleteBranch: vi.fn(),
+  getSyncState: vi.fn(),
+  syncWorkspace: vi.fn(),
+  continueSync: vi.fn(),
+  prepareConflictResolution: vi.fn(),
 }
 
 vi.mock("@tauri-apps/api/event", () => ({
@@ -83,6 +87,10 @@ describe("ChangedFilesStore", () => {
     mockGitService.checkMerge = vi.fn()
     mockGitService.mergeIntoMain = vi.fn()
     mockGitService.deleteBranch = vi.fn()
+    mockGitService.getSyncState = vi.fn(() => Promise.resolve(null))
+    mockGitService.syncWorkspace = vi.fn()
+    mockGitService.continueSync = vi.fn()
+    mockGitService.prepareConflictResolution = vi.fn()
     // Set up projectRegistry with the mock workspace store
     const { projectRegistry } = await import("../ProjectRegistry")
     vi.mocked(projectRegistry).selectedWorkspaceStore = mockWorkspaceStore as never
@@ -249,6 +257,56 @@ describe("ChangedFilesStore", () => {
     expect(store.showMergeConfirm).toBe(false)
   })
 
+  it("syncWithDefault hands conflicts to the agent", async () => {
+    vi.mocked(mockGitService.syncWorkspace).mockResolvedValue({
+      status: "conflicts",
+      strategy: "merge",
+      upstream: "origin/main",
+      conflicts: [],
+      message: "",
+    })
+    vi.mocked(mockGitService.prepareConflictResolution).mockResolvedValue({
+      strategy: "merge",
+      upstream: "origin/main",
+      files: ["file1.ts"],
+      prompt: "Resolve file1.ts",
+    })
+    const syncState = { strategy: "merge", upstream: "origin/main", conflicts: [] }
+    vi.mocked(mockGitService.getSyncState).mockResolvedValue(syncState)
+    vi.mocked(mockGitService.listChangedFiles).mockResolvedValue({
+      files: [],
+      uncommitted: [],
+      is_default_branch: false,
+      submodules: [],
+    })
+
+    const store = new ChangedFilesStore(workspacePath, workspaceId, mockGitService as never)
+    await store.syncWithDefault()
+
+    expect(mockGitService.syncWorkspace).toHaveBeenCalledWith(workspacePath, undefined, {
+      strategy: "merge",
+      fetch: true,
+    })
+    expect(mockWorkspaceStore.sendMessage).toHaveBeenCalledWith("Resolve file1.ts", {
+      type: "sync_conflicts",
+      label: "Resolve 1 conflicted file(s)",
+    })
+    expect(store.syncState).toEqual(syncState)
+    expect(store.syncing).toBe(false)
+  })
+
+  it("continueSync surfaces leftover conflict markers as an error", async () => {
+    vi.mocked(mockGitService.continueSync).mockRejectedValue(
+      new Error("Conflict markers remain in: a.ts")
+    )
+
+    const store = new ChangedFilesStore(workspacePath, workspaceId, mockGitService as never)
+    await store.continueSync()
+
+    expect(store.error).toBe("Conflict markers remain in: a.ts")
+    expect(mockWorkspaceStore.sendMessage).not.toHaveBeenCalled()
+  })
+
   it("checkMerge sets error on failure message", async () => {
     vi.mocked(mockGitService.checkMerge).mockResolvedValue({
       success: false,
@@ -350,7 +408,7 @@ describe("ChangedFilesStore", () => {
     const store = new ChangedFilesStore(workspacePath, workspaceId, mockGitService as never)
     await store.merge(true, true)
 
-    expect(mockGitService.deleteBranch).toHaveBeenCalledWith("/repo/path", "feature-branch")
+    expect(mockGitService.deleteBranch).toHaveBeenCalledWith("/repo/path", "feature-branch", false)
     expect(toastStore.show).toHaveBeenCalledWith(
       "Branch merged, workspace archived, and branch deleted"
     )
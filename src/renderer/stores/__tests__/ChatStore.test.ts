@@ -2842,5 +2842,19 @@ Live text.`,
       const cursor = (store as unknown as { catchUpCursor: () => number }).catchUpCursor()
       expect(cursor).toBe(2)
     })
+
+    it("records each message's seq, moving streamed text to its latest delta", () => {
+      const store = createChatStore()
+      const cb = getEventCallback(store)
+      cb({ kind: "userMessage", id: "u1", content: "A", timestamp: new Date() }, 1)
+      cb({ kind: "text", text: "B" }, 2)
+      cb({ kind: "text", text: "C" }, 3)
+      cb({ kind: "sessionId", sessionId: "s1" }, 4)
+
+      expect(store.messages.map((m) => [m.content, m.seq])).toEqual([
+        ["A", 1],
+        ["BC", 3],
+      ])
+    })
   })
 })
@@ -1,5 +1,6 @@
 import { describe, it, expect, vi, beforeEach } from "vitest"
 import { invoke } from "@tauri-apps/api/core"
+import type { Project } from "../../types"
 
 // Mock git and terminal services to avoid their own Tauri dependencies
 vi.mock("../../services/git", () => ({
@@ -842,6 +843,86 @@ describe("ProjectRegistry", () => {
       expect(invoke).toHaveBeenCalledWith("save_project_registry", expect.anything())
     })
 
+    it("saves with the version it loaded, then the one it wrote", async () => {
+      vi.mocked(invoke).mockImplementation((cmd: string) => {
+        if (cmd === "load_project_registry") {
+          return Promise.resolve({ projects: [], version: "v1" })
+        }
+        if (cmd === "save_project_registry") return Promise.resolve("v2")
+        return Promise.resolve(undefined)
+      })
+      vi.resetModules()
+      const { projectRegistry } = await import("../ProjectRegistry")
+      const { gitService } = await import("../../services/git")
+
+      await vi.waitFor(() => {
+        expect(invoke).toHaveBeenCalledWith("load_project_registry", undefined)
+      })
+      vi.mocked(gitService.listWorkspaces).mockResolvedValue([])
+
+      await projectRegistry.addProject("/home/user/one")
+      await projectRegistry.addProject("/home/user/two")
+
+      const versions = vi
+        .mocked(invoke)
+        .mock.calls.filter(([cmd]) => cmd === "save_project_registry")
+        .map(([, args]) => (args as { version: string }).version)
+      expect(versions).toEqual(["v1", "v2"])
+    })
+
+    it("merges its change into the latest projects when the save conflicts", async () => {
+      const workspace = {
+        id: "wt-1",
+        projectId: "repo-1",
+        branch: "feature",
+        path: "/test/feature",
+        isArchived: false,
+        createdAt: "2024-01-01T00:00:00.000Z",
+      }
+      const project = { id: "repo-1", name: "test", path: "/test", workspaces: [workspace] }
+      const setup = { status: "succeeded", steps: [] }
+      let loads = 0
+      let saves = 0
+      vi.mocked(invoke).mockImplementation((cmd: string) => {
+        if (cmd === "load_project_registry") {
+          loads++
+          // The backend recorded the workspace's setup after our first load
+          const workspaces = loads === 1 ? [workspace] : [{ ...workspace, setup }]
+          return Promise.resolve({
+            projects: [{ ...project, workspaces }],
+            version: `v${loads}`,
+          })
+        }
+        if (cmd === "save_project_registry") {
+          saves++
+          if (saves === 1) return Promise.reject("Conflict: projects.json has changed")
+          return Promise.resolve("v3")
+        }
+        return Promise.resolve(undefined)
+      })
+      vi.resetModules()
+      const { projectRegistry } = await import("../ProjectRegistry")
+
+      await vi.waitFor(() => {
+        expect(projectRegistry.projects).toHaveLength(1)
+      })
+      projectRegistry.updateProject("repo-1", { initPrompt: "Read the README" })
+
+      await vi.waitFor(() => {
+        expect(saves).toBe(2)
+      })
+      const [, args] = vi
+        .mocked(invoke)
+        .mock.calls.filter(([cmd]) => cmd === "save_project_registry")[1]
+      const { registry, version } = args as {
+        registry: { projects: Project[] }
+        version: string
+      }
+      expect(version).toBe("v2")
+      expect(registry.projects[0].initPrompt).toBe("Read the README")
+      expect(registry.projects[0].workspaces[0].setup).toEqual(setup)
+    })
+
     it("addWorkspace triggers saveToFile without blocking", async () => {
       vi.resetModules()
       const { projectRegistry } = await import("../ProjectRegistry")
@@ -869,8 +950,8 @@ describe("ProjectRegistry", () => {
     })
   })
 
-  describe("addWorkspace postCreate", () => {
-    it("executes postCreate via run_post_create_command when provided", async () => {
+  describe("addWorkspace setup", () => {
+    it("runs setup via run_workspace_setup when postCreate is provided", async () => {
       vi.resetModules()
       const { projectRegistry } = await import("../ProjectRegistry")
       const { gitService } = await import("../../services/git")
@@ -897,16 +978,51 @@ describe("ProjectRegistry", () => {
 
       await projectRegistry.addWorkspace("repo-1", "feature")
 
-      // postCreate runs fire-and-forget, so wait for the backend call
+      // Setup runs fire-and-forget, so wait for the backend call
+      await vi.waitFor(() => {
+        expect(invoke).toHaveBeenCalledWith("run_workspace_setup", {
+          projectId: "repo-1",
+          workspaceId: expect.any(String),
+        })
+      })
+    })
+
+    it("runs setup when only setup steps are configured", async () => {
+      vi.resetModules()
+      const { projectRegistry } = await import("../ProjectRegistry")
+      const { gitService } = await import("../../services/git")
+      const { runInAction } = await import("mobx")
+
+      await vi.waitFor(() => {
+        expect(invoke).toHaveBeenCalledWith("load_project_registry", undefined)
+      })
+
+      vi.mocked(gitService.addWorkspace).mockResolvedValue("/test/feature")
+
+      runInAction(() => {
+        projectRegistry.setProjects([
+          {
+            id: "repo-1",
+            name: "test",
+            path: "/test",
+            isGitRepo: true,
+            workspaces: [],
+            setupSteps: [{ name: "env", kind: "copyFiles", paths: [".env"] }],
+          },
+        ])
+      })
+
+      await projectRegistry.addWorkspace("repo-1", "feature")
+
       await vi.waitFor(() => {
-        expect(invoke).toHaveBeenCalledWith("run_post_create_command", {
+        expect(invoke).toHaveBeenCalledWith("run_workspace_setup", {
           projectId: "repo-1",
           workspaceId: expect.any(String),
         })
       })
     })
 
-    it("does not call run_post_create_command when no postCreate script", async () => {
+    it("does not call run_workspace_setup when nothing is configured", async () => {
       vi.resetModules()
       const { projectRegistry } = await import("../ProjectRegistry")
       const { gitService } = await import("../../services/git")
@@ -931,8 +1047,8 @@ describe("ProjectRegistry", () => {
         expect(invoke).toHaveBeenCalledWith("save_project_registry", expect.anything())
       })
 
-      // Should not call run_post_create_command
-      expect(invoke).not.toHaveBeenCalledWith("run_post_create_command", expect.anything())
+      // Should not call run_workspace_setup
+      expect(invoke).not.toHaveBeenCalledWith("run_workspace_setup", expect.anything())
     })
   })
 
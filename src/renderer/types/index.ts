@@ -7,6 +7,10 @@ export interface Project {
   initPrompt?: string
   prPrompt?: string
   postCreate?: string
+  /** Named steps run when a workspace is created, before postCreate */
+  setupSteps?: SetupStep[]
+  /** Shell command run in a workspace before it is archived */
+  preArchive?: string
   /** Regex pattern to filter out workspaces from the list (matches against path) */
   workspaceFilter?: string
   /** Whether to show GitHub PR buttons for workspaces (default: true) */
@@ -23,8 +27,36 @@ export interface Project {
    *  for the spawned `claude` process. Empty/undefined uses the default ~/.claude.
    *  Supports a leading ~ or $HOME (expanded in the Rust backend). Claude only. */
   claudeConfigDir?: string
+  /** Directory new workspaces are created under, overriding the global setting */
+  workspaceRoot?: string
+  /**
+   * Naming template for new workspaces ({repo}, {branch}, {animal}, {n}), overriding the
+   * global setting
+   */
+  workspaceNaming?: string
+  /** Version control backend; detected from the checkout when unset */
+  vcs?: Vcs
+  /** Repositories of a multi-repo project; each workspace gets a worktree of every one */
+  repos?: ProjectRepo[]
+  /** Regexes for secrets redacted from this project's chats, besides the built-in detectors */
+  redactPatterns?: string[]
+  /** Env vars whose values are redacted from this project's chats */
+  sensitiveEnvVars?: string[]
+}
+
+/** One repository of a multi-repo project */
+export interface ProjectRepo {
+  /** Directory name of its worktree inside the workspace */
+  name: string
+  /** Path to the repository, absolute or relative to the project path */
+  path: string
+  /** Default branch of this repository, overriding the project's */
+  mainBranch?: string
 }
 
+/** Version control backend for a project: git worktrees or Jujutsu workspaces */
+export type Vcs = "git" | "jj"
+
 export interface Workspace {
   id: string
   projectId: string
@@ -37,10 +69,61 @@ export interface Workspace {
   prNumber?: number
   prUrl?: string
   prState?: "OPEN" | "MERGED" | "CLOSED"
+  /** Overrides the project's initPrompt (e.g. seeded from a GitHub issue) */
+  initPrompt?: string
+  /** Progress of the project's setup steps, recorded by the backend */
+  setup?: WorkspaceSetup
   /** If set, this workspace is from a remote Overseer server (inherited from project) */
   remoteServerUrl?: string
 }
 
+/** What a setup step does, tagged by `kind` */
+export type SetupAction =
+  /** Copy files or directories (e.g. `.env`) from the project root; relative paths */
+  | { kind: "copyFiles"; paths: string[] }
+  /** Install dependencies; `command` overrides the one picked from the lockfile */
+  | { kind: "install"; command?: string }
+  | { kind: "shell"; command: string }
+
+export type SetupStep = { name: string } & SetupAction
+
+export type SetupStatus = "pending" | "running" | "succeeded" | "failed" | "skipped"
+
+export interface SetupStepResult {
+  name: string
+  status: SetupStatus
+  exitCode?: number
+  error?: string
+  /** The last lines of output, kept for failed steps */
+  output?: string
+}
+
+export interface WorkspaceSetup {
+  status: SetupStatus
+  steps: SetupStepResult[]
+  startedAt: string
+  finishedAt?: string
+}
+
+/** Emitted on `workspace:setup:{workspaceId}` while setup runs */
+export type SetupEvent =
+  | { kind: "started"; steps: string[] }
+  | { kind: "stepStarted"; index: number; name: string }
+  | { kind: "output"; index: number; stream: "stdout" | "stderr"; line: string }
+  | { kind: "stepFinished"; index: number; result: SetupStepResult }
+  | { kind: "finished"; setup: WorkspaceSetup }
+
+/** Emitted on `config:changed` when a shared document changes */
+export interface ConfigChange {
+  document: "projects" | "chatIndex" | "approvals"
+  /** Chat directory name of the project (chat indexes and approvals) */
+  project?: string
+  /** Chat directory name of the workspace (chat indexes) */
+  workspace?: string
+  /** The document's new version */
+  version: string | null
+}
+
 export interface Session {
   id: string
   workspaceId: string
@@ -82,6 +165,14 @@ export interface MessageMeta {
   phase?: "implementation" | "review"
   /** Display label for the review agent (e.g. "Gemini 2.5 Pro") when a review agent is configured */
   reviewAgentLabel?: string
+  /** For handoff messages: the agent the chat was handed over from */
+  fromAgent?: AgentType
+  /** For handoff messages: the agent that took the chat over */
+  toAgent?: AgentType
+  /** For PR review messages: the PR the threads belong to */
+  prNumber?: number
+  /** For PR review messages: the review threads sent */
+  threadIds?: string[]
 }
 
 export interface Message {
@@ -103,6 +194,8 @@ export interface Message {
   toolUseId?: string
   /** Attachments sent with this user message */
   attachments?: Attachment[]
+  /** Seq of the persisted event this message came from (the latest one, for streamed text) */
+  seq?: number
 }
 
 export interface QuestionOption {
@@ -236,8 +329,15 @@ export interface PendingToolUse {
 // --- Changed Files ---
 
 export interface ChangedFile {
-  status: "M" | "A" | "D" | "R" | "?"
+  status: "M" | "A" | "D" | "R" | "C" | "?"
   path: string
+  /** Path the file was renamed or copied from (R and C only) */
+  oldPath?: string
+  /** Lines added; null for binary files or when unknown */
+  additions?: number | null
+  /** Lines removed; null for binary files or when unknown */
+  deletions?: number | null
+  isBinary?: boolean
   /** True if this is an uncommitted change (staged/unstaged vs HEAD) */
   isUncommitted?: boolean
   /** Path to the containing submodule, if this file is inside one */
@@ -268,15 +368,157 @@ export interface ChangedFilesResult {
   submodules: SubmoduleResult[]
 }
 
+// --- Structured Diffs ---
+
+/** How a structured diff treats whitespace-only changes */
+export type WhitespaceMode = "show" | "ignoreAtEol" | "ignoreChange" | "ignoreAll"
+
+export interface DiffOptions {
+  whitespace?: WhitespaceMode
+  /** Lines of context around each change (git's default of 3 when omitted) */
+  contextLines?: number | null
+}
+
+/** A run of text within a changed line */
+export interface DiffSegment {
+  text: string
+  /** Whether this run differs from the paired line */
+  changed: boolean
+}
+
+export interface DiffLine {
+  kind: "context" | "added" | "removed"
+  content: string
+  /** Line number in the old file (null for added lines) */
+  oldLine: number | null
+  /** Line number in the new file (null for removed lines) */
+  newLine: number | null
+  noNewline: boolean
+  /** Word-level breakdown; absent when the whole line changed */
+  segments?: DiffSegment[]
+}
+
+export interface DiffHunk {
+  oldStart: number
+  oldLines: number
+  newStart: number
+  newLines: number
+  /** Text after the closing @@ (usually the enclosing function) */
+  section: string
+  lines: DiffLine[]
+}
+
+export interface FileDiff {
+  oldPath: string | null
+  newPath: string | null
+  status: ChangedFile["status"]
+  isBinary: boolean
+  hunks: DiffHunk[]
+}
+
+// --- Forks ---
+
+/** How a forked chat's first message resumes the agent */
+export type ForkResume =
+  | { mode: "claude"; sessionId: string; resumeAt: string | null }
+  | { mode: "codex"; threadId: string; dropTurns: number }
+  | { mode: "replay" }
+
+/** Where a forked chat came from, and how its agent resumes */
+export interface ChatFork {
+  sourceChatId: string
+  /** Last event copied from the source (the end of the chosen turn) */
+  throughSeq: number
+  forkedAt: string
+  resume: ForkResume
+  /** The copied conversation as a prompt, for when the native fork can't be used */
+  replayPrompt: string
+  /** True until the forked chat's first message takes the plan */
+  pending: boolean
+}
+
+export interface ForkedChat {
+  metadata: {
+    id: string
+    label: string
+    agentType?: AgentType | null
+    modelVersion?: string | null
+    permissionMode?: string | null
+    effortLevel?: string | null
+    sandboxed?: boolean
+    createdAt: string
+    updatedAt: string
+  }
+  fork: ChatFork
+}
+
+// --- Handoffs ---
+
+/** Context transfer prompt for the agent taking over a chat */
+export interface HandoffPrompt {
+  /** Timeline label, e.g. "Handed off from Claude to Codex" */
+  label: string
+  prompt: string
+}
+
+// --- Checkpoints ---
+
+/** Snapshot of a workspace taken before a chat turn (a hidden git ref). */
+export interface Checkpoint {
+  chatId: string
+  /** Id of the user message that started the turn */
+  turnId: string
+  refName: string
+  commit: string
+  /** HEAD at the time, null on an unborn branch */
+  head: string | null
+  createdAt: string
+}
+
+export interface TurnCheckpoint {
+  turnId: string
+  seq: number
+  content: string
+  timestamp: string
+  /** null if no checkpoint was taken (e.g. non-git workspace) */
+  checkpoint: Checkpoint | null
+}
+
+export interface RewindResult {
+  checkpoint: Checkpoint
+  /** Snapshot of the workspace just before the restore */
+  undoCommit: string
+  removedEvents: number
+}
+
 // --- Commits ---
 
 export interface Commit {
   shortId: string
+  /** First line of the commit message */
   message: string
+  id?: string
+  fullMessage?: string
+  authorName?: string
+  authorEmail?: string
+  /** Author date, RFC 3339 */
+  date?: string
 }
 
 // --- Merge ---
 
+/**
+ * How a workspace branch lands on the default branch: a merge (fast-forward
+ * when possible), one squashed commit, or a rebase followed by a fast-forward.
+ */
+export type MergeStrategy = "merge" | "squash" | "rebase"
+
+export interface MergeOptions {
+  strategy?: MergeStrategy
+  /** Push the default branch to origin after a successful merge */
+  push?: boolean
+}
+
 export interface MergeResult {
   success: boolean
   /**
@@ -287,4 +529,153 @@ export interface MergeResult {
   alreadyUpToDate: boolean
   conflicts: string[]
   message: string
+  strategy?: MergeStrategy
+  /** The default branch was pushed to origin afterwards */
+  pushed?: boolean
+}
+
+export interface WorkspaceMove {
+  workspaceId: string
+  branch: string
+  from: string
+  to: string
+  /** Set if this workspace couldn't be moved */
+  error?: string
+}
+
+export interface MigrationReport {
+  dryRun: boolean
+  layout: { root: string | null; naming: string | null }
+  moves: WorkspaceMove[]
+  /** Workspaces already in place */
+  unchanged: number
+}
+
+export interface GcOptions {
+  /** Only report; change nothing */
+  dryRun?: boolean
+  /** Also delete collected workspaces' branches (`git branch -d`) */
+  deleteBranches?: boolean
+}
+
+export type GcReason = "merged" | "prMerged" | "prClosed"
+
+export interface WorkspaceUsage {
+  workspaceId: string
+  branch: string
+  path: string
+  bytes: number
+  chatBytes: number
+  /** Set if the workspace can be collected */
+  reason?: GcReason
+  archived: boolean
+  branchDeleted: boolean
+  /** Processes stopped before the workspace was archived */
+  stoppedProcesses?: StoppedProcess[]
+  /** Set if archiving failed */
+  error?: string
+}
+
+export interface OrphanedChatDir {
+  name: string
+  bytes: number
+  archived: boolean
+  error?: string
+}
+
+export interface GcReport {
+  dryRun: boolean
+  defaultBranch: string
+  workspaces: WorkspaceUsage[]
+  orphanedChats: OrphanedChatDir[]
+  staleWorktrees: string[]
+  archivedChatBytes: number
+  totalBytes: number
+  reclaimableBytes: number
+}
+
+export type SyncStrategy = "merge" | "rebase"
+
+export interface SyncOptions {
+  strategy?: SyncStrategy
+  /** Fetch origin first and sync from origin/<default> when it exists */
+  fetch?: boolean
+}
+
+/** A file left conflicted by a sync. Sides are null if missing, binary or too large. */
+export interface ConflictFile {
+  path: string
+  base: string | null
+  /** The workspace branch's version */
+  branch: string | null
+  /** The default branch's version */
+  upstream: string | null
+  /** 1-based lines that still hold conflict markers */
+  markerLines: number[]
+}
+
+export interface SyncResult {
+  status: "upToDate" | "synced" | "conflicts"
+  strategy: SyncStrategy
+  upstream: string
+  conflicts: ConflictFile[]
+  message: string
+}
+
+/** A sync stopped on conflicts */
+export interface SyncState {
+  strategy: SyncStrategy
+  upstream: string
+  conflicts: ConflictFile[]
+}
+
+export interface ConflictResolution {
+  strategy: SyncStrategy
+  upstream: string
+  files: string[]
+  prompt: string
+}
+
+/** A prompt built from the unresolved review threads on a workspace's PR */
+export interface ReviewFeedback {
+  prNumber: number
+  prUrl: string
+  threadIds: string[]
+  prompt: string
+}
+
+/** Who started a process found running in a workspace */
+export type ProcessSource = "terminal" | "agent" | "workspace"
+
+/** A process running in a workspace */
+export interface WorkspaceProcess {
+  pid: number
+  command: string
+  source: ProcessSource
+  /** TCP ports it was listening on */
+  ports: number[]
+}
+
+/** A process stopped when its workspace was archived */
+export interface StoppedProcess extends WorkspaceProcess {
+  /** True when it ignored SIGTERM and was killed */
+  forced: boolean
+  error?: string
+}
+
+/** Outcome of the project's preArchive hook */
+export interface PreArchiveHookResult {
+  command: string
+  success: boolean
+  exitCode: number
+  /** The last lines of output, kept when the hook failed */
+  output?: string
+}
+
+/** What archiving a workspace tore down before removing it */
+export interface TeardownReport {
+  hook?: PreArchiveHookResult
+  processes: StoppedProcess[]
+  /** Processes Overseer didn't start, left running in the workspace directory */
+  leftRunning?: WorkspaceProcess[]
 }
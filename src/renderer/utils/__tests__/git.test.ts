@@ -1,5 +1,6 @@
 import { describe, it, expect } from "vitest"
-import { isDefaultBranch } from "../git"
+import { describeTeardown, isDefaultBranch } from "../git"
+import type { StoppedProcess } from "../../types"
 
 describe("isDefaultBranch", () => {
   describe("without mainBranch override", () => {
@@ -44,3 +45,50 @@ describe("isDefaultBranch", () => {
     })
   })
 })
+
+describe("describeTeardown", () => {
+  const stopped = (pid: number, ports: number[], error?: string): StoppedProcess => ({
+    pid,
+    command: "node server.js",
+    source: "terminal",
+    ports,
+    forced: false,
+    error,
+  })
+
+  it("returns null when nothing was torn down", () => {
+    expect(describeTeardown(undefined)).toBeNull()
+    expect(describeTeardown({ processes: [] })).toBeNull()
+    expect(
+      describeTeardown({
+        hook: { command: "make down", success: true, exitCode: 0 },
+        processes: [],
+      })
+    ).toBeNull()
+  })
+
+  it("lists stopped processes and the ports they held", () => {
+    expect(describeTeardown({ processes: [stopped(1, [5173, 3000]), stopped(2, [3000])] })).toBe(
+      "Stopped 2 processes holding ports 3000, 5173"
+    )
+    expect(describeTeardown({ processes: [stopped(1, [])] })).toBe("Stopped 1 process")
+  })
+
+  it("reports a failed hook and processes that couldn't be stopped", () => {
+    expect(
+      describeTeardown({
+        hook: { command: "make down", success: false, exitCode: 2 },
+        processes: [stopped(7, [8080], "Operation not permitted")],
+      })
+    ).toBe("preArchive hook failed (exit 2); couldn't stop 7 (Operation not permitted)")
+  })
+
+  it("lists processes Overseer didn't start, which were left running", () => {
+    expect(
+      describeTeardown({
+        processes: [],
+        leftRunning: [{ pid: 9, command: "postgres", source: "workspace", ports: [5432] }],
+      })
+    ).toBe("left running 9 (postgres), not started by Overseer")
+  })
+})
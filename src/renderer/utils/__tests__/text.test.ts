@@ -1,5 +1,5 @@
 import { describe, it, expect } from "vitest"
-import { countLines } from "../text"
+import { countLines, formatBytes } from "../text"
 
 describe("countLines", () => {
   it("returns 0 for empty string", () => {
@@ -22,3 +22,15 @@ describe("countLines", () => {
     expect(countLines("a\nb\nc\nd\ne")).toBe(5)
   })
 })
+
+describe("formatBytes", () => {
+  it("keeps small counts in bytes", () => {
+    expect(formatBytes(0)).toBe("0 B")
+    expect(formatBytes(1023)).toBe("1023 B")
+  })
+
+  it("uses one decimal below 10 of a unit", () => {
+    expect(formatBytes(1536)).toBe("1.5 KB")
+    expect(formatBytes(25 * 1024 * 1024)).toBe("25 MB")
+  })
+})
@@ -1,3 +1,5 @@
+import type { TeardownReport } from "../types"
+
 /**
  * Check if a branch name is the project's default branch.
  *
@@ -10,3 +12,33 @@ export function isDefaultBranch(branch: string, mainBranch: string | undefined):
   }
   return branch === "main" || branch === "master"
 }
+
+/**
+ * One-line summary of what archiving a workspace tore down, or null when there
+ * is nothing worth telling (no hook failure, no processes stopped or left running).
+ */
+export function describeTeardown(report: TeardownReport | undefined): string | null {
+  if (!report) return null
+  const parts: string[] = []
+  if (report.hook && !report.hook.success) {
+    parts.push(`preArchive hook failed (exit ${report.hook.exitCode})`)
+  }
+  const stopped = report.processes.filter((p) => !p.error)
+  if (stopped.length > 0) {
+    const ports = [...new Set(stopped.flatMap((p) => p.ports))].sort((a, b) => a - b)
+    const noun = stopped.length === 1 ? "process" : "processes"
+    const portList = `port${ports.length === 1 ? "" : "s"} ${ports.join(", ")}`
+    const held = ports.length > 0 ? ` holding ${portList}` : ""
+    parts.push(`Stopped ${stopped.length} ${noun}${held}`)
+  }
+  const failed = report.processes.filter((p) => p.error)
+  if (failed.length > 0) {
+    parts.push(`couldn't stop ${failed.map((p) => `${p.pid} (${p.error})`).join(", ")}`)
+  }
+  const others = report.leftRunning ?? []
+  if (others.length > 0) {
+    const list = others.map((p) => `${p.pid} (${p.command})`).join(", ")
+    parts.push(`left running ${list}, not started by Overseer`)
+  }
+  return parts.length > 0 ? parts.join("; ") : null
+}
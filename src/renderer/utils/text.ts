@@ -5,3 +5,18 @@ export function countLines(s: string): number {
   if (!s) return 0
   return s.split("\n").length
 }
+
+/**
+ * Format a byte count for display, e.g. 1536 → "1.5 KB"
+ */
+export function formatBytes(bytes: number): string {
+  const units = ["B", "KB", "MB", "GB", "TB"]
+  let value = bytes
+  let unit = 0
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024
+    unit++
+  }
+  const digits = unit === 0 || value >= 10 ? 0 : 1
+  return `${value.toFixed(digits)} ${units[unit]}`
+}